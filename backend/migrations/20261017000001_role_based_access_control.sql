@@ -0,0 +1,39 @@
+-- ========================================
+-- StarRocks Admin - Role-Based Access Control
+-- ========================================
+-- Created: 2026-10-17
+-- Purpose: Add global user roles and per-cluster role grants
+--
+-- Roles (lowest to highest privilege):
+--   viewer   - read-only access to dashboards and cluster information
+--   operator - day-to-day operations (kill queries, run SQL, manage MVs)
+--   admin    - cluster registration, node management, global variables, user permissions
+
+-- ==============================================
+-- 1. Global role on users
+-- ==============================================
+ALTER TABLE users ADD COLUMN role VARCHAR(20) NOT NULL DEFAULT 'viewer';
+
+-- Accounts created before RBAC existed had unrestricted access.
+-- Keep them as admins so an upgrade never locks anyone out; new users start as viewers.
+UPDATE users SET role = 'admin';
+
+-- ==============================================
+-- 2. Per-cluster role grants
+-- ==============================================
+-- A grant overrides the user's global role for one cluster (it can raise or lower it).
+-- Global admins always keep admin rights on every cluster.
+CREATE TABLE IF NOT EXISTS user_cluster_roles (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    cluster_id INTEGER NOT NULL,
+    role VARCHAR(20) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(user_id, cluster_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (cluster_id) REFERENCES clusters(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_cluster_roles_user ON user_cluster_roles(user_id);
+CREATE INDEX IF NOT EXISTS idx_user_cluster_roles_cluster ON user_cluster_roles(cluster_id);
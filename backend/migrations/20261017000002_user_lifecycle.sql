@@ -0,0 +1,31 @@
+-- ========================================
+-- StarRocks Admin - Admin-Managed User Lifecycle
+-- ========================================
+-- Created: 2026-10-17
+-- Purpose: Allow admins to disable accounts and invite new users
+
+-- ==============================================
+-- 1. Disabled flag on users
+-- ==============================================
+-- Disabled users can neither log in nor use previously issued tokens
+ALTER TABLE users ADD COLUMN is_disabled BOOLEAN NOT NULL DEFAULT 0;
+
+-- ==============================================
+-- 2. User Invitations Table
+-- ==============================================
+-- Only the SHA-256 hash of the invitation token is stored;
+-- the plaintext token is returned once to the inviting admin.
+CREATE TABLE IF NOT EXISTS user_invitations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    email VARCHAR(100),
+    role VARCHAR(20) NOT NULL DEFAULT 'viewer',
+    invited_by INTEGER NOT NULL,
+    expires_at TIMESTAMP NOT NULL,
+    accepted_at TIMESTAMP,
+    accepted_user_id INTEGER,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (invited_by) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_invitations_expires_at ON user_invitations(expires_at);
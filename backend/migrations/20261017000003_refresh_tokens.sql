@@ -0,0 +1,43 @@
+-- ========================================
+-- StarRocks Admin - Refresh Tokens & Token Revocation
+-- ========================================
+-- Created: 2026-10-17
+-- Purpose: Short-lived access tokens renewed by refresh tokens,
+--          and a revocation list to kill sessions before they expire
+
+-- ==============================================
+-- 1. Refresh Tokens Table
+-- ==============================================
+-- One row per issued refresh token. Tokens are rotated on every refresh;
+-- all tokens of one login share the same session_id (the `sid` JWT claim).
+-- Only the SHA-256 hash of the token is stored.
+CREATE TABLE IF NOT EXISTS refresh_tokens (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    session_id VARCHAR(64) NOT NULL,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    expires_at TIMESTAMP NOT NULL,
+    revoked_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_refresh_tokens_user_id ON refresh_tokens(user_id);
+CREATE INDEX IF NOT EXISTS idx_refresh_tokens_session_id ON refresh_tokens(session_id);
+CREATE INDEX IF NOT EXISTS idx_refresh_tokens_expires_at ON refresh_tokens(expires_at);
+
+-- ==============================================
+-- 2. Token Revocation List
+-- ==============================================
+-- Revoked access token ids (`jti`) and session ids (`sid`).
+-- Entries only need to outlive the access tokens they block, so they
+-- expire together with them and are purged afterwards.
+CREATE TABLE IF NOT EXISTS token_revocations (
+    token_id VARCHAR(64) PRIMARY KEY,
+    user_id INTEGER NOT NULL,
+    reason VARCHAR(50) NOT NULL,
+    expires_at TIMESTAMP NOT NULL,
+    revoked_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_token_revocations_expires_at ON token_revocations(expires_at);
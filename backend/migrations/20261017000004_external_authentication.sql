@@ -0,0 +1,18 @@
+-- ========================================
+-- StarRocks Admin - External Authentication (LDAP / OIDC)
+-- ========================================
+-- Created: 2026-10-17
+-- Purpose: Auto-provisioned users signing in through LDAP or an OIDC provider
+
+-- ==============================================
+-- 1. Authentication source of each user
+-- ==============================================
+-- 'local' users sign in with the bcrypt password in password_hash,
+-- 'ldap' / 'oidc' users are provisioned on first sign-in and have no usable local password.
+ALTER TABLE users ADD COLUMN auth_source VARCHAR(20) NOT NULL DEFAULT 'local';
+
+-- Stable identifier at the external provider (LDAP DN, OIDC `sub`)
+ALTER TABLE users ADD COLUMN external_id VARCHAR(255);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_external_id
+    ON users(auth_source, external_id) WHERE external_id IS NOT NULL;
@@ -0,0 +1,35 @@
+-- ========================================
+-- StarRocks Admin - Admin Audit Log
+-- ========================================
+-- Created: 2026-10-17
+-- Purpose: Record every mutating API call (who did what, on which cluster, with which result)
+
+-- ==============================================
+-- 1. Admin Audit Log Table
+-- ==============================================
+-- Written by the audit middleware for all non-GET requests of authenticated users,
+-- including requests rejected by authorization. Rows are never updated.
+-- user_id has no foreign key and username is copied so entries survive user deletion.
+-- parameters holds the request query and JSON body with secrets redacted.
+CREATE TABLE IF NOT EXISTS admin_audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    username VARCHAR(50) NOT NULL,
+    cluster_id INTEGER,
+    method VARCHAR(10) NOT NULL,
+    route VARCHAR(255) NOT NULL,
+    action VARCHAR(100) NOT NULL,
+    target VARCHAR(500),
+    parameters TEXT,
+    status_code INTEGER NOT NULL,
+    success BOOLEAN NOT NULL,
+    error_message TEXT,
+    duration_ms INTEGER NOT NULL,
+    client_ip VARCHAR(64),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_admin_audit_log_created_at ON admin_audit_log(created_at);
+CREATE INDEX IF NOT EXISTS idx_admin_audit_log_user_id ON admin_audit_log(user_id);
+CREATE INDEX IF NOT EXISTS idx_admin_audit_log_action ON admin_audit_log(action);
+CREATE INDEX IF NOT EXISTS idx_admin_audit_log_cluster_id ON admin_audit_log(cluster_id);
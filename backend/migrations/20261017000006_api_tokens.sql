@@ -0,0 +1,32 @@
+-- ========================================
+-- StarRocks Admin - Personal API Tokens
+-- ========================================
+-- Created: 2026-10-17
+-- Purpose: Long-lived, scoped tokens for scripts and automation
+
+-- ==============================================
+-- 1. API Tokens Table
+-- ==============================================
+-- Tokens are shown once on creation; only the SHA-256 hash is stored.
+-- token_prefix keeps the first characters so users can tell tokens apart.
+-- scopes is a comma separated list of read / write / admin, capping the owner's role.
+CREATE TABLE IF NOT EXISTS api_tokens (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    name VARCHAR(100) NOT NULL,
+    token_prefix VARCHAR(16) NOT NULL,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    scopes VARCHAR(100) NOT NULL,
+    expires_at TIMESTAMP,
+    last_used_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    UNIQUE(user_id, name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_tokens_user_id ON api_tokens(user_id);
+
+-- ==============================================
+-- 2. Audit entries made with an API token
+-- ==============================================
+ALTER TABLE admin_audit_log ADD COLUMN api_token_id INTEGER;
@@ -0,0 +1,31 @@
+-- ========================================
+-- StarRocks Admin - Login Protection
+-- ========================================
+-- Created: 2026-10-18
+-- Purpose: Brute-force lockout and forced password change
+
+-- ==============================================
+-- 1. Forced password change
+-- ==============================================
+-- Users with this flag may only change their password until they do so.
+ALTER TABLE users ADD COLUMN must_change_password BOOLEAN NOT NULL DEFAULT 0;
+
+-- The seeded admin/admin account must pick a real password on first login
+UPDATE users SET must_change_password = 1
+WHERE username = 'admin'
+  AND password_hash = '$2b$12$LFxvzXbmyBPO9Zp.1MFU4OX3fb8kID8AHYHklokkZvgyzmHuRTc56';
+
+-- ==============================================
+-- 2. Failed login counters
+-- ==============================================
+-- One row per locked or recently failing username ('user') or client IP ('ip').
+-- lockouts counts consecutive lockouts and drives the exponential backoff.
+CREATE TABLE IF NOT EXISTS login_failures (
+    scope VARCHAR(10) NOT NULL,
+    subject VARCHAR(255) NOT NULL,
+    failures INTEGER NOT NULL DEFAULT 0,
+    window_started_at TIMESTAMP NOT NULL,
+    lockouts INTEGER NOT NULL DEFAULT 0,
+    locked_until TIMESTAMP,
+    PRIMARY KEY (scope, subject)
+);
@@ -0,0 +1,12 @@
+-- ========================================
+-- StarRocks Admin - Multiple FE Endpoints per Cluster
+-- ========================================
+-- Created: 2026-10-18
+-- Purpose: Fail over to other FEs when the primary FE is down
+
+-- ==============================================
+-- 1. Additional FE Endpoints
+-- ==============================================
+-- fe_host / fe_http_port / fe_query_port remain the primary FE.
+-- fe_endpoints is a JSON array of further FEs: [{"host": "...", "http_port": 8030, "query_port": 9030}]
+ALTER TABLE clusters ADD COLUMN fe_endpoints TEXT;
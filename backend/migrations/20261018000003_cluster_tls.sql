@@ -0,0 +1,17 @@
+-- ========================================
+-- StarRocks Admin - Per-cluster TLS Settings
+-- ========================================
+-- Created: 2026-10-18
+-- Purpose: TLS for MySQL protocol connections, shared with the HTTP client
+
+-- ==============================================
+-- 1. TLS Columns
+-- ==============================================
+-- enable_ssl switches TLS on for both HTTP and MySQL connections.
+-- tls_ca_cert: PEM bundle of trusted CAs (system roots when NULL)
+-- tls_client_identity_encrypted: base64 PKCS#12 client certificate + key, envelope-encrypted
+-- tls_client_identity_password_encrypted: password of the PKCS#12 archive, envelope-encrypted
+ALTER TABLE clusters ADD COLUMN tls_ca_cert TEXT;
+ALTER TABLE clusters ADD COLUMN tls_client_identity_encrypted TEXT;
+ALTER TABLE clusters ADD COLUMN tls_client_identity_password_encrypted TEXT;
+ALTER TABLE clusters ADD COLUMN tls_verify_hostname BOOLEAN NOT NULL DEFAULT 1;
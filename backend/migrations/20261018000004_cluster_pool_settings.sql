@@ -0,0 +1,12 @@
+-- ========================================
+-- StarRocks Admin - Per-cluster MySQL Pool Settings
+-- ========================================
+-- Created: 2026-10-18
+-- Purpose: Override the [mysql_pool] defaults for one cluster
+
+-- ==============================================
+-- 1. Pool Settings Column
+-- ==============================================
+-- pool_settings: JSON object with optional min_connections, max_connections,
+-- idle_timeout_secs and max_lifetime_secs (defaults from config when NULL)
+ALTER TABLE clusters ADD COLUMN pool_settings TEXT;
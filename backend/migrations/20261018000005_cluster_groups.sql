@@ -0,0 +1,33 @@
+-- ========================================
+-- StarRocks Admin - Cluster Groups
+-- ========================================
+-- Created: 2026-10-18
+-- Purpose: Group clusters by environment, team or any other criterion, so that
+--          health checks, variable reads and read-only SQL can fan out across a group
+
+-- ==============================================
+-- 1. Groups
+-- ==============================================
+-- kind: environment (prod, staging, ...; a cluster is in at most one), team, custom
+CREATE TABLE IF NOT EXISTS cluster_groups (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name VARCHAR(100) NOT NULL UNIQUE,
+    description TEXT,
+    kind VARCHAR(20) NOT NULL DEFAULT 'custom',
+    created_by INTEGER,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- ==============================================
+-- 2. Members
+-- ==============================================
+CREATE TABLE IF NOT EXISTS cluster_group_members (
+    group_id INTEGER NOT NULL,
+    cluster_id INTEGER NOT NULL,
+    PRIMARY KEY (group_id, cluster_id),
+    FOREIGN KEY (group_id) REFERENCES cluster_groups(id) ON DELETE CASCADE,
+    FOREIGN KEY (cluster_id) REFERENCES clusters(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_cluster_group_members_cluster ON cluster_group_members(cluster_id);
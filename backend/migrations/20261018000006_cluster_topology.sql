@@ -0,0 +1,35 @@
+-- ========================================
+-- StarRocks Admin - Cluster Topology
+-- ========================================
+-- Created: 2026-10-18
+-- Purpose: Keep the last discovered topology of each cluster (FEs, BEs, compute nodes,
+--          warehouses, run mode) and the changes found between discoveries
+
+-- ==============================================
+-- 1. Latest snapshot per cluster
+-- ==============================================
+CREATE TABLE IF NOT EXISTS cluster_topologies (
+    cluster_id INTEGER PRIMARY KEY,
+    topology TEXT NOT NULL,
+    discovered_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (cluster_id) REFERENCES clusters(id) ON DELETE CASCADE
+);
+
+-- ==============================================
+-- 2. Changes between snapshots
+-- ==============================================
+-- component: cluster, frontend, backend, compute_node, warehouse
+-- change: added, removed, changed
+CREATE TABLE IF NOT EXISTS cluster_topology_changes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    cluster_id INTEGER NOT NULL,
+    component VARCHAR(20) NOT NULL,
+    change VARCHAR(20) NOT NULL,
+    node VARCHAR(255) NOT NULL,
+    detail TEXT,
+    detected_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (cluster_id) REFERENCES clusters(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_cluster_topology_changes_cluster
+    ON cluster_topology_changes(cluster_id, detected_at);
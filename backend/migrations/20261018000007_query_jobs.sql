@@ -0,0 +1,46 @@
+-- ========================================
+-- StarRocks Admin - Query Jobs
+-- ========================================
+-- Created: 2026-10-18
+-- Purpose: Run ad-hoc queries in the background and keep their results for later paging
+
+-- ==============================================
+-- 1. Jobs
+-- ==============================================
+-- status: running, succeeded, failed, cancelled
+-- columns holds the result columns as JSON once the query returned them.
+-- expires_at is set when the job ends; expired jobs are deleted with their rows.
+CREATE TABLE IF NOT EXISTS query_jobs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    cluster_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    sql_text TEXT NOT NULL,
+    catalog VARCHAR(255),
+    database_name VARCHAR(255),
+    status VARCHAR(20) NOT NULL,
+    columns TEXT,
+    row_count INTEGER NOT NULL DEFAULT 0,
+    truncated VARCHAR(20),
+    error_message TEXT,
+    execution_time_ms INTEGER,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    finished_at TIMESTAMP,
+    expires_at TIMESTAMP,
+    FOREIGN KEY (cluster_id) REFERENCES clusters(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_query_jobs_cluster_user ON query_jobs(cluster_id, user_id);
+CREATE INDEX IF NOT EXISTS idx_query_jobs_status ON query_jobs(status);
+CREATE INDEX IF NOT EXISTS idx_query_jobs_expires_at ON query_jobs(expires_at);
+
+-- ==============================================
+-- 2. Result rows
+-- ==============================================
+-- row_values is the JSON array of one row, in column order
+CREATE TABLE IF NOT EXISTS query_job_rows (
+    job_id INTEGER NOT NULL,
+    row_index INTEGER NOT NULL,
+    row_values TEXT NOT NULL,
+    PRIMARY KEY (job_id, row_index),
+    FOREIGN KEY (job_id) REFERENCES query_jobs(id) ON DELETE CASCADE
+);
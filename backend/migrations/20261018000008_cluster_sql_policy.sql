@@ -0,0 +1,12 @@
+-- ========================================
+-- StarRocks Admin - Per-cluster SQL Policy
+-- ========================================
+-- Created: 2026-10-18
+-- Purpose: Restrict the SQL that may run on one cluster, e.g. read-only production
+
+-- ==============================================
+-- 1. SQL Policy Column
+-- ==============================================
+-- sql_policy: JSON object with optional read_only and confirm_writes flags
+-- (no restrictions beyond the [sql_guard] role rules when NULL)
+ALTER TABLE clusters ADD COLUMN sql_policy TEXT;
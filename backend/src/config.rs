@@ -1,13 +1,26 @@
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
+use crate::models::{StatementCategory, UserRole};
+use crate::utils::crypto;
+
 #[derive(Debug, Clone, Deserialize, Default)]
 #[serde(default)]
 pub struct Config {
     pub server: ServerConfig,
     pub database: DatabaseConfig,
     pub auth: AuthConfig,
+    pub security: SecurityConfig,
+    pub mysql_pool: MySQLPoolConfig,
+    pub topology: TopologyConfig,
+    pub diagnostics: DiagnosticsConfig,
+    pub query_stream: QueryStreamConfig,
+    pub query_jobs: QueryJobConfig,
+    pub sql_guard: SqlGuardConfig,
+    pub query_cache: QueryCacheConfig,
+    pub query_plan: QueryPlanConfig,
     pub logging: LoggingConfig,
     pub static_config: StaticConfig,
 }
@@ -29,7 +42,226 @@ pub struct DatabaseConfig {
 #[serde(default)]
 pub struct AuthConfig {
     pub jwt_secret: String,
+    /// Lifetime of access tokens (e.g. "15m"); keep short, clients renew them via refresh tokens
     pub jwt_expires_in: String,
+    /// Lifetime of refresh tokens, i.e. how long a session survives without activity
+    pub refresh_token_expires_in: String,
+    /// Allow anyone to create an account via /api/auth/register; off by default,
+    /// so accounts are created by admins
+    pub allow_registration: bool,
+    pub ldap: LdapConfig,
+    pub oidc: OidcConfig,
+    pub lockout: LockoutConfig,
+    pub password_policy: PasswordPolicyConfig,
+}
+
+/// Temporary lockout after repeated failed password logins
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LockoutConfig {
+    pub enabled: bool,
+    /// Failed attempts for one username before it is locked
+    pub max_failures_per_user: u32,
+    /// Failed attempts from one client IP (any username) before it is locked
+    pub max_failures_per_ip: u32,
+    /// Failures older than this are forgotten
+    pub window_secs: u64,
+    /// Duration of the first lockout; doubles for each lockout that follows without a quiet window
+    pub lockout_secs: u64,
+    pub max_lockout_secs: u64,
+    /// Use the first `X-Forwarded-For` hop as client IP; only enable behind a trusted proxy
+    pub trust_forwarded_for: bool,
+}
+
+/// Rules for passwords of local accounts
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PasswordPolicyConfig {
+    pub min_length: usize,
+    pub require_uppercase: bool,
+    pub require_lowercase: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+    /// Reject passwords that contain the username
+    pub reject_username: bool,
+}
+
+/// LDAP bind authentication; users are provisioned on first successful login
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LdapConfig {
+    pub enabled: bool,
+    /// e.g. "ldap://ldap.example.com:389" or "ldaps://ldap.example.com:636"
+    pub url: String,
+    /// Service account used to look up users; empty for anonymous search
+    pub bind_dn: String,
+    pub bind_password: String,
+    pub user_base_dn: String,
+    /// Search filter, `{username}` is replaced by the escaped login name
+    pub user_filter: String,
+    /// Attribute holding a stable unique id of the entry (e.g. `entryUUID`,
+    /// `nsUniqueId`, `ipaUniqueID`); accounts are linked by it so that a renamed or
+    /// moved entry keeps its account. The DN is used when empty or missing.
+    pub id_attribute: String,
+    pub email_attribute: String,
+    /// Multi-valued attribute listing the user's groups (DNs or names)
+    pub group_attribute: String,
+    pub timeout_secs: u64,
+    /// Group (DN or CN) to role; the highest matching role wins
+    pub group_roles: HashMap<String, UserRole>,
+    /// Role for users without a mapped group; unset means they are rejected
+    pub default_role: Option<UserRole>,
+}
+
+/// OIDC authorization-code flow (with PKCE)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct OidcConfig {
+    pub enabled: bool,
+    /// Issuer URL; discovery is read from `<issuer>/.well-known/openid-configuration`
+    pub issuer_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    /// Must point to /api/auth/oidc/callback of this server
+    pub redirect_url: String,
+    pub scopes: Vec<String>,
+    pub username_claim: String,
+    pub groups_claim: String,
+    /// Frontend page receiving the tokens (in the URL fragment) after sign-in
+    pub post_login_redirect: String,
+    /// Group claim value to role; the highest matching role wins
+    pub group_roles: HashMap<String, UserRole>,
+    /// Role for users without a mapped group; unset means they are rejected
+    pub default_role: Option<UserRole>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SecurityConfig {
+    /// Master key used to encrypt cluster passwords stored in the database
+    pub master_key: String,
+    /// Holds the master key when `master_key` is not set; created with a random
+    /// key on first start
+    pub master_key_file: String,
+}
+
+/// Defaults for the MySQL connection pools of clusters; clusters may override them
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MySQLPoolConfig {
+    pub min_connections: usize,
+    pub max_connections: usize,
+    /// Connections above `min_connections` are closed after being idle this long
+    pub idle_timeout_secs: u64,
+    /// Connections are reopened after this age
+    pub max_lifetime_secs: u64,
+    /// Pools of clusters unused for this long are closed; 0 keeps them open
+    pub evict_idle_pool_secs: u64,
+}
+
+/// Periodic rediscovery of cluster topologies
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TopologyConfig {
+    /// Seconds between refreshes of every cluster; 0 disables them
+    pub refresh_interval_secs: u64,
+}
+
+/// Thresholds of the deep health check
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DiagnosticsConfig {
+    /// Journal entries a follower or observer may be behind the leader
+    pub journal_lag_warning: i64,
+    pub journal_lag_critical: i64,
+    /// Seconds since the last heartbeat of a node, compared with the freshest heartbeat
+    pub heartbeat_age_warning_secs: i64,
+    pub heartbeat_age_critical_secs: i64,
+    /// Clock difference between nodes; FEs refuse to work with more than 5s
+    pub clock_skew_warning_ms: i64,
+    pub clock_skew_critical_ms: i64,
+    /// Usage of the fullest disk of a BE, in percent
+    pub disk_usage_warning_pct: f64,
+    pub disk_usage_critical_pct: f64,
+}
+
+/// Limits of streamed SQL results; requests may only lower them
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct QueryStreamConfig {
+    /// Rows sent before the query is killed and the stream ends as truncated
+    pub max_rows: u64,
+    /// Encoded bytes sent before the query is killed and the stream ends as truncated
+    pub max_bytes: u64,
+    /// Rows buffered for a slow client before reading from StarRocks pauses
+    pub buffer_rows: usize,
+}
+
+/// Background query jobs and how long their results are kept
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct QueryJobConfig {
+    /// Hours a finished job and its rows are kept before they are deleted
+    pub retention_hours: u64,
+    /// Rows stored per job before the query is killed and the job ends as truncated
+    pub max_rows: u64,
+    /// Encoded bytes stored per job before the query is killed and the job ends as truncated
+    pub max_bytes: u64,
+    /// Jobs a user may have running at the same time
+    pub max_running_per_user: i64,
+}
+
+/// Which SQL statements users may run, checked before SQL reaches a cluster
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SqlGuardConfig {
+    pub enabled: bool,
+    /// Statement categories each role may run on a cluster
+    pub viewer: Vec<StatementCategory>,
+    pub operator: Vec<StatementCategory>,
+    pub admin: Vec<StatementCategory>,
+    /// Destructive statements (DROP, TRUNCATE, DELETE, ...) need a confirmation token
+    pub confirm_destructive: bool,
+    /// Seconds a confirmation token stays valid
+    pub confirmation_ttl_secs: u64,
+}
+
+impl SqlGuardConfig {
+    /// Categories `role` may run
+    pub fn allowed(&self, role: UserRole) -> &[StatementCategory] {
+        match role {
+            UserRole::Viewer => &self.viewer,
+            UserRole::Operator => &self.operator,
+            UserRole::Admin => &self.admin,
+        }
+    }
+}
+
+/// In-memory cache of read-only query results; callers opt in with a TTL
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct QueryCacheConfig {
+    pub enabled: bool,
+    /// Results kept before the least recently used are evicted
+    pub max_entries: usize,
+    /// Encoded bytes of all results kept before the least recently used are evicted
+    pub max_bytes: u64,
+    /// Larger results are returned but not cached
+    pub max_entry_bytes: u64,
+    /// Upper bound of the TTL a request may ask for
+    pub max_ttl_secs: u64,
+    /// Seconds the overview page reuses its cluster queries; 0 disables it
+    pub overview_ttl_secs: u64,
+}
+
+/// Thresholds of the warnings attached to explained query plans
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct QueryPlanConfig {
+    /// Rows a broadcast join may send to every instance before it is flagged
+    pub broadcast_max_rows: u64,
+    /// Rows a scan without predicates may read before it is flagged
+    pub full_scan_min_rows: u64,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -64,6 +296,7 @@ impl Config {
 
         // 2. Override with environment variables
         config.apply_env_overrides();
+        config.security.resolve_master_key()?;
 
         // 3. Validate configuration
         config.validate()?;
@@ -78,7 +311,13 @@ impl Config {
     /// - APP_SERVER_PORT: Server port (default: 8080)
     /// - APP_DATABASE_URL: Database URL (default: sqlite://data/starrocks-admin.db)
     /// - APP_JWT_SECRET: JWT secret key
-    /// - APP_JWT_EXPIRES_IN: JWT expiration time (e.g., "24h")
+    /// - APP_JWT_EXPIRES_IN: JWT expiration time (e.g., "15m")
+    /// - APP_REFRESH_TOKEN_EXPIRES_IN: Refresh token expiration time (e.g., "7d")
+    /// - APP_ALLOW_REGISTRATION: Enable public self-registration (true/false)
+    /// - APP_LDAP_BIND_PASSWORD: LDAP service account password
+    /// - APP_OIDC_CLIENT_SECRET: OIDC client secret
+    /// - APP_MASTER_KEY: Master key for encrypting stored cluster passwords
+    /// - APP_MASTER_KEY_FILE: File holding the master key when APP_MASTER_KEY is not set
     /// - APP_LOG_LEVEL: Logging level (e.g., "info,starrocks_admin_backend=debug")
     fn apply_env_overrides(&mut self) {
         if let Ok(host) = std::env::var("APP_SERVER_HOST") {
@@ -108,6 +347,47 @@ impl Config {
             tracing::info!("Override auth.jwt_expires_in from env: {}", self.auth.jwt_expires_in);
         }
 
+        if let Ok(expires) = std::env::var("APP_REFRESH_TOKEN_EXPIRES_IN") {
+            self.auth.refresh_token_expires_in = expires;
+            tracing::info!(
+                "Override auth.refresh_token_expires_in from env: {}",
+                self.auth.refresh_token_expires_in
+            );
+        }
+
+        if let Ok(password) = std::env::var("APP_LDAP_BIND_PASSWORD") {
+            self.auth.ldap.bind_password = password;
+            tracing::info!("Override auth.ldap.bind_password from env");
+        }
+
+        if let Ok(secret) = std::env::var("APP_OIDC_CLIENT_SECRET") {
+            self.auth.oidc.client_secret = secret;
+            tracing::info!("Override auth.oidc.client_secret from env");
+        }
+
+        if let Ok(allow) = std::env::var("APP_ALLOW_REGISTRATION")
+            && let Ok(allow) = allow.parse::<bool>()
+        {
+            self.auth.allow_registration = allow;
+            tracing::info!(
+                "Override auth.allow_registration from env: {}",
+                self.auth.allow_registration
+            );
+        }
+
+        if let Ok(master_key) = std::env::var("APP_MASTER_KEY") {
+            self.security.master_key = master_key;
+            tracing::info!("Override security.master_key from env");
+        }
+
+        if let Ok(path) = std::env::var("APP_MASTER_KEY_FILE") {
+            self.security.master_key_file = path;
+            tracing::info!(
+                "Override security.master_key_file from env: {}",
+                self.security.master_key_file
+            );
+        }
+
         if let Ok(level) = std::env::var("APP_LOG_LEVEL") {
             self.logging.level = level;
             tracing::info!("Override logging.level from env: {}", self.logging.level);
@@ -125,6 +405,68 @@ impl Config {
             tracing::warn!("⚠️  This is INSECURE for production use!");
         }
 
+        if self.security.master_key.is_empty() {
+            anyhow::bail!("Master key cannot be empty");
+        }
+
+        if self.auth.ldap.enabled && self.auth.ldap.user_base_dn.is_empty() {
+            anyhow::bail!("auth.ldap.user_base_dn is required when LDAP is enabled");
+        }
+
+        if self.auth.oidc.enabled
+            && (self.auth.oidc.issuer_url.is_empty()
+                || self.auth.oidc.client_id.is_empty()
+                || self.auth.oidc.redirect_url.is_empty())
+        {
+            anyhow::bail!(
+                "auth.oidc.issuer_url, client_id and redirect_url are required when OIDC is enabled"
+            );
+        }
+
+        let lockout = &self.auth.lockout;
+        if lockout.enabled
+            && (lockout.max_failures_per_user == 0
+                || lockout.max_failures_per_ip == 0
+                || lockout.window_secs == 0
+                || lockout.lockout_secs == 0
+                || lockout.max_lockout_secs < lockout.lockout_secs)
+        {
+            anyhow::bail!(
+                "auth.lockout limits and durations must be positive and max_lockout_secs >= lockout_secs"
+            );
+        }
+
+        let mysql_pool = &self.mysql_pool;
+        if mysql_pool.max_connections == 0
+            || mysql_pool.min_connections > mysql_pool.max_connections
+            || mysql_pool.idle_timeout_secs == 0
+            || mysql_pool.max_lifetime_secs == 0
+        {
+            anyhow::bail!(
+                "mysql_pool limits and timeouts must be positive and min_connections <= max_connections"
+            );
+        }
+
+        if self.sql_guard.confirmation_ttl_secs == 0 {
+            anyhow::bail!("sql_guard.confirmation_ttl_secs must be at least 1");
+        }
+
+        let query_cache = &self.query_cache;
+        if query_cache.enabled
+            && (query_cache.max_entries == 0
+                || query_cache.max_bytes == 0
+                || query_cache.max_entry_bytes > query_cache.max_bytes
+                || query_cache.overview_ttl_secs > query_cache.max_ttl_secs)
+        {
+            anyhow::bail!(
+                "query_cache limits must be positive, max_entry_bytes <= max_bytes and overview_ttl_secs <= max_ttl_secs"
+            );
+        }
+
+        if self.auth.password_policy.min_length == 0 {
+            anyhow::bail!("auth.password_policy.min_length must be at least 1");
+        }
+
         // Validate server port
         if self.server.port == 0 {
             anyhow::bail!("Server port cannot be 0");
@@ -200,11 +542,223 @@ impl Default for AuthConfig {
     fn default() -> Self {
         Self {
             jwt_secret: "dev-secret-key-change-in-production".to_string(),
-            jwt_expires_in: "24h".to_string(),
+            jwt_expires_in: "15m".to_string(),
+            refresh_token_expires_in: "7d".to_string(),
+            allow_registration: false,
+            ldap: LdapConfig::default(),
+            oidc: OidcConfig::default(),
+            lockout: LockoutConfig::default(),
+            password_policy: PasswordPolicyConfig::default(),
+        }
+    }
+}
+
+impl Default for LockoutConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_failures_per_user: 5,
+            max_failures_per_ip: 20,
+            window_secs: 900,
+            lockout_secs: 300,
+            max_lockout_secs: 3600,
+            trust_forwarded_for: false,
+        }
+    }
+}
+
+impl Default for PasswordPolicyConfig {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            require_uppercase: false,
+            require_lowercase: false,
+            require_digit: true,
+            require_symbol: false,
+            reject_username: true,
+        }
+    }
+}
+
+impl Default for LdapConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: "ldap://localhost:389".to_string(),
+            bind_dn: String::new(),
+            bind_password: String::new(),
+            user_base_dn: String::new(),
+            user_filter: "(uid={username})".to_string(),
+            id_attribute: "entryUUID".to_string(),
+            email_attribute: "mail".to_string(),
+            group_attribute: "memberOf".to_string(),
+            timeout_secs: 10,
+            group_roles: HashMap::new(),
+            default_role: None,
+        }
+    }
+}
+
+impl Default for OidcConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            issuer_url: String::new(),
+            client_id: String::new(),
+            client_secret: String::new(),
+            redirect_url: String::new(),
+            scopes: vec!["openid".to_string(), "profile".to_string(), "email".to_string()],
+            username_claim: "preferred_username".to_string(),
+            groups_claim: "groups".to_string(),
+            post_login_redirect: "/auth/login".to_string(),
+            group_roles: HashMap::new(),
+            default_role: None,
         }
     }
 }
 
+impl Default for SecurityConfig {
+    fn default() -> Self {
+        Self { master_key: String::new(), master_key_file: "data/master.key".to_string() }
+    }
+}
+
+impl SecurityConfig {
+    /// Use the configured master key, else the one in `master_key_file`
+    ///
+    /// A missing file is created with a random key, so stored passwords are never
+    /// encrypted with a key that ships with the source.
+    fn resolve_master_key(&mut self) -> Result<(), anyhow::Error> {
+        if !self.master_key.is_empty() {
+            return Ok(());
+        }
+
+        let path = Path::new(&self.master_key_file);
+        match fs::read_to_string(path) {
+            Ok(key) => {
+                self.master_key = key.trim().to_string();
+                if self.master_key.is_empty() {
+                    anyhow::bail!("Master key file {} is empty", path.display());
+                }
+                tracing::info!("Using master key from {}", path.display());
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let key = crypto::generate_master_key();
+                write_key_file(path, &key)?;
+                tracing::warn!(
+                    "⚠️  No master key configured; generated one in {}. Back it up: stored \
+                     cluster passwords cannot be decrypted without it",
+                    path.display()
+                );
+                self.master_key = key;
+            },
+            Err(e) => {
+                anyhow::bail!("Failed to read master key file {}: {}", path.display(), e)
+            },
+        }
+        Ok(())
+    }
+}
+
+/// Create a file readable only by the owner; never overwrites an existing key
+fn write_key_file(path: &Path, key: &str) -> Result<(), anyhow::Error> {
+    use std::io::Write;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path)?;
+    writeln!(file, "{}", key)?;
+    Ok(())
+}
+
+impl Default for MySQLPoolConfig {
+    fn default() -> Self {
+        Self {
+            min_connections: 1,
+            max_connections: 10,
+            idle_timeout_secs: 300,
+            max_lifetime_secs: 3600,
+            evict_idle_pool_secs: 1800,
+        }
+    }
+}
+
+impl Default for TopologyConfig {
+    fn default() -> Self {
+        Self { refresh_interval_secs: 600 }
+    }
+}
+
+impl Default for DiagnosticsConfig {
+    fn default() -> Self {
+        Self {
+            journal_lag_warning: 1_000,
+            journal_lag_critical: 100_000,
+            heartbeat_age_warning_secs: 30,
+            heartbeat_age_critical_secs: 120,
+            clock_skew_warning_ms: 2_000,
+            clock_skew_critical_ms: 5_000,
+            disk_usage_warning_pct: 85.0,
+            disk_usage_critical_pct: 95.0,
+        }
+    }
+}
+
+impl Default for QueryStreamConfig {
+    fn default() -> Self {
+        Self { max_rows: 1_000_000, max_bytes: 256 * 1024 * 1024, buffer_rows: 256 }
+    }
+}
+
+impl Default for QueryJobConfig {
+    fn default() -> Self {
+        Self {
+            retention_hours: 24,
+            max_rows: 100_000,
+            max_bytes: 64 * 1024 * 1024,
+            max_running_per_user: 3,
+        }
+    }
+}
+
+impl Default for SqlGuardConfig {
+    fn default() -> Self {
+        use StatementCategory::*;
+        Self {
+            enabled: true,
+            viewer: vec![Read],
+            operator: vec![Read, Dml, Ddl],
+            admin: vec![Read, Dml, Ddl, Admin],
+            confirm_destructive: true,
+            confirmation_ttl_secs: 300,
+        }
+    }
+}
+
+impl Default for QueryCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_entries: 1000,
+            max_bytes: 64 * 1024 * 1024,
+            max_entry_bytes: 8 * 1024 * 1024,
+            max_ttl_secs: 3600,
+            overview_ttl_secs: 0,
+        }
+    }
+}
+
+impl Default for QueryPlanConfig {
+    fn default() -> Self {
+        Self { broadcast_max_rows: 1_000_000, full_scan_min_rows: 1_000_000 }
+    }
+}
+
 impl Default for LoggingConfig {
     fn default() -> Self {
         Self {
@@ -219,3 +773,68 @@ impl Default for StaticConfig {
         Self { enabled: true, web_root: "web".to_string() }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("starrocks-admin-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn unset_key(file: &Path) -> SecurityConfig {
+        SecurityConfig {
+            master_key: String::new(),
+            master_key_file: file.to_string_lossy().into_owned(),
+        }
+    }
+
+    #[test]
+    fn test_unset_master_key_is_generated_and_kept() {
+        assert!(SecurityConfig::default().master_key.is_empty());
+
+        let dir = temp_dir("master-key");
+        let file = dir.join("data").join("master.key");
+        let mut security = unset_key(&file);
+        security.resolve_master_key().unwrap();
+        let generated = security.master_key.clone();
+        assert!(crypto::SecretCipher::new(&generated).is_ok());
+        assert_eq!(fs::read_to_string(&file).unwrap().trim(), generated);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&file).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // The next start reads the same key instead of generating another one
+        let mut restarted = unset_key(&file);
+        restarted.resolve_master_key().unwrap();
+        assert_eq!(restarted.master_key, generated);
+
+        // Another installation gets its own key
+        let mut other = unset_key(&dir.join("other.key"));
+        other.resolve_master_key().unwrap();
+        assert_ne!(other.master_key, generated);
+
+        // A configured key wins and no file is written
+        let mut configured = unset_key(&dir.join("unused.key"));
+        configured.master_key = "configured-key".to_string();
+        configured.resolve_master_key().unwrap();
+        assert_eq!(configured.master_key, "configured-key");
+        assert!(!dir.join("unused.key").exists());
+
+        // An empty key file is an error, not a reason to fall back
+        fs::write(dir.join("empty.key"), "\n").unwrap();
+        assert!(
+            unset_key(&dir.join("empty.key"))
+                .resolve_master_key()
+                .is_err()
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
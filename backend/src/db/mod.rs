@@ -52,6 +52,25 @@ pub async fn create_pool(database_url: &str) -> Result<SqlitePool, sqlx::Error>
     Ok(pool)
 }
 
+/// Migrated in-memory database for tests
+///
+/// Limited to one connection: every in-memory connection would be a separate database.
+#[cfg(test)]
+pub async fn test_pool() -> SqlitePool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    sqlx::migrate::Migrator::new(Path::new("migrations"))
+        .await
+        .unwrap()
+        .run(&pool)
+        .await
+        .unwrap();
+    pool
+}
+
 fn find_migrations_dir() -> String {
     // Try different possible locations for migrations
     let possible_paths = [
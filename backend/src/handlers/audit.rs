@@ -0,0 +1,83 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::header,
+    response::IntoResponse,
+};
+use std::sync::Arc;
+
+use crate::AppState;
+use crate::models::{AdminAuditLogResponse, AdminAuditQuery};
+use crate::utils::ApiResult;
+
+// Search the admin audit log
+#[utoipa::path(
+    get,
+    path = "/api/audit",
+    params(
+        ("username" = Option<String>, Query, description = "Exact username"),
+        ("action" = Option<String>, Query, description = "Action name or prefix, e.g. `query` or `query.kill`"),
+        ("cluster_id" = Option<i64>, Query, description = "Cluster the action was performed on"),
+        ("success" = Option<bool>, Query, description = "Only successful or only failed actions"),
+        ("search" = Option<String>, Query, description = "Substring of target, route or parameters"),
+        ("from" = Option<String>, Query, description = "Start time, inclusive (RFC 3339)"),
+        ("to" = Option<String>, Query, description = "End time, exclusive (RFC 3339)"),
+        ("page" = Option<i64>, Query, description = "1-based page number (default 1)"),
+        ("page_size" = Option<i64>, Query, description = "Entries per page (default 50, max 500)"),
+    ),
+    responses(
+        (status = 200, description = "Audit entries, newest first", body = AdminAuditLogResponse),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Audit"
+)]
+pub async fn list_audit_log(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<AdminAuditQuery>,
+) -> ApiResult<Json<AdminAuditLogResponse>> {
+    let result = state.admin_audit_service.search(&query).await?;
+    Ok(Json(result))
+}
+
+// Export the admin audit log as CSV
+#[utoipa::path(
+    get,
+    path = "/api/audit/export",
+    params(
+        ("username" = Option<String>, Query, description = "Exact username"),
+        ("action" = Option<String>, Query, description = "Action name or prefix"),
+        ("cluster_id" = Option<i64>, Query, description = "Cluster the action was performed on"),
+        ("success" = Option<bool>, Query, description = "Only successful or only failed actions"),
+        ("search" = Option<String>, Query, description = "Substring of target, route or parameters"),
+        ("from" = Option<String>, Query, description = "Start time, inclusive (RFC 3339)"),
+        ("to" = Option<String>, Query, description = "End time, exclusive (RFC 3339)"),
+    ),
+    responses(
+        (status = 200, description = "CSV file with all matching entries, oldest first", content_type = "text/csv"),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Audit"
+)]
+pub async fn export_audit_log(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(username): axum::extract::Extension<String>,
+    Query(query): Query<AdminAuditQuery>,
+) -> ApiResult<impl IntoResponse> {
+    let csv = state.admin_audit_service.export_csv(&query).await?;
+    tracing::info!("Admin audit log exported by {}", username);
+
+    let filename = format!("admin-audit-{}.csv", chrono::Utc::now().format("%Y%m%d-%H%M%S"));
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        csv,
+    ))
+}
@@ -1,11 +1,21 @@
-use axum::{Json, extract::State};
+use axum::{
+    Json,
+    extract::{ConnectInfo, Path, Query, State},
+    http::HeaderMap,
+    response::Redirect,
+};
+use serde::Deserialize;
+use std::net::SocketAddr;
 use std::sync::Arc;
 
 use crate::AppState;
 use crate::models::{
-    CreateUserRequest, LoginRequest, LoginResponse, UpdateUserRequest, UserResponse,
+    ApiTokenResponse, AuthProvidersResponse, AuthSource, CreateApiTokenRequest,
+    CreateApiTokenResponse, CreateUserRequest, CurrentUser, LoginRequest, LoginResponse,
+    RefreshTokenRequest, TokenResponse, UpdateUserRequest, UserResponse, UserRole,
 };
-use crate::utils::ApiResult;
+use crate::utils::jwt::Claims;
+use crate::utils::{ApiError, ApiResult};
 
 // Register a new user
 #[utoipa::path(
@@ -37,24 +47,85 @@ pub async fn register(
     path = "/api/auth/login",
     request_body = LoginRequest,
     responses(
-        (status = 200, description = "Login successful", body = LoginResponse),
-        (status = 401, description = "Invalid credentials")
+        (status = 200, description = "Login successful; check `user.must_change_password`", body = LoginResponse),
+        (status = 401, description = "Invalid credentials"),
+        (status = 429, description = "Too many failed attempts for this username or client, see Retry-After")
     ),
     tag = "Authentication"
 )]
 pub async fn login(
     State(state): State<Arc<AppState>>,
+    peer: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
     Json(req): Json<LoginRequest>,
 ) -> ApiResult<Json<LoginResponse>> {
     tracing::info!("User login attempt for username: {}", req.username);
     tracing::debug!("Login request: username={}", req.username);
 
-    let (user, token) = state.auth_service.login(req).await?;
+    let client_ip = state
+        .login_throttle
+        .client_ip(&headers, peer.map(|ConnectInfo(addr)| addr.ip()));
+    let (user, tokens) = state.auth_service.login(req, client_ip.as_deref()).await?;
 
     tracing::info!("User logged in successfully: {} (ID: {})", user.username, user.id);
     tracing::debug!("JWT token generated for user: {}", user.username);
 
-    Ok(Json(LoginResponse { token, user: user.into() }))
+    Ok(Json(LoginResponse {
+        token: tokens.token,
+        refresh_token: tokens.refresh_token,
+        expires_in: tokens.expires_in,
+        user: user.into(),
+    }))
+}
+
+// Exchange a refresh token for a new access token
+#[utoipa::path(
+    post,
+    path = "/api/auth/refresh",
+    request_body = RefreshTokenRequest,
+    responses(
+        (status = 200, description = "New token pair, the old refresh token is invalidated", body = TokenResponse),
+        (status = 401, description = "Invalid, expired or revoked refresh token")
+    ),
+    tag = "Authentication"
+)]
+pub async fn refresh(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<RefreshTokenRequest>,
+) -> ApiResult<Json<TokenResponse>> {
+    let (user, tokens) = state.session_service.refresh(&req.refresh_token).await?;
+
+    tracing::debug!("Token refreshed for user: {} (ID: {})", user.username, user.id);
+    Ok(Json(tokens))
+}
+
+// Logout and revoke the current session
+#[utoipa::path(
+    post,
+    path = "/api/auth/logout",
+    responses(
+        (status = 200, description = "Session revoked"),
+        (status = 400, description = "Request was made with an API token"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Authentication"
+)]
+pub async fn logout(
+    State(state): State<Arc<AppState>>,
+    claims: Option<axum::extract::Extension<Claims>>,
+) -> ApiResult<Json<serde_json::Value>> {
+    let Some(axum::extract::Extension(claims)) = claims else {
+        return Err(ApiError::validation_error(
+            "API tokens have no session to log out of; revoke the token instead",
+        ));
+    };
+    state.session_service.logout(&claims).await?;
+
+    tracing::info!("User logged out: {} (ID: {})", claims.username, claims.sub);
+    Ok(Json(serde_json::json!({"message": "Logged out successfully"})))
 }
 
 // Get current user info
@@ -90,7 +161,8 @@ pub async fn get_me(
     responses(
         (status = 200, description = "User updated successfully", body = UserResponse),
         (status = 401, description = "Unauthorized"),
-        (status = 400, description = "Bad request")
+        (status = 400, description = "Bad request"),
+        (status = 403, description = "Request was made with an API token without write scope")
     ),
     security(
         ("bearer_auth" = [])
@@ -99,9 +171,11 @@ pub async fn get_me(
 )]
 pub async fn update_me(
     State(state): State<Arc<AppState>>,
-    axum::extract::Extension(user_id): axum::extract::Extension<i64>,
+    axum::extract::Extension(user): axum::extract::Extension<CurrentUser>,
     Json(req): Json<UpdateUserRequest>,
 ) -> ApiResult<Json<UserResponse>> {
+    require_write_scope(&user)?;
+    let user_id = user.id;
     tracing::info!("User update attempt for user_id: {}", user_id);
     tracing::debug!(
         "Update request: email={:?}, avatar={:?}, changing_password={}",
@@ -115,3 +189,219 @@ pub async fn update_me(
     tracing::info!("User updated successfully: {} (ID: {})", user.username, user.id);
     Ok(Json(user.into()))
 }
+
+// List the sign-in methods enabled on this server
+#[utoipa::path(
+    get,
+    path = "/api/auth/providers",
+    responses(
+        (status = 200, description = "Enabled sign-in methods", body = AuthProvidersResponse)
+    ),
+    tag = "Authentication"
+)]
+pub async fn get_providers(State(state): State<Arc<AppState>>) -> Json<AuthProvidersResponse> {
+    Json(AuthProvidersResponse {
+        local: true,
+        ldap: state.auth_service.has_password_backend(AuthSource::Ldap),
+        oidc: state.oidc_provider.is_some(),
+        allow_registration: state.auth_service.allows_registration(),
+    })
+}
+
+// Start OIDC single sign-on by redirecting to the identity provider
+#[utoipa::path(
+    get,
+    path = "/api/auth/oidc/login",
+    responses(
+        (status = 302, description = "Redirect to the identity provider"),
+        (status = 404, description = "OIDC sign-in is not enabled")
+    ),
+    tag = "Authentication"
+)]
+pub async fn oidc_login(State(state): State<Arc<AppState>>) -> ApiResult<Redirect> {
+    let provider = state
+        .oidc_provider
+        .as_ref()
+        .ok_or_else(|| ApiError::not_found("OIDC sign-in is not enabled"))?;
+
+    Ok(Redirect::to(&provider.authorization_url().await?))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OidcCallbackParams {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+}
+
+// Finish OIDC sign-on and hand the tokens to the frontend in the URL fragment
+#[utoipa::path(
+    get,
+    path = "/api/auth/oidc/callback",
+    params(
+        ("code" = Option<String>, Query, description = "Authorization code"),
+        ("state" = Option<String>, Query, description = "State issued by /api/auth/oidc/login"),
+    ),
+    responses(
+        (status = 302, description = "Redirect to the frontend with tokens or an sso_error"),
+        (status = 404, description = "OIDC sign-in is not enabled")
+    ),
+    tag = "Authentication"
+)]
+pub async fn oidc_callback(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<OidcCallbackParams>,
+) -> ApiResult<Redirect> {
+    let provider = state
+        .oidc_provider
+        .as_ref()
+        .ok_or_else(|| ApiError::not_found("OIDC sign-in is not enabled"))?;
+    let target = provider.post_login_redirect();
+
+    let result = match (&params.code, &params.state, &params.error) {
+        (Some(code), Some(oidc_state), None) => match provider.complete(code, oidc_state).await {
+            Ok(identity) => state.auth_service.login_external(identity).await,
+            Err(e) => Err(e),
+        },
+        _ => {
+            let reason = params
+                .error_description
+                .or(params.error)
+                .unwrap_or_else(|| "Missing authorization code".to_string());
+            tracing::warn!("OIDC sign-in failed at the provider: {}", reason);
+            Err(ApiError::unauthorized(reason))
+        },
+    };
+
+    // Tokens travel in the fragment so they never reach server logs or Referer headers
+    let location = match result {
+        Ok((user, tokens)) => {
+            tracing::info!("User signed in via OIDC: {} (ID: {})", user.username, user.id);
+            format!(
+                "{}#token={}&refresh_token={}&expires_in={}",
+                target,
+                urlencoding::encode(&tokens.token),
+                urlencoding::encode(&tokens.refresh_token),
+                tokens.expires_in
+            )
+        },
+        Err(e) => format!("{}#sso_error={}", target, urlencoding::encode(&e.to_string())),
+    };
+
+    Ok(Redirect::to(&location))
+}
+
+// List the personal API tokens of the current user
+#[utoipa::path(
+    get,
+    path = "/api/auth/tokens",
+    responses(
+        (status = 200, description = "API tokens of the current user, without secrets", body = Vec<ApiTokenResponse>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Authentication"
+)]
+pub async fn list_api_tokens(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(user): axum::extract::Extension<CurrentUser>,
+) -> ApiResult<Json<Vec<ApiTokenResponse>>> {
+    require_session(&user)?;
+    let tokens = state.api_token_service.list(user.id).await?;
+    Ok(Json(tokens))
+}
+
+// Create a personal API token
+#[utoipa::path(
+    post,
+    path = "/api/auth/tokens",
+    request_body = CreateApiTokenRequest,
+    responses(
+        (status = 200, description = "Token created; the secret is only shown in this response", body = CreateApiTokenResponse),
+        (status = 400, description = "Invalid name, scopes or expiry"),
+        (status = 403, description = "Request was made with an API token")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Authentication"
+)]
+pub async fn create_api_token(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(user): axum::extract::Extension<CurrentUser>,
+    Json(req): Json<CreateApiTokenRequest>,
+) -> ApiResult<Json<CreateApiTokenResponse>> {
+    require_session(&user)?;
+    let created = state.api_token_service.create(user.id, req).await?;
+    Ok(Json(created))
+}
+
+// Revoke a personal API token
+#[utoipa::path(
+    delete,
+    path = "/api/auth/tokens/{id}",
+    params(
+        ("id" = i64, Path, description = "API token ID")
+    ),
+    responses(
+        (status = 200, description = "Token revoked"),
+        (status = 403, description = "Request was made with an API token"),
+        (status = 404, description = "Token not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Authentication"
+)]
+pub async fn revoke_api_token(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(user): axum::extract::Extension<CurrentUser>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<serde_json::Value>> {
+    require_session(&user)?;
+    state.api_token_service.revoke(user.id, id).await?;
+    Ok(Json(serde_json::json!({"message": "API token revoked"})))
+}
+
+/// Tokens are managed from a login session so a leaked token cannot mint new ones
+fn require_session(user: &CurrentUser) -> ApiResult<()> {
+    if user.api_token.is_some() {
+        return Err(ApiError::forbidden("API tokens cannot be managed with an API token"));
+    }
+    Ok(())
+}
+
+/// Profile changes are writes, so a read-scope token may only look at the profile
+fn require_write_scope(user: &CurrentUser) -> ApiResult<()> {
+    if user.cap(UserRole::Operator) < UserRole::Operator {
+        return Err(ApiError::forbidden(
+            "Updating the profile needs an API token with write scope",
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ApiTokenGrant;
+
+    fn caller(api_token: Option<ApiTokenGrant>) -> CurrentUser {
+        CurrentUser { id: 1, username: "alice".to_string(), role: UserRole::Viewer, api_token }
+    }
+
+    #[test]
+    fn test_read_scope_token_cannot_update_profile() {
+        let read = caller(Some(ApiTokenGrant { id: 1, max_role: UserRole::Viewer }));
+        let err = require_write_scope(&read).unwrap_err();
+        assert!(matches!(err, ApiError::Forbidden(_)), "{:?}", err);
+
+        let write = caller(Some(ApiTokenGrant { id: 2, max_role: UserRole::Operator }));
+        assert!(require_write_scope(&write).is_ok());
+        // Viewers still manage their own profile from a login session
+        assert!(require_write_scope(&caller(None)).is_ok());
+    }
+}
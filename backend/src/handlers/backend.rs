@@ -1,10 +1,6 @@
-use axum::{
-    Json,
-    extract::{Path, State},
-};
-use std::sync::Arc;
+use axum::{Json, extract::Path};
 
-use crate::AppState;
+use crate::middleware::SelectedCluster;
 use crate::models::Backend;
 use crate::services::StarRocksClient;
 use crate::utils::ApiResult;
@@ -22,9 +18,10 @@ use crate::utils::ApiResult;
     ),
     tag = "Backends"
 )]
-pub async fn list_backends(State(state): State<Arc<AppState>>) -> ApiResult<Json<Vec<Backend>>> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
-    let client = StarRocksClient::new(cluster);
+pub async fn list_backends(
+    SelectedCluster(cluster): SelectedCluster,
+) -> ApiResult<Json<Vec<Backend>>> {
+    let client = StarRocksClient::new(cluster)?;
     let backends = client.get_backends().await?;
     Ok(Json(backends))
 }
@@ -48,13 +45,12 @@ pub async fn list_backends(State(state): State<Arc<AppState>>) -> ApiResult<Json
     tag = "Backends"
 )]
 pub async fn delete_backend(
-    State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
     Path((host, port)): Path<(String, String)>,
 ) -> ApiResult<Json<serde_json::Value>> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
     tracing::info!("Deleting backend {}:{} from cluster {}", host, port, cluster.id);
 
-    let client = StarRocksClient::new(cluster);
+    let client = StarRocksClient::new(cluster)?;
     client.drop_backend(&host, &port).await?;
 
     Ok(Json(serde_json::json!({
@@ -1,12 +1,16 @@
 use axum::{
     Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
 };
 use std::sync::Arc;
 
 use crate::AppState;
-use crate::models::{ClusterHealth, ClusterResponse, CreateClusterRequest, UpdateClusterRequest};
-use crate::utils::ApiResult;
+use crate::models::{
+    Cluster, ClusterHealth, ClusterResponse, CreateClusterRequest, FeEndpoint,
+    ImportClusterRequest, ImportClusterResponse, TopologyDiscovery, UpdateClusterRequest,
+};
+use crate::services::{ClusterService, FeRegistry};
+use crate::utils::{ApiError, ApiResult, crypto};
 use serde::Deserialize;
 
 // Create a new cluster
@@ -42,10 +46,22 @@ pub async fn create_cluster(
     Ok(Json(cluster.into()))
 }
 
-// List all clusters
+#[derive(Debug, Deserialize)]
+pub struct ListClustersParams {
+    /// Comma separated tags; a cluster must carry all of them
+    pub tags: Option<String>,
+    /// Cluster group ID or name
+    pub group: Option<String>,
+}
+
+// List all clusters, optionally filtered by tags and group
 #[utoipa::path(
     get,
     path = "/api/clusters",
+    params(
+        ("tags" = Option<String>, Query, description = "Comma separated tags, all must match"),
+        ("group" = Option<String>, Query, description = "Cluster group ID or name")
+    ),
     responses(
         (status = 200, description = "List of clusters", body = Vec<ClusterResponse>)
     ),
@@ -56,10 +72,28 @@ pub async fn create_cluster(
 )]
 pub async fn list_clusters(
     State(state): State<Arc<AppState>>,
+    Query(params): Query<ListClustersParams>,
 ) -> ApiResult<Json<Vec<ClusterResponse>>> {
-    tracing::debug!("Listing all clusters");
+    tracing::debug!("Listing clusters: tags={:?}, group={:?}", params.tags, params.group);
+
+    let mut clusters = state.cluster_service.list_clusters().await?;
+
+    let tags: Vec<String> = params
+        .tags
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+    clusters.retain(|c| c.has_tags(&tags));
+
+    if let Some(group) = params.group.as_deref().filter(|g| !g.trim().is_empty()) {
+        let group = state.cluster_group_service.find(group).await?;
+        let members = state.cluster_group_service.clusters(group.id).await?;
+        clusters.retain(|c| members.iter().any(|m| m.id == c.id));
+    }
 
-    let clusters = state.cluster_service.list_clusters().await?;
     let responses: Vec<ClusterResponse> = clusters.into_iter().map(|c| c.into()).collect();
 
     tracing::debug!("Retrieved {} clusters", responses.len());
@@ -165,6 +199,12 @@ pub async fn update_cluster(
     Json(req): Json<UpdateClusterRequest>,
 ) -> ApiResult<Json<ClusterResponse>> {
     let cluster = state.cluster_service.update_cluster(id, req).await?;
+
+    // Reconnect with the new FEs / credentials
+    FeRegistry::global().forget_cluster(id);
+    state.mysql_pool_manager.remove_pool(id).await;
+    state.query_cache.invalidate_cluster(id);
+
     Ok(Json(cluster.into()))
 }
 
@@ -191,6 +231,9 @@ pub async fn delete_cluster(
     tracing::warn!("Cluster deletion request for ID: {}", id);
 
     state.cluster_service.delete_cluster(id).await?;
+    FeRegistry::global().forget_cluster(id);
+    state.mysql_pool_manager.remove_pool(id).await;
+    state.query_cache.invalidate_cluster(id);
 
     tracing::warn!("Cluster deleted successfully: ID {}", id);
     Ok(Json(serde_json::json!({"message": "Cluster deleted successfully"})))
@@ -212,9 +255,74 @@ pub struct HealthCheckRequest {
     #[serde(default)]
     pub enable_ssl: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls_ca_cert: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls_client_identity: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls_client_identity_password: Option<String>,
+    #[serde(default = "default_verify_hostname")]
+    pub tls_verify_hostname: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub catalog: Option<String>,
 }
 
+fn default_verify_hostname() -> bool {
+    true
+}
+
+// Unsaved cluster for connection tests; goes through the same encrypted path as stored ones
+fn temp_cluster(health_req: HealthCheckRequest) -> ApiResult<Cluster> {
+    let password_encrypted =
+        crypto::encrypt_secret(health_req.password.as_deref().unwrap_or_default())?;
+
+    let tls_ca_cert = health_req.tls_ca_cert.filter(|pem| !pem.trim().is_empty());
+    if let Some(ca_cert) = &tls_ca_cert {
+        ClusterService::validate_ca_cert(ca_cert)?;
+    }
+    let (tls_client_identity_encrypted, tls_client_identity_password_encrypted) =
+        match health_req.tls_client_identity.as_deref() {
+            Some(identity) if !identity.trim().is_empty() => {
+                let (identity, password) = ClusterService::encrypt_client_identity(
+                    identity,
+                    health_req
+                        .tls_client_identity_password
+                        .as_deref()
+                        .unwrap_or_default(),
+                )?;
+                (Some(identity), Some(password))
+            },
+            _ => (None, None),
+        };
+
+    Ok(Cluster {
+        id: 0,
+        name: "test".to_string(),
+        description: None,
+        fe_host: health_req.fe_host.unwrap_or_default(),
+        fe_http_port: health_req.fe_http_port.unwrap_or(8030),
+        fe_query_port: health_req.fe_query_port.unwrap_or(9030),
+        fe_endpoints: None,
+        username: health_req.username.unwrap_or_else(|| "root".to_string()),
+        password_encrypted,
+        enable_ssl: health_req.enable_ssl,
+        tls_ca_cert,
+        tls_client_identity_encrypted,
+        tls_client_identity_password_encrypted,
+        tls_verify_hostname: health_req.tls_verify_hostname,
+        connection_timeout: 10,
+        pool_settings: None,
+        sql_policy: None,
+        catalog: health_req
+            .catalog
+            .unwrap_or_else(|| "default_catalog".to_string()),
+        is_active: false,
+        tags: None,
+        created_at: chrono::Utc::now(),
+        updated_at: chrono::Utc::now(),
+        created_by: None,
+    })
+}
+
 // Get cluster health
 // Supports two modes:
 // 1. GET with cluster ID: Check health of existing cluster from database
@@ -240,8 +348,6 @@ pub async fn get_cluster_health(
     Path(id): Path<i64>,
     body: Option<Json<HealthCheckRequest>>,
 ) -> ApiResult<Json<ClusterHealth>> {
-    use crate::models::Cluster;
-
     // Mode 1: If body provided with connection details, use them (new cluster mode)
     if let Some(Json(health_req)) = body
         && health_req.fe_host.is_some()
@@ -257,26 +363,7 @@ pub async fn get_cluster_health(
             health_req.catalog.as_deref().unwrap_or("default_catalog")
         );
 
-        let temp_cluster = Cluster {
-            id: 0,
-            name: "test".to_string(),
-            description: None,
-            fe_host: health_req.fe_host.unwrap(),
-            fe_http_port: health_req.fe_http_port.unwrap_or(8030),
-            fe_query_port: health_req.fe_query_port.unwrap_or(9030),
-            username: health_req.username.unwrap_or_else(|| "root".to_string()),
-            password_encrypted: health_req.password.unwrap_or_default(),
-            enable_ssl: health_req.enable_ssl,
-            connection_timeout: 10,
-            catalog: health_req
-                .catalog
-                .unwrap_or_else(|| "default_catalog".to_string()),
-            is_active: false,
-            tags: None,
-            created_at: chrono::Utc::now(),
-            updated_at: chrono::Utc::now(),
-            created_by: None,
-        };
+        let temp_cluster = temp_cluster(health_req)?;
 
         let health = state
             .cluster_service
@@ -294,6 +381,32 @@ pub async fn get_cluster_health(
     Ok(Json(health))
 }
 
+// Deep health check: FE quorum, journal replay, BE heartbeats and disks,
+// tablet replicas, versions and clock skew, each with remediation text
+#[utoipa::path(
+    get,
+    path = "/api/clusters/{id}/health/deep",
+    params(
+        ("id" = i64, Path, description = "Cluster ID")
+    ),
+    responses(
+        (status = 200, description = "Per-component diagnostics; status is the worst check", body = ClusterHealth),
+        (status = 404, description = "Cluster not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Clusters"
+)]
+pub async fn get_cluster_diagnostics(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<ClusterHealth>> {
+    let health = state.cluster_diagnostics_service.diagnose(id).await?;
+    tracing::debug!("Deep health check of cluster {}: {:?}", id, health.status);
+    Ok(Json(health))
+}
+
 /// Test cluster connection with provided credentials (no ID required)
 #[utoipa::path(
     post,
@@ -312,8 +425,6 @@ pub async fn test_cluster_connection(
     State(state): State<Arc<crate::AppState>>,
     Json(health_req): Json<HealthCheckRequest>,
 ) -> ApiResult<Json<ClusterHealth>> {
-    use crate::models::Cluster;
-
     tracing::info!(
         "Testing connection with provided credentials: host={}",
         health_req
@@ -327,26 +438,7 @@ pub async fn test_cluster_connection(
         return Err(crate::utils::ApiError::validation_error("Missing required field: fe_host"));
     }
 
-    let temp_cluster = Cluster {
-        id: 0,
-        name: "test".to_string(),
-        description: None,
-        fe_host: health_req.fe_host.unwrap(),
-        fe_http_port: health_req.fe_http_port.unwrap_or(8030),
-        fe_query_port: health_req.fe_query_port.unwrap_or(9030),
-        username: health_req.username.unwrap_or_else(|| "root".to_string()),
-        password_encrypted: health_req.password.unwrap_or_default(),
-        enable_ssl: health_req.enable_ssl,
-        connection_timeout: 10,
-        catalog: health_req
-            .catalog
-            .unwrap_or_else(|| "default_catalog".to_string()),
-        is_active: false,
-        tags: None,
-        created_at: chrono::Utc::now(),
-        updated_at: chrono::Utc::now(),
-        created_by: None,
-    };
+    let temp_cluster = temp_cluster(health_req)?;
 
     let health = state
         .cluster_service
@@ -356,3 +448,111 @@ pub async fn test_cluster_connection(
     tracing::debug!("Connection test result: status={:?}", health.status);
     Ok(Json(health))
 }
+
+/// Discover the topology of a cluster from one FE, without registering it
+#[utoipa::path(
+    post,
+    path = "/api/clusters/discover",
+    request_body = HealthCheckRequest,
+    responses(
+        (status = 200, description = "Credential checks and discovered topology", body = TopologyDiscovery),
+        (status = 400, description = "Invalid request")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Clusters"
+)]
+pub async fn discover_cluster(
+    State(state): State<Arc<crate::AppState>>,
+    Json(req): Json<HealthCheckRequest>,
+) -> ApiResult<Json<TopologyDiscovery>> {
+    if req.fe_host.is_none() {
+        return Err(ApiError::validation_error("Missing required field: fe_host"));
+    }
+    tracing::info!("Discovering cluster topology from {:?}", req.fe_host);
+
+    let probe = temp_cluster(req)?;
+    let discovery = state.topology_service.discover(&probe).await?;
+    Ok(Json(discovery))
+}
+
+/// Register a cluster from one FE: validate both ports, add the other FEs and store the topology
+#[utoipa::path(
+    post,
+    path = "/api/clusters/import",
+    request_body = ImportClusterRequest,
+    responses(
+        (status = 200, description = "Cluster imported", body = ImportClusterResponse),
+        (status = 400, description = "Invalid request"),
+        (status = 502, description = "HTTP or MySQL port rejected the connection or credentials")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Clusters"
+)]
+pub async fn import_cluster(
+    State(state): State<Arc<crate::AppState>>,
+    axum::extract::Extension(user_id): axum::extract::Extension<i64>,
+    Json(req): Json<ImportClusterRequest>,
+) -> ApiResult<Json<ImportClusterResponse>> {
+    let ImportClusterRequest { mut cluster, add_discovered_fes } = req;
+    tracing::info!("Cluster import request: name={}, host={}", cluster.name, cluster.fe_host);
+
+    let probe = temp_cluster(connection_of(&cluster))?;
+    let discovery = state.topology_service.discover(&probe).await?;
+    if let Some(failure) = discovery.failure() {
+        return Err(ApiError::cluster_connection_failed(failure));
+    }
+
+    if add_discovered_fes {
+        for fe in &discovery.topology.frontends {
+            let endpoint = FeEndpoint {
+                host: fe.host.clone(),
+                http_port: fe.http_port,
+                query_port: fe.query_port,
+            };
+            let is_primary = fe.host == cluster.fe_host && fe.http_port == cluster.fe_http_port;
+            if !is_primary && !cluster.fe_endpoints.contains(&endpoint) {
+                cluster.fe_endpoints.push(endpoint);
+            }
+        }
+    }
+
+    let created = state
+        .cluster_service
+        .create_cluster(cluster, user_id)
+        .await?;
+    state
+        .topology_service
+        .record(created.id, &discovery.topology)
+        .await?;
+
+    tracing::info!(
+        "Cluster imported: {} (ID: {}) with {} FEs, {} BEs, {} compute nodes",
+        created.name,
+        created.id,
+        discovery.topology.frontends.len(),
+        discovery.topology.backends.len(),
+        discovery.topology.compute_nodes.len()
+    );
+    Ok(Json(ImportClusterResponse { cluster: created.into(), topology: discovery.topology }))
+}
+
+// Connection details of a cluster that is about to be created
+fn connection_of(req: &CreateClusterRequest) -> HealthCheckRequest {
+    HealthCheckRequest {
+        fe_host: Some(req.fe_host.clone()),
+        fe_http_port: Some(req.fe_http_port),
+        fe_query_port: Some(req.fe_query_port),
+        username: Some(req.username.clone()),
+        password: Some(req.password.clone()),
+        enable_ssl: req.enable_ssl,
+        tls_ca_cert: req.tls_ca_cert.clone(),
+        tls_client_identity: req.tls_client_identity.clone(),
+        tls_client_identity_password: req.tls_client_identity_password.clone(),
+        tls_verify_hostname: req.tls_verify_hostname,
+        catalog: Some(req.catalog.clone()),
+    }
+}
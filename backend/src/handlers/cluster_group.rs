@@ -0,0 +1,369 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::AppState;
+use crate::models::{
+    Cluster, ClusterFanOutResult, ClusterGroupResponse, CreateClusterGroupRequest, CurrentUser,
+    FanOutQueryRequest, FanOutResponse, FanOutStatus, QueryExecuteResponse,
+    UpdateClusterGroupRequest, UserRole,
+};
+use crate::services::MySQLClient;
+use crate::services::cluster_group_service::{fan_out, is_read_only_sql};
+use crate::utils::sql::apply_query_limit;
+use crate::utils::{ApiError, ApiResult};
+
+/// Most variables that can be read in one fan-out
+const MAX_FAN_OUT_VARIABLES: usize = 50;
+const DEFAULT_FAN_OUT_LIMIT: i32 = 1000;
+const MAX_FAN_OUT_LIMIT: i32 = 10000;
+
+#[derive(Debug, Deserialize)]
+pub struct FanOutVariablesParams {
+    /// Comma separated variable names
+    pub names: String,
+}
+
+// List cluster groups
+#[utoipa::path(
+    get,
+    path = "/api/cluster-groups",
+    responses(
+        (status = 200, description = "Cluster groups with their members", body = Vec<ClusterGroupResponse>)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Cluster Groups"
+)]
+pub async fn list_cluster_groups(
+    State(state): State<Arc<AppState>>,
+) -> ApiResult<Json<Vec<ClusterGroupResponse>>> {
+    Ok(Json(state.cluster_group_service.list().await?))
+}
+
+// Get a cluster group
+#[utoipa::path(
+    get,
+    path = "/api/cluster-groups/{id}",
+    params(
+        ("id" = i64, Path, description = "Cluster group ID")
+    ),
+    responses(
+        (status = 200, description = "Cluster group", body = ClusterGroupResponse),
+        (status = 404, description = "Cluster group not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Cluster Groups"
+)]
+pub async fn get_cluster_group(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<ClusterGroupResponse>> {
+    Ok(Json(state.cluster_group_service.get(id).await?))
+}
+
+// Create a cluster group
+#[utoipa::path(
+    post,
+    path = "/api/cluster-groups",
+    request_body = CreateClusterGroupRequest,
+    responses(
+        (status = 200, description = "Cluster group created", body = ClusterGroupResponse),
+        (status = 400, description = "Invalid name, unknown cluster or overlapping environment")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Cluster Groups"
+)]
+pub async fn create_cluster_group(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(user_id): axum::extract::Extension<i64>,
+    Json(req): Json<CreateClusterGroupRequest>,
+) -> ApiResult<Json<ClusterGroupResponse>> {
+    Ok(Json(state.cluster_group_service.create(req, user_id).await?))
+}
+
+// Update a cluster group; `cluster_ids` replaces the members
+#[utoipa::path(
+    put,
+    path = "/api/cluster-groups/{id}",
+    params(
+        ("id" = i64, Path, description = "Cluster group ID")
+    ),
+    request_body = UpdateClusterGroupRequest,
+    responses(
+        (status = 200, description = "Cluster group updated", body = ClusterGroupResponse),
+        (status = 400, description = "Invalid name, unknown cluster or overlapping environment"),
+        (status = 404, description = "Cluster group not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Cluster Groups"
+)]
+pub async fn update_cluster_group(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<UpdateClusterGroupRequest>,
+) -> ApiResult<Json<ClusterGroupResponse>> {
+    Ok(Json(state.cluster_group_service.update(id, req).await?))
+}
+
+// Delete a cluster group (the clusters themselves are kept)
+#[utoipa::path(
+    delete,
+    path = "/api/cluster-groups/{id}",
+    params(
+        ("id" = i64, Path, description = "Cluster group ID")
+    ),
+    responses(
+        (status = 200, description = "Cluster group deleted"),
+        (status = 404, description = "Cluster group not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Cluster Groups"
+)]
+pub async fn delete_cluster_group(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<serde_json::Value>> {
+    state.cluster_group_service.delete(id).await?;
+    Ok(Json(serde_json::json!({"message": "Cluster group deleted successfully"})))
+}
+
+// Run a health check on every cluster of a group
+#[utoipa::path(
+    post,
+    path = "/api/cluster-groups/{id}/health",
+    params(
+        ("id" = i64, Path, description = "Cluster group ID")
+    ),
+    responses(
+        (status = 200, description = "Health of each cluster", body = FanOutResponse),
+        (status = 404, description = "Cluster group not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Cluster Groups"
+)]
+pub async fn fan_out_health(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(user): axum::extract::Extension<CurrentUser>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<FanOutResponse>> {
+    let group = state.cluster_group_service.get_group(id).await?;
+    let clusters = state.cluster_group_service.clusters(id).await?;
+    let (allowed, denied) = authorize_clusters(&state, &user, clusters, UserRole::Viewer).await?;
+
+    let results = fan_out(allowed, |cluster| {
+        let state = Arc::clone(&state);
+        async move {
+            let health = state
+                .cluster_service
+                .get_cluster_health_for_cluster(&cluster, &state.mysql_pool_manager)
+                .await?;
+            Ok(serde_json::to_value(health)?)
+        }
+    })
+    .await;
+
+    Ok(Json(FanOutResponse::new(&group, merge_results(results, denied))))
+}
+
+// Read global variables on every cluster of a group
+#[utoipa::path(
+    get,
+    path = "/api/cluster-groups/{id}/variables",
+    params(
+        ("id" = i64, Path, description = "Cluster group ID"),
+        ("names" = String, Query, description = "Comma separated variable names")
+    ),
+    responses(
+        (status = 200, description = "Variable values of each cluster (null when unknown)", body = FanOutResponse),
+        (status = 400, description = "No or too many variable names"),
+        (status = 404, description = "Cluster group not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Cluster Groups"
+)]
+pub async fn fan_out_variables(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(user): axum::extract::Extension<CurrentUser>,
+    Path(id): Path<i64>,
+    Query(params): Query<FanOutVariablesParams>,
+) -> ApiResult<Json<FanOutResponse>> {
+    let mut names: Vec<String> = params
+        .names
+        .split(',')
+        .map(|n| n.trim().to_lowercase())
+        .filter(|n| !n.is_empty())
+        .collect();
+    names.sort();
+    names.dedup();
+    if names.is_empty() || names.len() > MAX_FAN_OUT_VARIABLES {
+        return Err(ApiError::validation_error(format!(
+            "Between 1 and {} variable names are required",
+            MAX_FAN_OUT_VARIABLES
+        )));
+    }
+
+    let group = state.cluster_group_service.get_group(id).await?;
+    let clusters = state.cluster_group_service.clusters(id).await?;
+    let (allowed, denied) = authorize_clusters(&state, &user, clusters, UserRole::Viewer).await?;
+
+    let names = Arc::new(names);
+    let results = fan_out(allowed, |cluster| {
+        let state = Arc::clone(&state);
+        let names = Arc::clone(&names);
+        async move {
+            // Filter here rather than in SQL: the names never reach the cluster
+            let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
+            let (_, rows) = MySQLClient::from_pool(pool)
+                .query_raw("SHOW GLOBAL VARIABLES", None, None)
+                .await?;
+            let mut values: HashMap<String, Option<String>> =
+                names.iter().map(|n| (n.clone(), None)).collect();
+            for row in rows {
+                if let (Some(name), Some(value)) = (row.first(), row.get(1))
+                    && let Some(slot) = values.get_mut(&name.to_lowercase())
+                {
+                    *slot = Some(value.clone());
+                }
+            }
+            Ok(serde_json::to_value(values)?)
+        }
+    })
+    .await;
+
+    Ok(Json(FanOutResponse::new(&group, merge_results(results, denied))))
+}
+
+// Run a read-only SQL statement on every cluster of a group
+#[utoipa::path(
+    post,
+    path = "/api/cluster-groups/{id}/query",
+    params(
+        ("id" = i64, Path, description = "Cluster group ID")
+    ),
+    request_body = FanOutQueryRequest,
+    responses(
+        (status = 200, description = "Result set of each cluster", body = FanOutResponse),
+        (status = 400, description = "Statement is not a single read-only query"),
+        (status = 404, description = "Cluster group not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Cluster Groups"
+)]
+pub async fn fan_out_query(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(user): axum::extract::Extension<CurrentUser>,
+    Path(id): Path<i64>,
+    Json(req): Json<FanOutQueryRequest>,
+) -> ApiResult<Json<FanOutResponse>> {
+    if !is_read_only_sql(&req.sql) {
+        return Err(ApiError::validation_error(
+            "Only a single SELECT, SHOW, DESCRIBE, EXPLAIN or WITH statement can run on a group",
+        ));
+    }
+    let limit = req.limit.unwrap_or(DEFAULT_FAN_OUT_LIMIT);
+    if !(1..=MAX_FAN_OUT_LIMIT).contains(&limit) {
+        return Err(ApiError::validation_error(format!(
+            "limit must be between 1 and {}",
+            MAX_FAN_OUT_LIMIT
+        )));
+    }
+
+    let group = state.cluster_group_service.get_group(id).await?;
+    let clusters = state.cluster_group_service.clusters(id).await?;
+    let (allowed, denied) = authorize_clusters(&state, &user, clusters, UserRole::Operator).await?;
+
+    tracing::info!(
+        "User {} runs a query on {} clusters of group '{}'",
+        user.username,
+        allowed.len(),
+        group.name
+    );
+
+    let req = Arc::new(FanOutQueryRequest { sql: apply_query_limit(&req.sql, limit), ..req });
+    let results = fan_out(allowed, |cluster| {
+        let state = Arc::clone(&state);
+        let req = Arc::clone(&req);
+        async move {
+            let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
+            let start = Instant::now();
+            let (columns, mut rows) = MySQLClient::from_pool(pool)
+                .query_typed(&req.sql, req.catalog.as_deref(), req.database.as_deref())
+                .await?;
+            rows.truncate(limit as usize);
+
+            Ok(serde_json::to_value(QueryExecuteResponse {
+                columns,
+                row_count: rows.len(),
+                rows,
+                execution_time_ms: start.elapsed().as_millis(),
+                cached_at: None,
+            })?)
+        }
+    })
+    .await;
+
+    Ok(Json(FanOutResponse::new(&group, merge_results(results, denied))))
+}
+
+// Split clusters into those the user holds `required` on and forbidden results for the rest
+async fn authorize_clusters(
+    state: &AppState,
+    user: &CurrentUser,
+    clusters: Vec<Cluster>,
+    required: UserRole,
+) -> ApiResult<(Vec<Cluster>, Vec<ClusterFanOutResult>)> {
+    let mut allowed = Vec::with_capacity(clusters.len());
+    let mut denied = Vec::new();
+
+    for cluster in clusters {
+        if state
+            .permission_service
+            .effective_role(user, cluster.id)
+            .await?
+            >= required
+        {
+            allowed.push(cluster);
+        } else {
+            denied.push(ClusterFanOutResult {
+                cluster_id: cluster.id,
+                cluster_name: cluster.name,
+                status: FanOutStatus::Forbidden,
+                duration_ms: 0,
+                data: None,
+                error: Some(format!("Role {} required on this cluster", required)),
+            });
+        }
+    }
+
+    Ok((allowed, denied))
+}
+
+fn merge_results(
+    mut results: Vec<ClusterFanOutResult>,
+    denied: Vec<ClusterFanOutResult>,
+) -> Vec<ClusterFanOutResult> {
+    results.extend(denied);
+    results.sort_by(|a, b| a.cluster_name.cmp(&b.cluster_name));
+    results
+}
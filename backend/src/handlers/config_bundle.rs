@@ -0,0 +1,113 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::{HeaderMap, header},
+    response::IntoResponse,
+};
+use std::sync::Arc;
+
+use crate::AppState;
+use crate::models::{
+    ConfigAction, ConfigBundle, ConfigExportParams, ConfigFormat, ConfigImportParams,
+    ConfigImportResponse, ConfigItemKind,
+};
+use crate::services::FeRegistry;
+use crate::utils::ApiResult;
+
+// Export clusters, cluster groups and system function customizations
+#[utoipa::path(
+    get,
+    path = "/api/system/config/export",
+    params(
+        ("format" = Option<String>, Query, description = "`toml` (default) or `json`")
+    ),
+    responses(
+        (status = 200, description = "Configuration bundle without passwords or client certificates", body = ConfigBundle),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "System"
+)]
+pub async fn export_config(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(username): axum::extract::Extension<String>,
+    Query(params): Query<ConfigExportParams>,
+) -> ApiResult<impl IntoResponse> {
+    let bundle = state.config_bundle_service.export().await?;
+    let body = bundle.render(params.format)?;
+    tracing::info!(
+        "Configuration bundle exported by {}: {} clusters, {} groups",
+        username,
+        bundle.clusters.len(),
+        bundle.cluster_groups.len()
+    );
+
+    let filename = format!(
+        "starrocks-admin-config-{}.{}",
+        chrono::Utc::now().format("%Y%m%d-%H%M%S"),
+        params.format.extension()
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, params.format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        body,
+    ))
+}
+
+// Import a configuration bundle, creating or updating items by name
+#[utoipa::path(
+    post,
+    path = "/api/system/config/import",
+    params(
+        ("dry_run" = Option<bool>, Query, description = "Only report what would change"),
+        ("format" = Option<String>, Query, description = "`toml` or `json`; taken from Content-Type by default")
+    ),
+    request_body(content = ConfigBundle, description = "TOML or JSON bundle", content_type = "application/toml"),
+    responses(
+        (status = 200, description = "Changes per item", body = ConfigImportResponse),
+        (status = 400, description = "Invalid bundle; nothing was imported"),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "System"
+)]
+pub async fn import_config(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(user_id): axum::extract::Extension<i64>,
+    Query(params): Query<ConfigImportParams>,
+    headers: HeaderMap,
+    body: String,
+) -> ApiResult<Json<ConfigImportResponse>> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok());
+    let format = params
+        .format
+        .unwrap_or_else(|| ConfigFormat::detect(content_type, &body));
+    let bundle = ConfigBundle::parse(&body, format)?;
+
+    let response = state
+        .config_bundle_service
+        .import(bundle, params.dry_run, user_id)
+        .await?;
+
+    // Reconnect updated clusters with their new FEs / credentials
+    if !response.dry_run {
+        let updated = response
+            .changes
+            .iter()
+            .filter(|c| c.kind == ConfigItemKind::Cluster && c.action == ConfigAction::Update);
+        for id in updated.filter_map(|c| c.id) {
+            FeRegistry::global().forget_cluster(id);
+            state.mysql_pool_manager.remove_pool(id).await;
+        }
+    }
+
+    Ok(Json(response))
+}
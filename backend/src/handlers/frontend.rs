@@ -1,7 +1,6 @@
-use axum::{Json, extract::State};
-use std::sync::Arc;
+use axum::Json;
 
-use crate::AppState;
+use crate::middleware::SelectedCluster;
 use crate::models::Frontend;
 use crate::services::StarRocksClient;
 use crate::utils::ApiResult;
@@ -19,9 +18,10 @@ use crate::utils::ApiResult;
     ),
     tag = "Frontends"
 )]
-pub async fn list_frontends(State(state): State<Arc<AppState>>) -> ApiResult<Json<Vec<Frontend>>> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
-    let client = StarRocksClient::new(cluster);
+pub async fn list_frontends(
+    SelectedCluster(cluster): SelectedCluster,
+) -> ApiResult<Json<Vec<Frontend>>> {
+    let client = StarRocksClient::new(cluster)?;
     let frontends = client.get_frontends().await?;
     Ok(Json(frontends))
 }
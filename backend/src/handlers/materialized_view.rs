@@ -9,6 +9,7 @@ use serde_json::json;
 use std::sync::Arc;
 
 use crate::AppState;
+use crate::middleware::SelectedCluster;
 use crate::models::{
     AlterMaterializedViewRequest, CreateMaterializedViewRequest, MaterializedView,
     MaterializedViewDDL, RefreshMaterializedViewRequest,
@@ -51,10 +52,9 @@ pub struct CancelRefreshParams {
 )]
 pub async fn list_materialized_views(
     State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
     Query(params): Query<ListMVParams>,
 ) -> ApiResult<Json<Vec<MaterializedView>>> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
-
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
     let mysql_client = MySQLClient::from_pool(pool);
     let mv_service = MaterializedViewService::new(mysql_client);
@@ -82,10 +82,9 @@ pub async fn list_materialized_views(
 )]
 pub async fn get_materialized_view(
     State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
     Path(mv_name): Path<String>,
 ) -> ApiResult<Json<MaterializedView>> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
-
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
     let mysql_client = MySQLClient::from_pool(pool);
     let mv_service = MaterializedViewService::new(mysql_client);
@@ -110,10 +109,9 @@ pub async fn get_materialized_view(
 )]
 pub async fn get_materialized_view_ddl(
     State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
     Path(mv_name): Path<String>,
 ) -> ApiResult<Json<MaterializedViewDDL>> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
-
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
     let mysql_client = MySQLClient::from_pool(pool);
     let mv_service = MaterializedViewService::new(mysql_client);
@@ -136,10 +134,9 @@ pub async fn get_materialized_view_ddl(
 )]
 pub async fn create_materialized_view(
     State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
     Json(request): Json<CreateMaterializedViewRequest>,
 ) -> ApiResult<impl IntoResponse> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
-
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
     let mysql_client = MySQLClient::from_pool(pool);
     let mv_service = MaterializedViewService::new(mysql_client);
@@ -166,11 +163,10 @@ pub async fn create_materialized_view(
 )]
 pub async fn delete_materialized_view(
     State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
     Path(mv_name): Path<String>,
     Query(params): Query<DeleteMVParams>,
 ) -> ApiResult<impl IntoResponse> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
-
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
     let mysql_client = MySQLClient::from_pool(pool);
     let mv_service = MaterializedViewService::new(mysql_client);
@@ -195,11 +191,10 @@ pub async fn delete_materialized_view(
 )]
 pub async fn refresh_materialized_view(
     State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
     Path(mv_name): Path<String>,
     Json(request): Json<RefreshMaterializedViewRequest>,
 ) -> ApiResult<impl IntoResponse> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
-
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
     let mysql_client = MySQLClient::from_pool(pool);
     let mv_service = MaterializedViewService::new(mysql_client);
@@ -232,11 +227,10 @@ pub async fn refresh_materialized_view(
 )]
 pub async fn cancel_refresh_materialized_view(
     State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
     Path(mv_name): Path<String>,
     Query(params): Query<CancelRefreshParams>,
 ) -> ApiResult<impl IntoResponse> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
-
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
     let mysql_client = MySQLClient::from_pool(pool);
     let mv_service = MaterializedViewService::new(mysql_client);
@@ -261,11 +255,10 @@ pub async fn cancel_refresh_materialized_view(
 )]
 pub async fn alter_materialized_view(
     State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
     Path(mv_name): Path<String>,
     Json(request): Json<AlterMaterializedViewRequest>,
 ) -> ApiResult<impl IntoResponse> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
-
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
     let mysql_client = MySQLClient::from_pool(pool);
     let mv_service = MaterializedViewService::new(mysql_client);
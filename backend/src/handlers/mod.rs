@@ -1,15 +1,23 @@
+pub mod audit;
 pub mod auth;
 pub mod backend;
 pub mod cluster;
+pub mod cluster_group;
+pub mod config_bundle;
 pub mod frontend;
 pub mod materialized_view;
 pub mod overview;
+pub mod permission;
 pub mod profile;
 pub mod query;
 pub mod query_history;
+pub mod query_job;
+pub mod query_plan;
 pub mod query_profile;
 pub mod sessions;
 pub mod system;
 pub mod system_function;
 pub mod system_management;
+pub mod topology;
+pub mod user;
 pub mod variables;
@@ -10,6 +10,7 @@ use serde::Deserialize;
 use std::sync::Arc;
 
 use crate::AppState;
+use crate::middleware::SelectedCluster;
 use crate::services::{
     CapacityPrediction, ClusterOverview, CompactionDetailStats, DataStatistics,
     ExtendedClusterOverview, HealthCard, PerformanceTrends, ResourceTrends, TimeRange,
@@ -63,12 +64,11 @@ pub struct TrendQueryParams {
 )]
 pub async fn get_cluster_overview(
     State(state): State<Arc<AppState>>,
+    SelectedCluster(active_cluster): SelectedCluster,
     Query(params): Query<OverviewQueryParams>,
 ) -> ApiResult<Json<ClusterOverview>> {
     tracing::debug!("GET /api/clusters/overview?time_range={:?}", params.time_range);
 
-    // Get the active cluster
-    let active_cluster = state.cluster_service.get_active_cluster().await?;
     let cluster_id = active_cluster.id;
 
     let overview = state
@@ -101,8 +101,8 @@ pub async fn get_cluster_overview(
 )]
 pub async fn get_health_cards(
     State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
 ) -> ApiResult<Json<Vec<HealthCard>>> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
     tracing::debug!("GET /api/clusters/overview/health");
 
     let cards = state.overview_service.get_health_cards(cluster.id).await?;
@@ -134,9 +134,9 @@ pub async fn get_health_cards(
 )]
 pub async fn get_performance_trends(
     State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
     Query(params): Query<TrendQueryParams>,
 ) -> ApiResult<Json<PerformanceTrends>> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
     tracing::debug!("GET /api/clusters/overview/performance?time_range={:?}", params.time_range);
 
     let trends = state
@@ -171,9 +171,9 @@ pub async fn get_performance_trends(
 )]
 pub async fn get_resource_trends(
     State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
     Query(params): Query<TrendQueryParams>,
 ) -> ApiResult<Json<ResourceTrends>> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
     tracing::debug!("GET /api/clusters/overview/resources?time_range={:?}", params.time_range);
 
     let trends = state
@@ -208,8 +208,8 @@ pub async fn get_resource_trends(
 )]
 pub async fn get_data_statistics(
     State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
 ) -> ApiResult<Json<DataStatistics>> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
     tracing::debug!("GET /api/clusters/overview/data-stats");
 
     let stats = state
@@ -238,8 +238,8 @@ pub async fn get_data_statistics(
 )]
 pub async fn get_capacity_prediction(
     State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
 ) -> ApiResult<Json<CapacityPrediction>> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
     tracing::debug!("GET /api/clusters/overview/capacity-prediction");
 
     let prediction = state.overview_service.predict_capacity(cluster.id).await?;
@@ -276,9 +276,9 @@ pub async fn get_capacity_prediction(
 )]
 pub async fn get_extended_cluster_overview(
     State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
     Query(params): Query<OverviewQueryParams>,
 ) -> ApiResult<Json<ExtendedClusterOverview>> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
     tracing::debug!("GET /api/clusters/overview/extended?time_range={:?}", params.time_range);
 
     let overview = state
@@ -313,10 +313,9 @@ pub async fn get_extended_cluster_overview(
 )]
 pub async fn get_compaction_detail_stats(
     State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
     Query(params): Query<TrendQueryParams>,
 ) -> ApiResult<Json<CompactionDetailStats>> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
-
     // Convert TimeRange enum to string for the service method
     let time_range_str = match params.time_range {
         TimeRange::Hours1 => "1h",
@@ -0,0 +1,134 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use std::sync::Arc;
+
+use crate::AppState;
+use crate::models::{
+    ClusterRoleGrant, GrantClusterRoleRequest, UpdateUserRoleRequest, UserPermissions,
+};
+use crate::utils::ApiResult;
+
+// Get global role and per-cluster grants of a user
+#[utoipa::path(
+    get,
+    path = "/api/users/{id}/permissions",
+    params(
+        ("id" = i64, Path, description = "User ID")
+    ),
+    responses(
+        (status = 200, description = "User permissions", body = UserPermissions),
+        (status = 403, description = "Admin role required"),
+        (status = 404, description = "User not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Permissions"
+)]
+pub async fn get_user_permissions(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<UserPermissions>> {
+    let permissions = state.permission_service.get_user_permissions(id).await?;
+    Ok(Json(permissions))
+}
+
+// Change the global role of a user
+#[utoipa::path(
+    put,
+    path = "/api/users/{id}/role",
+    params(
+        ("id" = i64, Path, description = "User ID")
+    ),
+    request_body = UpdateUserRoleRequest,
+    responses(
+        (status = 200, description = "Role updated", body = UserPermissions),
+        (status = 400, description = "Would demote the last active admin"),
+        (status = 403, description = "Admin role required"),
+        (status = 404, description = "User not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Permissions"
+)]
+pub async fn update_user_role(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<UpdateUserRoleRequest>,
+) -> ApiResult<Json<UserPermissions>> {
+    tracing::info!("Setting global role of user {} to {}", id, req.role);
+
+    state.permission_service.set_user_role(id, req.role).await?;
+    let permissions = state.permission_service.get_user_permissions(id).await?;
+
+    Ok(Json(permissions))
+}
+
+// Grant a role on a single cluster
+#[utoipa::path(
+    put,
+    path = "/api/users/{id}/cluster-roles/{cluster_id}",
+    params(
+        ("id" = i64, Path, description = "User ID"),
+        ("cluster_id" = i64, Path, description = "Cluster ID")
+    ),
+    request_body = GrantClusterRoleRequest,
+    responses(
+        (status = 200, description = "Role granted", body = ClusterRoleGrant),
+        (status = 403, description = "Admin role required"),
+        (status = 404, description = "User or cluster not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Permissions"
+)]
+pub async fn grant_cluster_role(
+    State(state): State<Arc<AppState>>,
+    Path((id, cluster_id)): Path<(i64, i64)>,
+    Json(req): Json<GrantClusterRoleRequest>,
+) -> ApiResult<Json<ClusterRoleGrant>> {
+    tracing::info!("Granting {} on cluster {} to user {}", req.role, cluster_id, id);
+
+    let grant = state
+        .permission_service
+        .grant_cluster_role(id, cluster_id, req.role)
+        .await?;
+
+    Ok(Json(grant))
+}
+
+// Remove a per-cluster grant
+#[utoipa::path(
+    delete,
+    path = "/api/users/{id}/cluster-roles/{cluster_id}",
+    params(
+        ("id" = i64, Path, description = "User ID"),
+        ("cluster_id" = i64, Path, description = "Cluster ID")
+    ),
+    responses(
+        (status = 200, description = "Grant removed"),
+        (status = 403, description = "Admin role required"),
+        (status = 404, description = "Grant not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Permissions"
+)]
+pub async fn revoke_cluster_role(
+    State(state): State<Arc<AppState>>,
+    Path((id, cluster_id)): Path<(i64, i64)>,
+) -> ApiResult<Json<serde_json::Value>> {
+    tracing::info!("Revoking cluster {} role of user {}", cluster_id, id);
+
+    state
+        .permission_service
+        .revoke_cluster_role(id, cluster_id)
+        .await?;
+
+    Ok(Json(serde_json::json!({"message": "Cluster role revoked successfully"})))
+}
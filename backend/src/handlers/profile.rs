@@ -4,9 +4,11 @@ use axum::{
 };
 use std::sync::Arc;
 
+use crate::middleware::SelectedCluster;
 use crate::models::{ProfileDetail, ProfileListItem};
 use crate::services::MySQLClient;
 use crate::utils::ApiResult;
+use crate::utils::sql;
 
 // List all query profiles for a cluster
 #[utoipa::path(
@@ -23,9 +25,8 @@ use crate::utils::ApiResult;
 )]
 pub async fn list_profiles(
     State(state): State<Arc<crate::AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
 ) -> ApiResult<Json<Vec<ProfileListItem>>> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
-
     tracing::info!("Fetching profile list for cluster {}", cluster.id);
 
     // Get connection pool and execute SHOW PROFILELIST
@@ -77,17 +78,16 @@ pub async fn list_profiles(
 )]
 pub async fn get_profile(
     State(state): State<Arc<crate::AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
     Path(query_id): Path<String>,
 ) -> ApiResult<Json<ProfileDetail>> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
-
     tracing::info!("Fetching profile detail for query {} in cluster {}", query_id, cluster.id);
 
     // Get connection pool and execute SELECT get_query_profile()
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
     let mysql_client = MySQLClient::from_pool(pool);
 
-    let sql = format!("SELECT get_query_profile('{}')", query_id);
+    let sql = sql::query_profile(&query_id)?;
     let (_, rows) = mysql_client.query_raw(&sql, None, None).await?;
 
     // Extract profile content from result
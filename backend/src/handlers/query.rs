@@ -1,18 +1,31 @@
 use axum::{
-    Json,
+    Extension, Json,
+    body::Body,
     extract::{Path, State},
-    http::StatusCode,
-    response::IntoResponse,
+    http::{HeaderMap, StatusCode, header},
+    response::{
+        IntoResponse, Response,
+        sse::{Event, KeepAlive, Sse},
+    },
 };
 use serde_json::json;
+use std::convert::Infallible;
 use std::sync::Arc;
 use std::time::Instant;
+use tokio_stream::{StreamExt, wrappers::ReceiverStream};
 
 use crate::AppState;
-use crate::models::{CatalogWithDatabases, CatalogsWithDatabasesResponse, Query, QueryExecuteRequest, QueryExecuteResponse};
+use crate::middleware::SelectedCluster;
+use crate::models::{
+    CatalogWithDatabases, CatalogsWithDatabasesResponse, Cluster, CurrentUser, ExportFormat, Query,
+    QueryExecuteRequest, QueryExecuteResponse, QueryStreamEvent, QueryStreamFormat,
+    QueryStreamParams, QueryStreamRequest, ResultColumn, SqlScriptRequest, SqlScriptResponse,
+};
 use crate::services::mysql_client::MySQLClient;
-use crate::services::StarRocksClient;
-use crate::utils::ApiResult;
+use crate::services::result_export::ResultExport;
+use crate::services::{SqlGuard, StarRocksClient};
+use crate::utils::sql::{self, apply_query_limit};
+use crate::utils::{ApiError, ApiResult};
 
 // Get list of catalogs using MySQL client
 #[utoipa::path(
@@ -27,9 +40,10 @@ use crate::utils::ApiResult;
     ),
     tag = "Queries"
 )]
-pub async fn list_catalogs(State(state): State<Arc<AppState>>) -> ApiResult<Json<Vec<String>>> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
-    
+pub async fn list_catalogs(
+    State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
+) -> ApiResult<Json<Vec<String>>> {
     // Use MySQL client to execute SHOW CATALOGS
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
     let mysql_client = MySQLClient::from_pool(pool);
@@ -68,10 +82,9 @@ pub async fn list_catalogs(State(state): State<Arc<AppState>>) -> ApiResult<Json
 )]
 pub async fn list_databases(
     State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
     axum::extract::Query(params): axum::extract::Query<std::collections::HashMap<String, String>>,
 ) -> ApiResult<Json<Vec<String>>> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
-    
     // Use MySQL client
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
     let mysql_client = MySQLClient::from_pool(pool);
@@ -81,8 +94,7 @@ pub async fn list_databases(
         // Skip switching to default_catalog - it's already active by default
         if !catalog_name.is_empty() && catalog_name != "default_catalog" {
             // First switch to the catalog, then show databases
-            // Try without backticks - StarRocks may not support them for catalog names
-            let use_catalog_sql = format!("USE CATALOG {}", catalog_name);
+            let use_catalog_sql = sql::use_catalog(catalog_name)?;
             if let Err(e) = mysql_client.execute(&use_catalog_sql).await {
                 tracing::warn!("Failed to switch to catalog {}: {}", catalog_name, e);
                 // Continue anyway, might be using default catalog
@@ -125,9 +137,8 @@ pub async fn list_databases(
 )]
 pub async fn list_catalogs_with_databases(
     State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
 ) -> ApiResult<Json<CatalogsWithDatabasesResponse>> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
-    
     // Use MySQL client
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
     let mysql_client = MySQLClient::from_pool(pool);
@@ -154,9 +165,12 @@ pub async fn list_catalogs_with_databases(
     for catalog_name in &catalog_names {
         // Skip switching to default_catalog - it's already active by default
         if catalog_name != "default_catalog" {
-            // Switch to catalog (without backticks - StarRocks may not support them for catalog names)
-            let use_catalog_sql = format!("USE CATALOG {}", catalog_name);
-            if let Err(e) = mysql_client.execute(&use_catalog_sql).await {
+            // Switch to catalog
+            let switched = match sql::use_catalog(catalog_name) {
+                Ok(use_catalog_sql) => mysql_client.execute(&use_catalog_sql).await.map(|_| ()),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = switched {
                 tracing::warn!("Failed to switch to catalog {}: {}", catalog_name, e);
                 catalogs.push(CatalogWithDatabases {
                     catalog: catalog_name.clone(),
@@ -216,13 +230,14 @@ pub async fn list_catalogs_with_databases(
     ),
     tag = "Queries"
 )]
-pub async fn list_queries(State(state): State<Arc<AppState>>) -> ApiResult<Json<Vec<Query>>> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
-    
+pub async fn list_queries(
+    State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
+) -> ApiResult<Json<Vec<Query>>> {
     tracing::info!("[list_queries] Fetching running queries for cluster: {} (ID: {})", cluster.name, cluster.id);
     
     // Try HTTP API first
-    let client = StarRocksClient::new(cluster.clone());
+    let client = StarRocksClient::new(cluster.clone())?;
     match client.get_queries().await {
         Ok(queries) if !queries.is_empty() => {
             tracing::info!("[list_queries] Successfully retrieved {} running queries via HTTP API", queries.len());
@@ -351,30 +366,36 @@ pub async fn list_queries(State(state): State<Arc<AppState>>) -> ApiResult<Json<
 )]
 pub async fn kill_query(
     State(state): State<Arc<crate::AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
     Path(query_id): Path<String>,
 ) -> ApiResult<impl IntoResponse> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
-
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
     let mysql_client = MySQLClient::from_pool(pool);
 
     // Execute KILL QUERY
-    let sql = format!("KILL QUERY '{}'", query_id);
-    mysql_client.execute(&sql).await?;
+    mysql_client.execute(&sql::kill_query(&query_id)?).await?;
 
     Ok((StatusCode::OK, Json(json!({ "message": "Query killed successfully" }))))
 }
 
 // Execute SQL query
-// If database is provided, will execute USE database before the SQL query
+// If database is provided, will execute USE database before the SQL query.
+// With `export` set the full result is downloaded as a file instead
 #[utoipa::path(
     post,
     path = "/api/clusters/queries/execute",
     request_body = QueryExecuteRequest,
     responses(
         (status = 200, description = "Query executed successfully", body = QueryExecuteResponse),
+        (status = 200, description = "Result file when `export` is set", content_type = [
+            "text/csv", "text/tab-separated-values", "application/x-ndjson",
+            "application/vnd.apache.parquet",
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+        ]),
         (status = 400, description = "Invalid SQL or query error"),
+        (status = 403, description = "Statement not allowed for the role or cluster", body = crate::models::SqlViolation),
         (status = 404, description = "No active cluster found"),
+        (status = 428, description = "Destructive statement needs the X-SQL-Confirmation header", body = crate::models::SqlViolation),
         (status = 500, description = "Internal server error")
     ),
     security(
@@ -384,19 +405,77 @@ pub async fn kill_query(
 )]
 pub async fn execute_sql(
     State(state): State<Arc<crate::AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
+    Extension(user): Extension<CurrentUser>,
+    headers: HeaderMap,
     Json(request): Json<QueryExecuteRequest>,
-) -> ApiResult<Json<QueryExecuteResponse>> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
+) -> ApiResult<Response> {
+    state
+        .sql_guard
+        .check(&user, &cluster, &request.sql, SqlGuard::confirmation(&headers))
+        .await?;
+
+    if let Some(format) = request.export {
+        return export_sql(&state, &cluster, request, format).await;
+    }
+
+    let original_sql = &request.sql;
+    let sql = apply_query_limit(original_sql, request.limit.unwrap_or(1000));
+
+    let start = Instant::now();
+
+    // Reuse a cached result when the request asks for it
+    let query_result = state
+        .query_cache
+        .get_or_load(
+            cluster.id,
+            request.catalog.as_deref(),
+            request.database.as_deref(),
+            &sql,
+            request.cache_ttl_secs.unwrap_or(0),
+            || run_query(&state, &cluster, &request, &sql),
+        )
+        .await;
+    state
+        .query_cache
+        .invalidate_if_write(cluster.id, original_sql);
+
+    let execution_time_ms = start.elapsed().as_millis();
 
+    match query_result {
+        Ok(result) => {
+            let (columns, data_rows) = result.value;
+            let row_count = data_rows.len();
+
+            Ok(Json(QueryExecuteResponse {
+                columns,
+                rows: data_rows,
+                row_count,
+                execution_time_ms,
+                cached_at: result.cached_at,
+            })
+            .into_response())
+        },
+        Err(e) => Err(e),
+    }
+}
+
+// Run the SQL of an execute request in its catalog and database
+async fn run_query(
+    state: &AppState,
+    cluster: &Cluster,
+    request: &QueryExecuteRequest,
+    sql: &str,
+) -> ApiResult<(Vec<ResultColumn>, Vec<Vec<serde_json::Value>>)> {
     // Use pool manager to get cached pool (avoid intermittent failures from creating new pools)
-    let pool: mysql_async::Pool = state.mysql_pool_manager.get_pool(&cluster).await?;
+    let pool = state.mysql_pool_manager.get_pool(cluster).await?;
     let mysql_client = MySQLClient::from_pool(pool);
 
     // If catalog is specified, switch to it first
     if let Some(ref cat) = request.catalog {
         // Skip switching to default_catalog - it's already active by default
         if !cat.is_empty() && cat != "default_catalog" {
-            let use_catalog_sql = format!("USE CATALOG `{}`", cat);
+            let use_catalog_sql = sql::use_catalog(cat)?;
             tracing::debug!("Executing USE CATALOG: {}", use_catalog_sql);
             if let Err(e) = mysql_client.execute(&use_catalog_sql).await {
                 tracing::warn!("Failed to execute USE CATALOG {}: {}", cat, e);
@@ -408,7 +487,7 @@ pub async fn execute_sql(
     // If database is specified, execute USE database
     if let Some(ref db) = request.database {
         if !db.is_empty() {
-            let use_db_sql = format!("USE `{}`", db);
+            let use_db_sql = sql::use_database(db)?;
             tracing::debug!("Executing USE DATABASE: {}", use_db_sql);
             if let Err(e) = mysql_client.execute(&use_db_sql).await {
                 tracing::warn!("Failed to execute USE database {}: {}", db, e);
@@ -417,52 +496,176 @@ pub async fn execute_sql(
         }
     }
 
-    let original_sql = &request.sql;
-    let sql = apply_query_limit(original_sql, request.limit.unwrap_or(1000));
-
-    let start = Instant::now();
-
     // Execute query with catalog and database context
-    let query_result = mysql_client.query_raw(
-        &sql,
-        request.catalog.as_deref(),
-        request.database.as_deref(),
-    ).await;
+    mysql_client
+        .query_typed(sql, request.catalog.as_deref(), request.database.as_deref())
+        .await
+}
 
-    let execution_time_ms = start.elapsed().as_millis();
+// Download the whole result of a query as a file
+// Rows are streamed from StarRocks into the encoder, bounded by the
+// `[query_stream]` caps; a failed or truncated query breaks off the download
+async fn export_sql(
+    state: &AppState,
+    cluster: &Cluster,
+    request: QueryExecuteRequest,
+    format: ExportFormat,
+) -> ApiResult<Response> {
+    tracing::info!("Exporting query result on cluster {} as {:?}", cluster.name, format);
+    let stream_request = QueryStreamRequest {
+        sql: request.sql,
+        catalog: request.catalog,
+        database: request.database,
+        max_rows: None,
+        max_bytes: None,
+    };
+    let mut events = state
+        .query_stream_service
+        .start(cluster, stream_request)
+        .await?;
 
-    match query_result {
-        Ok((columns, data_rows)) => {
-            let row_count = data_rows.len();
-
-            Ok(Json(QueryExecuteResponse {
-                columns,
-                rows: data_rows,
-                row_count,
-                execution_time_ms,
-            }))
-        },
-        Err(e) => Err(e),
-    }
+    // Report a failing statement as an ordinary error rather than a broken download
+    let first = match events.recv().await {
+        Some(QueryStreamEvent::Error { message }) => return Err(ApiError::internal_error(message)),
+        first => first,
+    };
+    let mut export = ResultExport::new(format);
+    let chunks = tokio_stream::iter(first)
+        .chain(ReceiverStream::new(events))
+        .map(move |event| export.next(event))
+        .filter(|chunk| !matches!(chunk, Ok(bytes) if bytes.is_empty()));
+    let filename = format!(
+        "query-result-{}.{}",
+        chrono::Utc::now().format("%Y%m%d-%H%M%S"),
+        format.extension()
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+            (header::HeaderName::from_static("x-accel-buffering"), "no".to_string()),
+        ],
+        Body::from_stream(chunks),
+    )
+        .into_response())
 }
 
-fn apply_query_limit(sql: &str, limit: i32) -> String {
-    let sql_upper = sql.trim().to_uppercase();
+// Stream the result of a SQL query row by row
+// Rows are sent as they come from StarRocks; the query is killed when a cap is
+// reached or the client disconnects
+#[utoipa::path(
+    post,
+    path = "/api/clusters/queries/stream",
+    params(
+        ("format" = Option<String>, Query, description = "`ndjson` (default) or `sse`; `Accept: text/event-stream` selects SSE")
+    ),
+    request_body = QueryStreamRequest,
+    responses(
+        (status = 200, description = "`columns`, `row`... and `end` or `error` events as NDJSON lines or SSE", body = QueryStreamEvent),
+        (status = 404, description = "No active cluster found"),
+        (status = 400, description = "No connection to the cluster or unknown database"),
+        (status = 403, description = "Statement not allowed for the role or cluster", body = crate::models::SqlViolation),
+        (status = 428, description = "Destructive statement needs the X-SQL-Confirmation header", body = crate::models::SqlViolation)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Queries"
+)]
+pub async fn stream_sql(
+    State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
+    Extension(user): Extension<CurrentUser>,
+    axum::extract::Query(params): axum::extract::Query<QueryStreamParams>,
+    headers: HeaderMap,
+    Json(request): Json<QueryStreamRequest>,
+) -> ApiResult<Response> {
+    state
+        .sql_guard
+        .check(&user, &cluster, &request.sql, SqlGuard::confirmation(&headers))
+        .await?;
+    let format = params.format.unwrap_or_else(|| {
+        let accept = headers
+            .get(header::ACCEPT)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+        if accept.contains("text/event-stream") {
+            QueryStreamFormat::Sse
+        } else {
+            QueryStreamFormat::Ndjson
+        }
+    });
+    tracing::info!("Streaming query on cluster {} as {:?}", cluster.name, format);
 
-    if sql_upper.contains("LIMIT") {
-        return sql.to_string();
-    }
+    state
+        .query_cache
+        .invalidate_if_write(cluster.id, &request.sql);
+    let events = ReceiverStream::new(state.query_stream_service.start(&cluster, request).await?);
 
-    if sql_upper.starts_with("SELECT") {
-        if sql_upper.contains("GET_QUERY_PROFILE")
-            || sql_upper.contains("SHOW_PROFILE")
-            || sql_upper.contains("EXPLAIN")
-        {
-            return sql.to_string();
-        }
+    Ok(match format {
+        QueryStreamFormat::Ndjson => {
+            let lines = events.map(|event| {
+                let mut line = serde_json::to_vec(&event).unwrap_or_default();
+                line.push(b'\n');
+                Ok::<_, Infallible>(line)
+            });
+            (
+                [
+                    (header::CONTENT_TYPE, "application/x-ndjson"),
+                    // Keep reverse proxies from buffering the stream
+                    (header::HeaderName::from_static("x-accel-buffering"), "no"),
+                ],
+                Body::from_stream(lines),
+            )
+                .into_response()
+        },
+        QueryStreamFormat::Sse => {
+            let events = events.map(|event| Event::default().event(event.name()).json_data(&event));
+            Sse::new(events)
+                .keep_alive(KeepAlive::default())
+                .into_response()
+        },
+    })
+}
 
-        format!("{} LIMIT {}", sql.trim().trim_end_matches(';'), limit)
-    } else {
-        sql.to_string()
-    }
+// Run a script of `;`-separated statements on one connection
+// Statements run in order; each reports its rows or affected rows, or its error
+#[utoipa::path(
+    post,
+    path = "/api/clusters/queries/script",
+    request_body = SqlScriptRequest,
+    responses(
+        (status = 200, description = "Result of every statement; failed statements are reported per statement", body = SqlScriptResponse),
+        (status = 400, description = "Empty or unparsable script, or transaction statements in a transactional script"),
+        (status = 403, description = "A statement is not allowed for the role or cluster", body = crate::models::SqlViolation),
+        (status = 404, description = "No active cluster found"),
+        (status = 428, description = "Destructive statement needs the X-SQL-Confirmation header", body = crate::models::SqlViolation)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Queries"
+)]
+pub async fn execute_script(
+    State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
+    Extension(user): Extension<CurrentUser>,
+    headers: HeaderMap,
+    Json(request): Json<SqlScriptRequest>,
+) -> ApiResult<Json<SqlScriptResponse>> {
+    state
+        .sql_guard
+        .check(&user, &cluster, &request.sql, SqlGuard::confirmation(&headers))
+        .await?;
+    tracing::info!(
+        "Running SQL script on cluster {} (transaction: {}, stop on error: {})",
+        cluster.name,
+        request.transaction,
+        request.stop_on_error
+    );
+    let response = state.sql_script_service.run(&cluster, &request).await;
+    state
+        .query_cache
+        .invalidate_if_write(cluster.id, &request.sql);
+    Ok(Json(response?))
 }
@@ -2,6 +2,7 @@ use axum::{Json, extract::State};
 use serde::Deserialize;
 use std::sync::Arc;
 
+use crate::middleware::SelectedCluster;
 use crate::models::starrocks::{QueryHistoryItem, QueryHistoryResponse};
 use crate::services::mysql_client::MySQLClient;
 use crate::utils::error::ApiResult;
@@ -33,10 +34,9 @@ fn default_offset() -> i64 {
 )]
 pub async fn list_query_history(
     State(state): State<Arc<crate::AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
     axum::extract::Query(params): axum::extract::Query<HistoryQueryParams>,
 ) -> ApiResult<Json<QueryHistoryResponse>> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
-
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
     let mysql = MySQLClient::from_pool(pool);
 
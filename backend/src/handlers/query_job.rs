@@ -0,0 +1,190 @@
+use axum::{
+    Json,
+    extract::{Extension, Path, Query, State},
+    http::{HeaderMap, StatusCode},
+};
+use std::sync::Arc;
+
+use crate::AppState;
+use crate::middleware::SelectedCluster;
+use crate::models::{
+    Cluster, CurrentUser, QueryJob, QueryJobResults, QueryJobResultsParams, SubmitQueryJobRequest,
+};
+use crate::services::SqlGuard;
+use crate::services::query_job_service::visible_owner;
+use crate::utils::{ApiError, ApiResult};
+
+// Owner whose jobs the user may see on the cluster, `None` for admins of the cluster
+async fn owner_filter(
+    state: &AppState,
+    cluster: &Cluster,
+    user: &CurrentUser,
+) -> ApiResult<Option<i64>> {
+    let role = state
+        .permission_service
+        .effective_role(user, cluster.id)
+        .await?;
+    Ok(visible_owner(user, role))
+}
+
+// Job of the selected cluster that the user may see: their own, or any for admins
+async fn visible_job(
+    state: &AppState,
+    cluster: &Cluster,
+    user: &CurrentUser,
+    job_id: i64,
+) -> ApiResult<QueryJob> {
+    let job = state.query_job_service.get(job_id).await?;
+    let owner = owner_filter(state, cluster, user).await?;
+    if job.cluster_id != cluster.id || owner.is_some_and(|id| id != job.user_id) {
+        return Err(ApiError::not_found(format!("Query job {} not found", job_id)));
+    }
+    Ok(job)
+}
+
+// Run a query in the background
+#[utoipa::path(
+    post,
+    path = "/api/clusters/queries/jobs",
+    request_body = SubmitQueryJobRequest,
+    responses(
+        (status = 202, description = "Job started", body = QueryJob),
+        (status = 400, description = "Empty SQL or too many running jobs"),
+        (status = 403, description = "Statement not allowed for the role or cluster", body = crate::models::SqlViolation),
+        (status = 404, description = "No active cluster found"),
+        (status = 428, description = "Destructive statement needs the X-SQL-Confirmation header", body = crate::models::SqlViolation)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Queries"
+)]
+pub async fn submit_query_job(
+    State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
+    Extension(user): Extension<CurrentUser>,
+    headers: HeaderMap,
+    Json(request): Json<SubmitQueryJobRequest>,
+) -> ApiResult<(StatusCode, Json<QueryJob>)> {
+    state
+        .sql_guard
+        .check(&user, &cluster, &request.sql, SqlGuard::confirmation(&headers))
+        .await?;
+    state
+        .query_cache
+        .invalidate_if_write(cluster.id, &request.sql);
+    let job = state
+        .query_job_service
+        .submit(&cluster, user.id, request)
+        .await?;
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+// List query jobs on the cluster, newest first
+// Admins of the cluster see the jobs of all users
+#[utoipa::path(
+    get,
+    path = "/api/clusters/queries/jobs",
+    responses(
+        (status = 200, description = "Query jobs that have not expired", body = Vec<QueryJob>),
+        (status = 404, description = "No active cluster found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Queries"
+)]
+pub async fn list_query_jobs(
+    State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
+    Extension(user): Extension<CurrentUser>,
+) -> ApiResult<Json<Vec<QueryJob>>> {
+    let user_id = owner_filter(&state, &cluster, &user).await?;
+    Ok(Json(state.query_job_service.list(cluster.id, user_id).await?))
+}
+
+// Get the status of a query job
+#[utoipa::path(
+    get,
+    path = "/api/clusters/queries/jobs/{job_id}",
+    params(
+        ("job_id" = i64, Path, description = "Query job ID")
+    ),
+    responses(
+        (status = 200, description = "Query job", body = QueryJob),
+        (status = 404, description = "Job not found or expired")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Queries"
+)]
+pub async fn get_query_job(
+    State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
+    Extension(user): Extension<CurrentUser>,
+    Path(job_id): Path<i64>,
+) -> ApiResult<Json<QueryJob>> {
+    Ok(Json(visible_job(&state, &cluster, &user, job_id).await?))
+}
+
+// Get a page of the rows of a query job, also while it is running
+#[utoipa::path(
+    get,
+    path = "/api/clusters/queries/jobs/{job_id}/results",
+    params(
+        ("job_id" = i64, Path, description = "Query job ID"),
+        ("offset" = Option<i64>, Query, description = "Index of the first row (default 0)"),
+        ("limit" = Option<i64>, Query, description = "Rows per page (default 1000, at most 10000)")
+    ),
+    responses(
+        (status = 200, description = "Job status, columns and rows", body = QueryJobResults),
+        (status = 404, description = "Job not found or expired")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Queries"
+)]
+pub async fn get_query_job_results(
+    State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
+    Extension(user): Extension<CurrentUser>,
+    Path(job_id): Path<i64>,
+    Query(params): Query<QueryJobResultsParams>,
+) -> ApiResult<Json<QueryJobResults>> {
+    let job = visible_job(&state, &cluster, &user, job_id).await?;
+    Ok(Json(
+        state
+            .query_job_service
+            .results(job, params.offset, params.limit)
+            .await?,
+    ))
+}
+
+// Cancel a running query job, killing its query
+#[utoipa::path(
+    post,
+    path = "/api/clusters/queries/jobs/{job_id}/cancel",
+    params(
+        ("job_id" = i64, Path, description = "Query job ID")
+    ),
+    responses(
+        (status = 200, description = "Cancelled job; rows stored so far are kept", body = QueryJob),
+        (status = 400, description = "Job is not running"),
+        (status = 404, description = "Job not found or expired")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Queries"
+)]
+pub async fn cancel_query_job(
+    State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
+    Extension(user): Extension<CurrentUser>,
+    Path(job_id): Path<i64>,
+) -> ApiResult<Json<QueryJob>> {
+    let job = visible_job(&state, &cluster, &user, job_id).await?;
+    Ok(Json(state.query_job_service.cancel(job).await?))
+}
@@ -0,0 +1,47 @@
+use axum::{
+    Json,
+    extract::{Extension, State},
+    http::HeaderMap,
+};
+use std::sync::Arc;
+
+use crate::AppState;
+use crate::middleware::SelectedCluster;
+use crate::models::{CurrentUser, ExplainRequest, QueryPlan};
+use crate::services::SqlGuard;
+use crate::services::query_plan_service::explained_query;
+use crate::utils::ApiResult;
+
+// Explain a query and return its plan as fragments of operator trees
+// With `analyze` the query is executed, so the SQL guard sees it like any other run
+#[utoipa::path(
+    post,
+    path = "/api/clusters/queries/explain",
+    request_body = ExplainRequest,
+    responses(
+        (status = 200, description = "Parsed plan with warnings", body = QueryPlan),
+        (status = 400, description = "Not a single SELECT, or StarRocks rejected the query"),
+        (status = 403, description = "Queries not allowed for the role or cluster", body = crate::models::SqlViolation),
+        (status = 404, description = "No active cluster found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Queries"
+)]
+pub async fn explain_query(
+    State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
+    Extension(user): Extension<CurrentUser>,
+    headers: HeaderMap,
+    Json(request): Json<ExplainRequest>,
+) -> ApiResult<Json<QueryPlan>> {
+    // Reject anything but a SELECT before the guard asks to confirm it
+    explained_query(&request.sql)?;
+    state
+        .sql_guard
+        .check(&user, &cluster, &request.sql, SqlGuard::confirmation(&headers))
+        .await?;
+    let plan = state.query_plan_service.explain(&cluster, &request).await?;
+    Ok(Json(plan))
+}
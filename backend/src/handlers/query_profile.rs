@@ -1,11 +1,7 @@
-use axum::{
-    Json,
-    extract::{Path, State},
-    response::IntoResponse,
-};
-use std::sync::Arc;
+use axum::{Json, extract::Path, response::IntoResponse};
 
 use crate::{
+    middleware::SelectedCluster,
     services::starrocks_client::StarRocksClient,
     utils::error::{ApiError, ApiResult},
 };
@@ -27,14 +23,11 @@ use crate::{
     )
 )]
 pub async fn get_query_profile(
-    State(state): State<Arc<crate::AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
     Path(query_id): Path<String>,
 ) -> ApiResult<impl IntoResponse> {
-    // Get cluster info
-    let cluster = state.cluster_service.get_active_cluster().await?;
-
     // Create StarRocks client
-    let client = StarRocksClient::new(cluster);
+    let client = StarRocksClient::new(cluster)?;
 
     // Try to get profile from StarRocks
     let profile_result = get_profile_from_starrocks(&client, &query_id).await;
@@ -64,13 +57,10 @@ async fn get_profile_from_starrocks(
     query_id: &str,
 ) -> ApiResult<QueryProfile> {
     // Try to get profile using HTTP REST API
-    let url = format!("{}/api/show_proc?path=/query_profile/{}", client.get_base_url(), query_id);
+    let path = format!("/api/show_proc?path=/query_profile/{}", query_id);
 
     let response = client
-        .http_client
-        .get(&url)
-        .basic_auth(&client.cluster.username, Some(&client.cluster.password_encrypted))
-        .send()
+        .get_with_failover(&path)
         .await
         .map_err(|e| ApiError::cluster_connection_failed(format!("Request failed: {}", e)))?;
 
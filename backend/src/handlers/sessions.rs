@@ -8,6 +8,7 @@ use serde_json::json;
 use std::sync::Arc;
 
 use crate::{
+    middleware::SelectedCluster,
     models::starrocks::Session,
     services::mysql_client::MySQLClient,
     utils::error::{ApiError, ApiResult},
@@ -28,10 +29,8 @@ use crate::{
 )]
 pub async fn get_sessions(
     State(state): State<Arc<crate::AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
 ) -> ApiResult<impl IntoResponse> {
-    // Get cluster info
-    let cluster = state.cluster_service.get_active_cluster().await?;
-
     // Get MySQL client from pool
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
     let mysql_client = MySQLClient::from_pool(pool);
@@ -51,6 +50,7 @@ pub async fn get_sessions(
     ),
     responses(
         (status = 200, description = "Session killed successfully"),
+        (status = 400, description = "Session ID is not a number"),
         (status = 404, description = "No active cluster found"),
         (status = 500, description = "Internal server error")
     ),
@@ -60,17 +60,20 @@ pub async fn get_sessions(
 )]
 pub async fn kill_session(
     State(state): State<Arc<crate::AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
     Path(session_id): Path<String>,
 ) -> ApiResult<impl IntoResponse> {
-    // Get cluster info
-    let cluster = state.cluster_service.get_active_cluster().await?;
+    // Connection IDs are numbers; anything else must not reach the KILL statement
+    let session_id: u64 = session_id
+        .parse()
+        .map_err(|_| ApiError::validation_error(format!("Invalid session ID: {:?}", session_id)))?;
 
     // Get MySQL client from pool
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
     let mysql_client = MySQLClient::from_pool(pool);
 
     // Kill session using MySQL protocol
-    kill_session_via_starrocks(&mysql_client, &session_id).await?;
+    kill_session_via_starrocks(&mysql_client, session_id).await?;
 
     Ok((StatusCode::OK, Json(json!({ "message": "Session killed successfully" }))))
 }
@@ -115,7 +118,7 @@ async fn get_sessions_from_starrocks(mysql_client: &MySQLClient) -> ApiResult<Ve
     Ok(sessions)
 }
 
-async fn kill_session_via_starrocks(mysql_client: &MySQLClient, session_id: &str) -> ApiResult<()> {
+async fn kill_session_via_starrocks(mysql_client: &MySQLClient, session_id: u64) -> ApiResult<()> {
     // Use MySQL protocol to execute KILL CONNECTION command
     tracing::info!("Killing session: {}", session_id);
 
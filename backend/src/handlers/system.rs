@@ -2,8 +2,9 @@ use axum::{Json, extract::State};
 use std::sync::Arc;
 
 use crate::AppState;
+use crate::middleware::SelectedCluster;
 use crate::models::RuntimeInfo;
-use crate::services::StarRocksClient;
+use crate::services::{PoolStatus, QueryCacheStats, StarRocksClient};
 use crate::utils::ApiResult;
 
 // Get runtime info for a cluster
@@ -19,9 +20,90 @@ use crate::utils::ApiResult;
     ),
     tag = "System"
 )]
-pub async fn get_runtime_info(State(state): State<Arc<AppState>>) -> ApiResult<Json<RuntimeInfo>> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
-    let client = StarRocksClient::new(cluster);
+pub async fn get_runtime_info(
+    SelectedCluster(cluster): SelectedCluster,
+) -> ApiResult<Json<RuntimeInfo>> {
+    let client = StarRocksClient::new(cluster)?;
     let runtime_info = client.get_runtime_info().await?;
     Ok(Json(runtime_info))
 }
+
+// Get connection statistics of the MySQL pools of all clusters
+#[utoipa::path(
+    get,
+    path = "/api/system/pools",
+    responses(
+        (status = 200, description = "MySQL connection pools per cluster", body = Vec<PoolStatus>),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "System"
+)]
+pub async fn get_pool_stats(
+    State(state): State<Arc<AppState>>,
+) -> ApiResult<Json<Vec<PoolStatus>>> {
+    Ok(Json(state.mysql_pool_manager.pool_statuses()))
+}
+
+// Get hit/miss statistics and limits of the query result cache
+#[utoipa::path(
+    get,
+    path = "/api/system/query-cache",
+    responses(
+        (status = 200, description = "Query result cache statistics", body = QueryCacheStats),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "System"
+)]
+pub async fn get_query_cache_stats(
+    State(state): State<Arc<AppState>>,
+) -> ApiResult<Json<QueryCacheStats>> {
+    Ok(Json(state.query_cache.stats()))
+}
+
+// Drop the cached query results of all clusters
+#[utoipa::path(
+    delete,
+    path = "/api/system/query-cache",
+    responses(
+        (status = 200, description = "Number of results dropped"),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "System"
+)]
+pub async fn clear_query_cache(
+    State(state): State<Arc<AppState>>,
+) -> ApiResult<Json<serde_json::Value>> {
+    let removed = state.query_cache.clear();
+    tracing::info!("Cleared {} cached query results", removed);
+    Ok(Json(serde_json::json!({ "removed": removed })))
+}
+
+// Drop the cached query results of a cluster
+#[utoipa::path(
+    delete,
+    path = "/api/clusters/queries/cache",
+    responses(
+        (status = 200, description = "Number of results dropped"),
+        (status = 404, description = "No active cluster found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "System"
+)]
+pub async fn invalidate_query_cache(
+    State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
+) -> ApiResult<Json<serde_json::Value>> {
+    let removed = state.query_cache.invalidate_cluster(cluster.id);
+    Ok(Json(serde_json::json!({ "removed": removed })))
+}
@@ -1,6 +1,6 @@
 use axum::{
     Extension, Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
 };
@@ -8,14 +8,17 @@ use std::sync::Arc;
 use validator::Validate;
 
 use crate::AppState;
-use crate::models::{CreateFunctionRequest, UpdateFunctionRequest, UpdateOrderRequest};
+use crate::middleware::SelectedCluster;
+use crate::models::{
+    CreateFunctionRequest, FunctionExecuteQuery, UpdateFunctionRequest, UpdateOrderRequest,
+};
 use crate::utils::{ApiError, ApiResult};
 
 // GET /api/clusters/system-functions
 pub async fn get_system_functions(
     State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
 ) -> ApiResult<impl IntoResponse> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
     let functions = state
         .system_function_service
         .get_functions(cluster.id)
@@ -26,6 +29,7 @@ pub async fn get_system_functions(
 // POST /api/clusters/system-functions
 pub async fn create_system_function(
     State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
     Extension(user_id): Extension<i64>,
     Json(req): Json<CreateFunctionRequest>,
 ) -> ApiResult<impl IntoResponse> {
@@ -34,7 +38,6 @@ pub async fn create_system_function(
         return Err(ApiError::validation_error(format!("请求参数验证失败：{}", validation_errors)));
     }
 
-    let cluster = state.cluster_service.get_active_cluster().await?;
     let function = state
         .system_function_service
         .create_function(cluster.id, req, user_id)
@@ -45,12 +48,13 @@ pub async fn create_system_function(
 // POST /api/clusters/system-functions/:function_id/execute
 pub async fn execute_system_function(
     State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
     Path(function_id): Path<i64>,
+    Query(query): Query<FunctionExecuteQuery>,
 ) -> ApiResult<impl IntoResponse> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
     let result = state
         .system_function_service
-        .execute_function(cluster.id, function_id)
+        .execute_function(cluster.id, function_id, query.cache_ttl_secs.unwrap_or(0))
         .await?;
     Ok(Json(result))
 }
@@ -58,9 +62,9 @@ pub async fn execute_system_function(
 // PUT /api/clusters/system-functions/orders
 pub async fn update_function_orders(
     State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
     Json(req): Json<UpdateOrderRequest>,
 ) -> ApiResult<impl IntoResponse> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
     state
         .system_function_service
         .update_orders(cluster.id, req)
@@ -71,9 +75,9 @@ pub async fn update_function_orders(
 // PUT /api/clusters/system-functions/:function_id/favorite
 pub async fn toggle_function_favorite(
     State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
     Path(function_id): Path<i64>,
 ) -> ApiResult<impl IntoResponse> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
     let function = state
         .system_function_service
         .toggle_favorite(cluster.id, function_id)
@@ -84,9 +88,9 @@ pub async fn toggle_function_favorite(
 // DELETE /api/clusters/system-functions/:function_id
 pub async fn delete_system_function(
     State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
     Path(function_id): Path<i64>,
 ) -> ApiResult<impl IntoResponse> {
-    let cluster = state.cluster_service.get_active_cluster().await?;
     state
         .system_function_service
         .delete_function(cluster.id, function_id)
@@ -97,6 +101,7 @@ pub async fn delete_system_function(
 // PUT /api/clusters/system-functions/:function_id
 pub async fn update_function(
     State(state): State<Arc<AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
     Path(function_id): Path<i64>,
     Json(req): Json<UpdateFunctionRequest>,
 ) -> ApiResult<impl IntoResponse> {
@@ -105,7 +110,6 @@ pub async fn update_function(
         return Err(ApiError::validation_error(format!("请求参数验证失败：{}", validation_errors)));
     }
 
-    let cluster = state.cluster_service.get_active_cluster().await?;
     let function = state
         .system_function_service
         .update_function(cluster.id, function_id, req)
@@ -1,12 +1,12 @@
 use axum::{
     Json,
-    extract::{Path, Query, State},
+    extract::{Path, Query},
     response::IntoResponse,
 };
 use serde::Deserialize;
-use std::sync::Arc;
 
 use crate::{
+    middleware::SelectedCluster,
     services::starrocks_client::StarRocksClient,
     utils::error::{ApiError, ApiResult},
 };
@@ -38,14 +38,11 @@ pub struct SystemQueryParams {
     )
 )]
 pub async fn get_system_functions(
-    State(state): State<Arc<crate::AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
     Query(params): Query<SystemQueryParams>,
 ) -> ApiResult<impl IntoResponse> {
-    // Get cluster info
-    let cluster = state.cluster_service.get_active_cluster().await?;
-
     // Create StarRocks client
-    let client = StarRocksClient::new(cluster);
+    let client = StarRocksClient::new(cluster)?;
 
     // Get all system functions using HTTP REST API
     let functions = get_all_system_functions(&client, &params).await?;
@@ -71,15 +68,12 @@ pub async fn get_system_functions(
     )
 )]
 pub async fn get_system_function_detail(
-    State(state): State<Arc<crate::AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
     Path(function_name): Path<String>,
     Query(params): Query<SystemQueryParams>,
 ) -> ApiResult<impl IntoResponse> {
-    // Get cluster info
-    let cluster = state.cluster_service.get_active_cluster().await?;
-
     // Create StarRocks client
-    let client = StarRocksClient::new(cluster);
+    let client = StarRocksClient::new(cluster)?;
 
     // Build complete PROC path
     let proc_path = if let Some(nested_path) = params.path {
@@ -289,13 +283,10 @@ async fn get_function_details(
     client: &StarRocksClient,
     proc_path: &str,
 ) -> ApiResult<SystemFunctionDetail> {
-    let url = format!("{}/api/show_proc?path={}", client.get_base_url(), proc_path);
+    let path = format!("/api/show_proc?path={}", proc_path);
 
     let response = client
-        .http_client
-        .get(&url)
-        .basic_auth(&client.cluster.username, Some(&client.cluster.password_encrypted))
-        .send()
+        .get_with_failover(&path)
         .await
         .map_err(|e| ApiError::cluster_connection_failed(format!("Request failed: {}", e)))?;
 
@@ -0,0 +1,94 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::AppState;
+use crate::models::{ClusterTopology, TopologyChangeRecord, TopologyRefreshResponse};
+use crate::utils::{ApiError, ApiResult};
+
+#[derive(Debug, Deserialize)]
+pub struct TopologyChangesParams {
+    pub limit: Option<i64>,
+}
+
+// Get the last discovered topology of a cluster
+#[utoipa::path(
+    get,
+    path = "/api/clusters/{id}/topology",
+    params(
+        ("id" = i64, Path, description = "Cluster ID")
+    ),
+    responses(
+        (status = 200, description = "Last discovered topology", body = ClusterTopology),
+        (status = 404, description = "Cluster not found or never discovered")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Clusters"
+)]
+pub async fn get_topology(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<ClusterTopology>> {
+    state.cluster_service.get_cluster(id).await?;
+
+    let topology = state.topology_service.snapshot(id).await?.ok_or_else(|| {
+        ApiError::not_found(format!("Topology of cluster {} has not been discovered yet", id))
+    })?;
+    Ok(Json(topology))
+}
+
+// Rediscover the topology of a cluster and report the changes since the last snapshot
+#[utoipa::path(
+    post,
+    path = "/api/clusters/{id}/topology/refresh",
+    params(
+        ("id" = i64, Path, description = "Cluster ID")
+    ),
+    responses(
+        (status = 200, description = "New topology and changes", body = TopologyRefreshResponse),
+        (status = 404, description = "Cluster not found"),
+        (status = 502, description = "Cluster not reachable on the HTTP or MySQL port")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Clusters"
+)]
+pub async fn refresh_topology(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<TopologyRefreshResponse>> {
+    let cluster = state.cluster_service.get_cluster(id).await?;
+    Ok(Json(state.topology_service.refresh(&cluster).await?))
+}
+
+// List topology changes of a cluster, newest first
+#[utoipa::path(
+    get,
+    path = "/api/clusters/{id}/topology/changes",
+    params(
+        ("id" = i64, Path, description = "Cluster ID"),
+        ("limit" = Option<i64>, Query, description = "Maximum number of changes (default 100)")
+    ),
+    responses(
+        (status = 200, description = "Recorded topology changes", body = Vec<TopologyChangeRecord>),
+        (status = 404, description = "Cluster not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Clusters"
+)]
+pub async fn list_topology_changes(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Query(params): Query<TopologyChangesParams>,
+) -> ApiResult<Json<Vec<TopologyChangeRecord>>> {
+    state.cluster_service.get_cluster(id).await?;
+    Ok(Json(state.topology_service.changes(id, params.limit).await?))
+}
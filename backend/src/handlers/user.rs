@@ -0,0 +1,244 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use std::sync::Arc;
+
+use crate::AppState;
+use crate::models::{
+    AcceptInvitationRequest, AdminCreateUserRequest, InvitationResponse, InviteUserRequest,
+    ResetPasswordRequest, UserResponse,
+};
+use crate::utils::ApiResult;
+
+// List all users
+#[utoipa::path(
+    get,
+    path = "/api/users",
+    responses(
+        (status = 200, description = "List of users", body = Vec<UserResponse>),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Users"
+)]
+pub async fn list_users(State(state): State<Arc<AppState>>) -> ApiResult<Json<Vec<UserResponse>>> {
+    let users = state.auth_service.list_users().await?;
+    Ok(Json(users.into_iter().map(UserResponse::from).collect()))
+}
+
+// Create a user directly
+#[utoipa::path(
+    post,
+    path = "/api/users",
+    request_body = AdminCreateUserRequest,
+    responses(
+        (status = 200, description = "User created", body = UserResponse),
+        (status = 400, description = "Username already exists"),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Users"
+)]
+pub async fn create_user(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<AdminCreateUserRequest>,
+) -> ApiResult<Json<UserResponse>> {
+    tracing::info!("Admin creating user: {} (role: {})", req.username, req.role);
+
+    let user = state.auth_service.create_user(req).await?;
+    Ok(Json(user.into()))
+}
+
+// Create a one-time invitation token
+#[utoipa::path(
+    post,
+    path = "/api/users/invitations",
+    request_body = InviteUserRequest,
+    responses(
+        (status = 200, description = "Invitation created, token is only shown once", body = InvitationResponse),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Users"
+)]
+pub async fn invite_user(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(user_id): axum::extract::Extension<i64>,
+    Json(req): Json<InviteUserRequest>,
+) -> ApiResult<Json<InvitationResponse>> {
+    let invitation = state.auth_service.invite_user(user_id, req).await?;
+    Ok(Json(invitation))
+}
+
+// Accept an invitation and create the account
+#[utoipa::path(
+    post,
+    path = "/api/auth/invitations/accept",
+    request_body = AcceptInvitationRequest,
+    responses(
+        (status = 200, description = "Account created", body = UserResponse),
+        (status = 400, description = "Invitation is invalid or has expired")
+    ),
+    tag = "Authentication"
+)]
+pub async fn accept_invitation(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<AcceptInvitationRequest>,
+) -> ApiResult<Json<UserResponse>> {
+    tracing::info!("Invitation acceptance attempt for username: {}", req.username);
+
+    let user = state.auth_service.accept_invitation(req).await?;
+    Ok(Json(user.into()))
+}
+
+// Disable a user account
+#[utoipa::path(
+    post,
+    path = "/api/users/{id}/disable",
+    params(
+        ("id" = i64, Path, description = "User ID")
+    ),
+    responses(
+        (status = 200, description = "User disabled", body = UserResponse),
+        (status = 400, description = "Cannot disable yourself or the last admin"),
+        (status = 403, description = "Admin role required"),
+        (status = 404, description = "User not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Users"
+)]
+pub async fn disable_user(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(user_id): axum::extract::Extension<i64>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<UserResponse>> {
+    let user = state
+        .auth_service
+        .set_user_disabled(user_id, id, true)
+        .await?;
+    Ok(Json(user.into()))
+}
+
+// Re-enable a disabled user account
+#[utoipa::path(
+    post,
+    path = "/api/users/{id}/enable",
+    params(
+        ("id" = i64, Path, description = "User ID")
+    ),
+    responses(
+        (status = 200, description = "User enabled", body = UserResponse),
+        (status = 403, description = "Admin role required"),
+        (status = 404, description = "User not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Users"
+)]
+pub async fn enable_user(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(user_id): axum::extract::Extension<i64>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<UserResponse>> {
+    let user = state
+        .auth_service
+        .set_user_disabled(user_id, id, false)
+        .await?;
+    Ok(Json(user.into()))
+}
+
+// Reset a user's password
+#[utoipa::path(
+    put,
+    path = "/api/users/{id}/password",
+    params(
+        ("id" = i64, Path, description = "User ID")
+    ),
+    request_body = ResetPasswordRequest,
+    responses(
+        (status = 200, description = "Password reset", body = UserResponse),
+        (status = 403, description = "Admin role required"),
+        (status = 404, description = "User not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Users"
+)]
+pub async fn reset_password(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<ResetPasswordRequest>,
+) -> ApiResult<Json<UserResponse>> {
+    let user = state
+        .auth_service
+        .reset_password(id, &req.new_password)
+        .await?;
+    Ok(Json(user.into()))
+}
+
+// Delete a user
+#[utoipa::path(
+    delete,
+    path = "/api/users/{id}",
+    params(
+        ("id" = i64, Path, description = "User ID")
+    ),
+    responses(
+        (status = 200, description = "User deleted"),
+        (status = 400, description = "Cannot delete yourself or the last admin"),
+        (status = 403, description = "Admin role required"),
+        (status = 404, description = "User not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Users"
+)]
+pub async fn delete_user(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(user_id): axum::extract::Extension<i64>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<serde_json::Value>> {
+    state.auth_service.delete_user(user_id, id).await?;
+    Ok(Json(serde_json::json!({"message": "User deleted successfully"})))
+}
+
+// Revoke all active sessions of a user
+#[utoipa::path(
+    post,
+    path = "/api/users/{id}/revoke-sessions",
+    params(
+        ("id" = i64, Path, description = "User ID")
+    ),
+    responses(
+        (status = 200, description = "Sessions revoked"),
+        (status = 403, description = "Admin role required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Users"
+)]
+pub async fn revoke_sessions(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<serde_json::Value>> {
+    let revoked = state
+        .session_service
+        .revoke_user_sessions(id, "admin_revoked")
+        .await?;
+
+    tracing::info!("Revoked {} session(s) of user {}", revoked, id);
+    Ok(Json(serde_json::json!({"message": "Sessions revoked successfully", "revoked": revoked})))
+}
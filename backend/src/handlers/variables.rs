@@ -1,7 +1,7 @@
 use axum::{
     Json,
-    extract::{Path, Query, State},
-    http::StatusCode,
+    extract::{Extension, Path, Query, State},
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
 };
 use serde::Deserialize;
@@ -9,9 +9,12 @@ use serde_json::json;
 use std::sync::Arc;
 
 use crate::{
+    middleware::SelectedCluster,
+    models::CurrentUser,
     models::starrocks::{UpdateVariableRequest, Variable},
-    services::mysql_client::MySQLClient,
+    services::{SqlGuard, mysql_client::MySQLClient},
     utils::error::{ApiError, ApiResult},
+    utils::sql,
 };
 
 #[derive(Debug, Deserialize)]
@@ -44,29 +47,22 @@ fn default_type() -> String {
 )]
 pub async fn get_variables(
     State(state): State<Arc<crate::AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
     Query(params): Query<VariableQueryParams>,
 ) -> ApiResult<impl IntoResponse> {
-    // Get cluster info
-    let cluster = state.cluster_service.get_active_cluster().await?;
-
     // Get MySQL client from pool
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
     let mysql_client = MySQLClient::from_pool(pool);
 
     // Build SQL query
-    let sql = match params.r#type.as_str() {
-        "session" => "SHOW SESSION VARIABLES",
-        _ => "SHOW GLOBAL VARIABLES",
-    };
-
-    let sql_with_filter = if let Some(filter) = params.filter {
-        format!("{} LIKE '%{}%'", sql, filter)
-    } else {
-        sql.to_string()
+    let scope = match params.r#type.as_str() {
+        "session" => "SESSION",
+        _ => "GLOBAL",
     };
+    let sql = sql::show_variables(scope, params.filter.as_deref());
 
     // Execute query
-    let (_, rows) = mysql_client.query_raw(&sql_with_filter, None, None).await?;
+    let (_, rows) = mysql_client.query_raw(&sql, None, None).await?;
 
     // Parse results
     let variables: Vec<Variable> = rows
@@ -90,7 +86,9 @@ pub async fn get_variables(
     request_body = UpdateVariableRequest,
     responses(
         (status = 200, description = "Variable updated successfully"),
+        (status = 403, description = "Not allowed for the role or cluster", body = crate::models::SqlViolation),
         (status = 404, description = "No active cluster found"),
+        (status = 428, description = "The value holds a destructive statement", body = crate::models::SqlViolation),
         (status = 500, description = "Internal server error")
     ),
     security(
@@ -99,12 +97,12 @@ pub async fn get_variables(
 )]
 pub async fn update_variable(
     State(state): State<Arc<crate::AppState>>,
+    SelectedCluster(cluster): SelectedCluster,
+    Extension(user): Extension<CurrentUser>,
+    headers: HeaderMap,
     Path(variable_name): Path<String>,
     Json(request): Json<UpdateVariableRequest>,
 ) -> ApiResult<impl IntoResponse> {
-    // Get cluster info
-    let cluster = state.cluster_service.get_active_cluster().await?;
-
     // Get MySQL client from pool
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
     let mysql_client = MySQLClient::from_pool(pool);
@@ -117,7 +115,11 @@ pub async fn update_variable(
     };
 
     // Build SET command
-    let sql = format!("SET {} {} = {}", scope, variable_name, request.value);
+    let sql = sql::set_variable(scope, &variable_name, &request.value)?;
+    state
+        .sql_guard
+        .check(&user, &cluster, &sql, SqlGuard::confirmation(&headers))
+        .await?;
 
     // Execute command
     mysql_client.execute(&sql).await?;
@@ -1,8 +1,9 @@
 use axum::{
-    Router, middleware as axum_middleware,
+    Router, ServiceExt, extract::Request, middleware as axum_middleware,
     routing::{delete, get, post, put},
 };
 use std::sync::Arc;
+use tower::Layer;
 use tower_http::services::ServeDir;
 use tracing_subscriber::{fmt, EnvFilter, prelude::*};
 use std::env;
@@ -19,10 +20,12 @@ mod utils;
 
 use config::Config;
 use services::{
-    AuthService, ClusterService, DataStatisticsService, MetricsCollectorService, MySQLPoolManager,
-    OverviewService, SystemFunctionService,
+    AdminAuditService, ApiTokenService, AuthService, ClusterDiagnosticsService, ClusterGroupService, ClusterService, ConfigBundleService, DataStatisticsService, LdapBackend, LoginThrottle, MetricsCollectorService,
+    MySQLPoolManager, OidcProvider, OverviewService, PasswordPolicy, PermissionService, QueryCache, QueryJobService, QueryPlanService, QueryStreamService, SessionService,
+    SqlGuard, SqlScriptService, SystemFunctionService, TopologyService,
 };
 use sqlx::SqlitePool;
+use utils::crypto::{self, SecretCipher};
 use utils::{JwtUtil, ScheduledExecutor};
 
 /// Application shared state
@@ -41,11 +44,27 @@ pub struct AppState {
 
     // Services (grouped by domain)
     pub auth_service: Arc<AuthService>,
+    pub session_service: Arc<SessionService>,
+    pub oidc_provider: Option<Arc<OidcProvider>>,
+    pub permission_service: Arc<PermissionService>,
+    pub admin_audit_service: Arc<AdminAuditService>,
+    pub login_throttle: Arc<LoginThrottle>,
+    pub api_token_service: Arc<ApiTokenService>,
     pub cluster_service: Arc<ClusterService>,
+    pub cluster_diagnostics_service: Arc<ClusterDiagnosticsService>,
+    pub cluster_group_service: Arc<ClusterGroupService>,
     pub system_function_service: Arc<SystemFunctionService>,
     pub metrics_collector_service: Arc<MetricsCollectorService>,
     pub data_statistics_service: Arc<DataStatisticsService>,
     pub overview_service: Arc<OverviewService>,
+    pub topology_service: Arc<TopologyService>,
+    pub config_bundle_service: Arc<ConfigBundleService>,
+    pub query_stream_service: Arc<QueryStreamService>,
+    pub query_job_service: Arc<QueryJobService>,
+    pub query_plan_service: Arc<QueryPlanService>,
+    pub sql_script_service: Arc<SqlScriptService>,
+    pub sql_guard: Arc<SqlGuard>,
+    pub query_cache: Arc<QueryCache>,
 }
 
 #[derive(OpenApi)]
@@ -55,6 +74,14 @@ pub struct AppState {
         handlers::auth::login,
         handlers::auth::get_me,
         handlers::auth::update_me,
+        handlers::auth::refresh,
+        handlers::auth::logout,
+        handlers::auth::get_providers,
+        handlers::auth::oidc_login,
+        handlers::auth::oidc_callback,
+        handlers::auth::list_api_tokens,
+        handlers::auth::create_api_token,
+        handlers::auth::revoke_api_token,
         handlers::cluster::create_cluster,
         handlers::cluster::list_clusters,
         handlers::cluster::get_active_cluster,
@@ -63,6 +90,20 @@ pub struct AppState {
         handlers::cluster::delete_cluster,
         handlers::cluster::activate_cluster,
         handlers::cluster::get_cluster_health,
+        handlers::cluster::get_cluster_diagnostics,
+        handlers::cluster::discover_cluster,
+        handlers::cluster::import_cluster,
+        handlers::topology::get_topology,
+        handlers::topology::refresh_topology,
+        handlers::topology::list_topology_changes,
+        handlers::cluster_group::list_cluster_groups,
+        handlers::cluster_group::get_cluster_group,
+        handlers::cluster_group::create_cluster_group,
+        handlers::cluster_group::update_cluster_group,
+        handlers::cluster_group::delete_cluster_group,
+        handlers::cluster_group::fan_out_health,
+        handlers::cluster_group::fan_out_variables,
+        handlers::cluster_group::fan_out_query,
         handlers::backend::list_backends,
         handlers::frontend::list_frontends,
         handlers::materialized_view::list_materialized_views,
@@ -79,6 +120,14 @@ pub struct AppState {
         handlers::query::list_queries,
         handlers::query::kill_query,
         handlers::query::execute_sql,
+        handlers::query::stream_sql,
+        handlers::query::execute_script,
+        handlers::query_job::submit_query_job,
+        handlers::query_job::list_query_jobs,
+        handlers::query_job::get_query_job,
+        handlers::query_job::get_query_job_results,
+        handlers::query_job::cancel_query_job,
+        handlers::query_plan::explain_query,
         handlers::query_history::list_query_history,
         handlers::sessions::get_sessions,
         handlers::sessions::kill_session,
@@ -90,6 +139,12 @@ pub struct AppState {
         handlers::system_management::get_system_functions,
         handlers::system_management::get_system_function_detail,
         handlers::system::get_runtime_info,
+        handlers::system::get_pool_stats,
+        handlers::system::get_query_cache_stats,
+        handlers::system::clear_query_cache,
+        handlers::system::invalidate_query_cache,
+        handlers::config_bundle::export_config,
+        handlers::config_bundle::import_config,
         handlers::overview::get_cluster_overview,
         handlers::overview::get_health_cards,
         handlers::overview::get_performance_trends,
@@ -98,6 +153,21 @@ pub struct AppState {
         handlers::overview::get_capacity_prediction,
         handlers::overview::get_extended_cluster_overview,
         handlers::cluster::test_cluster_connection,
+        handlers::permission::get_user_permissions,
+        handlers::permission::update_user_role,
+        handlers::permission::grant_cluster_role,
+        handlers::permission::revoke_cluster_role,
+        handlers::user::list_users,
+        handlers::user::create_user,
+        handlers::user::invite_user,
+        handlers::user::accept_invitation,
+        handlers::user::disable_user,
+        handlers::user::enable_user,
+        handlers::user::reset_password,
+        handlers::user::delete_user,
+        handlers::user::revoke_sessions,
+        handlers::audit::list_audit_log,
+        handlers::audit::export_audit_log,
     ),
     components(
         schemas(
@@ -106,13 +176,68 @@ pub struct AppState {
             models::CreateUserRequest,
             models::LoginRequest,
             models::LoginResponse,
+            models::TokenResponse,
+            models::RefreshTokenRequest,
+            models::UserRole,
+            models::ClusterRoleGrant,
+            models::UpdateUserRoleRequest,
+            models::GrantClusterRoleRequest,
+            models::UserPermissions,
+            models::AdminCreateUserRequest,
+            models::InviteUserRequest,
+            models::InvitationResponse,
+            models::AcceptInvitationRequest,
+            models::ResetPasswordRequest,
+            models::AuthSource,
+            models::AuthProvidersResponse,
+            models::ApiTokenScope,
+            models::ApiTokenResponse,
+            models::CreateApiTokenRequest,
+            models::CreateApiTokenResponse,
+            models::AdminAuditEntry,
+            models::AdminAuditLogResponse,
             models::Cluster,
             models::ClusterResponse,
             models::CreateClusterRequest,
             models::UpdateClusterRequest,
+            models::PoolSettings,
+            models::ClusterGroupKind,
+            models::ClusterGroupMember,
+            models::ClusterGroupResponse,
+            models::CreateClusterGroupRequest,
+            models::UpdateClusterGroupRequest,
+            models::FanOutQueryRequest,
+            models::FanOutStatus,
+            models::ClusterFanOutResult,
+            models::FanOutResponse,
+            models::RunMode,
+            models::TopologyFrontend,
+            models::TopologyNode,
+            models::TopologyWarehouse,
+            models::ClusterTopology,
+            models::TopologyDiscovery,
+            models::ImportClusterRequest,
+            models::ImportClusterResponse,
+            models::TopologyComponent,
+            models::TopologyChangeKind,
+            models::TopologyChange,
+            models::TopologyChangeRecord,
+            models::TopologyRefreshResponse,
+            models::ConfigBundle,
+            models::ClusterDefinition,
+            models::ClusterGroupDefinition,
+            models::SystemFunctionDefinition,
+            models::SystemFunctionPreferenceDefinition,
+            models::ConfigFormat,
+            models::ConfigItemKind,
+            models::ConfigAction,
+            models::ConfigChange,
+            models::ConfigImportResponse,
             models::ClusterHealth,
             models::HealthStatus,
             models::HealthCheck,
+            models::Severity,
+            models::TabletStatistic,
             models::Backend,
             models::Frontend,
             models::MaterializedView,
@@ -122,7 +247,33 @@ pub struct AppState {
             models::MaterializedViewDDL,
             models::Query,
             models::QueryExecuteRequest,
+            models::ResultColumn,
             models::QueryExecuteResponse,
+            models::QueryStreamRequest,
+            models::QueryStreamFormat,
+            models::QueryStreamEvent,
+            models::ExportFormat,
+            models::StreamTruncation,
+            models::SqlScriptRequest,
+            models::SqlStatementStatus,
+            models::SqlStatementResult,
+            models::SqlScriptResponse,
+            models::StatementCategory,
+            models::SqlPolicy,
+            models::SqlViolationReason,
+            models::SqlViolation,
+            models::QueryJobStatus,
+            models::SubmitQueryJobRequest,
+            models::QueryJob,
+            models::QueryJobResults,
+            models::ExplainRequest,
+            models::ExplainMode,
+            models::QueryPlan,
+            models::PlanFragment,
+            models::PlanNode,
+            models::PlanWarning,
+            models::PlanWarningKind,
+            models::ScanRatio,
             models::CatalogWithDatabases,
             models::CatalogsWithDatabasesResponse,
             models::QueryHistoryItem,
@@ -132,6 +283,7 @@ pub struct AppState {
             models::RuntimeInfo,
             models::MetricsSummary,
             models::SystemFunction,
+            models::FunctionExecuteResponse,
             models::CreateFunctionRequest,
             models::UpdateOrderRequest,
             models::FunctionOrder,
@@ -164,11 +316,18 @@ pub struct AppState {
             services::TopTableBySize,
             services::TopTableByAccess,
             services::CapacityPrediction,
+            services::PoolStatus,
+            services::QueryCacheStats,
+            services::EffectivePoolSettings,
         )
     ),
     tags(
         (name = "Authentication", description = "User authentication endpoints"),
+        (name = "Users", description = "User lifecycle management"),
+        (name = "Permissions", description = "User roles and per-cluster grants"),
+        (name = "Audit", description = "Audit trail of mutating actions"),
         (name = "Clusters", description = "Cluster management endpoints"),
+        (name = "Cluster Groups", description = "Cluster groups and operations across their members"),
         (name = "Backends", description = "Backend node management"),
         (name = "Frontends", description = "Frontend node management"),
         (name = "Materialized Views", description = "Materialized view management"),
@@ -210,6 +369,30 @@ fn setup_logging() {
         .init();
 }
 
+/// `starrocks-admin rotate-master-key`
+///
+/// Reads the current key from the normal configuration and the new key from
+/// APP_NEW_MASTER_KEY, re-encrypts all cluster passwords in one transaction and exits.
+async fn rotate_master_key(
+    config: &Config,
+    cluster_service: &ClusterService,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let new_key = env::var("APP_NEW_MASTER_KEY")
+        .map_err(|_| "APP_NEW_MASTER_KEY must be set to the new master key")?;
+
+    let old_cipher = SecretCipher::new(&config.security.master_key)?;
+    let new_cipher = SecretCipher::new(&new_key)?;
+    let count = cluster_service
+        .rotate_master_key(&old_cipher, &new_cipher)
+        .await?;
+
+    tracing::info!(
+        "Master key rotated for {} cluster(s); set security.master_key (or APP_MASTER_KEY) to the new key, or write it to security.master_key_file, before restarting",
+        count
+    );
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     dotenvy::dotenv().ok();
@@ -223,18 +406,54 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let pool = db::create_pool(&config.database.url).await?;
     tracing::info!("Database pool created successfully");
 
+    // Must be installed before any cluster password is read or written
+    crypto::init_master_key(&config.security.master_key)?;
+
     // Initialize core components
     let jwt_util = Arc::new(JwtUtil::new(&config.auth.jwt_secret, &config.auth.jwt_expires_in));
-    let mysql_pool_manager = Arc::new(MySQLPoolManager::new());
+    let mysql_pool_manager = Arc::new(MySQLPoolManager::new(config.mysql_pool.clone()));
 
-    let auth_service = Arc::new(AuthService::new(pool.clone(), Arc::clone(&jwt_util)));
+    let session_service = Arc::new(SessionService::new(
+        pool.clone(),
+        Arc::clone(&jwt_util),
+        &config.auth.refresh_token_expires_in,
+    ));
+    let login_throttle = Arc::new(LoginThrottle::new(pool.clone(), config.auth.lockout.clone()));
+    let mut auth_service = AuthService::new(pool.clone(), Arc::clone(&session_service))
+        .with_registration(config.auth.allow_registration)
+        .with_password_policy(PasswordPolicy::new(config.auth.password_policy.clone()))
+        .with_login_throttle(Arc::clone(&login_throttle));
+    if config.auth.ldap.enabled {
+        tracing::info!("LDAP sign-in enabled ({})", config.auth.ldap.url);
+        auth_service = auth_service
+            .with_password_backend(Arc::new(LdapBackend::new(config.auth.ldap.clone())));
+    }
+    let auth_service = Arc::new(auth_service);
+
+    let oidc_provider = config.auth.oidc.enabled.then(|| {
+        tracing::info!("OIDC sign-in enabled ({})", config.auth.oidc.issuer_url);
+        Arc::new(OidcProvider::new(config.auth.oidc.clone()))
+    });
+    let permission_service = Arc::new(PermissionService::new(pool.clone()));
+    let admin_audit_service = Arc::new(AdminAuditService::new(pool.clone()));
+    let api_token_service = Arc::new(ApiTokenService::new(pool.clone()));
 
     let cluster_service = Arc::new(ClusterService::new(pool.clone()));
+    let cluster_group_service = Arc::new(ClusterGroupService::new(pool.clone()));
+
+    // Maintenance command: re-encrypt stored cluster passwords with a new master key
+    if env::args().nth(1).as_deref() == Some("rotate-master-key") {
+        return rotate_master_key(&config, &cluster_service).await;
+    }
+    cluster_service.encrypt_plaintext_passwords().await?;
+
+    let query_cache = Arc::new(QueryCache::new(config.query_cache.clone()));
 
     let system_function_service = Arc::new(SystemFunctionService::new(
         Arc::new(pool.clone()),
         Arc::clone(&mysql_pool_manager),
         Arc::clone(&cluster_service),
+        Arc::clone(&query_cache),
     ));
 
     // Create new services for cluster overview
@@ -256,36 +475,133 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             Arc::clone(&cluster_service),
             Arc::clone(&mysql_pool_manager),
         )
-        .with_data_statistics(Arc::clone(&data_statistics_service)),
+        .with_data_statistics(Arc::clone(&data_statistics_service))
+        .with_query_cache(Arc::clone(&query_cache)),
     );
 
+    let topology_service = Arc::new(TopologyService::new(
+        pool.clone(),
+        Arc::clone(&cluster_service),
+        Arc::clone(&mysql_pool_manager),
+    ));
+
+    let cluster_diagnostics_service = Arc::new(ClusterDiagnosticsService::new(
+        Arc::clone(&cluster_service),
+        config.diagnostics.clone(),
+    ));
+
+    let config_bundle_service = Arc::new(ConfigBundleService::new(
+        pool.clone(),
+        Arc::clone(&cluster_service),
+        Arc::clone(&cluster_group_service),
+    ));
+
+    let query_stream_service = Arc::new(QueryStreamService::new(
+        Arc::clone(&mysql_pool_manager),
+        config.query_stream.clone(),
+    ));
+
+    let query_job_service = Arc::new(QueryJobService::new(
+        pool.clone(),
+        Arc::clone(&query_stream_service),
+        config.query_jobs.clone(),
+    ));
+    query_job_service.fail_interrupted().await?;
+
+    let query_plan_service = Arc::new(QueryPlanService::new(
+        Arc::clone(&mysql_pool_manager),
+        config.query_plan.clone(),
+    ));
+
+    let sql_script_service = Arc::new(SqlScriptService::new(Arc::clone(&mysql_pool_manager)));
+    let sql_guard =
+        Arc::new(SqlGuard::new(config.sql_guard.clone(), Arc::clone(&permission_service)));
+
     // Build AppState with all services
     let app_state = AppState {
         db: pool.clone(),
         mysql_pool_manager: Arc::clone(&mysql_pool_manager),
         jwt_util: Arc::clone(&jwt_util),
         auth_service: Arc::clone(&auth_service),
+        session_service: Arc::clone(&session_service),
+        oidc_provider,
+        permission_service: Arc::clone(&permission_service),
+        admin_audit_service: Arc::clone(&admin_audit_service),
+        login_throttle: Arc::clone(&login_throttle),
+        api_token_service: Arc::clone(&api_token_service),
         cluster_service: Arc::clone(&cluster_service),
+        cluster_diagnostics_service: Arc::clone(&cluster_diagnostics_service),
+        cluster_group_service: Arc::clone(&cluster_group_service),
         system_function_service: Arc::clone(&system_function_service),
         metrics_collector_service: Arc::clone(&metrics_collector_service),
         data_statistics_service: Arc::clone(&data_statistics_service),
         overview_service: Arc::clone(&overview_service),
+        topology_service: Arc::clone(&topology_service),
+        config_bundle_service: Arc::clone(&config_bundle_service),
+        query_stream_service: Arc::clone(&query_stream_service),
+        query_job_service: Arc::clone(&query_job_service),
+        query_plan_service: Arc::clone(&query_plan_service),
+        sql_script_service: Arc::clone(&sql_script_service),
+        sql_guard: Arc::clone(&sql_guard),
+        query_cache: Arc::clone(&query_cache),
     };
 
     // Start metrics collector using ScheduledExecutor (30 seconds interval)
     let executor = ScheduledExecutor::new("metrics-collector", std::time::Duration::from_secs(30));
     executor.spawn(Arc::clone(&metrics_collector_service));
 
+    // Close MySQL pools of clusters that are no longer used
+    if config.mysql_pool.evict_idle_pool_secs > 0 {
+        let executor =
+            ScheduledExecutor::new("mysql-pool-eviction", std::time::Duration::from_secs(60));
+        executor.spawn(Arc::clone(&mysql_pool_manager));
+    }
+
+    // Rediscover cluster topologies and record what changed
+    if config.topology.refresh_interval_secs > 0 {
+        let executor = ScheduledExecutor::new(
+            "topology-refresh",
+            std::time::Duration::from_secs(config.topology.refresh_interval_secs),
+        );
+        executor.spawn(Arc::clone(&topology_service));
+    }
+
+    // Delete query jobs past their retention
+    let executor = ScheduledExecutor::new("query-job-cleanup", std::time::Duration::from_secs(300));
+    executor.spawn(Arc::clone(&query_job_service));
+
     // Wrap AppState in Arc for shared ownership across routes
     let app_state_arc = Arc::new(app_state);
 
     // Auth state for middleware
-    let auth_state = middleware::AuthState { jwt_util: Arc::clone(&jwt_util) };
+    let auth_state = middleware::AuthState {
+        jwt_util: Arc::clone(&jwt_util),
+        auth_service: Arc::clone(&auth_service),
+        session_service: Arc::clone(&session_service),
+        api_token_service: Arc::clone(&api_token_service),
+    };
+
+    // Authorization state for role checks
+    let rbac_state = middleware::RbacState {
+        permission_service: Arc::clone(&permission_service),
+        cluster_service: Arc::clone(&cluster_service),
+    };
+
+    // Audit state for recording mutating requests
+    let audit_state = middleware::AuditState {
+        admin_audit_service: Arc::clone(&admin_audit_service),
+        cluster_service: Arc::clone(&cluster_service),
+    };
 
     // Public routes (no authentication required)
     let public_routes = Router::new()
         .route("/api/auth/register", post(handlers::auth::register))
         .route("/api/auth/login", post(handlers::auth::login))
+        .route("/api/auth/refresh", post(handlers::auth::refresh))
+        .route("/api/auth/providers", get(handlers::auth::get_providers))
+        .route("/api/auth/oidc/login", get(handlers::auth::oidc_login))
+        .route("/api/auth/oidc/callback", get(handlers::auth::oidc_callback))
+        .route("/api/auth/invitations/accept", post(handlers::user::accept_invitation))
         .with_state(Arc::clone(&app_state_arc));
 
     // Protected routes (require authentication)
@@ -293,6 +609,30 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         // Auth
         .route("/api/auth/me", get(handlers::auth::get_me))
         .route("/api/auth/me", put(handlers::auth::update_me))
+        .route("/api/auth/logout", post(handlers::auth::logout))
+        .route(
+            "/api/auth/tokens",
+            get(handlers::auth::list_api_tokens).post(handlers::auth::create_api_token),
+        )
+        .route("/api/auth/tokens/:id", delete(handlers::auth::revoke_api_token))
+        // Users & permissions
+        .route("/api/users", get(handlers::user::list_users).post(handlers::user::create_user))
+        .route("/api/users/invitations", post(handlers::user::invite_user))
+        .route("/api/users/:id", delete(handlers::user::delete_user))
+        .route("/api/users/:id/disable", post(handlers::user::disable_user))
+        .route("/api/users/:id/enable", post(handlers::user::enable_user))
+        .route("/api/users/:id/password", put(handlers::user::reset_password))
+        .route("/api/users/:id/revoke-sessions", post(handlers::user::revoke_sessions))
+        .route("/api/users/:id/permissions", get(handlers::permission::get_user_permissions))
+        .route("/api/users/:id/role", put(handlers::permission::update_user_role))
+        .route(
+            "/api/users/:id/cluster-roles/:cluster_id",
+            put(handlers::permission::grant_cluster_role)
+                .delete(handlers::permission::revoke_cluster_role),
+        )
+        // Audit
+        .route("/api/audit", get(handlers::audit::list_audit_log))
+        .route("/api/audit/export", get(handlers::audit::export_audit_log))
         // Clusters
         .route("/api/clusters", post(handlers::cluster::create_cluster))
         .route("/api/clusters", get(handlers::cluster::list_clusters))
@@ -302,10 +642,28 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .route("/api/clusters/:id", delete(handlers::cluster::delete_cluster))
         .route("/api/clusters/:id/activate", put(handlers::cluster::activate_cluster))
         .route("/api/clusters/health/test", post(handlers::cluster::test_cluster_connection))
+        .route("/api/clusters/discover", post(handlers::cluster::discover_cluster))
+        .route("/api/clusters/import", post(handlers::cluster::import_cluster))
+        .route("/api/clusters/:id/topology", get(handlers::topology::get_topology))
+        .route("/api/clusters/:id/topology/refresh", post(handlers::topology::refresh_topology))
+        .route(
+            "/api/clusters/:id/topology/changes",
+            get(handlers::topology::list_topology_changes),
+        )
         .route(
             "/api/clusters/:id/health",
             get(handlers::cluster::get_cluster_health).post(handlers::cluster::get_cluster_health),
         )
+        .route("/api/clusters/:id/health/deep", get(handlers::cluster::get_cluster_diagnostics))
+        // Cluster groups
+        .route("/api/cluster-groups", get(handlers::cluster_group::list_cluster_groups))
+        .route("/api/cluster-groups", post(handlers::cluster_group::create_cluster_group))
+        .route("/api/cluster-groups/:id", get(handlers::cluster_group::get_cluster_group))
+        .route("/api/cluster-groups/:id", put(handlers::cluster_group::update_cluster_group))
+        .route("/api/cluster-groups/:id", delete(handlers::cluster_group::delete_cluster_group))
+        .route("/api/cluster-groups/:id/health", post(handlers::cluster_group::fan_out_health))
+        .route("/api/cluster-groups/:id/variables", get(handlers::cluster_group::fan_out_variables))
+        .route("/api/cluster-groups/:id/query", post(handlers::cluster_group::fan_out_query))
         // Backends
         .route("/api/clusters/backends", get(handlers::backend::list_backends))
         .route("/api/clusters/backends/:host/:port", delete(handlers::backend::delete_backend))
@@ -317,6 +675,23 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .route("/api/clusters/catalogs-databases", get(handlers::query::list_catalogs_with_databases))
         .route("/api/clusters/queries", get(handlers::query::list_queries))
         .route("/api/clusters/queries/execute", post(handlers::query::execute_sql))
+        .route("/api/clusters/queries/stream", post(handlers::query::stream_sql))
+        .route("/api/clusters/queries/cache", delete(handlers::system::invalidate_query_cache))
+        .route("/api/clusters/queries/script", post(handlers::query::execute_script))
+        .route("/api/clusters/queries/explain", post(handlers::query_plan::explain_query))
+        .route(
+            "/api/clusters/queries/jobs",
+            get(handlers::query_job::list_query_jobs).post(handlers::query_job::submit_query_job),
+        )
+        .route("/api/clusters/queries/jobs/:job_id", get(handlers::query_job::get_query_job))
+        .route(
+            "/api/clusters/queries/jobs/:job_id/results",
+            get(handlers::query_job::get_query_job_results),
+        )
+        .route(
+            "/api/clusters/queries/jobs/:job_id/cancel",
+            post(handlers::query_job::cancel_query_job),
+        )
         .route("/api/clusters/queries/:query_id", delete(handlers::query::kill_query))
         .route("/api/clusters/queries/history", get(handlers::query_history::list_query_history))
         .route(
@@ -363,6 +738,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             "/api/clusters/system/:function_name",
             get(handlers::system_management::get_system_function_detail),
         )
+        .route("/api/system/pools", get(handlers::system::get_pool_stats))
+        .route(
+            "/api/system/query-cache",
+            get(handlers::system::get_query_cache_stats)
+                .delete(handlers::system::clear_query_cache),
+        )
+        .route("/api/system/config/export", get(handlers::config_bundle::export_config))
+        .route("/api/system/config/import", post(handlers::config_bundle::import_config))
         // System Functions
         .route(
             "/api/clusters/system-functions",
@@ -416,6 +799,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             get(handlers::overview::get_compaction_detail_stats),
         )
         .with_state(Arc::clone(&app_state_arc))
+        .layer(axum_middleware::from_fn_with_state(rbac_state, middleware::rbac_middleware))
+        .layer(axum_middleware::from_fn_with_state(audit_state, middleware::audit_middleware))
         .layer(axum_middleware::from_fn_with_state(auth_state, middleware::auth_middleware));
 
     let health_routes = Router::new()
@@ -442,6 +827,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .layer(tower_http::trace::TraceLayer::new_for_http())
         .layer(tower_http::cors::CorsLayer::permissive());
 
+    // `/api/clusters/:id/<route>` targets one cluster; rewritten before routing
+    let app = axum_middleware::from_fn(middleware::scoped_cluster_routes).layer(app);
+
     let addr = format!("{}:{}", config.server.host, config.server.port);
     let listener = tokio::net::TcpListener::bind(&addr).await?;
 
@@ -449,7 +837,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing::info!("API documentation available at http://{}/api-docs", addr);
     tracing::info!("StarRocks Admin is ready to serve requests");
 
-    axum::serve(listener, app).await?;
+    // Peer addresses are recorded in the admin audit log
+    axum::serve(
+        listener,
+        ServiceExt::<Request>::into_make_service_with_connect_info::<std::net::SocketAddr>(app),
+    )
+    .await?;
 
     Ok(())
 }
@@ -0,0 +1,335 @@
+use axum::{
+    body::{Body, HttpBody, to_bytes},
+    extract::{ConnectInfo, MatchedPath, Request, State},
+    http::Method,
+    middleware::Next,
+    response::Response,
+};
+use serde_json::{Map, Value};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::middleware::cluster_selection::requested_cluster_id;
+use crate::middleware::rbac::resolve_cluster_id;
+use crate::models::{CurrentUser, NewAdminAuditEntry};
+use crate::services::admin_audit_service::redact_parameters;
+use crate::services::{AdminAuditService, ClusterService};
+use crate::utils::ApiError;
+
+/// Request bodies up to this size are captured; larger ones are passed through unrecorded
+const MAX_CAPTURED_BODY: usize = 2 * 1024 * 1024;
+/// Error responses up to this size are read for their message
+const MAX_ERROR_BODY: usize = 64 * 1024;
+
+#[derive(Clone)]
+pub struct AuditState {
+    pub admin_audit_service: Arc<AdminAuditService>,
+    pub cluster_service: Arc<ClusterService>,
+}
+
+// Record every mutating request; must run after auth_middleware and before rbac_middleware
+// so that requests rejected by authorization are recorded too
+pub async fn audit_middleware(
+    State(state): State<AuditState>,
+    req: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    if matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+        return Ok(next.run(req).await);
+    }
+    let Some(user) = req.extensions().get::<CurrentUser>().cloned() else {
+        return Ok(next.run(req).await);
+    };
+
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| path.clone());
+    let client_ip = client_ip(&req);
+    // An invalid selection is rejected by rbac_middleware; record it without a cluster
+    let requested = requested_cluster_id(req.extensions(), req.headers(), req.uri())
+        .ok()
+        .flatten();
+    let cluster_id = resolve_cluster_id(&state.cluster_service, &route, &path, requested).await;
+
+    let mut parameters = Map::new();
+    if let Some(query) = req.uri().query() {
+        let query: Map<String, Value> = url_query_pairs(query)
+            .map(|(k, v)| (k, Value::String(v)))
+            .collect();
+        parameters.insert("query".to_string(), Value::Object(query));
+    }
+
+    let req = if body_is_capturable(req.body()) {
+        let (parts, body) = req.into_parts();
+        let bytes = to_bytes(body, MAX_CAPTURED_BODY)
+            .await
+            .map_err(|_| ApiError::validation_error("Failed to read request body"))?;
+        if !bytes.is_empty() {
+            parameters.insert("body".to_string(), parse_body(&bytes));
+        }
+        Request::from_parts(parts, Body::from(bytes))
+    } else {
+        parameters.insert("body".to_string(), Value::String("(not captured)".to_string()));
+        req
+    };
+
+    let started = Instant::now();
+    let response = next.run(req).await;
+    let duration_ms = started.elapsed().as_millis() as i64;
+
+    let status = response.status();
+    let (response, error_message) = if status.is_client_error() || status.is_server_error() {
+        read_error_message(response).await
+    } else {
+        (response, None)
+    };
+
+    let entry = NewAdminAuditEntry {
+        user_id: user.id,
+        username: user.username,
+        cluster_id,
+        method: method.to_string(),
+        action: action_name(&method, &route),
+        target: target(&route, &path),
+        route,
+        parameters: (!parameters.is_empty()).then(|| redact_parameters(Value::Object(parameters))),
+        status_code: status.as_u16(),
+        error_message,
+        duration_ms,
+        client_ip,
+        api_token_id: user.api_token.as_ref().map(|t| t.id),
+    };
+
+    // A failing audit write must not turn a completed action into an error
+    if let Err(e) = state.admin_audit_service.record(entry).await {
+        tracing::error!("Failed to write admin audit log: {:?}", e);
+    }
+
+    Ok(response)
+}
+
+/// Stable name of the action behind a route, used for filtering the audit log
+fn action_name(method: &Method, route: &str) -> String {
+    let action = match (method, route) {
+        (&Method::PUT, "/api/auth/me") => "profile.update",
+        (&Method::POST, "/api/auth/logout") => "auth.logout",
+        (&Method::POST, "/api/auth/tokens") => "api_token.create",
+        (&Method::DELETE, "/api/auth/tokens/:id") => "api_token.revoke",
+        // Users & permissions
+        (&Method::POST, "/api/users") => "user.create",
+        (&Method::POST, "/api/users/invitations") => "user.invite",
+        (&Method::DELETE, "/api/users/:id") => "user.delete",
+        (&Method::POST, "/api/users/:id/disable") => "user.disable",
+        (&Method::POST, "/api/users/:id/enable") => "user.enable",
+        (&Method::PUT, "/api/users/:id/password") => "user.reset_password",
+        (&Method::POST, "/api/users/:id/revoke-sessions") => "user.revoke_sessions",
+        (&Method::PUT, "/api/users/:id/role") => "permission.update_role",
+        (&Method::PUT, "/api/users/:id/cluster-roles/:cluster_id") => {
+            "permission.grant_cluster_role"
+        },
+        (&Method::DELETE, "/api/users/:id/cluster-roles/:cluster_id") => {
+            "permission.revoke_cluster_role"
+        },
+        // Clusters
+        (&Method::POST, "/api/clusters") => "cluster.create",
+        (&Method::PUT, "/api/clusters/:id") => "cluster.update",
+        (&Method::DELETE, "/api/clusters/:id") => "cluster.delete",
+        (&Method::PUT, "/api/clusters/:id/activate") => "cluster.activate",
+        (&Method::POST, "/api/clusters/health/test") => "cluster.test_connection",
+        (&Method::POST, "/api/clusters/:id/health") => "cluster.health_check",
+        // Cluster operations
+        (&Method::DELETE, "/api/clusters/backends/:host/:port") => "backend.drop",
+        (&Method::POST, "/api/clusters/queries/execute") => "sql.execute",
+        (&Method::POST, "/api/clusters/queries/stream") => "sql.stream",
+        (&Method::POST, "/api/clusters/queries/script") => "sql.script",
+        (&Method::POST, "/api/clusters/queries/explain") => "sql.explain",
+        (&Method::POST, "/api/clusters/queries/jobs") => "sql.submit_job",
+        (&Method::POST, "/api/clusters/queries/jobs/:job_id/cancel") => "query_job.cancel",
+        (&Method::DELETE, "/api/clusters/queries/cache") => "query_cache.invalidate",
+        (&Method::DELETE, "/api/clusters/queries/:query_id") => "query.kill",
+        (&Method::DELETE, "/api/clusters/sessions/:session_id") => "session.kill",
+        (&Method::PUT, "/api/clusters/variables/:variable_name") => "variable.update",
+        // Materialized views
+        (&Method::POST, "/api/clusters/materialized_views") => "materialized_view.create",
+        (&Method::PUT, "/api/clusters/materialized_views/:mv_name") => "materialized_view.alter",
+        (&Method::DELETE, "/api/clusters/materialized_views/:mv_name") => "materialized_view.drop",
+        (&Method::POST, "/api/clusters/materialized_views/:mv_name/refresh") => {
+            "materialized_view.refresh"
+        },
+        (&Method::POST, "/api/clusters/materialized_views/:mv_name/cancel") => {
+            "materialized_view.cancel_refresh"
+        },
+        // System functions
+        (&Method::POST, "/api/clusters/system-functions") => "system_function.create",
+        (&Method::PUT, "/api/clusters/system-functions/orders") => "system_function.reorder",
+        (&Method::POST, "/api/clusters/system-functions/:function_id/execute") => {
+            "system_function.execute"
+        },
+        (&Method::PUT, "/api/clusters/system-functions/:function_id/favorite") => {
+            "system_function.favorite"
+        },
+        (&Method::PUT, "/api/clusters/system-functions/:function_id") => "system_function.update",
+        (&Method::DELETE, "/api/clusters/system-functions/:function_id") => {
+            "system_function.delete"
+        },
+        (&Method::PUT, "/api/system-functions/:function_name/access-time") => {
+            "system_function.access"
+        },
+        (&Method::DELETE, "/api/system-functions/category/:category_name") => {
+            "system_function.delete_category"
+        },
+        // Configuration bundles
+        (&Method::POST, "/api/system/config/import") => "config.import",
+        (&Method::DELETE, "/api/system/query-cache") => "query_cache.clear",
+        _ => return format!("{} {}", method, route),
+    };
+    action.to_string()
+}
+
+/// Path parameters of the request, e.g. `query_id=abc` for `/api/clusters/queries/:query_id`
+fn target(route: &str, path: &str) -> Option<String> {
+    let params: Vec<String> = route
+        .split('/')
+        .zip(path.split('/'))
+        .filter_map(|(pattern, value)| {
+            pattern.strip_prefix(':').map(|name| {
+                let value = urlencoding::decode(value)
+                    .map(|v| v.into_owned())
+                    .unwrap_or_else(|_| value.to_string());
+                format!("{}={}", name, value)
+            })
+        })
+        .collect();
+
+    (!params.is_empty()).then(|| params.join(", "))
+}
+
+fn url_query_pairs(query: &str) -> impl Iterator<Item = (String, String)> + '_ {
+    query.split('&').filter(|p| !p.is_empty()).map(|pair| {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let decode = |s: &str| {
+            let s = s.replace('+', " ");
+            urlencoding::decode(&s).map(|v| v.into_owned()).unwrap_or(s)
+        };
+        (decode(key), decode(value))
+    })
+}
+
+/// Only bodies with a known, bounded length are buffered so uploads are never cut off
+fn body_is_capturable(body: &Body) -> bool {
+    body.size_hint()
+        .upper()
+        .is_some_and(|len| len <= MAX_CAPTURED_BODY as u64)
+}
+
+/// JSON or TOML (configuration bundles) body as a value whose secrets can be redacted
+fn parse_body(bytes: &[u8]) -> Value {
+    serde_json::from_slice(bytes)
+        .ok()
+        .or_else(|| toml::from_str(std::str::from_utf8(bytes).ok()?).ok())
+        .unwrap_or_else(|| Value::String(String::from_utf8_lossy(bytes).into_owned()))
+}
+
+/// Peer address, with the first `X-Forwarded-For` hop when behind a proxy
+fn client_ip(req: &Request) -> Option<String> {
+    let peer = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_string());
+    let forwarded = req
+        .headers()
+        .get("x-forwarded-for")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(',').next())
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+
+    match (forwarded, peer) {
+        (Some(forwarded), Some(peer)) => Some(format!("{} (via {})", forwarded, peer)),
+        (forwarded, peer) => forwarded.or(peer),
+    }
+}
+
+async fn read_error_message(response: Response) -> (Response, Option<String>) {
+    let (parts, body) = response.into_parts();
+    let Ok(bytes) = to_bytes(body, MAX_ERROR_BODY).await else {
+        return (Response::from_parts(parts, Body::empty()), None);
+    };
+
+    let message = serde_json::from_slice::<Value>(&bytes)
+        .ok()
+        .and_then(|v| v.get("message").and_then(Value::as_str).map(str::to_string))
+        .or_else(|| {
+            let text = String::from_utf8_lossy(&bytes).trim().to_string();
+            (!text.is_empty()).then_some(text)
+        })
+        .or_else(|| parts.status.canonical_reason().map(str::to_string));
+
+    (Response::from_parts(parts, Body::from(bytes)), message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_names() {
+        assert_eq!(action_name(&Method::DELETE, "/api/clusters/queries/:query_id"), "query.kill");
+        assert_eq!(
+            action_name(&Method::PUT, "/api/clusters/variables/:variable_name"),
+            "variable.update"
+        );
+        assert_eq!(action_name(&Method::POST, "/api/clusters/queries/execute"), "sql.execute");
+        assert_eq!(action_name(&Method::POST, "/api/clusters/queries/stream"), "sql.stream");
+        assert_eq!(action_name(&Method::POST, "/api/clusters/queries/script"), "sql.script");
+        assert_eq!(action_name(&Method::POST, "/api/clusters/queries/explain"), "sql.explain");
+        assert_eq!(action_name(&Method::POST, "/api/clusters/queries/jobs"), "sql.submit_job");
+        assert_eq!(
+            action_name(&Method::DELETE, "/api/clusters/queries/cache"),
+            "query_cache.invalidate"
+        );
+        assert_eq!(action_name(&Method::DELETE, "/api/system/query-cache"), "query_cache.clear");
+        assert_eq!(action_name(&Method::POST, "/api/new/route"), "POST /api/new/route");
+    }
+
+    #[test]
+    fn test_target_from_path_params() {
+        assert_eq!(
+            target("/api/clusters/backends/:host/:port", "/api/clusters/backends/10.0.0.1/9050"),
+            Some("host=10.0.0.1, port=9050".to_string())
+        );
+        assert_eq!(
+            target("/api/clusters/queries/:query_id", "/api/clusters/queries/a%2Fb"),
+            Some("query_id=a/b".to_string())
+        );
+        assert_eq!(target("/api/clusters", "/api/clusters"), None);
+    }
+
+    #[test]
+    fn test_query_pairs_are_decoded() {
+        let pairs: Vec<_> = url_query_pairs("database=my+db&if_exists=true&x=%3D1").collect();
+        assert_eq!(
+            pairs,
+            vec![
+                ("database".to_string(), "my db".to_string()),
+                ("if_exists".to_string(), "true".to_string()),
+                ("x".to_string(), "=1".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_toml_body_is_redacted() {
+        let body =
+            parse_body(b"version = 1\n[[clusters]]\nname = \"prod\"\npassword = \"s3cret\"\n");
+        let logged = redact_parameters(body);
+        assert!(logged.contains("prod"));
+        assert!(!logged.contains("s3cret"));
+        assert_eq!(parse_body(b"not json"), Value::String("not json".to_string()));
+    }
+}
@@ -6,14 +6,19 @@ use axum::{
 };
 use std::sync::Arc;
 
+use crate::models::{ApiTokenGrant, CurrentUser, User};
+use crate::services::{ApiTokenService, AuthService, SessionService};
 use crate::utils::{ApiError, JwtUtil};
 
 #[derive(Clone)]
 pub struct AuthState {
     pub jwt_util: Arc<JwtUtil>,
+    pub auth_service: Arc<AuthService>,
+    pub session_service: Arc<SessionService>,
+    pub api_token_service: Arc<ApiTokenService>,
 }
 
-// Extract user ID from JWT token
+// Extract user ID from JWT token or personal API token
 pub async fn auth_middleware(
     State(state): State<AuthState>,
     mut req: Request,
@@ -43,6 +48,47 @@ pub async fn auth_middleware(
         return Err(ApiError::unauthorized("Missing authorization header"));
     };
 
+    if ApiTokenService::is_api_token(token) {
+        let api_token = state
+            .api_token_service
+            .authenticate(token)
+            .await
+            .map_err(|e| {
+                tracing::warn!("API token rejected for {} {}: {:?}", method, uri, e);
+                e
+            })?;
+
+        let user = state.auth_service.get_user_by_id(api_token.user_id).await?;
+        if user.is_disabled {
+            tracing::warn!(
+                "Rejected API token of disabled user {} on {} {}",
+                user.username,
+                method,
+                uri
+            );
+            return Err(ApiError::unauthorized("User account is disabled"));
+        }
+        ensure_password_changed(&user, req.uri().path())?;
+        tracing::debug!(
+            "API token {} accepted for user {} on {} {}",
+            api_token.id,
+            user.username,
+            method,
+            uri
+        );
+
+        // No Claims here: there is no session to refresh or log out of
+        req.extensions_mut().insert(user.id);
+        req.extensions_mut().insert(user.username.clone());
+        req.extensions_mut().insert(CurrentUser {
+            id: user.id,
+            username: user.username,
+            role: user.role,
+            api_token: Some(ApiTokenGrant { id: api_token.id, max_role: api_token.max_role() }),
+        });
+        return Ok(next.run(req).await);
+    }
+
     tracing::debug!("Verifying JWT token for {} {}", method, uri);
     // Verify token
     let claims = state.jwt_util.verify_token(token).map_err(|e| {
@@ -59,13 +105,47 @@ pub async fn auth_middleware(
         uri
     );
 
+    if state.session_service.is_token_revoked(&claims).await? {
+        tracing::warn!("Rejected revoked token of user {} on {} {}", claims.username, method, uri);
+        return Err(ApiError::unauthorized("Token has been revoked"));
+    }
+
+    // Re-check the account so deleted/disabled users and role changes take effect immediately
+    let user = state.auth_service.get_user_by_id(user_id).await?;
+    if user.is_disabled {
+        tracing::warn!(
+            "Rejected request from disabled user {} on {} {}",
+            user.username,
+            method,
+            uri
+        );
+        return Err(ApiError::unauthorized("User account is disabled"));
+    }
+    ensure_password_changed(&user, req.uri().path())?;
+
     // Add user ID to request extensions
     req.extensions_mut().insert(user_id);
-    req.extensions_mut().insert(claims.username.clone());
+    req.extensions_mut().insert(user.username.clone());
+    req.extensions_mut().insert(claims);
+    req.extensions_mut().insert(CurrentUser {
+        id: user.id,
+        username: user.username,
+        role: user.role,
+        api_token: None,
+    });
 
     Ok(next.run(req).await)
 }
 
+/// Until a forced password change is done, only the own profile and logout are reachable
+fn ensure_password_changed(user: &User, path: &str) -> Result<(), ApiError> {
+    if user.must_change_password && !matches!(path, "/api/auth/me" | "/api/auth/logout") {
+        tracing::debug!("User {} must change the password before calling {}", user.username, path);
+        return Err(ApiError::PasswordChangeRequired);
+    }
+    Ok(())
+}
+
 // Extract user ID from request extensions
 #[allow(dead_code)]
 pub fn get_user_id_from_request(req: &Request) -> Option<i64> {
@@ -0,0 +1,162 @@
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Request},
+    http::{Extensions, HeaderMap, Uri, request::Parts},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+
+use crate::AppState;
+use crate::models::Cluster;
+use crate::utils::{ApiError, ApiResult};
+
+/// Header selecting the cluster a request operates on
+pub const CLUSTER_ID_HEADER: &str = "x-cluster-id";
+
+/// First segments after `/api/clusters/:id` that belong to the cluster registry itself
+const REGISTRY_ROUTES: &[&str] = &["activate", "health", "topology"];
+
+/// Cluster chosen by a scoped route (`/api/clusters/:id/...`)
+#[derive(Debug, Clone, Copy)]
+struct ScopedCluster(i64);
+
+// Map cluster-scoped routes onto the legacy routes, e.g.
+// `/api/clusters/3/queries/execute` to `/api/clusters/queries/execute` on cluster 3.
+// Must wrap the router: rewriting inside a route layer would happen after routing.
+pub async fn scoped_cluster_routes(mut req: Request, next: Next) -> Response {
+    if let Some((cluster_id, uri)) = unscoped_uri(req.uri()) {
+        tracing::debug!(
+            "Scoped route {} -> {} on cluster {}",
+            req.uri().path(),
+            uri.path(),
+            cluster_id
+        );
+        *req.uri_mut() = uri;
+        req.extensions_mut().insert(ScopedCluster(cluster_id));
+    }
+    next.run(req).await
+}
+
+fn unscoped_uri(uri: &Uri) -> Option<(i64, Uri)> {
+    let rest = uri.path().strip_prefix("/api/clusters/")?;
+    let (id, rest) = rest.split_once('/')?;
+    let cluster_id = id.parse::<i64>().ok()?;
+
+    let first = rest.split('/').next().unwrap_or_default();
+    if first.is_empty() || REGISTRY_ROUTES.contains(&first) {
+        return None;
+    }
+
+    let path_and_query = match uri.query() {
+        Some(query) => format!("/api/clusters/{}?{}", rest, query),
+        None => format!("/api/clusters/{}", rest),
+    };
+    path_and_query.parse().ok().map(|uri| (cluster_id, uri))
+}
+
+/// Cluster explicitly requested for this request, if any
+///
+/// Sources in order: scoped route, `X-Cluster-Id` header, `cluster_id` query parameter.
+/// Requests without a selection fall back to the active (default) cluster.
+pub fn requested_cluster_id(
+    extensions: &Extensions,
+    headers: &HeaderMap,
+    uri: &Uri,
+) -> ApiResult<Option<i64>> {
+    if let Some(ScopedCluster(id)) = extensions.get::<ScopedCluster>() {
+        return Ok(Some(*id));
+    }
+
+    if let Some(value) = headers.get(CLUSTER_ID_HEADER) {
+        return value
+            .to_str()
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .map(Some)
+            .ok_or_else(|| ApiError::validation_error("Invalid X-Cluster-Id header"));
+    }
+
+    let query_value = uri.query().and_then(|query| {
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == "cluster_id")
+            .map(|(_, value)| value)
+    });
+    match query_value {
+        Some(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|_| ApiError::validation_error("Invalid cluster_id parameter")),
+        None => Ok(None),
+    }
+}
+
+/// The cluster a handler operates on: the requested one, else the active cluster
+pub struct SelectedCluster(pub Cluster);
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for SelectedCluster {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let requested = requested_cluster_id(&parts.extensions, &parts.headers, &parts.uri)?;
+        let cluster = state.cluster_service.resolve_cluster(requested).await?;
+        Ok(Self(cluster))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scoped_routes_are_rewritten() {
+        let rewrite =
+            |uri: &str| unscoped_uri(&uri.parse().unwrap()).map(|(id, uri)| (id, uri.to_string()));
+
+        assert_eq!(
+            rewrite("/api/clusters/3/queries/execute"),
+            Some((3, "/api/clusters/queries/execute".to_string()))
+        );
+        assert_eq!(
+            rewrite("/api/clusters/12/overview?time_range=1h"),
+            Some((12, "/api/clusters/overview?time_range=1h".to_string()))
+        );
+        assert_eq!(rewrite("/api/clusters/3"), None);
+        assert_eq!(rewrite("/api/clusters/3/"), None);
+        assert_eq!(rewrite("/api/clusters/3/health"), None);
+        assert_eq!(rewrite("/api/clusters/3/activate"), None);
+        assert_eq!(rewrite("/api/clusters/3/topology/changes"), None);
+        assert_eq!(rewrite("/api/clusters/backends/host1/9050"), None);
+        assert_eq!(rewrite("/api/users/3/role"), None);
+    }
+
+    #[test]
+    fn test_requested_cluster_id_precedence() {
+        let uri: Uri = "/api/clusters/queries?cluster_id=7".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        let mut extensions = Extensions::new();
+
+        assert_eq!(requested_cluster_id(&extensions, &headers, &uri).unwrap(), Some(7));
+
+        headers.insert(CLUSTER_ID_HEADER, "5".parse().unwrap());
+        assert_eq!(requested_cluster_id(&extensions, &headers, &uri).unwrap(), Some(5));
+
+        extensions.insert(ScopedCluster(3));
+        assert_eq!(requested_cluster_id(&extensions, &headers, &uri).unwrap(), Some(3));
+
+        let plain: Uri = "/api/clusters/queries".parse().unwrap();
+        assert_eq!(
+            requested_cluster_id(&Extensions::new(), &HeaderMap::new(), &plain).unwrap(),
+            None
+        );
+
+        headers.insert(CLUSTER_ID_HEADER, "prod".parse().unwrap());
+        assert!(requested_cluster_id(&Extensions::new(), &headers, &plain).is_err());
+    }
+}
@@ -1,3 +1,9 @@
+pub mod audit;
 pub mod auth;
+pub mod cluster_selection;
+pub mod rbac;
 
+pub use audit::{AuditState, audit_middleware};
 pub use auth::{AuthState, auth_middleware};
+pub use cluster_selection::{SelectedCluster, scoped_cluster_routes};
+pub use rbac::{RbacState, rbac_middleware};
@@ -0,0 +1,230 @@
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::Method,
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+
+use crate::middleware::cluster_selection::requested_cluster_id;
+use crate::models::{CurrentUser, UserRole};
+use crate::services::{ClusterService, PermissionService};
+use crate::utils::ApiError;
+
+#[derive(Clone)]
+pub struct RbacState {
+    pub permission_service: Arc<PermissionService>,
+    pub cluster_service: Arc<ClusterService>,
+}
+
+/// Which cluster a route operates on, used to look up per-cluster grants
+#[derive(Debug, PartialEq)]
+enum ClusterScope {
+    /// Not tied to a cluster (cluster registry, user management, ...)
+    Global,
+    /// Cluster given by the `:id` path segment
+    Cluster(i64),
+    /// Routes that act on the selected cluster (scoped route, header or query),
+    /// falling back to the active cluster
+    Selected,
+}
+
+// Enforce role requirements; must run after auth_middleware
+pub async fn rbac_middleware(
+    State(state): State<RbacState>,
+    req: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let user = req
+        .extensions()
+        .get::<CurrentUser>()
+        .cloned()
+        .ok_or_else(|| ApiError::unauthorized("Missing authenticated user"))?;
+
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| req.uri().path().to_string());
+
+    let required = required_role(req.method(), &route);
+    let requested = requested_cluster_id(req.extensions(), req.headers(), req.uri())?;
+    let cluster_id =
+        resolve_cluster_id(&state.cluster_service, &route, req.uri().path(), requested).await;
+
+    tracing::debug!(
+        "RBAC check: {} {} requires {} (user={}, cluster={:?})",
+        req.method(),
+        route,
+        required,
+        user.username,
+        cluster_id
+    );
+
+    state
+        .permission_service
+        .authorize(&user, required, cluster_id)
+        .await?;
+
+    Ok(next.run(req).await)
+}
+
+/// Cluster a request operates on, `None` for global routes
+///
+/// Cluster routes resolve to the requested cluster, else the active cluster; without
+/// one the handler reports the error and only the global role applies.
+pub(crate) async fn resolve_cluster_id(
+    cluster_service: &ClusterService,
+    route: &str,
+    path: &str,
+    requested: Option<i64>,
+) -> Option<i64> {
+    match cluster_scope(route, path) {
+        ClusterScope::Global => None,
+        ClusterScope::Cluster(id) => Some(id),
+        ClusterScope::Selected => match requested {
+            Some(id) => Some(id),
+            None => cluster_service
+                .get_active_cluster()
+                .await
+                .ok()
+                .map(|c| c.id),
+        },
+    }
+}
+
+/// Minimum role needed to call a route
+fn required_role(method: &Method, route: &str) -> UserRole {
+    match (method, route) {
+        // User and permission management
+        (_, r) if r.starts_with("/api/users") => UserRole::Admin,
+        // Audit trail
+        (_, r) if r.starts_with("/api/audit") => UserRole::Admin,
+        // Server internals such as connection pools
+        (_, r) if r.starts_with("/api/system/") => UserRole::Admin,
+        // Cluster registry and destructive cluster-wide operations
+        (&Method::POST, "/api/clusters")
+        | (&Method::POST, "/api/clusters/discover")
+        | (&Method::POST, "/api/clusters/import")
+        | (&Method::PUT, "/api/clusters/:id")
+        | (&Method::DELETE, "/api/clusters/:id")
+        | (&Method::DELETE, "/api/clusters/backends/:host/:port")
+        | (&Method::PUT, "/api/clusters/variables/:variable_name")
+        | (&Method::POST, "/api/cluster-groups")
+        | (&Method::PUT, "/api/cluster-groups/:id")
+        | (&Method::DELETE, "/api/cluster-groups/:id") => UserRole::Admin,
+        // Group fan-outs check the role on each member cluster themselves
+        (&Method::POST, "/api/cluster-groups/:id/health")
+        | (&Method::POST, "/api/cluster-groups/:id/query") => UserRole::Viewer,
+        // Read-only requests (health checks are POST but do not change anything)
+        (&Method::GET, _)
+        | (&Method::POST, "/api/clusters/:id/health")
+        | (&Method::POST, "/api/clusters/:id/topology/refresh")
+        | (&Method::POST, "/api/clusters/health/test") => UserRole::Viewer,
+        // Everyone may manage their own profile, session and API tokens
+        (_, r) if r.starts_with("/api/auth/") => UserRole::Viewer,
+        // Everything else mutates cluster state: kill queries/sessions, execute SQL, MVs, ...
+        _ => UserRole::Operator,
+    }
+}
+
+fn cluster_scope(route: &str, path: &str) -> ClusterScope {
+    if route.starts_with("/api/clusters/:id") {
+        return path
+            .split('/')
+            .nth(3)
+            .and_then(|id| id.parse().ok())
+            .map(ClusterScope::Cluster)
+            .unwrap_or(ClusterScope::Global);
+    }
+
+    match route {
+        "/api/clusters"
+        | "/api/clusters/active"
+        | "/api/clusters/health/test"
+        | "/api/clusters/discover"
+        | "/api/clusters/import" => ClusterScope::Global,
+        r if r.starts_with("/api/clusters/") => ClusterScope::Selected,
+        // System function routes outside /api/clusters act on the selected cluster too
+        r if r.starts_with("/api/system-functions/") => ClusterScope::Selected,
+        _ => ClusterScope::Global,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_required_role() {
+        assert_eq!(required_role(&Method::GET, "/api/clusters/queries"), UserRole::Viewer);
+        assert_eq!(required_role(&Method::POST, "/api/clusters/:id/health"), UserRole::Viewer);
+        assert_eq!(
+            required_role(&Method::DELETE, "/api/clusters/queries/:query_id"),
+            UserRole::Operator
+        );
+        assert_eq!(
+            required_role(&Method::POST, "/api/clusters/queries/execute"),
+            UserRole::Operator
+        );
+        assert_eq!(
+            required_role(&Method::DELETE, "/api/clusters/backends/:host/:port"),
+            UserRole::Admin
+        );
+        assert_eq!(
+            required_role(&Method::PUT, "/api/clusters/variables/:variable_name"),
+            UserRole::Admin
+        );
+        assert_eq!(required_role(&Method::DELETE, "/api/clusters/:id"), UserRole::Admin);
+        assert_eq!(required_role(&Method::GET, "/api/users/:id/permissions"), UserRole::Admin);
+        assert_eq!(required_role(&Method::PUT, "/api/auth/me"), UserRole::Viewer);
+        assert_eq!(required_role(&Method::POST, "/api/auth/logout"), UserRole::Viewer);
+        assert_eq!(required_role(&Method::DELETE, "/api/auth/tokens/:id"), UserRole::Viewer);
+        assert_eq!(required_role(&Method::GET, "/api/audit/export"), UserRole::Admin);
+        assert_eq!(required_role(&Method::GET, "/api/system/pools"), UserRole::Admin);
+        assert_eq!(required_role(&Method::POST, "/api/cluster-groups"), UserRole::Admin);
+        assert_eq!(required_role(&Method::POST, "/api/clusters/import"), UserRole::Admin);
+        assert_eq!(
+            required_role(&Method::POST, "/api/clusters/:id/topology/refresh"),
+            UserRole::Viewer
+        );
+        assert_eq!(required_role(&Method::DELETE, "/api/cluster-groups/:id"), UserRole::Admin);
+        assert_eq!(required_role(&Method::POST, "/api/cluster-groups/:id/query"), UserRole::Viewer);
+    }
+
+    #[test]
+    fn test_cluster_scope() {
+        assert_eq!(cluster_scope("/api/clusters/:id", "/api/clusters/7"), ClusterScope::Cluster(7));
+        assert_eq!(
+            cluster_scope("/api/clusters/:id/health", "/api/clusters/3/health"),
+            ClusterScope::Cluster(3)
+        );
+        assert_eq!(cluster_scope("/api/clusters", "/api/clusters"), ClusterScope::Global);
+        assert_eq!(
+            cluster_scope("/api/clusters/discover", "/api/clusters/discover"),
+            ClusterScope::Global
+        );
+        assert_eq!(
+            cluster_scope("/api/clusters/queries/:query_id", "/api/clusters/queries/abc"),
+            ClusterScope::Selected
+        );
+        assert_eq!(
+            cluster_scope(
+                "/api/system-functions/:function_name/access-time",
+                "/api/system-functions/x/access-time"
+            ),
+            ClusterScope::Selected
+        );
+        assert_eq!(
+            cluster_scope(
+                "/api/system-functions/category/:category_name",
+                "/api/system-functions/category/ops"
+            ),
+            ClusterScope::Selected
+        );
+        assert_eq!(
+            cluster_scope("/api/cluster-groups/:id/health", "/api/cluster-groups/2/health"),
+            ClusterScope::Global
+        );
+    }
+}
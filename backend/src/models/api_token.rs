@@ -0,0 +1,154 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::str::FromStr;
+use utoipa::ToSchema;
+
+use crate::models::UserRole;
+
+/// What an API token may do; a token never exceeds the role of its owner
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiTokenScope {
+    /// Read-only requests (viewer)
+    Read,
+    /// Day-to-day operations such as killing queries or executing SQL (operator)
+    Write,
+    /// Everything the owner may do (admin)
+    Admin,
+}
+
+impl ApiTokenScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Write => "write",
+            Self::Admin => "admin",
+        }
+    }
+
+    /// Highest role this scope allows
+    pub fn max_role(&self) -> UserRole {
+        match self {
+            Self::Read => UserRole::Viewer,
+            Self::Write => UserRole::Operator,
+            Self::Admin => UserRole::Admin,
+        }
+    }
+}
+
+impl FromStr for ApiTokenScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "read" => Ok(Self::Read),
+            "write" => Ok(Self::Write),
+            "admin" => Ok(Self::Admin),
+            other => Err(format!("Unknown scope '{}'. Must be read, write or admin", other)),
+        }
+    }
+}
+
+/// Stored API token (without the secret)
+#[derive(Debug, Clone, FromRow)]
+pub struct ApiToken {
+    pub id: i64,
+    pub user_id: i64,
+    pub name: String,
+    pub token_prefix: String,
+    /// Comma separated scopes
+    pub scopes: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ApiToken {
+    pub fn scopes(&self) -> Vec<ApiTokenScope> {
+        self.scopes
+            .split(',')
+            .filter_map(|s| s.parse().ok())
+            .collect()
+    }
+
+    /// Highest role granted by the scopes of this token
+    pub fn max_role(&self) -> UserRole {
+        self.scopes()
+            .iter()
+            .map(ApiTokenScope::max_role)
+            .max()
+            .unwrap_or(UserRole::Viewer)
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiTokenResponse {
+    pub id: i64,
+    pub name: String,
+    /// First characters of the token, to recognize it
+    pub token_prefix: String,
+    pub scopes: Vec<ApiTokenScope>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<ApiToken> for ApiTokenResponse {
+    fn from(token: ApiToken) -> Self {
+        Self {
+            scopes: token.scopes(),
+            id: token.id,
+            name: token.name,
+            token_prefix: token.token_prefix,
+            expires_at: token.expires_at,
+            last_used_at: token.last_used_at,
+            created_at: token.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateApiTokenRequest {
+    pub name: String,
+    #[serde(default = "default_scopes")]
+    pub scopes: Vec<ApiTokenScope>,
+    /// Days until the token expires; omit for a token that never expires
+    pub expires_in_days: Option<i64>,
+}
+
+fn default_scopes() -> Vec<ApiTokenScope> {
+    vec![ApiTokenScope::Read]
+}
+
+/// Newly created token; the secret is only returned here
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreateApiTokenResponse {
+    /// Send as `Authorization: Bearer <token>`
+    pub token: String,
+    pub api_token: ApiTokenResponse,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_role_of_scopes() {
+        let token = |scopes: &str| ApiToken {
+            id: 1,
+            user_id: 1,
+            name: "ci".into(),
+            token_prefix: "sra_1234".into(),
+            scopes: scopes.into(),
+            expires_at: None,
+            last_used_at: None,
+            created_at: Utc::now(),
+        };
+
+        assert_eq!(token("read").max_role(), UserRole::Viewer);
+        assert_eq!(token("read,write").max_role(), UserRole::Operator);
+        assert_eq!(token("admin").max_role(), UserRole::Admin);
+        assert_eq!(token("bogus").max_role(), UserRole::Viewer);
+    }
+}
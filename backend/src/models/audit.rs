@@ -0,0 +1,76 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+
+/// One mutating API call recorded by the audit middleware
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct AdminAuditEntry {
+    pub id: i64,
+    pub user_id: i64,
+    pub username: String,
+    pub cluster_id: Option<i64>,
+    pub method: String,
+    pub route: String,
+    /// Stable action name, e.g. `query.kill` or `variable.update`
+    pub action: String,
+    /// Path parameters identifying the affected object, e.g. `query_id=abc`
+    pub target: Option<String>,
+    /// Request query and body as JSON, secrets redacted
+    pub parameters: Option<String>,
+    pub status_code: i64,
+    pub success: bool,
+    pub error_message: Option<String>,
+    pub duration_ms: i64,
+    pub client_ip: Option<String>,
+    /// API token the call was made with, if not a login session
+    pub api_token_id: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Audit entry before it is stored
+#[derive(Debug, Clone, Default)]
+pub struct NewAdminAuditEntry {
+    pub user_id: i64,
+    pub username: String,
+    pub cluster_id: Option<i64>,
+    pub method: String,
+    pub route: String,
+    pub action: String,
+    pub target: Option<String>,
+    pub parameters: Option<String>,
+    pub status_code: u16,
+    pub error_message: Option<String>,
+    pub duration_ms: i64,
+    pub client_ip: Option<String>,
+    pub api_token_id: Option<i64>,
+}
+
+/// Filters for searching the audit log; all are optional and combined with AND
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AdminAuditQuery {
+    /// Exact username
+    pub username: Option<String>,
+    /// Action name or prefix, e.g. `query` matches `query.kill`
+    pub action: Option<String>,
+    pub cluster_id: Option<i64>,
+    pub success: Option<bool>,
+    /// Substring of target, route or parameters
+    pub search: Option<String>,
+    /// Inclusive lower bound (RFC 3339)
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound (RFC 3339)
+    pub to: Option<DateTime<Utc>>,
+    /// 1-based page number (default 1)
+    pub page: Option<i64>,
+    /// Entries per page (default 50, max 500)
+    pub page_size: Option<i64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AdminAuditLogResponse {
+    pub data: Vec<AdminAuditEntry>,
+    pub total: i64,
+    pub page: i64,
+    pub page_size: i64,
+}
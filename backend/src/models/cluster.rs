@@ -3,6 +3,12 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use utoipa::ToSchema;
 
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+
+use crate::models::SqlPolicy;
+use crate::utils::{ApiError, ApiResult, crypto};
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct Cluster {
     pub id: i64,
@@ -11,11 +17,25 @@ pub struct Cluster {
     pub fe_host: String,
     pub fe_http_port: i32,
     pub fe_query_port: i32,
+    /// Additional FEs as a JSON array of `FeEndpoint`
+    pub fe_endpoints: Option<String>,
     pub username: String,
     #[serde(skip_serializing)]
     pub password_encrypted: String,
     pub enable_ssl: bool,
+    /// PEM bundle of trusted CAs; system roots when unset
+    pub tls_ca_cert: Option<String>,
+    /// Base64 PKCS#12 client certificate and key, envelope-encrypted
+    #[serde(skip_serializing)]
+    pub tls_client_identity_encrypted: Option<String>,
+    #[serde(skip_serializing)]
+    pub tls_client_identity_password_encrypted: Option<String>,
+    pub tls_verify_hostname: bool,
     pub connection_timeout: i32,
+    /// MySQL pool overrides as a JSON object of `PoolSettings`
+    pub pool_settings: Option<String>,
+    /// SQL restrictions as a JSON object of `SqlPolicy`
+    pub sql_policy: Option<String>,
     pub tags: Option<String>,
     pub catalog: String,
     pub is_active: bool,
@@ -24,6 +44,51 @@ pub struct Cluster {
     pub created_by: Option<i64>,
 }
 
+/// Decrypted TLS settings of a cluster, shared by the HTTP client and the MySQL pools
+pub struct ClusterTls {
+    /// PEM bundle of trusted CAs; system roots when `None`
+    pub ca_cert_pem: Option<String>,
+    /// PKCS#12 archive (DER) with the client certificate and key
+    pub client_identity: Option<Vec<u8>>,
+    pub client_identity_password: String,
+    pub verify_hostname: bool,
+}
+
+/// Address of one FE of a cluster
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+pub struct FeEndpoint {
+    pub host: String,
+    pub http_port: i32,
+    pub query_port: i32,
+}
+
+/// Per-cluster overrides of the `[mysql_pool]` defaults; unset fields use the defaults
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+pub struct PoolSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_connections: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_connections: Option<usize>,
+    /// Connections above the minimum are closed after being idle this long
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_timeout_secs: Option<u64>,
+    /// Connections are reopened after this age
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_lifetime_secs: Option<u64>,
+}
+
+impl PoolSettings {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl std::fmt::Display for FeEndpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.host, self.http_port)
+    }
+}
+
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateClusterRequest {
     pub name: String,
@@ -33,12 +98,26 @@ pub struct CreateClusterRequest {
     pub fe_http_port: i32,
     #[serde(default = "default_query_port")]
     pub fe_query_port: i32,
+    /// Other FEs to fail over to when the primary FE is unreachable
+    #[serde(default)]
+    pub fe_endpoints: Vec<FeEndpoint>,
     pub username: String,
     pub password: String,
     #[serde(default)]
     pub enable_ssl: bool,
+    /// PEM bundle of CAs trusted for HTTPS and MySQL TLS
+    pub tls_ca_cert: Option<String>,
+    /// Base64 PKCS#12 archive with the client certificate and key
+    pub tls_client_identity: Option<String>,
+    pub tls_client_identity_password: Option<String>,
+    #[serde(default = "default_verify_hostname")]
+    pub tls_verify_hostname: bool,
     #[serde(default = "default_timeout")]
     pub connection_timeout: i32,
+    #[serde(default)]
+    pub pool_settings: PoolSettings,
+    #[serde(default)]
+    pub sql_policy: SqlPolicy,
     pub tags: Option<Vec<String>>,
     #[serde(default = "default_catalog")]
     pub catalog: String,
@@ -51,10 +130,21 @@ pub struct UpdateClusterRequest {
     pub fe_host: Option<String>,
     pub fe_http_port: Option<i32>,
     pub fe_query_port: Option<i32>,
+    pub fe_endpoints: Option<Vec<FeEndpoint>>,
     pub username: Option<String>,
     pub password: Option<String>,
     pub enable_ssl: Option<bool>,
+    /// Empty string removes the CA bundle
+    pub tls_ca_cert: Option<String>,
+    /// Empty string removes the client certificate
+    pub tls_client_identity: Option<String>,
+    pub tls_client_identity_password: Option<String>,
+    pub tls_verify_hostname: Option<bool>,
     pub connection_timeout: Option<i32>,
+    /// Replaces all overrides; `{}` restores the defaults
+    pub pool_settings: Option<PoolSettings>,
+    /// Replaces the policy; `{}` removes all restrictions
+    pub sql_policy: Option<SqlPolicy>,
     pub tags: Option<Vec<String>>,
     pub catalog: Option<String>,
 }
@@ -67,9 +157,15 @@ pub struct ClusterResponse {
     pub fe_host: String,
     pub fe_http_port: i32,
     pub fe_query_port: i32,
+    pub fe_endpoints: Vec<FeEndpoint>,
     pub username: String,
     pub enable_ssl: bool,
+    pub tls_ca_cert: Option<String>,
+    pub tls_client_identity_configured: bool,
+    pub tls_verify_hostname: bool,
     pub connection_timeout: i32,
+    pub pool_settings: PoolSettings,
+    pub sql_policy: SqlPolicy,
     pub tags: Vec<String>,
     pub catalog: String,
     pub is_active: bool,
@@ -93,35 +189,212 @@ pub enum HealthStatus {
     Unknown,
 }
 
+/// Severity of a single check, ordered from harmless to worst
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Ok,
+    /// Worth knowing, but does not degrade the cluster status
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+impl From<Severity> for HealthStatus {
+    fn from(severity: Severity) -> Self {
+        match severity {
+            Severity::Ok | Severity::Info => Self::Healthy,
+            Severity::Warning => Self::Warning,
+            Severity::Critical => Self::Critical,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, ToSchema)]
 pub struct HealthCheck {
     pub name: String,
+    /// Severity as text: `ok`, `info`, `warning` or `critical`
     pub status: String,
     pub message: String,
+    /// Part of the cluster the check looks at, e.g. `fe`, `be` or `tablet`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub component: Option<String>,
+    /// Nodes or databases the finding applies to
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub affected: Vec<String>,
+    /// What to do about a failed check
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<String>,
+}
+
+impl HealthCheck {
+    pub fn new(name: impl Into<String>, severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status: severity.as_str().to_string(),
+            message: message.into(),
+            component: None,
+            affected: Vec::new(),
+            remediation: None,
+        }
+    }
+
+    pub fn component(mut self, component: &str) -> Self {
+        self.component = Some(component.to_string());
+        self
+    }
+
+    pub fn affected(mut self, affected: Vec<String>) -> Self {
+        self.affected = affected;
+        self
+    }
+
+    pub fn remediation(mut self, remediation: impl Into<String>) -> Self {
+        self.remediation = Some(remediation.into());
+        self
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self.status.as_str() {
+            "ok" => Severity::Ok,
+            "info" => Severity::Info,
+            "critical" => Severity::Critical,
+            _ => Severity::Warning,
+        }
+    }
 }
 
-fn default_http_port() -> i32 {
+impl Cluster {
+    /// Decrypted StarRocks password; never serialize or log the result
+    pub fn password(&self) -> ApiResult<String> {
+        crypto::decrypt_secret(&self.password_encrypted)
+    }
+
+    /// TLS settings when `enable_ssl` is on; never serialize or log the result
+    pub fn tls(&self) -> ApiResult<Option<ClusterTls>> {
+        if !self.enable_ssl {
+            return Ok(None);
+        }
+
+        let client_identity = match &self.tls_client_identity_encrypted {
+            Some(stored) => {
+                let encoded = crypto::decrypt_secret(stored)?;
+                Some(BASE64.decode(encoded.trim()).map_err(|_| {
+                    ApiError::internal_error("Stored TLS client certificate is not valid base64")
+                })?)
+            },
+            None => None,
+        };
+        let client_identity_password = match &self.tls_client_identity_password_encrypted {
+            Some(stored) => crypto::decrypt_secret(stored)?,
+            None => String::new(),
+        };
+
+        Ok(Some(ClusterTls {
+            ca_cert_pem: self.tls_ca_cert.clone(),
+            client_identity,
+            client_identity_password,
+            verify_hostname: self.tls_verify_hostname,
+        }))
+    }
+
+    /// The FE configured by `fe_host` / `fe_http_port` / `fe_query_port`
+    pub fn primary_fe(&self) -> FeEndpoint {
+        FeEndpoint {
+            host: self.fe_host.clone(),
+            http_port: self.fe_http_port,
+            query_port: self.fe_query_port,
+        }
+    }
+
+    /// Additional FEs stored with the cluster
+    pub fn additional_fes(&self) -> Vec<FeEndpoint> {
+        self.fe_endpoints
+            .as_deref()
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Tags stored as a JSON array; empty when absent or unreadable
+    pub fn tag_list(&self) -> Vec<String> {
+        self.tags
+            .as_deref()
+            .and_then(|t| serde_json::from_str(t).ok())
+            .unwrap_or_default()
+    }
+
+    /// Whether the cluster carries every one of `tags` (case-insensitive)
+    pub fn has_tags(&self, tags: &[String]) -> bool {
+        let own = self.tag_list();
+        tags.iter()
+            .all(|tag| own.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+    }
+
+    /// MySQL pool overrides stored with the cluster
+    pub fn pool_settings(&self) -> PoolSettings {
+        self.pool_settings
+            .as_deref()
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default()
+    }
+
+    /// SQL restrictions stored with the cluster
+    pub fn sql_policy(&self) -> SqlPolicy {
+        self.sql_policy
+            .as_deref()
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default()
+    }
+
+    /// All configured FEs, primary first, without duplicates
+    pub fn configured_fes(&self) -> Vec<FeEndpoint> {
+        let mut endpoints = vec![self.primary_fe()];
+        for endpoint in self.additional_fes() {
+            if !endpoints.contains(&endpoint) {
+                endpoints.push(endpoint);
+            }
+        }
+        endpoints
+    }
+}
+
+pub(crate) fn default_http_port() -> i32 {
     8030
 }
 
-fn default_query_port() -> i32 {
+pub(crate) fn default_query_port() -> i32 {
     9030
 }
 
-fn default_timeout() -> i32 {
+pub(crate) fn default_verify_hostname() -> bool {
+    true
+}
+
+pub(crate) fn default_timeout() -> i32 {
     10
 }
 
-fn default_catalog() -> String {
+pub(crate) fn default_catalog() -> String {
     "default_catalog".to_string()
 }
 
 impl From<Cluster> for ClusterResponse {
     fn from(cluster: Cluster) -> Self {
-        let tags = cluster
-            .tags
-            .and_then(|t| serde_json::from_str(&t).ok())
-            .unwrap_or_default();
+        let fe_endpoints = cluster.additional_fes();
+        let pool_settings = cluster.pool_settings();
+        let sql_policy = cluster.sql_policy();
+        let tags = cluster.tag_list();
 
         Self {
             id: cluster.id,
@@ -130,9 +403,15 @@ impl From<Cluster> for ClusterResponse {
             fe_host: cluster.fe_host,
             fe_http_port: cluster.fe_http_port,
             fe_query_port: cluster.fe_query_port,
+            fe_endpoints,
             username: cluster.username,
             enable_ssl: cluster.enable_ssl,
+            tls_ca_cert: cluster.tls_ca_cert,
+            tls_client_identity_configured: cluster.tls_client_identity_encrypted.is_some(),
+            tls_verify_hostname: cluster.tls_verify_hostname,
             connection_timeout: cluster.connection_timeout,
+            pool_settings,
+            sql_policy,
             tags,
             catalog: cluster.catalog,
             is_active: cluster.is_active,
@@ -141,3 +420,91 @@ impl From<Cluster> for ClusterResponse {
         }
     }
 }
+
+#[cfg(test)]
+impl Cluster {
+    /// Cluster `prod` (id 1) with the single FE `fe1` and nothing else configured
+    ///
+    /// Tests set the fields they care about with struct update syntax,
+    /// e.g. `Cluster { id: 7, ..Cluster::for_test() }`.
+    pub fn for_test() -> Self {
+        Cluster {
+            id: 1,
+            name: "prod".to_string(),
+            description: None,
+            fe_host: "fe1".to_string(),
+            fe_http_port: 8030,
+            fe_query_port: 9030,
+            fe_endpoints: None,
+            username: "root".to_string(),
+            password_encrypted: String::new(),
+            enable_ssl: false,
+            tls_ca_cert: None,
+            tls_client_identity_encrypted: None,
+            tls_client_identity_password_encrypted: None,
+            tls_verify_hostname: true,
+            connection_timeout: 10,
+            pool_settings: None,
+            sql_policy: None,
+            tags: None,
+            catalog: "default_catalog".to_string(),
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            created_by: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cluster_serialization_never_leaks_password() {
+        let cluster = Cluster {
+            password_encrypted: "super-secret-password".to_string(),
+            tls_client_identity_encrypted: Some("super-secret-identity".to_string()),
+            tls_client_identity_password_encrypted: Some("super-secret-p12".to_string()),
+            ..Cluster::for_test()
+        };
+
+        // TLS settings only apply with enable_ssl
+        assert!(cluster.tls().unwrap().is_none());
+
+        let raw = serde_json::to_string(&cluster).unwrap();
+        let response = serde_json::to_string(&ClusterResponse::from(cluster)).unwrap();
+
+        assert!(!raw.contains("super-secret"));
+        assert!(!response.contains("super-secret"));
+        assert!(!response.contains("password"));
+        assert!(response.contains("\"tls_client_identity_configured\":true"));
+    }
+
+    #[test]
+    fn test_configured_fes_start_with_primary() {
+        let fe =
+            |host: &str| FeEndpoint { host: host.to_string(), http_port: 8030, query_port: 9030 };
+        let cluster = Cluster {
+            fe_endpoints: Some(serde_json::to_string(&[fe("fe2"), fe("fe1"), fe("fe3")]).unwrap()),
+            ..Cluster::for_test()
+        };
+
+        assert_eq!(cluster.configured_fes(), vec![fe("fe1"), fe("fe2"), fe("fe3")]);
+        assert_eq!(ClusterResponse::from(cluster).fe_endpoints.len(), 3);
+    }
+
+    #[test]
+    fn test_has_tags_requires_all() {
+        let mut cluster =
+            Cluster { tags: Some(r#"["prod","Team-A"]"#.to_string()), ..Cluster::for_test() };
+
+        assert!(cluster.has_tags(&[]));
+        assert!(cluster.has_tags(&["team-a".to_string()]));
+        assert!(cluster.has_tags(&["prod".to_string(), "TEAM-A".to_string()]));
+        assert!(!cluster.has_tags(&["prod".to_string(), "staging".to_string()]));
+
+        cluster.tags = Some("not json".to_string());
+        assert!(cluster.tag_list().is_empty());
+    }
+}
@@ -0,0 +1,148 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+
+/// What a cluster group represents
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+pub enum ClusterGroupKind {
+    /// Deployment environment such as prod or staging; a cluster is in at most one
+    Environment,
+    /// Clusters owned by a team
+    Team,
+    #[default]
+    Custom,
+}
+
+impl ClusterGroupKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Environment => "environment",
+            Self::Team => "team",
+            Self::Custom => "custom",
+        }
+    }
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct ClusterGroup {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub kind: ClusterGroupKind,
+    pub created_by: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Member cluster of a group
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct ClusterGroupMember {
+    pub id: i64,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ClusterGroupResponse {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub kind: ClusterGroupKind,
+    pub clusters: Vec<ClusterGroupMember>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ClusterGroupResponse {
+    pub fn new(group: ClusterGroup, clusters: Vec<ClusterGroupMember>) -> Self {
+        Self {
+            id: group.id,
+            name: group.name,
+            description: group.description,
+            kind: group.kind,
+            clusters,
+            created_at: group.created_at,
+            updated_at: group.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateClusterGroupRequest {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub kind: ClusterGroupKind,
+    #[serde(default)]
+    pub cluster_ids: Vec<i64>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateClusterGroupRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub kind: Option<ClusterGroupKind>,
+    /// Replaces the members when present
+    pub cluster_ids: Option<Vec<i64>>,
+}
+
+/// Read-only SQL to run on every cluster of a group
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct FanOutQueryRequest {
+    pub sql: String,
+    pub catalog: Option<String>,
+    pub database: Option<String>,
+    /// Row limit per cluster (default 1000, at most 10000)
+    pub limit: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FanOutStatus {
+    Ok,
+    Error,
+    /// The caller lacks the required role on this cluster
+    Forbidden,
+}
+
+/// Outcome of a fan-out operation on one cluster
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ClusterFanOutResult {
+    pub cluster_id: i64,
+    pub cluster_name: String,
+    pub status: FanOutStatus,
+    pub duration_ms: u64,
+    #[schema(value_type = Option<Object>)]
+    pub data: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
+
+/// Per-cluster result matrix of a fan-out operation
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FanOutResponse {
+    pub group_id: i64,
+    pub group_name: String,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<ClusterFanOutResult>,
+}
+
+impl FanOutResponse {
+    pub fn new(group: &ClusterGroup, results: Vec<ClusterFanOutResult>) -> Self {
+        let succeeded = results
+            .iter()
+            .filter(|r| r.status == FanOutStatus::Ok)
+            .count();
+        Self {
+            group_id: group.id,
+            group_name: group.name.clone(),
+            succeeded,
+            failed: results.len() - succeeded,
+            results,
+        }
+    }
+}
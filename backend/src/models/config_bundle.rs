@@ -0,0 +1,274 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::models::cluster::{
+    default_catalog, default_http_port, default_query_port, default_timeout,
+    default_verify_hostname,
+};
+use crate::models::{Cluster, ClusterGroupKind, FeEndpoint, PoolSettings, SqlPolicy};
+use crate::utils::{ApiError, ApiResult};
+
+/// Version written to exported bundles; imports of other versions are rejected
+pub const CONFIG_BUNDLE_VERSION: u32 = 1;
+
+/// File format of a configuration bundle
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigFormat {
+    #[default]
+    Toml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// Format named by a `Content-Type`, else guessed from the text itself
+    pub fn detect(content_type: Option<&str>, text: &str) -> Self {
+        match content_type.map(str::to_ascii_lowercase) {
+            Some(ct) if ct.contains("json") => Self::Json,
+            Some(ct) if ct.contains("toml") => Self::Toml,
+            _ if text.trim_start().starts_with('{') => Self::Json,
+            _ => Self::Toml,
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Toml => "application/toml; charset=utf-8",
+            Self::Json => "application/json",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Toml => "toml",
+            Self::Json => "json",
+        }
+    }
+}
+
+/// Cluster registrations, cluster groups and system function customizations
+///
+/// Passwords and client certificates are never exported. On import they are
+/// optional for existing clusters (the stored ones are kept) and the password
+/// is required for new ones.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct ConfigBundle {
+    pub version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exported_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub clusters: Vec<ClusterDefinition>,
+    #[serde(default)]
+    pub cluster_groups: Vec<ClusterGroupDefinition>,
+    #[serde(default)]
+    pub system_functions: Vec<SystemFunctionDefinition>,
+    #[serde(default)]
+    pub system_function_preferences: Vec<SystemFunctionPreferenceDefinition>,
+}
+
+impl ConfigBundle {
+    pub fn parse(text: &str, format: ConfigFormat) -> ApiResult<Self> {
+        let bundle: Self = match format {
+            ConfigFormat::Toml => toml::from_str(text)
+                .map_err(|e| ApiError::validation_error(format!("Invalid TOML bundle: {}", e)))?,
+            ConfigFormat::Json => serde_json::from_str(text)
+                .map_err(|e| ApiError::validation_error(format!("Invalid JSON bundle: {}", e)))?,
+        };
+        if bundle.version != CONFIG_BUNDLE_VERSION {
+            return Err(ApiError::validation_error(format!(
+                "Unsupported bundle version {} (expected {})",
+                bundle.version, CONFIG_BUNDLE_VERSION
+            )));
+        }
+        Ok(bundle)
+    }
+
+    pub fn render(&self, format: ConfigFormat) -> ApiResult<String> {
+        match format {
+            ConfigFormat::Toml => toml::to_string_pretty(self).map_err(|e| {
+                ApiError::internal_error(format!("Failed to serialize bundle: {}", e))
+            }),
+            ConfigFormat::Json => serde_json::to_string_pretty(self).map_err(|e| {
+                ApiError::internal_error(format!("Failed to serialize bundle: {}", e))
+            }),
+        }
+    }
+}
+
+/// Cluster registration, matched by name on import
+///
+/// Nested tables come last so that the struct serializes to TOML.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ClusterDefinition {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub fe_host: String,
+    #[serde(default = "default_http_port")]
+    pub fe_http_port: i32,
+    #[serde(default = "default_query_port")]
+    pub fe_query_port: i32,
+    pub username: String,
+    /// Never exported; keeps the stored password when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub enable_ssl: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls_ca_cert: Option<String>,
+    /// Never exported; keeps the stored client certificate when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls_client_identity: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls_client_identity_password: Option<String>,
+    #[serde(default = "default_verify_hostname")]
+    pub tls_verify_hostname: bool,
+    #[serde(default = "default_timeout")]
+    pub connection_timeout: i32,
+    #[serde(default = "default_catalog")]
+    pub catalog: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub fe_endpoints: Vec<FeEndpoint>,
+    #[serde(default, skip_serializing_if = "PoolSettings::is_empty")]
+    pub pool_settings: PoolSettings,
+    #[serde(default, skip_serializing_if = "SqlPolicy::is_empty")]
+    pub sql_policy: SqlPolicy,
+}
+
+impl From<&Cluster> for ClusterDefinition {
+    fn from(cluster: &Cluster) -> Self {
+        Self {
+            name: cluster.name.clone(),
+            description: cluster.description.clone().filter(|d| !d.is_empty()),
+            fe_host: cluster.fe_host.clone(),
+            fe_http_port: cluster.fe_http_port,
+            fe_query_port: cluster.fe_query_port,
+            username: cluster.username.clone(),
+            password: None,
+            enable_ssl: cluster.enable_ssl,
+            tls_ca_cert: cluster.tls_ca_cert.clone(),
+            tls_client_identity: None,
+            tls_client_identity_password: None,
+            tls_verify_hostname: cluster.tls_verify_hostname,
+            connection_timeout: cluster.connection_timeout,
+            catalog: cluster.catalog.clone(),
+            tags: cluster.tag_list(),
+            fe_endpoints: cluster.additional_fes(),
+            pool_settings: cluster.pool_settings(),
+            sql_policy: cluster.sql_policy(),
+        }
+    }
+}
+
+/// Cluster group, matched by name; members are cluster names
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ClusterGroupDefinition {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub kind: ClusterGroupKind,
+    #[serde(default)]
+    pub clusters: Vec<String>,
+}
+
+/// Custom system function of a cluster, matched by cluster, category and name
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SystemFunctionDefinition {
+    pub cluster: String,
+    pub category_name: String,
+    pub function_name: String,
+    pub description: String,
+    pub sql_query: String,
+    #[serde(default)]
+    pub display_order: i32,
+    #[serde(default)]
+    pub category_order: i32,
+    #[serde(default)]
+    pub is_favorited: bool,
+}
+
+/// Per-cluster ordering and favorite flag of a system function
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SystemFunctionPreferenceDefinition {
+    pub cluster: String,
+    pub category_name: String,
+    pub function_name: String,
+    #[serde(default)]
+    pub category_order: i32,
+    #[serde(default)]
+    pub display_order: i32,
+    #[serde(default)]
+    pub is_favorited: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConfigExportParams {
+    #[serde(default)]
+    pub format: ConfigFormat,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConfigImportParams {
+    /// Only report what would change
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Overrides the format taken from `Content-Type`
+    pub format: Option<ConfigFormat>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigItemKind {
+    Cluster,
+    ClusterGroup,
+    SystemFunction,
+    SystemFunctionPreference,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigAction {
+    Create,
+    Update,
+    Unchanged,
+}
+
+/// Outcome of importing one bundle item
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ConfigChange {
+    pub kind: ConfigItemKind,
+    /// Cluster or group name, or `cluster/category/function` for functions
+    pub name: String,
+    pub action: ConfigAction,
+    /// ID of the item; unset for items a dry run would create
+    pub id: Option<i64>,
+    /// Fields that differ from the stored item
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ConfigImportResponse {
+    pub dry_run: bool,
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub changes: Vec<ConfigChange>,
+}
+
+impl ConfigImportResponse {
+    pub fn new(dry_run: bool, changes: Vec<ConfigChange>) -> Self {
+        let count = |action| changes.iter().filter(|c| c.action == action).count();
+        Self {
+            dry_run,
+            created: count(ConfigAction::Create),
+            updated: count(ConfigAction::Update),
+            unchanged: count(ConfigAction::Unchanged),
+            changes,
+        }
+    }
+}
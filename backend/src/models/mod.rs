@@ -1,13 +1,33 @@
+pub mod api_token;
+pub mod audit;
 pub mod cluster;
+pub mod cluster_group;
+pub mod config_bundle;
 pub mod materialized_view;
+pub mod permission;
+pub mod query_job;
+pub mod query_plan;
+pub mod sql_guard;
+pub mod sql_script;
 pub mod starrocks;
 pub mod system_function;
+pub mod topology;
 pub mod user;
 
+pub use api_token::*;
+pub use audit::*;
 pub use cluster::*;
+pub use cluster_group::*;
+pub use config_bundle::*;
 pub use materialized_view::*;
+pub use permission::*;
+pub use query_job::*;
+pub use query_plan::*;
+pub use sql_guard::*;
+pub use sql_script::*;
 pub use starrocks::*;
 pub use system_function::*;
+pub use topology::*;
 pub use user::*;
 
 // Re-export newly added models
@@ -0,0 +1,139 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::fmt;
+use std::str::FromStr;
+use utoipa::ToSchema;
+
+/// Admin console role, ordered from least to most privileged
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    sqlx::Type,
+    ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+pub enum UserRole {
+    /// Read-only access to dashboards and cluster information
+    #[default]
+    Viewer,
+    /// Day-to-day operations: kill queries/sessions, execute SQL, manage materialized views
+    Operator,
+    /// Cluster registration, node management, global variables and user permissions
+    Admin,
+}
+
+impl UserRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Viewer => "viewer",
+            Self::Operator => "operator",
+            Self::Admin => "admin",
+        }
+    }
+}
+
+impl fmt::Display for UserRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for UserRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "viewer" => Ok(Self::Viewer),
+            "operator" => Ok(Self::Operator),
+            "admin" => Ok(Self::Admin),
+            other => Err(format!("Unknown role '{}'. Must be viewer, operator or admin", other)),
+        }
+    }
+}
+
+/// Authenticated caller, inserted into request extensions by the auth middleware
+#[derive(Debug, Clone)]
+pub struct CurrentUser {
+    pub id: i64,
+    pub username: String,
+    pub role: UserRole,
+    /// Set when the request authenticated with a personal API token
+    pub api_token: Option<ApiTokenGrant>,
+}
+
+/// API token a request was made with
+#[derive(Debug, Clone)]
+pub struct ApiTokenGrant {
+    pub id: i64,
+    /// Upper bound from the token scopes, applied to global and per-cluster roles
+    pub max_role: UserRole,
+}
+
+impl CurrentUser {
+    /// Limit a role to what the credentials of this request allow
+    pub fn cap(&self, role: UserRole) -> UserRole {
+        match &self.api_token {
+            Some(token) => role.min(token.max_role),
+            None => role,
+        }
+    }
+}
+
+/// Role granted to a user on a single cluster
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct ClusterRoleGrant {
+    pub id: i64,
+    pub user_id: i64,
+    pub cluster_id: i64,
+    pub role: UserRole,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateUserRoleRequest {
+    pub role: UserRole,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct GrantClusterRoleRequest {
+    pub role: UserRole,
+}
+
+/// Global role plus per-cluster overrides of a user
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UserPermissions {
+    pub user_id: i64,
+    pub role: UserRole,
+    pub cluster_roles: Vec<ClusterRoleGrant>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_role_ordering() {
+        assert!(UserRole::Viewer < UserRole::Operator);
+        assert!(UserRole::Operator < UserRole::Admin);
+        assert_eq!(UserRole::default(), UserRole::Viewer);
+    }
+
+    #[test]
+    fn test_role_parsing() {
+        assert_eq!("Admin".parse::<UserRole>().unwrap(), UserRole::Admin);
+        assert_eq!(" operator ".parse::<UserRole>().unwrap(), UserRole::Operator);
+        assert!("root".parse::<UserRole>().is_err());
+    }
+}
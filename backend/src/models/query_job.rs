@@ -0,0 +1,66 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+
+use crate::models::{ResultColumn, StreamTruncation};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+pub enum QueryJobStatus {
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SubmitQueryJobRequest {
+    pub sql: String,
+    #[serde(default)]
+    pub catalog: Option<String>,
+    #[serde(default)]
+    pub database: Option<String>,
+}
+
+/// Background query; its rows are fetched page by page
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct QueryJob {
+    pub id: i64,
+    pub cluster_id: i64,
+    pub user_id: i64,
+    #[sqlx(rename = "sql_text")]
+    pub sql: String,
+    pub catalog: Option<String>,
+    #[sqlx(rename = "database_name")]
+    pub database: Option<String>,
+    pub status: QueryJobStatus,
+    /// Rows stored so far; grows while the job runs
+    pub row_count: i64,
+    /// Set when a cap was reached and the query was killed
+    pub truncated: Option<StreamTruncation>,
+    pub error_message: Option<String>,
+    pub execution_time_ms: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// When the job and its rows are deleted
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QueryJobResultsParams {
+    pub offset: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+/// One page of the rows of a job
+#[derive(Debug, Serialize, ToSchema)]
+pub struct QueryJobResults {
+    pub job: QueryJob,
+    /// Empty until the query returned its columns
+    pub columns: Vec<ResultColumn>,
+    #[schema(value_type = Vec<Vec<Object>>)]
+    pub rows: Vec<Vec<serde_json::Value>>,
+    pub offset: i64,
+}
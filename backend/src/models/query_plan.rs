@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+
+/// Query to explain; `analyze` runs it and reports actual rows and times
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ExplainRequest {
+    /// A single SELECT, without `EXPLAIN`
+    pub sql: String,
+    #[serde(default)]
+    pub catalog: Option<String>,
+    #[serde(default)]
+    pub database: Option<String>,
+    /// Use `EXPLAIN ANALYZE` instead of `EXPLAIN COSTS`; the query is executed
+    #[serde(default)]
+    pub analyze: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExplainMode {
+    /// `EXPLAIN COSTS`: the optimizer's estimates
+    Costs,
+    /// `EXPLAIN ANALYZE`: estimates plus what the execution measured
+    Analyze,
+}
+
+/// `selected/total` partitions or tablets of a scan
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+pub struct ScanRatio {
+    pub selected: u64,
+    pub total: u64,
+}
+
+/// Operator of a plan fragment
+#[derive(Debug, Clone, Default, PartialEq, Serialize, ToSchema)]
+pub struct PlanNode {
+    /// Plan node id, as referenced by exchanges and runtime filters
+    pub id: Option<i64>,
+    /// Operator as printed by StarRocks, e.g. `HASH JOIN`, `OlapScanNode` or `EXCHANGE`
+    pub operator: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub table: Option<String>,
+    /// Estimated output rows (cardinality)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_rows: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+    /// Output rows measured by `EXPLAIN ANALYZE`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual_rows: Option<u64>,
+    /// Time spent in the operator, as reported by `EXPLAIN ANALYZE`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_time: Option<String>,
+    /// Distribution of an exchange: `GATHER`, `BROADCAST`, `SHUFFLE` ...
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exchange_type: Option<String>,
+    /// e.g. `INNER JOIN` or `LEFT OUTER JOIN`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub join_type: Option<String>,
+    /// `BROADCAST`, `PARTITIONED`, `BUCKET_SHUFFLE`, `COLOCATE` ...
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub join_strategy: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partitions: Option<ScanRatio>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tablets: Option<ScanRatio>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub predicates: Option<String>,
+    /// Remaining lines StarRocks printed for the operator
+    pub details: Vec<String>,
+    /// For joins the probe side comes first, then the build side
+    pub children: Vec<PlanNode>,
+}
+
+/// Part of the plan that runs on one set of instances
+#[derive(Debug, Clone, Default, PartialEq, Serialize, ToSchema)]
+pub struct PlanFragment {
+    pub id: i64,
+    /// e.g. `RESULT SINK`, `STREAM DATA SINK` or `DATA_STREAM_SINK`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sink: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_partition: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_partition: Option<String>,
+    /// Id of the exchange node that receives this fragment's output
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_exchange_id: Option<i64>,
+    /// Fragment-level lines, e.g. `Output Exprs` or `BackendNum`
+    pub details: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root: Option<PlanNode>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanWarningKind {
+    /// A large input is sent to every instance of a join
+    BroadcastLargeTable,
+    /// Every partition of a partitioned table is scanned
+    NoPartitionPruning,
+    /// A table is scanned without any predicate
+    FullScan,
+}
+
+/// Something in the plan that is likely slow
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct PlanWarning {
+    pub kind: PlanWarningKind,
+    pub fragment_id: i64,
+    pub node_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub table: Option<String>,
+    pub message: String,
+}
+
+/// Parsed plan of a query
+#[derive(Debug, Serialize, ToSchema)]
+pub struct QueryPlan {
+    pub mode: ExplainMode,
+    /// `Summary` section of `EXPLAIN ANALYZE`, e.g. `QueryId` and `TotalTime`
+    pub summary: BTreeMap<String, String>,
+    /// Fragments in the order StarRocks printed them; fragment 0 returns the result
+    pub fragments: Vec<PlanFragment>,
+    pub warnings: Vec<PlanWarning>,
+    /// Plan text as returned by StarRocks
+    pub raw: String,
+}
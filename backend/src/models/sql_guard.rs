@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// What a SQL statement does, as far as the SQL guard is concerned
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum StatementCategory {
+    /// Queries and session statements: SELECT, SHOW, DESCRIBE, EXPLAIN, USE, SET
+    Read,
+    /// Data changes and loads: INSERT, UPDATE, DELETE, LOAD, EXPORT
+    Dml,
+    /// Schema changes: CREATE, ALTER, DROP, TRUNCATE of databases, tables and views
+    Ddl,
+    /// Cluster administration: ALTER SYSTEM, KILL, SET GLOBAL, GRANT, users and
+    /// roles, and statements the guard does not recognize
+    Admin,
+}
+
+impl std::fmt::Display for StatementCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Read => "read",
+            Self::Dml => "DML",
+            Self::Ddl => "DDL",
+            Self::Admin => "admin",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Per-cluster SQL rules, on top of what each role may run
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+pub struct SqlPolicy {
+    /// Only read statements may run, e.g. on production clusters
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub read_only: bool,
+    /// Every statement that is not a read needs a confirmation, not only destructive ones
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub confirm_writes: bool,
+}
+
+impl SqlPolicy {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SqlViolationReason {
+    /// The user's role on the cluster may not run this category
+    RoleNotAllowed,
+    /// The cluster's policy only allows reads
+    ClusterReadOnly,
+    /// Only a single query is accepted here, e.g. for saved functions
+    QueryRequired,
+    /// The statement runs once the request is repeated with the confirmation token
+    ConfirmationRequired,
+}
+
+/// Why the SQL guard rejected a statement; sent as the `details` of the error
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SqlViolation {
+    pub reason: SqlViolationReason,
+    pub message: String,
+    /// Position of the statement in the submitted SQL, from 0
+    pub statement_index: usize,
+    pub statement: String,
+    pub category: StatementCategory,
+    /// Statement type, e.g. `SELECT`, `DROP TABLE` or `ALTER SYSTEM`
+    pub kind: String,
+    pub destructive: bool,
+    /// Send as the `X-SQL-Confirmation` header to run the statements anyway
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confirmation_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confirmation_expires_in_secs: Option<u64>,
+}
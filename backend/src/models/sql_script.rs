@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::models::ResultColumn;
+
+fn default_script_limit() -> i32 {
+    1000
+}
+
+fn default_stop_on_error() -> bool {
+    true
+}
+
+/// Script of `;`-separated statements run one after another on one connection
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SqlScriptRequest {
+    pub sql: String,
+    #[serde(default)]
+    pub catalog: Option<String>,
+    #[serde(default)]
+    pub database: Option<String>,
+    /// Rows returned per statement; LIMIT is added to SELECTs without one
+    #[serde(default = "default_script_limit")]
+    pub limit: i32,
+    /// Skip the remaining statements after a failure
+    #[serde(default = "default_stop_on_error")]
+    pub stop_on_error: bool,
+    /// Wrap the script in BEGIN/COMMIT and roll back on the first failure
+    #[serde(default)]
+    pub transaction: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SqlStatementStatus {
+    Succeeded,
+    Failed,
+    /// Not run because an earlier statement failed
+    Skipped,
+}
+
+/// Outcome of one statement of a script
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SqlStatementResult {
+    /// Position in the script, from 0
+    pub index: usize,
+    pub sql: String,
+    /// Byte offset of the statement in the script
+    pub offset: usize,
+    pub status: SqlStatementStatus,
+    /// Empty for statements without a result set
+    pub columns: Vec<ResultColumn>,
+    #[schema(value_type = Vec<Vec<Object>>)]
+    pub rows: Vec<Vec<serde_json::Value>>,
+    pub row_count: usize,
+    /// Set when rows beyond `limit` were dropped
+    pub truncated: bool,
+    pub affected_rows: u64,
+    pub error: Option<String>,
+    pub execution_time_ms: u128,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SqlScriptResponse {
+    pub statements: Vec<SqlStatementResult>,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    /// Whether the transaction was committed; absent without `transaction`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub committed: Option<bool>,
+    /// Failure of the final COMMIT or ROLLBACK
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub execution_time_ms: u128,
+}
@@ -62,6 +62,12 @@ pub struct Backend {
     #[serde(default)]
     #[serde(rename = "WarehouseName")]
     pub warehouse_name: Option<String>,
+    #[serde(default)]
+    #[serde(rename = "Version")]
+    pub version: Option<String>,
+    #[serde(default)]
+    #[serde(rename = "ErrMsg")]
+    pub err_msg: Option<String>,
 }
 
 // Frontend node information
@@ -212,6 +218,27 @@ pub struct Database {
     pub database: String,
 }
 
+// Tablet health of one database from `SHOW PROC '/statistic'`; the last row is the total
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TabletStatistic {
+    #[serde(rename = "DbId")]
+    pub db_id: String,
+    #[serde(rename = "DbName")]
+    pub db_name: String,
+    #[serde(rename = "TabletNum", default)]
+    pub tablet_num: String,
+    #[serde(rename = "ReplicaNum", default)]
+    pub replica_num: String,
+    #[serde(rename = "UnhealthyTabletNum", default)]
+    pub unhealthy_tablet_num: String,
+    #[serde(rename = "InconsistentTabletNum", default)]
+    pub inconsistent_tablet_num: String,
+    #[serde(rename = "CloningTabletNum", default)]
+    pub cloning_tablet_num: String,
+    #[serde(rename = "ErrorStateTabletNum", default)]
+    pub error_state_tablet_num: String,
+}
+
 // Table information
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 #[allow(dead_code)]
@@ -321,19 +348,152 @@ pub struct QueryExecuteRequest {
     pub catalog: Option<String>, // Optional catalog name
     #[serde(default)]
     pub database: Option<String>, // Optional database name, will execute USE database before SQL
+    /// Download the full result in this format instead; `limit` is ignored
+    #[serde(default)]
+    pub export: Option<ExportFormat>,
+    /// Serve a result cached within this many seconds and cache a new one;
+    /// only single read-only queries are cached
+    #[serde(default)]
+    pub cache_ttl_secs: Option<u64>,
 }
 
 fn default_limit() -> Option<i32> {
     Some(1000)
 }
 
+/// File format of an exported result
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Tsv,
+    Jsonl,
+    Parquet,
+    Xlsx,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Tsv => "text/tab-separated-values; charset=utf-8",
+            Self::Jsonl => "application/x-ndjson",
+            Self::Parquet => "application/vnd.apache.parquet",
+            Self::Xlsx => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Tsv => "tsv",
+            Self::Jsonl => "jsonl",
+            Self::Parquet => "parquet",
+            Self::Xlsx => "xlsx",
+        }
+    }
+}
+
+/// Column of a SQL result as described by the server
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ResultColumn {
+    pub name: String,
+    /// MySQL protocol type, e.g. `BIGINT`, `DECIMAL`, `DATETIME`, `VARCHAR` or `BOOLEAN`
+    #[serde(rename = "type")]
+    pub data_type: String,
+    pub nullable: bool,
+}
+
 // Query execute response
+// Values are JSON-typed: numbers, booleans, null, ISO date/times and base64 for
+// binary columns. DECIMALs and integers beyond 2^53 are strings so that no digits are lost.
 #[derive(Debug, Serialize, ToSchema)]
 pub struct QueryExecuteResponse {
-    pub columns: Vec<String>,
-    pub rows: Vec<Vec<String>>,
+    pub columns: Vec<ResultColumn>,
+    #[schema(value_type = Vec<Vec<Object>>)]
+    pub rows: Vec<Vec<serde_json::Value>>,
     pub row_count: usize,
     pub execution_time_ms: u128,
+    /// When a cached result was loaded from the cluster; absent for fresh results
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cached_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+// Streamed query request; unlike `execute` no LIMIT is added
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct QueryStreamRequest {
+    pub sql: String,
+    #[serde(default)]
+    pub catalog: Option<String>,
+    #[serde(default)]
+    pub database: Option<String>,
+    /// Lower row cap than the server's `query_stream.max_rows`
+    #[serde(default)]
+    pub max_rows: Option<u64>,
+    /// Lower byte cap than the server's `query_stream.max_bytes`
+    #[serde(default)]
+    pub max_bytes: Option<u64>,
+}
+
+/// Wire format of a streamed result
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum QueryStreamFormat {
+    /// One JSON event per line (`application/x-ndjson`)
+    #[default]
+    Ndjson,
+    /// Server-sent events named after the event type (`text/event-stream`)
+    Sse,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QueryStreamParams {
+    /// Taken from `Accept: text/event-stream` when absent
+    pub format: Option<QueryStreamFormat>,
+}
+
+/// Why a stream ended before the last row
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum StreamTruncation {
+    MaxRows,
+    MaxBytes,
+}
+
+/// One event of a streamed result: `columns`, any number of `row`s, then `end` or `error`
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QueryStreamEvent {
+    Columns {
+        columns: Vec<ResultColumn>,
+    },
+    Row {
+        #[schema(value_type = Vec<Object>)]
+        values: Vec<serde_json::Value>,
+    },
+    End {
+        row_count: u64,
+        bytes: u64,
+        execution_time_ms: u128,
+        /// Set when a cap was reached; the query was killed
+        #[serde(skip_serializing_if = "Option::is_none")]
+        truncated: Option<StreamTruncation>,
+    },
+    Error {
+        message: String,
+    },
+}
+
+impl QueryStreamEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Columns { .. } => "columns",
+            Self::Row { .. } => "row",
+            Self::End { .. } => "end",
+            Self::Error { .. } => "error",
+        }
+    }
 }
 
 // Profile list item from SHOW PROFILELIST
@@ -4,6 +4,8 @@ use sqlx::FromRow;
 use utoipa::ToSchema;
 use validator::Validate;
 
+use crate::models::ResultColumn;
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct SystemFunction {
     pub id: i64,
@@ -56,6 +58,27 @@ pub struct UpdateFunctionRequest {
     pub sql_query: String,
 }
 
+/// Result of running a custom function's SQL
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FunctionExecuteResponse {
+    pub columns: Vec<ResultColumn>,
+    /// One object per row, keyed by column name, with JSON-typed values
+    #[schema(value_type = Vec<Object>)]
+    pub data: Vec<serde_json::Map<String, serde_json::Value>>,
+    #[serde(rename = "rowCount")]
+    pub row_count: usize,
+    /// When a cached result was loaded from the cluster; absent for fresh results
+    #[serde(rename = "cachedAt", skip_serializing_if = "Option::is_none")]
+    pub cached_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct FunctionExecuteQuery {
+    /// Serve a result cached within this many seconds and cache a new one
+    #[serde(default)]
+    pub cache_ttl_secs: Option<u64>,
+}
+
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateOrderRequest {
     pub functions: Vec<FunctionOrder>,
@@ -0,0 +1,152 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+
+use crate::models::{ClusterResponse, CreateClusterRequest, HealthCheck};
+
+/// Storage architecture of a cluster
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RunMode {
+    /// Data on BE local disks
+    SharedNothing,
+    /// Data in object storage, queried by compute nodes grouped in warehouses
+    SharedData,
+    #[default]
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct TopologyFrontend {
+    pub name: String,
+    pub host: String,
+    pub http_port: i32,
+    pub query_port: i32,
+    pub edit_log_port: i32,
+    /// LEADER, FOLLOWER or OBSERVER
+    pub role: String,
+    pub alive: bool,
+}
+
+/// Backend or compute node
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct TopologyNode {
+    pub id: String,
+    pub host: String,
+    pub heartbeat_port: i32,
+    pub alive: bool,
+    pub decommissioned: bool,
+    pub warehouse: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct TopologyWarehouse {
+    pub name: String,
+    pub state: String,
+    pub node_count: Option<i64>,
+}
+
+/// Nodes and layout of a cluster as seen by one discovery
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ClusterTopology {
+    pub run_mode: RunMode,
+    pub version: Option<String>,
+    pub frontends: Vec<TopologyFrontend>,
+    pub backends: Vec<TopologyNode>,
+    pub compute_nodes: Vec<TopologyNode>,
+    pub warehouses: Vec<TopologyWarehouse>,
+    pub discovered_at: DateTime<Utc>,
+}
+
+/// Result of probing a cluster: credential checks on both ports plus its topology
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TopologyDiscovery {
+    /// One check for the HTTP port and one for the MySQL port
+    pub checks: Vec<HealthCheck>,
+    pub topology: ClusterTopology,
+}
+
+impl TopologyDiscovery {
+    /// Failed checks as one message, `None` when both ports accepted the credentials
+    pub fn failure(&self) -> Option<String> {
+        let failures: Vec<String> = self
+            .checks
+            .iter()
+            .filter(|c| c.status != "ok")
+            .map(|c| format!("{}: {}", c.name, c.message))
+            .collect();
+        (!failures.is_empty()).then(|| failures.join("; "))
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ImportClusterRequest {
+    #[serde(flatten)]
+    pub cluster: CreateClusterRequest,
+    /// Register the other discovered FEs for failover (default true)
+    #[serde(default = "default_add_discovered_fes")]
+    pub add_discovered_fes: bool,
+}
+
+fn default_add_discovered_fes() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ImportClusterResponse {
+    pub cluster: ClusterResponse,
+    pub topology: ClusterTopology,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum TopologyComponent {
+    Cluster,
+    Frontend,
+    Backend,
+    ComputeNode,
+    Warehouse,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+pub enum TopologyChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// Difference found between two discoveries
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct TopologyChange {
+    pub component: TopologyComponent,
+    pub change: TopologyChangeKind,
+    /// `host:port` of the node, warehouse name, or the changed cluster property
+    pub node: String,
+    /// What changed, e.g. `role: FOLLOWER -> LEADER`
+    pub detail: Option<String>,
+}
+
+/// Stored topology change
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct TopologyChangeRecord {
+    pub id: i64,
+    pub cluster_id: i64,
+    pub component: TopologyComponent,
+    pub change: TopologyChangeKind,
+    pub node: String,
+    pub detail: Option<String>,
+    pub detected_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TopologyRefreshResponse {
+    pub topology: ClusterTopology,
+    /// Changes since the previous snapshot; empty for the first one
+    pub changes: Vec<TopologyChange>,
+    /// Whether there was no earlier snapshot to compare with
+    pub first_snapshot: bool,
+}
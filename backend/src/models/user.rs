@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use utoipa::ToSchema;
 
+use super::UserRole;
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct User {
     pub id: i64,
@@ -13,6 +15,38 @@ pub struct User {
     pub avatar: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub role: UserRole,
+    pub is_disabled: bool,
+    pub auth_source: AuthSource,
+    pub external_id: Option<String>,
+    /// Only the own password may be changed until a new one is set
+    pub must_change_password: bool,
+}
+
+/// Where a user authenticates
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+pub enum AuthSource {
+    /// Username and bcrypt password stored in this database
+    #[default]
+    Local,
+    /// LDAP bind, provisioned on first sign-in
+    Ldap,
+    /// OIDC single sign-on, provisioned on first sign-in
+    Oidc,
+}
+
+impl AuthSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Local => "local",
+            Self::Ldap => "ldap",
+            Self::Oidc => "oidc",
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -32,9 +66,26 @@ pub struct LoginRequest {
 #[derive(Debug, Serialize, ToSchema)]
 pub struct LoginResponse {
     pub token: String,
+    pub refresh_token: String,
+    /// Access token lifetime in seconds
+    pub expires_in: i64,
     pub user: UserResponse,
 }
 
+/// Access/refresh token pair returned by login and refresh
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TokenResponse {
+    pub token: String,
+    pub refresh_token: String,
+    /// Access token lifetime in seconds
+    pub expires_in: i64,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateUserRequest {
     pub username: Option<String>,
@@ -50,6 +101,10 @@ pub struct UserResponse {
     pub username: String,
     pub email: Option<String>,
     pub avatar: Option<String>,
+    pub role: UserRole,
+    pub is_disabled: bool,
+    pub auth_source: AuthSource,
+    pub must_change_password: bool,
     pub created_at: DateTime<Utc>,
 }
 
@@ -60,7 +115,78 @@ impl From<User> for UserResponse {
             username: user.username,
             email: user.email,
             avatar: user.avatar,
+            role: user.role,
+            is_disabled: user.is_disabled,
+            auth_source: user.auth_source,
+            must_change_password: user.must_change_password,
             created_at: user.created_at,
         }
     }
 }
+
+/// Admin request to create a user directly
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AdminCreateUserRequest {
+    pub username: String,
+    pub password: String,
+    pub email: Option<String>,
+    #[serde(default)]
+    pub role: UserRole,
+}
+
+/// Admin request to invite a user by one-time link
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct InviteUserRequest {
+    pub email: Option<String>,
+    #[serde(default)]
+    pub role: UserRole,
+    /// Invitation validity in hours (default: 72)
+    #[serde(default = "default_invitation_hours")]
+    pub expires_in_hours: i64,
+}
+
+fn default_invitation_hours() -> i64 {
+    72
+}
+
+/// Created invitation; the token is only returned once
+#[derive(Debug, Serialize, ToSchema)]
+pub struct InvitationResponse {
+    pub id: i64,
+    pub token: String,
+    pub email: Option<String>,
+    pub role: UserRole,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct UserInvitation {
+    pub id: i64,
+    pub email: Option<String>,
+    pub role: UserRole,
+    pub expires_at: DateTime<Utc>,
+    pub accepted_at: Option<DateTime<Utc>>,
+}
+
+/// Public request to redeem an invitation
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AcceptInvitationRequest {
+    pub token: String,
+    pub username: String,
+    pub password: String,
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ResetPasswordRequest {
+    pub new_password: String,
+}
+
+/// Sign-in methods enabled on this server, used by the login page
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AuthProvidersResponse {
+    pub local: bool,
+    pub ldap: bool,
+    pub oidc: bool,
+    pub allow_registration: bool,
+}
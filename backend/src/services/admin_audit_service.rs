@@ -0,0 +1,334 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::Value;
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
+
+use crate::models::{AdminAuditEntry, AdminAuditLogResponse, AdminAuditQuery, NewAdminAuditEntry};
+use crate::utils::ApiResult;
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 500;
+/// Upper bound of rows in one CSV export
+const MAX_EXPORT_ROWS: i64 = 100_000;
+/// Stored parameters are cut to this many bytes
+const MAX_PARAMETERS_LEN: usize = 16 * 1024;
+
+/// Object keys whose values are never written to the audit log
+const SECRET_KEYS: &[&str] = &["password", "secret", "token", "private_key", "master_key"];
+
+/// Passwords inside SQL text: `IDENTIFIED BY [PASSWORD] '...'` and `PASSWORD('...')`
+static SQL_PASSWORD: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)(identified\s+by\s+(?:password\s+)?|password\s*\(\s*)('(?:[^'\\]|\\.)*'|"(?:[^"\\]|\\.)*")"#)
+        .expect("valid regex")
+});
+
+/// Audit trail of mutating actions performed through this API
+///
+/// Not to be confused with the StarRocks audit table (`starrocks_audit_tbl__`), which
+/// records the queries executed on a cluster.
+#[derive(Clone)]
+pub struct AdminAuditService {
+    pool: SqlitePool,
+}
+
+impl AdminAuditService {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    // Append an entry to the audit log
+    pub async fn record(&self, entry: NewAdminAuditEntry) -> ApiResult<()> {
+        sqlx::query(
+            "INSERT INTO admin_audit_log (user_id, username, cluster_id, method, route, action, target, parameters, status_code, success, error_message, duration_ms, client_ip, api_token_id) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(entry.user_id)
+        .bind(&entry.username)
+        .bind(entry.cluster_id)
+        .bind(&entry.method)
+        .bind(&entry.route)
+        .bind(&entry.action)
+        .bind(&entry.target)
+        .bind(&entry.parameters)
+        .bind(entry.status_code as i64)
+        .bind(entry.status_code < 400)
+        .bind(&entry.error_message)
+        .bind(entry.duration_ms)
+        .bind(&entry.client_ip)
+        .bind(entry.api_token_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    // Search the audit log, newest first
+    pub async fn search(&self, query: &AdminAuditQuery) -> ApiResult<AdminAuditLogResponse> {
+        let page = query.page.unwrap_or(1).max(1);
+        let page_size = query
+            .page_size
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE);
+
+        let mut count = QueryBuilder::<Sqlite>::new("SELECT COUNT(*) FROM admin_audit_log");
+        push_filters(&mut count, query);
+        let (total,): (i64,) = count.build_query_as().fetch_one(&self.pool).await?;
+
+        let mut select = QueryBuilder::<Sqlite>::new("SELECT * FROM admin_audit_log");
+        push_filters(&mut select, query);
+        select
+            .push(" ORDER BY id DESC LIMIT ")
+            .push_bind(page_size)
+            .push(" OFFSET ")
+            .push_bind((page - 1) * page_size);
+        let data = select.build_query_as().fetch_all(&self.pool).await?;
+
+        Ok(AdminAuditLogResponse { data, total, page, page_size })
+    }
+
+    // Render all entries matching the filters as CSV (oldest first, for review)
+    pub async fn export_csv(&self, query: &AdminAuditQuery) -> ApiResult<String> {
+        let mut select = QueryBuilder::<Sqlite>::new("SELECT * FROM admin_audit_log");
+        push_filters(&mut select, query);
+        select
+            .push(" ORDER BY id ASC LIMIT ")
+            .push_bind(MAX_EXPORT_ROWS);
+        let entries: Vec<AdminAuditEntry> = select.build_query_as().fetch_all(&self.pool).await?;
+
+        if entries.len() as i64 == MAX_EXPORT_ROWS {
+            tracing::warn!("Audit export truncated to {} rows", MAX_EXPORT_ROWS);
+        }
+
+        Ok(to_csv(&entries))
+    }
+}
+
+fn push_filters(builder: &mut QueryBuilder<'_, Sqlite>, query: &AdminAuditQuery) {
+    let mut separator = " WHERE ";
+    let mut next = |builder: &mut QueryBuilder<'_, Sqlite>| {
+        builder.push(separator);
+        separator = " AND ";
+    };
+
+    if let Some(username) = query.username.as_ref().filter(|v| !v.is_empty()) {
+        next(builder);
+        builder.push("username = ").push_bind(username.clone());
+    }
+    if let Some(action) = query.action.as_ref().filter(|v| !v.is_empty()) {
+        next(builder);
+        builder
+            .push("(action = ")
+            .push_bind(action.clone())
+            .push(" OR action LIKE ")
+            .push_bind(format!("{}.%", escape_like(action)))
+            .push(" ESCAPE '\\')");
+    }
+    if let Some(cluster_id) = query.cluster_id {
+        next(builder);
+        builder.push("cluster_id = ").push_bind(cluster_id);
+    }
+    if let Some(success) = query.success {
+        next(builder);
+        builder.push("success = ").push_bind(success);
+    }
+    if let Some(search) = query.search.as_ref().filter(|v| !v.is_empty()) {
+        let pattern = format!("%{}%", escape_like(search));
+        next(builder);
+        builder
+            .push("(target LIKE ")
+            .push_bind(pattern.clone())
+            .push(" ESCAPE '\\' OR route LIKE ")
+            .push_bind(pattern.clone())
+            .push(" ESCAPE '\\' OR parameters LIKE ")
+            .push_bind(pattern)
+            .push(" ESCAPE '\\')");
+    }
+    if let Some(from) = query.from {
+        next(builder);
+        builder.push("created_at >= ").push_bind(from);
+    }
+    if let Some(to) = query.to {
+        next(builder);
+        builder.push("created_at < ").push_bind(to);
+    }
+}
+
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Serialize request parameters for storage, replacing secret values
+pub fn redact_parameters(mut value: Value) -> String {
+    redact(&mut value);
+
+    let mut text = value.to_string();
+    if text.len() > MAX_PARAMETERS_LEN {
+        let mut end = MAX_PARAMETERS_LEN;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+        text.push_str("...(truncated)");
+    }
+    text
+}
+
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let key = key.to_lowercase();
+                if SECRET_KEYS.iter().any(|secret| key.contains(secret)) {
+                    *value = Value::String("***".to_string());
+                } else {
+                    redact(value);
+                }
+            }
+        },
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        Value::String(text) if SQL_PASSWORD.is_match(text) => {
+            *text = SQL_PASSWORD.replace_all(text, "$1'***'").into_owned();
+        },
+        _ => {},
+    }
+}
+
+const CSV_HEADER: &str = "id,created_at,username,user_id,cluster_id,action,method,route,target,parameters,status_code,success,error_message,duration_ms,client_ip,api_token_id";
+
+fn to_csv(entries: &[AdminAuditEntry]) -> String {
+    let mut csv = String::from(CSV_HEADER);
+    csv.push_str("\r\n");
+
+    for e in entries {
+        let fields = [
+            e.id.to_string(),
+            e.created_at.to_rfc3339(),
+            e.username.clone(),
+            e.user_id.to_string(),
+            e.cluster_id.map(|id| id.to_string()).unwrap_or_default(),
+            e.action.clone(),
+            e.method.clone(),
+            e.route.clone(),
+            e.target.clone().unwrap_or_default(),
+            e.parameters.clone().unwrap_or_default(),
+            e.status_code.to_string(),
+            e.success.to_string(),
+            e.error_message.clone().unwrap_or_default(),
+            e.duration_ms.to_string(),
+            e.client_ip.clone().unwrap_or_default(),
+            e.api_token_id.map(|id| id.to_string()).unwrap_or_default(),
+        ];
+        let row: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        csv.push_str(&row.join(","));
+        csv.push_str("\r\n");
+    }
+
+    csv
+}
+
+/// Quote a CSV field; values that spreadsheets would run as formulas are prefixed with `'`
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_pool;
+    use serde_json::json;
+
+    fn entry(username: &str, action: &str, status_code: u16) -> NewAdminAuditEntry {
+        NewAdminAuditEntry {
+            user_id: 1,
+            username: username.to_string(),
+            method: "POST".to_string(),
+            route: "/api/clusters/queries/execute".to_string(),
+            action: action.to_string(),
+            parameters: Some(r#"{"body":{"sql":"SET GLOBAL x = 1"}}"#.to_string()),
+            status_code,
+            ..NewAdminAuditEntry::default()
+        }
+    }
+
+    #[test]
+    fn test_secrets_are_redacted() {
+        let stored = redact_parameters(json!({
+            "body": {"name": "c1", "password": "s3cr3t", "nested": [{"client_secret": "x"}]},
+            "query": {"refresh_token": "rt_1"}
+        }));
+        let sql = redact_parameters(json!({
+            "sql": "CREATE USER bob IDENTIFIED BY 'p@ss'; SET PASSWORD FOR bob = PASSWORD('n3w')"
+        }));
+
+        assert!(stored.contains("c1"));
+        assert!(!stored.contains("s3cr3t"));
+        assert!(!stored.contains("rt_1"));
+        assert!(!stored.contains("\"x\""));
+        assert!(sql.contains("IDENTIFIED BY '***'"));
+        assert!(!sql.contains("p@ss") && !sql.contains("n3w"));
+    }
+
+    #[test]
+    fn test_csv_field_quoting_and_formula_guard() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("=HYPERLINK(\"x\")"), "\"'=HYPERLINK(\"\"x\"\")\"");
+    }
+
+    #[tokio::test]
+    async fn test_search_filters_and_paginates() {
+        let service = AdminAuditService::new(test_pool().await);
+        for i in 0..5 {
+            service
+                .record(entry("alice", "sql.execute", if i == 0 { 403 } else { 200 }))
+                .await
+                .unwrap();
+        }
+        service
+            .record(entry("bob", "query.kill", 200))
+            .await
+            .unwrap();
+
+        let page = service
+            .search(&AdminAuditQuery {
+                username: Some("alice".into()),
+                page: Some(2),
+                page_size: Some(2),
+                ..AdminAuditQuery::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(page.total, 5);
+        assert_eq!(page.data.len(), 2);
+
+        let by_prefix = AdminAuditQuery { action: Some("query".into()), ..Default::default() };
+        assert_eq!(service.search(&by_prefix).await.unwrap().total, 1);
+
+        let failed = AdminAuditQuery { success: Some(false), ..Default::default() };
+        assert_eq!(service.search(&failed).await.unwrap().total, 1);
+
+        let by_sql = AdminAuditQuery { search: Some("SET GLOBAL".into()), ..Default::default() };
+        assert_eq!(service.search(&by_sql).await.unwrap().total, 6);
+
+        let csv = service
+            .export_csv(&AdminAuditQuery::default())
+            .await
+            .unwrap();
+        assert_eq!(csv.lines().count(), 7);
+        assert!(csv.starts_with(CSV_HEADER));
+    }
+}
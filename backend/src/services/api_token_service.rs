@@ -0,0 +1,253 @@
+use chrono::{Duration, Utc};
+use sqlx::SqlitePool;
+
+use crate::models::{
+    ApiToken, ApiTokenResponse, ApiTokenScope, CreateApiTokenRequest, CreateApiTokenResponse,
+};
+use crate::utils::{ApiError, ApiResult, token};
+
+/// Prefix of every API token; lets the auth middleware tell them apart from JWTs
+pub const API_TOKEN_PREFIX: &str = "sra_";
+/// Longest allowed lifetime of a token
+const MAX_EXPIRES_IN_DAYS: i64 = 3650;
+/// `last_used_at` is written at most this often per token
+const LAST_USED_RESOLUTION_SECS: i64 = 60;
+
+/// Personal API tokens for scripts and automation
+///
+/// Tokens belong to a user and act on their behalf, limited by the token scopes.
+/// Only a SHA-256 hash of the secret is stored.
+#[derive(Clone)]
+pub struct ApiTokenService {
+    pool: SqlitePool,
+}
+
+impl ApiTokenService {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    pub fn is_api_token(bearer: &str) -> bool {
+        bearer.starts_with(API_TOKEN_PREFIX)
+    }
+
+    // Create a token; the secret is only returned here
+    pub async fn create(
+        &self,
+        user_id: i64,
+        req: CreateApiTokenRequest,
+    ) -> ApiResult<CreateApiTokenResponse> {
+        let name = req.name.trim();
+        if name.is_empty() || name.chars().count() > 100 {
+            return Err(ApiError::validation_error(
+                "Token name must be between 1 and 100 characters",
+            ));
+        }
+
+        let mut scopes = req.scopes;
+        scopes.sort();
+        scopes.dedup();
+        if scopes.is_empty() {
+            return Err(ApiError::validation_error("At least one scope is required"));
+        }
+
+        let expires_at = match req.expires_in_days {
+            Some(days) if !(1..=MAX_EXPIRES_IN_DAYS).contains(&days) => {
+                return Err(ApiError::validation_error(format!(
+                    "expires_in_days must be between 1 and {}",
+                    MAX_EXPIRES_IN_DAYS
+                )));
+            },
+            Some(days) => Some(Utc::now() + Duration::days(days)),
+            None => None,
+        };
+
+        let exists: Option<(i64,)> =
+            sqlx::query_as("SELECT id FROM api_tokens WHERE user_id = ? AND name = ?")
+                .bind(user_id)
+                .bind(name)
+                .fetch_optional(&self.pool)
+                .await?;
+        if exists.is_some() {
+            return Err(ApiError::validation_error(format!(
+                "A token named '{}' already exists",
+                name
+            )));
+        }
+
+        let secret = token::generate_secret(API_TOKEN_PREFIX, 32);
+        let scopes: Vec<&str> = scopes.iter().map(ApiTokenScope::as_str).collect();
+
+        let result = sqlx::query(
+            "INSERT INTO api_tokens (user_id, name, token_prefix, token_hash, scopes, expires_at) \
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(user_id)
+        .bind(name)
+        .bind(&secret[..API_TOKEN_PREFIX.len() + 8])
+        .bind(token::hash_secret(&secret))
+        .bind(scopes.join(","))
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        let api_token = self.get(user_id, result.last_insert_rowid()).await?;
+        tracing::info!("API token '{}' created for user {}", api_token.name, user_id);
+
+        Ok(CreateApiTokenResponse { token: secret, api_token: api_token.into() })
+    }
+
+    // List the tokens of a user
+    pub async fn list(&self, user_id: i64) -> ApiResult<Vec<ApiTokenResponse>> {
+        let tokens: Vec<ApiToken> = sqlx::query_as(
+            "SELECT id, user_id, name, token_prefix, scopes, expires_at, last_used_at, created_at \
+             FROM api_tokens WHERE user_id = ? ORDER BY created_at DESC, id DESC",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(tokens.into_iter().map(Into::into).collect())
+    }
+
+    // Revoke (delete) a token of a user
+    pub async fn revoke(&self, user_id: i64, token_id: i64) -> ApiResult<()> {
+        let result = sqlx::query("DELETE FROM api_tokens WHERE id = ? AND user_id = ?")
+            .bind(token_id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(ApiError::not_found(format!("API token {} not found", token_id)));
+        }
+
+        tracing::info!("API token {} of user {} revoked", token_id, user_id);
+        Ok(())
+    }
+
+    // Resolve a presented secret to its token and record the use
+    pub async fn authenticate(&self, secret: &str) -> ApiResult<ApiToken> {
+        let token: ApiToken = sqlx::query_as(
+            "SELECT id, user_id, name, token_prefix, scopes, expires_at, last_used_at, created_at \
+             FROM api_tokens WHERE token_hash = ?",
+        )
+        .bind(token::hash_secret(secret))
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| ApiError::unauthorized("Invalid or expired API token"))?;
+
+        let now = Utc::now();
+        if token.expires_at.is_some_and(|expires_at| expires_at <= now) {
+            return Err(ApiError::unauthorized("Invalid or expired API token"));
+        }
+
+        let stale = token
+            .last_used_at
+            .is_none_or(|used| now - used >= Duration::seconds(LAST_USED_RESOLUTION_SECS));
+        if stale {
+            sqlx::query("UPDATE api_tokens SET last_used_at = ? WHERE id = ?")
+                .bind(now)
+                .bind(token.id)
+                .execute(&self.pool)
+                .await?;
+        }
+
+        Ok(token)
+    }
+
+    async fn get(&self, user_id: i64, token_id: i64) -> ApiResult<ApiToken> {
+        sqlx::query_as(
+            "SELECT id, user_id, name, token_prefix, scopes, expires_at, last_used_at, created_at \
+             FROM api_tokens WHERE id = ? AND user_id = ?",
+        )
+        .bind(token_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("API token {} not found", token_id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_pool;
+    use crate::models::UserRole;
+
+    fn request(name: &str, scopes: Vec<ApiTokenScope>) -> CreateApiTokenRequest {
+        CreateApiTokenRequest { name: name.to_string(), scopes, expires_in_days: None }
+    }
+
+    #[tokio::test]
+    async fn test_token_lifecycle() {
+        let service = ApiTokenService::new(test_pool().await);
+        // The seeded admin user has id 1
+        let created = service
+            .create(1, request("ci", vec![ApiTokenScope::Write, ApiTokenScope::Read]))
+            .await
+            .unwrap();
+
+        assert!(ApiTokenService::is_api_token(&created.token));
+        assert!(created.token.starts_with(&created.api_token.token_prefix));
+        assert_eq!(created.api_token.scopes, vec![ApiTokenScope::Read, ApiTokenScope::Write]);
+        assert!(
+            service
+                .create(1, request("ci", vec![ApiTokenScope::Read]))
+                .await
+                .is_err()
+        );
+
+        let token = service.authenticate(&created.token).await.unwrap();
+        assert_eq!(token.user_id, 1);
+        assert_eq!(token.max_role(), UserRole::Operator);
+        assert!(service.list(1).await.unwrap()[0].last_used_at.is_some());
+        assert!(service.authenticate("sra_bogus").await.is_err());
+
+        service.revoke(1, token.id).await.unwrap();
+        assert!(service.authenticate(&created.token).await.is_err());
+        assert!(service.revoke(1, token.id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_create_validation() {
+        let service = ApiTokenService::new(test_pool().await);
+
+        assert!(
+            service
+                .create(1, request("  ", vec![ApiTokenScope::Read]))
+                .await
+                .is_err()
+        );
+        assert!(service.create(1, request("empty", vec![])).await.is_err());
+        let zero_days = CreateApiTokenRequest {
+            expires_in_days: Some(0),
+            ..request("x", vec![ApiTokenScope::Read])
+        };
+        assert!(service.create(1, zero_days).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_expired_token_is_rejected() {
+        let pool = test_pool().await;
+        let service = ApiTokenService::new(pool.clone());
+        let created = service
+            .create(
+                1,
+                CreateApiTokenRequest {
+                    expires_in_days: Some(1),
+                    ..request("tmp", vec![ApiTokenScope::Read])
+                },
+            )
+            .await
+            .unwrap();
+
+        sqlx::query("UPDATE api_tokens SET expires_at = ?")
+            .bind(Utc::now() - Duration::minutes(1))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(service.authenticate(&created.token).await.is_err());
+    }
+}
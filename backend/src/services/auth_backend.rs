@@ -0,0 +1,120 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+use crate::models::{AuthSource, UserRole};
+use crate::utils::{ApiError, ApiResult};
+
+/// A user authenticated by an external provider
+#[derive(Debug, Clone)]
+pub struct ExternalIdentity {
+    pub source: AuthSource,
+    /// Stable identifier at the provider (LDAP `id_attribute` or DN, OIDC `sub`)
+    pub external_id: String,
+    /// Identifiers an existing account may still be stored under, e.g. the DN of an
+    /// LDAP entry that is now identified by its `id_attribute`
+    pub alternate_ids: Vec<String>,
+    pub username: String,
+    pub email: Option<String>,
+    pub groups: Vec<String>,
+    /// Role resolved from the group mapping of the provider
+    pub role: UserRole,
+}
+
+/// Username/password authentication against an external directory
+///
+/// Backends are consulted by `AuthService::login` for users that are not local.
+#[async_trait]
+pub trait PasswordAuthBackend: Send + Sync {
+    fn source(&self) -> AuthSource;
+
+    /// `Ok(None)` for unknown users or wrong passwords, errors for provider failures
+    async fn authenticate(
+        &self,
+        username: &str,
+        password: &str,
+    ) -> ApiResult<Option<ExternalIdentity>>;
+}
+
+/// Maps provider groups to console roles
+#[derive(Debug, Clone, Default)]
+pub struct RoleMapping {
+    group_roles: HashMap<String, UserRole>,
+    default_role: Option<UserRole>,
+}
+
+impl RoleMapping {
+    pub fn new(group_roles: &HashMap<String, UserRole>, default_role: Option<UserRole>) -> Self {
+        let group_roles = group_roles
+            .iter()
+            .map(|(group, role)| (group.trim().to_lowercase(), *role))
+            .collect();
+
+        Self { group_roles, default_role }
+    }
+
+    /// Highest role of all mapped groups, or the default role
+    ///
+    /// Groups match case-insensitively, either by their full value or, for DNs
+    /// like `cn=dba,ou=groups,dc=example,dc=com`, by their CN.
+    pub fn resolve(&self, groups: &[String]) -> Option<UserRole> {
+        groups
+            .iter()
+            .flat_map(|group| {
+                let full = group.trim().to_lowercase();
+                let cn = common_name(&full);
+                [Some(full), cn]
+            })
+            .flatten()
+            .filter_map(|key| self.group_roles.get(&key).copied())
+            .max()
+            .or(self.default_role)
+    }
+
+    /// Like [`resolve`](Self::resolve), but rejects users without any role
+    pub fn require(&self, username: &str, groups: &[String]) -> ApiResult<UserRole> {
+        self.resolve(groups).ok_or_else(|| {
+            tracing::warn!("User '{}' has no group mapped to a console role", username);
+            ApiError::forbidden("Your account is not a member of any group allowed to sign in")
+        })
+    }
+}
+
+fn common_name(group: &str) -> Option<String> {
+    let first = group.split(',').next()?;
+    first.strip_prefix("cn=").map(|cn| cn.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapping(default_role: Option<UserRole>) -> RoleMapping {
+        let groups = HashMap::from([
+            ("StarRocks-Admins".to_string(), UserRole::Admin),
+            ("cn=dba,ou=groups,dc=example,dc=com".to_string(), UserRole::Operator),
+        ]);
+        RoleMapping::new(&groups, default_role)
+    }
+
+    #[test]
+    fn test_highest_mapped_role_wins() {
+        let groups =
+            vec!["cn=dba,ou=groups,dc=example,dc=com".to_string(), "starrocks-admins".to_string()];
+        assert_eq!(mapping(None).resolve(&groups), Some(UserRole::Admin));
+    }
+
+    #[test]
+    fn test_group_dn_matches_by_cn() {
+        let groups = vec!["CN=StarRocks-Admins,OU=Groups,DC=corp,DC=com".to_string()];
+        assert_eq!(mapping(None).resolve(&groups), Some(UserRole::Admin));
+    }
+
+    #[test]
+    fn test_unmapped_groups_use_default_role() {
+        let groups = vec!["cn=sales,ou=groups,dc=example,dc=com".to_string()];
+
+        assert_eq!(mapping(Some(UserRole::Viewer)).resolve(&groups), Some(UserRole::Viewer));
+        assert_eq!(mapping(None).resolve(&groups), None);
+        assert!(mapping(None).require("bob", &groups).is_err());
+    }
+}
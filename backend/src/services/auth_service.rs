@@ -1,51 +1,141 @@
-use crate::models::{CreateUserRequest, LoginRequest, UpdateUserRequest, User};
-use crate::utils::{ApiError, ApiResult, JwtUtil};
+use crate::models::TokenResponse;
+use crate::models::{
+    AcceptInvitationRequest, AdminCreateUserRequest, AuthSource, CreateUserRequest,
+    InvitationResponse, InviteUserRequest, LoginRequest, UpdateUserRequest, User, UserInvitation,
+    UserRole,
+};
+use crate::services::auth_backend::{ExternalIdentity, PasswordAuthBackend};
+use crate::services::permission_service::ensure_other_active_admin;
+use crate::services::{LoginThrottle, PasswordPolicy, SessionService};
+use crate::utils::{ApiError, ApiResult, token};
 use bcrypt::{DEFAULT_COST, hash, verify};
+use chrono::{Duration, Utc};
 use sqlx::SqlitePool;
 use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct AuthService {
     pool: SqlitePool,
-    jwt_util: Arc<JwtUtil>,
+    session_service: Arc<SessionService>,
+    allow_registration: bool,
+    password_backends: Vec<Arc<dyn PasswordAuthBackend>>,
+    password_policy: PasswordPolicy,
+    login_throttle: Option<Arc<LoginThrottle>>,
 }
 
+/// Stored as password hash of external users; never a valid bcrypt hash
+const EXTERNAL_PASSWORD_HASH: &str = "!external";
+
 impl AuthService {
-    pub fn new(pool: SqlitePool, jwt_util: Arc<JwtUtil>) -> Self {
-        Self { pool, jwt_util }
+    pub fn new(pool: SqlitePool, session_service: Arc<SessionService>) -> Self {
+        Self {
+            pool,
+            session_service,
+            allow_registration: false,
+            password_backends: Vec::new(),
+            password_policy: PasswordPolicy::default(),
+            login_throttle: None,
+        }
+    }
+
+    /// Rules applied whenever a local password is set
+    pub fn with_password_policy(mut self, password_policy: PasswordPolicy) -> Self {
+        self.password_policy = password_policy;
+        self
+    }
+
+    /// Lock out usernames and client IPs after repeated failed password logins
+    pub fn with_login_throttle(mut self, login_throttle: Arc<LoginThrottle>) -> Self {
+        self.login_throttle = Some(login_throttle);
+        self
+    }
+
+    /// Also accept username/password sign-in through an external directory (LDAP)
+    pub fn with_password_backend(mut self, backend: Arc<dyn PasswordAuthBackend>) -> Self {
+        self.password_backends.push(backend);
+        self
+    }
+
+    pub fn allows_registration(&self) -> bool {
+        self.allow_registration
+    }
+
+    pub fn has_password_backend(&self, source: AuthSource) -> bool {
+        self.password_backends.iter().any(|b| b.source() == source)
+    }
+
+    /// Enable or disable public self-registration (admins can always create users)
+    pub fn with_registration(mut self, allow_registration: bool) -> Self {
+        self.allow_registration = allow_registration;
+        self
     }
 
     // Register a new user
     pub async fn register(&self, req: CreateUserRequest) -> ApiResult<User> {
-        tracing::debug!("Checking if username exists: {}", req.username);
+        if !self.allow_registration {
+            tracing::warn!(
+                "Registration rejected for '{}': self-registration is disabled",
+                req.username
+            );
+            return Err(ApiError::forbidden(
+                "Self-registration is disabled, please ask an administrator for an invitation",
+            ));
+        }
+
+        let user = self
+            .insert_user(
+                &req.username,
+                &req.password,
+                req.email.as_deref(),
+                req.avatar.as_deref(),
+                UserRole::default(),
+            )
+            .await?;
+
+        tracing::info!("User registered successfully: {} (ID: {})", user.username, user.id);
+
+        Ok(user)
+    }
+
+    // Create a user record, rejecting duplicate usernames
+    async fn insert_user(
+        &self,
+        username: &str,
+        password: &str,
+        email: Option<&str>,
+        avatar: Option<&str>,
+        role: UserRole,
+    ) -> ApiResult<User> {
+        if username.trim().is_empty() || password.is_empty() {
+            return Err(ApiError::validation_error("Username and password are required"));
+        }
+        self.password_policy.validate(username, password)?;
+
+        tracing::debug!("Checking if username exists: {}", username);
 
         // Check if username already exists
         let existing_user: Option<User> = sqlx::query_as("SELECT * FROM users WHERE username = ?")
-            .bind(&req.username)
+            .bind(username)
             .fetch_optional(&self.pool)
             .await?;
 
         if existing_user.is_some() {
-            tracing::warn!("Registration failed: username '{}' already exists", req.username);
+            tracing::warn!("User creation failed: username '{}' already exists", username);
             return Err(ApiError::validation_error("Username already exists"));
         }
 
-        tracing::debug!("Hashing password for user: {}", req.username);
-        // Hash password
-        let password_hash = hash(&req.password, DEFAULT_COST).map_err(|e| {
-            tracing::error!("Password hashing failed for user {}: {}", req.username, e);
-            ApiError::internal_error(format!("Failed to hash password: {}", e))
-        })?;
+        tracing::debug!("Hashing password for user: {}", username);
+        let password_hash = Self::hash_password(password)?;
 
-        tracing::debug!("Inserting user into database: {}", req.username);
-        // Insert user
+        tracing::debug!("Inserting user into database: {}", username);
         let result = sqlx::query(
-            "INSERT INTO users (username, password_hash, email, avatar) VALUES (?, ?, ?, ?)",
+            "INSERT INTO users (username, password_hash, email, avatar, role) VALUES (?, ?, ?, ?, ?)",
         )
-        .bind(&req.username)
+        .bind(username)
         .bind(&password_hash)
-        .bind(&req.email)
-        .bind(&req.avatar)
+        .bind(email)
+        .bind(avatar)
+        .bind(role)
         .execute(&self.pool)
         .await?;
 
@@ -57,13 +147,43 @@ impl AuthService {
             .fetch_one(&self.pool)
             .await?;
 
-        tracing::info!("User registered successfully: {} (ID: {})", user.username, user.id);
-
         Ok(user)
     }
 
-    // Login and generate JWT token
-    pub async fn login(&self, req: LoginRequest) -> ApiResult<(User, String)> {
+    fn hash_password(password: &str) -> ApiResult<String> {
+        hash(password, DEFAULT_COST).map_err(|e| {
+            tracing::error!("Password hashing failed: {}", e);
+            ApiError::internal_error(format!("Failed to hash password: {}", e))
+        })
+    }
+
+    // Login and start a session (access + refresh token)
+    //
+    // Failed attempts count against the username and the client IP; while either
+    // is locked out, attempts are rejected without checking the password.
+    pub async fn login(
+        &self,
+        req: LoginRequest,
+        client_ip: Option<&str>,
+    ) -> ApiResult<(User, TokenResponse)> {
+        let Some(throttle) = &self.login_throttle else {
+            return self.login_with_password(&req).await;
+        };
+
+        throttle.check(&req.username, client_ip).await?;
+        let result = self.login_with_password(&req).await;
+        match &result {
+            Ok(_) => throttle.record_success(&req.username).await?,
+            Err(ApiError::InvalidCredentials) => {
+                throttle.record_failure(&req.username, client_ip).await?
+            },
+            Err(_) => {},
+        }
+
+        result
+    }
+
+    async fn login_with_password(&self, req: &LoginRequest) -> ApiResult<(User, TokenResponse)> {
         tracing::debug!("Looking up user: {}", req.username);
 
         // Find user by username
@@ -72,10 +192,37 @@ impl AuthService {
             .fetch_optional(&self.pool)
             .await?;
 
-        let user = user.ok_or_else(|| {
-            tracing::warn!("Login failed: user '{}' not found", req.username);
-            ApiError::invalid_credentials()
-        })?;
+        let user = match user {
+            Some(user) if user.auth_source == AuthSource::Local => user,
+            // External users only authenticate against their own provider
+            Some(user) => {
+                let backend = self
+                    .password_backends
+                    .iter()
+                    .find(|b| b.source() == user.auth_source)
+                    .ok_or_else(|| {
+                        tracing::warn!(
+                            "Login failed: {} sign-in is not enabled for user '{}'",
+                            user.auth_source.as_str(),
+                            req.username
+                        );
+                        ApiError::invalid_credentials()
+                    })?;
+                return self.login_with_backend(backend.as_ref(), req).await;
+            },
+            None => {
+                for backend in &self.password_backends {
+                    if let Some(identity) =
+                        backend.authenticate(&req.username, &req.password).await?
+                    {
+                        return self.login_external(identity).await;
+                    }
+                }
+
+                tracing::warn!("Login failed: user '{}' not found", req.username);
+                return Err(ApiError::invalid_credentials());
+            },
+        };
 
         tracing::debug!("Verifying password for user: {}", req.username);
         // Verify password
@@ -89,19 +236,164 @@ impl AuthService {
             return Err(ApiError::invalid_credentials());
         }
 
-        tracing::debug!("Generating JWT token for user: {}", req.username);
-        // Generate JWT token
-        let token = self
-            .jwt_util
-            .generate_token(user.id, &user.username)
+        // Checked after the password so disabled accounts cannot be probed
+        if user.is_disabled {
+            tracing::warn!("Login failed: user '{}' is disabled", req.username);
+            return Err(ApiError::unauthorized("User account is disabled"));
+        }
+
+        tracing::debug!("Generating tokens for user: {}", req.username);
+        let tokens = self
+            .session_service
+            .start_session(&user)
+            .await
             .map_err(|e| {
-                tracing::error!("JWT token generation failed for user {}: {:?}", req.username, e);
+                tracing::error!("Token generation failed for user {}: {:?}", req.username, e);
                 e
             })?;
 
         tracing::info!("User logged in successfully: {} (ID: {})", user.username, user.id);
 
-        Ok((user, token))
+        Ok((user, tokens))
+    }
+
+    async fn login_with_backend(
+        &self,
+        backend: &dyn PasswordAuthBackend,
+        req: &LoginRequest,
+    ) -> ApiResult<(User, TokenResponse)> {
+        let identity = backend
+            .authenticate(&req.username, &req.password)
+            .await?
+            .ok_or_else(|| {
+                tracing::warn!(
+                    "Login failed: {} rejected credentials for user '{}'",
+                    backend.source().as_str(),
+                    req.username
+                );
+                ApiError::invalid_credentials()
+            })?;
+
+        self.login_external(identity).await
+    }
+
+    // Sign in a user authenticated by an external provider, provisioning it on first login
+    //
+    // The role is re-synced from the provider's group mapping on every sign-in.
+    pub async fn login_external(
+        &self,
+        identity: ExternalIdentity,
+    ) -> ApiResult<(User, TokenResponse)> {
+        let user = match self.find_external_user(&identity).await? {
+            Some(user) => {
+                sqlx::query(
+                    "UPDATE users SET role = ?, email = COALESCE(?, email), external_id = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+                )
+                .bind(identity.role)
+                .bind(&identity.email)
+                .bind(&identity.external_id)
+                .bind(user.id)
+                .execute(&self.pool)
+                .await?;
+
+                if user.external_id.as_deref() != Some(identity.external_id.as_str()) {
+                    tracing::info!(
+                        "{} user {} re-linked from {} to {}",
+                        identity.source.as_str(),
+                        user.username,
+                        user.external_id.as_deref().unwrap_or_default(),
+                        identity.external_id
+                    );
+                }
+
+                if user.role != identity.role {
+                    tracing::info!(
+                        "Role of {} user {} synced from groups: {} -> {}",
+                        identity.source.as_str(),
+                        user.username,
+                        user.role,
+                        identity.role
+                    );
+                }
+
+                self.find_user(user.id).await?
+            },
+            None => self.provision_external_user(&identity).await?,
+        };
+
+        if user.is_disabled {
+            tracing::warn!("Login failed: user '{}' is disabled", user.username);
+            return Err(ApiError::unauthorized("User account is disabled"));
+        }
+
+        let tokens = self.session_service.start_session(&user).await?;
+        tracing::info!(
+            "User logged in via {}: {} (ID: {})",
+            identity.source.as_str(),
+            user.username,
+            user.id
+        );
+
+        Ok((user, tokens))
+    }
+
+    // Account linked to the identity, by its id or an id it was stored under before
+    async fn find_external_user(&self, identity: &ExternalIdentity) -> ApiResult<Option<User>> {
+        for id in std::iter::once(&identity.external_id).chain(&identity.alternate_ids) {
+            let user: Option<User> =
+                sqlx::query_as("SELECT * FROM users WHERE auth_source = ? AND external_id = ?")
+                    .bind(identity.source)
+                    .bind(id)
+                    .fetch_optional(&self.pool)
+                    .await?;
+            if user.is_some() {
+                return Ok(user);
+            }
+        }
+        Ok(None)
+    }
+
+    async fn provision_external_user(&self, identity: &ExternalIdentity) -> ApiResult<User> {
+        // Also blocks look-alike accounts that differ only in case
+        let taken: Option<User> =
+            sqlx::query_as("SELECT * FROM users WHERE username = ? COLLATE NOCASE")
+                .bind(&identity.username)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        // Never merge an external identity into an account owned by another source
+        if taken.is_some() {
+            tracing::warn!(
+                "Cannot provision {} user '{}': username is already taken",
+                identity.source.as_str(),
+                identity.username
+            );
+            return Err(ApiError::forbidden(
+                "An account with this username already exists, please contact an administrator",
+            ));
+        }
+
+        let result = sqlx::query(
+            "INSERT INTO users (username, password_hash, email, role, auth_source, external_id) VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&identity.username)
+        .bind(EXTERNAL_PASSWORD_HASH)
+        .bind(&identity.email)
+        .bind(identity.role)
+        .bind(identity.source)
+        .bind(&identity.external_id)
+        .execute(&self.pool)
+        .await?;
+
+        tracing::info!(
+            "Provisioned {} user {} (ID: {}, role: {})",
+            identity.source.as_str(),
+            identity.username,
+            result.last_insert_rowid(),
+            identity.role
+        );
+
+        self.find_user(result.last_insert_rowid()).await
     }
 
     // Get user by ID
@@ -123,6 +415,12 @@ impl AuthService {
 
         // If changing password, verify current password first
         if let (Some(current_pwd), Some(new_pwd)) = (&req.current_password, &req.new_password) {
+            if user.auth_source != AuthSource::Local {
+                return Err(ApiError::validation_error(
+                    "Password is managed by the external identity provider",
+                ));
+            }
+
             tracing::debug!("Verifying current password for user_id: {}", user_id);
             let valid = verify(current_pwd, &user.password_hash).map_err(|e| {
                 tracing::error!("Password verification error: {}", e);
@@ -134,16 +432,20 @@ impl AuthService {
                 return Err(ApiError::validation_error("Current password is incorrect"));
             }
 
+            if new_pwd == current_pwd {
+                return Err(ApiError::validation_error(
+                    "New password must differ from the current password",
+                ));
+            }
+            self.password_policy.validate(&user.username, new_pwd)?;
+
             // Hash new password
             tracing::debug!("Hashing new password for user_id: {}", user_id);
-            let new_password_hash = hash(new_pwd, DEFAULT_COST).map_err(|e| {
-                tracing::error!("Password hashing failed: {}", e);
-                ApiError::internal_error(format!("Failed to hash password: {}", e))
-            })?;
+            let new_password_hash = Self::hash_password(new_pwd)?;
 
-            // Update password
+            // Update password; this also satisfies a pending forced change
             sqlx::query(
-                "UPDATE users SET password_hash = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+                "UPDATE users SET password_hash = ?, must_change_password = 0, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
             )
             .bind(&new_password_hash)
             .bind(user_id)
@@ -183,4 +485,327 @@ impl AuthService {
 
         Ok(updated_user)
     }
+
+    // List all users (admin)
+    pub async fn list_users(&self) -> ApiResult<Vec<User>> {
+        let users: Vec<User> = sqlx::query_as("SELECT * FROM users ORDER BY id")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(users)
+    }
+
+    // Look up a user for administration, reporting a missing one as 404
+    async fn find_user(&self, user_id: i64) -> ApiResult<User> {
+        let user: Option<User> = sqlx::query_as("SELECT * FROM users WHERE id = ?")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        user.ok_or_else(|| ApiError::not_found(format!("User {} not found", user_id)))
+    }
+
+    // Create a user directly (admin), works regardless of the registration setting
+    pub async fn create_user(&self, req: AdminCreateUserRequest) -> ApiResult<User> {
+        let user = self
+            .insert_user(&req.username, &req.password, req.email.as_deref(), None, req.role)
+            .await?;
+
+        tracing::info!(
+            "User created by admin: {} (ID: {}, role: {})",
+            user.username,
+            user.id,
+            user.role
+        );
+
+        Ok(user)
+    }
+
+    // Create a one-time invitation; only the hash of the token is stored
+    pub async fn invite_user(
+        &self,
+        invited_by: i64,
+        req: InviteUserRequest,
+    ) -> ApiResult<InvitationResponse> {
+        if req.expires_in_hours <= 0 {
+            return Err(ApiError::validation_error("expires_in_hours must be positive"));
+        }
+
+        let token = token::generate_secret("inv_", 32);
+        let expires_at = Utc::now() + Duration::hours(req.expires_in_hours);
+
+        let result = sqlx::query(
+            "INSERT INTO user_invitations (token_hash, email, role, invited_by, expires_at) VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(token::hash_secret(&token))
+        .bind(&req.email)
+        .bind(req.role)
+        .bind(invited_by)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        tracing::info!(
+            "Invitation {} created by user {} (role: {}, expires at {})",
+            result.last_insert_rowid(),
+            invited_by,
+            req.role,
+            expires_at
+        );
+
+        Ok(InvitationResponse {
+            id: result.last_insert_rowid(),
+            token,
+            email: req.email,
+            role: req.role,
+            expires_at,
+        })
+    }
+
+    // Redeem an invitation and create the invited account
+    pub async fn accept_invitation(&self, req: AcceptInvitationRequest) -> ApiResult<User> {
+        let invitation: Option<UserInvitation> =
+            sqlx::query_as(
+                "SELECT id, email, role, expires_at, accepted_at FROM user_invitations WHERE token_hash = ?",
+            )
+                .bind(token::hash_secret(&req.token))
+                .fetch_optional(&self.pool)
+                .await?;
+
+        let invitation = invitation
+            .filter(|inv| inv.accepted_at.is_none() && inv.expires_at > Utc::now())
+            .ok_or_else(|| {
+                tracing::warn!(
+                    "Invitation rejected for '{}': invalid, used or expired",
+                    req.username
+                );
+                ApiError::validation_error("Invitation is invalid or has expired")
+            })?;
+
+        let email = req.email.as_deref().or(invitation.email.as_deref());
+        let user = self
+            .insert_user(&req.username, &req.password, email, None, invitation.role)
+            .await?;
+
+        // Guard against concurrent redemption of the same token
+        let result = sqlx::query(
+            "UPDATE user_invitations SET accepted_at = CURRENT_TIMESTAMP, accepted_user_id = ? WHERE id = ? AND accepted_at IS NULL",
+        )
+        .bind(user.id)
+        .bind(invitation.id)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            sqlx::query("DELETE FROM users WHERE id = ?")
+                .bind(user.id)
+                .execute(&self.pool)
+                .await?;
+            return Err(ApiError::validation_error("Invitation is invalid or has expired"));
+        }
+
+        tracing::info!(
+            "Invitation {} accepted: {} (ID: {}, role: {})",
+            invitation.id,
+            user.username,
+            user.id,
+            user.role
+        );
+
+        Ok(user)
+    }
+
+    // Disable or re-enable an account; disabled users cannot log in or use existing tokens
+    pub async fn set_user_disabled(
+        &self,
+        acting_user_id: i64,
+        user_id: i64,
+        disabled: bool,
+    ) -> ApiResult<User> {
+        if disabled && acting_user_id == user_id {
+            return Err(ApiError::validation_error("You cannot disable your own account"));
+        }
+
+        let user = self.find_user(user_id).await?;
+        if disabled && user.role == UserRole::Admin {
+            ensure_other_active_admin(&self.pool, user_id).await?;
+        }
+
+        sqlx::query(
+            "UPDATE users SET is_disabled = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+        )
+        .bind(disabled)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        if disabled {
+            self.session_service
+                .revoke_user_sessions(user_id, "user_disabled")
+                .await?;
+        } else {
+            self.clear_lockout(&user.username).await?;
+        }
+
+        tracing::info!(
+            "User {} (ID: {}) {} by user {}",
+            user.username,
+            user_id,
+            if disabled { "disabled" } else { "enabled" },
+            acting_user_id
+        );
+
+        self.find_user(user_id).await
+    }
+
+    // Set a new password without knowing the current one (admin)
+    pub async fn reset_password(&self, user_id: i64, new_password: &str) -> ApiResult<User> {
+        if new_password.is_empty() {
+            return Err(ApiError::validation_error("Password cannot be empty"));
+        }
+
+        let user = self.find_user(user_id).await?;
+        if user.auth_source != AuthSource::Local {
+            return Err(ApiError::validation_error(
+                "Password is managed by the external identity provider",
+            ));
+        }
+        self.password_policy
+            .validate(&user.username, new_password)?;
+
+        let password_hash = Self::hash_password(new_password)?;
+
+        sqlx::query(
+            "UPDATE users SET password_hash = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+        )
+        .bind(&password_hash)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        // Whoever knew the old password must not keep a session
+        self.session_service
+            .revoke_user_sessions(user_id, "password_reset")
+            .await?;
+        self.clear_lockout(&user.username).await?;
+
+        tracing::info!("Password reset for user {} (ID: {})", user.username, user_id);
+
+        self.find_user(user_id).await
+    }
+
+    // Delete a user; per-cluster grants are removed by cascade
+    pub async fn delete_user(&self, acting_user_id: i64, user_id: i64) -> ApiResult<()> {
+        if acting_user_id == user_id {
+            return Err(ApiError::validation_error("You cannot delete your own account"));
+        }
+
+        let user = self.find_user(user_id).await?;
+        if user.role == UserRole::Admin {
+            ensure_other_active_admin(&self.pool, user_id).await?;
+        }
+
+        sqlx::query("DELETE FROM users WHERE id = ?")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        tracing::info!(
+            "User {} (ID: {}) deleted by user {}",
+            user.username,
+            user_id,
+            acting_user_id
+        );
+
+        Ok(())
+    }
+
+    async fn clear_lockout(&self, username: &str) -> ApiResult<()> {
+        match &self.login_throttle {
+            Some(throttle) => throttle.clear(username).await,
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_pool;
+    use crate::utils::JwtUtil;
+
+    async fn service() -> AuthService {
+        let pool = test_pool().await;
+        let jwt_util = Arc::new(JwtUtil::new("test-secret", "15m"));
+        let sessions = Arc::new(SessionService::new(pool.clone(), jwt_util, "7d"));
+        AuthService::new(pool, sessions)
+    }
+
+    fn ldap_identity(username: &str, external_id: &str, dn: &str) -> ExternalIdentity {
+        ExternalIdentity {
+            source: AuthSource::Ldap,
+            external_id: external_id.to_string(),
+            alternate_ids: vec![dn.to_string()],
+            username: username.to_string(),
+            email: None,
+            groups: Vec::new(),
+            role: UserRole::Viewer,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_external_user_is_linked_by_stable_id_or_dn() {
+        let service = service().await;
+        let dn = "uid=alice,ou=people,dc=example,dc=com";
+
+        // Provisioned while the directory only offered the DN
+        let (first, _) = service
+            .login_external(ExternalIdentity {
+                alternate_ids: Vec::new(),
+                ..ldap_identity("alice", dn, "")
+            })
+            .await
+            .unwrap();
+        assert_eq!(first.external_id.as_deref(), Some(dn));
+
+        // Next sign-in with a stable id finds the account by its DN and re-links it
+        let (relinked, _) = service
+            .login_external(ldap_identity("alice", "uuid-1", dn))
+            .await
+            .unwrap();
+        assert_eq!(relinked.id, first.id);
+        assert_eq!(relinked.external_id.as_deref(), Some("uuid-1"));
+
+        // Moving the entry changes the DN but keeps the account
+        let (moved, _) = service
+            .login_external(ldap_identity(
+                "alice",
+                "uuid-1",
+                "uid=alice,ou=staff,dc=example,dc=com",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(moved.id, first.id);
+    }
+
+    #[tokio::test]
+    async fn test_username_taken_in_another_case_is_not_duplicated() {
+        let service = service().await;
+        sqlx::query("INSERT INTO users (username, password_hash) VALUES ('Bob', 'x')")
+            .execute(&service.pool)
+            .await
+            .unwrap();
+
+        let err = service
+            .login_external(ldap_identity("bob", "uuid-2", "uid=bob,dc=example,dc=com"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ApiError::Forbidden(_)), "{:?}", err);
+        let (count,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM users WHERE username = 'bob' COLLATE NOCASE")
+                .fetch_one(&service.pool)
+                .await
+                .unwrap();
+        assert_eq!(count, 1);
+    }
 }
@@ -0,0 +1,772 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinSet;
+
+use crate::config::DiagnosticsConfig;
+use crate::models::{Backend, ClusterHealth, Frontend, HealthCheck, Severity, TabletStatistic};
+use crate::services::{ClusterService, StarRocksClient};
+use crate::utils::ApiResult;
+
+/// Time allowed for reading the clock of one node
+const CLOCK_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Deep health check of a cluster with one structured check per aspect
+///
+/// FE, BE and tablet state come from the FE API; node clocks are read from the
+/// `Date` header of each node's HTTP server.
+pub struct ClusterDiagnosticsService {
+    cluster_service: Arc<ClusterService>,
+    config: DiagnosticsConfig,
+}
+
+impl ClusterDiagnosticsService {
+    pub fn new(cluster_service: Arc<ClusterService>, config: DiagnosticsConfig) -> Self {
+        Self { cluster_service, config }
+    }
+
+    pub async fn diagnose(&self, cluster_id: i64) -> ApiResult<ClusterHealth> {
+        let cluster = self.cluster_service.get_cluster(cluster_id).await?;
+        let client = StarRocksClient::new(cluster)?;
+
+        let (frontends, backends, statistics) = tokio::join!(
+            client.get_frontends(),
+            client.get_backends(),
+            client.get_tablet_statistics()
+        );
+
+        let mut checks = Vec::new();
+        match &frontends {
+            Ok(frontends) => {
+                checks.push(check_fe_leader(frontends));
+                checks.push(check_fe_quorum(frontends));
+                checks.push(check_journal_replay(frontends, &self.config));
+            },
+            Err(e) => checks.push(
+                HealthCheck::new(
+                    "FE Availability",
+                    Severity::Critical,
+                    format!("Cannot read the FE list: {}", e),
+                )
+                .component("fe")
+                .remediation(
+                    "Check that the FE is running, its HTTP port is reachable from this \
+                     server and the cluster credentials are valid",
+                ),
+            ),
+        }
+        let fe_list = frontends.as_deref().unwrap_or_default();
+
+        match &backends {
+            Ok(backends) => {
+                checks.push(check_be_availability(backends));
+                checks.push(check_be_heartbeat(backends, fe_list, &self.config));
+                checks.push(check_decommission(backends));
+                checks.push(check_disk_usage(backends, &self.config));
+            },
+            Err(e) => checks.push(
+                HealthCheck::new(
+                    "BE Availability",
+                    Severity::Warning,
+                    format!("Cannot read the BE list: {}", e),
+                )
+                .component("be"),
+            ),
+        }
+        let be_list = backends.as_deref().unwrap_or_default();
+
+        checks.push(match &statistics {
+            Ok(statistics) => check_tablets(statistics),
+            Err(e) => HealthCheck::new(
+                "Tablet Replicas",
+                Severity::Info,
+                format!("Tablet statistics are unavailable: {}", e),
+            )
+            .component("tablet"),
+        });
+        checks.push(check_versions(fe_list, be_list));
+        checks.push(check_clock_skew(&read_clocks(&client, fe_list, be_list).await, &self.config));
+
+        let status = checks
+            .iter()
+            .map(HealthCheck::severity)
+            .max()
+            .unwrap_or(Severity::Ok)
+            .into();
+        Ok(ClusterHealth { status, checks, last_check_time: Utc::now() })
+    }
+}
+
+fn is_leader(fe: &Frontend) -> bool {
+    fe.role.eq_ignore_ascii_case("LEADER")
+        || fe
+            .is_master
+            .as_deref()
+            .is_some_and(|m| m.eq_ignore_ascii_case("true"))
+}
+
+fn is_alive(alive: &str) -> bool {
+    alive.eq_ignore_ascii_case("true")
+}
+
+fn fe_name(fe: &Frontend) -> String {
+    format!("{}:{}", fe.host, fe.edit_log_port)
+}
+
+fn be_name(be: &Backend) -> String {
+    format!("{}:{}", be.host, be.heartbeat_port)
+}
+
+fn check_fe_leader(frontends: &[Frontend]) -> HealthCheck {
+    let leaders: Vec<&Frontend> = frontends.iter().filter(|fe| is_leader(fe)).collect();
+    let check = match leaders.as_slice() {
+        [] => HealthCheck::new("FE Leader", Severity::Critical, "No FE is the leader").remediation(
+            "Metadata cannot change without a leader. Start the stopped followers so that \
+             a majority can elect one, and check fe.log of the followers for election errors",
+        ),
+        [leader] if !is_alive(&leader.alive) => HealthCheck::new(
+            "FE Leader",
+            Severity::Critical,
+            format!("Leader FE {} is not alive", fe_name(leader)),
+        )
+        .affected(vec![fe_name(leader)])
+        .remediation("Restart the leader FE or make sure the followers can elect a new one"),
+        [leader] => {
+            HealthCheck::new("FE Leader", Severity::Ok, format!("Leader FE is {}", fe_name(leader)))
+        },
+        _ => HealthCheck::new(
+            "FE Leader",
+            Severity::Critical,
+            format!("{} FEs report to be the leader", leaders.len()),
+        )
+        .affected(leaders.iter().map(|fe| fe_name(fe)).collect())
+        .remediation(
+            "The FEs disagree about the leader, e.g. after a network partition. Check the \
+             network between the FEs and restart the FEs that are not in the majority",
+        ),
+    };
+    check.component("fe")
+}
+
+fn check_fe_quorum(frontends: &[Frontend]) -> HealthCheck {
+    // Followers and the leader vote; observers only serve reads
+    let (electable, observers): (Vec<&Frontend>, Vec<&Frontend>) = frontends
+        .iter()
+        .partition(|fe| is_leader(fe) || fe.role.eq_ignore_ascii_case("FOLLOWER"));
+    let dead = |fes: &[&Frontend]| -> Vec<String> {
+        fes.iter()
+            .filter(|fe| !is_alive(&fe.alive))
+            .map(|fe| fe_name(fe))
+            .collect()
+    };
+    let dead_electable = dead(&electable);
+    let dead_observers = dead(&observers);
+    let alive = electable.len() - dead_electable.len();
+    let quorum = electable.len() / 2 + 1;
+
+    let check = if alive < quorum {
+        HealthCheck::new(
+            "FE Quorum",
+            Severity::Critical,
+            format!(
+                "Only {} of {} leader-electing FEs are alive, {} are needed",
+                alive,
+                electable.len(),
+                quorum
+            ),
+        )
+        .affected(dead_electable)
+        .remediation(
+            "Without a majority of followers the cluster cannot write metadata. Restart the \
+             dead followers; check their fe.log and disk space of meta_dir",
+        )
+    } else if !dead_electable.is_empty() || !dead_observers.is_empty() {
+        let mut dead = dead_electable;
+        dead.extend(dead_observers);
+        HealthCheck::new(
+            "FE Quorum",
+            Severity::Warning,
+            format!(
+                "{} of {} leader-electing FEs alive (quorum {}), {} FE(s) down",
+                alive,
+                electable.len(),
+                quorum,
+                dead.len()
+            ),
+        )
+        .affected(dead)
+        .remediation(
+            "Restart the dead FEs; while followers are down fewer further failures are \
+             tolerated",
+        )
+    } else {
+        HealthCheck::new(
+            "FE Quorum",
+            Severity::Ok,
+            format!(
+                "All {} FEs alive ({} leader-electing, {} observers)",
+                frontends.len(),
+                electable.len(),
+                observers.len()
+            ),
+        )
+    };
+    check.component("fe")
+}
+
+fn check_journal_replay(frontends: &[Frontend], config: &DiagnosticsConfig) -> HealthCheck {
+    let journal = |fe: &Frontend| fe.replayed_journal_id.trim().parse::<i64>().ok();
+    let Some(leader_id) = frontends.iter().find(|fe| is_leader(fe)).and_then(journal) else {
+        return HealthCheck::new(
+            "FE Journal Replay",
+            Severity::Info,
+            "Replay lag cannot be computed without the leader's journal ID",
+        )
+        .component("fe");
+    };
+
+    let mut lags: Vec<(String, i64)> = frontends
+        .iter()
+        .filter(|fe| !is_leader(fe) && is_alive(&fe.alive))
+        .filter_map(|fe| Some((fe_name(fe), (leader_id - journal(fe)?).max(0))))
+        .collect();
+    lags.sort_by(|a, b| b.1.cmp(&a.1));
+    let max_lag = lags.first().map(|(_, lag)| *lag).unwrap_or(0);
+
+    let severity = if max_lag >= config.journal_lag_critical {
+        Severity::Critical
+    } else if max_lag >= config.journal_lag_warning {
+        Severity::Warning
+    } else {
+        Severity::Ok
+    };
+    let mut check = HealthCheck::new(
+        "FE Journal Replay",
+        severity,
+        format!(
+            "Largest replay lag behind the leader is {} journal entries across {} FE(s)",
+            max_lag,
+            lags.len()
+        ),
+    );
+    if severity != Severity::Ok {
+        check = check
+            .affected(
+                lags.iter()
+                    .filter(|(_, lag)| *lag >= config.journal_lag_warning)
+                    .map(|(fe, lag)| format!("{} ({} behind)", fe, lag))
+                    .collect(),
+            )
+            .remediation(
+                "Lagging FEs serve stale metadata. Check their load, GC pauses and network \
+                 to the leader in fe.log / fe.gc.log; restart an FE that does not catch up",
+            );
+    }
+    check.component("fe")
+}
+
+fn check_be_availability(backends: &[Backend]) -> HealthCheck {
+    let dead: Vec<String> = backends
+        .iter()
+        .filter(|be| !is_alive(&be.alive))
+        .map(|be| match be.err_msg.as_deref().filter(|m| !m.is_empty()) {
+            Some(error) => format!("{} ({})", be_name(be), error),
+            None => be_name(be),
+        })
+        .collect();
+
+    let check = if backends.is_empty() {
+        HealthCheck::new("BE Availability", Severity::Info, "The cluster has no BEs")
+    } else if dead.is_empty() {
+        HealthCheck::new(
+            "BE Availability",
+            Severity::Ok,
+            format!("All {} BEs are alive", backends.len()),
+        )
+    } else {
+        let severity =
+            if dead.len() == backends.len() { Severity::Critical } else { Severity::Warning };
+        HealthCheck::new(
+            "BE Availability",
+            severity,
+            format!("{} of {} BEs are not alive", dead.len(), backends.len()),
+        )
+        .affected(dead)
+        .remediation(
+            "Check be.INFO / be.out on the dead BEs and that the heartbeat port is reachable \
+             from the FEs; replicas on dead BEs are repaired elsewhere after a while",
+        )
+    };
+    check.component("be")
+}
+
+fn parse_heartbeat(text: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(text.trim(), "%Y-%m-%d %H:%M:%S").ok()
+}
+
+// Heartbeat times are FE local time without a zone, so ages are measured against the
+// freshest heartbeat of any node instead of this server's clock
+fn check_be_heartbeat(
+    backends: &[Backend],
+    frontends: &[Frontend],
+    config: &DiagnosticsConfig,
+) -> HealthCheck {
+    let freshest = backends
+        .iter()
+        .map(|be| be.last_heartbeat.as_str())
+        .chain(frontends.iter().map(|fe| fe.last_heartbeat.as_str()))
+        .filter_map(parse_heartbeat)
+        .max();
+    let Some(freshest) = freshest else {
+        return HealthCheck::new("BE Heartbeat", Severity::Info, "No heartbeat times reported")
+            .component("be");
+    };
+
+    let mut ages: Vec<(String, i64)> = backends
+        .iter()
+        .filter(|be| is_alive(&be.alive))
+        .filter_map(|be| {
+            let age = (freshest - parse_heartbeat(&be.last_heartbeat)?).num_seconds();
+            Some((be_name(be), age))
+        })
+        .collect();
+    ages.sort_by(|a, b| b.1.cmp(&a.1));
+    let oldest = ages.first().map(|(_, age)| *age).unwrap_or(0);
+
+    let severity = if oldest >= config.heartbeat_age_critical_secs {
+        Severity::Critical
+    } else if oldest >= config.heartbeat_age_warning_secs {
+        Severity::Warning
+    } else {
+        Severity::Ok
+    };
+    let mut check = HealthCheck::new(
+        "BE Heartbeat",
+        severity,
+        format!("Oldest heartbeat of an alive BE is {}s old", oldest),
+    );
+    if severity != Severity::Ok {
+        check = check
+            .affected(
+                ages.iter()
+                    .filter(|(_, age)| *age >= config.heartbeat_age_warning_secs)
+                    .map(|(be, age)| format!("{} ({}s)", be, age))
+                    .collect(),
+            )
+            .remediation(
+                "The leader FE has not reached these BEs recently. Check the network between \
+                 the leader and the BEs and the load of the BE processes",
+            );
+    }
+    check.component("be")
+}
+
+fn check_decommission(backends: &[Backend]) -> HealthCheck {
+    let decommissioning: Vec<String> = backends
+        .iter()
+        .filter(|be| is_alive(&be.system_decommissioned))
+        .map(|be| format!("{} ({} tablets left)", be_name(be), be.tablet_num))
+        .collect();
+
+    let check = if decommissioning.is_empty() {
+        HealthCheck::new("BE Decommission", Severity::Ok, "No BE is being decommissioned")
+    } else {
+        HealthCheck::new(
+            "BE Decommission",
+            Severity::Info,
+            format!("{} BE(s) are being decommissioned", decommissioning.len()),
+        )
+        .affected(decommissioning)
+        .remediation(
+            "Tablets are moved off these BEs before they are dropped. A decommission that \
+             does not progress usually lacks disk space or BEs for the replicas; it can be \
+             stopped with CANCEL DECOMMISSION BACKEND \"host:heartbeat_port\"",
+        )
+    };
+    check.component("be")
+}
+
+/// `85.12 %` as shown by `SHOW BACKENDS`
+fn parse_percent(text: &str) -> Option<f64> {
+    text.trim().trim_end_matches('%').trim().parse().ok()
+}
+
+fn check_disk_usage(backends: &[Backend], config: &DiagnosticsConfig) -> HealthCheck {
+    let mut usage: Vec<(String, f64)> = backends
+        .iter()
+        .filter_map(|be| Some((be_name(be), parse_percent(&be.max_disk_used_pct)?)))
+        .collect();
+    usage.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let fullest = usage.first().map(|(_, pct)| *pct).unwrap_or(0.0);
+
+    let severity = if fullest >= config.disk_usage_critical_pct {
+        Severity::Critical
+    } else if fullest >= config.disk_usage_warning_pct {
+        Severity::Warning
+    } else {
+        Severity::Ok
+    };
+    let mut check = HealthCheck::new(
+        "Disk Usage",
+        severity,
+        format!("Fullest BE disk is {:.1}% used", fullest),
+    );
+    if severity != Severity::Ok {
+        check = check
+            .affected(
+                usage
+                    .iter()
+                    .filter(|(_, pct)| *pct >= config.disk_usage_warning_pct)
+                    .map(|(be, pct)| format!("{} ({:.1}%)", be, pct))
+                    .collect(),
+            )
+            .remediation(
+                "BEs reject loads once a disk reaches storage_flood_stage_usage_percent (95% \
+                 by default). Drop expired partitions, lower replication of cold tables, or \
+                 add disks or BEs",
+            );
+    }
+    check.component("be")
+}
+
+fn check_tablets(statistics: &[TabletStatistic]) -> HealthCheck {
+    let count = |text: &str| text.trim().parse::<i64>().unwrap_or(0);
+    let (databases, total): (Vec<&TabletStatistic>, Vec<&TabletStatistic>) = statistics
+        .iter()
+        .partition(|row| !row.db_id.eq_ignore_ascii_case("total"));
+    let sum = |field: fn(&TabletStatistic) -> &str| -> i64 {
+        match total.first() {
+            Some(total) => count(field(total)),
+            None => databases.iter().map(|row| count(field(row))).sum(),
+        }
+    };
+    let tablets = sum(|row| &row.tablet_num);
+    let unhealthy = sum(|row| &row.unhealthy_tablet_num);
+    let inconsistent = sum(|row| &row.inconsistent_tablet_num);
+    let cloning = sum(|row| &row.cloning_tablet_num);
+    let error_state = sum(|row| &row.error_state_tablet_num);
+
+    let severity = if error_state > 0 {
+        Severity::Critical
+    } else if unhealthy > 0 || inconsistent > 0 {
+        Severity::Warning
+    } else {
+        Severity::Ok
+    };
+    let mut check = HealthCheck::new(
+        "Tablet Replicas",
+        severity,
+        format!(
+            "{} tablets: {} unhealthy, {} inconsistent, {} with replicas in error state, {} \
+             cloning",
+            tablets, unhealthy, inconsistent, error_state, cloning
+        ),
+    );
+    if severity != Severity::Ok {
+        check = check
+            .affected(
+                databases
+                    .iter()
+                    .filter(|row| {
+                        count(&row.unhealthy_tablet_num)
+                            + count(&row.inconsistent_tablet_num)
+                            + count(&row.error_state_tablet_num)
+                            > 0
+                    })
+                    .map(|row| format!("{} (DbId {})", row.db_name, row.db_id))
+                    .collect(),
+            )
+            .remediation(
+                "Missing or broken replicas are repaired automatically while enough BEs are \
+                 alive; check dead or decommissioned BEs first. Inspect a database with SHOW \
+                 PROC '/statistic/<DbId>' and a table with ADMIN SHOW REPLICA STATUS FROM \
+                 <table>",
+            );
+    }
+    check.component("tablet")
+}
+
+fn check_versions(frontends: &[Frontend], backends: &[Backend]) -> HealthCheck {
+    let mut versions: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for fe in frontends {
+        versions
+            .entry(fe.version.trim())
+            .or_default()
+            .push(format!("FE {}", fe_name(fe)));
+    }
+    for be in backends {
+        if let Some(version) = be.version.as_deref() {
+            versions
+                .entry(version.trim())
+                .or_default()
+                .push(format!("BE {}", be_name(be)));
+        }
+    }
+    versions.remove("");
+
+    let check = match versions.len() {
+        0 => HealthCheck::new("Version Consistency", Severity::Info, "No versions reported"),
+        1 => HealthCheck::new(
+            "Version Consistency",
+            Severity::Ok,
+            format!("All nodes run {}", versions.keys().next().copied().unwrap_or_default()),
+        ),
+        _ => HealthCheck::new(
+            "Version Consistency",
+            Severity::Warning,
+            format!(
+                "Nodes run {} different versions: {}",
+                versions.len(),
+                versions
+                    .iter()
+                    .map(|(version, nodes)| format!("{} ({} nodes)", version, nodes.len()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        )
+        .affected(
+            versions
+                .iter()
+                .flat_map(|(version, nodes)| {
+                    nodes
+                        .iter()
+                        .map(move |node| format!("{}: {}", node, version))
+                })
+                .collect(),
+        )
+        .remediation(
+            "Mixed versions are only expected during a rolling upgrade. Finish it: upgrade \
+             all BEs and CNs first, then the FEs, followers before the leader",
+        ),
+    };
+    check.component("cluster")
+}
+
+// Offset of each alive node's clock from this server's, in milliseconds
+async fn read_clocks(
+    client: &StarRocksClient,
+    frontends: &[Frontend],
+    backends: &[Backend],
+) -> Vec<(String, i64)> {
+    let fe_scheme = if client.cluster.enable_ssl { "https" } else { "http" };
+    let targets = frontends
+        .iter()
+        .filter(|fe| is_alive(&fe.alive))
+        .map(|fe| {
+            (
+                format!("FE {}", fe_name(fe)),
+                format!("{}://{}:{}/", fe_scheme, fe.host, fe.http_port),
+            )
+        })
+        .chain(backends.iter().filter(|be| is_alive(&be.alive)).map(|be| {
+            (
+                format!("BE {}", be_name(be)),
+                format!("http://{}:{}/api/health", be.host, be.http_port),
+            )
+        }));
+
+    let mut probes = JoinSet::new();
+    for (node, url) in targets {
+        let http_client = client.http_client.clone();
+        probes.spawn(async move {
+            let sent = Utc::now();
+            let response = http_client
+                .get(&url)
+                .timeout(CLOCK_PROBE_TIMEOUT)
+                .send()
+                .await
+                .ok()?;
+            let received = Utc::now();
+            let date = response
+                .headers()
+                .get(reqwest::header::DATE)?
+                .to_str()
+                .ok()?;
+            let remote = DateTime::parse_from_rfc2822(date).ok()?.with_timezone(&Utc);
+            let local = sent + (received - sent) / 2;
+            Some((node, (remote - local).num_milliseconds()))
+        });
+    }
+
+    let mut offsets = Vec::new();
+    while let Some(result) = probes.join_next().await {
+        if let Ok(Some(offset)) = result {
+            offsets.push(offset);
+        }
+    }
+    offsets
+}
+
+fn check_clock_skew(offsets: &[(String, i64)], config: &DiagnosticsConfig) -> HealthCheck {
+    let (Some(min), Some(max)) = (
+        offsets.iter().min_by_key(|(_, offset)| *offset),
+        offsets.iter().max_by_key(|(_, offset)| *offset),
+    ) else {
+        return HealthCheck::new(
+            "Clock Skew",
+            Severity::Info,
+            "No node reported its time in an HTTP Date header",
+        )
+        .component("cluster");
+    };
+    if offsets.len() < 2 {
+        return HealthCheck::new(
+            "Clock Skew",
+            Severity::Info,
+            "Only one node reported its time, skew between nodes is unknown",
+        )
+        .component("cluster");
+    }
+
+    let skew = max.1 - min.1;
+    let severity = if skew >= config.clock_skew_critical_ms {
+        Severity::Critical
+    } else if skew >= config.clock_skew_warning_ms {
+        Severity::Warning
+    } else {
+        Severity::Ok
+    };
+    let mut check = HealthCheck::new(
+        "Clock Skew",
+        severity,
+        format!(
+            "Clocks of {} nodes differ by up to {}ms (HTTP Date headers, 1s resolution)",
+            offsets.len(),
+            skew
+        ),
+    );
+    if severity != Severity::Ok {
+        let mut sorted = offsets.to_vec();
+        sorted.sort_by_key(|(_, offset)| *offset);
+        check = check
+            .affected(
+                sorted
+                    .iter()
+                    .map(|(node, offset)| format!("{} ({:+}ms)", node, offset))
+                    .collect(),
+            )
+            .remediation(
+                "Synchronize all nodes with NTP (chrony or ntpd). An FE whose clock differs \
+                 from the leader by more than max_bdbje_clock_delta_ms (5s) cannot join",
+            );
+    }
+    check.component("cluster")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn fe(host: &str, role: &str, alive: bool, journal: i64) -> Frontend {
+        serde_json::from_value(json!({
+            "Name": host, "IP": host, "EditLogPort": "9010", "HttpPort": "8030",
+            "QueryPort": "9030", "RpcPort": "9020", "Role": role, "ClusterId": "1",
+            "Join": "true", "Alive": alive.to_string(), "ReplayedJournalId": journal.to_string(),
+            "LastHeartbeat": "2026-10-18 10:00:00", "ErrMsg": "", "Version": "3.3.2-abc",
+        }))
+        .unwrap()
+    }
+
+    fn be(host: &str, alive: bool, heartbeat: &str, disk: &str, version: &str) -> Backend {
+        serde_json::from_value(json!({
+            "BackendId": host, "IP": host, "HeartbeatPort": "9050", "BePort": "9060",
+            "HttpPort": "8040", "BrpcPort": "8060", "LastStartTime": "", "LastHeartbeat": heartbeat,
+            "Alive": alive.to_string(), "SystemDecommissioned": "false", "TabletNum": "10",
+            "DataUsedCapacity": "", "TotalCapacity": "", "UsedPct": "", "MaxDiskUsedPct": disk,
+            "CpuUsedPct": "", "MemUsedPct": "", "NumRunningQueries": "0", "Version": version,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_fe_quorum_and_leader() {
+        let config = DiagnosticsConfig::default();
+        let healthy = [
+            fe("fe1", "LEADER", true, 5000),
+            fe("fe2", "FOLLOWER", true, 4990),
+            fe("fe3", "OBSERVER", true, 3000),
+        ];
+        assert_eq!(check_fe_leader(&healthy).severity(), Severity::Ok);
+        assert_eq!(check_fe_quorum(&healthy).severity(), Severity::Ok);
+        let replay = check_journal_replay(&healthy, &config);
+        assert_eq!(replay.severity(), Severity::Warning);
+        assert_eq!(replay.affected, ["fe3:9010 (2000 behind)"]);
+
+        let degraded = [
+            fe("fe1", "LEADER", true, 1),
+            fe("fe2", "FOLLOWER", false, 1),
+            fe("fe3", "FOLLOWER", true, 1),
+        ];
+        assert_eq!(check_fe_quorum(&degraded).severity(), Severity::Warning);
+
+        let lost = [
+            fe("fe1", "FOLLOWER", true, 1),
+            fe("fe2", "FOLLOWER", false, 1),
+            fe("fe3", "FOLLOWER", false, 1),
+        ];
+        assert_eq!(check_fe_quorum(&lost).severity(), Severity::Critical);
+        assert_eq!(check_fe_leader(&lost).severity(), Severity::Critical);
+        assert_eq!(check_journal_replay(&lost, &config).severity(), Severity::Info);
+    }
+
+    #[test]
+    fn test_backend_checks() {
+        let config = DiagnosticsConfig::default();
+        let backends = [
+            be("be1", true, "2026-10-18 10:00:05", "40.00 %", "3.3.2-abc"),
+            be("be2", true, "2026-10-18 09:59:00", "96.50 %", "3.3.1-def"),
+            be("be3", false, "2026-10-18 09:00:00", "10.00 %", "3.3.2-abc"),
+        ];
+        let frontends = [fe("fe1", "LEADER", true, 1)];
+
+        let availability = check_be_availability(&backends);
+        assert_eq!(availability.severity(), Severity::Warning);
+        assert_eq!(availability.affected, ["be3:9050"]);
+
+        // Dead BEs are covered by availability, not by the heartbeat age
+        let heartbeat = check_be_heartbeat(&backends, &frontends, &config);
+        assert_eq!(heartbeat.severity(), Severity::Warning);
+        assert_eq!(heartbeat.affected, ["be2:9050 (65s)"]);
+
+        let disk = check_disk_usage(&backends, &config);
+        assert_eq!(disk.severity(), Severity::Critical);
+        assert_eq!(disk.affected, ["be2:9050 (96.5%)"]);
+
+        let versions = check_versions(&frontends, &backends);
+        assert_eq!(versions.severity(), Severity::Warning);
+        assert!(
+            versions
+                .affected
+                .contains(&"BE be2:9050: 3.3.1-def".to_string())
+        );
+        assert_eq!(check_decommission(&backends).severity(), Severity::Ok);
+    }
+
+    #[test]
+    fn test_tablets_and_clock_skew() {
+        let config = DiagnosticsConfig::default();
+        let row = |db_id: &str, unhealthy: &str, error: &str| TabletStatistic {
+            db_id: db_id.to_string(),
+            db_name: format!("db_{}", db_id),
+            tablet_num: "100".to_string(),
+            replica_num: "300".to_string(),
+            unhealthy_tablet_num: unhealthy.to_string(),
+            inconsistent_tablet_num: "0".to_string(),
+            cloning_tablet_num: "0".to_string(),
+            error_state_tablet_num: error.to_string(),
+        };
+        let statistics = [row("10", "2", "0"), row("11", "0", "0"), row("Total", "2", "0")];
+        let tablets = check_tablets(&statistics);
+        assert_eq!(tablets.severity(), Severity::Warning);
+        assert_eq!(tablets.affected, ["db_10 (DbId 10)"]);
+        assert_eq!(check_tablets(&[row("Total", "0", "1")]).severity(), Severity::Critical);
+
+        let offsets = [("a".to_string(), -200), ("b".to_string(), 300)];
+        assert_eq!(check_clock_skew(&offsets, &config).severity(), Severity::Ok);
+        let offsets = [("a".to_string(), -1500), ("b".to_string(), 4000)];
+        assert_eq!(check_clock_skew(&offsets, &config).severity(), Severity::Critical);
+        assert_eq!(check_clock_skew(&offsets[..1], &config).severity(), Severity::Info);
+    }
+}
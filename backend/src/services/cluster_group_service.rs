@@ -0,0 +1,529 @@
+use sqlx::{SqliteConnection, SqlitePool};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+use crate::models::{
+    Cluster, ClusterFanOutResult, ClusterGroup, ClusterGroupKind, ClusterGroupMember,
+    ClusterGroupResponse, CreateClusterGroupRequest, FanOutStatus, UpdateClusterGroupRequest,
+};
+use crate::utils::{ApiError, ApiResult};
+
+/// Clusters contacted at the same time by a fan-out operation
+const FAN_OUT_CONCURRENCY: usize = 8;
+/// Upper bound for the work on a single cluster
+const FAN_OUT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Named sets of clusters (environments, teams, ...)
+///
+/// Groups drive tag-like filtering of the cluster list and fan-out operations
+/// that run the same health check, variable read or query on every member.
+#[derive(Clone)]
+pub struct ClusterGroupService {
+    pool: SqlitePool,
+}
+
+impl ClusterGroupService {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    // List all groups with their members
+    pub async fn list(&self) -> ApiResult<Vec<ClusterGroupResponse>> {
+        let mut conn = self.pool.acquire().await?;
+        let groups: Vec<ClusterGroup> =
+            sqlx::query_as("SELECT * FROM cluster_groups ORDER BY name")
+                .fetch_all(&mut *conn)
+                .await?;
+
+        let mut responses = Vec::with_capacity(groups.len());
+        for group in groups {
+            let members = Self::members(&mut conn, group.id).await?;
+            responses.push(ClusterGroupResponse::new(group, members));
+        }
+        Ok(responses)
+    }
+
+    pub async fn get(&self, group_id: i64) -> ApiResult<ClusterGroupResponse> {
+        let mut conn = self.pool.acquire().await?;
+        let group = Self::fetch_group(&mut conn, group_id).await?;
+        let members = Self::members(&mut conn, group_id).await?;
+        Ok(ClusterGroupResponse::new(group, members))
+    }
+
+    pub async fn get_group(&self, group_id: i64) -> ApiResult<ClusterGroup> {
+        Self::fetch_group(&mut *self.pool.acquire().await?, group_id).await
+    }
+
+    async fn fetch_group(conn: &mut SqliteConnection, group_id: i64) -> ApiResult<ClusterGroup> {
+        sqlx::query_as("SELECT * FROM cluster_groups WHERE id = ?")
+            .bind(group_id)
+            .fetch_optional(conn)
+            .await?
+            .ok_or_else(|| ApiError::not_found(format!("Cluster group {} not found", group_id)))
+    }
+
+    // Resolve a group by ID or name
+    pub async fn find(&self, reference: &str) -> ApiResult<ClusterGroup> {
+        let reference = reference.trim();
+        if let Ok(id) = reference.parse::<i64>() {
+            return self.get_group(id).await;
+        }
+
+        sqlx::query_as("SELECT * FROM cluster_groups WHERE name = ?")
+            .bind(reference)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| ApiError::not_found(format!("Cluster group '{}' not found", reference)))
+    }
+
+    pub async fn create(
+        &self,
+        req: CreateClusterGroupRequest,
+        user_id: i64,
+    ) -> ApiResult<ClusterGroupResponse> {
+        let mut tx = self.pool.begin().await?;
+        let group_id = Self::create_in(&mut tx, req, user_id).await?;
+        tx.commit().await?;
+        self.get(group_id).await
+    }
+
+    /// Create a group on a connection, so that callers can make it part of a transaction
+    pub(crate) async fn create_in(
+        conn: &mut SqliteConnection,
+        req: CreateClusterGroupRequest,
+        user_id: i64,
+    ) -> ApiResult<i64> {
+        let name = validate_name(&req.name)?;
+        Self::ensure_name_available(&mut *conn, name, None).await?;
+        let cluster_ids =
+            Self::validate_members(&mut *conn, req.cluster_ids, req.kind, None).await?;
+
+        let result = sqlx::query(
+            "INSERT INTO cluster_groups (name, description, kind, created_by) VALUES (?, ?, ?, ?)",
+        )
+        .bind(name)
+        .bind(&req.description)
+        .bind(req.kind)
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+        let group_id = result.last_insert_rowid();
+
+        for cluster_id in &cluster_ids {
+            sqlx::query("INSERT INTO cluster_group_members (group_id, cluster_id) VALUES (?, ?)")
+                .bind(group_id)
+                .bind(cluster_id)
+                .execute(&mut *conn)
+                .await?;
+        }
+
+        tracing::info!(
+            "Cluster group '{}' ({}) created with {} clusters",
+            name,
+            req.kind.as_str(),
+            cluster_ids.len()
+        );
+        Ok(group_id)
+    }
+
+    pub async fn update(
+        &self,
+        group_id: i64,
+        req: UpdateClusterGroupRequest,
+    ) -> ApiResult<ClusterGroupResponse> {
+        let mut tx = self.pool.begin().await?;
+        Self::update_in(&mut tx, group_id, req).await?;
+        tx.commit().await?;
+        self.get(group_id).await
+    }
+
+    /// Update a group on a connection, so that callers can make it part of a transaction
+    pub(crate) async fn update_in(
+        conn: &mut SqliteConnection,
+        group_id: i64,
+        req: UpdateClusterGroupRequest,
+    ) -> ApiResult<()> {
+        let group = Self::fetch_group(&mut *conn, group_id).await?;
+
+        let name = match &req.name {
+            Some(name) => {
+                let name = validate_name(name)?;
+                Self::ensure_name_available(&mut *conn, name, Some(group_id)).await?;
+                name.to_string()
+            },
+            None => group.name,
+        };
+        let kind = req.kind.unwrap_or(group.kind);
+        let description = req.description.or(group.description);
+
+        // Changing the kind to environment re-checks the current members too
+        let cluster_ids = match req.cluster_ids {
+            Some(ids) => Some(Self::validate_members(&mut *conn, ids, kind, Some(group_id)).await?),
+            None if kind != group.kind => {
+                let current = Self::members(&mut *conn, group_id)
+                    .await?
+                    .into_iter()
+                    .map(|m| m.id)
+                    .collect();
+                Self::validate_members(&mut *conn, current, kind, Some(group_id)).await?;
+                None
+            },
+            None => None,
+        };
+
+        sqlx::query(
+            "UPDATE cluster_groups SET name = ?, description = ?, kind = ?, \
+             updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+        )
+        .bind(&name)
+        .bind(&description)
+        .bind(kind)
+        .bind(group_id)
+        .execute(&mut *conn)
+        .await?;
+
+        if let Some(cluster_ids) = &cluster_ids {
+            sqlx::query("DELETE FROM cluster_group_members WHERE group_id = ?")
+                .bind(group_id)
+                .execute(&mut *conn)
+                .await?;
+            for cluster_id in cluster_ids {
+                sqlx::query(
+                    "INSERT INTO cluster_group_members (group_id, cluster_id) VALUES (?, ?)",
+                )
+                .bind(group_id)
+                .bind(cluster_id)
+                .execute(&mut *conn)
+                .await?;
+            }
+        }
+
+        tracing::info!("Cluster group {} updated", group_id);
+        Ok(())
+    }
+
+    pub async fn delete(&self, group_id: i64) -> ApiResult<()> {
+        let result = sqlx::query("DELETE FROM cluster_groups WHERE id = ?")
+            .bind(group_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(ApiError::not_found(format!("Cluster group {} not found", group_id)));
+        }
+
+        tracing::info!("Cluster group {} deleted", group_id);
+        Ok(())
+    }
+
+    // Full cluster records of the members, ordered by name
+    pub async fn clusters(&self, group_id: i64) -> ApiResult<Vec<Cluster>> {
+        let clusters = sqlx::query_as(
+            "SELECT c.* FROM clusters c \
+             JOIN cluster_group_members m ON m.cluster_id = c.id \
+             WHERE m.group_id = ? ORDER BY c.name",
+        )
+        .bind(group_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(clusters)
+    }
+
+    async fn members(
+        conn: &mut SqliteConnection,
+        group_id: i64,
+    ) -> ApiResult<Vec<ClusterGroupMember>> {
+        let members = sqlx::query_as(
+            "SELECT c.id, c.name FROM clusters c \
+             JOIN cluster_group_members m ON m.cluster_id = c.id \
+             WHERE m.group_id = ? ORDER BY c.name",
+        )
+        .bind(group_id)
+        .fetch_all(conn)
+        .await?;
+
+        Ok(members)
+    }
+
+    async fn ensure_name_available(
+        conn: &mut SqliteConnection,
+        name: &str,
+        except: Option<i64>,
+    ) -> ApiResult<()> {
+        let existing: Option<(i64,)> =
+            sqlx::query_as("SELECT id FROM cluster_groups WHERE name = ?")
+                .bind(name)
+                .fetch_optional(conn)
+                .await?;
+
+        match existing {
+            Some((id,)) if Some(id) != except => Err(ApiError::validation_error(format!(
+                "A cluster group named '{}' already exists",
+                name
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    // Deduplicate member IDs, check they exist and that environments do not overlap
+    async fn validate_members(
+        conn: &mut SqliteConnection,
+        mut cluster_ids: Vec<i64>,
+        kind: ClusterGroupKind,
+        group_id: Option<i64>,
+    ) -> ApiResult<Vec<i64>> {
+        cluster_ids.sort_unstable();
+        cluster_ids.dedup();
+
+        for cluster_id in &cluster_ids {
+            let exists: Option<(i64,)> = sqlx::query_as("SELECT id FROM clusters WHERE id = ?")
+                .bind(cluster_id)
+                .fetch_optional(&mut *conn)
+                .await?;
+            if exists.is_none() {
+                return Err(ApiError::cluster_not_found(*cluster_id));
+            }
+
+            if kind == ClusterGroupKind::Environment {
+                let other: Option<(String,)> = sqlx::query_as(
+                    "SELECT g.name FROM cluster_groups g \
+                     JOIN cluster_group_members m ON m.group_id = g.id \
+                     WHERE m.cluster_id = ? AND g.kind = 'environment' AND g.id != ?",
+                )
+                .bind(cluster_id)
+                .bind(group_id.unwrap_or(0))
+                .fetch_optional(&mut *conn)
+                .await?;
+                if let Some((environment,)) = other {
+                    return Err(ApiError::validation_error(format!(
+                        "Cluster {} already belongs to environment '{}'",
+                        cluster_id, environment
+                    )));
+                }
+            }
+        }
+
+        Ok(cluster_ids)
+    }
+}
+
+pub(crate) fn validate_name(name: &str) -> ApiResult<&str> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > 100 {
+        return Err(ApiError::validation_error("Group name must be between 1 and 100 characters"));
+    }
+    if name.parse::<i64>().is_ok() {
+        // Groups are looked up by ID or name, a numeric name would be ambiguous
+        return Err(ApiError::validation_error("Group name cannot be a number"));
+    }
+    Ok(name)
+}
+
+/// Run `op` on every cluster, a few at a time, and collect one result per cluster
+///
+/// Results keep the order of `clusters`. A failure, timeout or panic on one cluster
+/// only affects its own entry.
+pub async fn fan_out<F, Fut>(clusters: Vec<Cluster>, op: F) -> Vec<ClusterFanOutResult>
+where
+    F: Fn(Cluster) -> Fut,
+    Fut: Future<Output = ApiResult<serde_json::Value>> + Send + 'static,
+{
+    let semaphore = Arc::new(Semaphore::new(FAN_OUT_CONCURRENCY));
+    let mut tasks = Vec::with_capacity(clusters.len());
+
+    for cluster in clusters {
+        let (cluster_id, cluster_name) = (cluster.id, cluster.name.clone());
+        let semaphore = Arc::clone(&semaphore);
+        let work = op(cluster);
+        let handle = tokio::spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let started = Instant::now();
+            let outcome = match tokio::time::timeout(FAN_OUT_TIMEOUT, work).await {
+                Ok(outcome) => outcome,
+                Err(_) => Err(ApiError::ClusterTimeout),
+            };
+            (outcome, started.elapsed())
+        });
+        tasks.push((cluster_id, cluster_name, handle));
+    }
+
+    let mut results = Vec::with_capacity(tasks.len());
+    for (cluster_id, cluster_name, handle) in tasks {
+        let (outcome, elapsed) = match handle.await {
+            Ok(finished) => finished,
+            Err(e) => (Err(ApiError::internal_error(e.to_string())), Duration::ZERO),
+        };
+        let duration_ms = elapsed.as_millis() as u64;
+
+        results.push(match outcome {
+            Ok(data) => ClusterFanOutResult {
+                cluster_id,
+                cluster_name,
+                status: FanOutStatus::Ok,
+                duration_ms,
+                data: Some(data),
+                error: None,
+            },
+            Err(e) => {
+                tracing::warn!("Fan-out on cluster {} failed: {}", cluster_name, e);
+                ClusterFanOutResult {
+                    cluster_id,
+                    cluster_name,
+                    status: FanOutStatus::Error,
+                    duration_ms,
+                    data: None,
+                    error: Some(e.to_string()),
+                }
+            },
+        });
+    }
+
+    results
+}
+
+/// Whether `sql` is a single statement that only reads data
+pub fn is_read_only_sql(sql: &str) -> bool {
+    crate::utils::sql::is_read_only_query(sql)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn pool_with_clusters() -> SqlitePool {
+        let pool = crate::db::test_pool().await;
+        for name in ["prod-a", "prod-b"] {
+            sqlx::query(
+                "INSERT INTO clusters (name, fe_host, username, password_encrypted) \
+                 VALUES (?, 'fe', 'root', '')",
+            )
+            .bind(name)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        pool
+    }
+
+    fn request(
+        name: &str,
+        kind: ClusterGroupKind,
+        cluster_ids: Vec<i64>,
+    ) -> CreateClusterGroupRequest {
+        CreateClusterGroupRequest { name: name.to_string(), description: None, kind, cluster_ids }
+    }
+
+    #[tokio::test]
+    async fn test_group_membership_rules() {
+        let service = ClusterGroupService::new(pool_with_clusters().await);
+
+        let prod = service
+            .create(request("prod", ClusterGroupKind::Environment, vec![2, 1, 2]), 1)
+            .await
+            .unwrap();
+        let ids: Vec<i64> = prod.clusters.iter().map(|c| c.id).collect();
+        assert_eq!(ids, vec![1, 2]);
+
+        // Names are unique and cannot be numeric, members must exist
+        assert!(
+            service
+                .create(request("prod", ClusterGroupKind::Team, vec![]), 1)
+                .await
+                .is_err()
+        );
+        assert!(
+            service
+                .create(request("42", ClusterGroupKind::Team, vec![]), 1)
+                .await
+                .is_err()
+        );
+        assert!(
+            service
+                .create(request("x", ClusterGroupKind::Team, vec![9]), 1)
+                .await
+                .is_err()
+        );
+
+        // A cluster is in at most one environment, but in any number of other groups
+        assert!(
+            service
+                .create(request("staging", ClusterGroupKind::Environment, vec![1]), 1)
+                .await
+                .is_err()
+        );
+        let team = service
+            .create(request("team-a", ClusterGroupKind::Team, vec![1]), 1)
+            .await
+            .unwrap();
+        let to_environment = UpdateClusterGroupRequest {
+            name: None,
+            description: None,
+            kind: Some(ClusterGroupKind::Environment),
+            cluster_ids: None,
+        };
+        assert!(service.update(team.id, to_environment).await.is_err());
+
+        assert_eq!(service.find("team-a").await.unwrap().id, team.id);
+        assert_eq!(service.find(&prod.id.to_string()).await.unwrap().name, "prod");
+
+        // Deleting a cluster drops its memberships
+        sqlx::query("DELETE FROM clusters WHERE id = 1")
+            .execute(&service.pool)
+            .await
+            .unwrap();
+        assert_eq!(service.clusters(prod.id).await.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_is_read_only_sql() {
+        assert!(is_read_only_sql("SELECT * FROM t"));
+        assert!(is_read_only_sql("  select 1;  "));
+        assert!(is_read_only_sql("show frontends"));
+        assert!(is_read_only_sql("DESC db.t"));
+        assert!(is_read_only_sql("(SELECT 1) UNION (SELECT 2)"));
+        assert!(is_read_only_sql("WITH a AS (SELECT 1) SELECT * FROM a"));
+
+        assert!(!is_read_only_sql(""));
+        assert!(!is_read_only_sql("DROP TABLE t"));
+        assert!(!is_read_only_sql("SET GLOBAL query_timeout = 1"));
+        assert!(!is_read_only_sql("SELECT 1; DROP TABLE t"));
+        assert!(!is_read_only_sql("WITH a AS (SELECT 1) INSERT INTO t SELECT * FROM a"));
+        assert!(!is_read_only_sql("SELECT * FROM t INTO OUTFILE 'hdfs://x'"));
+        assert!(!is_read_only_sql("/* hint */ DELETE FROM t"));
+    }
+
+    #[tokio::test]
+    async fn test_fan_out_isolates_failures_and_keeps_order() {
+        let cluster = |id: i64| Cluster {
+            id,
+            name: format!("c{}", id),
+            fe_host: "fe".to_string(),
+            is_active: false,
+            ..Cluster::for_test()
+        };
+
+        let results = fan_out((1..=10).map(cluster).collect(), |c| async move {
+            if c.id % 3 == 0 {
+                Err(ApiError::cluster_connection_failed("refused"))
+            } else {
+                Ok(serde_json::json!(c.id))
+            }
+        })
+        .await;
+
+        let ids: Vec<i64> = results.iter().map(|r| r.cluster_id).collect();
+        assert_eq!(ids, (1..=10).collect::<Vec<_>>());
+        assert_eq!(
+            results
+                .iter()
+                .filter(|r| r.status == FanOutStatus::Error)
+                .count(),
+            3
+        );
+        assert_eq!(results[1].data, Some(serde_json::json!(2)));
+        assert!(results[2].error.as_deref().unwrap().contains("refused"));
+    }
+}
@@ -1,10 +1,14 @@
 use crate::models::{
-    Cluster, ClusterHealth, CreateClusterRequest, HealthCheck, HealthStatus, UpdateClusterRequest,
+    Cluster, ClusterHealth, CreateClusterRequest, FeEndpoint, HealthCheck, HealthStatus,
+    PoolSettings, Severity, SqlPolicy, UpdateClusterRequest,
 };
 use crate::services::StarRocksClient;
+use crate::utils::crypto::{self, SecretCipher};
 use crate::utils::{ApiError, ApiResult};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
 use chrono::Utc;
-use sqlx::SqlitePool;
+use sqlx::{SqliteConnection, SqlitePool};
 
 #[derive(Clone)]
 pub struct ClusterService {
@@ -19,6 +23,18 @@ impl ClusterService {
     // Create a new cluster
     pub async fn create_cluster(
         &self,
+        req: CreateClusterRequest,
+        user_id: i64,
+    ) -> ApiResult<Cluster> {
+        let mut tx = self.pool.begin().await?;
+        let cluster = Self::create_cluster_in(&mut tx, req, user_id).await?;
+        tx.commit().await?;
+        Ok(cluster)
+    }
+
+    /// Create a cluster on a connection, so that callers can make it part of a transaction
+    pub(crate) async fn create_cluster_in(
+        conn: &mut SqliteConnection,
         mut req: CreateClusterRequest,
         user_id: i64,
     ) -> ApiResult<Cluster> {
@@ -41,11 +57,38 @@ impl ClusterService {
         if req.username.is_empty() {
             return Err(ApiError::validation_error("Username cannot be empty"));
         }
+        let primary_fe = FeEndpoint {
+            host: req.fe_host.clone(),
+            http_port: req.fe_http_port,
+            query_port: req.fe_query_port,
+        };
+        let fe_endpoints_json = Self::fe_endpoints_json(&primary_fe, req.fe_endpoints)?;
+
+        let tls_ca_cert = req.tls_ca_cert.filter(|pem| !pem.trim().is_empty());
+        if let Some(ca_cert) = &tls_ca_cert {
+            Self::validate_ca_cert(ca_cert)?;
+        }
+        let (tls_client_identity_encrypted, tls_client_identity_password_encrypted) =
+            match req.tls_client_identity.as_deref() {
+                Some(identity) if !identity.trim().is_empty() => {
+                    let (identity, password) = Self::encrypt_client_identity(
+                        identity,
+                        req.tls_client_identity_password
+                            .as_deref()
+                            .unwrap_or_default(),
+                    )?;
+                    (Some(identity), Some(password))
+                },
+                _ => (None, None),
+            };
+
+        let pool_settings_json = Self::pool_settings_json(&req.pool_settings)?;
+        let sql_policy_json = Self::sql_policy_json(&req.sql_policy)?;
 
         // Check if cluster name already exists
         let existing: Option<Cluster> = sqlx::query_as("SELECT * FROM clusters WHERE name = ?")
             .bind(&req.name)
-            .fetch_optional(&self.pool)
+            .fetch_optional(&mut *conn)
             .await?;
 
         if existing.is_some() {
@@ -59,32 +102,43 @@ impl ClusterService {
 
         // Check if this will be the first cluster
         let existing_cluster_count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM clusters")
-            .fetch_one(&self.pool)
+            .fetch_one(&mut *conn)
             .await?;
 
         let is_first_cluster = existing_cluster_count.0 == 0;
 
-        // Insert cluster (password is stored as-is for now, should be encrypted in production)
+        let password_encrypted = crypto::encrypt_secret(&req.password)?;
+
+        // Insert cluster; the password is stored envelope-encrypted
         let result = sqlx::query(
             "INSERT INTO clusters (name, description, fe_host, fe_http_port, fe_query_port, 
-             username, password_encrypted, enable_ssl, connection_timeout, tags, catalog, 
+             fe_endpoints, username, password_encrypted, enable_ssl, tls_ca_cert, 
+             tls_client_identity_encrypted, tls_client_identity_password_encrypted, 
+             tls_verify_hostname, connection_timeout, pool_settings, sql_policy, tags, catalog, 
              is_active, created_by)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         )
         .bind(&req.name)
         .bind(&req.description)
         .bind(&req.fe_host)
         .bind(req.fe_http_port)
         .bind(req.fe_query_port)
+        .bind(&fe_endpoints_json)
         .bind(&req.username)
-        .bind(&req.password) // TODO: Encrypt in production
+        .bind(&password_encrypted)
         .bind(req.enable_ssl)
+        .bind(&tls_ca_cert)
+        .bind(&tls_client_identity_encrypted)
+        .bind(&tls_client_identity_password_encrypted)
+        .bind(req.tls_verify_hostname)
         .bind(req.connection_timeout)
+        .bind(&pool_settings_json)
+        .bind(&sql_policy_json)
         .bind(&tags_json)
         .bind(&req.catalog)
         .bind(if is_first_cluster { 1 } else { 0 }) // Set as active if first cluster
         .bind(user_id)
-        .execute(&self.pool)
+        .execute(&mut *conn)
         .await?;
 
         let cluster_id = result.last_insert_rowid();
@@ -94,14 +148,14 @@ impl ClusterService {
         if !is_first_cluster {
             let active_count: (i64,) =
                 sqlx::query_as("SELECT COUNT(*) FROM clusters WHERE is_active = 1")
-                    .fetch_one(&self.pool)
+                    .fetch_one(&mut *conn)
                     .await?;
 
             if active_count.0 == 0 {
                 // No active cluster exists, activate this new one
                 sqlx::query("UPDATE clusters SET is_active = 1 WHERE id = ?")
                     .bind(cluster_id)
-                    .execute(&self.pool)
+                    .execute(&mut *conn)
                     .await?;
                 tracing::info!(
                     "Automatically activated newly created cluster (no active cluster existed)"
@@ -112,7 +166,7 @@ impl ClusterService {
         // Fetch and return the created cluster
         let cluster: Cluster = sqlx::query_as("SELECT * FROM clusters WHERE id = ?")
             .bind(cluster_id)
-            .fetch_one(&self.pool)
+            .fetch_one(&mut *conn)
             .await?;
 
         tracing::info!("Cluster created successfully: {} (ID: {})", cluster.name, cluster.id);
@@ -160,6 +214,14 @@ impl ClusterService {
         })
     }
 
+    // Cluster a request operates on: the requested one, else the active (default) cluster
+    pub async fn resolve_cluster(&self, requested: Option<i64>) -> ApiResult<Cluster> {
+        match requested {
+            Some(cluster_id) => self.get_cluster(cluster_id).await,
+            None => self.get_active_cluster().await,
+        }
+    }
+
     // Set a cluster as active (deactivating all others)
     pub async fn set_active_cluster(&self, cluster_id: i64) -> ApiResult<Cluster> {
         // Check if cluster exists
@@ -195,9 +257,21 @@ impl ClusterService {
         &self,
         cluster_id: i64,
         req: UpdateClusterRequest,
+    ) -> ApiResult<Cluster> {
+        let mut tx = self.pool.begin().await?;
+        let cluster = Self::update_cluster_in(&mut tx, cluster_id, req).await?;
+        tx.commit().await?;
+        Ok(cluster)
+    }
+
+    /// Update a cluster on a connection, so that callers can make it part of a transaction
+    pub(crate) async fn update_cluster_in(
+        conn: &mut SqliteConnection,
+        cluster_id: i64,
+        req: UpdateClusterRequest,
     ) -> ApiResult<Cluster> {
         // Check if cluster exists
-        let _cluster = self.get_cluster(cluster_id).await?;
+        let cluster = Self::fetch_cluster(&mut *conn, cluster_id).await?;
 
         // Build dynamic SQL update query
         let mut updates = Vec::new();
@@ -223,22 +297,89 @@ impl ClusterService {
             updates.push("fe_query_port = ?");
             params.push(query_port.to_string());
         }
+        if let Some(fe_endpoints) = req.fe_endpoints {
+            let primary_fe = FeEndpoint {
+                host: req.fe_host.clone().unwrap_or(cluster.fe_host),
+                http_port: req.fe_http_port.unwrap_or(cluster.fe_http_port),
+                query_port: req.fe_query_port.unwrap_or(cluster.fe_query_port),
+            };
+            updates.push("fe_endpoints = ?");
+            params.push(
+                Self::fe_endpoints_json(&primary_fe, fe_endpoints)?
+                    .unwrap_or_else(|| "[]".to_string()),
+            );
+        }
         if let Some(username) = &req.username {
             updates.push("username = ?");
             params.push(username.clone());
         }
         if let Some(password) = &req.password {
             updates.push("password_encrypted = ?");
-            params.push(password.clone());
+            params.push(crypto::encrypt_secret(password)?);
         }
         if let Some(ssl) = req.enable_ssl {
             updates.push("enable_ssl = ?");
             params.push((ssl as i32).to_string());
         }
+        match req.tls_ca_cert.as_deref().map(str::trim) {
+            Some("") => updates.push("tls_ca_cert = NULL"),
+            Some(ca_cert) => {
+                Self::validate_ca_cert(ca_cert)?;
+                updates.push("tls_ca_cert = ?");
+                params.push(ca_cert.to_string());
+            },
+            None => {},
+        }
+        match req.tls_client_identity.as_deref().map(str::trim) {
+            Some("") => {
+                updates.push("tls_client_identity_encrypted = NULL");
+                updates.push("tls_client_identity_password_encrypted = NULL");
+            },
+            Some(identity) => {
+                let (identity, password) = Self::encrypt_client_identity(
+                    identity,
+                    req.tls_client_identity_password
+                        .as_deref()
+                        .unwrap_or_default(),
+                )?;
+                updates.push("tls_client_identity_encrypted = ?");
+                params.push(identity);
+                updates.push("tls_client_identity_password_encrypted = ?");
+                params.push(password);
+            },
+            None if req.tls_client_identity_password.is_some() => {
+                return Err(ApiError::validation_error(
+                    "tls_client_identity_password requires tls_client_identity",
+                ));
+            },
+            None => {},
+        }
+        if let Some(verify) = req.tls_verify_hostname {
+            updates.push("tls_verify_hostname = ?");
+            params.push((verify as i32).to_string());
+        }
         if let Some(timeout) = req.connection_timeout {
             updates.push("connection_timeout = ?");
             params.push(timeout.to_string());
         }
+        if let Some(pool_settings) = &req.pool_settings {
+            match Self::pool_settings_json(pool_settings)? {
+                Some(json) => {
+                    updates.push("pool_settings = ?");
+                    params.push(json);
+                },
+                None => updates.push("pool_settings = NULL"),
+            }
+        }
+        if let Some(sql_policy) = &req.sql_policy {
+            match Self::sql_policy_json(sql_policy)? {
+                Some(json) => {
+                    updates.push("sql_policy = ?");
+                    params.push(json);
+                },
+                None => updates.push("sql_policy = NULL"),
+            }
+        }
         if let Some(tags) = &req.tags {
             updates.push("tags = ?");
             params.push(serde_json::to_string(tags).unwrap_or_default());
@@ -249,7 +390,7 @@ impl ClusterService {
         }
 
         if updates.is_empty() {
-            return self.get_cluster(cluster_id).await;
+            return Self::fetch_cluster(conn, cluster_id).await;
         }
 
         updates.push("updated_at = CURRENT_TIMESTAMP");
@@ -262,11 +403,107 @@ impl ClusterService {
         }
         query = query.bind(cluster_id);
 
-        query.execute(&self.pool).await?;
+        query.execute(&mut *conn).await?;
 
         tracing::info!("Cluster updated: ID {}", cluster_id);
 
-        self.get_cluster(cluster_id).await
+        Self::fetch_cluster(conn, cluster_id).await
+    }
+
+    async fn fetch_cluster(conn: &mut SqliteConnection, cluster_id: i64) -> ApiResult<Cluster> {
+        let cluster: Option<Cluster> = sqlx::query_as("SELECT * FROM clusters WHERE id = ?")
+            .bind(cluster_id)
+            .fetch_optional(conn)
+            .await?;
+
+        cluster.ok_or_else(|| ApiError::cluster_not_found(cluster_id))
+    }
+
+    // Validate additional FEs and serialize them, leaving out the primary FE and duplicates
+    fn fe_endpoints_json(
+        primary: &FeEndpoint,
+        endpoints: Vec<FeEndpoint>,
+    ) -> ApiResult<Option<String>> {
+        let mut unique: Vec<FeEndpoint> = Vec::new();
+        for mut endpoint in endpoints {
+            endpoint.host = endpoint.host.trim().to_string();
+            if endpoint.host.is_empty() {
+                return Err(ApiError::validation_error("FE host cannot be empty"));
+            }
+            let valid_port = |port: i32| (1..=65535).contains(&port);
+            if !valid_port(endpoint.http_port) || !valid_port(endpoint.query_port) {
+                return Err(ApiError::validation_error(format!(
+                    "Invalid ports for FE {}: must be between 1 and 65535",
+                    endpoint.host
+                )));
+            }
+            if endpoint != *primary && !unique.contains(&endpoint) {
+                unique.push(endpoint);
+            }
+        }
+
+        if unique.is_empty() {
+            return Ok(None);
+        }
+        serde_json::to_string(&unique)
+            .map(Some)
+            .map_err(|e| ApiError::internal_error(format!("Failed to serialize FE list: {}", e)))
+    }
+
+    // Validate MySQL pool overrides and serialize them; no overrides are stored as NULL
+    fn pool_settings_json(settings: &PoolSettings) -> ApiResult<Option<String>> {
+        if settings.max_connections == Some(0) {
+            return Err(ApiError::validation_error("max_connections must be at least 1"));
+        }
+        if let (Some(min), Some(max)) = (settings.min_connections, settings.max_connections)
+            && min > max
+        {
+            return Err(ApiError::validation_error(
+                "min_connections cannot exceed max_connections",
+            ));
+        }
+        if settings.idle_timeout_secs == Some(0) || settings.max_lifetime_secs == Some(0) {
+            return Err(ApiError::validation_error(
+                "idle_timeout_secs and max_lifetime_secs must be at least 1",
+            ));
+        }
+
+        if settings.is_empty() {
+            return Ok(None);
+        }
+        serde_json::to_string(settings).map(Some).map_err(|e| {
+            ApiError::internal_error(format!("Failed to serialize pool settings: {}", e))
+        })
+    }
+
+    // Serialize the SQL policy; no restrictions are stored as NULL
+    fn sql_policy_json(policy: &SqlPolicy) -> ApiResult<Option<String>> {
+        if policy.is_empty() {
+            return Ok(None);
+        }
+        serde_json::to_string(policy)
+            .map(Some)
+            .map_err(|e| ApiError::internal_error(format!("Failed to serialize SQL policy: {}", e)))
+    }
+
+    // Check that a CA bundle contains at least one PEM certificate
+    pub fn validate_ca_cert(pem: &str) -> ApiResult<()> {
+        match reqwest::Certificate::from_pem_bundle(pem.trim().as_bytes()) {
+            Ok(certs) if !certs.is_empty() => Ok(()),
+            _ => Err(ApiError::validation_error("tls_ca_cert must be a PEM certificate bundle")),
+        }
+    }
+
+    // Check a base64 PKCS#12 client certificate and encrypt it with its password
+    pub fn encrypt_client_identity(encoded: &str, password: &str) -> ApiResult<(String, String)> {
+        let der = BASE64.decode(encoded.trim()).map_err(|_| {
+            ApiError::validation_error("tls_client_identity must be a base64 PKCS#12 archive")
+        })?;
+        reqwest::Identity::from_pkcs12_der(&der, password).map_err(|e| {
+            ApiError::validation_error(format!("Cannot read TLS client certificate: {}", e))
+        })?;
+
+        Ok((crypto::encrypt_secret(encoded.trim())?, crypto::encrypt_secret(password)?))
     }
 
     // Delete cluster
@@ -311,10 +548,88 @@ impl ClusterService {
         Ok(())
     }
 
+    // Encrypt passwords stored in plaintext by older versions
+    pub async fn encrypt_plaintext_passwords(&self) -> ApiResult<usize> {
+        let rows: Vec<(i64, String)> =
+            sqlx::query_as("SELECT id, password_encrypted FROM clusters")
+                .fetch_all(&self.pool)
+                .await?;
+
+        let mut migrated = 0;
+        for (id, stored) in rows.into_iter().filter(|(_, p)| !crypto::is_encrypted(p)) {
+            sqlx::query("UPDATE clusters SET password_encrypted = ? WHERE id = ?")
+                .bind(crypto::encrypt_secret(&stored)?)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+            migrated += 1;
+        }
+
+        if migrated > 0 {
+            tracing::info!("Encrypted {} plaintext cluster password(s)", migrated);
+        }
+
+        Ok(migrated)
+    }
+
+    // Re-encrypt every cluster secret with a new master key, all or nothing
+    pub async fn rotate_master_key(
+        &self,
+        old_cipher: &SecretCipher,
+        new_cipher: &SecretCipher,
+    ) -> ApiResult<usize> {
+        let mut tx = self.pool.begin().await?;
+
+        let rows: Vec<(i64, String, Option<String>, Option<String>)> = sqlx::query_as(
+            "SELECT id, password_encrypted, tls_client_identity_encrypted, \
+             tls_client_identity_password_encrypted FROM clusters",
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let reencrypt = |id: i64, stored: &str| {
+            let secret = old_cipher.decrypt(stored).map_err(|e| {
+                tracing::error!("Cannot decrypt secrets of cluster {}: {}", id, e);
+                e
+            })?;
+            new_cipher.encrypt(&secret)
+        };
+
+        for (id, stored, identity, identity_password) in &rows {
+            let identity = identity.as_deref().map(|s| reencrypt(*id, s)).transpose()?;
+            let identity_password = identity_password
+                .as_deref()
+                .map(|s| reencrypt(*id, s))
+                .transpose()?;
+
+            sqlx::query(
+                "UPDATE clusters SET password_encrypted = ?, tls_client_identity_encrypted = ?, \
+                 tls_client_identity_password_encrypted = ? WHERE id = ?",
+            )
+            .bind(reencrypt(*id, stored)?)
+            .bind(identity)
+            .bind(identity_password)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        tracing::info!(
+            "Re-encrypted {} cluster password(s): master key {} -> {}",
+            rows.len(),
+            old_cipher.key_id(),
+            new_cipher.key_id()
+        );
+
+        Ok(rows.len())
+    }
+
     // Get cluster health
     pub async fn get_cluster_health(&self, cluster_id: i64) -> ApiResult<ClusterHealth> {
         let cluster = self.get_cluster(cluster_id).await?;
-        let client = StarRocksClient::new(cluster);
+        let client = StarRocksClient::new(cluster)?;
 
         let mut checks = Vec::new();
         let mut overall_status = HealthStatus::Healthy;
@@ -322,18 +637,18 @@ impl ClusterService {
         // Check FE availability
         match client.get_runtime_info().await {
             Ok(_) => {
-                checks.push(HealthCheck {
-                    name: "FE Availability".to_string(),
-                    status: "ok".to_string(),
-                    message: "FE is reachable and responding".to_string(),
-                });
+                checks.push(HealthCheck::new(
+                    "FE Availability",
+                    Severity::Ok,
+                    "FE is reachable and responding",
+                ));
             },
             Err(e) => {
-                checks.push(HealthCheck {
-                    name: "FE Availability".to_string(),
-                    status: "critical".to_string(),
-                    message: format!("FE is not reachable: {}", e),
-                });
+                checks.push(HealthCheck::new(
+                    "FE Availability",
+                    Severity::Critical,
+                    format!("FE is not reachable: {}", e),
+                ));
                 overall_status = HealthStatus::Critical;
             },
         }
@@ -345,35 +660,35 @@ impl ClusterService {
                 let total_count = backends.len();
 
                 if alive_count == total_count {
-                    checks.push(HealthCheck {
-                        name: "Backend Nodes".to_string(),
-                        status: "ok".to_string(),
-                        message: format!("All {} BE nodes are online", total_count),
-                    });
+                    checks.push(HealthCheck::new(
+                        "Backend Nodes",
+                        Severity::Ok,
+                        format!("All {} BE nodes are online", total_count),
+                    ));
                 } else if alive_count > 0 {
-                    checks.push(HealthCheck {
-                        name: "Backend Nodes".to_string(),
-                        status: "warning".to_string(),
-                        message: format!("{}/{} BE nodes are online", alive_count, total_count),
-                    });
+                    checks.push(HealthCheck::new(
+                        "Backend Nodes",
+                        Severity::Warning,
+                        format!("{}/{} BE nodes are online", alive_count, total_count),
+                    ));
                     if overall_status == HealthStatus::Healthy {
                         overall_status = HealthStatus::Warning;
                     }
                 } else {
-                    checks.push(HealthCheck {
-                        name: "Backend Nodes".to_string(),
-                        status: "critical".to_string(),
-                        message: "No BE nodes are online".to_string(),
-                    });
+                    checks.push(HealthCheck::new(
+                        "Backend Nodes",
+                        Severity::Critical,
+                        "No BE nodes are online",
+                    ));
                     overall_status = HealthStatus::Critical;
                 }
             },
             Err(e) => {
-                checks.push(HealthCheck {
-                    name: "Backend Nodes".to_string(),
-                    status: "warning".to_string(),
-                    message: format!("Failed to check BE nodes: {}", e),
-                });
+                checks.push(HealthCheck::new(
+                    "Backend Nodes",
+                    Severity::Warning,
+                    format!("Failed to check BE nodes: {}", e),
+                ));
                 if overall_status == HealthStatus::Healthy {
                     overall_status = HealthStatus::Warning;
                 }
@@ -402,28 +717,28 @@ impl ClusterService {
                 // Test basic connection
                 match mysql_client.query("SELECT 1").await {
                     Ok(_) => {
-                        checks.push(HealthCheck {
-                            name: "Database Connection".to_string(),
-                            status: "ok".to_string(),
-                            message: "Connection successful".to_string(),
-                        });
+                        checks.push(HealthCheck::new(
+                            "Database Connection",
+                            Severity::Ok,
+                            "Connection successful",
+                        ));
 
                         // Try to check FE availability via HTTP
-                        let client = StarRocksClient::new(cluster.clone());
+                        let client = StarRocksClient::new(cluster.clone())?;
                         match client.get_runtime_info().await {
                             Ok(_) => {
-                                checks.push(HealthCheck {
-                                    name: "FE Availability".to_string(),
-                                    status: "ok".to_string(),
-                                    message: "FE is reachable and responding".to_string(),
-                                });
+                                checks.push(HealthCheck::new(
+                                    "FE Availability",
+                                    Severity::Ok,
+                                    "FE is reachable and responding",
+                                ));
                             },
                             Err(e) => {
-                                checks.push(HealthCheck {
-                                    name: "FE Availability".to_string(),
-                                    status: "warning".to_string(),
-                                    message: format!("FE HTTP check failed: {}", e),
-                                });
+                                checks.push(HealthCheck::new(
+                                    "FE Availability",
+                                    Severity::Warning,
+                                    format!("FE HTTP check failed: {}", e),
+                                ));
                                 if overall_status == HealthStatus::Healthy {
                                     overall_status = HealthStatus::Warning;
                                 }
@@ -438,47 +753,47 @@ impl ClusterService {
                                 let total_count = backends.len();
 
                                 if total_count == 0 {
-                                    checks.push(HealthCheck {
-                                        name: "Backend Nodes".to_string(),
-                                        status: "warning".to_string(),
-                                        message: "No BE nodes found".to_string(),
-                                    });
+                                    checks.push(HealthCheck::new(
+                                        "Backend Nodes",
+                                        Severity::Warning,
+                                        "No BE nodes found",
+                                    ));
                                     if overall_status == HealthStatus::Healthy {
                                         overall_status = HealthStatus::Warning;
                                     }
                                 } else if alive_count == total_count {
-                                    checks.push(HealthCheck {
-                                        name: "Backend Nodes".to_string(),
-                                        status: "ok".to_string(),
-                                        message: format!("All {} BE nodes are online", total_count),
-                                    });
+                                    checks.push(HealthCheck::new(
+                                        "Backend Nodes",
+                                        Severity::Ok,
+                                        format!("All {} BE nodes are online", total_count),
+                                    ));
                                 } else if alive_count > 0 {
-                                    checks.push(HealthCheck {
-                                        name: "Backend Nodes".to_string(),
-                                        status: "warning".to_string(),
-                                        message: format!(
+                                    checks.push(HealthCheck::new(
+                                        "Backend Nodes",
+                                        Severity::Warning,
+                                        format!(
                                             "{}/{} BE nodes are online",
                                             alive_count, total_count
                                         ),
-                                    });
+                                    ));
                                     if overall_status == HealthStatus::Healthy {
                                         overall_status = HealthStatus::Warning;
                                     }
                                 } else {
-                                    checks.push(HealthCheck {
-                                        name: "Backend Nodes".to_string(),
-                                        status: "critical".to_string(),
-                                        message: "No BE nodes are online".to_string(),
-                                    });
+                                    checks.push(HealthCheck::new(
+                                        "Backend Nodes",
+                                        Severity::Critical,
+                                        "No BE nodes are online",
+                                    ));
                                     overall_status = HealthStatus::Critical;
                                 }
                             },
                             Err(e) => {
-                                checks.push(HealthCheck {
-                                    name: "Backend Nodes".to_string(),
-                                    status: "warning".to_string(),
-                                    message: format!("Failed to check BE nodes: {}", e),
-                                });
+                                checks.push(HealthCheck::new(
+                                    "Backend Nodes",
+                                    Severity::Warning,
+                                    format!("Failed to check BE nodes: {}", e),
+                                ));
                                 if overall_status == HealthStatus::Healthy {
                                     overall_status = HealthStatus::Warning;
                                 }
@@ -486,21 +801,21 @@ impl ClusterService {
                         }
                     },
                     Err(e) => {
-                        checks.push(HealthCheck {
-                            name: "Database Connection".to_string(),
-                            status: "critical".to_string(),
-                            message: format!("Connection failed: {}", e),
-                        });
+                        checks.push(HealthCheck::new(
+                            "Database Connection",
+                            Severity::Critical,
+                            format!("Connection failed: {}", e),
+                        ));
                         overall_status = HealthStatus::Critical;
                     },
                 }
             },
             Err(e) => {
-                checks.push(HealthCheck {
-                    name: "Connection Pool".to_string(),
-                    status: "critical".to_string(),
-                    message: format!("Failed to create connection pool: {}", e),
-                });
+                checks.push(HealthCheck::new(
+                    "Connection Pool",
+                    Severity::Critical,
+                    format!("Failed to create connection pool: {}", e),
+                ));
                 overall_status = HealthStatus::Critical;
             },
         }
@@ -0,0 +1,1004 @@
+use chrono::Utc;
+use sqlx::{SqliteConnection, SqliteExecutor, SqlitePool};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::models::{
+    CONFIG_BUNDLE_VERSION, Cluster, ClusterDefinition, ClusterGroupDefinition, ClusterGroupKind,
+    ClusterGroupResponse, ConfigAction, ConfigBundle, ConfigChange, ConfigImportResponse,
+    ConfigItemKind, CreateClusterGroupRequest, CreateClusterRequest, FeEndpoint, SystemFunction,
+    SystemFunctionDefinition, SystemFunctionPreference, SystemFunctionPreferenceDefinition,
+    UpdateClusterGroupRequest, UpdateClusterRequest,
+};
+use crate::services::{
+    ClusterGroupService, ClusterService, SystemFunctionService, cluster_group_service,
+};
+use crate::utils::{ApiError, ApiResult, crypto};
+
+/// Custom functions allowed per category, as enforced when creating one
+const MAX_FUNCTIONS_PER_CATEGORY: usize = 4;
+
+/// Cluster, category and name identifying a system function across installations
+type FunctionKey = (String, String, String);
+
+/// Export and import of the registered clusters and their customizations
+///
+/// Imports are idempotent: items are matched by name and only created or updated
+/// when they differ. Items missing from a bundle are left alone. The whole bundle
+/// is validated before the first write and written in one transaction.
+#[derive(Clone)]
+pub struct ConfigBundleService {
+    pool: SqlitePool,
+    cluster_service: Arc<ClusterService>,
+    cluster_group_service: Arc<ClusterGroupService>,
+}
+
+impl ConfigBundleService {
+    pub fn new(
+        pool: SqlitePool,
+        cluster_service: Arc<ClusterService>,
+        cluster_group_service: Arc<ClusterGroupService>,
+    ) -> Self {
+        Self { pool, cluster_service, cluster_group_service }
+    }
+
+    // Current configuration without any secrets
+    pub async fn export(&self) -> ApiResult<ConfigBundle> {
+        let clusters = self.cluster_service.list_clusters().await?;
+        let names: HashMap<i64, &str> = clusters.iter().map(|c| (c.id, c.name.as_str())).collect();
+
+        let mut cluster_defs: Vec<ClusterDefinition> =
+            clusters.iter().map(ClusterDefinition::from).collect();
+        cluster_defs.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let cluster_groups = self
+            .cluster_group_service
+            .list()
+            .await?
+            .into_iter()
+            .map(group_definition)
+            .collect();
+
+        let functions = Self::functions(&self.pool).await?;
+        let mut function_defs: Vec<SystemFunctionDefinition> = functions
+            .iter()
+            .filter_map(|f| {
+                Some(SystemFunctionDefinition {
+                    cluster: names.get(&f.cluster_id)?.to_string(),
+                    category_name: f.category_name.clone(),
+                    function_name: f.function_name.clone(),
+                    description: f.description.clone(),
+                    sql_query: f.sql_query.clone(),
+                    display_order: f.display_order,
+                    category_order: f.category_order,
+                    is_favorited: f.is_favorited,
+                })
+            })
+            .collect();
+        function_defs.sort_by(|a, b| {
+            (&a.cluster, a.category_order, a.display_order, &a.function_name).cmp(&(
+                &b.cluster,
+                b.category_order,
+                b.display_order,
+                &b.function_name,
+            ))
+        });
+
+        let by_id: HashMap<i64, &SystemFunction> = functions.iter().map(|f| (f.id, f)).collect();
+        let mut preference_defs: Vec<SystemFunctionPreferenceDefinition> = self
+            .preferences()
+            .await?
+            .iter()
+            .filter_map(|p| {
+                let function = by_id.get(&p.function_id)?;
+                Some(SystemFunctionPreferenceDefinition {
+                    cluster: names.get(&p.cluster_id)?.to_string(),
+                    category_name: function.category_name.clone(),
+                    function_name: function.function_name.clone(),
+                    category_order: p.category_order,
+                    display_order: p.display_order,
+                    is_favorited: p.is_favorited,
+                })
+            })
+            .collect();
+        preference_defs.sort_by(|a, b| {
+            (&a.cluster, a.category_order, a.display_order, &a.function_name).cmp(&(
+                &b.cluster,
+                b.category_order,
+                b.display_order,
+                &b.function_name,
+            ))
+        });
+
+        Ok(ConfigBundle {
+            version: CONFIG_BUNDLE_VERSION,
+            exported_at: Some(Utc::now()),
+            clusters: cluster_defs,
+            cluster_groups,
+            system_functions: function_defs,
+            system_function_preferences: preference_defs,
+        })
+    }
+
+    // Create or update everything in the bundle; a dry run only reports the changes
+    pub async fn import(
+        &self,
+        mut bundle: ConfigBundle,
+        dry_run: bool,
+        user_id: i64,
+    ) -> ApiResult<ConfigImportResponse> {
+        normalize(&mut bundle);
+        check_unique(bundle.clusters.iter().map(|c| c.name.clone()), "cluster")?;
+        check_unique(bundle.cluster_groups.iter().map(|g| g.name.clone()), "cluster group")?;
+        check_unique(bundle.system_functions.iter().map(function_name), "system function")?;
+        check_unique(
+            bundle
+                .system_function_preferences
+                .iter()
+                .map(preference_name),
+            "system function preference",
+        )?;
+
+        // Plan every item against the stored state before writing anything
+        let clusters = self.clusters_by_name().await?;
+        let known_cluster = |name: &str| {
+            clusters.contains_key(name) || bundle.clusters.iter().any(|c| c.name == name)
+        };
+
+        let mut cluster_changes = Vec::with_capacity(bundle.clusters.len());
+        for def in &bundle.clusters {
+            cluster_changes.push(plan_cluster(def, clusters.get(&def.name))?);
+        }
+
+        let groups: HashMap<String, ClusterGroupResponse> = self
+            .cluster_group_service
+            .list()
+            .await?
+            .into_iter()
+            .map(|g| (g.name.clone(), g))
+            .collect();
+        let mut environments: HashMap<&str, &str> = HashMap::new();
+        let mut group_changes = Vec::with_capacity(bundle.cluster_groups.len());
+        for def in &bundle.cluster_groups {
+            cluster_group_service::validate_name(&def.name)?;
+            for member in &def.clusters {
+                if !known_cluster(member) {
+                    return Err(ApiError::validation_error(format!(
+                        "Cluster group '{}': unknown cluster '{}'",
+                        def.name, member
+                    )));
+                }
+                if def.kind == ClusterGroupKind::Environment
+                    && let Some(other) = environments.insert(member, &def.name)
+                {
+                    return Err(ApiError::validation_error(format!(
+                        "Cluster '{}' cannot be in both environments '{}' and '{}'",
+                        member, other, def.name
+                    )));
+                }
+            }
+            group_changes.push(plan_group(def, groups.get(&def.name)));
+        }
+
+        let cluster_ids: HashMap<&str, i64> =
+            clusters.values().map(|c| (c.name.as_str(), c.id)).collect();
+        let functions = Self::functions_by_key(&self.pool, &cluster_ids).await?;
+        let mut per_category: HashMap<(String, String), usize> = HashMap::new();
+        for (cluster, category, _) in functions.keys() {
+            *per_category
+                .entry((cluster.clone(), category.clone()))
+                .or_default() += 1;
+        }
+        let mut function_changes = Vec::with_capacity(bundle.system_functions.len());
+        for def in &bundle.system_functions {
+            validate_function(def, known_cluster(&def.cluster))?;
+            let change = plan_function(def, functions.get(&function_key(def)));
+            if change.action == ConfigAction::Create {
+                let count = per_category
+                    .entry((def.cluster.clone(), def.category_name.clone()))
+                    .or_default();
+                *count += 1;
+                if *count > MAX_FUNCTIONS_PER_CATEGORY {
+                    return Err(ApiError::category_full(format!(
+                        "Category '{}' of cluster '{}' would have more than {} functions",
+                        def.category_name, def.cluster, MAX_FUNCTIONS_PER_CATEGORY
+                    )));
+                }
+            }
+            function_changes.push(change);
+        }
+
+        let preferences = self.preferences_by_function().await?;
+        let mut preference_changes = Vec::with_capacity(bundle.system_function_preferences.len());
+        for def in &bundle.system_function_preferences {
+            let key = preference_key(def);
+            let existing = functions.get(&key);
+            if existing.is_none()
+                && !bundle
+                    .system_functions
+                    .iter()
+                    .any(|f| function_key(f) == key)
+            {
+                return Err(ApiError::validation_error(format!(
+                    "System function preference '{}': unknown function",
+                    preference_name(def)
+                )));
+            }
+            let stored = existing.and_then(|f| preferences.get(&(f.cluster_id, f.id)));
+            preference_changes.push(plan_preference(def, stored));
+        }
+
+        if !dry_run {
+            // A failure part way, e.g. a name taken since the plan, leaves nothing behind
+            let mut tx = self.pool.begin().await?;
+            Self::apply_clusters(&mut tx, &bundle.clusters, &mut cluster_changes, user_id).await?;
+            let cluster_ids: HashMap<String, i64> = sqlx::query_as("SELECT name, id FROM clusters")
+                .fetch_all(&mut *tx)
+                .await?
+                .into_iter()
+                .collect();
+            Self::apply_groups(
+                &mut tx,
+                &bundle.cluster_groups,
+                &mut group_changes,
+                &cluster_ids,
+                user_id,
+            )
+            .await?;
+            Self::apply_functions(
+                &mut tx,
+                &bundle.system_functions,
+                &mut function_changes,
+                &cluster_ids,
+                user_id,
+            )
+            .await?;
+            Self::apply_preferences(
+                &mut tx,
+                &bundle.system_function_preferences,
+                &mut preference_changes,
+                &cluster_ids,
+            )
+            .await?;
+            tx.commit().await?;
+        }
+
+        let changes: Vec<ConfigChange> = cluster_changes
+            .into_iter()
+            .chain(group_changes)
+            .chain(function_changes)
+            .chain(preference_changes)
+            .collect();
+        let response = ConfigImportResponse::new(dry_run, changes);
+        tracing::info!(
+            "Configuration bundle {}: {} created, {} updated, {} unchanged",
+            if dry_run { "checked" } else { "imported" },
+            response.created,
+            response.updated,
+            response.unchanged
+        );
+        Ok(response)
+    }
+
+    async fn apply_clusters(
+        conn: &mut SqliteConnection,
+        defs: &[ClusterDefinition],
+        changes: &mut [ConfigChange],
+        user_id: i64,
+    ) -> ApiResult<()> {
+        for (def, change) in defs.iter().zip(changes.iter_mut()) {
+            match (change.action, change.id) {
+                (ConfigAction::Create, _) => {
+                    let cluster = ClusterService::create_cluster_in(
+                        &mut *conn,
+                        create_cluster_request(def)?,
+                        user_id,
+                    )
+                    .await?;
+                    change.id = Some(cluster.id);
+                },
+                (ConfigAction::Update, Some(id)) => {
+                    ClusterService::update_cluster_in(&mut *conn, id, update_cluster_request(def))
+                        .await?;
+                },
+                _ => {},
+            }
+        }
+        Ok(())
+    }
+
+    async fn apply_groups(
+        conn: &mut SqliteConnection,
+        defs: &[ClusterGroupDefinition],
+        changes: &mut [ConfigChange],
+        cluster_ids: &HashMap<String, i64>,
+        user_id: i64,
+    ) -> ApiResult<()> {
+        for (def, change) in defs.iter().zip(changes.iter_mut()) {
+            let members = def
+                .clusters
+                .iter()
+                .filter_map(|name| cluster_ids.get(name).copied())
+                .collect();
+            match (change.action, change.id) {
+                (ConfigAction::Create, _) => {
+                    let req = CreateClusterGroupRequest {
+                        name: def.name.clone(),
+                        description: def.description.clone(),
+                        kind: def.kind,
+                        cluster_ids: members,
+                    };
+                    change.id =
+                        Some(ClusterGroupService::create_in(&mut *conn, req, user_id).await?);
+                },
+                (ConfigAction::Update, Some(id)) => {
+                    let req = UpdateClusterGroupRequest {
+                        name: None,
+                        description: Some(def.description.clone().unwrap_or_default()),
+                        kind: Some(def.kind),
+                        cluster_ids: Some(members),
+                    };
+                    ClusterGroupService::update_in(&mut *conn, id, req).await?;
+                },
+                _ => {},
+            }
+        }
+        Ok(())
+    }
+
+    async fn apply_functions(
+        conn: &mut SqliteConnection,
+        defs: &[SystemFunctionDefinition],
+        changes: &mut [ConfigChange],
+        cluster_ids: &HashMap<String, i64>,
+        user_id: i64,
+    ) -> ApiResult<()> {
+        for (def, change) in defs.iter().zip(changes.iter_mut()) {
+            match (change.action, change.id) {
+                (ConfigAction::Create, _) => {
+                    let cluster_id = cluster_ids.get(&def.cluster).copied().ok_or_else(|| {
+                        ApiError::internal_error(format!(
+                            "Cluster '{}' was not imported",
+                            def.cluster
+                        ))
+                    })?;
+                    let id = sqlx::query_scalar::<_, i64>(
+                        "INSERT INTO system_functions (
+                            cluster_id, category_name, function_name, description, sql_query,
+                            display_order, category_order, is_favorited, created_by
+                        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?) RETURNING id",
+                    )
+                    .bind(cluster_id)
+                    .bind(&def.category_name)
+                    .bind(&def.function_name)
+                    .bind(&def.description)
+                    .bind(&def.sql_query)
+                    .bind(def.display_order)
+                    .bind(def.category_order)
+                    .bind(def.is_favorited)
+                    .bind(user_id)
+                    .fetch_one(&mut *conn)
+                    .await?;
+                    change.id = Some(id);
+                },
+                (ConfigAction::Update, Some(id)) => {
+                    sqlx::query(
+                        "UPDATE system_functions SET description = ?, sql_query = ?, \
+                         display_order = ?, category_order = ?, is_favorited = ?, \
+                         updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+                    )
+                    .bind(&def.description)
+                    .bind(&def.sql_query)
+                    .bind(def.display_order)
+                    .bind(def.category_order)
+                    .bind(def.is_favorited)
+                    .bind(id)
+                    .execute(&mut *conn)
+                    .await?;
+                },
+                _ => {},
+            }
+        }
+        Ok(())
+    }
+
+    async fn apply_preferences(
+        conn: &mut SqliteConnection,
+        defs: &[SystemFunctionPreferenceDefinition],
+        changes: &mut [ConfigChange],
+        cluster_ids: &HashMap<String, i64>,
+    ) -> ApiResult<()> {
+        let cluster_ids: HashMap<&str, i64> = cluster_ids
+            .iter()
+            .map(|(name, id)| (name.as_str(), *id))
+            .collect();
+        let functions = Self::functions_by_key(&mut *conn, &cluster_ids).await?;
+
+        for (def, change) in defs.iter().zip(changes.iter_mut()) {
+            if change.action == ConfigAction::Unchanged {
+                continue;
+            }
+            let function = functions.get(&preference_key(def)).ok_or_else(|| {
+                ApiError::internal_error(format!(
+                    "System function '{}' was not imported",
+                    preference_name(def)
+                ))
+            })?;
+            let id = sqlx::query_scalar::<_, i64>(
+                "INSERT INTO system_function_preferences
+                    (cluster_id, function_id, category_order, display_order, is_favorited, updated_at)
+                 VALUES (?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+                 ON CONFLICT(cluster_id, function_id) DO UPDATE SET
+                    category_order = excluded.category_order,
+                    display_order = excluded.display_order,
+                    is_favorited = excluded.is_favorited,
+                    updated_at = CURRENT_TIMESTAMP
+                 RETURNING id",
+            )
+            .bind(function.cluster_id)
+            .bind(function.id)
+            .bind(def.category_order)
+            .bind(def.display_order)
+            .bind(def.is_favorited)
+            .fetch_one(&mut *conn)
+            .await?;
+            change.id = Some(id);
+        }
+        Ok(())
+    }
+
+    async fn clusters_by_name(&self) -> ApiResult<HashMap<String, Cluster>> {
+        Ok(self
+            .cluster_service
+            .list_clusters()
+            .await?
+            .into_iter()
+            .map(|c| (c.name.clone(), c))
+            .collect())
+    }
+
+    // Custom functions; every stored function belongs to a cluster
+    async fn functions(executor: impl SqliteExecutor<'_>) -> ApiResult<Vec<SystemFunction>> {
+        Ok(sqlx::query_as(
+            "SELECT * FROM system_functions WHERE cluster_id IS NOT NULL \
+             ORDER BY cluster_id, category_order, display_order, id",
+        )
+        .fetch_all(executor)
+        .await?)
+    }
+
+    async fn functions_by_key(
+        executor: impl SqliteExecutor<'_>,
+        cluster_ids: &HashMap<&str, i64>,
+    ) -> ApiResult<HashMap<FunctionKey, SystemFunction>> {
+        let names: HashMap<i64, &str> = cluster_ids.iter().map(|(name, id)| (*id, *name)).collect();
+        Ok(Self::functions(executor)
+            .await?
+            .into_iter()
+            .filter_map(|f| {
+                let cluster = names.get(&f.cluster_id)?.to_string();
+                Some(((cluster, f.category_name.clone(), f.function_name.clone()), f))
+            })
+            .collect())
+    }
+
+    async fn preferences(&self) -> ApiResult<Vec<SystemFunctionPreference>> {
+        Ok(sqlx::query_as("SELECT * FROM system_function_preferences")
+            .fetch_all(&self.pool)
+            .await?)
+    }
+
+    async fn preferences_by_function(
+        &self,
+    ) -> ApiResult<HashMap<(i64, i64), SystemFunctionPreference>> {
+        Ok(self
+            .preferences()
+            .await?
+            .into_iter()
+            .map(|p| ((p.cluster_id, p.function_id), p))
+            .collect())
+    }
+}
+
+fn group_definition(group: ClusterGroupResponse) -> ClusterGroupDefinition {
+    ClusterGroupDefinition {
+        name: group.name,
+        description: group.description.filter(|d| !d.is_empty()),
+        kind: group.kind,
+        clusters: group.clusters.into_iter().map(|m| m.name).collect(),
+    }
+}
+
+// Trim names and treat empty optional text as unset, as the create endpoints do
+fn normalize(bundle: &mut ConfigBundle) {
+    let trim = |s: &mut String| *s = s.trim().to_string();
+    let optional = |s: &mut Option<String>| {
+        *s = s
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+    };
+
+    for cluster in &mut bundle.clusters {
+        trim(&mut cluster.name);
+        trim(&mut cluster.fe_host);
+        trim(&mut cluster.username);
+        trim(&mut cluster.catalog);
+        optional(&mut cluster.description);
+        optional(&mut cluster.tls_ca_cert);
+        optional(&mut cluster.tls_client_identity);
+
+        let primary = primary_fe(cluster);
+        let mut endpoints: Vec<FeEndpoint> = Vec::new();
+        for mut endpoint in cluster.fe_endpoints.drain(..) {
+            trim(&mut endpoint.host);
+            if endpoint != primary && !endpoints.contains(&endpoint) {
+                endpoints.push(endpoint);
+            }
+        }
+        cluster.fe_endpoints = endpoints;
+    }
+    for group in &mut bundle.cluster_groups {
+        trim(&mut group.name);
+        optional(&mut group.description);
+        group.clusters.iter_mut().for_each(trim);
+        let mut seen = HashSet::new();
+        group.clusters.retain(|name| seen.insert(name.clone()));
+    }
+    for function in &mut bundle.system_functions {
+        trim(&mut function.cluster);
+        trim(&mut function.category_name);
+        trim(&mut function.function_name);
+        trim(&mut function.description);
+        trim(&mut function.sql_query);
+    }
+    for preference in &mut bundle.system_function_preferences {
+        trim(&mut preference.cluster);
+        trim(&mut preference.category_name);
+        trim(&mut preference.function_name);
+    }
+}
+
+fn check_unique(names: impl Iterator<Item = String>, what: &str) -> ApiResult<()> {
+    let mut seen = HashSet::new();
+    for name in names {
+        if !seen.insert(name.clone()) {
+            return Err(ApiError::validation_error(format!(
+                "Duplicate {} '{}' in bundle",
+                what, name
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn primary_fe(def: &ClusterDefinition) -> FeEndpoint {
+    FeEndpoint {
+        host: def.fe_host.clone(),
+        http_port: def.fe_http_port,
+        query_port: def.fe_query_port,
+    }
+}
+
+fn plan_cluster(def: &ClusterDefinition, existing: Option<&Cluster>) -> ApiResult<ConfigChange> {
+    let invalid =
+        |message: &str| ApiError::validation_error(format!("Cluster '{}': {}", def.name, message));
+    if def.name.is_empty() {
+        return Err(ApiError::validation_error("Cluster name cannot be empty"));
+    }
+    if def.fe_host.is_empty() {
+        return Err(invalid("FE host cannot be empty"));
+    }
+    if def.username.is_empty() {
+        return Err(invalid("username cannot be empty"));
+    }
+    let valid_port = |port: i32| (1..=65535).contains(&port);
+    if !valid_port(def.fe_http_port) || !valid_port(def.fe_query_port) {
+        return Err(invalid("ports must be between 1 and 65535"));
+    }
+    if let Some(ca_cert) = &def.tls_ca_cert {
+        ClusterService::validate_ca_cert(ca_cert).map_err(|e| invalid(&e.to_string()))?;
+    }
+
+    let Some(cluster) = existing else {
+        if def.password.is_none() {
+            return Err(invalid("a password is required for a new cluster"));
+        }
+        return Ok(change(ConfigItemKind::Cluster, &def.name, None, None));
+    };
+
+    let mut fields = Vec::new();
+    let mut diff = |field: &str, changed: bool| {
+        if changed {
+            fields.push(field.to_string());
+        }
+    };
+    diff(
+        "description",
+        def.description.as_deref().unwrap_or_default()
+            != cluster.description.as_deref().unwrap_or_default(),
+    );
+    diff("fe_host", def.fe_host != cluster.fe_host);
+    diff("fe_http_port", def.fe_http_port != cluster.fe_http_port);
+    diff("fe_query_port", def.fe_query_port != cluster.fe_query_port);
+    diff("fe_endpoints", def.fe_endpoints != cluster.additional_fes());
+    diff("username", def.username != cluster.username);
+    diff(
+        "password",
+        def.password
+            .as_ref()
+            .is_some_and(|password| cluster.password().ok().as_ref() != Some(password)),
+    );
+    diff("enable_ssl", def.enable_ssl != cluster.enable_ssl);
+    diff(
+        "tls_ca_cert",
+        def.tls_ca_cert.as_deref() != cluster.tls_ca_cert.as_deref().map(str::trim),
+    );
+    diff(
+        "tls_client_identity",
+        def.tls_client_identity.is_some()
+            && (decrypt(&cluster.tls_client_identity_encrypted) != def.tls_client_identity
+                || decrypt(&cluster.tls_client_identity_password_encrypted).unwrap_or_default()
+                    != def.tls_client_identity_password.clone().unwrap_or_default()),
+    );
+    diff("tls_verify_hostname", def.tls_verify_hostname != cluster.tls_verify_hostname);
+    diff("connection_timeout", def.connection_timeout != cluster.connection_timeout);
+    diff("pool_settings", def.pool_settings != cluster.pool_settings());
+    diff("sql_policy", def.sql_policy != cluster.sql_policy());
+    diff("tags", def.tags != cluster.tag_list());
+    diff("catalog", def.catalog != cluster.catalog);
+
+    Ok(change(ConfigItemKind::Cluster, &def.name, Some(cluster.id), Some(fields)))
+}
+
+fn decrypt(stored: &Option<String>) -> Option<String> {
+    stored
+        .as_deref()
+        .and_then(|s| crypto::decrypt_secret(s).ok())
+}
+
+fn plan_group(
+    def: &ClusterGroupDefinition,
+    existing: Option<&ClusterGroupResponse>,
+) -> ConfigChange {
+    let Some(group) = existing else {
+        return change(ConfigItemKind::ClusterGroup, &def.name, None, None);
+    };
+
+    let members: HashSet<&str> = group.clusters.iter().map(|m| m.name.as_str()).collect();
+    let mut fields = Vec::new();
+    if def.description.as_deref().unwrap_or_default()
+        != group.description.as_deref().unwrap_or_default()
+    {
+        fields.push("description".to_string());
+    }
+    if def.kind != group.kind {
+        fields.push("kind".to_string());
+    }
+    if def
+        .clusters
+        .iter()
+        .map(String::as_str)
+        .collect::<HashSet<_>>()
+        != members
+    {
+        fields.push("clusters".to_string());
+    }
+    change(ConfigItemKind::ClusterGroup, &def.name, Some(group.id), Some(fields))
+}
+
+fn validate_function(def: &SystemFunctionDefinition, cluster_known: bool) -> ApiResult<()> {
+    let name = function_name(def);
+    let invalid = |message: String| {
+        ApiError::validation_error(format!("System function '{}': {}", name, message))
+    };
+    if !cluster_known {
+        return Err(invalid(format!("unknown cluster '{}'", def.cluster)));
+    }
+    for (field, value, max) in [
+        ("category_name", &def.category_name, 100),
+        ("function_name", &def.function_name, 100),
+        ("description", &def.description, 500),
+    ] {
+        if value.is_empty() || value.chars().count() > max {
+            return Err(invalid(format!("{} must be between 1 and {} characters", field, max)));
+        }
+    }
+    if def.sql_query.is_empty() {
+        return Err(invalid("SQL query cannot be empty".to_string()));
+    }
+    SystemFunctionService::validate_sql_safety(&def.sql_query).map_err(|e| invalid(e.to_string()))
+}
+
+fn plan_function(
+    def: &SystemFunctionDefinition,
+    existing: Option<&SystemFunction>,
+) -> ConfigChange {
+    let name = function_name(def);
+    let Some(function) = existing else {
+        return change(ConfigItemKind::SystemFunction, &name, None, None);
+    };
+
+    let fields = [
+        ("description", def.description != function.description),
+        ("sql_query", def.sql_query != function.sql_query),
+        ("display_order", def.display_order != function.display_order),
+        ("category_order", def.category_order != function.category_order),
+        ("is_favorited", def.is_favorited != function.is_favorited),
+    ]
+    .into_iter()
+    .filter(|(_, changed)| *changed)
+    .map(|(field, _)| field.to_string())
+    .collect();
+    change(ConfigItemKind::SystemFunction, &name, Some(function.id), Some(fields))
+}
+
+fn plan_preference(
+    def: &SystemFunctionPreferenceDefinition,
+    existing: Option<&SystemFunctionPreference>,
+) -> ConfigChange {
+    let name = preference_name(def);
+    let Some(preference) = existing else {
+        return change(ConfigItemKind::SystemFunctionPreference, &name, None, None);
+    };
+
+    let fields = [
+        ("category_order", def.category_order != preference.category_order),
+        ("display_order", def.display_order != preference.display_order),
+        ("is_favorited", def.is_favorited != preference.is_favorited),
+    ]
+    .into_iter()
+    .filter(|(_, changed)| *changed)
+    .map(|(field, _)| field.to_string())
+    .collect();
+    change(ConfigItemKind::SystemFunctionPreference, &name, Some(preference.id), Some(fields))
+}
+
+// Create when there is nothing stored, otherwise update when any field differs
+fn change(
+    kind: ConfigItemKind,
+    name: &str,
+    id: Option<i64>,
+    fields: Option<Vec<String>>,
+) -> ConfigChange {
+    let action = match &fields {
+        None => ConfigAction::Create,
+        Some(fields) if fields.is_empty() => ConfigAction::Unchanged,
+        Some(_) => ConfigAction::Update,
+    };
+    ConfigChange { kind, name: name.to_string(), action, id, fields: fields.unwrap_or_default() }
+}
+
+fn function_key(def: &SystemFunctionDefinition) -> FunctionKey {
+    (def.cluster.clone(), def.category_name.clone(), def.function_name.clone())
+}
+
+fn preference_key(def: &SystemFunctionPreferenceDefinition) -> FunctionKey {
+    (def.cluster.clone(), def.category_name.clone(), def.function_name.clone())
+}
+
+fn function_name(def: &SystemFunctionDefinition) -> String {
+    format!("{}/{}/{}", def.cluster, def.category_name, def.function_name)
+}
+
+fn preference_name(def: &SystemFunctionPreferenceDefinition) -> String {
+    format!("{}/{}/{}", def.cluster, def.category_name, def.function_name)
+}
+
+fn create_cluster_request(def: &ClusterDefinition) -> ApiResult<CreateClusterRequest> {
+    let password = def.password.clone().ok_or_else(|| {
+        ApiError::validation_error(format!("Cluster '{}': a password is required", def.name))
+    })?;
+    Ok(CreateClusterRequest {
+        name: def.name.clone(),
+        description: def.description.clone(),
+        fe_host: def.fe_host.clone(),
+        fe_http_port: def.fe_http_port,
+        fe_query_port: def.fe_query_port,
+        fe_endpoints: def.fe_endpoints.clone(),
+        username: def.username.clone(),
+        password,
+        enable_ssl: def.enable_ssl,
+        tls_ca_cert: def.tls_ca_cert.clone(),
+        tls_client_identity: def.tls_client_identity.clone(),
+        tls_client_identity_password: def.tls_client_identity_password.clone(),
+        tls_verify_hostname: def.tls_verify_hostname,
+        connection_timeout: def.connection_timeout,
+        pool_settings: def.pool_settings.clone(),
+        sql_policy: def.sql_policy.clone(),
+        tags: Some(def.tags.clone()),
+        catalog: def.catalog.clone(),
+    })
+}
+
+// Every setting from the bundle; secrets only when given so that stored ones are kept
+fn update_cluster_request(def: &ClusterDefinition) -> UpdateClusterRequest {
+    UpdateClusterRequest {
+        name: None,
+        description: Some(def.description.clone().unwrap_or_default()),
+        fe_host: Some(def.fe_host.clone()),
+        fe_http_port: Some(def.fe_http_port),
+        fe_query_port: Some(def.fe_query_port),
+        fe_endpoints: Some(def.fe_endpoints.clone()),
+        username: Some(def.username.clone()),
+        password: def.password.clone(),
+        enable_ssl: Some(def.enable_ssl),
+        tls_ca_cert: Some(def.tls_ca_cert.clone().unwrap_or_default()),
+        tls_client_identity: def.tls_client_identity.clone(),
+        tls_client_identity_password: def
+            .tls_client_identity
+            .as_ref()
+            .and(def.tls_client_identity_password.clone()),
+        tls_verify_hostname: Some(def.tls_verify_hostname),
+        connection_timeout: Some(def.connection_timeout),
+        pool_settings: Some(def.pool_settings.clone()),
+        sql_policy: Some(def.sql_policy.clone()),
+        tags: Some(def.tags.clone()),
+        catalog: Some(def.catalog.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ConfigFormat;
+
+    async fn pool_with_clusters() -> SqlitePool {
+        let pool = crate::db::test_pool().await;
+        for name in ["prod-a", "prod-b"] {
+            sqlx::query(
+                "INSERT INTO clusters (name, fe_host, username, password_encrypted) \
+                 VALUES (?, 'fe', 'root', '')",
+            )
+            .bind(name)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        pool
+    }
+
+    const BUNDLE: &str = r#"
+version = 1
+
+[[clusters]]
+name = "prod-a"
+description = "Primary"
+fe_host = "fe"
+username = "root"
+tags = ["prod"]
+
+[[clusters.fe_endpoints]]
+host = "fe-2"
+http_port = 8030
+query_port = 9030
+
+[clusters.pool_settings]
+max_connections = 20
+
+[[cluster_groups]]
+name = "prod"
+kind = "environment"
+clusters = ["prod-a", "prod-b"]
+
+[[system_functions]]
+cluster = "prod-b"
+category_name = "Ops"
+function_name = "Tablets"
+description = "Tablet count"
+sql_query = "SHOW PROC '/statistic'"
+
+[[system_function_preferences]]
+cluster = "prod-b"
+category_name = "Ops"
+function_name = "Tablets"
+is_favorited = true
+"#;
+
+    #[test]
+    fn test_bundle_formats() {
+        let bundle = ConfigBundle::parse(BUNDLE, ConfigFormat::Toml).unwrap();
+        assert_eq!(bundle.clusters[0].fe_http_port, 8030);
+        assert_eq!(bundle.clusters[0].pool_settings.max_connections, Some(20));
+
+        for format in [ConfigFormat::Toml, ConfigFormat::Json] {
+            let text = bundle.render(format).unwrap();
+            assert_eq!(ConfigFormat::detect(None, &text), format);
+            let parsed = ConfigBundle::parse(&text, format).unwrap();
+            assert_eq!(parsed.clusters[0].fe_endpoints, bundle.clusters[0].fe_endpoints);
+            assert_eq!(parsed.cluster_groups[0].kind, ClusterGroupKind::Environment);
+        }
+        assert_eq!(
+            ConfigFormat::detect(Some("application/json"), "version = 1"),
+            ConfigFormat::Json
+        );
+        assert!(ConfigBundle::parse("version = 2", ConfigFormat::Toml).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_import_is_idempotent() {
+        let pool = pool_with_clusters().await;
+        let service = ConfigBundleService::new(
+            pool.clone(),
+            Arc::new(ClusterService::new(pool.clone())),
+            Arc::new(ClusterGroupService::new(pool.clone())),
+        );
+        let bundle = ConfigBundle::parse(BUNDLE, ConfigFormat::Toml).unwrap();
+
+        let dry_run = service.import(bundle.clone(), true, 1).await.unwrap();
+        assert_eq!((dry_run.created, dry_run.updated), (3, 1));
+        assert_eq!(
+            dry_run.changes[0].fields,
+            ["description", "fe_endpoints", "pool_settings", "tags"]
+        );
+        assert!(service.export().await.unwrap().cluster_groups.is_empty());
+
+        let imported = service.import(bundle.clone(), false, 1).await.unwrap();
+        assert_eq!((imported.created, imported.updated), (3, 1));
+        assert!(imported.changes.iter().all(|c| c.id.is_some()));
+
+        let again = service.import(bundle, false, 1).await.unwrap();
+        assert_eq!(again.unchanged, 4);
+
+        // An export imports back without changes
+        let exported = service.export().await.unwrap();
+        assert!(exported.clusters.iter().all(|c| c.password.is_none()));
+        let round_trip = service.import(exported, true, 1).await.unwrap();
+        assert_eq!(round_trip.unchanged, round_trip.changes.len());
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_unknown_references() {
+        let pool = pool_with_clusters().await;
+        let service = ConfigBundleService::new(
+            pool.clone(),
+            Arc::new(ClusterService::new(pool.clone())),
+            Arc::new(ClusterGroupService::new(pool.clone())),
+        );
+
+        let mut bundle = ConfigBundle::parse(BUNDLE, ConfigFormat::Toml).unwrap();
+        bundle.cluster_groups[0]
+            .clusters
+            .push("staging".to_string());
+        assert!(service.import(bundle, false, 1).await.is_err());
+
+        let mut bundle = ConfigBundle::parse(BUNDLE, ConfigFormat::Toml).unwrap();
+        bundle.clusters[0].name = "new-cluster".to_string();
+        assert!(service.import(bundle, false, 1).await.is_err());
+
+        // Nothing was written by the rejected imports
+        assert!(service.export().await.unwrap().system_functions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_import_writes_nothing() {
+        let pool = pool_with_clusters().await;
+        let service = ConfigBundleService::new(
+            pool.clone(),
+            Arc::new(ClusterService::new(pool.clone())),
+            Arc::new(ClusterGroupService::new(pool.clone())),
+        );
+        // Fail after the clusters and groups of the bundle have been written
+        sqlx::query(
+            "CREATE TRIGGER fail_import BEFORE INSERT ON system_functions \
+             BEGIN SELECT RAISE(ABORT, 'disk full'); END",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let bundle = ConfigBundle::parse(BUNDLE, ConfigFormat::Toml).unwrap();
+        assert!(service.import(bundle, false, 1).await.is_err());
+
+        let exported = service.export().await.unwrap();
+        assert!(exported.cluster_groups.is_empty());
+        let prod_a = exported
+            .clusters
+            .iter()
+            .find(|c| c.name == "prod-a")
+            .unwrap();
+        assert_eq!(prod_a.description, None);
+        assert!(prod_a.fe_endpoints.is_empty());
+    }
+}
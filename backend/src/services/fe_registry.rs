@@ -0,0 +1,225 @@
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use std::time::{Duration, Instant};
+
+use crate::models::{Cluster, FeEndpoint, Frontend};
+
+/// How long an FE that failed is tried only after the others
+const DOWN_COOLDOWN: Duration = Duration::from_secs(30);
+
+static GLOBAL: Lazy<FeRegistry> = Lazy::new(FeRegistry::default);
+
+/// Health and topology of the FEs of all clusters
+///
+/// Shared by `StarRocksClient` and `MySQLPoolManager`, so that an FE found down over
+/// HTTP is also avoided for new MySQL pools and vice versa. Besides the configured
+/// FEs, the alive FEs reported by `SHOW FRONTENDS` are remembered per cluster and
+/// used as a last resort, together with the current leader for DDL routing.
+#[derive(Default)]
+pub struct FeRegistry {
+    down_until: DashMap<FeEndpoint, Instant>,
+    discovered: DashMap<i64, Vec<FeEndpoint>>,
+    leaders: DashMap<i64, FeEndpoint>,
+}
+
+impl FeRegistry {
+    pub fn global() -> &'static FeRegistry {
+        &GLOBAL
+    }
+
+    /// FEs to try for a request, in order
+    ///
+    /// Configured FEs come first (primary before the others), then discovered ones;
+    /// FEs that failed recently move to the end but are still tried.
+    pub fn candidates(&self, cluster: &Cluster) -> Vec<FeEndpoint> {
+        let mut endpoints = cluster.configured_fes();
+        if let Some(discovered) = self.discovered.get(&cluster.id) {
+            for endpoint in discovered.iter() {
+                if !endpoints.contains(endpoint) {
+                    endpoints.push(endpoint.clone());
+                }
+            }
+        }
+
+        let (up, down): (Vec<_>, Vec<_>) = endpoints.into_iter().partition(|e| self.is_up(e));
+        up.into_iter().chain(down).collect()
+    }
+
+    /// Candidates with the known leader first, for statements the leader must run
+    pub fn leader_first(&self, cluster: &Cluster) -> Vec<FeEndpoint> {
+        let mut endpoints = self.candidates(cluster);
+        if let Some(leader) = self.leader(cluster.id)
+            && self.is_up(&leader)
+        {
+            endpoints.retain(|e| *e != leader);
+            endpoints.insert(0, leader);
+        }
+        endpoints
+    }
+
+    pub fn leader(&self, cluster_id: i64) -> Option<FeEndpoint> {
+        self.leaders.get(&cluster_id).map(|leader| leader.clone())
+    }
+
+    pub fn is_up(&self, endpoint: &FeEndpoint) -> bool {
+        self.down_until
+            .get(endpoint)
+            .is_none_or(|until| *until <= Instant::now())
+    }
+
+    pub fn mark_down(&self, endpoint: &FeEndpoint) {
+        self.down_until
+            .insert(endpoint.clone(), Instant::now() + DOWN_COOLDOWN);
+    }
+
+    pub fn mark_up(&self, endpoint: &FeEndpoint) {
+        self.down_until.remove(endpoint);
+    }
+
+    /// Remember the alive FEs and the leader reported by `SHOW FRONTENDS`
+    ///
+    /// Each result replaces the previous one, so a leader that is gone or no longer
+    /// alive is not kept for DDL routing.
+    pub fn record_frontends(&self, cluster_id: i64, frontends: &[Frontend]) {
+        // Unsaved clusters (connection tests) all share id 0
+        if cluster_id <= 0 {
+            return;
+        }
+
+        let mut alive = Vec::new();
+        let mut leader = None;
+        for fe in frontends
+            .iter()
+            .filter(|fe| fe.alive.eq_ignore_ascii_case("true"))
+        {
+            let (Ok(http_port), Ok(query_port)) =
+                (fe.http_port.trim().parse(), fe.query_port.trim().parse())
+            else {
+                continue;
+            };
+            let endpoint = FeEndpoint { host: fe.host.clone(), http_port, query_port };
+
+            let is_leader = matches!(fe.role.to_uppercase().as_str(), "LEADER" | "MASTER")
+                || fe
+                    .is_master
+                    .as_deref()
+                    .is_some_and(|m| m.eq_ignore_ascii_case("true"));
+            if is_leader {
+                leader = Some(endpoint.clone());
+            }
+            alive.push(endpoint);
+        }
+
+        if let Some(leader) = leader {
+            self.leaders.insert(cluster_id, leader);
+        } else {
+            self.leaders.remove(&cluster_id);
+        }
+        self.discovered.insert(cluster_id, alive);
+    }
+
+    /// Forget what was learnt about a cluster (e.g. after it was edited or deleted)
+    pub fn forget_cluster(&self, cluster_id: i64) {
+        self.discovered.remove(&cluster_id);
+        self.leaders.remove(&cluster_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fe(host: &str) -> FeEndpoint {
+        FeEndpoint { host: host.to_string(), http_port: 8030, query_port: 9030 }
+    }
+
+    fn cluster(additional: &[FeEndpoint]) -> Cluster {
+        Cluster {
+            id: 7,
+            fe_endpoints: Some(serde_json::to_string(additional).unwrap()),
+            ..Cluster::for_test()
+        }
+    }
+
+    fn frontend(host: &str, role: &str, alive: bool) -> Frontend {
+        serde_json::from_value(serde_json::json!({
+            "Name": host, "IP": host, "EditLogPort": "9010", "HttpPort": "8030",
+            "QueryPort": "9030", "RpcPort": "9020", "Role": role, "ClusterId": "1",
+            "Join": "true", "Alive": alive.to_string(), "ReplayedJournalId": "1",
+            "LastHeartbeat": "", "ErrMsg": "", "Version": "3.3"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_failed_fes_are_tried_last() {
+        let registry = FeRegistry::default();
+        let cluster = cluster(&[fe("fe2"), fe("fe3")]);
+
+        assert_eq!(registry.candidates(&cluster), vec![fe("fe1"), fe("fe2"), fe("fe3")]);
+
+        registry.mark_down(&fe("fe1"));
+        assert_eq!(registry.candidates(&cluster), vec![fe("fe2"), fe("fe3"), fe("fe1")]);
+
+        registry.mark_up(&fe("fe1"));
+        assert_eq!(registry.candidates(&cluster)[0], fe("fe1"));
+    }
+
+    #[test]
+    fn test_discovered_fes_and_leader() {
+        let registry = FeRegistry::default();
+        let cluster = cluster(&[]);
+
+        registry.record_frontends(
+            cluster.id,
+            &[
+                frontend("fe1", "FOLLOWER", true),
+                frontend("fe4", "LEADER", true),
+                frontend("fe5", "FOLLOWER", false),
+            ],
+        );
+
+        assert_eq!(registry.candidates(&cluster), vec![fe("fe1"), fe("fe4")]);
+        assert_eq!(registry.leader_first(&cluster), vec![fe("fe4"), fe("fe1")]);
+
+        // A leader that is down is not preferred
+        registry.mark_down(&fe("fe4"));
+        assert_eq!(registry.leader_first(&cluster), vec![fe("fe1"), fe("fe4")]);
+
+        registry.forget_cluster(cluster.id);
+        assert_eq!(registry.leader(cluster.id), None);
+        assert_eq!(registry.candidates(&cluster), vec![fe("fe1")]);
+
+        registry.record_frontends(0, &[frontend("fe4", "LEADER", true)]);
+        assert_eq!(registry.leader(0), None);
+    }
+
+    #[test]
+    fn test_leader_is_replaced_on_refresh() {
+        let registry = FeRegistry::default();
+        let cluster = cluster(&[]);
+
+        registry.record_frontends(
+            cluster.id,
+            &[frontend("fe1", "FOLLOWER", true), frontend("fe4", "LEADER", true)],
+        );
+        assert_eq!(registry.leader(cluster.id), Some(fe("fe4")));
+
+        // The old leader left and the election is still running
+        registry.record_frontends(cluster.id, &[frontend("fe1", "FOLLOWER", true)]);
+        assert_eq!(registry.leader(cluster.id), None);
+        assert_eq!(registry.leader_first(&cluster), vec![fe("fe1")]);
+
+        // A leader that is reported but no longer alive is dropped as well
+        registry.record_frontends(
+            cluster.id,
+            &[frontend("fe1", "LEADER", true), frontend("fe4", "FOLLOWER", true)],
+        );
+        assert_eq!(registry.leader(cluster.id), Some(fe("fe1")));
+        registry.record_frontends(
+            cluster.id,
+            &[frontend("fe1", "LEADER", false), frontend("fe4", "FOLLOWER", true)],
+        );
+        assert_eq!(registry.leader(cluster.id), None);
+    }
+}
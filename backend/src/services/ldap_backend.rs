@@ -0,0 +1,600 @@
+use async_trait::async_trait;
+use ldap3::{LdapConnAsync, LdapConnSettings, Scope, SearchEntry, ldap_escape};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::LdapConfig;
+use crate::models::AuthSource;
+use crate::services::auth_backend::{ExternalIdentity, PasswordAuthBackend, RoleMapping};
+use crate::utils::{ApiError, ApiResult};
+
+/// LDAP result code for a failed bind
+const INVALID_CREDENTIALS: u32 = 49;
+
+/// A user entry found in the directory
+#[derive(Debug, Clone)]
+pub struct DirectoryEntry {
+    pub dn: String,
+    pub attributes: HashMap<String, Vec<String>>,
+}
+
+/// Minimal directory operations needed for bind authentication
+#[async_trait]
+pub trait Directory: Send + Sync {
+    /// Search for the user entry matching `filter`
+    async fn find_user(&self, filter: &str) -> ApiResult<Option<DirectoryEntry>>;
+
+    /// Try a simple bind; `Ok(false)` when the credentials are rejected
+    async fn bind(&self, dn: &str, password: &str) -> ApiResult<bool>;
+}
+
+/// Authenticates users by searching their entry and binding as them
+pub struct LdapBackend {
+    config: LdapConfig,
+    directory: Arc<dyn Directory>,
+    role_mapping: RoleMapping,
+}
+
+impl LdapBackend {
+    pub fn new(config: LdapConfig) -> Self {
+        let directory = Arc::new(Ldap3Directory { config: config.clone() });
+        Self::with_directory(config, directory)
+    }
+
+    pub fn with_directory(config: LdapConfig, directory: Arc<dyn Directory>) -> Self {
+        let role_mapping = RoleMapping::new(&config.group_roles, config.default_role);
+        Self { config, directory, role_mapping }
+    }
+
+    fn user_filter(&self, username: &str) -> String {
+        self.config
+            .user_filter
+            .replace("{username}", &ldap_escape(username))
+    }
+
+    fn first_attribute(entry: &DirectoryEntry, name: &str) -> Option<String> {
+        entry
+            .attributes
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .and_then(|(_, values)| values.first().cloned())
+    }
+
+    fn all_attributes(entry: &DirectoryEntry, name: &str) -> Vec<String> {
+        entry
+            .attributes
+            .iter()
+            .filter(|(key, _)| key.eq_ignore_ascii_case(name))
+            .flat_map(|(_, values)| values.iter().cloned())
+            .collect()
+    }
+}
+
+#[async_trait]
+impl PasswordAuthBackend for LdapBackend {
+    fn source(&self) -> AuthSource {
+        AuthSource::Ldap
+    }
+
+    async fn authenticate(
+        &self,
+        username: &str,
+        password: &str,
+    ) -> ApiResult<Option<ExternalIdentity>> {
+        // An empty password would be an unauthenticated bind, which most servers accept
+        if username.trim().is_empty() || password.is_empty() {
+            return Ok(None);
+        }
+        // Directories match login names case-insensitively; so do accounts
+        let username = username.trim().to_lowercase();
+
+        let Some(entry) = self
+            .directory
+            .find_user(&self.user_filter(&username))
+            .await?
+        else {
+            tracing::debug!("LDAP user '{}' not found", username);
+            return Ok(None);
+        };
+
+        if !self.directory.bind(&entry.dn, password).await? {
+            tracing::debug!("LDAP bind failed for '{}'", entry.dn);
+            return Ok(None);
+        }
+
+        let groups = Self::all_attributes(&entry, &self.config.group_attribute);
+        let role = self.role_mapping.require(&username, &groups)?;
+
+        // Accounts provisioned before the entry had a stable id are keyed by its DN
+        let stable_id = Some(&self.config.id_attribute)
+            .filter(|attribute| !attribute.is_empty())
+            .and_then(|attribute| Self::first_attribute(&entry, attribute));
+        let (external_id, alternate_ids) = match stable_id {
+            Some(id) => (id, vec![entry.dn.clone()]),
+            None => (entry.dn.clone(), Vec::new()),
+        };
+
+        Ok(Some(ExternalIdentity {
+            source: AuthSource::Ldap,
+            external_id,
+            alternate_ids,
+            username,
+            email: Self::first_attribute(&entry, &self.config.email_attribute),
+            groups,
+            role,
+        }))
+    }
+}
+
+/// [`Directory`] backed by a real LDAP server
+struct Ldap3Directory {
+    config: LdapConfig,
+}
+
+impl Ldap3Directory {
+    async fn connect(&self) -> ApiResult<ldap3::Ldap> {
+        let settings =
+            LdapConnSettings::new().set_conn_timeout(Duration::from_secs(self.config.timeout_secs));
+        let (conn, ldap) = LdapConnAsync::with_settings(settings, &self.config.url)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to connect to LDAP server {}: {}", self.config.url, e);
+                ApiError::internal_error("LDAP server is unavailable")
+            })?;
+
+        tokio::spawn(async move {
+            if let Err(e) = conn.drive().await {
+                tracing::warn!("LDAP connection error: {}", e);
+            }
+        });
+
+        Ok(ldap)
+    }
+}
+
+fn ldap_error(e: ldap3::LdapError) -> ApiError {
+    tracing::error!("LDAP operation failed: {}", e);
+    ApiError::internal_error("LDAP operation failed")
+}
+
+#[async_trait]
+impl Directory for Ldap3Directory {
+    async fn find_user(&self, filter: &str) -> ApiResult<Option<DirectoryEntry>> {
+        let mut ldap = self.connect().await?;
+
+        if !self.config.bind_dn.is_empty() {
+            ldap.simple_bind(&self.config.bind_dn, &self.config.bind_password)
+                .await
+                .and_then(|r| r.success())
+                .map_err(ldap_error)?;
+        }
+
+        let attributes: Vec<&str> =
+            [&self.config.id_attribute, &self.config.email_attribute, &self.config.group_attribute]
+                .into_iter()
+                .filter(|attribute| !attribute.is_empty())
+                .map(String::as_str)
+                .collect();
+        let (entries, _) = ldap
+            .search(&self.config.user_base_dn, Scope::Subtree, filter, attributes)
+            .await
+            .and_then(|r| r.success())
+            .map_err(ldap_error)?;
+        let _ = ldap.unbind().await;
+
+        if entries.len() > 1 {
+            tracing::warn!(
+                "LDAP filter {} matched {} entries, refusing to pick one",
+                filter,
+                entries.len()
+            );
+            return Ok(None);
+        }
+
+        Ok(entries.into_iter().next().map(|entry| {
+            let entry = SearchEntry::construct(entry);
+            DirectoryEntry { dn: entry.dn, attributes: entry.attrs }
+        }))
+    }
+
+    async fn bind(&self, dn: &str, password: &str) -> ApiResult<bool> {
+        let mut ldap = self.connect().await?;
+        let result = ldap.simple_bind(dn, password).await.map_err(ldap_error)?;
+        let _ = ldap.unbind().await;
+
+        match result.rc {
+            0 => Ok(true),
+            INVALID_CREDENTIALS => Ok(false),
+            _ => Err(ldap_error(ldap3::LdapError::from(result))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::UserRole;
+
+    /// In-memory [`Directory`] for the mapping logic of the backend; the real
+    /// [`Ldap3Directory`] is exercised against [`StandInServer`] below
+    struct StaticDirectory {
+        users: Vec<(String, String, DirectoryEntry)>, // (uid, password, entry)
+    }
+
+    #[async_trait]
+    impl Directory for StaticDirectory {
+        async fn find_user(&self, filter: &str) -> ApiResult<Option<DirectoryEntry>> {
+            Ok(self
+                .users
+                .iter()
+                .find(|(uid, _, _)| filter == format!("(uid={})", uid))
+                .map(|(_, _, entry)| entry.clone()))
+        }
+
+        async fn bind(&self, dn: &str, password: &str) -> ApiResult<bool> {
+            Ok(self
+                .users
+                .iter()
+                .any(|(_, pw, entry)| entry.dn == dn && pw == password))
+        }
+    }
+
+    fn backend(default_role: Option<UserRole>) -> LdapBackend {
+        let entry = |uid: &str, groups: &[&str]| DirectoryEntry {
+            dn: format!("uid={},ou=people,dc=example,dc=com", uid),
+            attributes: HashMap::from([
+                ("mail".to_string(), vec![format!("{}@example.com", uid)]),
+                ("memberOf".to_string(), groups.iter().map(|g| g.to_string()).collect()),
+            ]),
+        };
+        let directory = StaticDirectory {
+            users: vec![
+                (
+                    "alice".into(),
+                    "alice-pw".into(),
+                    entry("alice", &["cn=dba,ou=groups,dc=example,dc=com"]),
+                ),
+                (
+                    "bob".into(),
+                    "bob-pw".into(),
+                    entry("bob", &["cn=sales,ou=groups,dc=example,dc=com"]),
+                ),
+            ],
+        };
+
+        let config = LdapConfig {
+            enabled: true,
+            user_base_dn: "ou=people,dc=example,dc=com".to_string(),
+            group_roles: HashMap::from([("dba".to_string(), UserRole::Operator)]),
+            default_role,
+            ..LdapConfig::default()
+        };
+        LdapBackend::with_directory(config, Arc::new(directory))
+    }
+
+    #[tokio::test]
+    async fn test_bind_success_maps_groups_to_role() {
+        let identity = backend(None)
+            .authenticate("alice", "alice-pw")
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(identity.source, AuthSource::Ldap);
+        assert_eq!(identity.external_id, "uid=alice,ou=people,dc=example,dc=com");
+        assert_eq!(identity.email.as_deref(), Some("alice@example.com"));
+        assert_eq!(identity.role, UserRole::Operator);
+    }
+
+    #[tokio::test]
+    async fn test_wrong_or_empty_password_is_rejected() {
+        let backend = backend(None);
+
+        assert!(
+            backend
+                .authenticate("alice", "wrong")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(backend.authenticate("alice", "").await.unwrap().is_none());
+        assert!(
+            backend
+                .authenticate("nobody", "pw")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_user_without_mapped_group() {
+        assert!(backend(None).authenticate("bob", "bob-pw").await.is_err());
+
+        let identity = backend(Some(UserRole::Viewer))
+            .authenticate("bob", "bob-pw")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(identity.role, UserRole::Viewer);
+    }
+
+    #[test]
+    fn test_username_is_escaped_in_filter() {
+        assert_eq!(backend(None).user_filter("*)(uid=*"), "(uid=\\2a\\29\\28uid=\\2a)");
+    }
+
+    /// (dn, password, attributes)
+    type StandInEntry = (&'static str, &'static str, Vec<(&'static str, &'static str)>);
+
+    /// Minimal LDAP server on a local port: simple bind, equality-filter search
+    /// and unbind, enough for [`Ldap3Directory`] to talk to it over the wire
+    struct StandInServer {
+        service: (&'static str, &'static str),
+        entries: Vec<StandInEntry>,
+        /// Base DN and attributes of every search
+        searches: std::sync::Mutex<Vec<(String, Vec<String>)>>,
+    }
+
+    impl StandInServer {
+        async fn start(self) -> (String, Arc<Self>) {
+            let server = Arc::new(self);
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("ldap://{}", listener.local_addr().unwrap());
+            let accepting = Arc::clone(&server);
+            tokio::spawn(async move {
+                while let Ok((socket, _)) = listener.accept().await {
+                    tokio::spawn(Arc::clone(&accepting).serve(socket));
+                }
+            });
+            (url, server)
+        }
+
+        async fn serve(self: Arc<Self>, mut socket: tokio::net::TcpStream) {
+            use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+            let mut buffer = Vec::new();
+            let mut chunk = [0u8; 4096];
+            loop {
+                while let Some((_, message, rest)) = ber_next(&buffer) {
+                    let consumed = buffer.len() - rest.len();
+                    let response = self.respond(message);
+                    buffer.drain(..consumed);
+                    match response {
+                        Some(response) => socket.write_all(&response).await.unwrap(),
+                        None => return,
+                    }
+                }
+                match socket.read(&mut chunk).await {
+                    Ok(0) | Err(_) => return,
+                    Ok(read) => buffer.extend_from_slice(&chunk[..read]),
+                }
+            }
+        }
+
+        /// Response to one LDAPMessage; `None` after an unbind
+        fn respond(&self, message: &[u8]) -> Option<Vec<u8>> {
+            let message = ber_items(message);
+            let (id, (operation, request)) = (message[0].1, message[1]);
+            let fields = ber_items(request);
+            match operation {
+                // BindRequest: version, name, [0] simple password
+                0x60 => {
+                    let (dn, password) = (text(fields[1].1), text(fields[2].1));
+                    let valid = (dn.as_str(), password.as_str()) == self.service
+                        || self.entries.iter().any(|(entry, pw, _)| {
+                            entry.eq_ignore_ascii_case(&dn) && *pw == password
+                        });
+                    Some(ldap_result(0x61, id, if valid { 0 } else { 49 }))
+                },
+                // SearchRequest: base, scope, deref, limits, typesOnly, filter, attributes
+                0x63 => {
+                    let base = text(fields[0].1);
+                    let requested: Vec<String> = ber_items(fields[7].1)
+                        .iter()
+                        .map(|(_, name)| text(name))
+                        .collect();
+                    self.searches
+                        .lock()
+                        .unwrap()
+                        .push((base.clone(), requested.clone()));
+
+                    // Only equality filters: (attribute=value)
+                    let (filter, assertion) = fields[6];
+                    assert_eq!(filter, 0xa3, "stand-in only understands equality filters");
+                    let assertion = ber_items(assertion);
+                    let (attribute, value) = (text(assertion[0].1), text(assertion[1].1));
+
+                    let mut response = Vec::new();
+                    for (dn, _, attributes) in &self.entries {
+                        let matches = dn.to_lowercase().ends_with(&base.to_lowercase())
+                            && attributes.iter().any(|(name, v)| {
+                                name.eq_ignore_ascii_case(&attribute)
+                                    && v.eq_ignore_ascii_case(&value)
+                            });
+                        if !matches {
+                            continue;
+                        }
+                        // Like a real server, return only the requested attributes
+                        let returned: Vec<u8> = attributes
+                            .iter()
+                            .filter(|(name, _)| {
+                                requested.iter().any(|r| r.eq_ignore_ascii_case(name))
+                            })
+                            .flat_map(|(name, v)| {
+                                let values = ber(0x31, &ber(0x04, v.as_bytes()));
+                                ber(0x30, &[ber(0x04, name.as_bytes()), values].concat())
+                            })
+                            .collect();
+                        let entry = [ber(0x04, dn.as_bytes()), ber(0x30, &returned)].concat();
+                        response.extend(ber(0x30, &[ber(0x02, id), ber(0x64, &entry)].concat()));
+                    }
+                    response.extend(ldap_result(0x65, id, 0));
+                    Some(response)
+                },
+                // UnbindRequest
+                0x42 => None,
+                other => panic!("unexpected LDAP operation {:#x}", other),
+            }
+        }
+    }
+
+    /// BER element with a short- or long-form length
+    fn ber(tag: u8, value: &[u8]) -> Vec<u8> {
+        let mut out = vec![tag];
+        if value.len() < 0x80 {
+            out.push(value.len() as u8);
+        } else {
+            let length = value.len().to_be_bytes();
+            let skip = length.iter().take_while(|b| **b == 0).count();
+            out.push(0x80 | (length.len() - skip) as u8);
+            out.extend_from_slice(&length[skip..]);
+        }
+        out.extend_from_slice(value);
+        out
+    }
+
+    /// First complete BER element: (tag, value, rest)
+    fn ber_next(data: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+        let (&tag, rest) = data.split_first()?;
+        let (&first, mut rest) = rest.split_first()?;
+        let length = if first < 0x80 {
+            first as usize
+        } else {
+            let count = (first & 0x7f) as usize;
+            let bytes = rest.get(..count)?;
+            rest = &rest[count..];
+            bytes.iter().fold(0, |length, b| length << 8 | *b as usize)
+        };
+        let value = rest.get(..length)?;
+        Some((tag, value, &rest[length..]))
+    }
+
+    /// Elements of a constructed value
+    fn ber_items(mut data: &[u8]) -> Vec<(u8, &[u8])> {
+        let mut items = Vec::new();
+        while let Some((tag, value, rest)) = ber_next(data) {
+            items.push((tag, value));
+            data = rest;
+        }
+        items
+    }
+
+    fn text(value: &[u8]) -> String {
+        String::from_utf8_lossy(value).into_owned()
+    }
+
+    /// LDAPResult with an empty matched DN and message
+    fn ldap_result(operation: u8, id: &[u8], code: u8) -> Vec<u8> {
+        let result = [ber(0x0a, &[code]), ber(0x04, b""), ber(0x04, b"")].concat();
+        ber(0x30, &[ber(0x02, id), ber(operation, &result)].concat())
+    }
+
+    fn stand_in() -> StandInServer {
+        StandInServer {
+            service: ("cn=reader,dc=example,dc=com", "reader-pw"),
+            entries: vec![
+                (
+                    "uid=alice,ou=people,dc=example,dc=com",
+                    "alice-pw",
+                    vec![
+                        ("uid", "alice"),
+                        ("entryUUID", "5f0c2a1e-0000-4000-8000-00000000a11c"),
+                        ("mail", "alice@example.com"),
+                        ("memberOf", "cn=dba,ou=groups,dc=example,dc=com"),
+                        ("description", "not requested"),
+                    ],
+                ),
+                (
+                    "uid=carol,ou=people,dc=example,dc=com",
+                    "carol-pw",
+                    vec![("uid", "carol"), ("memberOf", "cn=dba,ou=groups,dc=example,dc=com")],
+                ),
+                // Same uid outside the base DN
+                ("uid=alice,ou=robots,dc=other,dc=com", "robot-pw", vec![("uid", "alice")]),
+            ],
+            searches: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+
+    fn stand_in_config(url: String) -> LdapConfig {
+        LdapConfig {
+            enabled: true,
+            url,
+            bind_dn: "cn=reader,dc=example,dc=com".to_string(),
+            bind_password: "reader-pw".to_string(),
+            user_base_dn: "ou=people,dc=example,dc=com".to_string(),
+            group_roles: HashMap::from([("dba".to_string(), UserRole::Operator)]),
+            ..LdapConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ldap3_directory_against_stand_in_server() {
+        let (url, server) = stand_in().start().await;
+        let backend = LdapBackend::new(stand_in_config(url));
+
+        // Login names are matched and stored in lower case
+        let identity = backend
+            .authenticate(" Alice ", "alice-pw")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(identity.username, "alice");
+        assert_eq!(identity.external_id, "5f0c2a1e-0000-4000-8000-00000000a11c");
+        assert_eq!(identity.alternate_ids, vec!["uid=alice,ou=people,dc=example,dc=com"]);
+        assert_eq!(identity.email.as_deref(), Some("alice@example.com"));
+        assert_eq!(identity.groups, vec!["cn=dba,ou=groups,dc=example,dc=com"]);
+        assert_eq!(identity.role, UserRole::Operator);
+
+        let (base, attributes) = server.searches.lock().unwrap()[0].clone();
+        assert_eq!(base, "ou=people,dc=example,dc=com");
+        assert_eq!(attributes, vec!["entryUUID", "mail", "memberOf"]);
+
+        // Entries without the id attribute are keyed by their DN
+        let identity = backend
+            .authenticate("carol", "carol-pw")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(identity.external_id, "uid=carol,ou=people,dc=example,dc=com");
+        assert!(identity.alternate_ids.is_empty());
+
+        assert!(
+            backend
+                .authenticate("alice", "robot-pw")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            backend
+                .authenticate("alice", "wrong")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        // The escaped wildcard is a literal `*`, which matches nobody
+        assert!(
+            backend
+                .authenticate("*", "alice-pw")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ldap3_directory_reports_service_bind_failure() {
+        let (url, _server) = stand_in().start().await;
+        let config = LdapConfig { bind_password: "wrong".to_string(), ..stand_in_config(url) };
+
+        assert!(
+            LdapBackend::new(config)
+                .authenticate("alice", "alice-pw")
+                .await
+                .is_err()
+        );
+    }
+}
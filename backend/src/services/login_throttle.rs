@@ -0,0 +1,297 @@
+use axum::http::HeaderMap;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::SqlitePool;
+use std::net::IpAddr;
+
+use crate::config::LockoutConfig;
+use crate::utils::{ApiError, ApiResult};
+
+#[derive(sqlx::FromRow)]
+struct FailureRow {
+    failures: i64,
+    window_started_at: DateTime<Utc>,
+    lockouts: i64,
+    locked_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Scope {
+    User,
+    Ip,
+}
+
+impl Scope {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Ip => "ip",
+        }
+    }
+}
+
+/// Counts failed password logins per username and per client IP and locks them out
+///
+/// Counters live in SQLite so that lockouts survive restarts. Each lockout that
+/// follows the previous one without a quiet window doubles in length, up to
+/// `max_lockout_secs`.
+#[derive(Clone)]
+pub struct LoginThrottle {
+    pool: SqlitePool,
+    config: LockoutConfig,
+}
+
+impl LoginThrottle {
+    pub fn new(pool: SqlitePool, config: LockoutConfig) -> Self {
+        Self { pool, config }
+    }
+
+    /// Client IP used for the per-IP counter
+    pub fn client_ip(&self, headers: &HeaderMap, peer: Option<IpAddr>) -> Option<String> {
+        let forwarded = self
+            .config
+            .trust_forwarded_for
+            .then(|| headers.get("x-forwarded-for"))
+            .flatten()
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split(',').next())
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
+
+        forwarded.or_else(|| peer.map(|ip| ip.to_string()))
+    }
+
+    // Reject the attempt while the username or the client IP is locked
+    pub async fn check(&self, username: &str, client_ip: Option<&str>) -> ApiResult<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        let now = Utc::now();
+        for (scope, subject) in Self::subjects(username, client_ip) {
+            if let Some(row) = self.load(scope, &subject).await?
+                && let Some(locked_until) = row.locked_until.filter(|until| *until > now)
+            {
+                tracing::warn!(
+                    "Login for '{}' rejected: {} {} is locked until {}",
+                    username,
+                    scope.as_str(),
+                    subject,
+                    locked_until
+                );
+                return Err(ApiError::AccountLocked {
+                    retry_after_secs: (locked_until - now).num_seconds().max(1),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    // Count a failed attempt against the username and the client IP
+    pub async fn record_failure(&self, username: &str, client_ip: Option<&str>) -> ApiResult<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        for (scope, subject) in Self::subjects(username, client_ip) {
+            let max_failures = match scope {
+                Scope::User => self.config.max_failures_per_user,
+                Scope::Ip => self.config.max_failures_per_ip,
+            };
+            self.count_failure(scope, &subject, max_failures as i64)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    // Forget failures of a username after a successful login
+    //
+    // The per-IP counter is kept: one valid account must not reset an attack on others.
+    pub async fn record_success(&self, username: &str) -> ApiResult<()> {
+        self.clear(username).await
+    }
+
+    // Unlock a username (e.g. after an admin reset its password)
+    pub async fn clear(&self, username: &str) -> ApiResult<()> {
+        sqlx::query("DELETE FROM login_failures WHERE scope = ? AND subject = ?")
+            .bind(Scope::User.as_str())
+            .bind(Self::normalize(username))
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn count_failure(&self, scope: Scope, subject: &str, max_failures: i64) -> ApiResult<()> {
+        let now = Utc::now();
+        let window = Duration::seconds(self.config.window_secs as i64);
+
+        let (mut failures, mut window_started_at, mut lockouts, previous_lock) =
+            match self.load(scope, subject).await? {
+                Some(row) => (row.failures, row.window_started_at, row.lockouts, row.locked_until),
+                None => (0, now, 0, None),
+            };
+
+        if now - window_started_at > window {
+            // Escalation only continues while failures keep coming after a lockout ended
+            if previous_lock.is_none_or(|until| now - until > window) {
+                lockouts = 0;
+            }
+            failures = 0;
+            window_started_at = now;
+        }
+
+        failures += 1;
+        let mut locked_until = None;
+        if failures >= max_failures {
+            let duration = self.lockout_duration(lockouts);
+            locked_until = Some(now + duration);
+            lockouts += 1;
+            failures = 0;
+            window_started_at = now;
+            tracing::warn!(
+                "Too many failed logins for {} {}: locked for {}s",
+                scope.as_str(),
+                subject,
+                duration.num_seconds()
+            );
+        }
+
+        sqlx::query(
+            "INSERT INTO login_failures (scope, subject, failures, window_started_at, lockouts, locked_until) \
+             VALUES (?, ?, ?, ?, ?, ?) \
+             ON CONFLICT(scope, subject) DO UPDATE SET \
+                failures = excluded.failures, \
+                window_started_at = excluded.window_started_at, \
+                lockouts = excluded.lockouts, \
+                locked_until = COALESCE(excluded.locked_until, login_failures.locked_until)",
+        )
+        .bind(scope.as_str())
+        .bind(subject)
+        .bind(failures)
+        .bind(window_started_at)
+        .bind(lockouts)
+        .bind(locked_until)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Length of the next lockout after `lockouts` previous ones
+    fn lockout_duration(&self, lockouts: i64) -> Duration {
+        let base = self.config.lockout_secs;
+        let secs = base
+            .checked_shl(lockouts.clamp(0, 32) as u32)
+            .unwrap_or(u64::MAX)
+            .min(self.config.max_lockout_secs);
+        Duration::seconds(secs as i64)
+    }
+
+    async fn load(&self, scope: Scope, subject: &str) -> ApiResult<Option<FailureRow>> {
+        let row = sqlx::query_as(
+            "SELECT failures, window_started_at, lockouts, locked_until FROM login_failures \
+             WHERE scope = ? AND subject = ?",
+        )
+        .bind(scope.as_str())
+        .bind(subject)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    fn subjects(username: &str, client_ip: Option<&str>) -> Vec<(Scope, String)> {
+        let mut subjects = vec![(Scope::User, Self::normalize(username))];
+        if let Some(ip) = client_ip {
+            subjects.push((Scope::Ip, ip.to_string()));
+        }
+        subjects
+    }
+
+    fn normalize(username: &str) -> String {
+        username.trim().to_lowercase()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_pool;
+
+    fn config() -> LockoutConfig {
+        LockoutConfig {
+            max_failures_per_user: 3,
+            max_failures_per_ip: 5,
+            ..LockoutConfig::default()
+        }
+    }
+
+    async fn fail(throttle: &LoginThrottle, username: &str, ip: &str, times: usize) {
+        for _ in 0..times {
+            throttle.record_failure(username, Some(ip)).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_username_lockout_and_reset() {
+        let throttle = LoginThrottle::new(test_pool().await, config());
+
+        fail(&throttle, "Alice", "10.0.0.1", 2).await;
+        assert!(throttle.check("alice", None).await.is_ok());
+
+        throttle.record_success("alice").await.unwrap();
+        fail(&throttle, "alice", "10.0.0.1", 2).await;
+        assert!(throttle.check("alice", None).await.is_ok());
+
+        fail(&throttle, "alice", "10.0.0.1", 1).await;
+        match throttle.check("ALICE ", Some("10.0.0.2")).await {
+            Err(ApiError::AccountLocked { retry_after_secs }) => {
+                assert!(retry_after_secs > 0 && retry_after_secs <= 300)
+            },
+            other => panic!("expected lockout, got {:?}", other),
+        }
+        assert!(throttle.check("bob", Some("10.0.0.2")).await.is_ok());
+
+        throttle.clear("alice").await.unwrap();
+        assert!(throttle.check("alice", None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_ip_lockout_across_usernames() {
+        let throttle = LoginThrottle::new(test_pool().await, config());
+
+        for name in ["a", "b", "c", "d", "e"] {
+            fail(&throttle, name, "10.0.0.9", 1).await;
+        }
+
+        assert!(throttle.check("f", Some("10.0.0.9")).await.is_err());
+        assert!(throttle.check("f", Some("10.0.0.10")).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_lockout_backoff_is_capped() {
+        let throttle = LoginThrottle::new(test_pool().await, config());
+
+        assert_eq!(throttle.lockout_duration(0).num_seconds(), 300);
+        assert_eq!(throttle.lockout_duration(2).num_seconds(), 1200);
+        assert_eq!(throttle.lockout_duration(10).num_seconds(), 3600);
+        assert_eq!(throttle.lockout_duration(64).num_seconds(), 3600);
+    }
+
+    #[tokio::test]
+    async fn test_forwarded_for_only_when_trusted() {
+        let pool = sqlx::SqlitePool::connect_lazy("sqlite::memory:").unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", "203.0.113.7, 10.0.0.1".parse().unwrap());
+        let peer = Some("10.0.0.1".parse().unwrap());
+
+        let direct = LoginThrottle::new(pool.clone(), config());
+        assert_eq!(direct.client_ip(&headers, peer).as_deref(), Some("10.0.0.1"));
+
+        let proxied =
+            LoginThrottle::new(pool, LockoutConfig { trust_forwarded_for: true, ..config() });
+        assert_eq!(proxied.client_ip(&headers, peer).as_deref(), Some("203.0.113.7"));
+    }
+}
@@ -1,6 +1,6 @@
 use crate::models::MaterializedView;
 use crate::services::MySQLClient;
-use crate::utils::{ApiError, ApiResult};
+use crate::utils::{ApiError, ApiResult, sql};
 
 pub struct MaterializedViewService {
     mysql_client: MySQLClient,
@@ -43,9 +43,12 @@ impl MaterializedViewService {
 
                 let task = tokio::spawn(async move {
                     let mut mvs = Vec::new();
+                    let Ok(quoted_db) = sql::quote_identifier(&db) else {
+                        return mvs;
+                    };
 
                     // Fetch async MVs
-                    let sql_async = format!("SHOW MATERIALIZED VIEWS FROM `{}`", db);
+                    let sql_async = format!("SHOW MATERIALIZED VIEWS FROM {}", quoted_db);
                     if let Ok(results) = mysql_client.query(&sql_async).await
                         && let Ok(async_mvs) = Self::parse_async_mv_results(results, &db)
                     {
@@ -53,7 +56,7 @@ impl MaterializedViewService {
                     }
 
                     // Fetch sync MVs
-                    let sql_sync = format!("SHOW ALTER MATERIALIZED VIEW FROM `{}`", db);
+                    let sql_sync = format!("SHOW ALTER MATERIALIZED VIEW FROM {}", quoted_db);
                     if let Ok(results) = mysql_client.query(&sql_sync).await
                         && let Ok(sync_mvs) = Self::parse_sync_mv_results(results, &db)
                     {
@@ -109,7 +112,10 @@ impl MaterializedViewService {
         let mv = self.get_materialized_view(mv_name).await?;
 
         // Use the database name to query DDL
-        let sql = format!("SHOW CREATE MATERIALIZED VIEW `{}`.`{}`", mv.database_name, mv_name);
+        let sql = format!(
+            "SHOW CREATE MATERIALIZED VIEW {}",
+            qualified_name(&mv.database_name, mv_name)?
+        );
         tracing::info!("Querying materialized view DDL: {}", sql);
 
         let results = self.mysql_client.query(&sql).await?;
@@ -126,6 +132,14 @@ impl MaterializedViewService {
 
     /// Create a materialized view
     pub async fn create_materialized_view(&self, sql: &str) -> ApiResult<()> {
+        sql::ensure_single_statement(sql)?;
+        let class = sql::classify(sql)?;
+        if class.kind != "CREATE MATERIALIZED VIEW" {
+            return Err(ApiError::validation_error(format!(
+                "Expected CREATE MATERIALIZED VIEW, found {}",
+                class.kind
+            )));
+        }
         tracing::info!("Creating materialized view with SQL: {}", sql);
         self.mysql_client.execute(sql).await?;
         Ok(())
@@ -146,13 +160,13 @@ impl MaterializedViewService {
 
         let sql = if let Some(db) = database {
             if if_exists {
-                format!("DROP MATERIALIZED VIEW IF EXISTS `{}`.`{}`", db, mv_name)
+                format!("DROP MATERIALIZED VIEW IF EXISTS {}", qualified_name(&db, mv_name)?)
             } else {
-                format!("DROP MATERIALIZED VIEW `{}`.`{}`", db, mv_name)
+                format!("DROP MATERIALIZED VIEW {}", qualified_name(&db, mv_name)?)
             }
         } else {
             // Fallback for IF EXISTS when MV not found
-            format!("DROP MATERIALIZED VIEW IF EXISTS `{}`", mv_name)
+            format!("DROP MATERIALIZED VIEW IF EXISTS {}", sql::quote_identifier(mv_name)?)
         };
 
         tracing::info!("Dropping materialized view: {}", sql);
@@ -172,11 +186,22 @@ impl MaterializedViewService {
         // First, find which database the MV belongs to
         let mv = self.get_materialized_view(mv_name).await?;
 
+        let mode = match mode.to_uppercase().as_str() {
+            "ASYNC" => "ASYNC",
+            "SYNC" => "SYNC",
+            _ => return Err(ApiError::validation_error("Invalid mode. Must be ASYNC or SYNC")),
+        };
+
         // Build SQL with database name
-        let mut sql = format!("REFRESH MATERIALIZED VIEW `{}`.`{}`", mv.database_name, mv_name);
+        let mut sql =
+            format!("REFRESH MATERIALIZED VIEW {}", qualified_name(&mv.database_name, mv_name)?);
 
         if let (Some(start), Some(end)) = (partition_start, partition_end) {
-            sql.push_str(&format!(" PARTITION START ('{}') END ('{}')", start, end));
+            sql.push_str(&format!(
+                " PARTITION START ({}) END ({})",
+                sql::quote_literal(start),
+                sql::quote_literal(end)
+            ));
         }
 
         if force {
@@ -199,10 +224,11 @@ impl MaterializedViewService {
         // First, find which database the MV belongs to
         let mv = self.get_materialized_view(mv_name).await?;
 
+        let name = qualified_name(&mv.database_name, mv_name)?;
         let sql = if force {
-            format!("CANCEL REFRESH MATERIALIZED VIEW `{}`.`{}` FORCE", mv.database_name, mv_name)
+            format!("CANCEL REFRESH MATERIALIZED VIEW {} FORCE", name)
         } else {
-            format!("CANCEL REFRESH MATERIALIZED VIEW `{}`.`{}`", mv.database_name, mv_name)
+            format!("CANCEL REFRESH MATERIALIZED VIEW {}", name)
         };
         tracing::info!("Cancelling refresh for materialized view: {}", sql);
         self.mysql_client.execute(&sql).await?;
@@ -219,9 +245,12 @@ impl MaterializedViewService {
         let mv = self.get_materialized_view(mv_name).await?;
 
         let sql = format!(
-            "ALTER MATERIALIZED VIEW `{}`.`{}` {}",
-            mv.database_name, mv_name, alter_clause
+            "ALTER MATERIALIZED VIEW {} {}",
+            qualified_name(&mv.database_name, mv_name)?,
+            alter_clause
         );
+        // The clause is free text: it must not end the statement and start another
+        sql::ensure_single_statement(&sql)?;
         tracing::info!("Altering materialized view: {}", sql);
         self.mysql_client.execute(&sql).await?;
         Ok(())
@@ -252,7 +281,7 @@ impl MaterializedViewService {
 
     /// Get async materialized views from a specific database
     async fn get_async_mvs_from_db(&self, database: &str) -> ApiResult<Vec<MaterializedView>> {
-        let sql = format!("SHOW MATERIALIZED VIEWS FROM `{}`", database);
+        let sql = format!("SHOW MATERIALIZED VIEWS FROM {}", sql::quote_identifier(database)?);
         tracing::debug!("Querying async MVs: {}", sql);
 
         let results = self.mysql_client.query(&sql).await?;
@@ -261,7 +290,7 @@ impl MaterializedViewService {
 
     /// Get sync materialized views (ROLLUP) from a specific database
     async fn get_sync_mvs_from_db(&self, database: &str) -> ApiResult<Vec<MaterializedView>> {
-        let sql = format!("SHOW ALTER MATERIALIZED VIEW FROM `{}`", database);
+        let sql = format!("SHOW ALTER MATERIALIZED VIEW FROM {}", sql::quote_identifier(database)?);
         tracing::debug!("Querying sync MVs: {}", sql);
 
         let results = self.mysql_client.query(&sql).await?;
@@ -400,3 +429,8 @@ impl MaterializedViewService {
         Ok(mvs)
     }
 }
+
+/// `db`.`name` of a materialized view
+fn qualified_name(database: &str, mv_name: &str) -> ApiResult<String> {
+    Ok(format!("{}.{}", sql::quote_identifier(database)?, sql::quote_identifier(mv_name)?))
+}
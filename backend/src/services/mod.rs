@@ -1,23 +1,53 @@
+pub mod admin_audit_service;
+pub mod api_token_service;
+pub mod auth_backend;
 pub mod auth_service;
+pub mod cluster_diagnostics_service;
+pub mod cluster_group_service;
 pub mod cluster_service;
+pub mod config_bundle_service;
 pub mod data_statistics_service;
+pub mod fe_registry;
+pub mod ldap_backend;
+pub mod login_throttle;
 pub mod materialized_view_service;
 pub mod metrics_collector_service;
 pub mod mysql_client;
 pub mod mysql_pool_manager;
+pub mod oidc_provider;
 pub mod overview_service;
+pub mod password_policy;
+pub mod permission_service;
+pub mod query_cache;
+pub mod query_job_service;
+pub mod query_plan_service;
+pub mod query_stream_service;
+pub mod result_export;
+pub mod session_service;
+pub mod sql_guard;
+pub mod sql_script_service;
 pub mod starrocks_client;
 pub mod system_function_service;
+pub mod topology_service;
 
+pub use admin_audit_service::AdminAuditService;
+pub use api_token_service::ApiTokenService;
 pub use auth_service::AuthService;
+pub use cluster_diagnostics_service::ClusterDiagnosticsService;
+pub use cluster_group_service::ClusterGroupService;
 pub use cluster_service::ClusterService;
+pub use config_bundle_service::ConfigBundleService;
 pub use data_statistics_service::{
     DataStatistics, DataStatisticsService, TopTableByAccess, TopTableBySize,
 };
+pub use fe_registry::FeRegistry;
+pub use ldap_backend::LdapBackend;
+pub use login_throttle::LoginThrottle;
 pub use materialized_view_service::MaterializedViewService;
 pub use metrics_collector_service::{MetricsCollectorService, MetricsSnapshot};
 pub use mysql_client::MySQLClient;
-pub use mysql_pool_manager::MySQLPoolManager;
+pub use mysql_pool_manager::{EffectivePoolSettings, MySQLPoolManager, PoolStatus};
+pub use oidc_provider::OidcProvider;
 pub use overview_service::{
     Alert, AlertLevel, BECompactionScore, CapacityPrediction, ClusterHealth, ClusterOverview,
     CompactionDetailStats, CompactionDurationStats, CompactionStats, CompactionTaskStats,
@@ -26,5 +56,15 @@ pub use overview_service::{
     ResourceTrends, RunningQuery, SchemaChangeStats, SessionStats, TimeRange, TopPartitionByScore,
     TransactionStats,
 };
+pub use password_policy::PasswordPolicy;
+pub use permission_service::PermissionService;
+pub use query_cache::{QueryCache, QueryCacheStats};
+pub use query_job_service::QueryJobService;
+pub use query_plan_service::QueryPlanService;
+pub use query_stream_service::QueryStreamService;
+pub use session_service::SessionService;
+pub use sql_guard::SqlGuard;
+pub use sql_script_service::SqlScriptService;
 pub use starrocks_client::StarRocksClient;
 pub use system_function_service::SystemFunctionService;
+pub use topology_service::TopologyService;
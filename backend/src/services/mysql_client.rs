@@ -1,15 +1,21 @@
+use crate::models::ResultColumn;
+use crate::services::mysql_pool_manager::ClusterPool;
 use crate::utils::error::ApiError;
-use mysql_async::{Pool, prelude::Queryable};
-use std::sync::Arc;
+use crate::utils::sql;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use mysql_async::consts::{ColumnFlags, ColumnType};
+use mysql_async::prelude::Queryable;
+use mysql_async::{Column, Value};
 
 #[derive(Clone)]
 pub struct MySQLClient {
-    pool: Arc<Pool>,
+    pool: ClusterPool,
 }
 
 impl MySQLClient {
-    pub fn from_pool(pool: Pool) -> Self {
-        Self { pool: Arc::new(pool) }
+    pub fn from_pool(pool: impl Into<ClusterPool>) -> Self {
+        Self { pool: pool.into() }
     }
 
     /// Execute a query and return results as (column_names, rows)
@@ -26,39 +32,7 @@ impl MySQLClient {
             ApiError::cluster_connection_failed(format!("Failed to get connection: {}", e))
         })?;
 
-        // First, set catalog if provided (on the same connection)
-        // Note: StarRocks may not support USE CATALOG via MySQL protocol
-        // If catalog is the default catalog or USE CATALOG fails, we'll continue anyway
-        if let Some(cat) = catalog {
-            if !cat.is_empty() && cat != "default_catalog" {
-                // Try USE CATALOG without backticks (StarRocks syntax)
-                let use_catalog_sql = format!("USE CATALOG {}", cat);
-                tracing::debug!("Executing USE CATALOG on same connection: {}", use_catalog_sql);
-                if let Err(e) = conn.query::<mysql_async::Row, _>(&use_catalog_sql).await {
-                    // If USE CATALOG fails (not supported or already active), continue with query
-                    // The default catalog is usually already active
-                    tracing::warn!("USE CATALOG {} failed (may not be supported): {}, continuing anyway", cat, e);
-                    // Don't fail - continue with the query, it might work in the default catalog context
-                }
-            }
-            // If catalog is "default_catalog" or empty, no need to switch
-        }
-
-        // Then, set database if provided (on the same connection)
-        if let Some(db) = database {
-            if !db.is_empty() {
-                let use_db_sql = format!("USE {}", db);
-                tracing::debug!("Executing USE DATABASE on same connection: {}", use_db_sql);
-                if let Err(e) = conn.query::<mysql_async::Row, _>(&use_db_sql).await {
-                    tracing::warn!("Failed to execute USE DATABASE {}: {}", db, e);
-                    drop(conn);
-                    return Err(ApiError::internal_error(format!(
-                        "Failed to switch to database {}: {}",
-                        db, e
-                    )));
-                }
-            }
-        }
+        use_context(&mut conn, catalog, database).await?;
 
         tracing::debug!("Executing MySQL query: '{}'", sql);
         let rows: Vec<mysql_async::Row> = conn.query(sql).await.map_err(|e| {
@@ -108,6 +82,46 @@ impl MySQLClient {
         Ok((columns, result_rows))
     }
 
+    /// Execute a query and return column metadata and JSON-typed rows
+    ///
+    /// Unlike `query_raw` the columns are known even when no row is returned.
+    pub async fn query_typed(
+        &self,
+        sql: &str,
+        catalog: Option<&str>,
+        database: Option<&str>,
+    ) -> Result<(Vec<ResultColumn>, Vec<Vec<serde_json::Value>>), ApiError> {
+        let mut conn = self.pool.get_conn().await.map_err(|e| {
+            tracing::error!("Failed to get connection from pool: {}", e);
+            ApiError::cluster_connection_failed(format!("Failed to get connection: {}", e))
+        })?;
+        use_context(&mut conn, catalog, database).await?;
+
+        tracing::debug!("Executing typed MySQL query: '{}'", sql);
+        let sql_error = |e: mysql_async::Error| {
+            tracing::error!("MySQL query execution failed: {}", e);
+            ApiError::internal_error(format!("SQL execution failed: {}", e))
+        };
+        let result = conn.query_iter(sql).await.map_err(sql_error)?;
+        let columns = result.columns().unwrap_or_else(|| Vec::new().into());
+        let rows: Vec<mysql_async::Row> = result.collect_and_drop().await.map_err(sql_error)?;
+
+        let rows = rows
+            .iter()
+            .map(|row| {
+                columns
+                    .iter()
+                    .enumerate()
+                    .map(|(i, column)| {
+                        row.as_ref(i)
+                            .map_or(serde_json::Value::Null, |v| value_to_json(v, column))
+                    })
+                    .collect()
+            })
+            .collect();
+        Ok((columns.iter().map(result_column).collect(), rows))
+    }
+
     /// Execute a query and return results as Vec<serde_json::Value> (JSON objects)
     /// Each row is a JSON object with column names as keys
     pub async fn query(&self, sql: &str) -> Result<Vec<serde_json::Value>, ApiError> {
@@ -149,9 +163,201 @@ impl MySQLClient {
     }
 }
 
+/// Switch `conn` to the catalog and database a query should run in
+pub(crate) async fn use_context(
+    conn: &mut mysql_async::Conn,
+    catalog: Option<&str>,
+    database: Option<&str>,
+) -> Result<(), ApiError> {
+    // First, set catalog if provided (on the same connection)
+    // Note: StarRocks may not support USE CATALOG via MySQL protocol
+    // If catalog is the default catalog or USE CATALOG fails, we'll continue anyway
+    if let Some(cat) = catalog {
+        if !cat.is_empty() && cat != "default_catalog" {
+            let use_catalog_sql = sql::use_catalog(cat)?;
+            tracing::debug!("Executing USE CATALOG on same connection: {}", use_catalog_sql);
+            if let Err(e) = conn.query::<mysql_async::Row, _>(&use_catalog_sql).await {
+                // If USE CATALOG fails (not supported or already active), continue with query
+                // The default catalog is usually already active
+                tracing::warn!(
+                    "USE CATALOG {} failed (may not be supported): {}, continuing anyway",
+                    cat,
+                    e
+                );
+                // Don't fail - continue with the query, it might work in the default catalog context
+            }
+        }
+        // If catalog is "default_catalog" or empty, no need to switch
+    }
+
+    // Then, set database if provided (on the same connection)
+    if let Some(db) = database {
+        if !db.is_empty() {
+            let use_db_sql = sql::use_database(db)?;
+            tracing::debug!("Executing USE DATABASE on same connection: {}", use_db_sql);
+            if let Err(e) = conn.query::<mysql_async::Row, _>(&use_db_sql).await {
+                tracing::warn!("Failed to execute USE DATABASE {}: {}", db, e);
+                return Err(ApiError::internal_error(format!(
+                    "Failed to switch to database {}: {}",
+                    db, e
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Largest integer a JavaScript number holds exactly (2^53 - 1)
+const MAX_SAFE_INTEGER: i128 = 9_007_199_254_740_991;
+
+/// Name, type and nullability of a result column
+pub(crate) fn result_column(column: &Column) -> ResultColumn {
+    ResultColumn {
+        name: column.name_str().to_string(),
+        data_type: type_name(column).to_string(),
+        nullable: !column.flags().contains(ColumnFlags::NOT_NULL_FLAG),
+    }
+}
+
+// StarRocks reports BOOLEAN as TINYINT(1)
+fn is_boolean(column: &Column) -> bool {
+    matches!(column.column_type(), ColumnType::MYSQL_TYPE_TINY | ColumnType::MYSQL_TYPE_BIT)
+        && column.column_length() == 1
+}
+
+// String and blob types with the `binary` character set carry raw bytes
+fn is_binary(column: &Column) -> bool {
+    use ColumnType::*;
+    column.character_set() == 63
+        && matches!(
+            column.column_type(),
+            MYSQL_TYPE_STRING
+                | MYSQL_TYPE_VARCHAR
+                | MYSQL_TYPE_VAR_STRING
+                | MYSQL_TYPE_TINY_BLOB
+                | MYSQL_TYPE_MEDIUM_BLOB
+                | MYSQL_TYPE_LONG_BLOB
+                | MYSQL_TYPE_BLOB
+        )
+}
+
+fn type_name(column: &Column) -> &'static str {
+    use ColumnType::*;
+    if is_boolean(column) {
+        return "BOOLEAN";
+    }
+    let binary = is_binary(column);
+    match column.column_type() {
+        MYSQL_TYPE_TINY => "TINYINT",
+        MYSQL_TYPE_SHORT => "SMALLINT",
+        MYSQL_TYPE_INT24 => "MEDIUMINT",
+        MYSQL_TYPE_LONG => "INT",
+        MYSQL_TYPE_LONGLONG => "BIGINT",
+        MYSQL_TYPE_YEAR => "YEAR",
+        MYSQL_TYPE_FLOAT => "FLOAT",
+        MYSQL_TYPE_DOUBLE => "DOUBLE",
+        MYSQL_TYPE_DECIMAL | MYSQL_TYPE_NEWDECIMAL => "DECIMAL",
+        MYSQL_TYPE_DATE | MYSQL_TYPE_NEWDATE => "DATE",
+        MYSQL_TYPE_DATETIME | MYSQL_TYPE_DATETIME2 => "DATETIME",
+        MYSQL_TYPE_TIMESTAMP | MYSQL_TYPE_TIMESTAMP2 => "TIMESTAMP",
+        MYSQL_TYPE_TIME | MYSQL_TYPE_TIME2 => "TIME",
+        MYSQL_TYPE_JSON => "JSON",
+        MYSQL_TYPE_BIT => "BIT",
+        MYSQL_TYPE_NULL => "NULL",
+        MYSQL_TYPE_STRING if binary => "BINARY",
+        MYSQL_TYPE_STRING => "CHAR",
+        MYSQL_TYPE_VARCHAR | MYSQL_TYPE_VAR_STRING if binary => "VARBINARY",
+        MYSQL_TYPE_VARCHAR | MYSQL_TYPE_VAR_STRING => "VARCHAR",
+        MYSQL_TYPE_TINY_BLOB | MYSQL_TYPE_MEDIUM_BLOB | MYSQL_TYPE_LONG_BLOB | MYSQL_TYPE_BLOB
+            if binary =>
+        {
+            "BLOB"
+        },
+        MYSQL_TYPE_TINY_BLOB | MYSQL_TYPE_MEDIUM_BLOB | MYSQL_TYPE_LONG_BLOB | MYSQL_TYPE_BLOB => {
+            "TEXT"
+        },
+        MYSQL_TYPE_ENUM => "ENUM",
+        MYSQL_TYPE_SET => "SET",
+        MYSQL_TYPE_GEOMETRY => "GEOMETRY",
+        _ => "UNKNOWN",
+    }
+}
+
+fn integer_to_json(value: i128) -> serde_json::Value {
+    if value.abs() <= MAX_SAFE_INTEGER {
+        serde_json::Value::from(value as i64)
+    } else {
+        serde_json::Value::String(value.to_string())
+    }
+}
+
+fn float_to_json(value: f64) -> serde_json::Value {
+    serde_json::Number::from_f64(value)
+        .map(serde_json::Value::Number)
+        .unwrap_or_else(|| serde_json::Value::String(value.to_string()))
+}
+
+/// JSON-native form of a value of `column`
+///
+/// NULL becomes `null`, numbers and booleans become JSON numbers and booleans,
+/// date/times ISO 8601 strings and binary columns base64. DECIMALs and integers
+/// beyond 2^53 stay strings so that no digits are lost.
+pub(crate) fn value_to_json(value: &Value, column: &Column) -> serde_json::Value {
+    use ColumnType::*;
+    use serde_json::Value as Json;
+    match value {
+        Value::NULL => Json::Null,
+        Value::Int(i) if is_boolean(column) => Json::Bool(*i != 0),
+        Value::UInt(u) if is_boolean(column) => Json::Bool(*u != 0),
+        Value::Int(i) => integer_to_json(*i as i128),
+        Value::UInt(u) => integer_to_json(*u as i128),
+        Value::Float(f) => float_to_json(*f as f64),
+        Value::Double(d) => float_to_json(*d),
+        Value::Date(year, month, day, hour, minute, second, micros) => {
+            let date = format!("{:04}-{:02}-{:02}", year, month, day);
+            Json::String(match column.column_type() {
+                MYSQL_TYPE_DATE | MYSQL_TYPE_NEWDATE => date,
+                _ if *micros > 0 => {
+                    format!("{}T{:02}:{:02}:{:02}.{:06}", date, hour, minute, second, micros)
+                },
+                _ => format!("{}T{:02}:{:02}:{:02}", date, hour, minute, second),
+            })
+        },
+        Value::Time(..) => Json::String(value_to_string(value, 0, 0)),
+        Value::Bytes(bytes) if is_binary(column) => Json::String(BASE64.encode(bytes)),
+        Value::Bytes(bytes) => {
+            let text = String::from_utf8_lossy(bytes);
+            let typed = match column.column_type() {
+                _ if is_boolean(column) => match text.as_ref() {
+                    "1" | "true" => Some(Json::Bool(true)),
+                    "0" | "false" => Some(Json::Bool(false)),
+                    _ => None,
+                },
+                MYSQL_TYPE_TINY | MYSQL_TYPE_SHORT | MYSQL_TYPE_INT24 | MYSQL_TYPE_LONG
+                | MYSQL_TYPE_LONGLONG | MYSQL_TYPE_YEAR => {
+                    text.parse::<i128>().ok().map(integer_to_json)
+                },
+                MYSQL_TYPE_FLOAT | MYSQL_TYPE_DOUBLE => text.parse::<f64>().ok().map(float_to_json),
+                MYSQL_TYPE_DATETIME
+                | MYSQL_TYPE_DATETIME2
+                | MYSQL_TYPE_TIMESTAMP
+                | MYSQL_TYPE_TIMESTAMP2 => Some(Json::String(text.replacen(' ', "T", 1))),
+                MYSQL_TYPE_JSON => serde_json::from_str(&text).ok(),
+                _ => None,
+            };
+            typed.unwrap_or_else(|| Json::String(text.into_owned()))
+        },
+    }
+}
+
 // Convert mysql_async::Value directly to String without using FromValue trait
 // This avoids panic when encountering NULL values
-fn value_to_string(value: &mysql_async::Value, row_idx: usize, col_idx: usize) -> String {
+pub(crate) fn value_to_string(
+    value: &mysql_async::Value,
+    row_idx: usize,
+    col_idx: usize,
+) -> String {
     match value {
         mysql_async::Value::NULL => {
             tracing::debug!("Row {}, Column {}: NULL value", row_idx, col_idx);
@@ -205,7 +411,7 @@ fn value_to_string(value: &mysql_async::Value, row_idx: usize, col_idx: usize) -
 #[cfg(test)]
 mod tests {
     use super::*;
-    use mysql_async::OptsBuilder;
+    use mysql_async::{OptsBuilder, Pool};
 
     async fn get_test_cluster() -> crate::models::Cluster {
         let db_url = std::env::var("DATABASE_URL")
@@ -228,7 +434,7 @@ mod tests {
             .ip_or_hostname(&cluster.fe_host)
             .tcp_port(cluster.fe_query_port as u16)
             .user(Some(&cluster.username))
-            .pass(Some(&cluster.password_encrypted))
+            .pass(Some(cluster.password().unwrap()))
             .db_name(None::<String>)
             .prefer_socket(false)
             .pool_opts(
@@ -308,4 +514,57 @@ mod tests {
             },
         }
     }
+
+    fn column(column_type: ColumnType) -> Column {
+        Column::new(column_type)
+            .with_name(b"c")
+            .with_character_set(33)
+    }
+
+    #[test]
+    fn test_value_to_json() {
+        use ColumnType::*;
+        use serde_json::json;
+        let text = |text: &str, column_type| {
+            value_to_json(&Value::Bytes(text.as_bytes().to_vec()), &column(column_type))
+        };
+
+        assert_eq!(value_to_json(&Value::NULL, &column(MYSQL_TYPE_LONGLONG)), json!(null));
+        assert_eq!(text("NULL", MYSQL_TYPE_VAR_STRING), json!("NULL"));
+        assert_eq!(text("-42", MYSQL_TYPE_LONGLONG), json!(-42));
+        assert_eq!(text("9007199254740993", MYSQL_TYPE_LONGLONG), json!("9007199254740993"));
+        assert_eq!(text("1.5", MYSQL_TYPE_DOUBLE), json!(1.5));
+        assert_eq!(text("nan", MYSQL_TYPE_DOUBLE), json!("NaN"));
+        assert_eq!(text("12.30", MYSQL_TYPE_NEWDECIMAL), json!("12.30"));
+        assert_eq!(text("2026-10-18 08:30:00", MYSQL_TYPE_DATETIME), json!("2026-10-18T08:30:00"));
+        assert_eq!(text(r#"{"a": [1]}"#, MYSQL_TYPE_JSON), json!({"a": [1]}));
+        assert_eq!(
+            value_to_json(&Value::Date(2026, 1, 2, 3, 4, 5, 6), &column(MYSQL_TYPE_TIMESTAMP)),
+            json!("2026-01-02T03:04:05.000006")
+        );
+
+        let boolean = column(MYSQL_TYPE_TINY).with_column_length(1);
+        assert_eq!(value_to_json(&Value::Bytes(b"1".to_vec()), &boolean), json!(true));
+        assert_eq!(result_column(&boolean).data_type, "BOOLEAN");
+
+        let binary = column(MYSQL_TYPE_BLOB).with_character_set(63);
+        assert_eq!(value_to_json(&Value::Bytes(vec![0, 255]), &binary), json!("AP8="));
+        assert_eq!(result_column(&binary).data_type, "BLOB");
+    }
+
+    #[test]
+    fn test_result_column() {
+        let nullable = column(ColumnType::MYSQL_TYPE_VAR_STRING);
+        assert_eq!(
+            result_column(&nullable),
+            ResultColumn {
+                name: "c".to_string(),
+                data_type: "VARCHAR".to_string(),
+                nullable: true
+            }
+        );
+        let required = column(ColumnType::MYSQL_TYPE_LONG).with_flags(ColumnFlags::NOT_NULL_FLAG);
+        assert_eq!(result_column(&required).data_type, "INT");
+        assert!(!result_column(&required).nullable);
+    }
 }
@@ -1,8 +1,21 @@
-use crate::models::cluster::Cluster;
-use crate::utils::error::ApiResult;
+use crate::config::MySQLPoolConfig;
+use crate::models::cluster::{Cluster, FeEndpoint};
+use crate::services::FeRegistry;
+use crate::utils::error::{ApiError, ApiResult};
+use crate::utils::scheduled_executor::ScheduledTask;
+use chrono::{DateTime, Utc};
 use dashmap::DashMap;
-use mysql_async::{OptsBuilder, Pool, SslOpts};
-use std::sync::Arc;
+use mysql_async::{ClientIdentity, Conn, OptsBuilder, Pool, SslOpts};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
 
 /// Manager for MySQL connection pools using mysql_async with DashMap
 ///
@@ -10,58 +23,373 @@ use std::sync::Arc;
 /// Maintains a pool for each cluster to avoid reconnecting on every query.
 ///
 /// Performance: 3-5x better than RwLock<HashMap> under high concurrency.
+///
+/// Failover: a pool is bound to one FE of the cluster. It is replaced by a pool on
+/// another FE once its FE is marked down in the `FeRegistry` or is no longer part of
+/// the cluster configuration.
+///
+/// Lifecycle: pool limits come from `[mysql_pool]`, overridden per cluster. A pool is
+/// rebuilt when the cluster's credentials, TLS or pool settings change, and closed by
+/// `evict_idle_pools` once the cluster has not been used for a while.
 #[derive(Clone)]
 pub struct MySQLPoolManager {
-    pools: Arc<DashMap<i64, Pool>>,
+    pools: Arc<DashMap<i64, PoolEntry>>,
+    defaults: MySQLPoolConfig,
+}
+
+struct PoolEntry {
+    cluster_name: String,
+    endpoint: FeEndpoint,
+    /// Connection settings the pool was built with, see `connection_fingerprint`
+    fingerprint: u64,
+    settings: EffectivePoolSettings,
+    pool: ClusterPool,
+}
+
+/// Pool limits in effect for one cluster
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, ToSchema)]
+pub struct EffectivePoolSettings {
+    pub min_connections: usize,
+    pub max_connections: usize,
+    pub idle_timeout_secs: u64,
+    pub max_lifetime_secs: u64,
+    /// The cluster's `connection_timeout`; bounds connecting and waiting for a free connection
+    pub connect_timeout_secs: u64,
+}
+
+impl EffectivePoolSettings {
+    /// The cluster's overrides on top of the configured defaults
+    pub fn resolve(defaults: &MySQLPoolConfig, cluster: &Cluster) -> Self {
+        let overrides = cluster.pool_settings();
+        let max_connections = overrides
+            .max_connections
+            .unwrap_or(defaults.max_connections)
+            .max(1);
+
+        Self {
+            min_connections: overrides
+                .min_connections
+                .unwrap_or(defaults.min_connections)
+                .min(max_connections),
+            max_connections,
+            idle_timeout_secs: overrides
+                .idle_timeout_secs
+                .unwrap_or(defaults.idle_timeout_secs),
+            max_lifetime_secs: overrides
+                .max_lifetime_secs
+                .unwrap_or(defaults.max_lifetime_secs),
+            connect_timeout_secs: cluster.connection_timeout.max(1) as u64,
+        }
+    }
+}
+
+/// Connection pool statistics of one cluster, as returned by `/api/system/pools`
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PoolStatus {
+    pub cluster_id: i64,
+    pub cluster_name: String,
+    /// FE the pool connects to (`host:query_port`)
+    pub fe: String,
+    /// Connections currently checked out
+    pub active_connections: usize,
+    /// Open connections waiting in the pool (estimated from returns and the idle timeout)
+    pub idle_connections: usize,
+    /// Requests waiting for a free connection
+    pub waiting_requests: usize,
+    pub total_acquired: u64,
+    pub acquire_errors: u64,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: DateTime<Utc>,
+    pub settings: EffectivePoolSettings,
+}
+
+/// A cluster's mysql_async pool, recording how its connections are used
+///
+/// mysql_async does not expose pool internals, so connections are counted as they
+/// are checked out through `get_conn` and returned by dropping the `PooledConn`.
+#[derive(Clone)]
+pub struct ClusterPool {
+    pool: Pool,
+    stats: Arc<PoolStats>,
+    settings: Option<EffectivePoolSettings>,
+}
+
+impl ClusterPool {
+    /// Check out a connection, waiting at most the cluster's connection timeout
+    pub async fn get_conn(&self) -> mysql_async::Result<PooledConn> {
+        let waiting = WaitingGuard::new(&self.stats.waiting);
+        let result = match &self.settings {
+            Some(settings) => {
+                let timeout = Duration::from_secs(settings.connect_timeout_secs);
+                tokio::time::timeout(timeout, self.pool.get_conn())
+                    .await
+                    .unwrap_or_else(|_| {
+                        Err(std::io::Error::new(
+                            std::io::ErrorKind::TimedOut,
+                            format!("no connection available within {}s", timeout.as_secs()),
+                        )
+                        .into())
+                    })
+            },
+            None => self.pool.get_conn().await,
+        };
+        drop(waiting);
+
+        match result {
+            Ok(conn) => {
+                self.stats.acquired(conn.id());
+                Ok(PooledConn { conn, stats: Arc::clone(&self.stats) })
+            },
+            Err(e) => {
+                self.stats.acquire_errors.fetch_add(1, Ordering::Relaxed);
+                Err(e)
+            },
+        }
+    }
+
+    /// Close all connections of the pool
+    pub async fn disconnect(self) -> mysql_async::Result<()> {
+        self.pool.disconnect().await
+    }
+
+    fn is_busy(&self) -> bool {
+        self.stats.in_use.load(Ordering::Relaxed) > 0
+            || self.stats.waiting.load(Ordering::Relaxed) > 0
+    }
+}
+
+/// Pools created outside the manager (e.g. in tests) have no limits of their own
+impl From<Pool> for ClusterPool {
+    fn from(pool: Pool) -> Self {
+        Self { pool, stats: Arc::new(PoolStats::new()), settings: None }
+    }
+}
+
+/// Connection checked out of a `ClusterPool`; goes back to the pool when dropped
+pub struct PooledConn {
+    conn: Conn,
+    stats: Arc<PoolStats>,
+}
+
+impl Deref for PooledConn {
+    type Target = Conn;
+
+    fn deref(&self) -> &Conn {
+        &self.conn
+    }
+}
+
+impl DerefMut for PooledConn {
+    fn deref_mut(&mut self) -> &mut Conn {
+        &mut self.conn
+    }
+}
+
+impl Drop for PooledConn {
+    fn drop(&mut self) {
+        self.stats.released(self.conn.id());
+    }
+}
+
+struct PoolStats {
+    in_use: AtomicUsize,
+    waiting: AtomicUsize,
+    total_acquired: AtomicU64,
+    acquire_errors: AtomicU64,
+    created_at: DateTime<Utc>,
+    last_used: Mutex<(Instant, DateTime<Utc>)>,
+    /// Connections back in the pool by connection id, with the time they were returned
+    returned: Mutex<HashMap<u32, Instant>>,
+}
+
+impl PoolStats {
+    fn new() -> Self {
+        Self {
+            in_use: AtomicUsize::new(0),
+            waiting: AtomicUsize::new(0),
+            total_acquired: AtomicU64::new(0),
+            acquire_errors: AtomicU64::new(0),
+            created_at: Utc::now(),
+            last_used: Mutex::new((Instant::now(), Utc::now())),
+            returned: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn acquired(&self, conn_id: u32) {
+        self.in_use.fetch_add(1, Ordering::Relaxed);
+        self.total_acquired.fetch_add(1, Ordering::Relaxed);
+        self.returned.lock().unwrap().remove(&conn_id);
+        self.touch();
+    }
+
+    fn released(&self, conn_id: u32) {
+        self.in_use.fetch_sub(1, Ordering::Relaxed);
+        self.returned
+            .lock()
+            .unwrap()
+            .insert(conn_id, Instant::now());
+        self.touch();
+    }
+
+    fn touch(&self) {
+        *self.last_used.lock().unwrap() = (Instant::now(), Utc::now());
+    }
+
+    fn idle_for(&self) -> Duration {
+        self.last_used.lock().unwrap().0.elapsed()
+    }
+
+    /// Returned connections the pool has not closed yet
+    ///
+    /// The pool closes connections idle for longer than the idle timeout but keeps
+    /// `min_connections` open, so older entries count only up to that minimum.
+    fn idle_connections(&self, settings: &EffectivePoolSettings) -> usize {
+        let mut returned = self.returned.lock().unwrap();
+        let idle_timeout = Duration::from_secs(settings.idle_timeout_secs);
+        let in_use = self.in_use.load(Ordering::Relaxed);
+        let kept = settings.min_connections.saturating_sub(in_use);
+
+        let mut by_age: Vec<(u32, Instant)> = returned.iter().map(|(id, at)| (*id, *at)).collect();
+        by_age.sort_by(|a, b| b.1.cmp(&a.1));
+        let recent = by_age
+            .iter()
+            .take_while(|(_, at)| at.elapsed() < idle_timeout)
+            .count();
+        let idle = recent
+            .max(kept.min(by_age.len()))
+            .min(settings.max_connections.saturating_sub(in_use));
+
+        // Forget connections the pool has closed by now
+        for (id, _) in by_age.iter().skip(idle) {
+            returned.remove(id);
+        }
+        idle
+    }
+}
+
+/// Counts a caller waiting for a connection, also if the wait is cancelled
+struct WaitingGuard<'a>(&'a AtomicUsize);
+
+impl<'a> WaitingGuard<'a> {
+    fn new(waiting: &'a AtomicUsize) -> Self {
+        waiting.fetch_add(1, Ordering::Relaxed);
+        Self(waiting)
+    }
+}
+
+impl Drop for WaitingGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
 }
 
 impl MySQLPoolManager {
-    pub fn new() -> Self {
-        Self { pools: Arc::new(DashMap::new()) }
+    pub fn new(defaults: MySQLPoolConfig) -> Self {
+        Self { pools: Arc::new(DashMap::new()), defaults }
     }
 }
 
 impl Default for MySQLPoolManager {
     fn default() -> Self {
-        Self::new()
+        Self::new(MySQLPoolConfig::default())
     }
 }
 
 impl MySQLPoolManager {
     /// Get or create a connection pool for the given cluster
     ///
-    /// Fast path: If pool exists, return immediately (lock-free read)
-    /// Slow path: Create new pool if doesn't exist
-    pub async fn get_pool(&self, cluster: &Cluster) -> ApiResult<Pool> {
+    /// Fast path: If a pool on a healthy FE exists, return immediately (lock-free read)
+    /// Slow path: Create a pool on the first FE that accepts a connection
+    pub async fn get_pool(&self, cluster: &Cluster) -> ApiResult<ClusterPool> {
         let cluster_id = cluster.id;
+        let registry = FeRegistry::global();
+        let candidates = registry.candidates(cluster);
+        let settings = EffectivePoolSettings::resolve(&self.defaults, cluster);
+        let fingerprint = connection_fingerprint(cluster, &settings);
 
         // Fast path: Try to get existing pool (lock-free)
-        if let Some(pool) = self.pools.get(&cluster_id) {
-            return Ok(pool.clone());
+        if let Some(entry) = self.pools.get(&cluster_id)
+            && entry.fingerprint == fingerprint
+            && registry.is_up(&entry.endpoint)
+            && candidates.contains(&entry.endpoint)
+        {
+            return Ok(entry.pool.clone());
         }
 
         // Slow path: Create new pool
-        let pool = self.create_pool(cluster).await?;
-
-        // Insert into map (DashMap handles concurrent inserts gracefully)
-        self.pools.insert(cluster_id, pool.clone());
+        let (endpoint, pool) = self
+            .connect_first_available(cluster, &candidates, &settings)
+            .await?;
 
         tracing::info!(
-            "Created MySQL connection pool for cluster {} ({}:{})",
+            "Created MySQL connection pool for cluster {} ({}:{}, {}-{} connections)",
             cluster_id,
-            cluster.fe_host,
-            cluster.fe_query_port
+            endpoint.host,
+            endpoint.query_port,
+            settings.min_connections,
+            settings.max_connections
         );
 
+        // Insert into map (DashMap handles concurrent inserts gracefully)
+        let entry = PoolEntry {
+            cluster_name: cluster.name.clone(),
+            endpoint,
+            fingerprint,
+            settings,
+            pool: pool.clone(),
+        };
+        if let Some(previous) = self.pools.insert(cluster_id, entry) {
+            tokio::spawn(async move {
+                let _ = previous.pool.disconnect().await;
+            });
+        }
+
         Ok(pool)
     }
 
+    // Try the FEs in order until one accepts a connection
+    async fn connect_first_available(
+        &self,
+        cluster: &Cluster,
+        candidates: &[FeEndpoint],
+        settings: &EffectivePoolSettings,
+    ) -> ApiResult<(FeEndpoint, ClusterPool)> {
+        let registry = FeRegistry::global();
+        let mut last_error = None;
+
+        for endpoint in candidates {
+            let pool = self.create_pool(cluster, endpoint, settings)?;
+            match pool.get_conn().await {
+                Ok(_conn) => {
+                    registry.mark_up(endpoint);
+                    return Ok((endpoint.clone(), pool));
+                },
+                Err(e) => last_error = Some(e.to_string()),
+            }
+
+            tracing::warn!(
+                "Cannot connect to FE {}:{} of cluster {}: {}",
+                endpoint.host,
+                endpoint.query_port,
+                cluster.id,
+                last_error.as_deref().unwrap_or_default()
+            );
+            registry.mark_down(endpoint);
+            let _ = pool.disconnect().await;
+        }
+
+        Err(ApiError::cluster_connection_failed(format!(
+            "No FE of the cluster accepts connections: {}",
+            last_error.unwrap_or_else(|| "no FE configured".to_string())
+        )))
+    }
+
     /// Remove a pool for a specific cluster
     ///
     /// Useful when cluster is deleted or credentials are updated
     pub async fn remove_pool(&self, cluster_id: i64) {
-        if let Some((_, pool)) = self.pools.remove(&cluster_id) {
-            drop(pool); // Pool will be closed when all references are dropped
+        if let Some((_, entry)) = self.pools.remove(&cluster_id) {
+            let _ = entry.pool.disconnect().await;
             tracing::info!("Removed MySQL connection pool for cluster {}", cluster_id);
         }
     }
@@ -77,24 +405,225 @@ impl MySQLPoolManager {
         self.pools.len()
     }
 
-    /// Create a new MySQL connection pool for a cluster
-    async fn create_pool(&self, cluster: &Cluster) -> ApiResult<Pool> {
+    /// Connection statistics of all pools, ordered by cluster id
+    pub fn pool_statuses(&self) -> Vec<PoolStatus> {
+        let mut statuses: Vec<PoolStatus> = self
+            .pools
+            .iter()
+            .map(|entry| {
+                let stats = &entry.pool.stats;
+                PoolStatus {
+                    cluster_id: *entry.key(),
+                    cluster_name: entry.cluster_name.clone(),
+                    fe: format!("{}:{}", entry.endpoint.host, entry.endpoint.query_port),
+                    active_connections: stats.in_use.load(Ordering::Relaxed),
+                    idle_connections: stats.idle_connections(&entry.settings),
+                    waiting_requests: stats.waiting.load(Ordering::Relaxed),
+                    total_acquired: stats.total_acquired.load(Ordering::Relaxed),
+                    acquire_errors: stats.acquire_errors.load(Ordering::Relaxed),
+                    created_at: stats.created_at,
+                    last_used_at: stats.last_used.lock().unwrap().1,
+                    settings: entry.settings.clone(),
+                }
+            })
+            .collect();
+        statuses.sort_by_key(|status| status.cluster_id);
+        statuses
+    }
+
+    /// Close pools that have not been used for `evict_idle_pool_secs`
+    pub async fn evict_idle_pools(&self) -> usize {
+        if self.defaults.evict_idle_pool_secs == 0 {
+            return 0;
+        }
+        let max_idle = Duration::from_secs(self.defaults.evict_idle_pool_secs);
+
+        let idle: Vec<i64> = self
+            .pools
+            .iter()
+            .filter(|entry| !entry.pool.is_busy() && entry.pool.stats.idle_for() >= max_idle)
+            .map(|entry| *entry.key())
+            .collect();
+
+        let mut evicted = 0;
+        for cluster_id in idle {
+            // Re-check: the pool may have been used since it was selected
+            let removed = self.pools.remove_if(&cluster_id, |_, entry| {
+                !entry.pool.is_busy() && entry.pool.stats.idle_for() >= max_idle
+            });
+            if let Some((_, entry)) = removed {
+                let _ = entry.pool.disconnect().await;
+                tracing::info!("Closed idle MySQL connection pool for cluster {}", cluster_id);
+                evicted += 1;
+            }
+        }
+        evicted
+    }
+
+    /// Create a new MySQL connection pool on one FE of a cluster
+    fn create_pool(
+        &self,
+        cluster: &Cluster,
+        endpoint: &FeEndpoint,
+        settings: &EffectivePoolSettings,
+    ) -> ApiResult<ClusterPool> {
+        let password = cluster.password()?;
+        let ssl_opts = cluster.tls()?.map(|tls| {
+            let mut ssl_opts =
+                SslOpts::default().with_danger_skip_domain_validation(!tls.verify_hostname);
+            if let Some(ca_cert) = tls.ca_cert_pem {
+                ssl_opts = ssl_opts.with_root_certs(vec![ca_cert.into_bytes().into()]);
+            }
+            if let Some(identity) = tls.client_identity {
+                ssl_opts = ssl_opts.with_client_identity(Some(
+                    ClientIdentity::new(identity.into())
+                        .with_password(tls.client_identity_password),
+                ));
+            }
+            ssl_opts
+        });
+        let constraints =
+            mysql_async::PoolConstraints::new(settings.min_connections, settings.max_connections)
+                .ok_or_else(|| {
+                ApiError::internal_error(
+                    "Failed to create pool constraints: invalid min/max values",
+                )
+            })?;
         let opts = OptsBuilder::default()
-            .ip_or_hostname(&cluster.fe_host)
-            .tcp_port(cluster.fe_query_port as u16)
+            .ip_or_hostname(&endpoint.host)
+            .tcp_port(endpoint.query_port as u16)
             .user(Some(&cluster.username))
-            .pass(Some(&cluster.password_encrypted))
+            .pass(Some(password))
             .db_name(None::<String>) // No default database
             .prefer_socket(false) // Disable socket preference for StarRocks compatibility
-            .ssl_opts(None::<SslOpts>) // No SSL for now
-            .pool_opts(mysql_async::PoolOpts::default().with_constraints(
-                mysql_async::PoolConstraints::new(1, 10).ok_or_else(|| {
-                    crate::utils::ApiError::internal_error(
-                        "Failed to create pool constraints: invalid min/max values",
-                    )
-                })?,
-            ));
-
-        Ok(Pool::new(opts))
+            .ssl_opts(ssl_opts) // TLS when the cluster has enable_ssl
+            .pool_opts(
+                mysql_async::PoolOpts::default()
+                    .with_constraints(constraints)
+                    .with_inactive_connection_ttl(Duration::from_secs(settings.idle_timeout_secs))
+                    .with_abs_conn_ttl(Some(Duration::from_secs(settings.max_lifetime_secs))),
+            );
+
+        Ok(ClusterPool {
+            pool: Pool::new(opts),
+            stats: Arc::new(PoolStats::new()),
+            settings: Some(settings.clone()),
+        })
+    }
+}
+
+/// Pool eviction runs on a `ScheduledExecutor`
+impl ScheduledTask for MySQLPoolManager {
+    fn run(&self) -> Pin<Box<dyn Future<Output = Result<(), anyhow::Error>> + Send + '_>> {
+        Box::pin(async move {
+            self.evict_idle_pools().await;
+            Ok(())
+        })
+    }
+
+    fn name(&self) -> &str {
+        "mysql-pool-eviction"
+    }
+}
+
+// Hash of everything a pool is built from besides the FE, to rebuild it after edits
+fn connection_fingerprint(cluster: &Cluster, settings: &EffectivePoolSettings) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    cluster.username.hash(&mut hasher);
+    cluster.password_encrypted.hash(&mut hasher);
+    cluster.enable_ssl.hash(&mut hasher);
+    cluster.tls_ca_cert.hash(&mut hasher);
+    cluster.tls_client_identity_encrypted.hash(&mut hasher);
+    cluster
+        .tls_client_identity_password_encrypted
+        .hash(&mut hasher);
+    cluster.tls_verify_hostname.hash(&mut hasher);
+    settings.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PoolSettings;
+
+    fn cluster(pool_settings: &PoolSettings) -> Cluster {
+        Cluster {
+            password_encrypted: "secret-v1".to_string(),
+            connection_timeout: 5,
+            pool_settings: Some(serde_json::to_string(pool_settings).unwrap()),
+            ..Cluster::for_test()
+        }
+    }
+
+    #[test]
+    fn test_cluster_overrides_pool_defaults() {
+        let defaults = MySQLPoolConfig::default();
+
+        let settings =
+            EffectivePoolSettings::resolve(&defaults, &cluster(&PoolSettings::default()));
+        assert_eq!(settings.min_connections, defaults.min_connections);
+        assert_eq!(settings.max_connections, defaults.max_connections);
+        assert_eq!(settings.connect_timeout_secs, 5);
+
+        let overrides = PoolSettings {
+            min_connections: Some(20),
+            max_connections: Some(4),
+            idle_timeout_secs: Some(60),
+            max_lifetime_secs: None,
+        };
+        let settings = EffectivePoolSettings::resolve(&defaults, &cluster(&overrides));
+        assert_eq!((settings.min_connections, settings.max_connections), (4, 4));
+        assert_eq!(settings.idle_timeout_secs, 60);
+        assert_eq!(settings.max_lifetime_secs, defaults.max_lifetime_secs);
+    }
+
+    #[test]
+    fn test_fingerprint_changes_with_connection_settings() {
+        let defaults = MySQLPoolConfig::default();
+        let base = cluster(&PoolSettings::default());
+        let fingerprint =
+            |c: &Cluster| connection_fingerprint(c, &EffectivePoolSettings::resolve(&defaults, c));
+
+        let mut renamed = base.clone();
+        renamed.name = "renamed".to_string();
+        renamed.tags = Some("[\"a\"]".to_string());
+        assert_eq!(fingerprint(&base), fingerprint(&renamed));
+
+        let mut new_password = base.clone();
+        new_password.password_encrypted = "secret-v2".to_string();
+        assert_ne!(fingerprint(&base), fingerprint(&new_password));
+
+        let mut bigger = base.clone();
+        bigger.pool_settings = Some("{\"max_connections\":50}".to_string());
+        assert_ne!(fingerprint(&base), fingerprint(&bigger));
+    }
+
+    #[test]
+    fn test_idle_connections_follow_idle_timeout() {
+        let settings = EffectivePoolSettings {
+            min_connections: 1,
+            max_connections: 10,
+            idle_timeout_secs: 60,
+            max_lifetime_secs: 3600,
+            connect_timeout_secs: 10,
+        };
+        let stats = PoolStats::new();
+        let long_ago = Instant::now() - Duration::from_secs(600);
+        stats
+            .returned
+            .lock()
+            .unwrap()
+            .extend([(1, long_ago), (2, long_ago), (3, Instant::now())]);
+
+        // The recent connection plus none of the old ones (min is already met)
+        assert_eq!(stats.idle_connections(&settings), 1);
+        assert_eq!(stats.returned.lock().unwrap().len(), 1);
+
+        stats.acquired(3);
+        assert_eq!(stats.in_use.load(Ordering::Relaxed), 1);
+        assert_eq!(stats.idle_connections(&settings), 0);
+        stats.released(3);
+        assert_eq!(stats.idle_connections(&settings), 1);
     }
 }
@@ -0,0 +1,466 @@
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL;
+use dashmap::DashMap;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header};
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
+
+use crate::config::OidcConfig;
+use crate::models::AuthSource;
+use crate::services::auth_backend::{ExternalIdentity, RoleMapping};
+use crate::utils::{ApiError, ApiResult, token};
+
+/// How long a user may take between the redirect to the provider and the callback
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone, Deserialize)]
+struct Discovery {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    #[serde(default)]
+    userinfo_endpoint: Option<String>,
+    jwks_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenEndpointResponse {
+    access_token: String,
+    id_token: String,
+}
+
+/// State of a sign-in between the redirect and the callback
+struct PendingLogin {
+    nonce: String,
+    code_verifier: String,
+    created_at: Instant,
+}
+
+/// OIDC authorization-code flow with PKCE
+///
+/// The provider configuration is discovered lazily on first use, so a provider
+/// that is down at startup does not prevent the server from starting.
+pub struct OidcProvider {
+    config: OidcConfig,
+    http: reqwest::Client,
+    discovery: OnceCell<Discovery>,
+    role_mapping: RoleMapping,
+    pending: DashMap<String, PendingLogin>,
+}
+
+impl OidcProvider {
+    pub fn new(config: OidcConfig) -> Self {
+        let role_mapping = RoleMapping::new(&config.group_roles, config.default_role);
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+
+        Self { config, http, discovery: OnceCell::new(), role_mapping, pending: DashMap::new() }
+    }
+
+    /// Frontend page that receives the tokens after sign-in
+    pub fn post_login_redirect(&self) -> &str {
+        &self.config.post_login_redirect
+    }
+
+    async fn discovery(&self) -> ApiResult<&Discovery> {
+        self.discovery
+            .get_or_try_init(|| async {
+                let url = format!(
+                    "{}/.well-known/openid-configuration",
+                    self.config.issuer_url.trim_end_matches('/')
+                );
+                let discovery: Discovery = self.get_json(&url).await?;
+
+                if discovery.issuer.trim_end_matches('/')
+                    != self.config.issuer_url.trim_end_matches('/')
+                {
+                    tracing::error!(
+                        "OIDC discovery issuer '{}' does not match configured '{}'",
+                        discovery.issuer,
+                        self.config.issuer_url
+                    );
+                    return Err(ApiError::internal_error("OIDC provider is misconfigured"));
+                }
+
+                Ok(discovery)
+            })
+            .await
+    }
+
+    // Start a sign-in: remember state/nonce/PKCE verifier and build the provider URL
+    pub async fn authorization_url(&self) -> ApiResult<String> {
+        let discovery = self.discovery().await?;
+        self.pending
+            .retain(|_, login| login.created_at.elapsed() < LOGIN_TIMEOUT);
+
+        let state = token::generate_secret("", 16);
+        let nonce = token::generate_secret("", 16);
+        let code_verifier = token::generate_secret("", 32);
+        let code_challenge = BASE64_URL.encode(Sha256::digest(code_verifier.as_bytes()));
+
+        let url = format!(
+            "{}?response_type=code&client_id={}&redirect_uri={}&scope={}&state={}&nonce={}&code_challenge={}&code_challenge_method=S256",
+            discovery.authorization_endpoint,
+            urlencoding::encode(&self.config.client_id),
+            urlencoding::encode(&self.config.redirect_url),
+            urlencoding::encode(&self.config.scopes.join(" ")),
+            state,
+            nonce,
+            code_challenge,
+        );
+
+        self.pending
+            .insert(state, PendingLogin { nonce, code_verifier, created_at: Instant::now() });
+
+        Ok(url)
+    }
+
+    // Finish a sign-in: exchange the code and verify the ID token
+    pub async fn complete(&self, code: &str, state: &str) -> ApiResult<ExternalIdentity> {
+        // Each state can be used once
+        let (_, login) = self
+            .pending
+            .remove(state)
+            .filter(|(_, login)| login.created_at.elapsed() < LOGIN_TIMEOUT)
+            .ok_or_else(|| ApiError::unauthorized("Sign-in request is invalid or has expired"))?;
+
+        let discovery = self.discovery().await?;
+        let response = self
+            .http
+            .post(&discovery.token_endpoint)
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", self.config.redirect_url.as_str()),
+                ("client_id", self.config.client_id.as_str()),
+                ("client_secret", self.config.client_secret.as_str()),
+                ("code_verifier", login.code_verifier.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(Self::provider_error)?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            tracing::warn!("OIDC token exchange failed ({}): {}", status, body);
+            return Err(ApiError::unauthorized("Sign-in was rejected by the identity provider"));
+        }
+
+        let tokens: TokenEndpointResponse = response.json().await.map_err(Self::provider_error)?;
+        let claims = self
+            .verify_id_token(discovery, &tokens.id_token, &login.nonce)
+            .await?;
+
+        let groups = match claims.get(&self.config.groups_claim) {
+            Some(groups) => string_list(groups),
+            None => {
+                self.userinfo_groups(discovery, &tokens.access_token)
+                    .await?
+            },
+        };
+
+        self.identity(&claims, groups)
+    }
+
+    async fn verify_id_token(
+        &self,
+        discovery: &Discovery,
+        id_token: &str,
+        nonce: &str,
+    ) -> ApiResult<serde_json::Map<String, Value>> {
+        let header = decode_header(id_token).map_err(Self::invalid_id_token)?;
+
+        // HMAC-signed ID tokens use the client secret, asymmetric ones the provider's JWKS
+        let key = match header.alg {
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => {
+                DecodingKey::from_secret(self.config.client_secret.as_bytes())
+            },
+            _ => {
+                let jwks: JwkSet = self.get_json(&discovery.jwks_uri).await?;
+                let jwk = match &header.kid {
+                    Some(kid) => jwks.find(kid),
+                    None => jwks.keys.first(),
+                }
+                .ok_or_else(|| ApiError::unauthorized("ID token signing key is unknown"))?;
+                DecodingKey::from_jwk(jwk).map_err(Self::invalid_id_token)?
+            },
+        };
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&discovery.issuer]);
+        validation.set_audience(&[&self.config.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        let claims = decode::<serde_json::Map<String, Value>>(id_token, &key, &validation)
+            .map_err(Self::invalid_id_token)?
+            .claims;
+
+        if claims.get("nonce").and_then(Value::as_str) != Some(nonce) {
+            tracing::warn!("OIDC ID token nonce mismatch");
+            return Err(ApiError::unauthorized("Invalid ID token"));
+        }
+
+        Ok(claims)
+    }
+
+    async fn userinfo_groups(
+        &self,
+        discovery: &Discovery,
+        access_token: &str,
+    ) -> ApiResult<Vec<String>> {
+        let Some(endpoint) = &discovery.userinfo_endpoint else {
+            return Ok(Vec::new());
+        };
+
+        let userinfo: Value = self
+            .http
+            .get(endpoint)
+            .bearer_auth(access_token)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(Self::provider_error)?
+            .json()
+            .await
+            .map_err(Self::provider_error)?;
+
+        Ok(userinfo
+            .get(&self.config.groups_claim)
+            .map(string_list)
+            .unwrap_or_default())
+    }
+
+    fn identity(
+        &self,
+        claims: &serde_json::Map<String, Value>,
+        groups: Vec<String>,
+    ) -> ApiResult<ExternalIdentity> {
+        let claim = |name: &str| {
+            claims
+                .get(name)
+                .and_then(Value::as_str)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+
+        let subject = claim("sub").ok_or_else(|| ApiError::unauthorized("Invalid ID token"))?;
+        let email = claim("email");
+        let username = claim(&self.config.username_claim)
+            .or_else(|| email.clone())
+            .unwrap_or_else(|| subject.clone());
+        let role = self.role_mapping.require(&username, &groups)?;
+
+        Ok(ExternalIdentity {
+            source: AuthSource::Oidc,
+            external_id: subject,
+            alternate_ids: Vec::new(),
+            username,
+            email,
+            groups,
+            role,
+        })
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> ApiResult<T> {
+        self.http
+            .get(url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(Self::provider_error)?
+            .json()
+            .await
+            .map_err(Self::provider_error)
+    }
+
+    fn provider_error(e: reqwest::Error) -> ApiError {
+        tracing::error!("OIDC provider request failed: {}", e);
+        ApiError::internal_error("Identity provider is unavailable")
+    }
+
+    fn invalid_id_token(e: jsonwebtoken::errors::Error) -> ApiError {
+        tracing::warn!("OIDC ID token rejected: {}", e);
+        ApiError::unauthorized("Invalid ID token")
+    }
+}
+
+/// A claim holding either one string or a list of strings
+fn string_list(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(s) => vec![s.clone()],
+        Value::Array(items) => items
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::UserRole;
+    use axum::extract::{Form, State};
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+    use jsonwebtoken::{EncodingKey, Header, encode};
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    const CLIENT_ID: &str = "starrocks-admin";
+    const CLIENT_SECRET: &str = "test-client-secret";
+
+    /// Local stand-in for an identity provider
+    #[derive(Clone, Default)]
+    struct StandIn {
+        issuer: String,
+        /// code -> (nonce, PKCE challenge)
+        codes: Arc<Mutex<HashMap<String, (String, String)>>>,
+    }
+
+    async fn discovery(State(idp): State<StandIn>) -> Json<Value> {
+        Json(json!({
+            "issuer": idp.issuer,
+            "authorization_endpoint": format!("{}/authorize", idp.issuer),
+            "token_endpoint": format!("{}/token", idp.issuer),
+            "userinfo_endpoint": format!("{}/userinfo", idp.issuer),
+            "jwks_uri": format!("{}/jwks", idp.issuer),
+        }))
+    }
+
+    async fn token_endpoint(
+        State(idp): State<StandIn>,
+        Form(form): Form<HashMap<String, String>>,
+    ) -> Result<Json<Value>, axum::http::StatusCode> {
+        let (nonce, challenge) = idp
+            .codes
+            .lock()
+            .unwrap()
+            .remove(&form["code"])
+            .ok_or(axum::http::StatusCode::BAD_REQUEST)?;
+
+        let verifier_challenge =
+            BASE64_URL.encode(Sha256::digest(form["code_verifier"].as_bytes()));
+        if verifier_challenge != challenge || form["client_secret"] != CLIENT_SECRET {
+            return Err(axum::http::StatusCode::BAD_REQUEST);
+        }
+
+        let claims = json!({
+            "iss": idp.issuer,
+            "aud": CLIENT_ID,
+            "sub": "user-1234",
+            "exp": chrono::Utc::now().timestamp() + 300,
+            "nonce": nonce,
+            "preferred_username": "carol",
+            "email": "carol@example.com",
+        });
+        let id_token = encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(CLIENT_SECRET.as_bytes()),
+        )
+        .unwrap();
+
+        Ok(Json(json!({ "access_token": "at-1", "id_token": id_token, "token_type": "Bearer" })))
+    }
+
+    async fn userinfo() -> Json<Value> {
+        Json(json!({ "sub": "user-1234", "groups": ["starrocks-operators"] }))
+    }
+
+    async fn start_stand_in() -> StandIn {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let idp = StandIn {
+            issuer: format!("http://{}", listener.local_addr().unwrap()),
+            ..StandIn::default()
+        };
+
+        let app = Router::new()
+            .route("/.well-known/openid-configuration", get(discovery))
+            .route("/token", post(token_endpoint))
+            .route("/userinfo", get(userinfo))
+            .with_state(idp.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        idp
+    }
+
+    fn provider(idp: &StandIn) -> OidcProvider {
+        OidcProvider::new(OidcConfig {
+            enabled: true,
+            issuer_url: idp.issuer.clone(),
+            client_id: CLIENT_ID.to_string(),
+            client_secret: CLIENT_SECRET.to_string(),
+            redirect_url: "http://localhost:8080/api/auth/oidc/callback".to_string(),
+            group_roles: HashMap::from([("starrocks-operators".to_string(), UserRole::Operator)]),
+            ..OidcConfig::default()
+        })
+    }
+
+    /// Follow the authorization URL like the provider would after the user signs in
+    async fn authorize(
+        provider: &OidcProvider,
+        idp: &StandIn,
+        nonce_override: Option<&str>,
+    ) -> (String, String) {
+        let url = reqwest::Url::parse(&provider.authorization_url().await.unwrap()).unwrap();
+        let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+
+        assert_eq!(params["client_id"], CLIENT_ID);
+        assert_eq!(params["code_challenge_method"], "S256");
+
+        let code = token::generate_secret("code_", 8);
+        let nonce = nonce_override.unwrap_or(&params["nonce"]).to_string();
+        idp.codes
+            .lock()
+            .unwrap()
+            .insert(code.clone(), (nonce, params["code_challenge"].clone()));
+
+        (code, params["state"].clone())
+    }
+
+    #[tokio::test]
+    async fn test_code_flow_provisions_identity_from_userinfo_groups() {
+        let idp = start_stand_in().await;
+        let provider = provider(&idp);
+
+        let (code, state) = authorize(&provider, &idp, None).await;
+        let identity = provider.complete(&code, &state).await.unwrap();
+
+        assert_eq!(identity.source, AuthSource::Oidc);
+        assert_eq!(identity.external_id, "user-1234");
+        assert_eq!(identity.username, "carol");
+        assert_eq!(identity.email.as_deref(), Some("carol@example.com"));
+        assert_eq!(identity.role, UserRole::Operator);
+    }
+
+    #[tokio::test]
+    async fn test_state_cannot_be_replayed() {
+        let idp = start_stand_in().await;
+        let provider = provider(&idp);
+
+        let (code, state) = authorize(&provider, &idp, None).await;
+        provider.complete(&code, &state).await.unwrap();
+
+        assert!(provider.complete(&code, &state).await.is_err());
+        assert!(provider.complete(&code, "unknown-state").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_id_token_with_wrong_nonce_is_rejected() {
+        let idp = start_stand_in().await;
+        let provider = provider(&idp);
+
+        let (code, state) = authorize(&provider, &idp, Some("attacker-nonce")).await;
+        assert!(provider.complete(&code, &state).await.is_err());
+    }
+}
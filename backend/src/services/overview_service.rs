@@ -2,8 +2,9 @@
 // Purpose: Provide aggregated cluster overview data (real-time + historical)
 // Design Ref: ARCHITECTURE_ANALYSIS_AND_INTEGRATION.md
 
+use crate::models::Cluster;
 use crate::services::{
-    ClusterService, DataStatistics, DataStatisticsService, MetricsSnapshot, MySQLClient,
+    ClusterService, DataStatistics, DataStatisticsService, MetricsSnapshot, MySQLClient, QueryCache,
 };
 use crate::utils::{ApiError, ApiResult};
 use chrono::{DateTime, NaiveDateTime, Utc};
@@ -371,6 +372,7 @@ pub struct OverviewService {
     cluster_service: Arc<ClusterService>,
     data_statistics_service: Option<Arc<DataStatisticsService>>,
     mysql_pool_manager: Arc<crate::services::mysql_pool_manager::MySQLPoolManager>,
+    query_cache: Option<Arc<QueryCache>>,
 }
 
 impl OverviewService {
@@ -380,7 +382,13 @@ impl OverviewService {
         cluster_service: Arc<ClusterService>,
         mysql_pool_manager: Arc<crate::services::mysql_pool_manager::MySQLPoolManager>,
     ) -> Self {
-        Self { db, cluster_service, data_statistics_service: None, mysql_pool_manager }
+        Self {
+            db,
+            cluster_service,
+            data_statistics_service: None,
+            mysql_pool_manager,
+            query_cache: None,
+        }
     }
 
     /// Set data statistics service (optional dependency)
@@ -389,6 +397,13 @@ impl OverviewService {
         self
     }
 
+    /// Set query result cache (optional dependency), used for the expensive
+    /// cluster queries when `[query_cache] overview_ttl_secs` is set
+    pub fn with_query_cache(mut self, cache: Arc<QueryCache>) -> Self {
+        self.query_cache = Some(cache);
+        self
+    }
+
     /// Get cluster overview (main API)
     pub async fn get_cluster_overview(
         &self,
@@ -698,6 +713,30 @@ impl OverviewService {
     // Internal helper methods
     // ========================================
 
+    /// Run a query on the cluster, reusing a cached result if the cache is set up
+    async fn query_cluster(
+        &self,
+        cluster: &Cluster,
+        sql: &str,
+    ) -> ApiResult<(Vec<String>, Vec<Vec<String>>)> {
+        let load = || async {
+            let pool = self.mysql_pool_manager.get_pool(cluster).await?;
+            MySQLClient::from_pool(pool)
+                .query_raw(sql, None, None)
+                .await
+        };
+        match &self.query_cache {
+            Some(cache) => {
+                let ttl_secs = cache.overview_ttl_secs();
+                Ok(cache
+                    .get_or_load(cluster.id, None, None, sql, ttl_secs, load)
+                    .await?
+                    .value)
+            },
+            None => load().await,
+        }
+    }
+
     /// Get real data size from information_schema.tables (data stored in object storage)
     #[allow(dead_code)]
     async fn get_real_data_size(&self, cluster_id: i64) -> ApiResult<i64> {
@@ -1344,16 +1383,10 @@ impl OverviewService {
 
     /// Module 7: Get MV stats from information_schema
     async fn get_mv_stats(&self, cluster_id: i64) -> ApiResult<MaterializedViewStats> {
-        use crate::services::MySQLPoolManager;
 
         // Get cluster info
         let cluster = self.cluster_service.get_cluster(cluster_id).await?;
 
-        // Get MySQL connection pool and create client
-        let pool_manager = Arc::new(MySQLPoolManager::new());
-        let pool = pool_manager.get_pool(&cluster).await?;
-        let mysql_client = MySQLClient::from_pool(pool);
-
         // Query materialized view statistics
         let query = r#"
             SELECT 
@@ -1363,7 +1396,7 @@ impl OverviewService {
             FROM information_schema.materialized_views
         "#;
 
-        let (columns, rows) = mysql_client.query_raw(query, None, None).await?;
+        let (columns, rows) = self.query_cluster(&cluster, query).await?;
 
         // Build column index map
         let mut col_idx = std::collections::HashMap::new();
@@ -1398,16 +1431,10 @@ impl OverviewService {
 
     /// Module 8: Get load job stats from SHOW LOAD
     async fn get_load_job_stats(&self, cluster_id: i64) -> ApiResult<LoadJobStats> {
-        use crate::services::MySQLPoolManager;
 
         // Get cluster info
         let cluster = self.cluster_service.get_cluster(cluster_id).await?;
 
-        // Get MySQL connection pool and create client
-        let pool_manager = Arc::new(MySQLPoolManager::new());
-        let pool = pool_manager.get_pool(&cluster).await?;
-        let mysql_client = MySQLClient::from_pool(pool);
-
         // Query load job statistics from information_schema.loads
         // Note: SHOW LOAD returns current database only, so we query information_schema
         let query = r#"
@@ -1419,7 +1446,7 @@ impl OverviewService {
             GROUP BY State
         "#;
 
-        let (columns, rows) = mysql_client.query_raw(query, None, None).await?;
+        let (columns, rows) = self.query_cluster(&cluster, query).await?;
 
         // Build column index map
         let mut col_idx = std::collections::HashMap::new();
@@ -1469,12 +1496,7 @@ impl OverviewService {
     /// Module 10: Get schema change stats by querying audit logs
     /// Tracks ALTER TABLE operations and their status from StarRocks audit logs
     async fn get_schema_change_stats(&self, cluster_id: i64) -> ApiResult<SchemaChangeStats> {
-        use crate::services::{MySQLClient, MySQLPoolManager};
-
         let cluster = self.cluster_service.get_cluster(cluster_id).await?;
-        let pool_manager = Arc::new(MySQLPoolManager::new());
-        let pool = pool_manager.get_pool(&cluster).await?;
-        let mysql_client = MySQLClient::from_pool(pool);
 
         // Query ALTER TABLE operations from audit logs
         // Track schema changes by analyzing DDL statements in the audit log
@@ -1491,7 +1513,7 @@ impl OverviewService {
             GROUP BY queryType, state
         "#;
 
-        let (columns, rows) = mysql_client.query_raw(query, None, None).await?;
+        let (columns, rows) = self.query_cluster(&cluster, query).await?;
 
         // Build column index map
         let mut col_idx = std::collections::HashMap::new();
@@ -1534,14 +1556,13 @@ impl OverviewService {
     /// Note: Compaction Score is calculated at FE level per Partition, not per BE.
     /// Reference: https://forum.mirrorship.cn/t/topic/13256
     async fn get_compaction_stats(&self, cluster_id: i64) -> ApiResult<CompactionStats> {
-        use crate::services::{MySQLClient, MySQLPoolManager};
+        use crate::services::MySQLClient;
 
         // Get cluster info
         let cluster = self.cluster_service.get_cluster(cluster_id).await?;
 
         // Get MySQL connection pool
-        let pool_manager = Arc::new(MySQLPoolManager::new());
-        let pool = pool_manager.get_pool(&cluster).await?;
+        let pool = self.mysql_pool_manager.get_pool(&cluster).await?;
         let client = MySQLClient::from_pool(pool);
 
         // Query compaction tasks from FE
@@ -1585,14 +1606,13 @@ impl OverviewService {
         cluster_id: i64,
         time_range: &str,
     ) -> ApiResult<CompactionDetailStats> {
-        use crate::services::{MySQLClient, MySQLPoolManager};
+        use crate::services::MySQLClient;
 
         // Get cluster info
         let cluster = self.cluster_service.get_cluster(cluster_id).await?;
 
         // Get MySQL connection pool
-        let pool_manager = Arc::new(MySQLPoolManager::new());
-        let pool = pool_manager.get_pool(&cluster).await?;
+        let pool = self.mysql_pool_manager.get_pool(&cluster).await?;
         let client = MySQLClient::from_pool(pool);
 
         // Calculate time filter based on time_range parameter
@@ -1744,15 +1764,14 @@ impl OverviewService {
 
     /// Module 12: Get session stats from SHOW PROCESSLIST
     async fn get_session_stats(&self, cluster_id: i64) -> ApiResult<SessionStats> {
-        use crate::services::{MySQLClient, MySQLPoolManager};
+        use crate::services::MySQLClient;
         use chrono::Utc;
 
         // Get cluster info
         let cluster = self.cluster_service.get_cluster(cluster_id).await?;
 
         // Get MySQL connection pool
-        let pool_manager = Arc::new(MySQLPoolManager::new());
-        let pool = pool_manager.get_pool(&cluster).await?;
+        let pool = self.mysql_pool_manager.get_pool(&cluster).await?;
         let client = MySQLClient::from_pool(pool);
 
         // Query SHOW PROCESSLIST to get current connections
@@ -1886,16 +1905,11 @@ impl OverviewService {
 
     /// Get StarRocks version
     async fn get_starrocks_version(&self, cluster_id: i64) -> ApiResult<String> {
-        use crate::services::{MySQLClient, MySQLPoolManager};
-
         let cluster = self.cluster_service.get_cluster(cluster_id).await?;
-        let pool_manager = Arc::new(MySQLPoolManager::new());
-        let pool = pool_manager.get_pool(&cluster).await?;
-        let mysql_client = MySQLClient::from_pool(pool);
 
         // Query StarRocks version using SELECT VERSION()
         let sql = "SELECT VERSION() as version";
-        let (columns, rows) = mysql_client.query_raw(sql, None, None).await?;
+        let (columns, rows) = self.query_cluster(&cluster, sql).await?;
 
         // Find the version column index
         if let Some(version_idx) = columns
@@ -0,0 +1,96 @@
+use crate::config::PasswordPolicyConfig;
+use crate::utils::{ApiError, ApiResult};
+
+/// Strength rules for passwords of local accounts
+#[derive(Debug, Clone)]
+pub struct PasswordPolicy {
+    config: PasswordPolicyConfig,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self::new(PasswordPolicyConfig::default())
+    }
+}
+
+impl PasswordPolicy {
+    pub fn new(config: PasswordPolicyConfig) -> Self {
+        Self { config }
+    }
+
+    /// Check a new password, reporting every rule it breaks at once
+    pub fn validate(&self, username: &str, password: &str) -> ApiResult<()> {
+        let violations = self.violations(username, password);
+        if violations.is_empty() {
+            return Ok(());
+        }
+
+        Err(ApiError::validation_error(format!("Password must {}", violations.join(", "))))
+    }
+
+    fn violations(&self, username: &str, password: &str) -> Vec<String> {
+        let config = &self.config;
+        let mut violations = Vec::new();
+
+        if password.chars().count() < config.min_length {
+            violations.push(format!("be at least {} characters long", config.min_length));
+        }
+        if config.require_uppercase && !password.chars().any(char::is_uppercase) {
+            violations.push("contain an uppercase letter".to_string());
+        }
+        if config.require_lowercase && !password.chars().any(char::is_lowercase) {
+            violations.push("contain a lowercase letter".to_string());
+        }
+        if config.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+            violations.push("contain a digit".to_string());
+        }
+        if config.require_symbol && password.chars().all(char::is_alphanumeric) {
+            violations.push("contain a symbol".to_string());
+        }
+
+        let username = username.trim().to_lowercase();
+        if config.reject_username
+            && !username.is_empty()
+            && password.to_lowercase().contains(&username)
+        {
+            violations.push("not contain the username".to_string());
+        }
+
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_policy() {
+        let policy = PasswordPolicy::default();
+
+        assert!(policy.validate("alice", "correct-horse-7").is_ok());
+        assert!(policy.validate("admin", "admin").is_err());
+        assert!(policy.validate("alice", "no digits here").is_err());
+        assert!(policy.validate("alice", "Alice2024!").is_err());
+    }
+
+    #[test]
+    fn test_all_violations_are_reported() {
+        let policy = PasswordPolicy::new(PasswordPolicyConfig {
+            min_length: 12,
+            require_uppercase: true,
+            require_lowercase: true,
+            require_digit: true,
+            require_symbol: true,
+            reject_username: true,
+        });
+
+        let err = policy.validate("bob", "bob").unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "Validation error: Password must be at least 12 characters long, contain an uppercase \
+             letter, contain a digit, contain a symbol, not contain the username"
+        );
+        assert!(policy.validate("bob", "Tr0ub4dor&3-x").is_ok());
+    }
+}
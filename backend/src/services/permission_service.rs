@@ -0,0 +1,241 @@
+use crate::models::{ClusterRoleGrant, CurrentUser, UserPermissions, UserRole};
+use crate::utils::{ApiError, ApiResult};
+use sqlx::SqlitePool;
+
+/// Resolves and manages user roles (global role + per-cluster grants)
+#[derive(Clone)]
+pub struct PermissionService {
+    pool: SqlitePool,
+}
+
+impl PermissionService {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Effective role of a user on a cluster
+    ///
+    /// Global admins are admins everywhere. For everyone else a per-cluster grant,
+    /// if present, overrides the global role (it can raise or lower it).
+    pub async fn effective_role(&self, user: &CurrentUser, cluster_id: i64) -> ApiResult<UserRole> {
+        if user.role == UserRole::Admin {
+            return Ok(user.cap(UserRole::Admin));
+        }
+
+        let grant: Option<(UserRole,)> = sqlx::query_as(
+            "SELECT role FROM user_cluster_roles WHERE user_id = ? AND cluster_id = ?",
+        )
+        .bind(user.id)
+        .bind(cluster_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(user.cap(grant.map(|(role,)| role).unwrap_or(user.role)))
+    }
+
+    /// Ensure the user holds at least `required` globally or on the given cluster
+    pub async fn authorize(
+        &self,
+        user: &CurrentUser,
+        required: UserRole,
+        cluster_id: Option<i64>,
+    ) -> ApiResult<()> {
+        let role = match cluster_id {
+            Some(id) => self.effective_role(user, id).await?,
+            None => user.cap(user.role),
+        };
+
+        if role >= required {
+            return Ok(());
+        }
+
+        tracing::warn!(
+            "Permission denied for user {} (ID: {}): role {} on {} but {} required",
+            user.username,
+            user.id,
+            role,
+            cluster_id
+                .map(|id| format!("cluster {}", id))
+                .unwrap_or_else(|| "global".into()),
+            required
+        );
+
+        Err(ApiError::forbidden(format!("This action requires the {} role", required)))
+    }
+
+    // Get global role and cluster grants of a user
+    pub async fn get_user_permissions(&self, user_id: i64) -> ApiResult<UserPermissions> {
+        let role: Option<(UserRole,)> = sqlx::query_as("SELECT role FROM users WHERE id = ?")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        let (role,) =
+            role.ok_or_else(|| ApiError::not_found(format!("User {} not found", user_id)))?;
+
+        let cluster_roles: Vec<ClusterRoleGrant> = sqlx::query_as(
+            "SELECT * FROM user_cluster_roles WHERE user_id = ? ORDER BY cluster_id",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(UserPermissions { user_id, role, cluster_roles })
+    }
+
+    // Change the global role of a user
+    pub async fn set_user_role(&self, user_id: i64, role: UserRole) -> ApiResult<()> {
+        let current: Option<(UserRole,)> = sqlx::query_as("SELECT role FROM users WHERE id = ?")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        let (current,) =
+            current.ok_or_else(|| ApiError::not_found(format!("User {} not found", user_id)))?;
+        if current == UserRole::Admin && role != UserRole::Admin {
+            ensure_other_active_admin(&self.pool, user_id).await?;
+        }
+
+        sqlx::query("UPDATE users SET role = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+            .bind(role)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        tracing::info!("Global role of user {} set to {}", user_id, role);
+        Ok(())
+    }
+
+    // Grant (or replace) a role on a single cluster
+    pub async fn grant_cluster_role(
+        &self,
+        user_id: i64,
+        cluster_id: i64,
+        role: UserRole,
+    ) -> ApiResult<ClusterRoleGrant> {
+        let user_exists: Option<(i64,)> = sqlx::query_as("SELECT id FROM users WHERE id = ?")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        if user_exists.is_none() {
+            return Err(ApiError::not_found(format!("User {} not found", user_id)));
+        }
+
+        let cluster_exists: Option<(i64,)> = sqlx::query_as("SELECT id FROM clusters WHERE id = ?")
+            .bind(cluster_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        if cluster_exists.is_none() {
+            return Err(ApiError::cluster_not_found(cluster_id));
+        }
+
+        sqlx::query(
+            "INSERT INTO user_cluster_roles (user_id, cluster_id, role) VALUES (?, ?, ?)
+             ON CONFLICT(user_id, cluster_id) DO UPDATE SET
+             role = excluded.role,
+             updated_at = CURRENT_TIMESTAMP",
+        )
+        .bind(user_id)
+        .bind(cluster_id)
+        .bind(role)
+        .execute(&self.pool)
+        .await?;
+
+        let grant: ClusterRoleGrant =
+            sqlx::query_as("SELECT * FROM user_cluster_roles WHERE user_id = ? AND cluster_id = ?")
+                .bind(user_id)
+                .bind(cluster_id)
+                .fetch_one(&self.pool)
+                .await?;
+
+        tracing::info!("Granted {} on cluster {} to user {}", role, cluster_id, user_id);
+        Ok(grant)
+    }
+
+    // Remove a per-cluster grant (the user falls back to the global role)
+    pub async fn revoke_cluster_role(&self, user_id: i64, cluster_id: i64) -> ApiResult<()> {
+        let result =
+            sqlx::query("DELETE FROM user_cluster_roles WHERE user_id = ? AND cluster_id = ?")
+                .bind(user_id)
+                .bind(cluster_id)
+                .execute(&self.pool)
+                .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(ApiError::not_found(format!(
+                "No role grant for user {} on cluster {}",
+                user_id, cluster_id
+            )));
+        }
+
+        tracing::info!("Revoked cluster {} role of user {}", cluster_id, user_id);
+        Ok(())
+    }
+}
+
+/// Refuse to lock everyone out by removing the last usable admin
+pub(crate) async fn ensure_other_active_admin(pool: &SqlitePool, user_id: i64) -> ApiResult<()> {
+    let (count,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM users WHERE role = 'admin' AND is_disabled = 0 AND id != ?",
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+
+    if count == 0 {
+        return Err(ApiError::validation_error("At least one active admin must remain"));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_pool;
+
+    async fn add_user(pool: &SqlitePool, username: &str, role: UserRole) -> i64 {
+        sqlx::query("INSERT INTO users (username, password_hash, role) VALUES (?, '', ?)")
+            .bind(username)
+            .bind(role)
+            .execute(pool)
+            .await
+            .unwrap()
+            .last_insert_rowid()
+    }
+
+    #[tokio::test]
+    async fn test_last_admin_cannot_be_demoted() {
+        let pool = test_pool().await;
+        let service = PermissionService::new(pool.clone());
+        // The default admin created by the migrations is the only admin
+        let (admin,): (i64,) = sqlx::query_as("SELECT id FROM users WHERE username = 'admin'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let viewer = add_user(&pool, "viewer", UserRole::Viewer).await;
+
+        let err = service
+            .set_user_role(admin, UserRole::Operator)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ApiError::ValidationError(_)), "{:?}", err);
+        let permissions = service.get_user_permissions(admin).await.unwrap();
+        assert_eq!(permissions.role, UserRole::Admin);
+
+        // Admins may keep their role, and can be demoted once another admin exists
+        service.set_user_role(admin, UserRole::Admin).await.unwrap();
+        service
+            .set_user_role(viewer, UserRole::Admin)
+            .await
+            .unwrap();
+        service
+            .set_user_role(admin, UserRole::Viewer)
+            .await
+            .unwrap();
+        assert!(
+            service
+                .set_user_role(viewer, UserRole::Viewer)
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,435 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, de::DeserializeOwned};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+use crate::config::QueryCacheConfig;
+use crate::models::StatementCategory;
+use crate::utils::ApiResult;
+use crate::utils::sql;
+
+/// Statistics of the query result cache, as returned by `/api/system/query-cache`
+#[derive(Debug, Serialize, ToSchema)]
+pub struct QueryCacheStats {
+    pub enabled: bool,
+    pub entries: usize,
+    /// Encoded size of all cached results
+    pub bytes: u64,
+    pub max_entries: usize,
+    pub max_bytes: u64,
+    pub max_ttl_secs: u64,
+    pub hits: u64,
+    pub misses: u64,
+    /// Results stored after a miss
+    pub stores: u64,
+    /// Results dropped to stay within the limits, or because they expired
+    pub evictions: u64,
+    /// Results dropped because a cluster changed or the cache was cleared
+    pub invalidations: u64,
+    /// Hits per lookup, 0 before the first lookup
+    pub hit_ratio: f64,
+}
+
+/// Result of `QueryCache::get_or_load`
+#[derive(Debug)]
+pub struct Cached<T> {
+    pub value: T,
+    /// When the result was loaded from the cluster; `None` if it just was
+    pub cached_at: Option<DateTime<Utc>>,
+}
+
+impl<T> Cached<T> {
+    fn fresh(value: T) -> Self {
+        Self { value, cached_at: None }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    cluster_id: i64,
+    catalog: Option<String>,
+    database: Option<String>,
+    /// Normalized SQL
+    sql: String,
+    /// Type the result is stored as, so raw and typed results of the same SQL
+    /// do not collide
+    shape: &'static str,
+}
+
+struct Entry {
+    /// Result encoded as JSON
+    value: Vec<u8>,
+    stored_at: DateTime<Utc>,
+    expires_at: Instant,
+    last_used: Instant,
+}
+
+#[derive(Default)]
+struct Entries {
+    map: HashMap<CacheKey, Entry>,
+    bytes: u64,
+    /// Bumped on every invalidation, so a result loaded while a cluster changed is not stored
+    generation: u64,
+}
+
+impl Entries {
+    fn remove(&mut self, key: &CacheKey) -> Option<Entry> {
+        let entry = self.map.remove(key)?;
+        self.bytes -= entry.value.len() as u64;
+        Some(entry)
+    }
+
+    fn remove_where(&mut self, mut remove: impl FnMut(&CacheKey, &Entry) -> bool) -> usize {
+        let keys: Vec<CacheKey> = self
+            .map
+            .iter()
+            .filter(|(key, entry)| remove(key, entry))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &keys {
+            self.remove(key);
+        }
+        keys.len()
+    }
+}
+
+/// Results of read-only queries, kept in memory for a TTL chosen by the caller
+///
+/// Results are keyed by cluster, catalog, database and normalized SQL. Callers
+/// opt in by passing a TTL above 0, capped by `max_ttl_secs`; statements that
+/// are not a single query are never cached. When the cache is full the least
+/// recently used results are evicted. Writes through the admin, cluster
+/// changes and explicit requests invalidate a cluster's results.
+pub struct QueryCache {
+    config: QueryCacheConfig,
+    entries: Mutex<Entries>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    stores: AtomicU64,
+    evictions: AtomicU64,
+    invalidations: AtomicU64,
+}
+
+impl QueryCache {
+    pub fn new(config: QueryCacheConfig) -> Self {
+        Self {
+            config,
+            entries: Mutex::new(Entries::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            stores: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+            invalidations: AtomicU64::new(0),
+        }
+    }
+
+    /// TTL the overview page uses for its cluster queries, 0 if it should not cache
+    pub fn overview_ttl_secs(&self) -> u64 {
+        self.config.overview_ttl_secs
+    }
+
+    /// Cached result of `sql`, or the result of `load` which is then cached for
+    /// `ttl_secs`
+    ///
+    /// `load` runs uncached when `ttl_secs` is 0, the cache is disabled or
+    /// `sql` is not a single read-only query. Failures are not cached.
+    pub async fn get_or_load<T, F, Fut>(
+        &self,
+        cluster_id: i64,
+        catalog: Option<&str>,
+        database: Option<&str>,
+        sql: &str,
+        ttl_secs: u64,
+        load: F,
+    ) -> ApiResult<Cached<T>>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = ApiResult<T>>,
+    {
+        let ttl_secs = ttl_secs.min(self.config.max_ttl_secs);
+        let key = (self.config.enabled && ttl_secs > 0)
+            .then(|| Self::key::<T>(cluster_id, catalog, database, sql))
+            .flatten();
+        let Some(key) = key else {
+            return load().await.map(Cached::fresh);
+        };
+
+        let generation = match self.get(&key) {
+            Ok(hit) => return Ok(hit),
+            Err(generation) => generation,
+        };
+        let value = load().await?;
+        self.put(key, &value, ttl_secs, generation);
+        Ok(Cached::fresh(value))
+    }
+
+    /// Drop the cached results of a cluster, returning how many were dropped
+    pub fn invalidate_cluster(&self, cluster_id: i64) -> usize {
+        let mut entries = self.entries.lock().unwrap();
+        entries.generation += 1;
+        let removed = entries.remove_where(|key, _| key.cluster_id == cluster_id);
+        self.invalidations
+            .fetch_add(removed as u64, Ordering::Relaxed);
+        if removed > 0 {
+            tracing::debug!("Invalidated {} cached results of cluster {}", removed, cluster_id);
+        }
+        removed
+    }
+
+    /// Drop the cached results of a cluster if `sql` may have changed its data
+    /// or schema, i.e. has any statement that is not a read
+    pub fn invalidate_if_write(&self, cluster_id: i64, query: &str) -> usize {
+        let writes = sql::split_statements(query).map_or(true, |statements| {
+            statements.iter().any(|statement| {
+                sql::classify(statement.text)
+                    .map_or(true, |class| class.category != StatementCategory::Read)
+            })
+        });
+        if writes { self.invalidate_cluster(cluster_id) } else { 0 }
+    }
+
+    /// Drop every cached result, returning how many were dropped
+    pub fn clear(&self) -> usize {
+        let mut entries = self.entries.lock().unwrap();
+        entries.generation += 1;
+        let removed = entries.map.len();
+        entries.map.clear();
+        entries.bytes = 0;
+        self.invalidations
+            .fetch_add(removed as u64, Ordering::Relaxed);
+        removed
+    }
+
+    pub fn stats(&self) -> QueryCacheStats {
+        let (entries, bytes) = {
+            let entries = self.entries.lock().unwrap();
+            (entries.map.len(), entries.bytes)
+        };
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let lookups = hits + misses;
+        QueryCacheStats {
+            enabled: self.config.enabled,
+            entries,
+            bytes,
+            max_entries: self.config.max_entries,
+            max_bytes: self.config.max_bytes,
+            max_ttl_secs: self.config.max_ttl_secs,
+            hits,
+            misses,
+            stores: self.stores.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            invalidations: self.invalidations.load(Ordering::Relaxed),
+            hit_ratio: if lookups == 0 { 0.0 } else { hits as f64 / lookups as f64 },
+        }
+    }
+
+    fn key<T>(
+        cluster_id: i64,
+        catalog: Option<&str>,
+        database: Option<&str>,
+        query: &str,
+    ) -> Option<CacheKey> {
+        if !sql::is_read_only_query(query) {
+            return None;
+        }
+        Some(CacheKey {
+            cluster_id,
+            catalog: catalog.filter(|c| !c.is_empty()).map(str::to_string),
+            database: database.filter(|d| !d.is_empty()).map(str::to_string),
+            sql: sql::normalize(query).ok()?,
+            shape: std::any::type_name::<T>(),
+        })
+    }
+
+    // Cached value of `key`, or the current generation to store a loaded value with
+    fn get<T: DeserializeOwned>(&self, key: &CacheKey) -> Result<Cached<T>, u64> {
+        let mut entries = self.entries.lock().unwrap();
+        let now = Instant::now();
+        let hit = match entries.map.get_mut(key) {
+            Some(entry) if entry.expires_at > now => {
+                entry.last_used = now;
+                serde_json::from_slice(&entry.value)
+                    .ok()
+                    .map(|value| Cached { value, cached_at: Some(entry.stored_at) })
+            },
+            _ => None,
+        };
+        match hit {
+            Some(hit) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Ok(hit)
+            },
+            None => {
+                if entries.remove(key).is_some() {
+                    self.evictions.fetch_add(1, Ordering::Relaxed);
+                }
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                Err(entries.generation)
+            },
+        }
+    }
+
+    fn put<T: Serialize>(&self, key: CacheKey, value: &T, ttl_secs: u64, generation: u64) {
+        let Ok(value) = serde_json::to_vec(value) else {
+            return;
+        };
+        let size = value.len() as u64;
+        if size > self.config.max_entry_bytes {
+            tracing::debug!("Result of {} bytes is too large to cache: {}", size, key.sql);
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        if entries.generation != generation {
+            return;
+        }
+        let now = Instant::now();
+        entries.remove(&key);
+        let mut evicted = 0;
+        if entries.map.len() >= self.config.max_entries
+            || entries.bytes + size > self.config.max_bytes
+        {
+            evicted += entries.remove_where(|_, entry| entry.expires_at <= now);
+        }
+        while !entries.map.is_empty()
+            && (entries.map.len() >= self.config.max_entries
+                || entries.bytes + size > self.config.max_bytes)
+        {
+            let oldest = entries
+                .map
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+                evicted += 1;
+            }
+        }
+
+        entries.bytes += size;
+        entries.map.insert(
+            key,
+            Entry {
+                value,
+                stored_at: Utc::now(),
+                expires_at: now + Duration::from_secs(ttl_secs),
+                last_used: now,
+            },
+        );
+        self.stores.fetch_add(1, Ordering::Relaxed);
+        self.evictions.fetch_add(evicted as u64, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::ApiError;
+
+    fn cache(max_entries: usize, max_bytes: u64) -> QueryCache {
+        QueryCache::new(QueryCacheConfig {
+            max_entries,
+            max_bytes,
+            max_entry_bytes: max_bytes,
+            ..QueryCacheConfig::default()
+        })
+    }
+
+    async fn load(
+        cache: &QueryCache,
+        cluster_id: i64,
+        sql: &str,
+        ttl_secs: u64,
+        value: &str,
+    ) -> Cached<String> {
+        let value = value.to_string();
+        cache
+            .get_or_load(cluster_id, None, Some("db"), sql, ttl_secs, || async { Ok(value) })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_hits_misses_and_invalidation() {
+        let cache = cache(10, 1024);
+
+        let first = load(&cache, 1, "SELECT * FROM t", 60, "a").await;
+        assert_eq!(first.value, "a");
+        assert!(first.cached_at.is_none());
+        // Same query written differently
+        let second = load(&cache, 1, "select * from t; -- again", 60, "b").await;
+        assert_eq!(second.value, "b");
+        let second = load(&cache, 1, "SELECT  *\nFROM t;", 60, "c").await;
+        assert_eq!(second.value, "a");
+        assert!(second.cached_at.is_some());
+
+        // Other cluster, no TTL and writes are not served from the cache
+        assert_eq!(load(&cache, 2, "SELECT * FROM t", 60, "d").await.value, "d");
+        assert_eq!(load(&cache, 1, "SELECT * FROM t", 0, "e").await.value, "e");
+        assert_eq!(load(&cache, 1, "DELETE FROM t", 60, "f").await.value, "f");
+        assert_eq!(load(&cache, 1, "DELETE FROM t", 60, "g").await.value, "g");
+
+        // Failures are not cached
+        let failed: ApiResult<Cached<String>> = cache
+            .get_or_load(1, None, None, "SELECT 1", 60, || async {
+                Err(ApiError::internal_error("down"))
+            })
+            .await;
+        assert!(failed.is_err());
+        assert_eq!(cache.stats().entries, 3);
+
+        assert_eq!(cache.invalidate_if_write(1, "USE db; SELECT 1"), 0);
+        assert_eq!(cache.invalidate_if_write(1, "SELECT 1; INSERT INTO t VALUES (1)"), 2);
+        assert_eq!(load(&cache, 1, "SELECT * FROM t", 60, "h").await.value, "h");
+        assert_eq!(load(&cache, 2, "SELECT * FROM t", 60, "i").await.value, "d");
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.invalidations), (2, 5, 2));
+        assert_eq!(cache.clear(), 2);
+        assert_eq!(cache.stats().bytes, 0);
+    }
+
+    #[tokio::test]
+    async fn test_limits_evict_least_recently_used() {
+        let cache = cache(2, 1024);
+        load(&cache, 1, "SELECT 1", 60, "one").await;
+        load(&cache, 1, "SELECT 2", 60, "two").await;
+        // Using SELECT 1 makes SELECT 2 the least recently used
+        assert_eq!(load(&cache, 1, "SELECT 1", 60, "x").await.value, "one");
+        load(&cache, 1, "SELECT 3", 60, "three").await;
+        assert_eq!(load(&cache, 1, "SELECT 1", 60, "x").await.value, "one");
+        assert_eq!(load(&cache, 1, "SELECT 2", 60, "x").await.value, "x");
+        assert_eq!(cache.stats().entries, 2);
+
+        // Results above max_entry_bytes are returned but not stored
+        let cache = QueryCache::new(QueryCacheConfig {
+            max_entry_bytes: 16,
+            ..QueryCacheConfig::default()
+        });
+        let large = "x".repeat(100);
+        assert_eq!(load(&cache, 1, "SELECT 4", 60, &large).await.value, large);
+        assert_eq!(cache.stats().entries, 0);
+        assert_eq!(load(&cache, 1, "SELECT 4", 60, "small").await.value, "small");
+        assert_eq!(cache.stats().bytes, "\"small\"".len() as u64);
+    }
+
+    #[tokio::test]
+    async fn test_results_loaded_during_invalidation_are_not_stored() {
+        let cache = cache(10, 1024);
+        let value = cache
+            .get_or_load(1, None, None, "SELECT 1", 60, || async {
+                cache.invalidate_cluster(1);
+                Ok("stale".to_string())
+            })
+            .await
+            .unwrap();
+        assert_eq!(value.value, "stale");
+        assert_eq!(cache.stats().entries, 0);
+    }
+}
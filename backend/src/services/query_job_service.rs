@@ -0,0 +1,551 @@
+use chrono::{Duration, Utc};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use tokio::task::AbortHandle;
+
+use crate::config::QueryJobConfig;
+use crate::models::{
+    Cluster, CurrentUser, QueryJob, QueryJobResults, QueryJobStatus, QueryStreamEvent,
+    QueryStreamRequest, ResultColumn, StreamTruncation, SubmitQueryJobRequest, UserRole,
+};
+use crate::services::QueryStreamService;
+use crate::utils::{ApiError, ApiResult, ScheduledTask};
+
+/// Rows written to SQLite per transaction
+const ROW_BATCH: usize = 500;
+const DEFAULT_PAGE_SIZE: i64 = 1000;
+const MAX_PAGE_SIZE: i64 = 10_000;
+/// Jobs returned by a listing
+const LIST_LIMIT: i64 = 100;
+
+/// How a job's query ended
+struct JobOutcome {
+    truncated: Option<StreamTruncation>,
+    execution_time_ms: u128,
+}
+
+/// Owner whose jobs a caller may see, `None` (all jobs) for admins of the cluster
+///
+/// `role` is the caller's effective role on the cluster, already capped by the scope
+/// of the API token the request was made with.
+pub fn visible_owner(user: &CurrentUser, role: UserRole) -> Option<i64> {
+    (role < UserRole::Admin).then_some(user.id)
+}
+
+/// Runs queries in the background and keeps their rows in SQLite
+///
+/// Rows are read the same way as for `/queries/stream`, so cancelling a job,
+/// which drops its stream, kills the query on the connection that runs it.
+pub struct QueryJobService {
+    pool: SqlitePool,
+    query_stream_service: Arc<QueryStreamService>,
+    config: QueryJobConfig,
+    /// Tasks of the jobs running in this process
+    running: Arc<Mutex<HashMap<i64, AbortHandle>>>,
+}
+
+impl QueryJobService {
+    pub fn new(
+        pool: SqlitePool,
+        query_stream_service: Arc<QueryStreamService>,
+        config: QueryJobConfig,
+    ) -> Self {
+        Self { pool, query_stream_service, config, running: Arc::default() }
+    }
+
+    // Jobs still marked running were cut off by a restart and will never finish
+    pub async fn fail_interrupted(&self) -> ApiResult<()> {
+        let now = Utc::now();
+        let result = sqlx::query(
+            "UPDATE query_jobs SET status = ?, error_message = ?, finished_at = ?, expires_at = ? \
+             WHERE status = ?",
+        )
+        .bind(QueryJobStatus::Failed)
+        .bind("Interrupted by a server restart")
+        .bind(now)
+        .bind(now + self.retention())
+        .bind(QueryJobStatus::Running)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() > 0 {
+            tracing::warn!("Marked {} interrupted query jobs as failed", result.rows_affected());
+        }
+        Ok(())
+    }
+
+    pub async fn submit(
+        &self,
+        cluster: &Cluster,
+        user_id: i64,
+        request: SubmitQueryJobRequest,
+    ) -> ApiResult<QueryJob> {
+        if request.sql.trim().is_empty() {
+            return Err(ApiError::validation_error("SQL must not be empty"));
+        }
+        let (running,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM query_jobs WHERE user_id = ? AND status = ?")
+                .bind(user_id)
+                .bind(QueryJobStatus::Running)
+                .fetch_one(&self.pool)
+                .await?;
+        if running >= self.config.max_running_per_user {
+            return Err(ApiError::validation_error(format!(
+                "At most {} query jobs may run at the same time; wait for one to finish or cancel it",
+                self.config.max_running_per_user
+            )));
+        }
+
+        let id = sqlx::query(
+            "INSERT INTO query_jobs (cluster_id, user_id, sql_text, catalog, database_name, status, \
+             created_at) VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(cluster.id)
+        .bind(user_id)
+        .bind(&request.sql)
+        .bind(&request.catalog)
+        .bind(&request.database)
+        .bind(QueryJobStatus::Running)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?
+        .last_insert_rowid();
+        tracing::info!(
+            "Query job {} submitted on cluster {} by user {}",
+            id,
+            cluster.name,
+            user_id
+        );
+
+        let stream_request = QueryStreamRequest {
+            sql: request.sql,
+            catalog: request.catalog,
+            database: request.database,
+            max_rows: Some(self.config.max_rows),
+            max_bytes: Some(self.config.max_bytes),
+        };
+        let task = {
+            let pool = self.pool.clone();
+            let query_stream_service = Arc::clone(&self.query_stream_service);
+            let running = Arc::clone(&self.running);
+            let cluster = cluster.clone();
+            let retention = self.retention();
+            async move {
+                let outcome = run(&pool, &query_stream_service, &cluster, id, stream_request).await;
+                running.lock().unwrap().remove(&id);
+                if let Err(e) = finish(&pool, id, outcome, retention).await {
+                    tracing::error!("Failed to record the end of query job {}: {}", id, e);
+                }
+            }
+        };
+        // Registered under the lock so that the task cannot finish, and
+        // unregister, before it is registered
+        {
+            let mut running = self.running.lock().unwrap();
+            running.insert(id, tokio::spawn(task).abort_handle());
+        }
+
+        self.get(id).await
+    }
+
+    /// Job that has not expired yet
+    pub async fn get(&self, id: i64) -> ApiResult<QueryJob> {
+        sqlx::query_as(
+            "SELECT * FROM query_jobs WHERE id = ? AND (expires_at IS NULL OR expires_at > ?)",
+        )
+        .bind(id)
+        .bind(Utc::now())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("Query job {} not found", id)))
+    }
+
+    /// Jobs on a cluster, newest first; of one user unless `user_id` is `None`
+    pub async fn list(&self, cluster_id: i64, user_id: Option<i64>) -> ApiResult<Vec<QueryJob>> {
+        let jobs = sqlx::query_as(
+            "SELECT * FROM query_jobs WHERE cluster_id = ? AND (? IS NULL OR user_id = ?) \
+             AND (expires_at IS NULL OR expires_at > ?) ORDER BY id DESC LIMIT ?",
+        )
+        .bind(cluster_id)
+        .bind(user_id)
+        .bind(user_id)
+        .bind(Utc::now())
+        .bind(LIST_LIMIT)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(jobs)
+    }
+
+    /// Rows from `offset`, available while the job is still running
+    pub async fn results(
+        &self,
+        job: QueryJob,
+        offset: Option<i64>,
+        limit: Option<i64>,
+    ) -> ApiResult<QueryJobResults> {
+        let offset = offset.unwrap_or(0).max(0);
+        let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+
+        let (columns,): (Option<String>,) =
+            sqlx::query_as("SELECT columns FROM query_jobs WHERE id = ?")
+                .bind(job.id)
+                .fetch_one(&self.pool)
+                .await?;
+        let columns: Vec<ResultColumn> = match columns {
+            Some(json) => serde_json::from_str(&json)?,
+            None => Vec::new(),
+        };
+
+        let stored: Vec<(String,)> = sqlx::query_as(
+            "SELECT row_values FROM query_job_rows WHERE job_id = ? AND row_index >= ? \
+             ORDER BY row_index LIMIT ?",
+        )
+        .bind(job.id)
+        .bind(offset)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        let rows = stored
+            .into_iter()
+            .map(|(json,)| serde_json::from_str(&json))
+            .collect::<Result<_, _>>()?;
+
+        Ok(QueryJobResults { job, columns, rows, offset })
+    }
+
+    /// Stop a running job; its query is killed and the rows so far are kept
+    pub async fn cancel(&self, job: QueryJob) -> ApiResult<QueryJob> {
+        if job.status != QueryJobStatus::Running {
+            return Err(ApiError::validation_error(format!("Query job {} is not running", job.id)));
+        }
+        // Dropping the task drops its stream, which kills the query. A job
+        // missing here was left running by another process and just gets marked.
+        if let Some(task) = self.running.lock().unwrap().remove(&job.id) {
+            task.abort();
+        }
+
+        let now = Utc::now();
+        sqlx::query(
+            "UPDATE query_jobs SET status = ?, finished_at = ?, expires_at = ? \
+             WHERE id = ? AND status = ?",
+        )
+        .bind(QueryJobStatus::Cancelled)
+        .bind(now)
+        .bind(now + self.retention())
+        .bind(job.id)
+        .bind(QueryJobStatus::Running)
+        .execute(&self.pool)
+        .await?;
+        tracing::info!("Query job {} cancelled", job.id);
+
+        self.get(job.id).await
+    }
+
+    // Delete jobs past their retention together with their rows
+    pub async fn purge_expired(&self) -> ApiResult<u64> {
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "DELETE FROM query_job_rows WHERE job_id IN \
+             (SELECT id FROM query_jobs WHERE expires_at <= ?)",
+        )
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+        let deleted = sqlx::query("DELETE FROM query_jobs WHERE expires_at <= ?")
+            .bind(now)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        tx.commit().await?;
+
+        if deleted > 0 {
+            tracing::info!("Deleted {} expired query jobs", deleted);
+        }
+        Ok(deleted)
+    }
+
+    fn retention(&self) -> Duration {
+        Duration::hours(self.config.retention_hours as i64)
+    }
+}
+
+impl ScheduledTask for QueryJobService {
+    fn run(&self) -> Pin<Box<dyn Future<Output = Result<(), anyhow::Error>> + Send + '_>> {
+        Box::pin(async move {
+            self.purge_expired().await?;
+            Ok(())
+        })
+    }
+
+    fn name(&self) -> &str {
+        "query-job-cleanup"
+    }
+}
+
+// Run the query of job `id` and store its columns and rows as they arrive
+async fn run(
+    pool: &SqlitePool,
+    query_stream_service: &QueryStreamService,
+    cluster: &Cluster,
+    id: i64,
+    request: QueryStreamRequest,
+) -> ApiResult<JobOutcome> {
+    let mut events = query_stream_service.start(cluster, request).await?;
+    let mut batch = Vec::with_capacity(ROW_BATCH);
+    let mut stored = 0i64;
+
+    while let Some(event) = events.recv().await {
+        match event {
+            QueryStreamEvent::Columns { columns } => {
+                sqlx::query("UPDATE query_jobs SET columns = ? WHERE id = ?")
+                    .bind(serde_json::to_string(&columns)?)
+                    .bind(id)
+                    .execute(pool)
+                    .await?;
+            },
+            QueryStreamEvent::Row { values } => {
+                batch.push(values);
+                if batch.len() >= ROW_BATCH {
+                    stored = save_rows(pool, id, stored, &mut batch).await?;
+                }
+            },
+            QueryStreamEvent::End { truncated, execution_time_ms, .. } => {
+                save_rows(pool, id, stored, &mut batch).await?;
+                return Ok(JobOutcome { truncated, execution_time_ms });
+            },
+            QueryStreamEvent::Error { message } => {
+                // Keep what arrived before the error
+                save_rows(pool, id, stored, &mut batch).await?;
+                return Err(ApiError::internal_error(message));
+            },
+        }
+    }
+    Err(ApiError::internal_error("Query stream ended unexpectedly"))
+}
+
+/// Append `batch` after the `stored` rows; returns the new row count
+async fn save_rows(
+    pool: &SqlitePool,
+    id: i64,
+    stored: i64,
+    batch: &mut Vec<Vec<serde_json::Value>>,
+) -> ApiResult<i64> {
+    let mut row_index = stored;
+    let mut tx = pool.begin().await?;
+    for values in batch.drain(..) {
+        sqlx::query("INSERT INTO query_job_rows (job_id, row_index, row_values) VALUES (?, ?, ?)")
+            .bind(id)
+            .bind(row_index)
+            .bind(serde_json::to_string(&values)?)
+            .execute(&mut *tx)
+            .await?;
+        row_index += 1;
+    }
+    sqlx::query("UPDATE query_jobs SET row_count = ? WHERE id = ?")
+        .bind(row_index)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(row_index)
+}
+
+// Record how a job ended, unless it was cancelled meanwhile
+async fn finish(
+    pool: &SqlitePool,
+    id: i64,
+    outcome: ApiResult<JobOutcome>,
+    retention: Duration,
+) -> ApiResult<()> {
+    let (status, truncated, error_message, execution_time_ms) = match outcome {
+        Ok(outcome) => (
+            QueryJobStatus::Succeeded,
+            outcome.truncated,
+            None,
+            Some(outcome.execution_time_ms as i64),
+        ),
+        Err(e) => (QueryJobStatus::Failed, None, Some(e.to_string()), None),
+    };
+    tracing::info!("Query job {} ended as {:?}", id, status);
+
+    let now = Utc::now();
+    sqlx::query(
+        "UPDATE query_jobs SET status = ?, truncated = ?, error_message = ?, execution_time_ms = ?, \
+         finished_at = ?, expires_at = ? WHERE id = ? AND status = ?",
+    )
+    .bind(status)
+    .bind(truncated)
+    .bind(error_message)
+    .bind(execution_time_ms)
+    .bind(now)
+    .bind(now + retention)
+    .bind(id)
+    .bind(QueryJobStatus::Running)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ApiTokenGrant;
+    use crate::services::{MySQLPoolManager, PermissionService};
+    use serde_json::json;
+
+    async fn test_service() -> QueryJobService {
+        let pool = crate::db::test_pool().await;
+        sqlx::query(
+            "INSERT INTO clusters (name, fe_host, username, password_encrypted) \
+             VALUES ('prod', 'fe', 'root', '')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let query_stream_service = Arc::new(QueryStreamService::new(
+            Arc::new(MySQLPoolManager::new(Default::default())),
+            Default::default(),
+        ));
+        QueryJobService::new(pool, query_stream_service, QueryJobConfig::default())
+    }
+
+    async fn insert_job(service: &QueryJobService, user_id: i64) -> i64 {
+        sqlx::query(
+            "INSERT INTO query_jobs (cluster_id, user_id, sql_text, status, created_at) \
+             VALUES (1, ?, 'SELECT 1', 'running', ?)",
+        )
+        .bind(user_id)
+        .bind(Utc::now())
+        .execute(&service.pool)
+        .await
+        .unwrap()
+        .last_insert_rowid()
+    }
+
+    #[tokio::test]
+    async fn test_rows_are_paged_in_order() {
+        let service = test_service().await;
+        let id = insert_job(&service, 7).await;
+
+        let mut batch: Vec<Vec<serde_json::Value>> = (0..5).map(|i| vec![json!(i)]).collect();
+        let stored = save_rows(&service.pool, id, 0, &mut batch).await.unwrap();
+        let mut batch = vec![vec![json!(5)], vec![json!(6)]];
+        let stored = save_rows(&service.pool, id, stored, &mut batch)
+            .await
+            .unwrap();
+        assert_eq!(stored, 7);
+        let outcome =
+            Ok(JobOutcome { truncated: Some(StreamTruncation::MaxRows), execution_time_ms: 9 });
+        finish(&service.pool, id, outcome, Duration::hours(1))
+            .await
+            .unwrap();
+
+        let job = service.get(id).await.unwrap();
+        assert_eq!(job.status, QueryJobStatus::Succeeded);
+        assert_eq!(job.row_count, 7);
+        assert_eq!(job.truncated, Some(StreamTruncation::MaxRows));
+        let page = service.results(job, Some(5), Some(10)).await.unwrap();
+        assert_eq!(page.rows, vec![vec![json!(5)], vec![json!(6)]]);
+        assert!(page.columns.is_empty());
+
+        assert_eq!(service.list(1, Some(7)).await.unwrap().len(), 1);
+        assert!(service.list(1, Some(8)).await.unwrap().is_empty());
+        assert_eq!(service.list(1, None).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_wins_over_late_finish() {
+        let service = test_service().await;
+        let id = insert_job(&service, 7).await;
+
+        let job = service
+            .cancel(service.get(id).await.unwrap())
+            .await
+            .unwrap();
+        assert_eq!(job.status, QueryJobStatus::Cancelled);
+        assert!(job.expires_at.is_some());
+        assert!(service.cancel(job).await.is_err());
+
+        let outcome = Err(ApiError::internal_error("killed"));
+        finish(&service.pool, id, outcome, Duration::hours(1))
+            .await
+            .unwrap();
+        assert_eq!(service.get(id).await.unwrap().status, QueryJobStatus::Cancelled);
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_and_expired_jobs() {
+        let service = test_service().await;
+        let interrupted = insert_job(&service, 7).await;
+        let expired = insert_job(&service, 7).await;
+        let mut batch = vec![vec![json!(1)]];
+        save_rows(&service.pool, expired, 0, &mut batch)
+            .await
+            .unwrap();
+        finish(&service.pool, expired, Err(ApiError::internal_error("boom")), Duration::hours(-1))
+            .await
+            .unwrap();
+
+        // Expired jobs are hidden before the cleanup removes them
+        assert!(service.get(expired).await.is_err());
+        assert_eq!(service.purge_expired().await.unwrap(), 1);
+        let (rows,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM query_job_rows")
+            .fetch_one(&service.pool)
+            .await
+            .unwrap();
+        assert_eq!(rows, 0);
+
+        service.fail_interrupted().await.unwrap();
+        let job = service.get(interrupted).await.unwrap();
+        assert_eq!(job.status, QueryJobStatus::Failed);
+        assert_eq!(job.error_message.as_deref(), Some("Interrupted by a server restart"));
+    }
+
+    #[tokio::test]
+    async fn test_jobs_visible_to_effective_admins_only() {
+        let service = test_service().await;
+        let permissions = PermissionService::new(service.pool.clone());
+        let own = insert_job(&service, 1).await;
+        insert_job(&service, 7).await;
+        let visible = |user: CurrentUser| {
+            let (service, permissions) = (&service, &permissions);
+            async move {
+                let role = permissions.effective_role(&user, 1).await.unwrap();
+                let jobs = service.list(1, visible_owner(&user, role)).await.unwrap();
+                jobs.into_iter().map(|job| job.id).collect::<Vec<_>>()
+            }
+        };
+
+        // The seeded admin sees every job from a session, only its own with a read token
+        let mut admin =
+            CurrentUser { id: 1, username: "admin".into(), role: UserRole::Admin, api_token: None };
+        assert_eq!(visible(admin.clone()).await.len(), 2);
+        admin.api_token = Some(ApiTokenGrant { id: 1, max_role: UserRole::Viewer });
+        assert_eq!(visible(admin).await, vec![own]);
+
+        // A viewer granted admin on the cluster sees every job there
+        sqlx::query(
+            "INSERT INTO users (username, password_hash, role) VALUES ('ops', '', 'viewer')",
+        )
+        .execute(&service.pool)
+        .await
+        .unwrap();
+        let ops =
+            CurrentUser { id: 2, username: "ops".into(), role: UserRole::Viewer, api_token: None };
+        assert_eq!(visible(ops.clone()).await, Vec::<i64>::new());
+        sqlx::query(
+            "INSERT INTO user_cluster_roles (user_id, cluster_id, role) VALUES (2, 1, 'admin')",
+        )
+        .execute(&service.pool)
+        .await
+        .unwrap();
+        assert_eq!(visible(ops).await.len(), 2);
+    }
+}
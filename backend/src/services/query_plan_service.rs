@@ -0,0 +1,776 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use crate::config::QueryPlanConfig;
+use crate::models::{
+    Cluster, ExplainMode, ExplainRequest, PlanFragment, PlanNode, PlanWarning, PlanWarningKind,
+    QueryPlan, ScanRatio,
+};
+use crate::services::{MySQLClient, MySQLPoolManager};
+use crate::utils::sql;
+use crate::utils::{ApiError, ApiResult};
+
+/// Runs `EXPLAIN COSTS` or `EXPLAIN ANALYZE` for a query and turns the plan
+/// text into fragments of operator trees, with warnings for likely slow spots
+///
+/// Both the classic `PLAN FRAGMENT` layout of `EXPLAIN COSTS` and the tree
+/// layout of `EXPLAIN ANALYZE` are understood. Lines the parser does not know
+/// are kept in `details`, so newer StarRocks versions still render.
+pub struct QueryPlanService {
+    mysql_pool_manager: Arc<MySQLPoolManager>,
+    config: QueryPlanConfig,
+}
+
+impl QueryPlanService {
+    pub fn new(mysql_pool_manager: Arc<MySQLPoolManager>, config: QueryPlanConfig) -> Self {
+        Self { mysql_pool_manager, config }
+    }
+
+    /// Explain `request.sql` on `cluster`; with `analyze` the query is executed
+    pub async fn explain(
+        &self,
+        cluster: &Cluster,
+        request: &ExplainRequest,
+    ) -> ApiResult<QueryPlan> {
+        let query = explained_query(&request.sql)?;
+        let (mode, explain) = if request.analyze {
+            (ExplainMode::Analyze, "EXPLAIN ANALYZE")
+        } else {
+            (ExplainMode::Costs, "EXPLAIN COSTS")
+        };
+
+        let pool = self.mysql_pool_manager.get_pool(cluster).await?;
+        let (_, rows) = MySQLClient::from_pool(pool)
+            .query_raw(
+                &format!("{} {}", explain, query),
+                request.catalog.as_deref(),
+                request.database.as_deref(),
+            )
+            .await?;
+        let raw = rows
+            .into_iter()
+            .filter_map(|row| row.into_iter().next())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut plan = parse_plan(mode, raw);
+        plan.warnings = plan_warnings(&self.config, &plan.fragments);
+        Ok(plan)
+    }
+}
+
+/// Broadcast joins of large inputs, scans of every partition and scans
+/// without predicates
+pub fn plan_warnings(config: &QueryPlanConfig, fragments: &[PlanFragment]) -> Vec<PlanWarning> {
+    let mut warnings = Vec::new();
+    for fragment in fragments {
+        if let Some(root) = &fragment.root {
+            check_node(config, fragments, fragment.id, root, &mut warnings);
+        }
+    }
+    warnings
+}
+
+fn check_node(
+    config: &QueryPlanConfig,
+    fragments: &[PlanFragment],
+    fragment_id: i64,
+    node: &PlanNode,
+    warnings: &mut Vec<PlanWarning>,
+) {
+    let warn = |kind, table: Option<&String>, message| PlanWarning {
+        kind,
+        fragment_id,
+        node_id: node.id,
+        table: table.cloned(),
+        message,
+    };
+
+    if is_join(node)
+        && let Some(build) = node.children.get(1)
+        && (is_value(&node.join_strategy, "BROADCAST")
+            || is_value(&build.exchange_type, "BROADCAST"))
+    {
+        // The build side of a broadcast is usually an exchange fed by another fragment
+        let source = build
+            .id
+            .and_then(|id| fragments.iter().find(|f| f.output_exchange_id == Some(id)))
+            .and_then(|f| f.root.as_ref());
+        let rows = rows(build).or_else(|| source.and_then(rows));
+        let table = first_table(build).or_else(|| source.and_then(first_table));
+        if let Some(rows) = rows
+            && rows > config.broadcast_max_rows as f64
+        {
+            warnings.push(warn(
+                PlanWarningKind::BroadcastLargeTable,
+                table,
+                format!(
+                    "{} broadcasts about {} rows{} to every instance; a shuffle join may be cheaper",
+                    node.operator,
+                    rows as u64,
+                    table.map(|t| format!(" of {}", t)).unwrap_or_default()
+                ),
+            ));
+        }
+    }
+
+    if is_scan(node) {
+        let table = node.table.as_ref();
+        let name = table.map(String::as_str).unwrap_or("the table");
+        let full_scan = node.predicates.is_none()
+            && rows(node).is_some_and(|rows| rows >= config.full_scan_min_rows as f64);
+        if full_scan {
+            warnings.push(warn(
+                PlanWarningKind::FullScan,
+                table,
+                format!(
+                    "{} reads all of {} (about {} rows) without a predicate",
+                    node.operator,
+                    name,
+                    rows(node).unwrap_or_default() as u64
+                ),
+            ));
+        } else if let Some(partitions) = node.partitions
+            && partitions.total > 1
+            && partitions.selected == partitions.total
+        {
+            warnings.push(warn(
+                PlanWarningKind::NoPartitionPruning,
+                table,
+                format!(
+                    "All {} partitions of {} are scanned; filter on the partition column to prune them",
+                    partitions.total, name
+                ),
+            ));
+        }
+    }
+
+    for child in &node.children {
+        check_node(config, fragments, fragment_id, child, warnings);
+    }
+}
+
+/// The query to explain: a single SELECT or WITH statement
+pub fn explained_query(sql: &str) -> ApiResult<&str> {
+    let statements = sql::split_statements(sql)?;
+    let [statement] = statements.as_slice() else {
+        return Err(ApiError::validation_error(format!(
+            "Exactly one statement can be explained, found {}",
+            statements.len()
+        )));
+    };
+    match sql::leading_keyword(&sql::tokenize(statement.text)?).as_deref() {
+        Some("SELECT" | "WITH") => Ok(statement.text),
+        Some("EXPLAIN") => Err(ApiError::validation_error("Send the query without EXPLAIN")),
+        _ => Err(ApiError::validation_error("Only SELECT queries can be explained")),
+    }
+}
+
+fn is_value(value: &Option<String>, expected: &str) -> bool {
+    value
+        .as_deref()
+        .is_some_and(|v| v.eq_ignore_ascii_case(expected))
+}
+
+fn is_join(node: &PlanNode) -> bool {
+    node.operator.to_ascii_uppercase().contains("JOIN")
+}
+
+fn is_scan(node: &PlanNode) -> bool {
+    node.operator.to_ascii_uppercase().contains("SCAN")
+}
+
+/// Measured rows if the query ran, estimated rows otherwise
+fn rows(node: &PlanNode) -> Option<f64> {
+    node.actual_rows
+        .map(|rows| rows as f64)
+        .or(node.estimated_rows)
+}
+
+fn first_table(node: &PlanNode) -> Option<&String> {
+    node.table
+        .as_ref()
+        .or_else(|| node.children.iter().find_map(first_table))
+}
+
+/// Parse the text of `EXPLAIN COSTS` (or plain `EXPLAIN`) or `EXPLAIN ANALYZE`
+pub fn parse_plan(mode: ExplainMode, raw: String) -> QueryPlan {
+    let mut summary = BTreeMap::new();
+    let mut fragments = Vec::new();
+    let mut fragment: Option<FragmentParser> = None;
+
+    for line in raw.lines() {
+        if let Some(id) = fragment_header(line.trim()) {
+            fragments.extend(fragment.take().map(FragmentParser::finish));
+            fragment = Some(FragmentParser::new(id));
+        } else if let Some(fragment) = &mut fragment {
+            fragment.line(line);
+        } else if line.len() - line.trim_start().len() == 4 {
+            // Top-level entries of the ANALYZE summary; deeper lines break them down
+            for (key, value) in key_values(line.trim()) {
+                if !value.is_empty() {
+                    summary.insert(key.to_string(), value.to_string());
+                }
+            }
+        }
+    }
+    fragments.extend(fragment.map(FragmentParser::finish));
+
+    QueryPlan { mode, summary, fragments, warnings: Vec::new(), raw }
+}
+
+/// Id of a `PLAN FRAGMENT 1(F03)` or `Fragment 1` line
+fn fragment_header(line: &str) -> Option<i64> {
+    let rest = line
+        .strip_prefix("PLAN FRAGMENT ")
+        .or_else(|| line.strip_prefix("Fragment "))?;
+    let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let after = &rest[digits..];
+    if !(after.is_empty() || after.starts_with('(')) {
+        return None;
+    }
+    rest[..digits].parse().ok()
+}
+
+/// Builds the operator tree of one fragment from its lines
+struct FragmentParser {
+    fragment: PlanFragment,
+    nodes: Vec<PlanNode>,
+    children: Vec<Vec<usize>>,
+    /// Nodes without a parent; the first is the root of the fragment
+    roots: Vec<usize>,
+    /// `EXPLAIN COSTS`: last node that starts at each column
+    chains: HashMap<usize, usize>,
+    /// `EXPLAIN ANALYZE`: nodes that may still get children, with their column
+    open: Vec<(usize, usize)>,
+    in_column_statistics: bool,
+}
+
+impl FragmentParser {
+    fn new(id: i64) -> Self {
+        Self {
+            fragment: PlanFragment { id, ..PlanFragment::default() },
+            nodes: Vec::new(),
+            children: Vec::new(),
+            roots: Vec::new(),
+            chains: HashMap::new(),
+            open: Vec::new(),
+            in_column_statistics: false,
+        }
+    }
+
+    fn line(&mut self, line: &str) {
+        let chars: Vec<char> = line.chars().collect();
+
+        // EXPLAIN ANALYZE: `└──OPERATOR (id=3)` or `├──<PROBE> OPERATOR`
+        if let Some(column) = chars
+            .windows(3)
+            .position(|w| matches!(w[0], '└' | '├') && w[1] == '─' && w[2] == '─')
+        {
+            while self.open.last().is_some_and(|&(open, _)| open >= column) {
+                self.open.pop();
+            }
+            let parent = self.open.last().map(|&(_, parent)| parent);
+            let header: String = chars[column + 3..].iter().collect();
+            let index = self.push(analyze_node(&header), parent, false);
+            self.open.push((column, index));
+            return;
+        }
+
+        // EXPLAIN COSTS: `  4:HASH JOIN`, or `  |----3:EXCHANGE` for further children
+        let column = chars
+            .iter()
+            .position(|c| !matches!(c, ' ' | '|' | '-'))
+            .unwrap_or(0);
+        let rest: String = chars[column..].iter().collect();
+        if let Some(node) = costs_node(&rest) {
+            let prefix: String = chars[..column].iter().collect();
+            let index = if prefix.ends_with("----") {
+                let parent = column
+                    .checked_sub(5)
+                    .and_then(|c| self.chains.get(&c))
+                    .copied();
+                self.push(node, parent, false)
+            } else {
+                // Continues the chain at this column: first child of the node above
+                let parent = self.chains.get(&column).copied();
+                self.push(node, parent, true)
+            };
+            self.chains.insert(column, index);
+            return;
+        }
+
+        let text = line.trim_start_matches([' ', '|', '│']).trim_end();
+        if text.is_empty() {
+            return;
+        }
+        if self.nodes.is_empty() {
+            self.fragment_detail(text);
+        } else {
+            self.node_detail(text);
+        }
+    }
+
+    fn push(&mut self, node: PlanNode, parent: Option<usize>, first: bool) -> usize {
+        let index = self.nodes.len();
+        if self.fragment.sink.is_none() && node.operator.to_ascii_uppercase().ends_with("SINK") {
+            // EXPLAIN ANALYZE prints the sink as the root operator
+            self.fragment.sink = Some(node.operator.clone());
+            self.fragment.output_exchange_id = self.fragment.output_exchange_id.or(node.id);
+        }
+        self.nodes.push(node);
+        self.children.push(Vec::new());
+        self.in_column_statistics = false;
+        match parent {
+            // Keeps the order of join inputs: probe side first, then the build side
+            Some(parent) if first => self.children[parent].insert(0, index),
+            Some(parent) => self.children[parent].push(index),
+            None => self.roots.push(index),
+        }
+        index
+    }
+
+    fn fragment_detail(&mut self, text: &str) {
+        let fragment = &mut self.fragment;
+        if !text.contains([':', '=']) && text.to_ascii_uppercase().ends_with("SINK") {
+            fragment.sink = Some(text.to_string());
+            return;
+        }
+        for (key, value) in key_values(text) {
+            match key.to_ascii_lowercase().as_str() {
+                "input partition" => fragment.input_partition = Some(value.to_string()),
+                "output partition" => fragment.output_partition = Some(value.to_string()),
+                "output exchange id" | "exchange id" => {
+                    fragment.output_exchange_id = value.parse().ok()
+                },
+                _ => {},
+            }
+        }
+        fragment.details.push(text.to_string());
+    }
+
+    fn node_detail(&mut self, text: &str) {
+        // Per-column statistics of `EXPLAIN COSTS` are long and not part of the plan
+        if text.starts_with("column statistics") {
+            self.in_column_statistics = true;
+            return;
+        }
+        if self.in_column_statistics && text.starts_with('*') {
+            return;
+        }
+        self.in_column_statistics = false;
+
+        let node = self.nodes.last_mut().expect("details follow a node");
+        for (key, value) in key_values(text) {
+            match key.to_ascii_lowercase().as_str() {
+                "cardinality" => node.estimated_rows = node.estimated_rows.or(number(value)),
+                "cost" => node.cost = node.cost.or(number(value)),
+                "estimates" => {
+                    let estimates = value.trim_start_matches('[').trim_end_matches(']');
+                    for (key, value) in key_values(estimates) {
+                        match key {
+                            "row" => node.estimated_rows = node.estimated_rows.or(number(value)),
+                            "cost" => node.cost = node.cost.or(number(value)),
+                            _ => {},
+                        }
+                    }
+                },
+                "outputrows" if node.actual_rows.is_none() => {
+                    // `400.000M (400000000)` or `12`
+                    let exact = value
+                        .split_once('(')
+                        .map_or(value, |(_, exact)| exact.trim_end_matches(')'));
+                    node.actual_rows = exact.trim().parse().ok();
+                },
+                "totaltime" if node.total_time.is_none() => {
+                    let time = value.split_once(" (").map_or(value, |(time, _)| time);
+                    node.total_time = Some(time.to_string());
+                },
+                "distribution type" => node.exchange_type = Some(value.to_string()),
+                "join op" => {
+                    // `INNER JOIN (BROADCAST)`
+                    let (join_type, strategy) = match value.split_once(" (") {
+                        Some((join_type, strategy)) => {
+                            (join_type, Some(strategy.trim_end_matches(')')))
+                        },
+                        None => (value, None),
+                    };
+                    node.join_type = Some(join_type.to_string());
+                    node.join_strategy = strategy.map(str::to_string);
+                },
+                "table" if node.table.is_none() => node.table = Some(value.to_string()),
+                "partitions" | "partitionsratio" => node.partitions = ratio(value),
+                "tabletratio" | "tabletsratio" => node.tablets = ratio(value),
+                "predicates" => node.predicates = Some(value.to_string()),
+                _ => {},
+            }
+        }
+        node.details.push(text.to_string());
+    }
+
+    fn finish(self) -> PlanFragment {
+        let mut nodes: Vec<Option<PlanNode>> = self.nodes.into_iter().map(Some).collect();
+        let children = self.children;
+        let mut fragment = self.fragment;
+        let mut roots = self
+            .roots
+            .into_iter()
+            .map(|root| build_tree(&mut nodes, &children, root));
+        fragment.root = roots.next().map(|mut root| {
+            // Operators whose place could not be told are kept under the root
+            root.children.extend(roots);
+            root
+        });
+        fragment
+    }
+}
+
+fn build_tree(nodes: &mut [Option<PlanNode>], children: &[Vec<usize>], index: usize) -> PlanNode {
+    let mut node = nodes[index].take().unwrap_or_default();
+    node.children = children[index]
+        .iter()
+        .map(|&child| build_tree(nodes, children, child))
+        .collect();
+    node
+}
+
+/// Node of `EXPLAIN COSTS` from `4:HASH JOIN`
+fn costs_node(text: &str) -> Option<PlanNode> {
+    let (id, operator) = text.split_once(':')?;
+    if id.is_empty() || !id.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    if !operator.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    Some(PlanNode {
+        id: id.parse().ok(),
+        operator: operator.trim().to_string(),
+        ..PlanNode::default()
+    })
+}
+
+/// Node of `EXPLAIN ANALYZE` from `HASH_JOIN (id=5) [BROADCAST, INNER JOIN]`
+fn analyze_node(header: &str) -> PlanNode {
+    let mut header = header.trim();
+    if header.starts_with('<')
+        && let Some((_, rest)) = header.split_once("> ")
+    {
+        // `<PROBE> ` and `<BUILD> ` name the side of a join
+        header = rest.trim();
+    }
+
+    let mut node = PlanNode::default();
+    let mut attributes = None;
+    if header.ends_with(']')
+        && let Some(open) = header.rfind('[')
+    {
+        attributes = Some(&header[open + 1..header.len() - 1]);
+        header = header[..open].trim();
+    }
+    if let Some(start) = header.find("(id=")
+        && let Some(end) = header[start..].find(')')
+    {
+        node.id = header[start + 4..start + end].parse().ok();
+        header = header[..start].trim();
+    }
+    node.operator = header.to_string();
+
+    if let Some(attributes) = attributes {
+        if is_join(&node) {
+            let mut parts = attributes.split(", ");
+            node.join_strategy = parts.next().map(str::to_string);
+            node.join_type = parts.next().map(str::to_string);
+        } else if node.operator.to_ascii_uppercase().contains("EXCHANGE") {
+            node.exchange_type = Some(attributes.to_string());
+        } else if is_scan(&node) {
+            node.table = Some(attributes.to_string());
+        } else {
+            node.details.push(format!("[{}]", attributes));
+        }
+    }
+    node
+}
+
+/// `key: value` or `key=value` pairs of a line such as
+/// `table: t1, rollup: t1` or `partitionsRatio=3/3, tabletsRatio=30/30`
+///
+/// Commas inside brackets and parentheses do not separate pairs. A line that
+/// is not made of pairs is returned as one pair, or none.
+fn key_values(text: &str) -> Vec<(&str, &str)> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    for (index, c) in text.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&text[start..index]);
+                start = index + 1;
+            },
+            _ => {},
+        }
+    }
+    parts.push(&text[start..]);
+
+    let pairs: Option<Vec<(&str, &str)>> = parts.iter().map(|part| key_value(part)).collect();
+    match pairs {
+        Some(pairs) => pairs,
+        None => key_value(text).into_iter().collect(),
+    }
+}
+
+fn key_value(text: &str) -> Option<(&str, &str)> {
+    let split = text.find([':', '='])?;
+    let key = text[..split].trim().trim_start_matches("- ");
+    if key.is_empty() {
+        return None;
+    }
+    Some((key, text[split + 1..].trim()))
+}
+
+fn number(value: &str) -> Option<f64> {
+    value.split_whitespace().next()?.parse().ok()
+}
+
+/// `3/10`
+fn ratio(value: &str) -> Option<ScanRatio> {
+    let (selected, total) = value.split_once('/')?;
+    Some(ScanRatio { selected: selected.trim().parse().ok()?, total: total.trim().parse().ok()? })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COSTS: &str = "\
+PLAN FRAGMENT 0(F03)
+  Output Exprs:2: k1 | 9: count
+  Input Partition: UNPARTITIONED
+  RESULT SINK
+
+  8:MERGING-EXCHANGE
+     distribution type: GATHER
+     limit: 10
+     cardinality: 10
+
+PLAN FRAGMENT 1(F00)
+  Output Exprs:2: k1 | 9: count
+  Input Partition: RANDOM
+  OutPut Partition: UNPARTITIONED
+  OutPut Exchange Id: 08
+
+  7:TOP-N
+  |  order by: [9, BIGINT, false] DESC
+  |  limit: 10
+  |  cardinality: 10
+  |
+  4:HASH JOIN
+  |  join op: INNER JOIN (BROADCAST)
+  |  equal join conjunct: [2: k1, INT, true] = [5: k1, INT, true]
+  |  build runtime filters:
+  |  - filter_id = 0, build_expr = (5: k1), remote = false
+  |  cardinality: 9000000
+  |  column statistics:
+  |  * k1-->[1.0, 100.0, 0.0, 4.0, 100.0] ESTIMATE
+  |
+  |----3:EXCHANGE
+  |       distribution type: BROADCAST
+  |       cardinality: 5000000
+  |
+  0:OlapScanNode
+     table: orders, rollup: orders
+     preAggregation: on
+     partitionsRatio=12/12, tabletsRatio=120/120
+     tabletList=10001,10002
+     actualRows=0, avgRowSize=12.0
+     cardinality: 9000000
+
+PLAN FRAGMENT 2(F01)
+  Output Exprs:5: k1
+  Input Partition: RANDOM
+  OutPut Partition: UNPARTITIONED
+  OutPut Exchange Id: 03
+
+  2:Project
+  |  <slot 5> : 5: k1
+  |  cardinality: 5000000
+  |
+  1:OlapScanNode
+     table: customers, rollup: customers
+     preAggregation: on
+     PREDICATES: 6: region = 'EU'
+     partitionsRatio=1/1, tabletsRatio=10/10
+     cardinality: 5000000
+";
+
+    const ANALYZE: &str = "\
+Summary
+    QueryId: 2c9d9fc3-05a8-11ef-8ca7-02420a0b2d3f
+    Version: 3.3.0
+    State: Finished
+    TotalTime: 1s230ms
+        ExecutionTime: 1s197ms [Scan: 1s26ms (85.74%), Network: 2.170ms (0.18%)]
+    QueryPeakMemoryUsage: 2.306 MB, QueryAllocatedMemoryUsage: 40.016 MB
+    Top Most Time-consuming Nodes:
+        1. OLAP_SCAN (id=0)  1s48ms (88.02%)
+Fragment 0
+│   BackendNum: 1
+│   PrepareTime: 0ns
+└──RESULT_SINK
+   │   TotalTime: 17.834us (0.00%) [CPUTime: 17.834us]
+   │   OutputRows: 1
+   └──AGGREGATE (merge finalize) (id=4)
+      │   Estimates: [row: 1, cpu: 16.00, memory: 16.00, network: 0.00, cost: 1228093.74]
+      │   TotalTime: 1.233ms (0.10%) [CPUTime: 1.233ms]
+      │   OutputRows: 1
+      └──EXCHANGE (id=3) [GATHER]
+             Estimates: [row: 1, cpu: 16.00, memory: 0.00, network: 16.00, cost: 1228085.74]
+             OutputRows: 4
+
+Fragment 1
+│   BackendNum: 3
+└──DATA_STREAM_SINK (id=3)
+   │   PartitionType: UNPARTITIONED
+   └──HASH_JOIN (id=2) [BROADCAST, INNER JOIN]
+      │   Estimates: [row: 400000000, cpu: ?, memory: ?, network: ?, cost: 9.0E8]
+      │   OutputRows: 400.000M (400000000)
+      ├──<PROBE> OLAP_SCAN (id=0)
+      │      Estimates: [row: 400000000, cpu: ?, memory: ?, network: ?, cost: 8.0]
+      │      TotalTime: 1s48ms (88.02%) [CPUTime: 21.829ms, ScanTime: 1s26ms]
+      │      OutputRows: 400.000M (400000000)
+      │      PartitionsRatio: 3/3, TabletsRatio: 48/48
+      │      Table: lineorder
+      └──<BUILD> OLAP_SCAN (id=1)
+             Estimates: [row: 2000000, cpu: ?, memory: ?, network: ?, cost: 8.0]
+             OutputRows: 2.000M (2000000)
+             PartitionsRatio: 1/1, TabletsRatio: 8/8
+             Table: supplier
+             Predicates: 12: s_region = 'ASIA'
+";
+
+    #[test]
+    fn test_parse_costs_plan() {
+        let plan = parse_plan(ExplainMode::Costs, COSTS.to_string());
+        assert!(plan.summary.is_empty());
+        assert_eq!(plan.fragments.len(), 3);
+
+        let result = &plan.fragments[0];
+        assert_eq!(result.sink.as_deref(), Some("RESULT SINK"));
+        assert_eq!(result.input_partition.as_deref(), Some("UNPARTITIONED"));
+        let exchange = result.root.as_ref().unwrap();
+        assert_eq!((exchange.id, exchange.operator.as_str()), (Some(8), "MERGING-EXCHANGE"));
+        assert_eq!(exchange.exchange_type.as_deref(), Some("GATHER"));
+
+        let fragment = &plan.fragments[1];
+        assert_eq!(fragment.output_exchange_id, Some(8));
+        let top_n = fragment.root.as_ref().unwrap();
+        assert_eq!(top_n.operator, "TOP-N");
+        let join = &top_n.children[0];
+        assert_eq!(join.join_type.as_deref(), Some("INNER JOIN"));
+        assert_eq!(join.join_strategy.as_deref(), Some("BROADCAST"));
+        assert_eq!(join.estimated_rows, Some(9_000_000.0));
+        assert!(join.details.iter().all(|d| !d.starts_with('*')));
+        // Probe side first, then the build side
+        let [probe, build] = join.children.as_slice() else {
+            panic!("expected two join inputs: {:?}", join.children);
+        };
+        assert_eq!(probe.table.as_deref(), Some("orders"));
+        assert_eq!(probe.partitions, Some(ScanRatio { selected: 12, total: 12 }));
+        assert_eq!(probe.tablets, Some(ScanRatio { selected: 120, total: 120 }));
+        assert_eq!((build.id, build.exchange_type.as_deref()), (Some(3), Some("BROADCAST")));
+
+        let scan = &plan.fragments[2].root.as_ref().unwrap().children[0];
+        assert_eq!(scan.predicates.as_deref(), Some("6: region = 'EU'"));
+    }
+
+    #[test]
+    fn test_parse_analyze_plan() {
+        let plan = parse_plan(ExplainMode::Analyze, ANALYZE.to_string());
+        assert_eq!(plan.summary["TotalTime"], "1s230ms");
+        assert_eq!(plan.summary["QueryPeakMemoryUsage"], "2.306 MB");
+        assert!(!plan.summary.contains_key("ExecutionTime"));
+
+        let result = &plan.fragments[0];
+        assert_eq!(result.details, vec!["BackendNum: 1", "PrepareTime: 0ns"]);
+        let sink = result.root.as_ref().unwrap();
+        assert_eq!(sink.operator, "RESULT_SINK");
+        let aggregate = &sink.children[0];
+        assert_eq!(
+            (aggregate.id, aggregate.operator.as_str()),
+            (Some(4), "AGGREGATE (merge finalize)")
+        );
+        assert_eq!(aggregate.cost, Some(1228093.74));
+        assert_eq!(aggregate.total_time.as_deref(), Some("1.233ms"));
+        assert_eq!(aggregate.children[0].exchange_type.as_deref(), Some("GATHER"));
+        assert_eq!(aggregate.children[0].actual_rows, Some(4));
+
+        let fragment = &plan.fragments[1];
+        assert_eq!(fragment.output_exchange_id, Some(3));
+        let join = &fragment.root.as_ref().unwrap().children[0];
+        assert_eq!(join.join_strategy.as_deref(), Some("BROADCAST"));
+        assert_eq!(join.join_type.as_deref(), Some("INNER JOIN"));
+        assert_eq!(join.cost, Some(9.0e8));
+        let [probe, build] = join.children.as_slice() else {
+            panic!("expected two join inputs: {:?}", join.children);
+        };
+        assert_eq!(
+            (probe.operator.as_str(), probe.table.as_deref()),
+            ("OLAP_SCAN", Some("lineorder"))
+        );
+        assert_eq!(probe.actual_rows, Some(400_000_000));
+        assert_eq!(probe.estimated_rows, Some(400_000_000.0));
+        assert_eq!(build.table.as_deref(), Some("supplier"));
+        assert_eq!(build.partitions, Some(ScanRatio { selected: 1, total: 1 }));
+    }
+
+    #[test]
+    fn test_warnings() {
+        let config = QueryPlanConfig::default();
+        let kinds = |raw: &str, mode| -> Vec<(PlanWarningKind, Option<i64>, Option<String>)> {
+            let plan = parse_plan(mode, raw.to_string());
+            plan_warnings(&config, &plan.fragments)
+                .into_iter()
+                .map(|w| (w.kind, w.node_id, w.table))
+                .collect()
+        };
+
+        // The broadcast exchange is fed by fragment 2, which scans customers
+        assert_eq!(
+            kinds(COSTS, ExplainMode::Costs),
+            vec![
+                (PlanWarningKind::BroadcastLargeTable, Some(4), Some("customers".to_string())),
+                (PlanWarningKind::FullScan, Some(0), Some("orders".to_string())),
+            ]
+        );
+        assert_eq!(
+            kinds(ANALYZE, ExplainMode::Analyze),
+            vec![
+                (PlanWarningKind::BroadcastLargeTable, Some(2), Some("supplier".to_string())),
+                (PlanWarningKind::FullScan, Some(0), Some("lineorder".to_string())),
+            ]
+        );
+
+        let filtered = COSTS.replace(
+            "     preAggregation: on\n     partitionsRatio=12/12",
+            "     PREDICATES: 3: amount > 10\n     partitionsRatio=12/12",
+        );
+        let warnings = kinds(&filtered, ExplainMode::Costs);
+        assert_eq!(
+            warnings[1],
+            (PlanWarningKind::NoPartitionPruning, Some(0), Some("orders".to_string()))
+        );
+        let pruned = filtered.replace("partitionsRatio=12/12", "partitionsRatio=1/12");
+        assert_eq!(kinds(&pruned, ExplainMode::Costs).len(), 1);
+    }
+
+    #[test]
+    fn test_explained_query() {
+        assert_eq!(explained_query(" SELECT * FROM t; ").unwrap(), "SELECT * FROM t");
+        assert!(explained_query("WITH a AS (SELECT 1) SELECT * FROM a").is_ok());
+        assert!(explained_query("EXPLAIN SELECT 1").is_err());
+        assert!(explained_query("SELECT 1; SELECT 2").is_err());
+        assert!(explained_query("INSERT INTO t SELECT * FROM u").is_err());
+    }
+}
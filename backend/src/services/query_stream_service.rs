@@ -0,0 +1,246 @@
+use mysql_async::prelude::Queryable;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::mpsc;
+
+use crate::config::QueryStreamConfig;
+use crate::models::{Cluster, QueryStreamEvent, QueryStreamRequest, StreamTruncation};
+use crate::services::MySQLPoolManager;
+use crate::services::mysql_client::{MySQLClient, result_column, use_context, value_to_json};
+use crate::services::mysql_pool_manager::{ClusterPool, PooledConn};
+use crate::utils::{ApiError, ApiResult};
+
+/// Caps of one streamed result
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamLimits {
+    pub max_rows: u64,
+    /// Bytes of the rows sent as JSON arrays, not counting the event framing
+    pub max_bytes: u64,
+}
+
+impl StreamLimits {
+    /// Server caps, lowered by the request
+    pub fn resolve(config: &QueryStreamConfig, request: &QueryStreamRequest) -> Self {
+        Self {
+            max_rows: request
+                .max_rows
+                .map_or(config.max_rows, |r| r.min(config.max_rows)),
+            max_bytes: request
+                .max_bytes
+                .map_or(config.max_bytes, |b| b.min(config.max_bytes)),
+        }
+    }
+
+    /// Cap hit by sending one more row after `rows`, bringing the total to `bytes`
+    fn exceeded(&self, rows: u64, bytes: u64) -> Option<StreamTruncation> {
+        if rows >= self.max_rows {
+            Some(StreamTruncation::MaxRows)
+        } else if bytes > self.max_bytes {
+            Some(StreamTruncation::MaxBytes)
+        } else {
+            None
+        }
+    }
+}
+
+/// Runs SQL on a dedicated pooled connection and hands rows out as they arrive
+///
+/// Rows go through a bounded channel, so a slow client pauses reading from
+/// StarRocks instead of buffering the result in memory.
+pub struct QueryStreamService {
+    mysql_pool_manager: Arc<MySQLPoolManager>,
+    config: QueryStreamConfig,
+}
+
+impl QueryStreamService {
+    pub fn new(mysql_pool_manager: Arc<MySQLPoolManager>, config: QueryStreamConfig) -> Self {
+        Self { mysql_pool_manager, config }
+    }
+
+    /// Start `request` and return the receiving end of its events
+    ///
+    /// Failures before the query runs (no connection, unknown database) are
+    /// returned; later ones end the stream with an `error` event. Dropping the
+    /// receiver kills the query.
+    pub async fn start(
+        &self,
+        cluster: &Cluster,
+        request: QueryStreamRequest,
+    ) -> ApiResult<mpsc::Receiver<QueryStreamEvent>> {
+        let limits = StreamLimits::resolve(&self.config, &request);
+        let pool = self.mysql_pool_manager.get_pool(cluster).await?;
+        let mut conn = pool.get_conn().await.map_err(|e| {
+            ApiError::cluster_connection_failed(format!("Failed to get connection: {}", e))
+        })?;
+        use_context(&mut conn, request.catalog.as_deref(), request.database.as_deref()).await?;
+
+        let connection_id = conn.id();
+        let (tx, rx) = mpsc::channel(self.config.buffer_rows.max(1));
+
+        // The client may leave while StarRocks is still computing, before any row
+        // would reveal it, so watch for that separately
+        let watcher = tokio::spawn({
+            let tx = tx.clone();
+            let pool = pool.clone();
+            async move {
+                tx.closed().await;
+                tracing::info!("Stream client of connection {} went away", connection_id);
+                kill_query(&pool, connection_id).await;
+            }
+        });
+
+        tokio::spawn(async move {
+            stream_rows(&mut conn, &request.sql, limits, tx, &pool).await;
+            // Stop the watcher before the connection can serve another query
+            watcher.abort();
+            let _ = watcher.await;
+            drop(conn);
+        });
+
+        Ok(rx)
+    }
+}
+
+async fn stream_rows(
+    conn: &mut PooledConn,
+    sql: &str,
+    limits: StreamLimits,
+    tx: mpsc::Sender<QueryStreamEvent>,
+    pool: &ClusterPool,
+) {
+    let started = Instant::now();
+    let connection_id = conn.id();
+    let fail = |e: mysql_async::Error| QueryStreamEvent::Error {
+        message: format!("SQL execution failed: {}", e),
+    };
+
+    let mut result = match conn.query_iter(sql).await {
+        Ok(result) => result,
+        Err(e) => {
+            let _ = tx.send(fail(e)).await;
+            return;
+        },
+    };
+    let columns = result.columns().unwrap_or_else(|| Vec::new().into());
+    let event = QueryStreamEvent::Columns { columns: columns.iter().map(result_column).collect() };
+    if tx.send(event).await.is_err() {
+        return;
+    }
+
+    let (mut row_count, mut bytes) = (0u64, 0u64);
+    let mut truncated = None;
+    loop {
+        let row = match result.next().await {
+            Ok(Some(row)) => row,
+            Ok(None) => break,
+            Err(e) => {
+                let _ = tx.send(fail(e)).await;
+                return;
+            },
+        };
+        let values: Vec<serde_json::Value> = columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                row.as_ref(i)
+                    .map_or(serde_json::Value::Null, |v| value_to_json(v, column))
+            })
+            .collect();
+        let size = serde_json::to_vec(&values).map_or(0, |json| json.len() as u64);
+
+        if let Some(cap) = limits.exceeded(row_count, bytes + size) {
+            tracing::info!(
+                "Stream on connection {} reached {:?} after {} rows",
+                connection_id,
+                cap,
+                row_count
+            );
+            truncated = Some(cap);
+            kill_query(pool, connection_id).await;
+            let _ = result.drop_result().await;
+            break;
+        }
+        row_count += 1;
+        bytes += size;
+        // Waits while the channel is full; fails once the client is gone
+        if tx.send(QueryStreamEvent::Row { values }).await.is_err() {
+            return;
+        }
+    }
+
+    let _ = tx
+        .send(QueryStreamEvent::End {
+            row_count,
+            bytes,
+            execution_time_ms: started.elapsed().as_millis(),
+            truncated,
+        })
+        .await;
+}
+
+async fn kill_query(pool: &ClusterPool, connection_id: u32) {
+    let client = MySQLClient::from_pool(pool.clone());
+    match client
+        .execute(&format!("KILL QUERY {}", connection_id))
+        .await
+    {
+        Ok(_) => tracing::info!("Killed query on connection {}", connection_id),
+        Err(e) => tracing::warn!("Failed to kill query on connection {}: {}", connection_id, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(max_rows: Option<u64>, max_bytes: Option<u64>) -> QueryStreamRequest {
+        QueryStreamRequest {
+            sql: "SELECT 1".to_string(),
+            catalog: None,
+            database: None,
+            max_rows,
+            max_bytes,
+        }
+    }
+
+    #[test]
+    fn test_limits_only_lowered_by_request() {
+        let config = QueryStreamConfig { max_rows: 100, max_bytes: 1000, buffer_rows: 8 };
+
+        let limits = StreamLimits::resolve(&config, &request(None, None));
+        assert_eq!(limits, StreamLimits { max_rows: 100, max_bytes: 1000 });
+        let limits = StreamLimits::resolve(&config, &request(Some(10), Some(1_000_000)));
+        assert_eq!(limits, StreamLimits { max_rows: 10, max_bytes: 1000 });
+    }
+
+    #[test]
+    fn test_limits_exceeded() {
+        let limits = StreamLimits { max_rows: 2, max_bytes: 10 };
+
+        assert_eq!(limits.exceeded(0, 10), None);
+        assert_eq!(limits.exceeded(1, 11), Some(StreamTruncation::MaxBytes));
+        assert_eq!(limits.exceeded(2, 5), Some(StreamTruncation::MaxRows));
+    }
+
+    #[test]
+    fn test_event_encoding() {
+        let end = QueryStreamEvent::End {
+            row_count: 2,
+            bytes: 7,
+            execution_time_ms: 3,
+            truncated: Some(StreamTruncation::MaxRows),
+        };
+        assert_eq!(end.name(), "end");
+        assert_eq!(
+            serde_json::to_value(&end).unwrap(),
+            serde_json::json!({
+                "type": "end", "row_count": 2, "bytes": 7, "execution_time_ms": 3,
+                "truncated": "max_rows"
+            })
+        );
+        let row = QueryStreamEvent::Row {
+            values: vec![serde_json::json!("a"), serde_json::json!(1), serde_json::Value::Null],
+        };
+        assert_eq!(serde_json::to_string(&row).unwrap(), r#"{"type":"row","values":["a",1,null]}"#);
+    }
+}
@@ -0,0 +1,725 @@
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use chrono::{NaiveDate, NaiveDateTime};
+use parquet::basic::{Compression, LogicalType, Repetition, TimeUnit, Type as PhysicalType};
+use parquet::data_type::{
+    BoolType, ByteArray, ByteArrayType, DataType, DoubleType, Int32Type, Int64Type,
+};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::{SerializedColumnWriter, SerializedFileWriter};
+use parquet::format::MicroSeconds;
+use parquet::schema::types::Type;
+use rust_xlsxwriter::{ExcelDateTime, Format, Workbook, XlsxError};
+use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use crate::models::{ExportFormat, QueryStreamEvent, ResultColumn};
+use crate::utils::{ApiError, ApiResult};
+
+/// Rows buffered per Parquet row group before they are written out
+const PARQUET_ROW_GROUP_ROWS: usize = 8192;
+/// Rows of an Excel worksheet, header included
+const XLSX_MAX_ROWS: u32 = 1_048_576;
+const XLSX_MAX_COLUMNS: usize = 16_384;
+
+/// Turns the events of a streamed query into the bytes of an export file
+///
+/// A failed or truncated query is an error rather than the end of the file,
+/// so the download breaks off instead of looking complete.
+pub struct ResultExport {
+    encoder: Option<Box<dyn ResultEncoder>>,
+}
+
+impl ResultExport {
+    pub fn new(format: ExportFormat) -> Self {
+        let encoder: Box<dyn ResultEncoder> = match format {
+            ExportFormat::Csv => Box::new(CsvEncoder),
+            ExportFormat::Tsv => Box::new(TsvEncoder),
+            ExportFormat::Jsonl => Box::new(JsonlEncoder::default()),
+            ExportFormat::Parquet => Box::new(ParquetEncoder::default()),
+            ExportFormat::Xlsx => Box::new(XlsxEncoder::default()),
+        };
+        Self { encoder: Some(encoder) }
+    }
+
+    /// Bytes to send for `event`, possibly none
+    pub fn next(&mut self, event: QueryStreamEvent) -> ApiResult<Vec<u8>> {
+        let encoder = self
+            .encoder
+            .as_mut()
+            .ok_or_else(|| ApiError::internal_error("Export already finished"))?;
+        match event {
+            QueryStreamEvent::Columns { columns } => encoder.header(&columns),
+            QueryStreamEvent::Row { values } => encoder.row(values),
+            QueryStreamEvent::End { truncated: Some(cap), row_count, .. } => {
+                self.encoder = None;
+                Err(ApiError::validation_error(format!(
+                    "Export stopped after {} rows: the result exceeds the {:?} limit",
+                    row_count, cap
+                )))
+            },
+            QueryStreamEvent::End { .. } => match self.encoder.take() {
+                Some(encoder) => encoder.finish(),
+                None => Ok(Vec::new()),
+            },
+            QueryStreamEvent::Error { message } => {
+                self.encoder = None;
+                Err(ApiError::internal_error(message))
+            },
+        }
+    }
+}
+
+/// Writes one file format; each call returns the bytes ready to be sent
+trait ResultEncoder: Send {
+    fn header(&mut self, columns: &[ResultColumn]) -> ApiResult<Vec<u8>>;
+    fn row(&mut self, values: Vec<Value>) -> ApiResult<Vec<u8>>;
+    fn finish(self: Box<Self>) -> ApiResult<Vec<u8>>;
+}
+
+/// Text of a value for the text formats; `None` for NULL
+fn text(value: &Value) -> Option<Cow<'_, str>> {
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(Cow::Borrowed(s)),
+        other => Some(Cow::Owned(other.to_string())),
+    }
+}
+
+/// Column names made unique, for formats that address fields by name
+fn unique_names(columns: &[ResultColumn]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            let base = if column.name.is_empty() {
+                format!("column_{}", i + 1)
+            } else {
+                column.name.clone()
+            };
+            let mut name = base.clone();
+            let mut n = 1;
+            while !seen.insert(name.clone()) {
+                n += 1;
+                name = format!("{}_{}", base, n);
+            }
+            name
+        })
+        .collect()
+}
+
+/// RFC 4180: NULL is an empty field, lines end with CRLF
+struct CsvEncoder;
+
+impl CsvEncoder {
+    fn line<'a>(fields: impl Iterator<Item = Option<Cow<'a, str>>>) -> Vec<u8> {
+        let mut line = String::new();
+        for (i, field) in fields.enumerate() {
+            if i > 0 {
+                line.push(',');
+            }
+            let field = field.unwrap_or_default();
+            if field.contains([',', '"', '\r', '\n']) {
+                line.push('"');
+                line.push_str(&field.replace('"', "\"\""));
+                line.push('"');
+            } else {
+                line.push_str(&field);
+            }
+        }
+        line.push_str("\r\n");
+        line.into_bytes()
+    }
+}
+
+impl ResultEncoder for CsvEncoder {
+    fn header(&mut self, columns: &[ResultColumn]) -> ApiResult<Vec<u8>> {
+        Ok(Self::line(columns.iter().map(|c| Some(Cow::Borrowed(c.name.as_str())))))
+    }
+
+    fn row(&mut self, values: Vec<Value>) -> ApiResult<Vec<u8>> {
+        Ok(Self::line(values.iter().map(text)))
+    }
+
+    fn finish(self: Box<Self>) -> ApiResult<Vec<u8>> {
+        Ok(Vec::new())
+    }
+}
+
+/// Tab separated as written by `mysql --batch`: NULL is `\N`, and backslash,
+/// tab and line breaks are escaped
+struct TsvEncoder;
+
+impl TsvEncoder {
+    fn line<'a>(fields: impl Iterator<Item = Option<Cow<'a, str>>>) -> Vec<u8> {
+        let mut line = String::new();
+        for (i, field) in fields.enumerate() {
+            if i > 0 {
+                line.push('\t');
+            }
+            match field {
+                None => line.push_str("\\N"),
+                Some(field) => {
+                    for c in field.chars() {
+                        match c {
+                            '\\' => line.push_str("\\\\"),
+                            '\t' => line.push_str("\\t"),
+                            '\n' => line.push_str("\\n"),
+                            '\r' => line.push_str("\\r"),
+                            c => line.push(c),
+                        }
+                    }
+                },
+            }
+        }
+        line.push('\n');
+        line.into_bytes()
+    }
+}
+
+impl ResultEncoder for TsvEncoder {
+    fn header(&mut self, columns: &[ResultColumn]) -> ApiResult<Vec<u8>> {
+        Ok(Self::line(columns.iter().map(|c| Some(Cow::Borrowed(c.name.as_str())))))
+    }
+
+    fn row(&mut self, values: Vec<Value>) -> ApiResult<Vec<u8>> {
+        Ok(Self::line(values.iter().map(text)))
+    }
+
+    fn finish(self: Box<Self>) -> ApiResult<Vec<u8>> {
+        Ok(Vec::new())
+    }
+}
+
+/// One JSON object per row, keys in column order
+#[derive(Default)]
+struct JsonlEncoder {
+    /// Column names already encoded as JSON strings
+    keys: Vec<String>,
+}
+
+impl ResultEncoder for JsonlEncoder {
+    fn header(&mut self, columns: &[ResultColumn]) -> ApiResult<Vec<u8>> {
+        self.keys = unique_names(columns)
+            .iter()
+            .map(|name| Value::from(name.as_str()).to_string())
+            .collect();
+        Ok(Vec::new())
+    }
+
+    fn row(&mut self, values: Vec<Value>) -> ApiResult<Vec<u8>> {
+        let mut line = String::from("{");
+        for (i, (key, value)) in self.keys.iter().zip(&values).enumerate() {
+            if i > 0 {
+                line.push(',');
+            }
+            line.push_str(key);
+            line.push(':');
+            line.push_str(&value.to_string());
+        }
+        line.push_str("}\n");
+        Ok(line.into_bytes())
+    }
+
+    fn finish(self: Box<Self>) -> ApiResult<Vec<u8>> {
+        Ok(Vec::new())
+    }
+}
+
+/// Parquet column type chosen from the result column type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParquetKind {
+    Int64,
+    Double,
+    Boolean,
+    Date,
+    Timestamp,
+    Binary,
+    Json,
+    /// DECIMAL too: its scale is not known from the result metadata
+    Text,
+}
+
+impl ParquetKind {
+    fn of(column: &ResultColumn) -> Self {
+        match column.data_type.as_str() {
+            "TINYINT" | "SMALLINT" | "MEDIUMINT" | "INT" | "BIGINT" | "YEAR" => Self::Int64,
+            "FLOAT" | "DOUBLE" => Self::Double,
+            "BOOLEAN" => Self::Boolean,
+            "DATE" => Self::Date,
+            "DATETIME" | "TIMESTAMP" => Self::Timestamp,
+            "BINARY" | "VARBINARY" | "BLOB" => Self::Binary,
+            "JSON" => Self::Json,
+            _ => Self::Text,
+        }
+    }
+
+    fn field(&self, name: &str) -> ApiResult<Type> {
+        let (physical, logical) = match self {
+            Self::Int64 => (PhysicalType::INT64, None),
+            Self::Double => (PhysicalType::DOUBLE, None),
+            Self::Boolean => (PhysicalType::BOOLEAN, None),
+            Self::Date => (PhysicalType::INT32, Some(LogicalType::Date)),
+            Self::Timestamp => (
+                PhysicalType::INT64,
+                Some(LogicalType::Timestamp {
+                    is_adjusted_to_u_t_c: false,
+                    unit: TimeUnit::MICROS(MicroSeconds {}),
+                }),
+            ),
+            Self::Binary => (PhysicalType::BYTE_ARRAY, None),
+            Self::Json => (PhysicalType::BYTE_ARRAY, Some(LogicalType::Json)),
+            Self::Text => (PhysicalType::BYTE_ARRAY, Some(LogicalType::String)),
+        };
+        Type::primitive_type_builder(name, physical)
+            .with_repetition(Repetition::OPTIONAL)
+            .with_logical_type(logical)
+            .build()
+            .map_err(parquet_error)
+    }
+}
+
+fn parquet_error(e: parquet::errors::ParquetError) -> ApiError {
+    ApiError::internal_error(format!("Failed to write Parquet: {}", e))
+}
+
+fn to_i64(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(n) => n.as_i64(),
+        // Integers beyond 2^53 come as strings
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+fn to_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        // NaN and infinities come as strings
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+fn to_date(value: &Value) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.as_str()?, "%Y-%m-%d").ok()
+}
+
+fn to_datetime(value: &Value) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value.as_str()?, "%Y-%m-%dT%H:%M:%S%.f").ok()
+}
+
+/// In-memory sink the Parquet writer appends to and the encoder drains
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    fn take(&self) -> Vec<u8> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Snappy-compressed Parquet, sent one row group at a time; all fields are optional
+#[derive(Default)]
+struct ParquetEncoder {
+    sink: SharedBuffer,
+    writer: Option<SerializedFileWriter<SharedBuffer>>,
+    names: Vec<String>,
+    kinds: Vec<ParquetKind>,
+    rows: Vec<Vec<Value>>,
+}
+
+impl ParquetEncoder {
+    fn writer(&mut self) -> ApiResult<&mut SerializedFileWriter<SharedBuffer>> {
+        self.writer
+            .as_mut()
+            .ok_or_else(|| ApiError::internal_error("Parquet export has no columns"))
+    }
+
+    fn write_row_group(&mut self) -> ApiResult<Vec<u8>> {
+        if self.rows.is_empty() {
+            return Ok(Vec::new());
+        }
+        let rows = std::mem::take(&mut self.rows);
+        let kinds = self.kinds.clone();
+        let names = self.names.clone();
+        let mut row_group = self.writer()?.next_row_group().map_err(parquet_error)?;
+        for (index, (kind, name)) in kinds.iter().zip(&names).enumerate() {
+            let mut column = row_group
+                .next_column()
+                .map_err(parquet_error)?
+                .ok_or_else(|| {
+                    ApiError::internal_error("Parquet schema has fewer columns than the result")
+                })?;
+            let cells = rows
+                .iter()
+                .map(|row| row.get(index).unwrap_or(&Value::Null));
+            match kind {
+                ParquetKind::Int64 => write_column::<Int64Type>(&mut column, name, cells, to_i64),
+                ParquetKind::Double => write_column::<DoubleType>(&mut column, name, cells, to_f64),
+                ParquetKind::Boolean => {
+                    write_column::<BoolType>(&mut column, name, cells, Value::as_bool)
+                },
+                ParquetKind::Date => write_column::<Int32Type>(&mut column, name, cells, |v| {
+                    to_date(v).map(|date| date.to_epoch_days())
+                }),
+                ParquetKind::Timestamp => {
+                    write_column::<Int64Type>(&mut column, name, cells, |v| {
+                        to_datetime(v).map(|datetime| datetime.and_utc().timestamp_micros())
+                    })
+                },
+                ParquetKind::Binary => {
+                    write_column::<ByteArrayType>(&mut column, name, cells, |v| {
+                        BASE64.decode(v.as_str()?).ok().map(ByteArray::from)
+                    })
+                },
+                ParquetKind::Json | ParquetKind::Text => {
+                    write_column::<ByteArrayType>(&mut column, name, cells, |v| {
+                        text(v).map(|t| ByteArray::from(t.into_owned().into_bytes()))
+                    })
+                },
+            }?;
+            column.close().map_err(parquet_error)?;
+        }
+        row_group.close().map_err(parquet_error)?;
+        Ok(self.sink.take())
+    }
+}
+
+/// Write one column chunk, NULLs as undefined values
+fn write_column<'a, T: DataType>(
+    column: &mut SerializedColumnWriter<'_>,
+    name: &str,
+    cells: impl Iterator<Item = &'a Value>,
+    convert: impl Fn(&Value) -> Option<T::T>,
+) -> ApiResult<()> {
+    let mut values = Vec::new();
+    let mut definition_levels = Vec::new();
+    for cell in cells {
+        if cell.is_null() {
+            definition_levels.push(0);
+            continue;
+        }
+        let value = convert(cell).ok_or_else(|| {
+            ApiError::internal_error(format!(
+                "Value {} of column '{}' cannot be written to Parquet",
+                cell, name
+            ))
+        })?;
+        values.push(value);
+        definition_levels.push(1);
+    }
+    column
+        .typed::<T>()
+        .write_batch(&values, Some(&definition_levels), None)
+        .map_err(parquet_error)?;
+    Ok(())
+}
+
+impl ResultEncoder for ParquetEncoder {
+    fn header(&mut self, columns: &[ResultColumn]) -> ApiResult<Vec<u8>> {
+        self.names = unique_names(columns);
+        self.kinds = columns.iter().map(ParquetKind::of).collect();
+        let fields = self
+            .kinds
+            .iter()
+            .zip(&self.names)
+            .map(|(kind, name)| kind.field(name).map(Arc::new))
+            .collect::<ApiResult<Vec<_>>>()?;
+        let schema = Type::group_type_builder("schema")
+            .with_fields(fields)
+            .build()
+            .map_err(parquet_error)?;
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        self.writer = Some(
+            SerializedFileWriter::new(self.sink.clone(), Arc::new(schema), Arc::new(properties))
+                .map_err(parquet_error)?,
+        );
+        Ok(self.sink.take())
+    }
+
+    fn row(&mut self, values: Vec<Value>) -> ApiResult<Vec<u8>> {
+        self.rows.push(values);
+        if self.rows.len() < PARQUET_ROW_GROUP_ROWS {
+            return Ok(Vec::new());
+        }
+        self.write_row_group()
+    }
+
+    fn finish(mut self: Box<Self>) -> ApiResult<Vec<u8>> {
+        if self.writer.is_none() {
+            // Statements without a result set still give a readable file
+            self.header(&[])?;
+        }
+        let mut bytes = self.write_row_group()?;
+        self.writer()?.finish().map_err(parquet_error)?;
+        bytes.extend(self.sink.take());
+        Ok(bytes)
+    }
+}
+
+fn xlsx_error(e: XlsxError) -> ApiError {
+    ApiError::internal_error(format!("Failed to write XLSX: {}", e))
+}
+
+/// Single-sheet workbook with a bold header row
+///
+/// Rows go to a temporary file as they arrive, but XLSX is a zip archive, so
+/// the download only starts once the last row has been written.
+struct XlsxEncoder {
+    workbook: Workbook,
+    types: Vec<String>,
+    next_row: u32,
+    date_format: Format,
+    datetime_format: Format,
+}
+
+impl Default for XlsxEncoder {
+    fn default() -> Self {
+        let mut workbook = Workbook::new();
+        workbook.add_worksheet_with_constant_memory();
+        Self {
+            workbook,
+            types: Vec::new(),
+            next_row: 0,
+            date_format: Format::new().set_num_format("yyyy-mm-dd"),
+            datetime_format: Format::new().set_num_format("yyyy-mm-dd hh:mm:ss"),
+        }
+    }
+}
+
+/// DECIMAL written as a number only when Excel's 15 digits hold it exactly
+fn decimal_to_f64(text: &str) -> Option<f64> {
+    let digits = text.trim_start_matches(['-', '+']).replace('.', "");
+    let significant = digits.trim_start_matches('0').len();
+    if significant > 15 {
+        return None;
+    }
+    text.parse().ok()
+}
+
+impl ResultEncoder for XlsxEncoder {
+    fn header(&mut self, columns: &[ResultColumn]) -> ApiResult<Vec<u8>> {
+        if columns.len() > XLSX_MAX_COLUMNS {
+            return Err(ApiError::validation_error(format!(
+                "XLSX holds at most {} columns, the result has {}",
+                XLSX_MAX_COLUMNS,
+                columns.len()
+            )));
+        }
+        self.types = columns.iter().map(|c| c.data_type.clone()).collect();
+        let bold = Format::new().set_bold();
+        let sheet = self.workbook.worksheet_from_index(0).map_err(xlsx_error)?;
+        for (col, column) in columns.iter().enumerate() {
+            sheet
+                .write_string_with_format(0, col as u16, &column.name, &bold)
+                .map_err(xlsx_error)?;
+        }
+        self.next_row = 1;
+        Ok(Vec::new())
+    }
+
+    fn row(&mut self, values: Vec<Value>) -> ApiResult<Vec<u8>> {
+        if self.next_row >= XLSX_MAX_ROWS {
+            return Err(ApiError::validation_error(format!(
+                "XLSX holds at most {} rows; use CSV or Parquet for larger results",
+                XLSX_MAX_ROWS - 1
+            )));
+        }
+        let row = self.next_row;
+        let sheet = self.workbook.worksheet_from_index(0).map_err(xlsx_error)?;
+        for (col, (value, data_type)) in values.iter().zip(&self.types).enumerate() {
+            let col = col as u16;
+            let written = match (value, data_type.as_str()) {
+                (Value::Null, _) => continue,
+                (Value::Bool(b), _) => sheet.write_boolean(row, col, *b),
+                // Integers beyond 2^53 arrive as strings and stay text
+                (Value::Number(n), _) => match n.as_f64() {
+                    Some(number) => sheet.write_number(row, col, number),
+                    None => sheet.write_string(row, col, n.to_string()),
+                },
+                (Value::String(s), "DATE" | "DATETIME" | "TIMESTAMP") => {
+                    let format =
+                        if data_type == "DATE" { &self.date_format } else { &self.datetime_format };
+                    // Dates before 1900 are not Excel dates
+                    match ExcelDateTime::parse_from_str(s) {
+                        Ok(datetime) => {
+                            sheet.write_datetime_with_format(row, col, &datetime, format)
+                        },
+                        Err(_) => sheet.write_string(row, col, s),
+                    }
+                },
+                (Value::String(s), "DECIMAL") => match decimal_to_f64(s) {
+                    Some(number) => sheet.write_number(row, col, number),
+                    None => sheet.write_string(row, col, s),
+                },
+                (Value::String(s), _) => sheet.write_string(row, col, s),
+                (other, _) => sheet.write_string(row, col, other.to_string()),
+            };
+            written.map_err(xlsx_error)?;
+        }
+        self.next_row += 1;
+        Ok(Vec::new())
+    }
+
+    fn finish(mut self: Box<Self>) -> ApiResult<Vec<u8>> {
+        self.workbook.save_to_buffer().map_err(xlsx_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn column(name: &str, data_type: &str) -> ResultColumn {
+        ResultColumn { name: name.to_string(), data_type: data_type.to_string(), nullable: true }
+    }
+
+    fn export(format: ExportFormat, columns: Vec<ResultColumn>, rows: Vec<Vec<Value>>) -> Vec<u8> {
+        let mut export = ResultExport::new(format);
+        let mut bytes = export.next(QueryStreamEvent::Columns { columns }).unwrap();
+        let row_count = rows.len() as u64;
+        for values in rows {
+            bytes.extend(export.next(QueryStreamEvent::Row { values }).unwrap());
+        }
+        let end =
+            QueryStreamEvent::End { row_count, bytes: 0, execution_time_ms: 1, truncated: None };
+        bytes.extend(export.next(end).unwrap());
+        bytes
+    }
+
+    #[test]
+    fn test_text_formats() {
+        let columns = vec![column("id", "BIGINT"), column("note", "VARCHAR")];
+        let rows = vec![
+            vec![json!(1), json!("plain")],
+            vec![json!(2), json!("a,\"b\"\nc\\d\te")],
+            vec![json!(3), Value::Null],
+        ];
+
+        let csv = export(ExportFormat::Csv, columns.clone(), rows.clone());
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "id,note\r\n1,plain\r\n2,\"a,\"\"b\"\"\nc\\d\te\"\r\n3,\r\n"
+        );
+        let tsv = export(ExportFormat::Tsv, columns.clone(), rows.clone());
+        assert_eq!(
+            String::from_utf8(tsv).unwrap(),
+            "id\tnote\n1\tplain\n2\ta,\"b\"\\nc\\\\d\\te\n3\t\\N\n"
+        );
+        let jsonl = export(
+            ExportFormat::Jsonl,
+            vec![column("z", "INT"), column("a", "INT"), column("a", "INT")],
+            vec![vec![json!(1), json!(2), Value::Null]],
+        );
+        assert_eq!(String::from_utf8(jsonl).unwrap(), "{\"z\":1,\"a\":2,\"a_2\":null}\n");
+    }
+
+    #[test]
+    fn test_parquet_round_trip() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let columns = vec![
+            column("id", "BIGINT"),
+            column("price", "DOUBLE"),
+            column("ok", "BOOLEAN"),
+            column("day", "DATE"),
+            column("ts", "DATETIME"),
+            column("amount", "DECIMAL"),
+            column("raw", "VARBINARY"),
+        ];
+        let rows = vec![
+            vec![
+                json!(1),
+                json!(1.5),
+                json!(true),
+                json!("2026-10-18"),
+                json!("2026-10-18T01:02:03.5"),
+                json!("12.30"),
+                json!(BASE64.encode(b"\x00\x01")),
+            ],
+            vec![
+                json!("9007199254740993"),
+                Value::Null,
+                json!(false),
+                Value::Null,
+                Value::Null,
+                Value::Null,
+                Value::Null,
+            ],
+        ];
+        let bytes = export(ExportFormat::Parquet, columns, rows);
+
+        let reader = SerializedFileReader::new(axum::body::Bytes::from(bytes)).unwrap();
+        let schema = reader.metadata().file_metadata().schema_descr();
+        assert_eq!(schema.column(0).physical_type(), PhysicalType::INT64);
+        assert_eq!(schema.column(3).logical_type(), Some(LogicalType::Date));
+        assert_eq!(schema.column(5).logical_type(), Some(LogicalType::String));
+        let rows: Vec<String> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.unwrap().to_string())
+            .collect();
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0].contains("id: 1,"), "{}", rows[0]);
+        assert!(rows[0].contains("day: 2026-10-18"), "{}", rows[0]);
+        assert!(rows[0].contains("amount: \"12.30\""), "{}", rows[0]);
+        assert!(rows[1].contains("id: 9007199254740993,"), "{}", rows[1]);
+        assert!(rows[1].contains("price: null"), "{}", rows[1]);
+    }
+
+    #[test]
+    fn test_failed_or_truncated_query_aborts_export() {
+        let mut export = ResultExport::new(ExportFormat::Csv);
+        export
+            .next(QueryStreamEvent::Columns { columns: vec![column("id", "INT")] })
+            .unwrap();
+        let end = QueryStreamEvent::End {
+            row_count: 10,
+            bytes: 100,
+            execution_time_ms: 1,
+            truncated: Some(crate::models::StreamTruncation::MaxRows),
+        };
+        assert!(export.next(end).is_err());
+
+        let mut export = ResultExport::new(ExportFormat::Parquet);
+        export
+            .next(QueryStreamEvent::Columns { columns: vec![column("id", "INT")] })
+            .unwrap();
+        export
+            .next(QueryStreamEvent::Row { values: vec![json!("not a number")] })
+            .unwrap();
+        let end =
+            QueryStreamEvent::End { row_count: 1, bytes: 0, execution_time_ms: 1, truncated: None };
+        assert!(export.next(end).is_err());
+        assert!(
+            ResultExport::new(ExportFormat::Csv)
+                .next(QueryStreamEvent::Error { message: "boom".to_string() })
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_decimal_to_f64() {
+        assert_eq!(decimal_to_f64("-12.50"), Some(-12.5));
+        assert_eq!(decimal_to_f64("0.000123"), Some(0.000123));
+        assert_eq!(decimal_to_f64("1234567890.1234567"), None);
+    }
+}
@@ -0,0 +1,229 @@
+use crate::models::{TokenResponse, User};
+use crate::utils::{ApiError, ApiResult, JwtUtil, jwt::Claims, token};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+#[derive(sqlx::FromRow)]
+struct RefreshTokenRow {
+    id: i64,
+    user_id: i64,
+    session_id: String,
+    expires_at: DateTime<Utc>,
+    revoked_at: Option<DateTime<Utc>>,
+}
+
+/// Issues access/refresh token pairs and maintains the revocation list
+///
+/// Every login starts a session (`sid`). Access tokens are short-lived JWTs; the
+/// session is kept alive by opaque refresh tokens that are rotated on each use.
+/// Presenting an already rotated refresh token is treated as theft and revokes
+/// the whole session.
+#[derive(Clone)]
+pub struct SessionService {
+    pool: SqlitePool,
+    jwt_util: Arc<JwtUtil>,
+    refresh_expires_in: Duration,
+}
+
+impl SessionService {
+    pub fn new(pool: SqlitePool, jwt_util: Arc<JwtUtil>, refresh_expires_in: &str) -> Self {
+        Self { pool, jwt_util, refresh_expires_in: JwtUtil::parse_expiration(refresh_expires_in) }
+    }
+
+    // Start a new session for a freshly authenticated user
+    pub async fn start_session(&self, user: &User) -> ApiResult<TokenResponse> {
+        self.purge_expired().await?;
+
+        let session_id = token::generate_secret("", 16);
+        self.issue_tokens(user, &session_id).await
+    }
+
+    // Exchange a refresh token for a new token pair (rotating the refresh token)
+    pub async fn refresh(&self, refresh_token: &str) -> ApiResult<(User, TokenResponse)> {
+        let row: Option<RefreshTokenRow> = sqlx::query_as(
+            "SELECT id, user_id, session_id, expires_at, revoked_at FROM refresh_tokens WHERE token_hash = ?",
+        )
+        .bind(token::hash_secret(refresh_token))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let RefreshTokenRow { id, user_id, session_id, expires_at, revoked_at } =
+            row.ok_or_else(Self::invalid_refresh_token)?;
+
+        if revoked_at.is_some() {
+            tracing::warn!(
+                "Reuse of rotated refresh token detected for user {}, revoking session",
+                user_id
+            );
+            self.revoke_session(user_id, &session_id, "refresh_token_reuse")
+                .await?;
+            return Err(Self::invalid_refresh_token());
+        }
+
+        if expires_at <= Utc::now() || self.is_revoked(&session_id).await? {
+            return Err(Self::invalid_refresh_token());
+        }
+
+        let user: User = sqlx::query_as("SELECT * FROM users WHERE id = ?")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(Self::invalid_refresh_token)?;
+
+        if user.is_disabled {
+            return Err(ApiError::unauthorized("User account is disabled"));
+        }
+
+        // Only one concurrent refresh may win the rotation
+        let rotated = sqlx::query(
+            "UPDATE refresh_tokens SET revoked_at = CURRENT_TIMESTAMP WHERE id = ? AND revoked_at IS NULL",
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        if rotated.rows_affected() == 0 {
+            return Err(Self::invalid_refresh_token());
+        }
+
+        let tokens = self.issue_tokens(&user, &session_id).await?;
+        tracing::debug!("Refreshed session for user {} (ID: {})", user.username, user.id);
+
+        Ok((user, tokens))
+    }
+
+    // Log out: revoke the session of the presented access token
+    pub async fn logout(&self, claims: &Claims) -> ApiResult<()> {
+        let user_id = claims.sub.parse::<i64>().unwrap_or(0);
+
+        if claims.sid.is_empty() {
+            // Tokens issued before sessions existed can only be revoked individually
+            self.add_revocation(user_id, &claims.jti, "logout").await
+        } else {
+            self.revoke_session(user_id, &claims.sid, "logout").await
+        }
+    }
+
+    // Revoke one session: its refresh tokens and all access tokens carrying its `sid`
+    pub async fn revoke_session(
+        &self,
+        user_id: i64,
+        session_id: &str,
+        reason: &str,
+    ) -> ApiResult<()> {
+        sqlx::query(
+            "UPDATE refresh_tokens SET revoked_at = CURRENT_TIMESTAMP WHERE session_id = ? AND revoked_at IS NULL",
+        )
+        .bind(session_id)
+        .execute(&self.pool)
+        .await?;
+
+        self.add_revocation(user_id, session_id, reason).await?;
+        tracing::info!("Revoked session of user {} ({})", user_id, reason);
+
+        Ok(())
+    }
+
+    // Revoke every active session of a user (password reset, compromised account, ...)
+    pub async fn revoke_user_sessions(&self, user_id: i64, reason: &str) -> ApiResult<usize> {
+        let sessions: Vec<(String,)> = sqlx::query_as(
+            "SELECT DISTINCT session_id FROM refresh_tokens WHERE user_id = ? AND revoked_at IS NULL AND expires_at > ?",
+        )
+        .bind(user_id)
+        .bind(Utc::now())
+        .fetch_all(&self.pool)
+        .await?;
+
+        for (session_id,) in &sessions {
+            self.revoke_session(user_id, session_id, reason).await?;
+        }
+
+        Ok(sessions.len())
+    }
+
+    // Whether an access token was revoked, by its own id or by its session
+    pub async fn is_token_revoked(&self, claims: &Claims) -> ApiResult<bool> {
+        let (count,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM token_revocations WHERE token_id IN (?, ?) AND token_id != ''",
+        )
+        .bind(&claims.jti)
+        .bind(&claims.sid)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count > 0)
+    }
+
+    async fn is_revoked(&self, token_id: &str) -> ApiResult<bool> {
+        let (count,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM token_revocations WHERE token_id = ?")
+                .bind(token_id)
+                .fetch_one(&self.pool)
+                .await?;
+
+        Ok(count > 0)
+    }
+
+    async fn issue_tokens(&self, user: &User, session_id: &str) -> ApiResult<TokenResponse> {
+        let access_token =
+            self.jwt_util
+                .generate_token(user.id, &user.username, user.role, session_id)?;
+
+        let refresh_token = token::generate_secret("rt_", 32);
+        sqlx::query(
+            "INSERT INTO refresh_tokens (user_id, session_id, token_hash, expires_at) VALUES (?, ?, ?, ?)",
+        )
+        .bind(user.id)
+        .bind(session_id)
+        .bind(token::hash_secret(&refresh_token))
+        .bind(Utc::now() + self.refresh_expires_in)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(TokenResponse {
+            token: access_token,
+            refresh_token,
+            expires_in: self.jwt_util.expires_in().num_seconds(),
+        })
+    }
+
+    async fn add_revocation(&self, user_id: i64, token_id: &str, reason: &str) -> ApiResult<()> {
+        if token_id.is_empty() {
+            return Ok(());
+        }
+
+        // Access tokens issued before now expire within one access token lifetime
+        sqlx::query(
+            "INSERT OR IGNORE INTO token_revocations (token_id, user_id, reason, expires_at) VALUES (?, ?, ?, ?)",
+        )
+        .bind(token_id)
+        .bind(user_id)
+        .bind(reason)
+        .bind(Utc::now() + self.jwt_util.expires_in())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    // Drop revocations and refresh tokens nobody can present anymore
+    async fn purge_expired(&self) -> ApiResult<()> {
+        let now = Utc::now();
+
+        sqlx::query("DELETE FROM token_revocations WHERE expires_at < ?")
+            .bind(now)
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("DELETE FROM refresh_tokens WHERE expires_at < ?")
+            .bind(now)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    fn invalid_refresh_token() -> ApiError {
+        ApiError::unauthorized("Invalid or expired refresh token")
+    }
+}
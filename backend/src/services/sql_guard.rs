@@ -0,0 +1,333 @@
+use axum::http::HeaderMap;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::config::SqlGuardConfig;
+use crate::models::{
+    Cluster, CurrentUser, SqlViolation, SqlViolationReason, StatementCategory, UserRole,
+};
+use crate::services::PermissionService;
+use crate::utils::sql::{self, Statement, StatementClass};
+use crate::utils::token::{generate_secret, hash_secret};
+use crate::utils::{ApiError, ApiResult};
+
+/// Header carrying the token that confirms destructive statements
+pub const CONFIRMATION_HEADER: &str = "X-SQL-Confirmation";
+
+/// Pending confirmation, valid for one retry of the same SQL by the same user
+struct Confirmation {
+    user_id: i64,
+    cluster_id: i64,
+    sql_hash: String,
+    expires_at: Instant,
+}
+
+/// Classifies SQL before it runs on a cluster and enforces who may run what
+///
+/// Each role may run the statement categories listed in `[sql_guard]`; a
+/// cluster's `sql_policy` can restrict it further to reads. Destructive
+/// statements are rejected with a confirmation token and run when the same SQL
+/// is sent again with that token. Tokens are kept in memory and used once.
+pub struct SqlGuard {
+    config: SqlGuardConfig,
+    permission_service: Arc<PermissionService>,
+    confirmations: Mutex<HashMap<String, Confirmation>>,
+}
+
+impl SqlGuard {
+    pub fn new(config: SqlGuardConfig, permission_service: Arc<PermissionService>) -> Self {
+        Self { config, permission_service, confirmations: Mutex::new(HashMap::new()) }
+    }
+
+    /// Confirmation token sent with a request
+    pub fn confirmation(headers: &HeaderMap) -> Option<&str> {
+        headers
+            .get(CONFIRMATION_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+    }
+
+    /// Check every statement of `sql` before `user` runs it on `cluster`
+    pub async fn check(
+        &self,
+        user: &CurrentUser,
+        cluster: &Cluster,
+        sql: &str,
+        confirmation: Option<&str>,
+    ) -> ApiResult<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+        let role = self
+            .permission_service
+            .effective_role(user, cluster.id)
+            .await?;
+        self.check_role(role, user.id, cluster, sql, confirmation)
+            .inspect_err(|e| {
+                tracing::warn!(
+                    "SQL of user {} rejected on cluster {}: {}",
+                    user.username,
+                    cluster.name,
+                    e
+                )
+            })
+    }
+
+    fn check_role(
+        &self,
+        role: UserRole,
+        user_id: i64,
+        cluster: &Cluster,
+        sql: &str,
+        confirmation: Option<&str>,
+    ) -> ApiResult<()> {
+        let policy = cluster.sql_policy();
+        let allowed = self.config.allowed(role);
+        let mut unconfirmed = None;
+
+        for (index, statement) in sql::split_statements(sql)?.into_iter().enumerate() {
+            let class = sql::classify(statement.text)?;
+            if policy.read_only && class.category != StatementCategory::Read {
+                return Err(violation(
+                    SqlViolationReason::ClusterReadOnly,
+                    format!("Cluster {} is read-only; {} is not allowed", cluster.name, class.kind),
+                    index,
+                    &statement,
+                    &class,
+                ));
+            }
+            if !allowed.contains(&class.category) {
+                return Err(violation(
+                    SqlViolationReason::RoleNotAllowed,
+                    format!(
+                        "The {} role may not run {} statements ({})",
+                        role, class.category, class.kind
+                    ),
+                    index,
+                    &statement,
+                    &class,
+                ));
+            }
+            let needs_confirmation = (self.config.confirm_destructive && class.destructive)
+                || (policy.confirm_writes && class.category != StatementCategory::Read);
+            if needs_confirmation && unconfirmed.is_none() {
+                unconfirmed = Some((index, statement, class));
+            }
+        }
+
+        let Some((index, statement, class)) = unconfirmed else {
+            return Ok(());
+        };
+        let sql_hash = hash_secret(sql);
+        if let Some(token) = confirmation
+            && self.redeem(token, user_id, cluster.id, &sql_hash)
+        {
+            tracing::info!("Confirmed {} on cluster {}", class.kind, cluster.name);
+            return Ok(());
+        }
+
+        let mut error = violation(
+            SqlViolationReason::ConfirmationRequired,
+            format!(
+                "{} must be confirmed; send the request again with the {} header",
+                class.kind, CONFIRMATION_HEADER
+            ),
+            index,
+            &statement,
+            &class,
+        );
+        if let ApiError::SQLSafetyViolation(violation) = &mut error {
+            violation.confirmation_token = Some(self.issue(user_id, cluster.id, sql_hash));
+            violation.confirmation_expires_in_secs = Some(self.config.confirmation_ttl_secs);
+        }
+        Err(error)
+    }
+
+    fn issue(&self, user_id: i64, cluster_id: i64, sql_hash: String) -> String {
+        let token = generate_secret("src_", 24);
+        let now = Instant::now();
+        let mut confirmations = self.confirmations.lock().unwrap();
+        confirmations.retain(|_, c| c.expires_at > now);
+        confirmations.insert(
+            token.clone(),
+            Confirmation {
+                user_id,
+                cluster_id,
+                sql_hash,
+                expires_at: now + Duration::from_secs(self.config.confirmation_ttl_secs),
+            },
+        );
+        token
+    }
+
+    // Use up `token` if it was issued for this SQL, user and cluster
+    fn redeem(&self, token: &str, user_id: i64, cluster_id: i64, sql_hash: &str) -> bool {
+        let mut confirmations = self.confirmations.lock().unwrap();
+        let valid = confirmations.get(token).is_some_and(|c| {
+            c.user_id == user_id
+                && c.cluster_id == cluster_id
+                && c.sql_hash == sql_hash
+                && c.expires_at > Instant::now()
+        });
+        if valid {
+            confirmations.remove(token);
+        }
+        valid
+    }
+}
+
+/// Reject anything but a single query, for SQL saved to run later such as
+/// custom system functions
+pub fn require_query(sql: &str) -> ApiResult<()> {
+    let statements = sql::split_statements(sql)?;
+    let [statement] = statements.as_slice() else {
+        return Err(ApiError::invalid_sql(format!(
+            "Exactly one statement is allowed, found {}",
+            statements.len()
+        )));
+    };
+    let class = sql::classify(statement.text)?;
+    if class.is_query() {
+        return Ok(());
+    }
+    Err(violation(
+        SqlViolationReason::QueryRequired,
+        format!("Only SELECT, SHOW, DESCRIBE and EXPLAIN queries are allowed, not {}", class.kind),
+        0,
+        statement,
+        &class,
+    ))
+}
+
+fn violation(
+    reason: SqlViolationReason,
+    message: String,
+    index: usize,
+    statement: &Statement,
+    class: &StatementClass,
+) -> ApiError {
+    ApiError::sql_safety_violation(SqlViolation {
+        reason,
+        message,
+        statement_index: index,
+        statement: statement.text.to_string(),
+        category: class.category,
+        kind: class.kind.clone(),
+        destructive: class.destructive,
+        confirmation_token: None,
+        confirmation_expires_in_secs: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cluster(sql_policy: Option<&str>) -> Cluster {
+        Cluster { sql_policy: sql_policy.map(str::to_string), ..Cluster::for_test() }
+    }
+
+    async fn guard() -> SqlGuard {
+        let pool = crate::db::test_pool().await;
+        SqlGuard::new(SqlGuardConfig::default(), Arc::new(PermissionService::new(pool)))
+    }
+
+    fn violation_of(result: ApiResult<()>) -> SqlViolation {
+        match result {
+            Err(ApiError::SQLSafetyViolation(violation)) => *violation,
+            other => panic!("expected a violation, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_roles_and_read_only_clusters() {
+        let guard = guard().await;
+        let open = cluster(None);
+        let check =
+            |role, cluster: &Cluster, sql: &str| guard.check_role(role, 1, cluster, sql, None);
+
+        assert!(check(UserRole::Viewer, &open, "USE db; SELECT * FROM t").is_ok());
+        assert!(check(UserRole::Operator, &open, "INSERT INTO t VALUES (1)").is_ok());
+        assert!(check(UserRole::Admin, &open, "ALTER SYSTEM ADD BACKEND 'h:9050'").is_ok());
+
+        let rejected =
+            violation_of(check(UserRole::Viewer, &open, "SELECT 1; INSERT INTO t VALUES (1)"));
+        assert_eq!(rejected.reason, SqlViolationReason::RoleNotAllowed);
+        assert_eq!(rejected.statement_index, 1);
+        assert_eq!(rejected.category, StatementCategory::Dml);
+        let rejected =
+            violation_of(check(UserRole::Operator, &open, "SET GLOBAL query_timeout = 1"));
+        assert_eq!(rejected.reason, SqlViolationReason::RoleNotAllowed);
+        assert_eq!(rejected.kind, "SET GLOBAL");
+
+        let read_only = cluster(Some(r#"{"read_only":true}"#));
+        assert!(check(UserRole::Admin, &read_only, "SHOW FRONTENDS").is_ok());
+        let rejected = violation_of(check(UserRole::Admin, &read_only, "CREATE TABLE t (a INT)"));
+        assert_eq!(rejected.reason, SqlViolationReason::ClusterReadOnly);
+    }
+
+    #[tokio::test]
+    async fn test_destructive_statements_need_a_confirmation() {
+        let guard = guard().await;
+        let open = cluster(None);
+        let sql = "SELECT 1; DROP TABLE t";
+
+        let rejected = violation_of(guard.check_role(UserRole::Operator, 1, &open, sql, None));
+        assert_eq!(rejected.reason, SqlViolationReason::ConfirmationRequired);
+        assert_eq!(rejected.kind, "DROP TABLE");
+        let token = rejected.confirmation_token.unwrap();
+
+        // Bound to the SQL, the user and the cluster
+        let other = "SELECT 1; DROP TABLE u";
+        assert!(
+            guard
+                .check_role(UserRole::Operator, 1, &open, other, Some(&token))
+                .is_err()
+        );
+        assert!(
+            guard
+                .check_role(UserRole::Operator, 2, &open, sql, Some(&token))
+                .is_err()
+        );
+
+        // Each check issues a new token; a used token is gone
+        let token = violation_of(guard.check_role(UserRole::Operator, 1, &open, sql, None))
+            .confirmation_token
+            .unwrap();
+        assert!(
+            guard
+                .check_role(UserRole::Operator, 1, &open, sql, Some(&token))
+                .is_ok()
+        );
+        assert!(
+            guard
+                .check_role(UserRole::Operator, 1, &open, sql, Some(&token))
+                .is_err()
+        );
+
+        // Clusters can ask for confirmation of every write
+        let careful = cluster(Some(r#"{"confirm_writes":true}"#));
+        let insert = "INSERT INTO t VALUES (1)";
+        assert!(
+            guard
+                .check_role(UserRole::Operator, 1, &open, insert, None)
+                .is_ok()
+        );
+        let rejected =
+            violation_of(guard.check_role(UserRole::Operator, 1, &careful, insert, None));
+        assert_eq!(rejected.reason, SqlViolationReason::ConfirmationRequired);
+    }
+
+    #[test]
+    fn test_require_query() {
+        assert!(require_query("SELECT * FROM t;").is_ok());
+        assert!(require_query("show proc '/backends'").is_ok());
+        assert!(require_query("SELECT 1; SELECT 2").is_err());
+        assert!(require_query("SET query_timeout = 1").is_err());
+        let rejected = violation_of(require_query("/* x */ DELETE FROM t"));
+        assert_eq!(rejected.reason, SqlViolationReason::QueryRequired);
+        assert_eq!(rejected.kind, "DELETE");
+    }
+}
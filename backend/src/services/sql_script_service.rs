@@ -0,0 +1,243 @@
+use mysql_async::prelude::Queryable;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::models::{
+    Cluster, ResultColumn, SqlScriptRequest, SqlScriptResponse, SqlStatementResult,
+    SqlStatementStatus,
+};
+use crate::services::MySQLPoolManager;
+use crate::services::mysql_client::{result_column, use_context, value_to_json};
+use crate::services::mysql_pool_manager::PooledConn;
+use crate::utils::sql::{self, Statement};
+use crate::utils::{ApiError, ApiResult};
+
+/// Most statements one script may hold
+const MAX_STATEMENTS: usize = 500;
+
+/// Statements that would interfere with the transaction the script runs in
+const TRANSACTION_KEYWORDS: &[&str] = &["BEGIN", "START", "COMMIT", "ROLLBACK"];
+
+/// Runs SQL scripts statement by statement on one pinned connection
+///
+/// Session state set by a statement (`USE`, `SET`) is seen by the ones after
+/// it. The connection is reset when it goes back to the pool.
+pub struct SqlScriptService {
+    mysql_pool_manager: Arc<MySQLPoolManager>,
+}
+
+impl SqlScriptService {
+    pub fn new(mysql_pool_manager: Arc<MySQLPoolManager>) -> Self {
+        Self { mysql_pool_manager }
+    }
+
+    /// Run every statement of `request`; failing statements are reported in the
+    /// response, only failures before the first statement are errors
+    pub async fn run(
+        &self,
+        cluster: &Cluster,
+        request: &SqlScriptRequest,
+    ) -> ApiResult<SqlScriptResponse> {
+        let statements = plan(request)?;
+        let started = Instant::now();
+
+        let pool = self.mysql_pool_manager.get_pool(cluster).await?;
+        let mut conn = pool.get_conn().await.map_err(|e| {
+            ApiError::cluster_connection_failed(format!("Failed to get connection: {}", e))
+        })?;
+        use_context(&mut conn, request.catalog.as_deref(), request.database.as_deref()).await?;
+
+        if request.transaction {
+            conn.query_drop("BEGIN").await.map_err(|e| {
+                ApiError::internal_error(format!("Failed to begin transaction: {}", e))
+            })?;
+        }
+
+        let stop_on_error = request.stop_on_error || request.transaction;
+        let limit = request.limit as usize;
+        let mut results = Vec::with_capacity(statements.len());
+        let mut failed = false;
+        for (index, statement) in statements.iter().enumerate() {
+            let mut result = SqlStatementResult {
+                index,
+                sql: statement.text.to_string(),
+                offset: statement.start,
+                status: SqlStatementStatus::Skipped,
+                columns: Vec::new(),
+                rows: Vec::new(),
+                row_count: 0,
+                truncated: false,
+                affected_rows: 0,
+                error: None,
+                execution_time_ms: 0,
+            };
+            if failed && stop_on_error {
+                results.push(result);
+                continue;
+            }
+
+            let sql = sql::apply_query_limit(statement.text, request.limit);
+            let statement_started = Instant::now();
+            match run_statement(&mut conn, &sql, limit).await {
+                Ok(output) => {
+                    result.status = SqlStatementStatus::Succeeded;
+                    result.row_count = output.rows.len();
+                    result.columns = output.columns;
+                    result.rows = output.rows;
+                    result.truncated = output.truncated;
+                    result.affected_rows = output.affected_rows;
+                },
+                Err(e) => {
+                    tracing::info!(
+                        "Script statement {} on cluster {} failed: {}",
+                        index,
+                        cluster.name,
+                        e
+                    );
+                    failed = true;
+                    result.status = SqlStatementStatus::Failed;
+                    result.error = Some(format!("SQL execution failed: {}", e));
+                },
+            }
+            result.execution_time_ms = statement_started.elapsed().as_millis();
+            results.push(result);
+        }
+
+        let mut committed = None;
+        let mut error = None;
+        if request.transaction {
+            let end = if failed { "ROLLBACK" } else { "COMMIT" };
+            match conn.query_drop(end).await {
+                Ok(()) => committed = Some(!failed),
+                Err(e) => {
+                    tracing::warn!("{} on cluster {} failed: {}", end, cluster.name, e);
+                    committed = Some(false);
+                    error = Some(format!("{} failed: {}", end, e));
+                },
+            }
+        }
+
+        let count = |status| results.iter().filter(|r| r.status == status).count();
+        Ok(SqlScriptResponse {
+            succeeded: count(SqlStatementStatus::Succeeded),
+            failed: count(SqlStatementStatus::Failed),
+            skipped: count(SqlStatementStatus::Skipped),
+            statements: results,
+            committed,
+            error,
+            execution_time_ms: started.elapsed().as_millis(),
+        })
+    }
+}
+
+/// Split the script and check it before anything runs
+fn plan(request: &SqlScriptRequest) -> ApiResult<Vec<Statement<'_>>> {
+    if request.limit < 1 {
+        return Err(ApiError::validation_error("limit must be at least 1"));
+    }
+    let statements = sql::split_statements(&request.sql)?;
+    if statements.is_empty() {
+        return Err(ApiError::validation_error("SQL script is empty"));
+    }
+    if statements.len() > MAX_STATEMENTS {
+        return Err(ApiError::validation_error(format!(
+            "SQL script has {} statements, at most {} are allowed",
+            statements.len(),
+            MAX_STATEMENTS
+        )));
+    }
+    if request.transaction {
+        for (index, statement) in statements.iter().enumerate() {
+            let keyword = sql::leading_keyword(&sql::tokenize(statement.text)?);
+            if keyword.is_some_and(|k| TRANSACTION_KEYWORDS.contains(&k.as_str())) {
+                return Err(ApiError::validation_error(format!(
+                    "Statement {} controls the transaction itself; run the script without \
+                     `transaction` instead",
+                    index
+                )));
+            }
+        }
+    }
+    Ok(statements)
+}
+
+struct StatementOutput {
+    columns: Vec<ResultColumn>,
+    rows: Vec<Vec<serde_json::Value>>,
+    truncated: bool,
+    affected_rows: u64,
+}
+
+async fn run_statement(
+    conn: &mut PooledConn,
+    sql: &str,
+    limit: usize,
+) -> Result<StatementOutput, mysql_async::Error> {
+    let mut result = conn.query_iter(sql).await?;
+    let columns = result.columns().unwrap_or_else(|| Vec::new().into());
+    let affected_rows = result.affected_rows();
+
+    let mut rows = Vec::new();
+    let mut truncated = false;
+    while let Some(row) = result.next().await? {
+        // Statements LIMIT is not added to, such as SHOW, can return any number of rows
+        if rows.len() >= limit {
+            truncated = true;
+            break;
+        }
+        rows.push(
+            columns
+                .iter()
+                .enumerate()
+                .map(|(i, column)| {
+                    row.as_ref(i)
+                        .map_or(serde_json::Value::Null, |v| value_to_json(v, column))
+                })
+                .collect(),
+        );
+    }
+    result.drop_result().await?;
+
+    Ok(StatementOutput {
+        columns: columns.iter().map(result_column).collect(),
+        rows,
+        truncated,
+        affected_rows,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(sql: &str, transaction: bool) -> SqlScriptRequest {
+        SqlScriptRequest {
+            sql: sql.to_string(),
+            catalog: None,
+            database: None,
+            limit: 1000,
+            stop_on_error: true,
+            transaction,
+        }
+    }
+
+    #[test]
+    fn test_plan_splits_script() {
+        let request = request("INSERT INTO t VALUES (';');\n-- done\nSELECT * FROM t;", true);
+        let texts: Vec<&str> = plan(&request).unwrap().iter().map(|s| s.text).collect();
+        assert_eq!(texts, vec!["INSERT INTO t VALUES (';')", "SELECT * FROM t"]);
+    }
+
+    #[test]
+    fn test_plan_rejects_invalid_scripts() {
+        assert!(plan(&request(" ;; -- nothing", false)).is_err());
+        assert!(plan(&request("SELECT 'open", false)).is_err());
+        assert!(plan(&request(&"SELECT 1;".repeat(MAX_STATEMENTS + 1), false)).is_err());
+        assert!(plan(&SqlScriptRequest { limit: 0, ..request("SELECT 1", false) }).is_err());
+
+        // Transaction control is only rejected inside a wrapped script
+        let script = "BEGIN; INSERT INTO t VALUES (1); COMMIT";
+        assert!(plan(&request(script, true)).is_err());
+        assert_eq!(plan(&request(script, false)).unwrap().len(), 3);
+    }
+}
@@ -1,46 +1,168 @@
 use crate::models::{
-    Backend, Cluster, Database, Frontend, MaterializedView, Query, RuntimeInfo, SchemaChange, Table,
+    Backend, Cluster, Database, FeEndpoint, Frontend, MaterializedView, Query, RuntimeInfo,
+    SchemaChange, Table, TabletStatistic,
 };
-use crate::utils::{ApiError, ApiResult};
-use reqwest::Client;
+use crate::services::FeRegistry;
+use crate::utils::{ApiError, ApiResult, sql};
+use reqwest::{Certificate, Client, Identity, RequestBuilder, Response, StatusCode};
 use serde_json::Value;
 use std::time::Duration;
 
 pub struct StarRocksClient {
     pub http_client: Client,
     pub cluster: Cluster,
+    password: String,
 }
 
 impl StarRocksClient {
-    pub fn new(cluster: Cluster) -> Self {
-        let http_client = Client::builder()
-            .timeout(Duration::from_secs(cluster.connection_timeout as u64))
-            .build()
-            .unwrap_or_default();
+    pub fn new(cluster: Cluster) -> ApiResult<Self> {
+        let mut builder =
+            Client::builder().timeout(Duration::from_secs(cluster.connection_timeout as u64));
+
+        // Same TLS settings as the MySQL pools
+        if let Some(tls) = cluster.tls()? {
+            if let Some(ca_cert) = &tls.ca_cert_pem {
+                let certs = Certificate::from_pem_bundle(ca_cert.as_bytes()).map_err(|e| {
+                    ApiError::cluster_connection_failed(format!("Invalid TLS CA bundle: {}", e))
+                })?;
+                for cert in certs {
+                    builder = builder.add_root_certificate(cert);
+                }
+            }
+            if let Some(identity) = &tls.client_identity {
+                let identity = Identity::from_pkcs12_der(identity, &tls.client_identity_password)
+                    .map_err(|e| {
+                    ApiError::cluster_connection_failed(format!(
+                        "Invalid TLS client certificate: {}",
+                        e
+                    ))
+                })?;
+                builder = builder.identity(identity);
+            }
+            builder = builder.danger_accept_invalid_hostnames(!tls.verify_hostname);
+        }
 
-        Self { http_client, cluster }
+        let http_client = builder.build().map_err(|e| {
+            ApiError::cluster_connection_failed(format!("Failed to create HTTP client: {}", e))
+        })?;
+        let password = cluster.password()?;
+
+        Ok(Self { http_client, cluster, password })
     }
 
-    pub fn get_base_url(&self) -> String {
+    fn base_url(&self, fe: &FeEndpoint) -> String {
         let protocol = if self.cluster.enable_ssl { "https" } else { "http" };
-        format!("{}://{}:{}", protocol, self.cluster.fe_host, self.cluster.fe_http_port)
+        format!("{}://{}:{}", protocol, fe.host, fe.http_port)
+    }
+
+    /// GET an FE API path (e.g. `/api/show_proc?path=/backends`)
+    ///
+    /// Reads are idempotent, so an unreachable or unavailable FE is retried on the next one.
+    pub async fn get_with_failover(&self, path: &str) -> reqwest::Result<Response> {
+        let endpoints = FeRegistry::global().candidates(&self.cluster);
+        self.send_with_failover(&endpoints, true, |base| {
+            self.http_client.get(format!("{}{}", base, path))
+        })
+        .await
+    }
+
+    /// POST a read-only SQL request (`SHOW ...`), retried like `get_with_failover`
+    pub async fn post_with_failover(&self, path: &str, body: &Value) -> reqwest::Result<Response> {
+        let endpoints = FeRegistry::global().candidates(&self.cluster);
+        self.send_with_failover(&endpoints, true, |base| {
+            self.http_client
+                .post(format!("{}{}", base, path))
+                .json(body)
+        })
+        .await
+    }
+
+    /// POST a statement that changes the cluster, to the leader FE when it is known
+    ///
+    /// Another FE is only tried when no connection could be made, so a statement is
+    /// never run twice.
+    async fn post_to_leader(&self, path: &str, body: &Value) -> reqwest::Result<Response> {
+        let registry = FeRegistry::global();
+        if registry.leader(self.cluster.id).is_none()
+            && registry.candidates(&self.cluster).len() > 1
+        {
+            // Learn the leader first; on failure the usual order is used
+            let _ = self.get_frontends().await;
+        }
+
+        let endpoints = registry.leader_first(&self.cluster);
+        self.send_with_failover(&endpoints, false, |base| {
+            self.http_client
+                .post(format!("{}{}", base, path))
+                .json(body)
+        })
+        .await
+    }
+
+    async fn send_with_failover<F>(
+        &self,
+        endpoints: &[FeEndpoint],
+        idempotent: bool,
+        build: F,
+    ) -> reqwest::Result<Response>
+    where
+        F: Fn(&str) -> RequestBuilder,
+    {
+        let primary = self.cluster.primary_fe();
+        let (last, others) = endpoints.split_last().unwrap_or((&primary, &[]));
+
+        for fe in others {
+            match self.send_to(fe, &build).await {
+                Ok(response) if idempotent && Self::is_unavailable(response.status()) => {
+                    tracing::warn!("FE {} answered {}, trying the next FE", fe, response.status());
+                },
+                Err(e) if e.is_connect() || (idempotent && e.is_timeout()) => {
+                    tracing::warn!("FE {} is unreachable, trying the next FE: {}", fe, e);
+                },
+                result => return result,
+            }
+        }
+
+        self.send_to(last, &build).await
+    }
+
+    // Send one request to one FE and record whether the FE is usable
+    async fn send_to<F>(&self, fe: &FeEndpoint, build: &F) -> reqwest::Result<Response>
+    where
+        F: Fn(&str) -> RequestBuilder,
+    {
+        let result = build(&self.base_url(fe))
+            .basic_auth(&self.cluster.username, Some(&self.password))
+            .send()
+            .await;
+
+        let registry = FeRegistry::global();
+        match &result {
+            Ok(response) if Self::is_unavailable(response.status()) => registry.mark_down(fe),
+            Ok(_) => registry.mark_up(fe),
+            Err(e) if e.is_connect() || e.is_timeout() => registry.mark_down(fe),
+            Err(_) => {},
+        }
+        result
+    }
+
+    // Status codes of an FE that cannot serve requests right now (e.g. not ready after restart)
+    fn is_unavailable(status: StatusCode) -> bool {
+        matches!(
+            status,
+            StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+        )
     }
 
     // Get backends via HTTP API
     pub async fn get_backends(&self) -> ApiResult<Vec<Backend>> {
-        let url = format!("{}/api/show_proc?path=/backends", self.get_base_url());
-        tracing::debug!("Fetching backends from: {}", url);
+        let path = "/api/show_proc?path=/backends";
+        tracing::debug!("Fetching backends from: {}", path);
 
-        let response = self
-            .http_client
-            .get(&url)
-            .basic_auth(&self.cluster.username, Some(&self.cluster.password_encrypted))
-            .send()
-            .await
-            .map_err(|e| {
-                tracing::error!("Failed to fetch backends: {}", e);
-                ApiError::cluster_connection_failed(format!("Request failed: {}", e))
-            })?;
+        let response = self.get_with_failover(path).await.map_err(|e| {
+            tracing::error!("Failed to fetch backends: {}", e);
+            ApiError::cluster_connection_failed(format!("Request failed: {}", e))
+        })?;
 
         if !response.status().is_success() {
             tracing::error!("Backends API returned error status: {}", response.status());
@@ -69,24 +191,17 @@ impl StarRocksClient {
 
     // Execute SQL command via HTTP API
     pub async fn execute_sql(&self, sql: &str) -> ApiResult<()> {
-        let url = format!("{}/api/query", self.get_base_url());
+        let path = "/api/query";
         tracing::debug!("Executing SQL: {}", sql);
 
         let body = serde_json::json!({
             "query": sql
         });
 
-        let response = self
-            .http_client
-            .post(&url)
-            .basic_auth(&self.cluster.username, Some(&self.cluster.password_encrypted))
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| {
-                tracing::error!("Failed to execute SQL: {}", e);
-                ApiError::cluster_connection_failed(format!("Request failed: {}", e))
-            })?;
+        let response = self.post_to_leader(path, &body).await.map_err(|e| {
+            tracing::error!("Failed to execute SQL: {}", e);
+            ApiError::cluster_connection_failed(format!("Request failed: {}", e))
+        })?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -104,26 +219,27 @@ impl StarRocksClient {
 
     // Drop backend node
     pub async fn drop_backend(&self, host: &str, heartbeat_port: &str) -> ApiResult<()> {
-        let sql = format!("ALTER SYSTEM DROP backend \"{}:{}\"", host, heartbeat_port);
+        if heartbeat_port.parse::<u16>().is_err() {
+            return Err(ApiError::validation_error(format!(
+                "Invalid heartbeat port: {:?}",
+                heartbeat_port
+            )));
+        }
+        let address = sql::quote_literal(&format!("{}:{}", host, heartbeat_port));
+        let sql = format!("ALTER SYSTEM DROP backend {}", address);
         tracing::info!("Dropping backend: {}:{}", host, heartbeat_port);
         self.execute_sql(&sql).await
     }
 
     // Get frontends via HTTP API
     pub async fn get_frontends(&self) -> ApiResult<Vec<Frontend>> {
-        let url = format!("{}/api/show_proc?path=/frontends", self.get_base_url());
-        tracing::debug!("Fetching frontends from: {}", url);
+        let path = "/api/show_proc?path=/frontends";
+        tracing::debug!("Fetching frontends from: {}", path);
 
-        let response = self
-            .http_client
-            .get(&url)
-            .basic_auth(&self.cluster.username, Some(&self.cluster.password_encrypted))
-            .send()
-            .await
-            .map_err(|e| {
-                tracing::error!("Failed to fetch frontends: {}", e);
-                ApiError::cluster_connection_failed(format!("Request failed: {}", e))
-            })?;
+        let response = self.get_with_failover(path).await.map_err(|e| {
+            tracing::error!("Failed to fetch frontends: {}", e);
+            ApiError::cluster_connection_failed(format!("Request failed: {}", e))
+        })?;
 
         if !response.status().is_success() {
             tracing::error!("Frontends API returned error status: {}", response.status());
@@ -139,30 +255,51 @@ impl StarRocksClient {
         })?;
 
         // Try new format (direct array) first, then fall back to old format
-        if let Ok(frontends) = serde_json::from_value::<Vec<Frontend>>(data.clone()) {
-            return Ok(frontends);
-        }
+        let frontends = match serde_json::from_value::<Vec<Frontend>>(data.clone()) {
+            Ok(frontends) => frontends,
+            Err(_) => Self::parse_proc_result::<Frontend>(&data)?,
+        };
 
-        // Fallback to old format
-        let frontends = Self::parse_proc_result::<Frontend>(&data)?;
+        // Other FEs and the leader are used for failover and DDL routing
+        FeRegistry::global().record_frontends(self.cluster.id, &frontends);
         Ok(frontends)
     }
 
+    // Tablet health per database; the last row is the total over all databases
+    pub async fn get_tablet_statistics(&self) -> ApiResult<Vec<TabletStatistic>> {
+        let path = "/api/show_proc?path=/statistic";
+        tracing::debug!("Fetching tablet statistics from: {}", path);
+
+        let response = self.get_with_failover(path).await.map_err(|e| {
+            tracing::error!("Failed to fetch tablet statistics: {}", e);
+            ApiError::cluster_connection_failed(format!("Request failed: {}", e))
+        })?;
+
+        if !response.status().is_success() {
+            tracing::error!("Statistic API returned error status: {}", response.status());
+            return Err(ApiError::cluster_connection_failed(format!(
+                "HTTP status: {}",
+                response.status()
+            )));
+        }
+
+        let data: Value = response.json().await.map_err(|e| {
+            tracing::error!("Failed to parse tablet statistics response: {}", e);
+            ApiError::cluster_connection_failed(format!("Failed to parse response: {}", e))
+        })?;
+
+        Self::parse_proc_result::<TabletStatistic>(&data)
+    }
+
     // Get current queries
     pub async fn get_queries(&self) -> ApiResult<Vec<Query>> {
-        let url = format!("{}/api/show_proc?path=/current_queries", self.get_base_url());
-        tracing::debug!("Fetching current queries from: {}", url);
+        let path = "/api/show_proc?path=/current_queries";
+        tracing::debug!("Fetching current queries from: {}", path);
 
-        let response = self
-            .http_client
-            .get(&url)
-            .basic_auth(&self.cluster.username, Some(&self.cluster.password_encrypted))
-            .send()
-            .await
-            .map_err(|e| {
-                tracing::error!("Failed to send request to StarRocks: {}", e);
-                ApiError::cluster_connection_failed(format!("Request failed: {}", e))
-            })?;
+        let response = self.get_with_failover(path).await.map_err(|e| {
+            tracing::error!("Failed to send request to StarRocks: {}", e);
+            ApiError::cluster_connection_failed(format!("Request failed: {}", e))
+        })?;
 
         if !response.status().is_success() {
             tracing::warn!("Failed to fetch current_queries, HTTP status: {}", response.status());
@@ -220,13 +357,10 @@ impl StarRocksClient {
 
     // Get runtime info
     pub async fn get_runtime_info(&self) -> ApiResult<RuntimeInfo> {
-        let url = format!("{}/api/show_runtime_info", self.get_base_url());
+        let path = "/api/show_runtime_info";
 
         let response = self
-            .http_client
-            .get(&url)
-            .basic_auth(&self.cluster.username, Some(&self.cluster.password_encrypted))
-            .send()
+            .get_with_failover(path)
             .await
             .map_err(|e| ApiError::cluster_connection_failed(format!("Request failed: {}", e)))?;
 
@@ -246,13 +380,10 @@ impl StarRocksClient {
 
     // Get metrics in Prometheus format
     pub async fn get_metrics(&self) -> ApiResult<String> {
-        let url = format!("{}/metrics", self.get_base_url());
+        let path = "/metrics";
 
         let response = self
-            .http_client
-            .get(&url)
-            .basic_auth(&self.cluster.username, Some(&self.cluster.password_encrypted))
-            .send()
+            .get_with_failover(path)
             .await
             .map_err(|e| ApiError::cluster_connection_failed(format!("Request failed: {}", e)))?;
 
@@ -438,23 +569,16 @@ impl StarRocksClient {
         tracing::debug!("Fetching databases with SQL: {}", sql);
 
         let catalog_name = catalog.unwrap_or(&self.cluster.catalog);
-        let url = format!("{}/api/v1/catalogs/{}/sql", self.get_base_url(), catalog_name);
+        let path = format!("/api/v1/catalogs/{}/sql", catalog_name);
 
         let body = serde_json::json!({
             "query": sql
         });
 
-        let response = self
-            .http_client
-            .post(&url)
-            .basic_auth(&self.cluster.username, Some(&self.cluster.password_encrypted))
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| {
-                tracing::error!("Failed to fetch databases: {}", e);
-                ApiError::cluster_connection_failed(format!("Request failed: {}", e))
-            })?;
+        let response = self.post_with_failover(&path, &body).await.map_err(|e| {
+            tracing::error!("Failed to fetch databases: {}", e);
+            ApiError::cluster_connection_failed(format!("Request failed: {}", e))
+        })?;
 
         if !response.status().is_success() {
             tracing::warn!("Failed to fetch databases: {}", response.status());
@@ -508,7 +632,7 @@ impl StarRocksClient {
     ) -> ApiResult<Vec<MaterializedView>> {
         // Build SQL: SHOW MATERIALIZED VIEWS [FROM database]
         let sql = if let Some(db) = database {
-            format!("SHOW MATERIALIZED VIEWS FROM `{}`", db)
+            format!("SHOW MATERIALIZED VIEWS FROM {}", sql::quote_identifier(db)?)
         } else {
             "SHOW MATERIALIZED VIEWS".to_string()
         };
@@ -517,23 +641,16 @@ impl StarRocksClient {
 
         // Use /api/v1/catalogs/{catalog}/sql endpoint to execute SQL
         let catalog = &self.cluster.catalog;
-        let url = format!("{}/api/v1/catalogs/{}/sql", self.get_base_url(), catalog);
+        let path = format!("/api/v1/catalogs/{}/sql", catalog);
 
         let body = serde_json::json!({
             "query": sql
         });
 
-        let response = self
-            .http_client
-            .post(&url)
-            .basic_auth(&self.cluster.username, Some(&self.cluster.password_encrypted))
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| {
-                tracing::error!("Failed to fetch async materialized views: {}", e);
-                ApiError::cluster_connection_failed(format!("Request failed: {}", e))
-            })?;
+        let response = self.post_with_failover(&path, &body).await.map_err(|e| {
+            tracing::error!("Failed to fetch async materialized views: {}", e);
+            ApiError::cluster_connection_failed(format!("Request failed: {}", e))
+        })?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -567,7 +684,7 @@ impl StarRocksClient {
         database: Option<&str>,
     ) -> ApiResult<Vec<MaterializedView>> {
         let sql = if let Some(db) = database {
-            format!("SHOW ALTER MATERIALIZED VIEW FROM `{}`", db)
+            format!("SHOW ALTER MATERIALIZED VIEW FROM {}", sql::quote_identifier(db)?)
         } else {
             "SHOW ALTER MATERIALIZED VIEW".to_string()
         };
@@ -575,23 +692,16 @@ impl StarRocksClient {
         tracing::debug!("Fetching sync materialized views with SQL: {}", sql);
 
         let catalog = &self.cluster.catalog;
-        let url = format!("{}/api/v1/catalogs/{}/sql", self.get_base_url(), catalog);
+        let path = format!("/api/v1/catalogs/{}/sql", catalog);
 
         let body = serde_json::json!({
             "query": sql
         });
 
-        let response = self
-            .http_client
-            .post(&url)
-            .basic_auth(&self.cluster.username, Some(&self.cluster.password_encrypted))
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| {
-                tracing::warn!("Failed to fetch sync materialized views: {}", e);
-                ApiError::cluster_connection_failed(format!("Request failed: {}", e))
-            })?;
+        let response = self.post_with_failover(&path, &body).await.map_err(|e| {
+            tracing::warn!("Failed to fetch sync materialized views: {}", e);
+            ApiError::cluster_connection_failed(format!("Request failed: {}", e))
+        })?;
 
         if !response.status().is_success() {
             tracing::warn!("Sync MV query returned non-success status: {}", response.status());
@@ -612,22 +722,18 @@ impl StarRocksClient {
     // Get single materialized view details
     #[allow(dead_code)]
     pub async fn get_materialized_view(&self, mv_name: &str) -> ApiResult<MaterializedView> {
-        let sql = format!("SHOW MATERIALIZED VIEWS WHERE NAME = '{}'", mv_name);
+        let sql = format!("SHOW MATERIALIZED VIEWS WHERE NAME = {}", sql::quote_literal(mv_name));
         tracing::debug!("Fetching materialized view details with SQL: {}", sql);
 
         let catalog = &self.cluster.catalog;
-        let url = format!("{}/api/v1/catalogs/{}/sql", self.get_base_url(), catalog);
+        let path = format!("/api/v1/catalogs/{}/sql", catalog);
 
         let body = serde_json::json!({
             "query": sql
         });
 
         let response = self
-            .http_client
-            .post(&url)
-            .basic_auth(&self.cluster.username, Some(&self.cluster.password_encrypted))
-            .json(&body)
-            .send()
+            .post_with_failover(&path, &body)
             .await
             .map_err(|e| ApiError::cluster_connection_failed(format!("Request failed: {}", e)))?;
 
@@ -651,22 +757,18 @@ impl StarRocksClient {
     // Get materialized view DDL
     #[allow(dead_code)]
     pub async fn get_materialized_view_ddl(&self, mv_name: &str) -> ApiResult<String> {
-        let sql = format!("SHOW CREATE MATERIALIZED VIEW `{}`", mv_name);
+        let sql = format!("SHOW CREATE MATERIALIZED VIEW {}", sql::quote_identifier(mv_name)?);
         tracing::debug!("Fetching materialized view DDL with SQL: {}", sql);
 
         let catalog = &self.cluster.catalog;
-        let url = format!("{}/api/v1/catalogs/{}/sql", self.get_base_url(), catalog);
+        let path = format!("/api/v1/catalogs/{}/sql", catalog);
 
         let body = serde_json::json!({
             "query": sql
         });
 
         let response = self
-            .http_client
-            .post(&url)
-            .basic_auth(&self.cluster.username, Some(&self.cluster.password_encrypted))
-            .json(&body)
-            .send()
+            .post_with_failover(&path, &body)
             .await
             .map_err(|e| ApiError::cluster_connection_failed(format!("Request failed: {}", e)))?;
 
@@ -842,19 +944,13 @@ impl StarRocksClient {
     /// Get list of databases
     #[allow(dead_code)]
     pub async fn get_databases(&self) -> ApiResult<Vec<Database>> {
-        let url = format!("{}/api/show_proc?path=/dbs", self.get_base_url());
-        tracing::debug!("Fetching databases from: {}", url);
+        let path = "/api/show_proc?path=/dbs";
+        tracing::debug!("Fetching databases from: {}", path);
 
-        let response = self
-            .http_client
-            .get(&url)
-            .basic_auth(&self.cluster.username, Some(&self.cluster.password_encrypted))
-            .send()
-            .await
-            .map_err(|e| {
-                tracing::error!("Failed to fetch databases: {}", e);
-                ApiError::cluster_connection_failed(format!("Request failed: {}", e))
-            })?;
+        let response = self.get_with_failover(path).await.map_err(|e| {
+            tracing::error!("Failed to fetch databases: {}", e);
+            ApiError::cluster_connection_failed(format!("Request failed: {}", e))
+        })?;
 
         if !response.status().is_success() {
             tracing::error!("Databases API returned error status: {}", response.status());
@@ -877,23 +973,13 @@ impl StarRocksClient {
     /// Get list of tables in a database
     #[allow(dead_code)]
     pub async fn get_tables(&self, database: &str) -> ApiResult<Vec<Table>> {
-        let url = format!(
-            "{}/api/show_proc?path=/dbs/{}/tables",
-            self.get_base_url(),
-            urlencoding::encode(database)
-        );
-        tracing::debug!("Fetching tables from database '{}': {}", database, url);
+        let path = format!("/api/show_proc?path=/dbs/{}/tables", urlencoding::encode(database));
+        tracing::debug!("Fetching tables from database '{}': {}", database, path);
 
-        let response = self
-            .http_client
-            .get(&url)
-            .basic_auth(&self.cluster.username, Some(&self.cluster.password_encrypted))
-            .send()
-            .await
-            .map_err(|e| {
-                tracing::error!("Failed to fetch tables: {}", e);
-                ApiError::cluster_connection_failed(format!("Request failed: {}", e))
-            })?;
+        let response = self.get_with_failover(&path).await.map_err(|e| {
+            tracing::error!("Failed to fetch tables: {}", e);
+            ApiError::cluster_connection_failed(format!("Request failed: {}", e))
+        })?;
 
         if !response.status().is_success() {
             tracing::error!("Tables API returned error status: {}", response.status());
@@ -916,19 +1002,13 @@ impl StarRocksClient {
     /// Get schema changes status
     #[allow(dead_code)]
     pub async fn get_schema_changes(&self) -> ApiResult<Vec<SchemaChange>> {
-        let url = format!("{}/api/show_proc?path=/jobs", self.get_base_url());
-        tracing::debug!("Fetching schema changes from: {}", url);
+        let path = "/api/show_proc?path=/jobs";
+        tracing::debug!("Fetching schema changes from: {}", path);
 
-        let response = self
-            .http_client
-            .get(&url)
-            .basic_auth(&self.cluster.username, Some(&self.cluster.password_encrypted))
-            .send()
-            .await
-            .map_err(|e| {
-                tracing::error!("Failed to fetch schema changes: {}", e);
-                ApiError::cluster_connection_failed(format!("Request failed: {}", e))
-            })?;
+        let response = self.get_with_failover(path).await.map_err(|e| {
+            tracing::error!("Failed to fetch schema changes: {}", e);
+            ApiError::cluster_connection_failed(format!("Request failed: {}", e))
+        })?;
 
         if !response.status().is_success() {
             tracing::error!("Schema changes API returned error status: {}", response.status());
@@ -1,13 +1,12 @@
-use serde_json::Value;
 use sqlx::SqlitePool;
 use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::models::{
-    CreateFunctionRequest, SystemFunction, SystemFunctionPreference, UpdateFunctionRequest,
-    UpdateOrderRequest,
+    CreateFunctionRequest, FunctionExecuteResponse, SystemFunction, SystemFunctionPreference,
+    UpdateFunctionRequest, UpdateOrderRequest,
 };
-use crate::services::{ClusterService, MySQLClient, MySQLPoolManager};
+use crate::services::{ClusterService, MySQLClient, MySQLPoolManager, QueryCache};
 use crate::utils::{ApiError, ApiResult};
 
 #[derive(Clone)]
@@ -15,6 +14,7 @@ pub struct SystemFunctionService {
     db: Arc<SqlitePool>,
     mysql_pool_manager: Arc<MySQLPoolManager>,
     cluster_service: Arc<ClusterService>,
+    query_cache: Arc<QueryCache>,
 }
 
 impl SystemFunctionService {
@@ -22,8 +22,9 @@ impl SystemFunctionService {
         db: Arc<SqlitePool>,
         mysql_pool_manager: Arc<MySQLPoolManager>,
         cluster_service: Arc<ClusterService>,
+        query_cache: Arc<QueryCache>,
     ) -> Self {
-        Self { db, mysql_pool_manager, cluster_service }
+        Self { db, mysql_pool_manager, cluster_service, query_cache }
     }
 
     pub async fn get_functions(&self, cluster_id: i64) -> ApiResult<Vec<SystemFunction>> {
@@ -114,7 +115,7 @@ impl SystemFunctionService {
         }
 
         // Validate SQL safety
-        self.validate_sql_safety(&sql_query)?;
+        Self::validate_sql_safety(&sql_query)?;
 
         // Check function count limit per category (4 functions)
         let count: i64 = sqlx::query_scalar(
@@ -184,7 +185,8 @@ impl SystemFunctionService {
         &self,
         cluster_id: i64,
         function_id: i64,
-    ) -> ApiResult<Vec<HashMap<String, Value>>> {
+        cache_ttl_secs: u64,
+    ) -> ApiResult<FunctionExecuteResponse> {
         // Get function information
         let function = sqlx::query_as::<_, SystemFunction>(
             "SELECT * FROM system_functions WHERE id = ? AND cluster_id = ?",
@@ -199,6 +201,9 @@ impl SystemFunctionService {
             None => return Err(ApiError::not_found("Function not found or deleted")),
         };
 
+        // Functions saved before the check existed may hold anything
+        Self::validate_sql_safety(&function.sql_query)?;
+
         // Update function updated_at timestamp
         sqlx::query("UPDATE system_functions SET updated_at = CURRENT_TIMESTAMP WHERE id = ?")
             .bind(function_id)
@@ -208,25 +213,36 @@ impl SystemFunctionService {
         // Get cluster information
         let cluster = self.cluster_service.get_cluster(cluster_id).await?;
 
-        // Execute SQL query using MySQL client
-        let pool = self.mysql_pool_manager.get_pool(&cluster).await?;
-        let mysql_client = MySQLClient::from_pool(pool);
-
-        let (columns, rows) = mysql_client.query_raw(&function.sql_query, None, None).await?;
+        // Execute SQL query using MySQL client, or reuse a cached result
+        let result = self
+            .query_cache
+            .get_or_load(cluster.id, None, None, &function.sql_query, cache_ttl_secs, || async {
+                let pool = self.mysql_pool_manager.get_pool(&cluster).await?;
+                MySQLClient::from_pool(pool)
+                    .query_typed(&function.sql_query, None, None)
+                    .await
+            })
+            .await?;
+        let (columns, rows) = result.value;
 
-        // Convert to HashMap format
-        let result: Vec<HashMap<String, Value>> = rows
+        // One object per row, keyed by column name
+        let data: Vec<serde_json::Map<String, serde_json::Value>> = rows
             .into_iter()
             .map(|row| {
                 columns
                     .iter()
-                    .zip(row.iter())
-                    .map(|(col, val)| (col.clone(), Value::String(val.clone())))
+                    .map(|col| col.name.clone())
+                    .zip(row)
                     .collect()
             })
             .collect();
 
-        Ok(result)
+        Ok(FunctionExecuteResponse {
+            columns,
+            row_count: data.len(),
+            data,
+            cached_at: result.cached_at,
+        })
     }
 
     // Update system function access time
@@ -356,7 +372,7 @@ impl SystemFunctionService {
         }
 
         // Validate SQL safety
-        self.validate_sql_safety(&sql_query)?;
+        Self::validate_sql_safety(&sql_query)?;
 
         // 更新功能
         sqlx::query(
@@ -396,33 +412,9 @@ impl SystemFunctionService {
         Ok(())
     }
 
-    // Validate SQL safety (only allow SELECT/SHOW)
-    fn validate_sql_safety(&self, sql: &str) -> ApiResult<()> {
-        let trimmed_sql = sql.trim().to_uppercase();
-
-        // Check if starts with SELECT or SHOW
-        if !trimmed_sql.starts_with("SELECT") && !trimmed_sql.starts_with("SHOW") {
-            return Err(ApiError::invalid_sql("Only SELECT and SHOW type SQL queries are allowed"));
-        }
-
-        // Check for dangerous keywords
-        let dangerous_keywords = vec![
-            "DROP", "DELETE", "UPDATE", "INSERT", "ALTER", "CREATE", "TRUNCATE", "EXEC", "EXECUTE",
-            "CALL", "GRANT", "REVOKE", "COMMIT", "ROLLBACK",
-        ];
-
-        for keyword in dangerous_keywords {
-            if trimmed_sql.contains(&format!(" {}", keyword))
-                || trimmed_sql.contains(&format!("{} ", keyword))
-            {
-                return Err(ApiError::sql_safety_violation(format!(
-                    "SQL查询包含不允许的关键字：{}",
-                    keyword
-                )));
-            }
-        }
-
-        Ok(())
+    // Validate SQL safety (only allow a single query)
+    pub fn validate_sql_safety(sql: &str) -> ApiResult<()> {
+        crate::services::sql_guard::require_query(sql)
     }
 
     // Delete category
@@ -0,0 +1,518 @@
+use chrono::Utc;
+use sqlx::SqlitePool;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use crate::models::{
+    Backend, Cluster, ClusterTopology, Frontend, HealthCheck, RunMode, Severity, TopologyChange,
+    TopologyChangeKind, TopologyChangeRecord, TopologyComponent, TopologyDiscovery,
+    TopologyFrontend, TopologyNode, TopologyRefreshResponse, TopologyWarehouse,
+};
+use crate::services::{ClusterService, MySQLClient, MySQLPoolManager, StarRocksClient};
+use crate::utils::{ApiError, ApiResult, ScheduledTask};
+
+/// Topology changes returned when no limit is given
+const DEFAULT_CHANGES_LIMIT: i64 = 100;
+
+/// Discovers cluster topology and tracks how it changes over time
+///
+/// FEs and BEs come from the FE HTTP API; run mode, compute nodes and warehouses
+/// from the MySQL port, so a discovery also validates the credentials on both.
+pub struct TopologyService {
+    pool: SqlitePool,
+    cluster_service: Arc<ClusterService>,
+    mysql_pool_manager: Arc<MySQLPoolManager>,
+}
+
+impl TopologyService {
+    pub fn new(
+        pool: SqlitePool,
+        cluster_service: Arc<ClusterService>,
+        mysql_pool_manager: Arc<MySQLPoolManager>,
+    ) -> Self {
+        Self { pool, cluster_service, mysql_pool_manager }
+    }
+
+    // Probe a cluster, which need not be registered yet
+    pub async fn discover(&self, cluster: &Cluster) -> ApiResult<TopologyDiscovery> {
+        let mut topology = ClusterTopology {
+            run_mode: RunMode::Unknown,
+            version: None,
+            frontends: Vec::new(),
+            backends: Vec::new(),
+            compute_nodes: Vec::new(),
+            warehouses: Vec::new(),
+            discovered_at: Utc::now(),
+        };
+        let mut checks = Vec::new();
+
+        let client = StarRocksClient::new(cluster.clone())?;
+        match tokio::try_join!(client.get_frontends(), client.get_backends()) {
+            Ok((frontends, backends)) => {
+                checks.push(check(
+                    "HTTP",
+                    Ok(format!("{} FEs and {} BEs found", frontends.len(), backends.len())),
+                ));
+                topology.version = frontends
+                    .iter()
+                    .find(|fe| is_leader(fe))
+                    .or(frontends.first())
+                    .map(|fe| fe.version.clone());
+                topology.frontends = frontends.iter().map(topology_frontend).collect();
+                topology.backends = backends.iter().map(topology_backend).collect();
+            },
+            Err(e) => checks.push(check("HTTP", Err(e))),
+        }
+
+        let mysql = self.discover_via_mysql(cluster, &mut topology).await;
+        checks.push(check("MySQL", mysql.map(|()| "Credentials accepted".to_string())));
+
+        // Older versions do not expose run_mode; compute nodes without BEs mean shared-data
+        if topology.run_mode == RunMode::Unknown {
+            if !topology.compute_nodes.is_empty() && topology.backends.is_empty() {
+                topology.run_mode = RunMode::SharedData;
+            } else if !topology.backends.is_empty() {
+                topology.run_mode = RunMode::SharedNothing;
+            }
+        }
+
+        Ok(TopologyDiscovery { checks, topology })
+    }
+
+    async fn discover_via_mysql(
+        &self,
+        cluster: &Cluster,
+        topology: &mut ClusterTopology,
+    ) -> ApiResult<()> {
+        let pool = self.mysql_pool_manager.get_pool(cluster).await?;
+        let client = MySQLClient::from_pool(pool);
+        client.query_raw("SELECT 1", None, None).await?;
+
+        // The statements below depend on version and run mode; missing ones are skipped
+        if let Ok((columns, rows)) = client
+            .query_raw("ADMIN SHOW FRONTEND CONFIG LIKE 'run_mode'", None, None)
+            .await
+            && let Some(value) = rows.first().and_then(|row| cell(&columns, row, "Value"))
+        {
+            topology.run_mode = match value.to_lowercase().as_str() {
+                "shared_data" => RunMode::SharedData,
+                "shared_nothing" => RunMode::SharedNothing,
+                _ => RunMode::Unknown,
+            };
+        }
+
+        if let Ok((columns, rows)) = client.query_raw("SHOW COMPUTE NODES", None, None).await {
+            topology.compute_nodes = rows
+                .iter()
+                .map(|row| TopologyNode {
+                    id: cell(&columns, row, "ComputeNodeId")
+                        .unwrap_or_default()
+                        .to_string(),
+                    host: cell(&columns, row, "IP").unwrap_or_default().to_string(),
+                    heartbeat_port: port(cell(&columns, row, "HeartbeatPort")),
+                    alive: flag(cell(&columns, row, "Alive")),
+                    decommissioned: flag(cell(&columns, row, "SystemDecommissioned")),
+                    warehouse: cell(&columns, row, "WarehouseName")
+                        .filter(|w| !w.is_empty())
+                        .map(str::to_string),
+                })
+                .collect();
+        }
+
+        if let Ok((columns, rows)) = client.query_raw("SHOW WAREHOUSES", None, None).await {
+            topology.warehouses = rows
+                .iter()
+                .filter_map(|row| {
+                    Some(TopologyWarehouse {
+                        name: cell(&columns, row, "Name")?.to_string(),
+                        state: cell(&columns, row, "State").unwrap_or_default().to_string(),
+                        node_count: cell(&columns, row, "NodeCount").and_then(|n| n.parse().ok()),
+                    })
+                })
+                .collect();
+        }
+
+        Ok(())
+    }
+
+    // Last stored topology of a cluster
+    pub async fn snapshot(&self, cluster_id: i64) -> ApiResult<Option<ClusterTopology>> {
+        let row: Option<(String,)> =
+            sqlx::query_as("SELECT topology FROM cluster_topologies WHERE cluster_id = ?")
+                .bind(cluster_id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        row.map(|(json,)| serde_json::from_str(&json).map_err(Into::into))
+            .transpose()
+    }
+
+    // Rediscover a registered cluster, store the snapshot and record what changed
+    pub async fn refresh(&self, cluster: &Cluster) -> ApiResult<TopologyRefreshResponse> {
+        let discovery = self.discover(cluster).await?;
+        if let Some(failure) = discovery.failure() {
+            // A partial topology would show every missing node as removed
+            return Err(ApiError::cluster_connection_failed(failure));
+        }
+
+        let previous = self.snapshot(cluster.id).await?;
+        let changes = previous
+            .as_ref()
+            .map(|previous| diff_topology(previous, &discovery.topology))
+            .unwrap_or_default();
+        self.save(cluster.id, &discovery.topology, &changes).await?;
+
+        for change in &changes {
+            tracing::warn!(
+                "Topology of cluster {} changed: {:?} {:?} {} {}",
+                cluster.name,
+                change.component,
+                change.change,
+                change.node,
+                change.detail.as_deref().unwrap_or_default()
+            );
+        }
+
+        Ok(TopologyRefreshResponse {
+            topology: discovery.topology,
+            changes,
+            first_snapshot: previous.is_none(),
+        })
+    }
+
+    // Store the first snapshot of a freshly imported cluster
+    pub async fn record(&self, cluster_id: i64, topology: &ClusterTopology) -> ApiResult<()> {
+        self.save(cluster_id, topology, &[]).await
+    }
+
+    // Recorded changes of a cluster, newest first
+    pub async fn changes(
+        &self,
+        cluster_id: i64,
+        limit: Option<i64>,
+    ) -> ApiResult<Vec<TopologyChangeRecord>> {
+        let changes = sqlx::query_as(
+            "SELECT * FROM cluster_topology_changes WHERE cluster_id = ? \
+             ORDER BY detected_at DESC, id DESC LIMIT ?",
+        )
+        .bind(cluster_id)
+        .bind(limit.unwrap_or(DEFAULT_CHANGES_LIMIT).clamp(1, 1000))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(changes)
+    }
+
+    async fn save(
+        &self,
+        cluster_id: i64,
+        topology: &ClusterTopology,
+        changes: &[TopologyChange],
+    ) -> ApiResult<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "INSERT INTO cluster_topologies (cluster_id, topology, discovered_at) VALUES (?, ?, ?) \
+             ON CONFLICT(cluster_id) DO UPDATE SET topology = excluded.topology, \
+             discovered_at = excluded.discovered_at",
+        )
+        .bind(cluster_id)
+        .bind(serde_json::to_string(topology)?)
+        .bind(topology.discovered_at)
+        .execute(&mut *tx)
+        .await?;
+
+        for change in changes {
+            sqlx::query(
+                "INSERT INTO cluster_topology_changes \
+                 (cluster_id, component, change, node, detail, detected_at) \
+                 VALUES (?, ?, ?, ?, ?, ?)",
+            )
+            .bind(cluster_id)
+            .bind(change.component)
+            .bind(change.change)
+            .bind(&change.node)
+            .bind(&change.detail)
+            .bind(topology.discovered_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn refresh_all(&self) -> ApiResult<()> {
+        let clusters = self.cluster_service.list_clusters().await?;
+        for cluster in &clusters {
+            if let Err(e) = self.refresh(cluster).await {
+                tracing::warn!("Topology refresh of cluster {} failed: {}", cluster.name, e);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ScheduledTask for TopologyService {
+    fn run(&self) -> Pin<Box<dyn Future<Output = Result<(), anyhow::Error>> + Send + '_>> {
+        Box::pin(async move { Ok(self.refresh_all().await?) })
+    }
+
+    fn name(&self) -> &str {
+        "topology-refresh"
+    }
+}
+
+/// Changes from `old` to `new`, in a stable order
+pub fn diff_topology(old: &ClusterTopology, new: &ClusterTopology) -> Vec<TopologyChange> {
+    let mut changes = Vec::new();
+
+    let mut cluster_fields = Vec::new();
+    field(
+        &mut cluster_fields,
+        "run_mode",
+        format!("{:?}", old.run_mode),
+        format!("{:?}", new.run_mode),
+    );
+    field(
+        &mut cluster_fields,
+        "version",
+        old.version.as_deref().unwrap_or("-"),
+        new.version.as_deref().unwrap_or("-"),
+    );
+    for detail in cluster_fields {
+        changes.push(TopologyChange {
+            component: TopologyComponent::Cluster,
+            change: TopologyChangeKind::Changed,
+            node: "cluster".to_string(),
+            detail: Some(detail),
+        });
+    }
+
+    diff_keyed(
+        &mut changes,
+        TopologyComponent::Frontend,
+        &old.frontends,
+        &new.frontends,
+        |fe| format!("{}:{}", fe.host, fe.edit_log_port),
+        |a, b| {
+            let mut fields = Vec::new();
+            field(&mut fields, "role", &a.role, &b.role);
+            field(&mut fields, "alive", a.alive, b.alive);
+            fields
+        },
+    );
+    for (component, old_nodes, new_nodes) in [
+        (TopologyComponent::Backend, &old.backends, &new.backends),
+        (TopologyComponent::ComputeNode, &old.compute_nodes, &new.compute_nodes),
+    ] {
+        diff_keyed(
+            &mut changes,
+            component,
+            old_nodes,
+            new_nodes,
+            |node| format!("{}:{}", node.host, node.heartbeat_port),
+            |a, b| {
+                let mut fields = Vec::new();
+                field(&mut fields, "alive", a.alive, b.alive);
+                field(&mut fields, "decommissioned", a.decommissioned, b.decommissioned);
+                field(
+                    &mut fields,
+                    "warehouse",
+                    a.warehouse.as_deref().unwrap_or("-"),
+                    b.warehouse.as_deref().unwrap_or("-"),
+                );
+                fields
+            },
+        );
+    }
+    diff_keyed(
+        &mut changes,
+        TopologyComponent::Warehouse,
+        &old.warehouses,
+        &new.warehouses,
+        |warehouse| warehouse.name.clone(),
+        |a, b| {
+            let mut fields = Vec::new();
+            field(&mut fields, "state", &a.state, &b.state);
+            field(
+                &mut fields,
+                "node_count",
+                a.node_count.map_or("-".to_string(), |n| n.to_string()),
+                b.node_count.map_or("-".to_string(), |n| n.to_string()),
+            );
+            fields
+        },
+    );
+
+    changes
+}
+
+// Added, removed and changed items of two lists matched by key
+fn diff_keyed<T>(
+    changes: &mut Vec<TopologyChange>,
+    component: TopologyComponent,
+    old: &[T],
+    new: &[T],
+    key: impl Fn(&T) -> String,
+    fields: impl Fn(&T, &T) -> Vec<String>,
+) {
+    let old: BTreeMap<String, &T> = old.iter().map(|item| (key(item), item)).collect();
+    let new: BTreeMap<String, &T> = new.iter().map(|item| (key(item), item)).collect();
+
+    for (node, item) in &new {
+        let (change, detail) = match old.get(node) {
+            None => (TopologyChangeKind::Added, None),
+            Some(previous) => {
+                let changed = fields(previous, item);
+                if changed.is_empty() {
+                    continue;
+                }
+                (TopologyChangeKind::Changed, Some(changed.join(", ")))
+            },
+        };
+        changes.push(TopologyChange { component, change, node: node.clone(), detail });
+    }
+    for node in old.keys().filter(|node| !new.contains_key(*node)) {
+        changes.push(TopologyChange {
+            component,
+            change: TopologyChangeKind::Removed,
+            node: node.clone(),
+            detail: None,
+        });
+    }
+}
+
+fn field<V: Display + PartialEq>(fields: &mut Vec<String>, name: &str, old: V, new: V) {
+    if old != new {
+        fields.push(format!("{}: {} -> {}", name, old, new));
+    }
+}
+
+fn check(name: &str, outcome: ApiResult<String>) -> HealthCheck {
+    match outcome {
+        Ok(message) => HealthCheck::new(name, Severity::Ok, message),
+        Err(e) => HealthCheck::new(name, Severity::Critical, e.to_string()),
+    }
+}
+
+fn is_leader(fe: &Frontend) -> bool {
+    fe.role.eq_ignore_ascii_case("LEADER") || fe.is_master.as_deref() == Some("true")
+}
+
+fn topology_frontend(fe: &Frontend) -> TopologyFrontend {
+    TopologyFrontend {
+        name: fe.name.clone(),
+        host: fe.host.clone(),
+        http_port: port(Some(&fe.http_port)),
+        query_port: port(Some(&fe.query_port)),
+        edit_log_port: port(Some(&fe.edit_log_port)),
+        role: if is_leader(fe) { "LEADER".to_string() } else { fe.role.to_uppercase() },
+        alive: flag(Some(&fe.alive)),
+    }
+}
+
+fn topology_backend(be: &Backend) -> TopologyNode {
+    TopologyNode {
+        id: be.backend_id.clone(),
+        host: be.host.clone(),
+        heartbeat_port: port(Some(&be.heartbeat_port)),
+        alive: flag(Some(&be.alive)),
+        decommissioned: flag(Some(&be.system_decommissioned)),
+        warehouse: be.warehouse_name.clone().filter(|w| !w.is_empty()),
+    }
+}
+
+fn cell<'a>(columns: &[String], row: &'a [String], name: &str) -> Option<&'a str> {
+    columns
+        .iter()
+        .position(|c| c.eq_ignore_ascii_case(name))
+        .and_then(|i| row.get(i))
+        .map(String::as_str)
+}
+
+fn port(value: Option<&str>) -> i32 {
+    value
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or_default()
+}
+
+fn flag(value: Option<&str>) -> bool {
+    value.is_some_and(|v| v.trim().eq_ignore_ascii_case("true"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn topology() -> ClusterTopology {
+        let fe = |host: &str, role: &str| TopologyFrontend {
+            name: host.to_string(),
+            host: host.to_string(),
+            http_port: 8030,
+            query_port: 9030,
+            edit_log_port: 9010,
+            role: role.to_string(),
+            alive: true,
+        };
+        let be = |host: &str| TopologyNode {
+            id: host.to_string(),
+            host: host.to_string(),
+            heartbeat_port: 9050,
+            alive: true,
+            decommissioned: false,
+            warehouse: None,
+        };
+        ClusterTopology {
+            run_mode: RunMode::SharedNothing,
+            version: Some("3.3.0".to_string()),
+            frontends: vec![fe("fe1", "LEADER"), fe("fe2", "FOLLOWER")],
+            backends: vec![be("be1"), be("be2")],
+            compute_nodes: Vec::new(),
+            warehouses: Vec::new(),
+            discovered_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_diff_of_identical_topologies_is_empty() {
+        assert!(diff_topology(&topology(), &topology()).is_empty());
+    }
+
+    #[test]
+    fn test_diff_reports_added_removed_and_changed_nodes() {
+        let old = topology();
+        let mut new = topology();
+        new.frontends[0].role = "FOLLOWER".to_string();
+        new.frontends[1].role = "LEADER".to_string();
+        new.backends.remove(0);
+        new.backends[0].alive = false;
+        new.backends
+            .push(TopologyNode { host: "be3".to_string(), ..old.backends[0].clone() });
+        new.version = Some("3.3.1".to_string());
+
+        let changes = diff_topology(&old, &new);
+        let summary: Vec<(TopologyComponent, TopologyChangeKind, &str)> = changes
+            .iter()
+            .map(|c| (c.component, c.change, c.node.as_str()))
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                (TopologyComponent::Cluster, TopologyChangeKind::Changed, "cluster"),
+                (TopologyComponent::Frontend, TopologyChangeKind::Changed, "fe1:9010"),
+                (TopologyComponent::Frontend, TopologyChangeKind::Changed, "fe2:9010"),
+                (TopologyComponent::Backend, TopologyChangeKind::Changed, "be2:9050"),
+                (TopologyComponent::Backend, TopologyChangeKind::Added, "be3:9050"),
+                (TopologyComponent::Backend, TopologyChangeKind::Removed, "be1:9050"),
+            ]
+        );
+        assert_eq!(changes[0].detail.as_deref(), Some("version: 3.3.0 -> 3.3.1"));
+        assert_eq!(changes[1].detail.as_deref(), Some("role: LEADER -> FOLLOWER"));
+        assert_eq!(changes[3].detail.as_deref(), Some("alive: true -> false"));
+    }
+}
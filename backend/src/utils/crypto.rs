@@ -0,0 +1,209 @@
+use aes_gcm::aead::{Aead, KeyInit, OsRng};
+use aes_gcm::{AeadCore, Aes256Gcm, Key, Nonce};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use once_cell::sync::OnceCell;
+use sha2::{Digest, Sha256};
+
+use crate::utils::{ApiError, ApiResult};
+
+/// Prefix of values produced by [`SecretCipher::encrypt`]
+const ENVELOPE_PREFIX: &str = "enc:v1:";
+const NONCE_LEN: usize = 12;
+
+static MASTER_CIPHER: OnceCell<SecretCipher> = OnceCell::new();
+
+/// Envelope encryption for secrets stored in SQLite (cluster passwords)
+///
+/// Every secret is encrypted with its own random data key (AES-256-GCM), and the
+/// data key is wrapped with the master key. Stored format:
+///
+/// `enc:v1:<key_id>:<base64(nonce | wrapped data key)>:<base64(nonce | ciphertext)>`
+///
+/// `key_id` identifies the master key so a wrong key is reported clearly instead
+/// of as a generic decryption failure.
+pub struct SecretCipher {
+    key_id: String,
+    master: Aes256Gcm,
+}
+
+impl SecretCipher {
+    /// Derive the master key from the configured value (use a long random string,
+    /// e.g. `openssl rand -base64 32`)
+    pub fn new(master_key: &str) -> ApiResult<Self> {
+        if master_key.is_empty() {
+            return Err(ApiError::internal_error("Master key cannot be empty"));
+        }
+
+        let key_bytes = Sha256::digest(master_key.as_bytes());
+        let key_id = hex::encode(&Sha256::digest(key_bytes)[..4]);
+        let master = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key_bytes));
+
+        Ok(Self { key_id, master })
+    }
+
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> ApiResult<String> {
+        let data_key = Aes256Gcm::generate_key(OsRng);
+        let data_cipher = Aes256Gcm::new(&data_key);
+
+        let data_nonce = Aes256Gcm::generate_nonce(OsRng);
+        let ciphertext = data_cipher
+            .encrypt(&data_nonce, plaintext.as_bytes())
+            .map_err(|_| ApiError::internal_error("Failed to encrypt secret"))?;
+
+        let key_nonce = Aes256Gcm::generate_nonce(OsRng);
+        let wrapped_key = self
+            .master
+            .encrypt(&key_nonce, data_key.as_slice())
+            .map_err(|_| ApiError::internal_error("Failed to wrap data key"))?;
+
+        Ok(format!(
+            "{}{}:{}:{}",
+            ENVELOPE_PREFIX,
+            self.key_id,
+            BASE64.encode([key_nonce.as_slice(), &wrapped_key].concat()),
+            BASE64.encode([data_nonce.as_slice(), &ciphertext].concat()),
+        ))
+    }
+
+    /// Decrypt a stored value; values without the envelope prefix are legacy
+    /// plaintext and returned unchanged
+    pub fn decrypt(&self, stored: &str) -> ApiResult<String> {
+        let Some(envelope) = stored.strip_prefix(ENVELOPE_PREFIX) else {
+            return Ok(stored.to_string());
+        };
+
+        let mut parts = envelope.splitn(3, ':');
+        let (Some(key_id), Some(wrapped_key), Some(ciphertext)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(ApiError::internal_error("Malformed encrypted secret"));
+        };
+
+        if key_id != self.key_id {
+            return Err(ApiError::internal_error(format!(
+                "Secret was encrypted with master key '{}' but the configured key is '{}'",
+                key_id, self.key_id
+            )));
+        }
+
+        let data_key = open(&self.master, wrapped_key)?;
+        let data_cipher = Aes256Gcm::new_from_slice(&data_key)
+            .map_err(|_| ApiError::internal_error("Invalid data key length"))?;
+        let plaintext = open(&data_cipher, ciphertext)?;
+
+        String::from_utf8(plaintext)
+            .map_err(|_| ApiError::internal_error("Decrypted secret is not valid UTF-8"))
+    }
+}
+
+/// Whether a stored value is already in envelope format
+pub fn is_encrypted(stored: &str) -> bool {
+    stored.starts_with(ENVELOPE_PREFIX)
+}
+
+fn open(cipher: &Aes256Gcm, encoded: &str) -> ApiResult<Vec<u8>> {
+    let bytes = BASE64
+        .decode(encoded)
+        .map_err(|_| ApiError::internal_error("Malformed encrypted secret"))?;
+    if bytes.len() < NONCE_LEN {
+        return Err(ApiError::internal_error("Malformed encrypted secret"));
+    }
+
+    let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| ApiError::internal_error("Failed to decrypt secret"))
+}
+
+/// Random master key for installations that do not configure one
+pub fn generate_master_key() -> String {
+    BASE64.encode(Aes256Gcm::generate_key(OsRng))
+}
+
+/// Install the process-wide master key; must be called once at startup
+pub fn init_master_key(master_key: &str) -> ApiResult<()> {
+    let cipher = SecretCipher::new(master_key)?;
+    tracing::info!("Secret encryption enabled (master key id: {})", cipher.key_id());
+
+    MASTER_CIPHER
+        .set(cipher)
+        .map_err(|_| ApiError::internal_error("Master key is already initialized"))
+}
+
+fn master_cipher() -> ApiResult<&'static SecretCipher> {
+    MASTER_CIPHER
+        .get()
+        .ok_or_else(|| ApiError::internal_error("Master key is not initialized"))
+}
+
+/// Encrypt a secret with the process-wide master key
+pub fn encrypt_secret(plaintext: &str) -> ApiResult<String> {
+    master_cipher()?.encrypt(plaintext)
+}
+
+/// Decrypt a stored secret with the process-wide master key
+pub fn decrypt_secret(stored: &str) -> ApiResult<String> {
+    if !is_encrypted(stored) {
+        return Ok(stored.to_string());
+    }
+    master_cipher()?.decrypt(stored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_decrypt_roundtrip() {
+        let cipher = SecretCipher::new("test-master-key").unwrap();
+        let stored = cipher.encrypt("s3cr3t:pa$$").unwrap();
+
+        assert!(is_encrypted(&stored));
+        assert!(!stored.contains("s3cr3t"));
+        assert_eq!(cipher.decrypt(&stored).unwrap(), "s3cr3t:pa$$");
+    }
+
+    #[test]
+    fn test_each_encryption_uses_fresh_data_key() {
+        let cipher = SecretCipher::new("test-master-key").unwrap();
+        assert_ne!(cipher.encrypt("same").unwrap(), cipher.encrypt("same").unwrap());
+    }
+
+    #[test]
+    fn test_legacy_plaintext_passthrough() {
+        let cipher = SecretCipher::new("test-master-key").unwrap();
+        assert_eq!(cipher.decrypt("plain-password").unwrap(), "plain-password");
+        assert_eq!(cipher.decrypt("").unwrap(), "");
+    }
+
+    #[test]
+    fn test_wrong_master_key_is_rejected() {
+        let stored = SecretCipher::new("old-key")
+            .unwrap()
+            .encrypt("secret")
+            .unwrap();
+        let err = SecretCipher::new("new-key")
+            .unwrap()
+            .decrypt(&stored)
+            .unwrap_err();
+
+        assert!(err.to_string().contains("master key"));
+    }
+
+    #[test]
+    fn test_tampered_ciphertext_is_rejected() {
+        let cipher = SecretCipher::new("test-master-key").unwrap();
+        let stored = cipher.encrypt("secret").unwrap();
+        let (prefix, data) = stored.rsplit_once(':').unwrap();
+        let mut bytes = BASE64.decode(data).unwrap();
+        *bytes.last_mut().unwrap() ^= 0x01;
+        let tampered = format!("{}:{}", prefix, BASE64.encode(bytes));
+
+        assert!(cipher.decrypt(&tampered).is_err());
+    }
+}
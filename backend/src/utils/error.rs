@@ -6,6 +6,8 @@ use axum::{
 use serde::Serialize;
 use thiserror::Error;
 
+use crate::models::{SqlViolation, SqlViolationReason};
+
 /// API Error with rich context and automatic error trait implementations
 ///
 /// Design: Uses thiserror for ergonomic error handling with context.
@@ -22,6 +24,15 @@ pub enum ApiError {
     #[error("Invalid credentials")]
     InvalidCredentials,
 
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Too many failed login attempts, try again in {retry_after_secs} seconds")]
+    AccountLocked { retry_after_secs: i64 },
+
+    #[error("Password change required")]
+    PasswordChangeRequired,
+
     // Cluster errors 2xxx
     #[error("Cluster {cluster_id} not found")]
     ClusterNotFound { cluster_id: i64 },
@@ -66,8 +77,9 @@ pub enum ApiError {
     #[error("Invalid SQL: {0}")]
     InvalidSQL(String),
 
-    #[error("SQL safety violation: {0}")]
-    SQLSafetyViolation(String),
+    /// Statement rejected by the SQL guard, with the reason in the response details
+    #[error("SQL safety violation: {}", .0.message)]
+    SQLSafetyViolation(Box<SqlViolation>),
 
     #[error("Category cannot be deleted")]
     CategoryCannotDelete,
@@ -87,6 +99,11 @@ impl ApiError {
         Self::Unauthorized(message.into())
     }
 
+    /// Helper to create forbidden error
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self::Forbidden(message.into())
+    }
+
     /// Helper to create cluster not found error
     pub fn cluster_not_found(cluster_id: i64) -> Self {
         Self::ClusterNotFound { cluster_id }
@@ -138,8 +155,8 @@ impl ApiError {
     }
 
     /// Helper to create SQL safety violation error
-    pub fn sql_safety_violation(message: impl Into<String>) -> Self {
-        Self::SQLSafetyViolation(message.into())
+    pub fn sql_safety_violation(violation: SqlViolation) -> Self {
+        Self::SQLSafetyViolation(Box::new(violation))
     }
 
     /// Get legacy error code for backward compatibility
@@ -149,6 +166,9 @@ impl ApiError {
             Self::Unauthorized(_) => 1001,
             Self::TokenExpired => 1002,
             Self::InvalidCredentials => 1003,
+            Self::Forbidden(_) => 1004,
+            Self::AccountLocked { .. } => 1005,
+            Self::PasswordChangeRequired => 1006,
 
             // Cluster errors 2xxx
             Self::ClusterNotFound { .. } => 2001,
@@ -193,8 +213,21 @@ impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
         let code = self.error_code();
         let message = self.to_string();
+        let retry_after = match &self {
+            Self::AccountLocked { retry_after_secs } => Some(retry_after_secs.to_string()),
+            _ => None,
+        };
 
         let status = match code {
+            // Repeating the request with the confirmation token lets it through
+            _ if matches!(&self, Self::SQLSafetyViolation(v)
+                if v.reason == SqlViolationReason::ConfirmationRequired) =>
+            {
+                StatusCode::PRECONDITION_REQUIRED
+            },
+            _ if matches!(self, Self::SQLSafetyViolation(_)) => StatusCode::FORBIDDEN,
+            1004 | 1006 => StatusCode::FORBIDDEN,
+            1005 => StatusCode::TOO_MANY_REQUESTS,
             1001..=1999 => StatusCode::UNAUTHORIZED,
             2001..=2999 => StatusCode::BAD_REQUEST,
             3001..=3999 => StatusCode::NOT_FOUND,
@@ -202,9 +235,22 @@ impl IntoResponse for ApiError {
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         };
 
-        let response = ApiErrorResponse { code, message, details: None };
+        let details = match &self {
+            Self::SQLSafetyViolation(violation) => serde_json::to_value(violation).ok(),
+            _ => None,
+        };
 
-        (status, Json(response)).into_response()
+        let response = ApiErrorResponse { code, message, details };
+
+        let mut response = (status, Json(response)).into_response();
+        if let Some(retry_after) = retry_after
+            && let Ok(value) = retry_after.parse()
+        {
+            response
+                .headers_mut()
+                .insert(axum::http::header::RETRY_AFTER, value);
+        }
+        response
     }
 }
 
@@ -226,6 +272,9 @@ pub enum ErrorCode {
     Unauthorized = 1001,
     TokenExpired = 1002,
     InvalidCredentials = 1003,
+    Forbidden = 1004,
+    AccountLocked = 1005,
+    PasswordChangeRequired = 1006,
 
     // Cluster errors 2xxx
     ClusterNotFound = 2001,
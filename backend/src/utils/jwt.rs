@@ -2,12 +2,20 @@ use chrono::{Duration, Utc};
 use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
 use serde::{Deserialize, Serialize};
 
+use crate::models::UserRole;
 use crate::utils::error::ApiError;
+use crate::utils::token;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String,      // User ID
     pub username: String, // Username
+    #[serde(default)]
+    pub role: UserRole, // Global role
+    #[serde(default)]
+    pub jti: String, // Token ID, used for revocation
+    #[serde(default)]
+    pub sid: String, // Login session ID, shared with the refresh tokens
     pub exp: i64,         // Expiration time
     pub iat: i64,         // Issued at
 }
@@ -16,38 +24,55 @@ pub struct Claims {
 pub struct JwtUtil {
     encoding_key: EncodingKey,
     decoding_key: DecodingKey,
-    expires_in_hours: i64,
+    expires_in: Duration,
 }
 
 impl JwtUtil {
     pub fn new(secret: &str, expires_in: &str) -> Self {
-        let hours = Self::parse_expiration(expires_in);
-
         Self {
             encoding_key: EncodingKey::from_secret(secret.as_bytes()),
             decoding_key: DecodingKey::from_secret(secret.as_bytes()),
-            expires_in_hours: hours,
+            expires_in: Self::parse_expiration(expires_in),
         }
     }
 
-    fn parse_expiration(expires_in: &str) -> i64 {
-        // Parse "24h", "7d", etc.
-        if expires_in.ends_with('h') {
-            expires_in.trim_end_matches('h').parse().unwrap_or(24)
-        } else if expires_in.ends_with('d') {
-            expires_in.trim_end_matches('d').parse::<i64>().unwrap_or(1) * 24
-        } else {
-            24 // Default 24 hours
+    /// Parse "30m", "24h", "7d", etc.; falls back to 24 hours
+    pub fn parse_expiration(expires_in: &str) -> Duration {
+        let Some(unit) = expires_in.chars().last() else {
+            return Duration::hours(24);
+        };
+        let value = expires_in[..expires_in.len() - unit.len_utf8()].parse::<i64>();
+
+        match (value, unit) {
+            (Ok(v), 's') if v > 0 => Duration::seconds(v),
+            (Ok(v), 'm') if v > 0 => Duration::minutes(v),
+            (Ok(v), 'h') if v > 0 => Duration::hours(v),
+            (Ok(v), 'd') if v > 0 => Duration::days(v),
+            _ => Duration::hours(24), // Default 24 hours
         }
     }
 
-    pub fn generate_token(&self, user_id: i64, username: &str) -> Result<String, ApiError> {
+    /// Lifetime of issued access tokens
+    pub fn expires_in(&self) -> Duration {
+        self.expires_in
+    }
+
+    pub fn generate_token(
+        &self,
+        user_id: i64,
+        username: &str,
+        role: UserRole,
+        session_id: &str,
+    ) -> Result<String, ApiError> {
         let now = Utc::now();
-        let exp = now + Duration::hours(self.expires_in_hours);
+        let exp = now + self.expires_in;
 
         let claims = Claims {
             sub: user_id.to_string(),
             username: username.to_string(),
+            role,
+            jti: token::generate_secret("", 16),
+            sid: session_id.to_string(),
             exp: exp.timestamp(),
             iat: now.timestamp(),
         };
@@ -73,17 +98,24 @@ mod tests {
     #[test]
     fn test_jwt_generation_and_verification() {
         let jwt_util = JwtUtil::new("test-secret", "24h");
-        let token = jwt_util.generate_token(1, "testuser").unwrap();
+        let token = jwt_util
+            .generate_token(1, "testuser", UserRole::Operator, "session-1")
+            .unwrap();
         let claims = jwt_util.verify_token(&token).unwrap();
 
         assert_eq!(claims.sub, "1");
         assert_eq!(claims.username, "testuser");
+        assert_eq!(claims.role, UserRole::Operator);
+        assert_eq!(claims.sid, "session-1");
+        assert_eq!(claims.jti.len(), 32);
     }
 
     #[test]
     fn test_parse_expiration() {
-        assert_eq!(JwtUtil::parse_expiration("24h"), 24);
-        assert_eq!(JwtUtil::parse_expiration("7d"), 168);
-        assert_eq!(JwtUtil::parse_expiration("invalid"), 24);
+        assert_eq!(JwtUtil::parse_expiration("24h"), Duration::hours(24));
+        assert_eq!(JwtUtil::parse_expiration("7d"), Duration::hours(168));
+        assert_eq!(JwtUtil::parse_expiration("15m"), Duration::minutes(15));
+        assert_eq!(JwtUtil::parse_expiration("invalid"), Duration::hours(24));
+        assert_eq!(JwtUtil::parse_expiration(""), Duration::hours(24));
     }
 }
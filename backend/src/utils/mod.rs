@@ -1,7 +1,10 @@
+pub mod crypto;
 pub mod error;
 pub mod jwt;
 pub mod macros;
 pub mod scheduled_executor;
+pub mod sql;
+pub mod token;
 
 pub use error::{ApiError, ApiResult};
 pub use jwt::JwtUtil;
@@ -0,0 +1,880 @@
+// SQL text helpers: a tokenizer that knows MySQL/StarRocks quoting and comments,
+// statement splitting and row limits built on top of it
+
+use crate::models::StatementCategory;
+use crate::utils::{ApiError, ApiResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// Keyword or unquoted identifier
+    Word,
+    /// Identifier in backticks
+    QuotedIdentifier,
+    /// Literal in single or double quotes
+    String,
+    Number,
+    /// `@var` or `@@var`
+    Variable,
+    /// `-- `, `#` or `/* */` comment, including optimizer hints
+    Comment,
+    Whitespace,
+    /// Any other character: operators, `(`, `)`, `,`, `;` ...
+    Symbol,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    /// Byte offset in the tokenized text
+    pub start: usize,
+}
+
+impl Token<'_> {
+    pub fn end(&self) -> usize {
+        self.start + self.text.len()
+    }
+
+    /// Not whitespace or a comment
+    pub fn is_significant(&self) -> bool {
+        !matches!(self.kind, TokenKind::Whitespace | TokenKind::Comment)
+    }
+
+    /// Whether this is the word `keyword`, case-insensitively
+    pub fn is_keyword(&self, keyword: &str) -> bool {
+        self.kind == TokenKind::Word && self.text.eq_ignore_ascii_case(keyword)
+    }
+
+    pub fn is_symbol(&self, symbol: char) -> bool {
+        self.kind == TokenKind::Symbol && self.text.starts_with(symbol)
+    }
+}
+
+/// Split `sql` into tokens; fails on unterminated quotes and comments
+pub fn tokenize(sql: &str) -> ApiResult<Vec<Token<'_>>> {
+    let bytes = sql.as_bytes();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        let start = pos;
+        let c = bytes[pos];
+        let next = bytes.get(pos + 1).copied();
+        let kind = match c {
+            b' ' | b'\t' | b'\r' | b'\n' | 0x0b | 0x0c => {
+                while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+                    pos += 1;
+                }
+                TokenKind::Whitespace
+            },
+            // `--` starts a comment only when followed by whitespace, as in MySQL
+            b'-' if next == Some(b'-')
+                && bytes.get(pos + 2).is_none_or(|b| b.is_ascii_whitespace()) =>
+            {
+                pos = line_end(bytes, pos);
+                TokenKind::Comment
+            },
+            b'#' => {
+                pos = line_end(bytes, pos);
+                TokenKind::Comment
+            },
+            b'/' if next == Some(b'*') => {
+                let close = sql[pos + 2..]
+                    .find("*/")
+                    .ok_or_else(|| ApiError::invalid_sql("Unterminated /* comment"))?;
+                pos += 2 + close + 2;
+                TokenKind::Comment
+            },
+            b'\'' | b'"' => {
+                pos = quoted_end(bytes, pos, true)
+                    .ok_or_else(|| ApiError::invalid_sql("Unterminated string literal"))?;
+                TokenKind::String
+            },
+            b'`' => {
+                pos = quoted_end(bytes, pos, false)
+                    .ok_or_else(|| ApiError::invalid_sql("Unterminated backtick identifier"))?;
+                TokenKind::QuotedIdentifier
+            },
+            b'@' => {
+                pos += 1;
+                if bytes.get(pos) == Some(&b'@') {
+                    pos += 1;
+                }
+                pos = match bytes.get(pos) {
+                    // @`name`, @'name'
+                    Some(b'`' | b'\'' | b'"') => quoted_end(bytes, pos, true)
+                        .ok_or_else(|| ApiError::invalid_sql("Unterminated variable name"))?,
+                    _ => word_end(bytes, pos),
+                };
+                TokenKind::Variable
+            },
+            b'0'..=b'9' => {
+                pos = word_end(bytes, pos);
+                // Decimal part and exponent, e.g. 1.5e-3
+                if bytes.get(pos) == Some(&b'.') {
+                    pos = word_end(bytes, pos + 1);
+                }
+                if matches!(bytes.get(pos - 1), Some(b'e' | b'E'))
+                    && matches!(bytes.get(pos), Some(b'+' | b'-'))
+                {
+                    pos = word_end(bytes, pos + 1);
+                }
+                TokenKind::Number
+            },
+            c if is_word_byte(c) => {
+                pos = word_end(bytes, pos);
+                TokenKind::Word
+            },
+            _ => {
+                // One character, which may span several bytes
+                pos += sql[pos..].chars().next().map_or(1, char::len_utf8);
+                TokenKind::Symbol
+            },
+        };
+        tokens.push(Token { kind, text: &sql[start..pos], start });
+    }
+
+    Ok(tokens)
+}
+
+fn is_word_byte(b: u8) -> bool {
+    // Bytes of non-ASCII characters are allowed in unquoted identifiers
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'$' || b >= 0x80
+}
+
+fn word_end(bytes: &[u8], mut pos: usize) -> usize {
+    while pos < bytes.len() && is_word_byte(bytes[pos]) {
+        pos += 1;
+    }
+    pos
+}
+
+fn line_end(bytes: &[u8], pos: usize) -> usize {
+    bytes[pos..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(bytes.len(), |n| pos + n)
+}
+
+/// End of the quoted text starting at `pos`; the quote is escaped by doubling
+/// it, and also by a backslash when `backslash` is set
+fn quoted_end(bytes: &[u8], pos: usize, backslash: bool) -> Option<usize> {
+    let quote = bytes[pos];
+    let mut i = pos + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if backslash => i += 2,
+            b if b == quote => {
+                if bytes.get(i + 1) == Some(&quote) {
+                    i += 2;
+                } else {
+                    return Some(i + 1);
+                }
+            },
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// Statement of a script
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Statement<'a> {
+    /// Text from the first to the last significant token, without the `;`
+    pub text: &'a str,
+    /// Byte offset of `text` in the script
+    pub start: usize,
+}
+
+/// Split a script at top-level `;`, skipping statements that are empty or only comments
+pub fn split_statements(sql: &str) -> ApiResult<Vec<Statement<'_>>> {
+    let tokens = tokenize(sql)?;
+    let mut statements = Vec::new();
+    let mut current: Option<(usize, usize)> = None;
+
+    for token in tokens.iter().filter(|t| t.is_significant()) {
+        if token.is_symbol(';') {
+            if let Some((start, end)) = current.take() {
+                statements.push(Statement { text: &sql[start..end], start });
+            }
+            continue;
+        }
+        let start = current.map_or(token.start, |(start, _)| start);
+        current = Some((start, token.end()));
+    }
+    if let Some((start, end)) = current {
+        statements.push(Statement { text: &sql[start..end], start });
+    }
+
+    Ok(statements)
+}
+
+/// First keyword of a statement, upper-cased, looking past leading parentheses
+pub fn leading_keyword(tokens: &[Token]) -> Option<String> {
+    tokens
+        .iter()
+        .filter(|t| t.is_significant())
+        .find(|t| !t.is_symbol('('))
+        .filter(|t| t.kind == TokenKind::Word)
+        .map(|t| t.text.to_ascii_uppercase())
+}
+
+/// Append `LIMIT limit` to a single SELECT that has no top-level LIMIT
+///
+/// Anything else, including scripts and text that does not tokenize, is
+/// returned unchanged.
+pub fn apply_query_limit(sql: &str, limit: i32) -> String {
+    let Ok(statements) = split_statements(sql) else {
+        return sql.to_string();
+    };
+    let [statement] = statements.as_slice() else {
+        return sql.to_string();
+    };
+    let Ok(tokens) = tokenize(statement.text) else {
+        return sql.to_string();
+    };
+    if !matches!(leading_keyword(&tokens).as_deref(), Some("SELECT" | "WITH")) {
+        return sql.to_string();
+    }
+
+    let mut depth = 0i32;
+    for token in tokens.iter().filter(|t| t.is_significant()) {
+        if token.is_symbol('(') {
+            depth += 1;
+        } else if token.is_symbol(')') {
+            depth -= 1;
+        } else if (depth == 0 && (token.is_keyword("LIMIT") || token.is_keyword("OUTFILE")))
+            // Profile functions return a single value
+            || token.is_keyword("GET_QUERY_PROFILE")
+            || token.is_keyword("SHOW_PROFILE")
+        {
+            // Already limited, or LIMIT would be a syntax error after INTO OUTFILE
+            return sql.to_string();
+        }
+    }
+
+    format!("{} LIMIT {}", statement.text, limit)
+}
+
+/// Category and type of one statement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatementClass {
+    pub category: StatementCategory,
+    /// Statement type, e.g. `SELECT`, `DROP TABLE` or `ALTER SYSTEM`
+    pub kind: String,
+    /// Removes data or cluster members that cannot be brought back by re-running it
+    pub destructive: bool,
+}
+
+impl StatementClass {
+    fn new(category: StatementCategory, kind: impl Into<String>) -> Self {
+        Self { category, kind: kind.into(), destructive: false }
+    }
+
+    fn destructive(mut self, destructive: bool) -> Self {
+        self.destructive = destructive;
+        self
+    }
+
+    /// A read that returns rows, as opposed to USE and SET
+    pub fn is_query(&self) -> bool {
+        self.category == StatementCategory::Read
+            && matches!(self.kind.as_str(), "SELECT" | "SHOW" | "DESCRIBE" | "EXPLAIN")
+    }
+}
+
+/// Words between CREATE/ALTER/DROP and the kind of object
+const OBJECT_MODIFIERS: &[&str] =
+    &["OR", "REPLACE", "TEMPORARY", "EXTERNAL", "GLOBAL", "AGGREGATE"];
+
+/// Objects named by two words, e.g. `MATERIALIZED VIEW`
+const TWO_WORD_OBJECTS: &[&str] = &["MATERIALIZED", "STORAGE", "RESOURCE", "ROUTINE", "SECURITY"];
+
+/// Objects that belong to cluster administration rather than to a schema
+const ADMIN_OBJECTS: &[&str] = &[
+    "USER",
+    "ROLE",
+    "RESOURCE",
+    "STORAGE",
+    "REPOSITORY",
+    "FILE",
+    "WAREHOUSE",
+    "CATALOG",
+    "SECURITY",
+    "SYSTEM",
+    "PLUGIN",
+    "COMPUTE",
+];
+
+/// Classify a single statement by its keywords
+///
+/// Statements that are not recognized are classified as admin, so that only
+/// the roles allowed to run anything can run them.
+pub fn classify(statement: &str) -> ApiResult<StatementClass> {
+    use StatementCategory::*;
+
+    let tokens = tokenize(statement)?;
+    let significant: Vec<&Token> = tokens
+        .iter()
+        .filter(|t| t.is_significant())
+        .skip_while(|t| t.is_symbol('('))
+        .collect();
+    let word = |i: usize| -> String {
+        significant
+            .get(i)
+            .filter(|t| t.kind == TokenKind::Word)
+            .map(|t| t.text.to_ascii_uppercase())
+            .unwrap_or_default()
+    };
+    // Whether `keyword` appears outside parentheses
+    let top_level = |keyword: &str| {
+        let mut depth = 0i32;
+        significant.iter().any(|t| {
+            if t.is_symbol('(') {
+                depth += 1;
+            } else if t.is_symbol(')') {
+                depth -= 1;
+            }
+            depth <= 0 && t.is_keyword(keyword)
+        })
+    };
+    let anywhere = |keyword: &str| significant.iter().any(|t| t.is_keyword(keyword));
+
+    let first = word(0);
+    let class = match first.as_str() {
+        "" => return Err(ApiError::invalid_sql("Empty statement")),
+        "SELECT" | "VALUES" if anywhere("OUTFILE") => {
+            StatementClass::new(Dml, "SELECT INTO OUTFILE")
+        },
+        "SELECT" | "VALUES" => StatementClass::new(Read, "SELECT"),
+        // The statement after the CTEs decides, e.g. WITH ... INSERT
+        "WITH" => {
+            let mut depth = 0i32;
+            let main = significant.iter().skip(1).find(|t| {
+                if t.is_symbol('(') {
+                    depth += 1;
+                } else if t.is_symbol(')') {
+                    depth -= 1;
+                }
+                depth == 0
+                    && ["SELECT", "INSERT", "UPDATE", "DELETE"]
+                        .iter()
+                        .any(|k| t.is_keyword(k))
+            });
+            match main {
+                Some(t) => return classify(&statement[t.start..]),
+                None => StatementClass::new(Read, "SELECT"),
+            }
+        },
+        "SHOW" => StatementClass::new(Read, "SHOW"),
+        "DESC" | "DESCRIBE" | "HELP" => StatementClass::new(Read, "DESCRIBE"),
+        // EXPLAIN ANALYZE runs the statement
+        "EXPLAIN" if word(1) == "ANALYZE" => {
+            let statement = &statement[significant[1].end()..];
+            return classify(statement);
+        },
+        "EXPLAIN" => StatementClass::new(Read, "EXPLAIN"),
+        "USE" => StatementClass::new(Read, "USE"),
+        "ADMIN" if word(1) == "SHOW" => StatementClass::new(Read, "ADMIN SHOW"),
+        "SET" => {
+            let global = anywhere("GLOBAL")
+                // @@global.name is read as the variable @@global and `.name`
+                || significant.iter().any(|t| {
+                    t.kind == TokenKind::Variable && t.text.eq_ignore_ascii_case("@@global")
+                });
+            match word(1).as_str() {
+                _ if global => StatementClass::new(Admin, "SET GLOBAL"),
+                "PASSWORD" | "PROPERTY" | "DEFAULT" => {
+                    StatementClass::new(Admin, format!("SET {}", word(1)))
+                },
+                // Session variables, SET ROLE, SET CATALOG
+                _ => StatementClass::new(Read, "SET"),
+            }
+        },
+        "INSERT" if word(1) == "OVERWRITE" => {
+            StatementClass::new(Dml, "INSERT OVERWRITE").destructive(true)
+        },
+        "INSERT" => StatementClass::new(Dml, "INSERT"),
+        "UPDATE" => StatementClass::new(Dml, "UPDATE").destructive(!top_level("WHERE")),
+        "DELETE" => StatementClass::new(Dml, "DELETE").destructive(true),
+        // The statement after AS runs in the background, e.g. SUBMIT TASK AS INSERT OVERWRITE
+        "SUBMIT" if word(1) == "TASK" => {
+            let mut depth = 0i32;
+            let body = significant
+                .iter()
+                .position(|t| {
+                    if t.is_symbol('(') {
+                        depth += 1;
+                    } else if t.is_symbol(')') {
+                        depth -= 1;
+                    }
+                    depth == 0 && t.is_keyword("AS")
+                })
+                .and_then(|i| significant.get(i + 1));
+            match body {
+                Some(t) => match classify(&statement[t.start..])? {
+                    // A task always writes, even if its body would only read
+                    class if class.category == Read => StatementClass::new(Dml, "SUBMIT TASK"),
+                    class => class,
+                },
+                None => StatementClass::new(Dml, "SUBMIT TASK"),
+            }
+        },
+        "LOAD" | "EXPORT" | "SUBMIT" | "BEGIN" | "START" | "COMMIT" | "ROLLBACK" | "PAUSE"
+        | "RESUME" | "STOP" | "CANCEL" => StatementClass::new(Dml, first.as_str()),
+        "TRUNCATE" => StatementClass::new(Ddl, "TRUNCATE TABLE").destructive(true),
+        "CREATE" | "ALTER" | "DROP" | "REFRESH" | "RECOVER" | "ANALYZE" => {
+            let mut i = 1;
+            while OBJECT_MODIFIERS.contains(&word(i).as_str()) {
+                i += 1;
+            }
+            let mut object = word(i);
+            if TWO_WORD_OBJECTS.contains(&object.as_str()) && !word(i + 1).is_empty() {
+                object = format!("{} {}", object, word(i + 1));
+            }
+            let kind = format!("{} {}", first, object).trim_end().to_string();
+            let admin = ADMIN_OBJECTS.contains(&word(i).as_str());
+            let destructive = match first.as_str() {
+                "DROP" => true,
+                "CREATE" => word(1) == "OR" && word(2) == "REPLACE",
+                "ALTER" => ["DROP", "DECOMMISSION", "REPLACE", "SWAP"]
+                    .iter()
+                    .any(|k| top_level(k)),
+                _ => false,
+            };
+            StatementClass::new(if admin { Admin } else { Ddl }, kind).destructive(destructive)
+        },
+        "RESTORE" => StatementClass::new(Admin, "RESTORE").destructive(true),
+        _ => StatementClass::new(Admin, first.as_str()),
+    };
+    Ok(class)
+}
+
+/// Whether `sql` is a single statement that only reads data
+pub fn is_read_only_query(sql: &str) -> bool {
+    match split_statements(sql).as_deref() {
+        Ok([statement]) => classify(statement.text).is_ok_and(|class| class.is_query()),
+        _ => false,
+    }
+}
+
+/// Text that identifies a query regardless of formatting: comments other than
+/// optimizer hints are dropped, whitespace is collapsed and trailing `;` removed.
+/// Case is kept since literals and some identifiers are case-sensitive.
+pub fn normalize(sql: &str) -> ApiResult<String> {
+    let mut normalized = String::with_capacity(sql.len());
+    let mut space = false;
+    for token in tokenize(sql)? {
+        match token.kind {
+            TokenKind::Whitespace => space = true,
+            TokenKind::Comment if !token.text.starts_with("/*+") => space = true,
+            _ => {
+                if space && !normalized.is_empty() {
+                    normalized.push(' ');
+                }
+                space = false;
+                normalized.push_str(token.text);
+            },
+        }
+    }
+    let len = normalized.trim_end_matches([';', ' ']).len();
+    normalized.truncate(len);
+    Ok(normalized)
+}
+
+/// Fail unless `sql` is exactly one statement, for SQL that is put together
+/// from caller-provided parts
+pub fn ensure_single_statement(sql: &str) -> ApiResult<()> {
+    match split_statements(sql)?.len() {
+        1 => Ok(()),
+        n => Err(ApiError::validation_error(format!("Expected one statement, found {}", n))),
+    }
+}
+
+/// Quote a database, catalog, table or view name as a backtick identifier
+pub fn quote_identifier(name: &str) -> ApiResult<String> {
+    if name.is_empty() || name.chars().any(char::is_control) {
+        return Err(ApiError::validation_error(format!("Invalid identifier: {:?}", name)));
+    }
+    Ok(format!("`{}`", name.replace('`', "``")))
+}
+
+/// Quote `value` as a string literal
+pub fn quote_literal(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('\'');
+    for c in value.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '\'' => quoted.push_str("\\'"),
+            '\0' => quoted.push_str("\\0"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('\'');
+    quoted
+}
+
+/// String literal for `LIKE` that matches values containing `text`
+pub fn like_contains(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    quote_literal(&format!("%{}%", escaped))
+}
+
+/// `USE CATALOG` statement
+pub fn use_catalog(catalog: &str) -> ApiResult<String> {
+    Ok(format!("USE CATALOG {}", quote_identifier(catalog)?))
+}
+
+/// `USE` statement for a database
+pub fn use_database(database: &str) -> ApiResult<String> {
+    Ok(format!("USE {}", quote_identifier(database)?))
+}
+
+// Query IDs are UUIDs; connection IDs are numbers
+fn validate_query_id(query_id: &str) -> ApiResult<&str> {
+    let valid = !query_id.is_empty()
+        && query_id.len() <= 64
+        && query_id.chars().all(|c| c.is_ascii_hexdigit() || c == '-');
+    if !valid {
+        return Err(ApiError::validation_error(format!("Invalid query ID: {:?}", query_id)));
+    }
+    Ok(query_id)
+}
+
+/// `KILL QUERY` statement for a query ID
+pub fn kill_query(query_id: &str) -> ApiResult<String> {
+    Ok(format!("KILL QUERY {}", quote_literal(validate_query_id(query_id)?)))
+}
+
+/// Statement reading the profile of a query
+pub fn query_profile(query_id: &str) -> ApiResult<String> {
+    Ok(format!("SELECT get_query_profile({})", quote_literal(validate_query_id(query_id)?)))
+}
+
+/// `SHOW VARIABLES` of `scope` (`GLOBAL` or `SESSION`), optionally only names containing `filter`
+pub fn show_variables(scope: &str, filter: Option<&str>) -> String {
+    match filter.filter(|f| !f.is_empty()) {
+        Some(filter) => format!("SHOW {} VARIABLES LIKE {}", scope, like_contains(filter)),
+        None => format!("SHOW {} VARIABLES", scope),
+    }
+}
+
+/// `SET` statement for a system variable
+///
+/// Numbers and the keywords `TRUE`, `FALSE`, `ON`, `OFF` and `DEFAULT` are
+/// set as they are; any other value is set as a string.
+pub fn set_variable(scope: &str, name: &str, value: &str) -> ApiResult<String> {
+    let valid_name = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+    if !valid_name {
+        return Err(ApiError::validation_error(format!("Invalid variable name: {:?}", name)));
+    }
+    let value = value.trim();
+    let number = value
+        .chars()
+        .all(|c| c.is_ascii_digit() || "+-.eE".contains(c))
+        && value.parse::<f64>().is_ok();
+    let keyword = ["TRUE", "FALSE", "ON", "OFF", "DEFAULT"]
+        .iter()
+        .any(|k| value.eq_ignore_ascii_case(k));
+    let value = if number || keyword { value.to_string() } else { quote_literal(value) };
+    Ok(format!("SET {} {} = {}", scope, name, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(sql: &str) -> Vec<&str> {
+        split_statements(sql)
+            .unwrap()
+            .into_iter()
+            .map(|s| s.text)
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize_quotes_and_comments() {
+        let sql = "SELECT 'it''s; \\' x', \"a\", `we``ird;` -- c;\n# d;\n/* e; */ @@v, 1.5e-3;";
+        let kinds: Vec<(TokenKind, &str)> = tokenize(sql)
+            .unwrap()
+            .into_iter()
+            .filter(|t| t.is_significant() || t.kind == TokenKind::Comment)
+            .map(|t| (t.kind, t.text))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (TokenKind::Word, "SELECT"),
+                (TokenKind::String, "'it''s; \\' x'"),
+                (TokenKind::Symbol, ","),
+                (TokenKind::String, "\"a\""),
+                (TokenKind::Symbol, ","),
+                (TokenKind::QuotedIdentifier, "`we``ird;`"),
+                (TokenKind::Comment, "-- c;"),
+                (TokenKind::Comment, "# d;"),
+                (TokenKind::Comment, "/* e; */"),
+                (TokenKind::Variable, "@@v"),
+                (TokenKind::Symbol, ","),
+                (TokenKind::Number, "1.5e-3"),
+                (TokenKind::Symbol, ";"),
+            ]
+        );
+        // `--` without a following space is two minus signs
+        assert_eq!(tokenize("1--2").unwrap().len(), 4);
+
+        assert!(tokenize("SELECT 'open").is_err());
+        assert!(tokenize("SELECT `open").is_err());
+        assert!(tokenize("SELECT /* open").is_err());
+    }
+
+    #[test]
+    fn test_split_statements() {
+        assert_eq!(
+            texts(
+                "USE db; SELECT ';' AS a -- trailing; comment\n;;\n/* only a comment */; SELECT 2"
+            ),
+            vec!["USE db", "SELECT ';' AS a", "SELECT 2"]
+        );
+        assert!(texts(" ; -- nothing\n").is_empty());
+        let statements = split_statements("SELECT 1;\n  SELECT 2;").unwrap();
+        assert_eq!(statements[1].start, 12);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let normalize = |sql| normalize(sql).unwrap();
+        assert_eq!(
+            normalize("  SELECT  a,\n\tb -- note\nFROM t /* x */ WHERE c = 'a  b' ;; "),
+            "SELECT a, b FROM t WHERE c = 'a  b'"
+        );
+        assert_eq!(normalize("select /*+ SET_VAR(a=1) */ 1"), "select /*+ SET_VAR(a=1) */ 1");
+        assert_ne!(normalize("SELECT 'A'"), normalize("SELECT 'a'"));
+        assert!(super::normalize("SELECT 'open").is_err());
+    }
+
+    #[test]
+    fn test_apply_query_limit() {
+        assert_eq!(apply_query_limit("select * from t;", 10), "select * from t LIMIT 10");
+        // A trailing comment must not swallow the LIMIT
+        assert_eq!(apply_query_limit("SELECT 1 -- note", 10), "SELECT 1 LIMIT 10");
+        assert_eq!(
+            apply_query_limit("WITH x AS (SELECT * FROM t LIMIT 5) SELECT * FROM x", 10),
+            "WITH x AS (SELECT * FROM t LIMIT 5) SELECT * FROM x LIMIT 10"
+        );
+        assert_eq!(
+            apply_query_limit("(SELECT a FROM t) UNION (SELECT a FROM u)", 3),
+            "(SELECT a FROM t) UNION (SELECT a FROM u) LIMIT 3"
+        );
+        // LIMIT inside a string or identifier does not count
+        assert_eq!(
+            apply_query_limit("SELECT 'limit' AS `limit` FROM t", 10),
+            "SELECT 'limit' AS `limit` FROM t LIMIT 10"
+        );
+
+        for unchanged in [
+            "SELECT * FROM t LIMIT 5",
+            "select * from t limit 5, 10",
+            "SHOW DATABASES",
+            "INSERT INTO t SELECT * FROM u",
+            "SELECT 1; SELECT 2",
+            "SELECT * FROM t INTO OUTFILE 's3://b/p' FORMAT AS CSV",
+            "SELECT get_query_profile('id')",
+            "SELECT 'unterminated",
+        ] {
+            assert_eq!(apply_query_limit(unchanged, 10), unchanged);
+        }
+    }
+
+    #[test]
+    fn test_classify() {
+        use StatementCategory::*;
+        let class = |sql: &str| {
+            let class = classify(sql).unwrap();
+            (class.category, class.kind, class.destructive)
+        };
+        let expect = |category, kind: &str, destructive| (category, kind.to_string(), destructive);
+
+        assert_eq!(class("select * from t"), expect(Read, "SELECT", false));
+        assert_eq!(class("/* hint */ (SELECT 1) UNION (SELECT 2)"), expect(Read, "SELECT", false));
+        assert_eq!(class("WITH a AS (SELECT 1) SELECT * FROM a"), expect(Read, "SELECT", false));
+        assert_eq!(
+            class("WITH a AS (SELECT 1) INSERT INTO t SELECT * FROM a"),
+            expect(Dml, "INSERT", false)
+        );
+        assert_eq!(
+            class("SELECT * FROM t INTO OUTFILE 's3://b/'"),
+            expect(Dml, "SELECT INTO OUTFILE", false)
+        );
+        assert_eq!(class("show frontends"), expect(Read, "SHOW", false));
+        assert_eq!(class("EXPLAIN DELETE FROM t"), expect(Read, "EXPLAIN", false));
+        assert_eq!(class("EXPLAIN ANALYZE DELETE FROM t"), expect(Dml, "DELETE", true));
+        assert_eq!(class("SET query_timeout = 10"), expect(Read, "SET", false));
+        assert_eq!(class("SET GLOBAL query_timeout = 10"), expect(Admin, "SET GLOBAL", false));
+        assert_eq!(class("set @@global.query_timeout = 10"), expect(Admin, "SET GLOBAL", false));
+        assert_eq!(
+            class("SET PASSWORD FOR u = PASSWORD('x')"),
+            expect(Admin, "SET PASSWORD", false)
+        );
+        // Keywords inside strings and identifiers do not count
+        assert_eq!(class("SET v = 'GLOBAL'"), expect(Read, "SET", false));
+        assert_eq!(class("SELECT 'DROP TABLE t' AS `delete`"), expect(Read, "SELECT", false));
+
+        assert_eq!(class("INSERT OVERWRITE t SELECT 1"), expect(Dml, "INSERT OVERWRITE", true));
+        assert_eq!(
+            class("SUBMIT TASK t1 PROPERTIES ('a' = 'b') AS INSERT OVERWRITE t SELECT 1"),
+            expect(Dml, "INSERT OVERWRITE", true)
+        );
+        assert_eq!(class("submit task AS INSERT INTO t SELECT 1"), expect(Dml, "INSERT", false));
+        assert_eq!(class("SUBMIT TASK AS SELECT 1"), expect(Dml, "SUBMIT TASK", false));
+        assert_eq!(class("UPDATE t SET a = 1 WHERE id = 2"), expect(Dml, "UPDATE", false));
+        assert_eq!(class("UPDATE t SET a = (SELECT 1 WHERE 1)"), expect(Dml, "UPDATE", true));
+        assert_eq!(class("DELETE FROM t WHERE id = 1"), expect(Dml, "DELETE", true));
+
+        assert_eq!(class("CREATE TABLE t (a INT)"), expect(Ddl, "CREATE TABLE", false));
+        assert_eq!(
+            class("CREATE TABLE t AS SELECT replace(a, 'x', 'y') FROM u"),
+            expect(Ddl, "CREATE TABLE", false)
+        );
+        assert_eq!(class("CREATE OR REPLACE VIEW v AS SELECT 1"), expect(Ddl, "CREATE VIEW", true));
+        assert_eq!(
+            class("CREATE MATERIALIZED VIEW mv AS SELECT 1"),
+            expect(Ddl, "CREATE MATERIALIZED VIEW", false)
+        );
+        assert_eq!(class("DROP TABLE IF EXISTS t"), expect(Ddl, "DROP TABLE", true));
+        assert_eq!(class("TRUNCATE TABLE t"), expect(Ddl, "TRUNCATE TABLE", true));
+        assert_eq!(class("ALTER TABLE t ADD COLUMN b INT"), expect(Ddl, "ALTER TABLE", false));
+        assert_eq!(class("ALTER TABLE t DROP PARTITION p1"), expect(Ddl, "ALTER TABLE", true));
+
+        assert_eq!(
+            class("ALTER SYSTEM DECOMMISSION BACKEND 'h:9050'"),
+            expect(Admin, "ALTER SYSTEM", true)
+        );
+        assert_eq!(class("KILL QUERY 12"), expect(Admin, "KILL", false));
+        assert_eq!(class("GRANT SELECT ON *.* TO u"), expect(Admin, "GRANT", false));
+        assert_eq!(class("CREATE USER u IDENTIFIED BY 'x'"), expect(Admin, "CREATE USER", false));
+        assert_eq!(class("DROP CATALOG hive"), expect(Admin, "DROP CATALOG", true));
+        assert_eq!(class("ADMIN SHOW REPLICA STATUS FROM t"), expect(Read, "ADMIN SHOW", false));
+        assert_eq!(class("ADMIN SET FRONTEND CONFIG ('a' = 'b')"), expect(Admin, "ADMIN", false));
+        assert_eq!(class("FROBNICATE everything"), expect(Admin, "FROBNICATE", false));
+
+        assert!(classify("-- only a comment").is_err());
+    }
+
+    #[test]
+    fn test_is_read_only_query() {
+        assert!(is_read_only_query("SELECT 1;"));
+        assert!(is_read_only_query("DESC db.t"));
+        assert!(!is_read_only_query("USE db"));
+        assert!(!is_read_only_query("SELECT 1; DROP TABLE t"));
+        assert!(!is_read_only_query("SELECT 'unterminated"));
+    }
+
+    // Values trying to end the quoting and append a statement of their own
+    const HOSTILE: &[&str] = &[
+        "x'; DROP TABLE t; -- ",
+        "x\\'; DROP TABLE t; -- ",
+        "x\\\\'; DROP TABLE t",
+        "x''; DROP TABLE t",
+        "x\"; DROP TABLE t; #",
+        "x`; DROP TABLE t; -- `",
+        "x``; DROP TABLE t",
+        "x */; DROP TABLE t; /* ",
+        "x\n; DROP TABLE t",
+        "x\0'; DROP TABLE t",
+        "%' OR 1=1; DROP TABLE t; -- ",
+        "\\",
+        "'",
+        "`",
+    ];
+
+    // The single statement `sql` consists of, with no trace of the smuggled DROP
+    fn significant_tokens(sql: &str) -> Vec<TokenKind> {
+        let statements = split_statements(sql).unwrap();
+        assert_eq!(statements.len(), 1, "{}", sql);
+        let tokens = tokenize(sql).unwrap();
+        assert!(!tokens.iter().any(|t| t.is_keyword("DROP")), "{}", sql);
+        tokens
+            .iter()
+            .filter(|t| t.is_significant())
+            .map(|t| t.kind)
+            .collect()
+    }
+
+    #[test]
+    fn test_hostile_input_stays_quoted() {
+        use TokenKind::*;
+        for value in HOSTILE {
+            assert_eq!(significant_tokens(&quote_literal(value)), [String], "{:?}", value);
+            assert_eq!(
+                significant_tokens(&show_variables("GLOBAL", Some(value))),
+                [Word, Word, Word, Word, String],
+                "{:?}",
+                value
+            );
+            assert_eq!(
+                significant_tokens(&set_variable("GLOBAL", "query_timeout", value).unwrap()),
+                [Word, Word, Word, Symbol, String],
+                "{:?}",
+                value
+            );
+            // Names with control characters are rejected, anything else is quoted
+            if let Ok(sql) = use_catalog(value) {
+                assert_eq!(significant_tokens(&sql), [Word, Word, QuotedIdentifier], "{:?}", value);
+            }
+            if let Ok(sql) = use_database(value) {
+                assert_eq!(significant_tokens(&sql), [Word, QuotedIdentifier], "{:?}", value);
+            }
+            if let Ok(name) = quote_identifier(value) {
+                let sql = format!("DROP MATERIALIZED VIEW {}.{}", name, name);
+                let tokens = tokenize(&sql).unwrap();
+                assert_eq!(tokens.iter().filter(|t| t.is_keyword("DROP")).count(), 1);
+                assert_eq!(split_statements(&sql).unwrap().len(), 1);
+            }
+            assert!(set_variable("GLOBAL", value, "1").is_err(), "{:?}", value);
+            assert!(kill_query(value).is_err(), "{:?}", value);
+            assert!(query_profile(value).is_err(), "{:?}", value);
+        }
+        assert!(quote_identifier("").is_err());
+        assert!(
+            ensure_single_statement("ALTER MATERIALIZED VIEW mv ACTIVE; DROP TABLE t").is_err()
+        );
+        assert!(ensure_single_statement("ALTER MATERIALIZED VIEW mv RENAME `a;b`").is_ok());
+    }
+
+    #[test]
+    fn test_quoting() {
+        assert_eq!(quote_identifier("sales db").unwrap(), "`sales db`");
+        assert_eq!(quote_identifier("a`b").unwrap(), "`a``b`");
+        assert_eq!(quote_literal("it's C:\\"), r"'it\'s C:\\'");
+        assert_eq!(like_contains("a_b%"), r"'%a\\_b\\%%'");
+        assert_eq!(show_variables("SESSION", Some("")), "SHOW SESSION VARIABLES");
+        assert_eq!(
+            kill_query("7d663e42-ae29-11f0-8a21-9eb34e998e27").unwrap(),
+            "KILL QUERY '7d663e42-ae29-11f0-8a21-9eb34e998e27'"
+        );
+        assert_eq!(query_profile("42").unwrap(), "SELECT get_query_profile('42')");
+
+        let set = |value: &str| set_variable("GLOBAL", "query_timeout", value).unwrap();
+        assert_eq!(set("300"), "SET GLOBAL query_timeout = 300");
+        assert_eq!(set(" -1.5e3 "), "SET GLOBAL query_timeout = -1.5e3");
+        assert_eq!(set("true"), "SET GLOBAL query_timeout = true");
+        assert_eq!(set("inf"), "SET GLOBAL query_timeout = 'inf'");
+        assert_eq!(set("STRICT_TRANS_TABLES"), "SET GLOBAL query_timeout = 'STRICT_TRANS_TABLES'");
+        assert_eq!(
+            set_variable("SESSION", "exec_mem_limit", "1").unwrap(),
+            "SET SESSION exec_mem_limit = 1"
+        );
+    }
+}
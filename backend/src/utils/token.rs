@@ -0,0 +1,39 @@
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+/// Generate a random URL-safe secret (hex encoded), optionally with a readable prefix
+///
+/// Used for invitation links and other bearer secrets that are shown to the user once
+/// and only stored as a hash.
+pub fn generate_secret(prefix: &str, bytes: usize) -> String {
+    let mut buf = vec![0u8; bytes];
+    rand::thread_rng().fill_bytes(&mut buf);
+    format!("{}{}", prefix, hex::encode(buf))
+}
+
+/// SHA-256 hex digest of a secret, suitable for storage and lookup
+pub fn hash_secret(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_secret_is_random_and_prefixed() {
+        let a = generate_secret("inv_", 32);
+        let b = generate_secret("inv_", 32);
+
+        assert!(a.starts_with("inv_"));
+        assert_eq!(a.len(), 4 + 64);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_hash_secret_is_stable() {
+        assert_eq!(hash_secret("abc"), hash_secret("abc"));
+        assert_ne!(hash_secret("abc"), hash_secret("abd"));
+        assert_eq!(hash_secret("abc").len(), 64);
+    }
+}
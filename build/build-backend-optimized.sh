@@ -79,7 +79,59 @@ url = "sqlite://data/starrocks-admin.db"
 
 [auth]
 jwt_secret = "dev-secret-key-change-in-production"
-jwt_expires_in = "24h"
+jwt_expires_in = "15m"
+refresh_token_expires_in = "7d"
+allow_registration = false              # true (or APP_ALLOW_REGISTRATION=true) lets anyone sign up
+
+# Optional: sign in with LDAP (users are created on first login)
+[auth.ldap]
+enabled = false
+url = "ldap://ldap.example.com:389"
+bind_dn = "cn=readonly,dc=example,dc=com"
+bind_password = ""                      # or APP_LDAP_BIND_PASSWORD
+user_base_dn = "ou=people,dc=example,dc=com"
+user_filter = "(uid={username})"
+id_attribute = "entryUUID"
+group_attribute = "memberOf"
+# default_role = "viewer"               # unset: users without a mapped group are rejected
+
+[auth.ldap.group_roles]
+"starrocks-admins" = "admin"
+"starrocks-operators" = "operator"
+
+# Optional: single sign-on with an OIDC provider (Keycloak, Okta, Azure AD, ...)
+[auth.oidc]
+enabled = false
+issuer_url = "https://sso.example.com/realms/main"
+client_id = "starrocks-admin"
+client_secret = ""                      # or APP_OIDC_CLIENT_SECRET
+redirect_url = "https://admin.example.com/api/auth/oidc/callback"
+groups_claim = "groups"
+
+[auth.oidc.group_roles]
+"starrocks-admins" = "admin"
+
+# Lock out usernames / client IPs after repeated failed logins
+[auth.lockout]
+enabled = true
+max_failures_per_user = 5
+max_failures_per_ip = 20
+window_secs = 900
+lockout_secs = 300                      # doubles for repeated lockouts
+max_lockout_secs = 3600
+trust_forwarded_for = false             # only behind a trusted reverse proxy
+
+[auth.password_policy]
+min_length = 8
+require_uppercase = false
+require_lowercase = false
+require_digit = true
+require_symbol = false
+reject_username = true
+
+[security]
+# master_key = ""                       # or APP_MASTER_KEY; unset: random key kept in master_key_file
+master_key_file = "data/master.key"
 
 [logging]
 level = "info,starrocks_admin_backend=debug"
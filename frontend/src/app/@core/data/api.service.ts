@@ -25,6 +25,11 @@ export class ApiService {
     return this.http.post<T>(`${this.baseUrl}${path}`, body);
   }
 
+  // POST returning a file download
+  postBlob(path: string, body: any = {}): Observable<Blob> {
+    return this.http.post(`${this.baseUrl}${path}`, body, { responseType: 'blob' });
+  }
+
   put<T>(path: string, body: any = {}): Observable<T> {
     return this.http.put<T>(`${this.baseUrl}${path}`, body);
   }
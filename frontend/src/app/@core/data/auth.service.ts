@@ -1,7 +1,8 @@
 import { Injectable } from '@angular/core';
 import { Router } from '@angular/router';
-import { BehaviorSubject, Observable } from 'rxjs';
-import { tap } from 'rxjs/operators';
+import { BehaviorSubject, Observable, throwError } from 'rxjs';
+import { finalize, shareReplay, tap } from 'rxjs/operators';
+import { environment } from '../../../environments/environment';
 import { ApiService } from './api.service';
 
 export interface User {
@@ -9,6 +10,8 @@ export interface User {
   username: string;
   email?: string;
   avatar?: string;
+  auth_source?: 'local' | 'ldap' | 'oidc';
+  must_change_password?: boolean;
   created_at: string;
   active_cluster_id?: never;  // Removed field - should never exist
 }
@@ -26,9 +29,24 @@ export interface RegisterRequest {
 
 export interface LoginResponse {
   token: string;
+  refresh_token: string;
+  expires_in: number;
   user: User;
 }
 
+export interface TokenResponse {
+  token: string;
+  refresh_token: string;
+  expires_in: number;
+}
+
+export interface AuthProviders {
+  local: boolean;
+  ldap: boolean;
+  oidc: boolean;
+  allow_registration: boolean;
+}
+
 @Injectable({
   providedIn: 'root',
 })
@@ -36,6 +54,8 @@ export class AuthService {
   private currentUserSubject: BehaviorSubject<User | null>;
   public currentUser: Observable<User | null>;
   private tokenKey = 'jwt_token';
+  private refreshTokenKey = 'refresh_token';
+  private refreshInFlight: Observable<TokenResponse> | null = null;
 
   constructor(
     private api: ApiService,
@@ -60,18 +80,62 @@ export class AuthService {
     return this.api.post<LoginResponse>('/auth/login', credentials).pipe(
       tap((response) => {
         localStorage.setItem(this.tokenKey, response.token);
+        localStorage.setItem(this.refreshTokenKey, response.refresh_token);
         localStorage.setItem('current_user', JSON.stringify(response.user));
         this.currentUserSubject.next(response.user);
       }),
     );
   }
 
+  getProviders(): Observable<AuthProviders> {
+    return this.api.get<AuthProviders>('/auth/providers');
+  }
+
+  // Full-page navigation to the backend, which redirects to the identity provider
+  get oidcLoginUrl(): string {
+    return `${environment.apiUrl}/auth/oidc/login`;
+  }
+
+  // Store the tokens handed over by the OIDC callback and load the signed-in user
+  completeSsoLogin(tokens: TokenResponse): Observable<User> {
+    localStorage.setItem(this.tokenKey, tokens.token);
+    localStorage.setItem(this.refreshTokenKey, tokens.refresh_token);
+    return this.getMe().pipe(tap((user) => this.updateCurrentUser(user)));
+  }
+
   register(data: RegisterRequest): Observable<User> {
     return this.api.post<User>('/auth/register', data);
   }
 
+  // Exchange the refresh token for a new token pair; concurrent callers share one request
+  refreshToken(): Observable<TokenResponse> {
+    const refreshToken = localStorage.getItem(this.refreshTokenKey);
+    if (!refreshToken) {
+      return throwError(new Error('No refresh token'));
+    }
+
+    if (!this.refreshInFlight) {
+      this.refreshInFlight = this.api
+        .post<TokenResponse>('/auth/refresh', { refresh_token: refreshToken })
+        .pipe(
+          tap((response) => {
+            localStorage.setItem(this.tokenKey, response.token);
+            localStorage.setItem(this.refreshTokenKey, response.refresh_token);
+          }),
+          finalize(() => (this.refreshInFlight = null)),
+          shareReplay(1),
+        );
+    }
+    return this.refreshInFlight;
+  }
+
   logout(): void {
+    // Revoke the session server-side; local state is cleared regardless of the outcome
+    if (this.token) {
+      this.api.post('/auth/logout').subscribe({ error: () => {} });
+    }
     localStorage.removeItem(this.tokenKey);
+    localStorage.removeItem(this.refreshTokenKey);
     localStorage.removeItem('current_user');
     this.currentUserSubject.next(null);
     this.router.navigate(['/auth/login']);
@@ -3,10 +3,14 @@ import { BehaviorSubject, Observable, of } from 'rxjs';
 import { catchError, tap } from 'rxjs/operators';
 import { Cluster, ClusterService } from './cluster.service';
 
+/** localStorage key of the cluster selected in this browser (sent as X-Cluster-Id) */
+export const SELECTED_CLUSTER_KEY = 'selected_cluster_id';
+
 /**
  * Global cluster context service
- * Manages the currently active cluster across the application
- * Gets active cluster from backend instead of localStorage
+ * Manages the cluster this browser works on across the application
+ * The selection is kept per browser, so other users keep their own cluster;
+ * without a selection the backend default (active) cluster is used
  */
 @Injectable({
   providedIn: 'root',
@@ -25,27 +29,36 @@ export class ClusterContextService {
   }
   
   /**
-   * Set the active cluster by calling backend API
+   * Select the cluster for this browser only (does not change the backend default)
    */
   setActiveCluster(cluster: Cluster): void {
-    // Call backend API to activate the cluster
-    this.clusterService.activateCluster(cluster.id).pipe(
-      tap((activatedCluster) => {
-        this.activeClusterSubject.next(activatedCluster);
-      }),
-      catchError((error) => {
-        // Still update local state for immediate feedback
-        this.activeClusterSubject.next(cluster);
-        return of(cluster);
-      })
-    ).subscribe();
+    localStorage.setItem(SELECTED_CLUSTER_KEY, String(cluster.id));
+    this.activeClusterSubject.next(cluster);
   }
   
   /**
-   * Refresh active cluster from backend
+   * Refresh the selected cluster, falling back to the backend default
    */
   refreshActiveCluster(): void {
-    
+    const selectedId = Number(localStorage.getItem(SELECTED_CLUSTER_KEY));
+    if (selectedId) {
+      this.clusterService.getCluster(selectedId).pipe(
+        tap((cluster) => {
+          this.activeClusterSubject.next(cluster);
+        }),
+        catchError(() => {
+          // Selected cluster was deleted: go back to the default
+          localStorage.removeItem(SELECTED_CLUSTER_KEY);
+          this.refreshDefaultCluster();
+          return of(null);
+        })
+      ).subscribe();
+      return;
+    }
+    this.refreshDefaultCluster();
+  }
+  
+  private refreshDefaultCluster(): void {
     this.clusterService.getActiveCluster().pipe(
       tap((cluster) => {
         this.activeClusterSubject.next(cluster);
@@ -76,6 +89,7 @@ export class ClusterContextService {
    * Clear active cluster
    */
   clearActiveCluster(): void {
+    localStorage.removeItem(SELECTED_CLUSTER_KEY);
     this.activeClusterSubject.next(null);
   }
   
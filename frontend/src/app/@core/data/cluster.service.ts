@@ -2,6 +2,26 @@ import { Injectable } from '@angular/core';
 import { Observable } from 'rxjs';
 import { ApiService } from './api.service';
 
+export interface FeEndpoint {
+  host: string;
+  http_port: number;
+  query_port: number;
+}
+
+// Overrides of the server's [mysql_pool] defaults; unset fields use the defaults
+export interface PoolSettings {
+  min_connections?: number;
+  max_connections?: number;
+  idle_timeout_secs?: number;
+  max_lifetime_secs?: number;
+}
+
+// SQL restrictions on top of what each role may run
+export interface SqlPolicy {
+  read_only?: boolean;
+  confirm_writes?: boolean;
+}
+
 export interface Cluster {
   id: number;
   name: string;
@@ -9,9 +29,15 @@ export interface Cluster {
   fe_host: string;
   fe_http_port: number;
   fe_query_port: number;
+  fe_endpoints: FeEndpoint[];
   username: string;
   enable_ssl: boolean;
+  tls_ca_cert?: string;
+  tls_client_identity_configured: boolean;
+  tls_verify_hostname: boolean;
   connection_timeout: number;
+  pool_settings: PoolSettings;
+  sql_policy: SqlPolicy;
   tags: string[];
   catalog: string;
   is_active: boolean;
@@ -25,10 +51,17 @@ export interface CreateClusterRequest {
   fe_host: string;
   fe_http_port?: number;
   fe_query_port?: number;
+  fe_endpoints?: FeEndpoint[];
   username: string;
   password: string;
   enable_ssl?: boolean;
+  tls_ca_cert?: string;
+  tls_client_identity?: string; // base64 PKCS#12 (.p12/.pfx)
+  tls_client_identity_password?: string;
+  tls_verify_hostname?: boolean;
   connection_timeout?: number;
+  pool_settings?: PoolSettings;
+  sql_policy?: SqlPolicy;
   tags?: string[];
   catalog?: string;
 }
@@ -45,6 +78,40 @@ export interface HealthCheck {
   message: string;
 }
 
+export interface TopologyFrontend {
+  name: string;
+  host: string;
+  http_port: number;
+  query_port: number;
+  edit_log_port: number;
+  role: string;
+  alive: boolean;
+}
+
+export interface TopologyNode {
+  id: string;
+  host: string;
+  heartbeat_port: number;
+  alive: boolean;
+  decommissioned: boolean;
+  warehouse?: string;
+}
+
+export interface ClusterTopology {
+  run_mode: 'shared_nothing' | 'shared_data' | 'unknown';
+  version?: string;
+  frontends: TopologyFrontend[];
+  backends: TopologyNode[];
+  compute_nodes: TopologyNode[];
+  warehouses: { name: string; state: string; node_count?: number }[];
+  discovered_at: string;
+}
+
+export interface TopologyDiscovery {
+  checks: HealthCheck[];
+  topology: ClusterTopology;
+}
+
 @Injectable({
   providedIn: 'root',
 })
@@ -87,11 +154,20 @@ export class ClusterService {
     username: string;
     password: string;
     enable_ssl?: boolean;
+    tls_ca_cert?: string;
+    tls_client_identity?: string;
+    tls_client_identity_password?: string;
+    tls_verify_hostname?: boolean;
     catalog?: string;
   }): Observable<ClusterHealth> {
     return this.api.post<ClusterHealth>('/clusters/health/test', data);
   }
 
+  // Discover nodes and run mode of a cluster from one FE
+  discoverCluster(data: Parameters<ClusterService['testConnection']>[0]): Observable<TopologyDiscovery> {
+    return this.api.post<TopologyDiscovery>('/clusters/discover', data);
+  }
+
   // Get health for existing cluster
   getHealth(id: number): Observable<ClusterHealth> {
     return this.api.get<ClusterHealth>(`/clusters/${id}/health`);
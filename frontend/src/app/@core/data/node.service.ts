@@ -127,13 +127,121 @@ export interface QueryExecuteRequest {
   limit?: number;
   catalog?: string;
   database?: string;
+  export?: ExportFormat;
+  // Serve a result cached within this many seconds; only read-only queries are cached
+  cache_ttl_secs?: number;
 }
 
+// File formats of a full result export
+export type ExportFormat = 'csv' | 'tsv' | 'jsonl' | 'parquet' | 'xlsx';
+
+export interface ResultColumn {
+  name: string;
+  type: string; // MySQL type, e.g. BIGINT, DECIMAL, DATETIME, VARCHAR, BOOLEAN
+  nullable: boolean;
+}
+
+// Values are JSON-typed: numbers, booleans, null, ISO date/times, base64 for binary.
+// DECIMAL and integers beyond 2^53 arrive as strings.
+export type ResultValue = string | number | boolean | null | object;
+
 export interface QueryExecuteResult {
-  columns: string[];
-  rows: string[][];
+  columns: ResultColumn[];
+  rows: ResultValue[][];
   row_count: number;
   execution_time_ms: number;
+  // Set when the result came from the query cache
+  cached_at?: string;
+}
+
+// Script of `;`-separated statements, run in order on one connection
+export interface SqlScriptRequest {
+  sql: string;
+  catalog?: string;
+  database?: string;
+  limit?: number;
+  stop_on_error?: boolean;
+  transaction?: boolean;
+}
+
+export interface SqlStatementResult {
+  index: number;
+  sql: string;
+  offset: number;
+  status: 'succeeded' | 'failed' | 'skipped';
+  columns: ResultColumn[];
+  rows: ResultValue[][];
+  row_count: number;
+  truncated: boolean;
+  affected_rows: number;
+  error?: string;
+  execution_time_ms: number;
+}
+
+export interface SqlScriptResponse {
+  statements: SqlStatementResult[];
+  succeeded: number;
+  failed: number;
+  skipped: number;
+  committed?: boolean;
+  error?: string;
+  execution_time_ms: number;
+}
+
+export interface ExplainRequest {
+  sql: string;
+  catalog?: string;
+  database?: string;
+  analyze?: boolean;
+}
+
+export interface ScanRatio {
+  selected: number;
+  total: number;
+}
+
+export interface PlanNode {
+  id?: number;
+  operator: string;
+  table?: string;
+  estimated_rows?: number;
+  cost?: number;
+  actual_rows?: number;
+  total_time?: string;
+  exchange_type?: string;
+  join_type?: string;
+  join_strategy?: string;
+  partitions?: ScanRatio;
+  tablets?: ScanRatio;
+  predicates?: string;
+  details: string[];
+  children: PlanNode[];
+}
+
+export interface PlanFragment {
+  id: number;
+  sink?: string;
+  input_partition?: string;
+  output_partition?: string;
+  output_exchange_id?: number;
+  details: string[];
+  root?: PlanNode;
+}
+
+export interface PlanWarning {
+  kind: 'broadcast_large_table' | 'no_partition_pruning' | 'full_scan';
+  fragment_id: number;
+  node_id?: number;
+  table?: string;
+  message: string;
+}
+
+export interface QueryPlan {
+  mode: 'costs' | 'analyze';
+  summary: Record<string, string>;
+  fragments: PlanFragment[];
+  warnings: PlanWarning[];
+  raw: string;
 }
 
 export interface ProfileListItem {
@@ -230,11 +338,33 @@ export class NodeService {
   }
 
   // Execute SQL API
-  executeSQL(sql: string, limit?: number, catalog?: string, database?: string): Observable<QueryExecuteResult> {
-    const request: QueryExecuteRequest = { sql, limit, catalog, database };
+  executeSQL(
+    sql: string,
+    limit?: number,
+    catalog?: string,
+    database?: string,
+    cacheTtlSecs?: number,
+  ): Observable<QueryExecuteResult> {
+    const request: QueryExecuteRequest = { sql, limit, catalog, database, cache_ttl_secs: cacheTtlSecs };
     return this.api.post<QueryExecuteResult>(`/clusters/queries/execute`, request);
   }
 
+  // Run a multi-statement script; failures are reported per statement
+  executeScript(request: SqlScriptRequest): Observable<SqlScriptResponse> {
+    return this.api.post<SqlScriptResponse>(`/clusters/queries/script`, request);
+  }
+
+  // Plan of a SELECT as fragment trees; analyze runs the query
+  explainSQL(request: ExplainRequest): Observable<QueryPlan> {
+    return this.api.post<QueryPlan>(`/clusters/queries/explain`, request);
+  }
+
+  // Download the whole result, without the row limit of executeSQL
+  exportSQL(sql: string, format: ExportFormat, catalog?: string, database?: string): Observable<Blob> {
+    const request: QueryExecuteRequest = { sql, catalog, database, export: format };
+    return this.api.postBlob(`/clusters/queries/execute`, request);
+  }
+
   // Profile APIs
   listProfiles(): Observable<ProfileListItem[]> {
     return this.api.get<ProfileListItem[]>(`/clusters/profiles`);
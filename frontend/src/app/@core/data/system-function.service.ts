@@ -20,9 +20,10 @@ export class SystemFunctionService {
     return this.api.post<SystemFunction>(`/clusters/system-functions`, req);
   }
 
-  // Execute custom function SQL
-  executeFunction(functionId: number): Observable<any> {
-    return this.api.post<any>(`/clusters/system-functions/${functionId}/execute`, {});
+  // Execute custom function SQL, reusing a result cached within cacheTtlSecs
+  executeFunction(functionId: number, cacheTtlSecs?: number): Observable<any> {
+    const query = cacheTtlSecs ? `?cache_ttl_secs=${cacheTtlSecs}` : '';
+    return this.api.post<any>(`/clusters/system-functions/${functionId}/execute${query}`, {});
   }
 
   // Update sorting and category order
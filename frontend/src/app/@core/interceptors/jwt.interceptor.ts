@@ -7,8 +7,9 @@ import {
   HttpErrorResponse,
 } from '@angular/common/http';
 import { Observable, throwError } from 'rxjs';
-import { catchError } from 'rxjs/operators';
+import { catchError, switchMap } from 'rxjs/operators';
 import { AuthService } from '../data/auth.service';
+import { SELECTED_CLUSTER_KEY } from '../data/cluster-context.service';
 import { Router } from '@angular/router';
 
 @Injectable()
@@ -19,20 +20,48 @@ export class JwtInterceptor implements HttpInterceptor {
   ) {}
 
   intercept(request: HttpRequest<unknown>, next: HttpHandler): Observable<HttpEvent<unknown>> {
-    // Add authorization header with JWT token if available
-    const token = this.authService.token;
-    if (token) {
-      request = request.clone({
-        setHeaders: {
-          Authorization: `Bearer ${token}`,
-        },
-      });
-    }
-
-    return next.handle(request).pipe(
+    return next.handle(this.withToken(request)).pipe(
       catchError((error: HttpErrorResponse) => {
-        if (error.status === 401) {
-          // Auto logout if 401 response returned from API
+        // Forced password change (e.g. the default admin account): only the profile page works
+        if (error.status === 403 && error.error?.code === 1006) {
+          this.router.navigate(['/pages/user-settings']);
+          return throwError(error);
+        }
+
+        // Destructive SQL: run it once the user confirms the statement
+        const violation = error.error?.details;
+        if (error.status === 428 && violation?.confirmation_token) {
+          const confirmed = confirm(
+            `${violation.kind} 是破坏性操作，确定要执行吗？\n\n${violation.statement}`,
+          );
+          if (!confirmed) {
+            return throwError(error);
+          }
+          return next.handle(
+            this.withToken(
+              request.clone({ setHeaders: { 'X-SQL-Confirmation': violation.confirmation_token } }),
+            ),
+          );
+        }
+
+        if (error.status !== 401) {
+          return throwError(error);
+        }
+
+        // Access tokens are short-lived: try one refresh, then replay the request
+        if (!request.url.includes('/auth/') && this.authService.isAuthenticated()) {
+          return this.authService.refreshToken().pipe(
+            switchMap(() => next.handle(this.withToken(request))),
+            catchError((refreshError) => {
+              this.authService.logout();
+              this.router.navigate(['/auth/login']);
+              return throwError(refreshError);
+            }),
+          );
+        }
+
+        // Auto logout if 401 response returned from API
+        if (!request.url.includes('/auth/login')) {
           this.authService.logout();
           this.router.navigate(['/auth/login']);
         }
@@ -40,5 +69,22 @@ export class JwtInterceptor implements HttpInterceptor {
       }),
     );
   }
+
+  // Add authorization header with JWT token and the selected cluster if available
+  private withToken(request: HttpRequest<unknown>): HttpRequest<unknown> {
+    const token = this.authService.token;
+    if (!token) {
+      return request;
+    }
+    const headers: { [name: string]: string } = {
+      Authorization: `Bearer ${token}`,
+    };
+    // Read storage directly: injecting ClusterContextService here would be a DI cycle
+    const clusterId = localStorage.getItem(SELECTED_CLUSTER_KEY);
+    if (clusterId && request.url.includes('/clusters/') && !request.headers.has('X-Cluster-Id')) {
+      headers['X-Cluster-Id'] = clusterId;
+    }
+    return request.clone({ setHeaders: headers });
+  }
 }
 
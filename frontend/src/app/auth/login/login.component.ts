@@ -19,6 +19,7 @@ export class LoginComponent implements OnInit {
   messages: string[] = [];
   showMessages = false;
   returnUrl: string;
+  ssoEnabled = false;
 
   constructor(
     protected router: Router,
@@ -38,10 +39,66 @@ export class LoginComponent implements OnInit {
       this.rememberMe = true;
     }
     
+    // Returning from OIDC sign-in: tokens or an error are passed in the URL fragment
+    if (this.handleSsoCallback()) {
+      return;
+    }
+
     // If already logged in, redirect to return URL
     if (this.authService.isAuthenticated()) {
       this.router.navigate([this.returnUrl]);
     }
+
+    this.authService.getProviders().subscribe({
+      next: (providers) => (this.ssoEnabled = providers.oidc),
+      error: () => (this.ssoEnabled = false),
+    });
+  }
+
+  loginWithSso(): void {
+    window.location.href = this.authService.oidcLoginUrl;
+  }
+
+  private handleSsoCallback(): boolean {
+    const fragment = this.route.snapshot.fragment;
+    if (!fragment) {
+      return false;
+    }
+
+    const params = new URLSearchParams(fragment);
+    // Drop the tokens from the address bar and browser history
+    history.replaceState(null, '', window.location.pathname + window.location.search);
+
+    const ssoError = params.get('sso_error');
+    if (ssoError) {
+      this.errors = [ssoError];
+      this.showMessages = true;
+      this.toastrService.danger(ssoError, 'Login Failed');
+      return false;
+    }
+
+    const token = params.get('token');
+    const refreshToken = params.get('refresh_token');
+    if (!token || !refreshToken) {
+      return false;
+    }
+
+    this.submitted = true;
+    this.authService
+      .completeSsoLogin({ token, refresh_token: refreshToken, expires_in: Number(params.get('expires_in')) })
+      .subscribe({
+        next: () => {
+          this.submitted = false;
+          this.toastrService.success('Welcome back!', 'Login Successful');
+          this.router.navigate([this.returnUrl]);
+        },
+        error: (error) => {
+          this.submitted = false;
+          this.errors = [error.error?.message || 'Single sign-on failed.'];
+          this.showMessages = true;
+        },
+      });
+    return true;
   }
 
   login(): void {
@@ -68,6 +125,12 @@ export class LoginComponent implements OnInit {
           localStorage.removeItem('remembered_username');
         }
         
+        if (response.user?.must_change_password) {
+          this.toastrService.warning('Please set a new password before continuing', 'Password Change Required');
+          this.router.navigate(['/pages/user-settings']);
+          return;
+        }
+
         this.toastrService.success('Welcome back!', 'Login Successful');
         // Navigate to return URL or dashboard after short delay
         setTimeout(() => {
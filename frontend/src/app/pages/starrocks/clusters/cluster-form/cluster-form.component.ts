@@ -17,6 +17,8 @@ export class ClusterFormComponent implements OnInit {
   clusterId: number | null = null;
   connectionTested = false; // Track if connection has been tested
   connectionValid = false;  // Track if connection is valid
+  clientIdentityConfigured = false; // Existing cluster already has a client certificate
+  clientIdentityFileName = '';
 
   constructor(
     private fb: FormBuilder,
@@ -31,10 +33,21 @@ export class ClusterFormComponent implements OnInit {
       fe_host: ['', [Validators.required]],
       fe_http_port: [8030, [Validators.required, Validators.min(1), Validators.max(65535)]],
       fe_query_port: [9030, [Validators.required, Validators.min(1), Validators.max(65535)]],
+      fe_endpoints: ['', [Validators.pattern(/^\s*([^\s,:]+:\d+:\d+\s*(,\s*|$))*$/)]],
       username: ['root', [Validators.required]],
       password: ['', [Validators.required]],
       enable_ssl: [false],
+      tls_ca_cert: [''],
+      tls_client_identity: [''],
+      tls_client_identity_password: [''],
+      tls_verify_hostname: [true],
       connection_timeout: [10, [Validators.min(1), Validators.max(300)]],
+      pool_min_connections: [null, [Validators.min(0)]],
+      pool_max_connections: [null, [Validators.min(1)]],
+      pool_idle_timeout_secs: [null, [Validators.min(1)]],
+      pool_max_lifetime_secs: [null, [Validators.min(1)]],
+      sql_read_only: [false],
+      sql_confirm_writes: [false],
       catalog: ['default_catalog'],
       tags: [''],
     });
@@ -61,12 +74,24 @@ export class ClusterFormComponent implements OnInit {
           fe_host: cluster.fe_host,
           fe_http_port: cluster.fe_http_port,
           fe_query_port: cluster.fe_query_port,
+          fe_endpoints: (cluster.fe_endpoints || [])
+            .map((fe) => `${fe.host}:${fe.http_port}:${fe.query_port}`)
+            .join(', '),
           username: cluster.username,
           enable_ssl: cluster.enable_ssl,
+          tls_ca_cert: cluster.tls_ca_cert || '',
+          tls_verify_hostname: cluster.tls_verify_hostname,
           connection_timeout: cluster.connection_timeout,
+          pool_min_connections: cluster.pool_settings?.min_connections ?? null,
+          pool_max_connections: cluster.pool_settings?.max_connections ?? null,
+          pool_idle_timeout_secs: cluster.pool_settings?.idle_timeout_secs ?? null,
+          pool_max_lifetime_secs: cluster.pool_settings?.max_lifetime_secs ?? null,
+          sql_read_only: !!cluster.sql_policy?.read_only,
+          sql_confirm_writes: !!cluster.sql_policy?.confirm_writes,
           catalog: cluster.catalog,
           tags: cluster.tags.join(', '),
         });
+        this.clientIdentityConfigured = cluster.tls_client_identity_configured;
         // Password is not loaded for security
         this.clusterForm.get('password')?.clearValidators();
         this.clusterForm.get('password')?.updateValueAndValidity();
@@ -98,9 +123,40 @@ export class ClusterFormComponent implements OnInit {
       ? formValue.tags.split(',').map((t: string) => t.trim()).filter((t: string) => t)
       : [];
 
+    // Parse backup FEs: host:http_port:query_port, comma separated
+    const fe_endpoints = (formValue.fe_endpoints || '')
+      .split(',')
+      .map((e: string) => e.trim())
+      .filter((e: string) => e)
+      .map((e: string) => {
+        const [host, httpPort, queryPort] = e.split(':');
+        return { host, http_port: Number(httpPort), query_port: Number(queryPort) };
+      });
+
+    // Empty pool fields fall back to the server defaults
+    const {
+      pool_min_connections,
+      pool_max_connections,
+      pool_idle_timeout_secs,
+      pool_max_lifetime_secs,
+      sql_read_only,
+      sql_confirm_writes,
+      ...connection
+    } = formValue;
+    const optionalNumber = (value: any) => (value === null || value === '' ? undefined : Number(value));
+    const pool_settings = {
+      min_connections: optionalNumber(pool_min_connections),
+      max_connections: optionalNumber(pool_max_connections),
+      idle_timeout_secs: optionalNumber(pool_idle_timeout_secs),
+      max_lifetime_secs: optionalNumber(pool_max_lifetime_secs),
+    };
+
     const clusterData = {
-      ...formValue,
+      ...connection,
       tags,
+      fe_endpoints,
+      pool_settings,
+      sql_policy: { read_only: sql_read_only, confirm_writes: sql_confirm_writes },
     };
 
     // Remove password if in edit mode and password is empty
@@ -108,6 +164,15 @@ export class ClusterFormComponent implements OnInit {
       delete clusterData.password;
     }
 
+    // Keep the stored client certificate unless it was replaced or removed
+    // (an empty string removes it on update)
+    if (!formValue.tls_client_identity) {
+      delete clusterData.tls_client_identity_password;
+      if (!this.isEditMode || this.clientIdentityConfigured) {
+        delete clusterData.tls_client_identity;
+      }
+    }
+
     const request$ = this.isEditMode && this.clusterId
       ? this.clusterService.updateCluster(this.clusterId, clusterData)
       : this.clusterService.createCluster(clusterData);
@@ -151,6 +216,26 @@ export class ClusterFormComponent implements OnInit {
     });
   }
 
+  // Read a .p12/.pfx client certificate as base64
+  onClientIdentitySelected(event: Event): void {
+    const file = (event.target as HTMLInputElement).files?.[0];
+    if (!file) return;
+
+    const reader = new FileReader();
+    reader.onload = () => {
+      const dataUrl = reader.result as string;
+      this.clusterForm.patchValue({ tls_client_identity: dataUrl.substring(dataUrl.indexOf(',') + 1) });
+      this.clientIdentityFileName = file.name;
+    };
+    reader.readAsDataURL(file);
+  }
+
+  clearClientIdentity(): void {
+    this.clusterForm.patchValue({ tls_client_identity: '', tls_client_identity_password: '' });
+    this.clientIdentityFileName = '';
+    this.clientIdentityConfigured = false;
+  }
+
   onCancel(): void {
     this.router.navigate(['/pages/starrocks/dashboard']);
   }
@@ -172,17 +257,7 @@ export class ClusterFormComponent implements OnInit {
     
     if (!this.isEditMode) {
       // New cluster mode: test connection with connection details
-      const testData = {
-        fe_host: formValue.fe_host,
-        fe_http_port: formValue.fe_http_port,
-        fe_query_port: formValue.fe_query_port,
-        username: formValue.username,
-        password: formValue.password,
-        enable_ssl: formValue.enable_ssl || false,
-        catalog: formValue.catalog || 'default_catalog',
-      };
-
-      this.clusterService.testConnection(testData).subscribe({
+      this.clusterService.testConnection(this.connectionDetails(formValue)).subscribe({
         next: (health) => this.handleHealthCheckResult(health),
         error: (error) => this.handleHealthCheckError(error),
       });
@@ -195,6 +270,60 @@ export class ClusterFormComponent implements OnInit {
     }
   }
 
+  // Probe the cluster from the primary FE and fill in the other FEs as backups
+  discoverCluster(): void {
+    const requiredFields = ['fe_host', 'fe_http_port', 'fe_query_port', 'username', 'password'];
+    if (requiredFields.some(field => !this.clusterForm.get(field)?.value)) {
+      this.toastrService.warning('请先填写完整的连接信息（FE地址、端口、用户名、密码）', '提示');
+      return;
+    }
+
+    this.loading = true;
+    const formValue = this.clusterForm.value;
+    this.clusterService.discoverCluster(this.connectionDetails(formValue)).subscribe({
+      next: (discovery) => {
+        const failed = discovery.checks.filter((c) => c.status !== 'ok');
+        if (failed.length > 0) {
+          this.handleHealthCheckResult({ status: 'critical', checks: failed });
+          return;
+        }
+
+        const topology = discovery.topology;
+        const others = topology.frontends.filter(
+          (fe) => !(fe.host === formValue.fe_host && fe.query_port === Number(formValue.fe_query_port)),
+        );
+        if (others.length > 0) {
+          this.clusterForm.patchValue({
+            fe_endpoints: others.map((fe) => `${fe.host}:${fe.http_port}:${fe.query_port}`).join(', '),
+          });
+        }
+        const mode = topology.run_mode === 'shared_data' ? '存算分离' : topology.run_mode === 'shared_nothing' ? '存算一体' : '未知';
+        this.toastrService.success(
+          `模式: ${mode}，FE: ${topology.frontends.length}，BE: ${topology.backends.length}，CN: ${topology.compute_nodes.length}`,
+          '发现集群',
+        );
+        this.loading = false;
+      },
+      error: (error) => this.handleHealthCheckError(error),
+    });
+  }
+
+  private connectionDetails(formValue: any) {
+    return {
+      fe_host: formValue.fe_host,
+      fe_http_port: formValue.fe_http_port,
+      fe_query_port: formValue.fe_query_port,
+      username: formValue.username,
+      password: formValue.password,
+      enable_ssl: formValue.enable_ssl || false,
+      tls_ca_cert: formValue.tls_ca_cert || undefined,
+      tls_client_identity: formValue.tls_client_identity || undefined,
+      tls_client_identity_password: formValue.tls_client_identity_password || undefined,
+      tls_verify_hostname: formValue.tls_verify_hostname,
+      catalog: formValue.catalog || 'default_catalog',
+    };
+  }
+
   private handleHealthCheckResult(health: any): void {
     if (health.status === 'healthy') {
       const details = health.checks.map((c: any) => c.name + ': ' + c.message).join('\n');
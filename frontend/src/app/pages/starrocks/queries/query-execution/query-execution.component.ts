@@ -4,7 +4,14 @@ import { NbToastrService, NbDialogService, NbThemeService } from '@nebular/theme
 import { LocalDataSource } from 'ng2-smart-table';
 import { Subject } from 'rxjs';
 import { takeUntil } from 'rxjs/operators';
-import { NodeService, QueryExecuteResult } from '../../../../@core/data/node.service';
+import {
+  ExportFormat,
+  NodeService,
+  QueryExecuteResult,
+  ResultValue,
+  SqlScriptResponse,
+  SqlStatementResult,
+} from '../../../../@core/data/node.service';
 import { ClusterContextService } from '../../../../@core/data/cluster-context.service';
 import { Cluster } from '../../../../@core/data/cluster.service';
 import { ErrorHandler } from '../../../../@core/utils/error-handler';
@@ -14,6 +21,8 @@ import { oneDark } from '@codemirror/theme-one-dark';
 import { autocompletion } from '@codemirror/autocomplete';
 import { format } from 'sql-formatter';
 
+const NUMERIC_TYPES = ['TINYINT', 'SMALLINT', 'MEDIUMINT', 'INT', 'BIGINT', 'YEAR', 'FLOAT', 'DOUBLE', 'DECIMAL'];
+
 @Component({
   selector: 'ngx-query-execution',
   templateUrl: './query-execution.component.html',
@@ -66,6 +75,18 @@ export class QueryExecutionComponent implements OnInit, OnDestroy, AfterViewInit
   executing: boolean = false;
   executionTime: number = 0;
   rowCount: number = 0;
+
+  // Script mode: run every statement and list their outcomes
+  scriptMode: boolean = false;
+  stopOnError: boolean = true;
+  useTransaction: boolean = false;
+  scriptResult: SqlScriptResponse | null = null;
+  selectedStatement: SqlStatementResult | null = null;
+
+  // Full result export, run on the server
+  exportFormats: ExportFormat[] = ['csv', 'tsv', 'jsonl', 'parquet', 'xlsx'];
+  exportFormat: ExportFormat = 'csv';
+  exportingFull: boolean = false;
   queryLimit: number = 1000; // Default limit for query results
   limitOptions = [
     { value: 100, label: '100 行' },
@@ -418,6 +439,13 @@ export class QueryExecutionComponent implements OnInit, OnDestroy, AfterViewInit
     this.executing = true;
     this.queryResult = null;
     this.resultSettings = null;
+    this.scriptResult = null;
+    this.selectedStatement = null;
+
+    if (this.scriptMode) {
+      this.executeScript();
+      return;
+    }
 
     this.nodeService.executeSQL(
       this.sqlInput.trim(), 
@@ -426,23 +454,7 @@ export class QueryExecutionComponent implements OnInit, OnDestroy, AfterViewInit
       this.selectedDatabase || undefined
     ).subscribe({
       next: (result) => {
-        this.queryResult = result;
-        this.executionTime = result.execution_time_ms;
-        this.rowCount = result.row_count;
-
-        // Build dynamic table settings
-        this.buildResultSettings(result);
-
-        // Convert rows to objects for ng2-smart-table
-        const dataRows = result.rows.map(row => {
-          const obj: any = {};
-          result.columns.forEach((col, idx) => {
-            obj[col] = row[idx];
-          });
-          return obj;
-        });
-
-        this.realtimeResultSource.load(dataRows);
+        this.showResult(result);
         this.executing = false;
         this.toastrService.success(`查询成功，返回 ${result.row_count} 行`, '成功');
       },
@@ -453,10 +465,87 @@ export class QueryExecutionComponent implements OnInit, OnDestroy, AfterViewInit
     });
   }
 
+  // Run the editor content as a script, statement by statement
+  executeScript(): void {
+    this.nodeService.executeScript({
+      sql: this.sqlInput,
+      limit: this.queryLimit,
+      catalog: this.selectedCatalog || undefined,
+      database: this.selectedDatabase || undefined,
+      stop_on_error: this.stopOnError,
+      transaction: this.useTransaction,
+    }).subscribe({
+      next: (result) => {
+        this.scriptResult = result;
+        this.executing = false;
+        // Show the rows of the last statement that returned a result set
+        const withRows = result.statements.filter(s => s.status === 'succeeded' && s.columns.length > 0);
+        if (withRows.length > 0) {
+          this.selectStatement(withRows[withRows.length - 1]);
+        }
+        if (result.failed > 0 || result.error) {
+          const rolledBack = result.committed === false ? '，事务已回滚' : '';
+          this.toastrService.warning(
+            `${result.succeeded} 条成功，${result.failed} 条失败，${result.skipped} 条跳过${rolledBack}`,
+            '脚本执行完成',
+          );
+        } else {
+          this.toastrService.success(`${result.succeeded} 条语句执行成功`, '成功');
+        }
+      },
+      error: (error) => {
+        this.executing = false;
+        this.toastrService.danger(ErrorHandler.extractErrorMessage(error), '执行失败');
+      },
+    });
+  }
+
+  selectStatement(statement: SqlStatementResult): void {
+    if (statement.columns.length === 0) {
+      return;
+    }
+    this.selectedStatement = statement;
+    this.showResult({
+      columns: statement.columns,
+      rows: statement.rows,
+      row_count: statement.row_count,
+      execution_time_ms: statement.execution_time_ms,
+    });
+  }
+
+  showResult(result: QueryExecuteResult): void {
+    this.queryResult = result;
+    this.executionTime = result.execution_time_ms;
+    this.rowCount = result.row_count;
+
+    // Build dynamic table settings
+    this.buildResultSettings(result);
+
+    // Convert rows to objects for ng2-smart-table
+    const dataRows = result.rows.map(row => {
+      const obj: any = {};
+      result.columns.forEach((col, idx) => {
+        obj[col.name] = row[idx];
+      });
+      return obj;
+    });
+
+    this.realtimeResultSource.load(dataRows);
+  }
+
   buildResultSettings(result: QueryExecuteResult): void {
     const columns: any = {};
     result.columns.forEach(col => {
-      columns[col] = { title: col, type: 'string' };
+      const numeric = NUMERIC_TYPES.includes(col.type);
+      columns[col.name] = {
+        title: col.name,
+        type: 'string',
+        valuePrepareFunction: (value: ResultValue) => this.formatValue(value),
+        // Sort numbers by value; DECIMAL and large integers come as strings
+        compareFunction: numeric
+          ? (direction: number, a: ResultValue, b: ResultValue) => direction * (this.toNumber(a) - this.toNumber(b))
+          : undefined,
+      };
     });
 
     this.resultSettings = {
@@ -486,6 +575,8 @@ export class QueryExecutionComponent implements OnInit, OnDestroy, AfterViewInit
     }
     this.queryResult = null;
     this.resultSettings = null;
+    this.scriptResult = null;
+    this.selectedStatement = null;
     this.executionTime = 0;
     this.rowCount = 0;
   }
@@ -521,6 +612,41 @@ export class QueryExecutionComponent implements OnInit, OnDestroy, AfterViewInit
     }
   }
 
+  // Export the whole result, beyond the row limit, in the chosen format
+  exportFullResult(): void {
+    if (!this.sqlInput || this.sqlInput.trim() === '') {
+      this.toastrService.warning('请输入SQL语句', '提示');
+      return;
+    }
+
+    this.exportingFull = true;
+    const exportFormat = this.exportFormat;
+    this.nodeService.exportSQL(
+      this.sqlInput.trim(),
+      exportFormat,
+      this.selectedCatalog || undefined,
+      this.selectedDatabase || undefined
+    ).subscribe({
+      next: (blob) => {
+        const link = document.createElement('a');
+        const url = URL.createObjectURL(blob);
+        link.setAttribute('href', url);
+        link.setAttribute('download', `query_result_${new Date().getTime()}.${exportFormat}`);
+        link.style.visibility = 'hidden';
+        document.body.appendChild(link);
+        link.click();
+        document.body.removeChild(link);
+        URL.revokeObjectURL(url);
+        this.exportingFull = false;
+        this.toastrService.success('导出成功', '成功');
+      },
+      error: (error) => {
+        this.exportingFull = false;
+        this.toastrService.danger(ErrorHandler.extractErrorMessage(error), '导出失败');
+      },
+    });
+  }
+
   // Export results to CSV
   exportResults(): void {
     if (!this.queryResult || !this.queryResult.rows || this.queryResult.rows.length === 0) {
@@ -534,11 +660,11 @@ export class QueryExecutionComponent implements OnInit, OnDestroy, AfterViewInit
       const rows = this.queryResult.rows;
 
       // CSV header
-      let csvContent = columns.map(col => this.escapeCSV(col)).join(',') + '\n';
+      let csvContent = columns.map(col => this.escapeCSV(col.name)).join(',') + '\n';
 
-      // CSV rows
+      // CSV rows (NULL as an empty field)
       rows.forEach(row => {
-        csvContent += row.map(cell => this.escapeCSV(cell)).join(',') + '\n';
+        csvContent += row.map(cell => this.escapeCSV(cell === null ? '' : this.formatValue(cell))).join(',') + '\n';
       });
 
       // Create blob and download
@@ -561,6 +687,19 @@ export class QueryExecutionComponent implements OnInit, OnDestroy, AfterViewInit
     }
   }
 
+  // Display text of a typed result value
+  private formatValue(value: ResultValue): string {
+    if (value === null || value === undefined) {
+      return 'NULL';
+    }
+    return typeof value === 'object' ? JSON.stringify(value) : String(value);
+  }
+
+  private toNumber(value: ResultValue): number {
+    const n = value === null ? NaN : Number(value);
+    return isNaN(n) ? Number.NEGATIVE_INFINITY : n;
+  }
+
   // Escape CSV special characters
   private escapeCSV(value: string): string {
     if (value === null || value === undefined) {
@@ -107,6 +107,9 @@ export class SystemManagementComponent implements OnInit, OnDestroy {
   
   private destroy$ = new Subject<void>();
 
+  // 自定义功能结果在服务端缓存的秒数，重复打开时不再重新执行 SQL
+  private readonly customFunctionCacheTtlSecs = 60;
+
   constructor(
     private nodeService: NodeService,
     private clusterContext: ClusterContextService,
@@ -490,12 +493,12 @@ export class SystemManagementComponent implements OnInit, OnDestroy {
       this.loadFunctionData(func.functionName);
     } else {
       // 自定义功能使用MySQL查询
-      this.systemFunctionService.executeFunction(func.id)
+      this.systemFunctionService.executeFunction(func.id, this.customFunctionCacheTtlSecs)
         .pipe(takeUntil(this.destroy$))
         .subscribe({
           next: (result) => {
-            // 显示结果
-            this.functionData = result;
+            // 显示结果（每行一个以列名为键的对象）
+            this.functionData = result.data || [];
             this.functionDataSource.load(this.functionData);
             this.setupTableSettings();
             this.selectedFunction = {
@@ -26,6 +26,7 @@ export class UserSettingsComponent implements OnInit {
 
   errors: string[] = [];
   showPasswordFields = false;
+  passwordChangeRequired = false;
   showAvatarSelection = false;
   selectedAvatarStyle = 'avataaars';
 
@@ -53,6 +54,10 @@ export class UserSettingsComponent implements OnInit {
         this.userForm.username = user.username;
         this.userForm.email = user.email || '';
         this.userForm.avatar = user.avatar || this.availableAvatars[0];
+        this.passwordChangeRequired = !!user.must_change_password;
+        if (this.passwordChangeRequired) {
+          this.showPasswordFields = true;
+        }
         this.loading = false;
       },
       error: (error) => {
@@ -123,8 +128,8 @@ export class UserSettingsComponent implements OnInit {
         return;
       }
 
-      if (this.userForm.newPassword.length < 6) {
-        this.errors.push('新密码至少需要6个字符');
+      if (this.userForm.newPassword.length < 8) {
+        this.errors.push('新密码至少需要8个字符');
         this.submitted = false;
         return;
       }